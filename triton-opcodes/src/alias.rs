@@ -0,0 +1,175 @@
+//! User-defined mnemonics: aliases for instructions, pseudo-instructions, and keywords of
+//! Triton assembly. Aliases allow teams to use their own dialect of Triton assembly. They are
+//! resolved before parsing, and never appear in formatted or disassembled code.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+use anyhow::Result;
+
+use crate::fmt::format;
+use crate::instruction::parse;
+use crate::instruction::parse_token;
+use crate::instruction::LabelledInstruction;
+use crate::lexer::tokenize;
+use crate::lexer::TokenKind;
+
+/// The keywords of structured control flow, which are not instructions.
+const KEYWORDS: [&str; 5] = ["repeat", "while", "if", "else", "end"];
+
+/// Instructions and keywords that are followed by an argument.
+const TAKES_ARGUMENT: [&str; 4] = ["push", "repeat", "call", "tail_call"];
+
+/// A mapping from user-defined mnemonics to the canonical mnemonics of Triton assembly.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AliasTable {
+    aliases: HashMap<String, String>,
+}
+
+impl AliasTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `alias` as another name for `target`, which must be an instruction, a
+    /// pseudo-instruction, a keyword like `while`, or a previously registered alias. An alias
+    /// cannot shadow a canonical mnemonic, and cannot be redefined.
+    pub fn with_alias(mut self, alias: &str, target: &str) -> Result<Self> {
+        if alias.is_empty()
+            || alias.contains(char::is_whitespace)
+            || alias.contains("//")
+            || alias.ends_with(':')
+        {
+            bail!("`{alias}` is not a valid mnemonic.");
+        }
+        if is_canonical_mnemonic(alias) {
+            bail!("Alias `{alias}` shadows a mnemonic of Triton assembly.");
+        }
+        if let Some(existing_target) = self.aliases.get(alias) {
+            bail!("`{alias}` is already an alias for `{existing_target}`.");
+        }
+
+        let target = self.resolve(target).to_string();
+        if !is_canonical_mnemonic(&target) {
+            bail!("Target `{target}` of alias `{alias}` is not a mnemonic of Triton assembly.");
+        }
+        self.aliases.insert(alias.to_string(), target);
+        Ok(self)
+    }
+
+    /// The canonical mnemonic the given mnemonic stands for. Mnemonics that are not aliases are
+    /// returned as they are.
+    pub fn resolve<'a>(&'a self, mnemonic: &'a str) -> &'a str {
+        self.aliases.get(mnemonic).map_or(mnemonic, String::as_str)
+    }
+
+    /// Replace every alias in the source code by its canonical mnemonic. Labels, arguments, and
+    /// comments are left as they are, as is all whitespace.
+    pub fn canonicalize(&self, source: &str) -> String {
+        let mut canonical = String::with_capacity(source.len());
+        let mut position = 0;
+        let mut expects_argument = false;
+
+        for token in tokenize(source) {
+            let replacement = match token.kind {
+                TokenKind::Comment | TokenKind::LabelDefinition => None,
+                _ if expects_argument => {
+                    expects_argument = false;
+                    None
+                }
+                _ => {
+                    let mnemonic = self.resolve(token.text);
+                    expects_argument = TAKES_ARGUMENT.contains(&mnemonic);
+                    Some(mnemonic)
+                }
+            };
+            canonical.push_str(&source[position..token.span.start]);
+            canonical.push_str(replacement.unwrap_or(token.text));
+            position = token.span.end;
+        }
+        canonical.push_str(&source[position..]);
+        canonical
+    }
+
+    /// Like [`parse`], but additionally accepts the registered aliases.
+    pub fn parse(&self, code_with_comments: &str) -> Result<Vec<LabelledInstruction>> {
+        parse(&self.canonicalize(code_with_comments))
+    }
+
+    /// Like [`format`], but additionally replaces aliases by their canonical mnemonics.
+    pub fn format(&self, source: &str) -> String {
+        format(&self.canonicalize(source))
+    }
+}
+
+fn is_canonical_mnemonic(mnemonic: &str) -> bool {
+    if mnemonic.ends_with(':') {
+        return false;
+    }
+    KEYWORDS.contains(&mnemonic)
+        || TAKES_ARGUMENT.contains(&mnemonic)
+        || parse_token(mnemonic, &mut "".split_whitespace()).is_ok()
+}
+
+#[cfg(test)]
+mod alias_tests {
+    use super::*;
+
+    fn team_dialect() -> AliasTable {
+        AliasTable::new()
+            .with_alias("jmp", "call")
+            .unwrap()
+            .with_alias("ld", "read_mem")
+            .unwrap()
+            .with_alias("absorb", "sponge_absorb")
+            .unwrap()
+            .with_alias("loop", "while")
+            .unwrap()
+            .with_alias("load", "ld")
+            .unwrap()
+    }
+
+    #[test]
+    fn aliases_parse_like_their_canonical_mnemonics_test() {
+        let aliases = team_dialect();
+        let dialect =
+            "main: push 1 loop push 0 end jmp ld // ld is an alias\n ld: load absorb halt";
+        let canonical = "main: push 1 while push 0 end call ld // ld is an alias\n \
+            ld: read_mem sponge_absorb halt";
+        assert_eq!(canonical, aliases.canonicalize(dialect));
+        assert_eq!(parse(canonical).unwrap(), aliases.parse(dialect).unwrap());
+        assert!(parse(dialect).is_err());
+    }
+
+    #[test]
+    fn formatted_and_disassembled_code_uses_canonical_mnemonics_test() {
+        let aliases = team_dialect();
+        let formatted = aliases.format("foo: jmp foo ld");
+        assert_eq!("foo:\n    call foo\n    read_mem\n", formatted);
+
+        let disassembled = aliases
+            .parse("foo: jmp foo ld")
+            .unwrap()
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["foo:", "call foo", "read_mem"], disassembled);
+    }
+
+    #[test]
+    fn invalid_aliases_are_rejected_test() {
+        let aliases = team_dialect();
+        assert!(aliases.clone().with_alias("add", "mul").is_err());
+        assert!(aliases.clone().with_alias("end", "halt").is_err());
+        assert!(aliases.clone().with_alias("jmp", "tail_call").is_err());
+        assert!(aliases
+            .clone()
+            .with_alias("foo", "no_such_instruction")
+            .is_err());
+        assert!(aliases.clone().with_alias("foo", "bar:").is_err());
+        assert!(aliases.clone().with_alias("foo bar", "halt").is_err());
+        assert!(aliases.clone().with_alias("foo:", "halt").is_err());
+        assert!(aliases.clone().with_alias("", "halt").is_err());
+        assert!(aliases.with_alias("stop", "halt").is_ok());
+    }
+}
@@ -0,0 +1,3 @@
+fn main() -> anyhow::Result<()> {
+    triton_opcodes::lsp::run_stdio_server()
+}
@@ -0,0 +1,113 @@
+//! Canonical formatting of Triton assembly.
+
+use crate::lexer::tokenize;
+use crate::lexer::Token;
+use crate::lexer::TokenKind;
+
+const INDENTATION: &str = "    ";
+
+/// Format the given Triton assembly canonically: every label definition goes on its own line,
+/// flush left; every instruction goes on its own indented line together with its argument, if
+/// any. Comments are preserved. A comment on the same line as some code stays on that line,
+/// separated from the code by a single space. Runs of blank lines are collapsed into a single
+/// blank line.
+///
+/// The source code is not required to be valid – tokens that cannot be parsed are formatted as
+/// instructions.
+pub fn format(source: &str) -> String {
+    let tokens = tokenize(source);
+    let mut lines: Vec<String> = vec![];
+    let mut previous_token: Option<&Token> = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let preceding_whitespace = match previous_token {
+            Some(previous) => &source[previous.span.end..token.span.start],
+            None => "",
+        };
+        let num_newlines = preceding_whitespace.matches('\n').count();
+        if num_newlines > 1 {
+            lines.push(String::new());
+        }
+
+        match token.kind {
+            TokenKind::LabelDefinition => lines.push(token.text.to_string()),
+            TokenKind::Instruction => lines.push(format!("{INDENTATION}{}", token.text)),
+            TokenKind::Argument | TokenKind::LabelUse => match lines.last_mut() {
+                Some(line) => *line = format!("{line} {}", token.text),
+                None => lines.push(format!("{INDENTATION}{}", token.text)),
+            },
+            TokenKind::Comment => {
+                let comment = token.text.trim_end();
+                match lines.last_mut() {
+                    Some(line) if previous_token.is_some() && num_newlines == 0 => {
+                        *line = format!("{line} {comment}")
+                    }
+                    _ => {
+                        let indentation = match next_code_token(&tokens[index..]) {
+                            Some(TokenKind::LabelDefinition) => "",
+                            _ => INDENTATION,
+                        };
+                        lines.push(format!("{indentation}{comment}"));
+                    }
+                }
+            }
+        }
+        previous_token = Some(token);
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// The kind of the first token that is not a comment, if any.
+fn next_code_token(tokens: &[Token]) -> Option<TokenKind> {
+    tokens
+        .iter()
+        .map(|token| token.kind)
+        .find(|&kind| kind != TokenKind::Comment)
+}
+
+#[cfg(test)]
+mod fmt_tests {
+    use crate::instruction::parse;
+
+    use super::*;
+
+    #[test]
+    fn format_test() {
+        let source = "  // entry point\ncall   main halt\n\n\n\nmain: push 1 // one\n  \
+            push   2 add\n// the end\n    return   ";
+        let expected = "    // entry point
+    call main
+    halt
+
+main:
+    push 1 // one
+    push 2
+    add
+    // the end
+    return
+";
+        assert_eq!(expected, format(source));
+    }
+
+    #[test]
+    fn comment_before_label_is_flush_left_test() {
+        let source = "halt // stop\n// a label\nfoo: return";
+        let expected = "    halt // stop\n// a label\nfoo:\n    return\n";
+        assert_eq!(expected, format(source));
+    }
+
+    #[test]
+    fn formatting_is_idempotent_and_preserves_semantics_test() {
+        let source = "foo: push 1//one\n\n\n  call foo// loop\n  bar: .x: lt halt";
+        let formatted = format(source);
+        assert_eq!(formatted, format(&formatted));
+        assert_eq!(parse(source).unwrap(), parse(&formatted).unwrap());
+    }
+
+    #[test]
+    fn empty_source_formats_to_empty_string_test() {
+        assert_eq!("", format(""));
+        assert_eq!("", format(" \n\n "));
+    }
+}
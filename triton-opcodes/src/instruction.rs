@@ -11,6 +11,7 @@ use anyhow::Result;
 use itertools::Itertools;
 use num_traits::One;
 use regex::Regex;
+use serde::Serialize;
 use strum::EnumCount;
 use strum::IntoEnumIterator;
 use strum_macros::Display as DisplayMacro;
@@ -18,11 +19,12 @@ use strum_macros::EnumCount as EnumCountMacro;
 use strum_macros::EnumIter;
 
 use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
 
 use AnInstruction::*;
 use TokenError::*;
 
-use crate::instruction::DivinationHint::Quotient;
+use crate::instruction::DivinationHint::*;
 use crate::ord_n::Ord16;
 use crate::ord_n::Ord16::*;
 use crate::ord_n::Ord7;
@@ -51,6 +53,35 @@ impl Display for LabelledInstruction {
 #[derive(Debug, DisplayMacro, Clone, Copy, PartialEq, Eq, Hash, EnumCountMacro)]
 pub enum DivinationHint {
     Quotient,
+
+    /// The high limb of `n / d` for the u64s `n` and `d`, given the stack `_ d_hi d_lo n_hi n_lo`.
+    #[strum(serialize = "quotient_u64_hi")]
+    QuotientU64Hi,
+
+    /// The low limb of `n / d`, given the stack `_ d_hi d_lo n_hi n_lo q_hi`.
+    #[strum(serialize = "quotient_u64_lo")]
+    QuotientU64Lo,
+
+    /// The high limb of `n % d`, given the stack `_ d_hi d_lo n_hi n_lo q_hi q_lo`.
+    #[strum(serialize = "remainder_u64_hi")]
+    RemainderU64Hi,
+
+    /// The low limb of `n % d`, given the stack `_ d_hi d_lo n_hi n_lo q_hi q_lo r_hi`.
+    #[strum(serialize = "remainder_u64_lo")]
+    RemainderU64Lo,
+}
+
+/// The way an instruction accesses random-access memory.
+#[derive(Debug, DisplayMacro, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum RamAccess {
+    /// The instruction does not access RAM.
+    None,
+
+    /// The instruction reads the value at address `st1` from RAM and puts it into `st0`.
+    Read,
+
+    /// The instruction writes the value `st0` to RAM at address `st1`.
+    Write,
 }
 
 /// A Triton VM instruction
@@ -73,6 +104,7 @@ pub enum AnInstruction<Dest: PartialEq + Default> {
     Nop,
     Skiz,
     Call(Dest),
+    TailCall(Dest),
     Return,
     Recurse,
     Assert,
@@ -81,6 +113,8 @@ pub enum AnInstruction<Dest: PartialEq + Default> {
     // Memory access
     ReadMem,
     WriteMem,
+    ReadMemInc,
+    WriteMemInc,
 
     // Hashing-related instructions
     Hash,
@@ -105,6 +139,100 @@ pub enum AnInstruction<Dest: PartialEq + Default> {
     WriteIo,
 }
 
+/// Invoke the given macro once with every variant of [`AnInstruction`].
+///
+/// Every variant is passed as `name: pattern => representative`. The name is `instruction_`
+/// followed by the variant's mnemonic, usable to refer to per-instruction items like functions.
+/// The pattern matches all instances of the variant, and the representative is the variant with
+/// default arguments, if any. Code that needs to handle every instruction – the simulator, the
+/// tables, pretty-printers – can use the patterns to build exhaustive `match` expressions and the
+/// representatives to build lists, instead of maintaining such lists by hand. Adding an
+/// instruction then results in a compile error wherever it is not yet handled.
+///
+/// ```
+/// use triton_opcodes::for_all_instructions;
+/// use triton_opcodes::instruction::Instruction;
+///
+/// macro_rules! mnemonics {
+///     ($($name:ident: $pattern:pat => $representative:expr),* $(,)?) => {
+///         [$($representative),*].map(|instruction: Instruction| instruction.to_string())
+///     };
+/// }
+/// let mnemonics = for_all_instructions!(mnemonics);
+/// assert_eq!("pop", mnemonics[0]);
+/// ```
+#[macro_export]
+macro_rules! for_all_instructions {
+    ($callback:ident) => {
+        $callback! {
+            instruction_pop: $crate::instruction::AnInstruction::Pop =>
+                $crate::instruction::AnInstruction::Pop,
+            instruction_push: $crate::instruction::AnInstruction::Push(_) =>
+                $crate::instruction::AnInstruction::Push(Default::default()),
+            instruction_divine: $crate::instruction::AnInstruction::Divine(_) =>
+                $crate::instruction::AnInstruction::Divine(Default::default()),
+            instruction_dup: $crate::instruction::AnInstruction::Dup(_) =>
+                $crate::instruction::AnInstruction::Dup(Default::default()),
+            instruction_swap: $crate::instruction::AnInstruction::Swap(_) =>
+                $crate::instruction::AnInstruction::Swap(Default::default()),
+            instruction_nop: $crate::instruction::AnInstruction::Nop =>
+                $crate::instruction::AnInstruction::Nop,
+            instruction_skiz: $crate::instruction::AnInstruction::Skiz =>
+                $crate::instruction::AnInstruction::Skiz,
+            instruction_call: $crate::instruction::AnInstruction::Call(_) =>
+                $crate::instruction::AnInstruction::Call(Default::default()),
+            instruction_tail_call: $crate::instruction::AnInstruction::TailCall(_) =>
+                $crate::instruction::AnInstruction::TailCall(Default::default()),
+            instruction_return: $crate::instruction::AnInstruction::Return =>
+                $crate::instruction::AnInstruction::Return,
+            instruction_recurse: $crate::instruction::AnInstruction::Recurse =>
+                $crate::instruction::AnInstruction::Recurse,
+            instruction_assert: $crate::instruction::AnInstruction::Assert =>
+                $crate::instruction::AnInstruction::Assert,
+            instruction_halt: $crate::instruction::AnInstruction::Halt =>
+                $crate::instruction::AnInstruction::Halt,
+            instruction_read_mem: $crate::instruction::AnInstruction::ReadMem =>
+                $crate::instruction::AnInstruction::ReadMem,
+            instruction_write_mem: $crate::instruction::AnInstruction::WriteMem =>
+                $crate::instruction::AnInstruction::WriteMem,
+            instruction_read_mem_inc: $crate::instruction::AnInstruction::ReadMemInc =>
+                $crate::instruction::AnInstruction::ReadMemInc,
+            instruction_write_mem_inc: $crate::instruction::AnInstruction::WriteMemInc =>
+                $crate::instruction::AnInstruction::WriteMemInc,
+            instruction_hash: $crate::instruction::AnInstruction::Hash =>
+                $crate::instruction::AnInstruction::Hash,
+            instruction_divine_sibling: $crate::instruction::AnInstruction::DivineSibling =>
+                $crate::instruction::AnInstruction::DivineSibling,
+            instruction_assert_vector: $crate::instruction::AnInstruction::AssertVector =>
+                $crate::instruction::AnInstruction::AssertVector,
+            instruction_add: $crate::instruction::AnInstruction::Add =>
+                $crate::instruction::AnInstruction::Add,
+            instruction_mul: $crate::instruction::AnInstruction::Mul =>
+                $crate::instruction::AnInstruction::Mul,
+            instruction_invert: $crate::instruction::AnInstruction::Invert =>
+                $crate::instruction::AnInstruction::Invert,
+            instruction_split: $crate::instruction::AnInstruction::Split =>
+                $crate::instruction::AnInstruction::Split,
+            instruction_eq: $crate::instruction::AnInstruction::Eq =>
+                $crate::instruction::AnInstruction::Eq,
+            instruction_lsb: $crate::instruction::AnInstruction::Lsb =>
+                $crate::instruction::AnInstruction::Lsb,
+            instruction_xxadd: $crate::instruction::AnInstruction::XxAdd =>
+                $crate::instruction::AnInstruction::XxAdd,
+            instruction_xxmul: $crate::instruction::AnInstruction::XxMul =>
+                $crate::instruction::AnInstruction::XxMul,
+            instruction_xinvert: $crate::instruction::AnInstruction::XInvert =>
+                $crate::instruction::AnInstruction::XInvert,
+            instruction_xbmul: $crate::instruction::AnInstruction::XbMul =>
+                $crate::instruction::AnInstruction::XbMul,
+            instruction_read_io: $crate::instruction::AnInstruction::ReadIo =>
+                $crate::instruction::AnInstruction::ReadIo,
+            instruction_write_io: $crate::instruction::AnInstruction::WriteIo =>
+                $crate::instruction::AnInstruction::WriteIo,
+        }
+    };
+}
+
 impl<Dest: Display + PartialEq + Default> Display for AnInstruction<Dest> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -119,6 +247,7 @@ impl<Dest: Display + PartialEq + Default> Display for AnInstruction<Dest> {
             Nop => write!(f, "nop"),
             Skiz => write!(f, "skiz"),
             Call(arg) => write!(f, "call {}", arg),
+            TailCall(arg) => write!(f, "tail_call {}", arg),
             Return => write!(f, "return"),
             Recurse => write!(f, "recurse"),
             Assert => write!(f, "assert"),
@@ -127,6 +256,8 @@ impl<Dest: Display + PartialEq + Default> Display for AnInstruction<Dest> {
             // Memory access
             ReadMem => write!(f, "read_mem"),
             WriteMem => write!(f, "write_mem"),
+            ReadMemInc => write!(f, "read_mem_inc"),
+            WriteMemInc => write!(f, "write_mem_inc"),
 
             // Hash instructions
             Hash => write!(f, "hash"),
@@ -156,36 +287,56 @@ impl<Dest: Display + PartialEq + Default> Display for AnInstruction<Dest> {
 impl<Dest: PartialEq + Default> AnInstruction<Dest> {
     /// Drop the specific argument in favor of a default one.
     pub fn strip(&self) -> Self {
+        macro_rules! strip {
+            ($($name:ident: $pattern:pat => $representative:expr),* $(,)?) => {
+                match self {
+                    $($pattern => $representative),*
+                }
+            };
+        }
+        for_all_instructions!(strip)
+    }
+
+    /// The instruction's mnemonic, without any argument.
+    pub fn name(&self) -> &'static str {
         match self {
-            Push(_) => Push(Default::default()),
-            Divine(_) => Divine(Default::default()),
-            Dup(_) => Dup(Default::default()),
-            Swap(_) => Swap(Default::default()),
-            Call(_) => Call(Default::default()),
-            Pop => Pop,
-            Nop => Nop,
-            Skiz => Skiz,
-            Return => Return,
-            Recurse => Recurse,
-            Assert => Assert,
-            Halt => Halt,
-            ReadMem => ReadMem,
-            WriteMem => WriteMem,
-            Hash => Hash,
-            DivineSibling => DivineSibling,
-            AssertVector => AssertVector,
-            Add => Add,
-            Mul => Mul,
-            Invert => Invert,
-            Split => Split,
-            Eq => Eq,
-            Lsb => Lsb,
-            XxAdd => XxAdd,
-            XxMul => XxMul,
-            XInvert => XInvert,
-            XbMul => XbMul,
-            ReadIo => ReadIo,
-            WriteIo => WriteIo,
+            Pop => "pop",
+            Push(_) => "push",
+            Divine(Some(Quotient)) => "divine_quotient",
+            Divine(Some(QuotientU64Hi)) => "divine_quotient_u64_hi",
+            Divine(Some(QuotientU64Lo)) => "divine_quotient_u64_lo",
+            Divine(Some(RemainderU64Hi)) => "divine_remainder_u64_hi",
+            Divine(Some(RemainderU64Lo)) => "divine_remainder_u64_lo",
+            Divine(None) => "divine",
+            Dup(_) => "dup",
+            Swap(_) => "swap",
+            Nop => "nop",
+            Skiz => "skiz",
+            Call(_) => "call",
+            TailCall(_) => "tail_call",
+            Return => "return",
+            Recurse => "recurse",
+            Assert => "assert",
+            Halt => "halt",
+            ReadMem => "read_mem",
+            WriteMem => "write_mem",
+            ReadMemInc => "read_mem_inc",
+            WriteMemInc => "write_mem_inc",
+            Hash => "hash",
+            DivineSibling => "divine_sibling",
+            AssertVector => "assert_vector",
+            Add => "add",
+            Mul => "mul",
+            Invert => "invert",
+            Split => "split",
+            Eq => "eq",
+            Lsb => "lsb",
+            XxAdd => "xxadd",
+            XxMul => "xxmul",
+            XInvert => "xinvert",
+            XbMul => "xbmul",
+            ReadIo => "read_io",
+            WriteIo => "write_io",
         }
     }
 
@@ -200,12 +351,15 @@ impl<Dest: PartialEq + Default> AnInstruction<Dest> {
             Nop => 8,
             Skiz => 6,
             Call(_) => 13,
+            TailCall(_) => 17,
             Return => 12,
             Recurse => 16,
             Assert => 10,
             Halt => 0,
             ReadMem => 20,
             WriteMem => 24,
+            ReadMemInc => 68,
+            WriteMemInc => 72,
             Hash => 28,
             DivineSibling => 32,
             AssertVector => 36,
@@ -230,16 +384,64 @@ impl<Dest: PartialEq + Default> AnInstruction<Dest> {
     pub fn is_op_stack_instruction(&self) -> bool {
         !matches!(
             self,
-            Nop | Call(_) | Return | Recurse | Halt | Hash | AssertVector
+            Nop | Call(_) | TailCall(_) | Return | Recurse | Halt | Hash | AssertVector
         )
     }
 
+    /// By how many elements the instruction changes the height of the operational stack, as
+    /// specified in the ISA. Negative values indicate shrinking of the stack.
+    pub fn op_stack_size_influence(&self) -> i32 {
+        match self {
+            Pop => -1,
+            Push(_) => 1,
+            Divine(_) => 1,
+            Dup(_) => 1,
+            Swap(_) => 0,
+            Nop => 0,
+            Skiz => -1,
+            Call(_) => 0,
+            TailCall(_) => 0,
+            Return => 0,
+            Recurse => 0,
+            Assert => -1,
+            Halt => 0,
+            ReadMem => 0,
+            WriteMem => 0,
+            ReadMemInc => 0,
+            WriteMemInc => 0,
+            Hash => 0,
+            DivineSibling => 0,
+            AssertVector => 0,
+            Add => -1,
+            Mul => -1,
+            Invert => 0,
+            Split => 1,
+            Eq => -1,
+            Lsb => 1,
+            XxAdd => 0,
+            XxMul => 0,
+            XInvert => 0,
+            XbMul => -1,
+            ReadIo => 1,
+            WriteIo => -1,
+        }
+    }
+
+    /// How the instruction accesses random-access memory, as specified in the ISA.
+    pub fn ram_access(&self) -> RamAccess {
+        match self {
+            ReadMem | ReadMemInc => RamAccess::Read,
+            WriteMem | WriteMemInc => RamAccess::Write,
+            _ => RamAccess::None,
+        }
+    }
+
     pub fn opcode_b(&self) -> BFieldElement {
         self.opcode().into()
     }
 
     pub fn size(&self) -> usize {
-        if matches!(self, Push(_) | Dup(_) | Swap(_) | Call(_)) {
+        if matches!(self, Push(_) | Dup(_) | Swap(_) | Call(_) | TailCall(_)) {
             2
         } else {
             1
@@ -254,7 +456,10 @@ impl<Dest: PartialEq + Default> AnInstruction<Dest> {
         ((opcode >> bit_number) & 1).into()
     }
 
-    fn map_call_address<F, NewDest: PartialEq + Default>(&self, f: F) -> AnInstruction<NewDest>
+    pub(crate) fn map_call_address<F, NewDest: PartialEq + Default>(
+        &self,
+        f: F,
+    ) -> AnInstruction<NewDest>
     where
         F: Fn(&Dest) -> NewDest,
     {
@@ -267,12 +472,15 @@ impl<Dest: PartialEq + Default> AnInstruction<Dest> {
             Nop => Nop,
             Skiz => Skiz,
             Call(label) => Call(f(label)),
+            TailCall(label) => TailCall(f(label)),
             Return => Return,
             Recurse => Recurse,
             Assert => Assert,
             Halt => Halt,
             ReadMem => ReadMem,
             WriteMem => WriteMem,
+            ReadMemInc => ReadMemInc,
+            WriteMemInc => WriteMemInc,
             Hash => Hash,
             DivineSibling => DivineSibling,
             AssertVector => AssertVector,
@@ -300,6 +508,7 @@ impl Instruction {
             Dup(arg) => Some(ord16_to_bfe(arg)),
             Swap(arg) => Some(ord16_to_bfe(arg)),
             Call(arg) => Some(*arg),
+            TailCall(arg) => Some(*arg),
             _ => None,
         }
     }
@@ -357,16 +566,16 @@ impl Display for TokenError {
 
 impl Error for TokenError {}
 
-/// Convert a program with labels to a program with absolute positions
-pub fn convert_labels(program: &[LabelledInstruction]) -> Vec<Instruction> {
+/// Map every label of the program to the absolute address it refers to. Local labels are
+/// mangled, _i.e._, a local label `.loop` following global label `foo:` is found as `foo.loop`.
+pub fn label_addresses(program: &[LabelledInstruction]) -> HashMap<String, usize> {
     let mut label_map = HashMap::<String, usize>::new();
     let mut instruction_pointer: usize = 0;
 
-    // 1. Add all labels to a map
-    for labelled_instruction in program.iter() {
+    for labelled_instruction in mangle_local_labels(program) {
         match labelled_instruction {
             LabelledInstruction::Label(label_name) => {
-                label_map.insert(label_name.clone(), instruction_pointer);
+                label_map.insert(label_name, instruction_pointer);
             }
 
             LabelledInstruction::Instruction(instr) => {
@@ -375,6 +584,16 @@ pub fn convert_labels(program: &[LabelledInstruction]) -> Vec<Instruction> {
         }
     }
 
+    label_map
+}
+
+/// Convert a program with labels to a program with absolute positions
+pub fn convert_labels(program: &[LabelledInstruction]) -> Vec<Instruction> {
+    let program = mangle_local_labels(program);
+
+    // 1. Add all labels to a map
+    let label_map = label_addresses(&program);
+
     // 2. Convert every label to the lookup value of that map
     program
         .iter()
@@ -406,35 +625,374 @@ pub fn parse(code_with_comments: &str) -> Result<Vec<LabelledInstruction>> {
     let code = remove_comments.replace_all(code_with_comments, "");
     let mut tokens = code.split_whitespace();
     let mut instructions = vec![];
+    let mut open_blocks: Vec<OpenBlock> = vec![];
+    let mut out_of_line_code = vec![];
+    let mut num_blocks = 0;
+    let mut current_scope = None;
 
     while let Some(token) = tokens.next() {
-        let mut instruction = parse_token(token, &mut tokens)?;
-        instructions.append(&mut instruction);
+        let control_structure = match token {
+            "repeat" => {
+                let num_iterations = tokens.next().ok_or(UnexpectedEndOfStream)?;
+                Some(ControlStructure::Repeat(num_iterations.parse()?))
+            }
+            "while" => Some(ControlStructure::While),
+            "if" => Some(ControlStructure::If),
+            _ => None,
+        };
+        if let Some(control_structure) = control_structure {
+            open_blocks.push(OpenBlock::new(control_structure, &current_scope));
+            continue;
+        }
+
+        match token {
+            "else" => match open_blocks.last_mut() {
+                Some(open_block) if open_block.kind == ControlStructure::If => {
+                    if open_block.then_branch.is_some() {
+                        bail!("`if` with more than one `else`.");
+                    }
+                    open_block.then_branch = Some(std::mem::take(&mut open_block.body));
+                }
+                _ => bail!("`else` without matching `if`."),
+            },
+            "end" => {
+                let Some(open_block) = open_blocks.pop() else {
+                    bail!("`end` without matching `repeat`, `while`, or `if`.");
+                };
+                let (call_site, out_of_line) = open_block.lower(num_blocks)?;
+                num_blocks += 1;
+                match open_blocks.last_mut() {
+                    Some(enclosing_block) => enclosing_block.body.extend_by_block(call_site),
+                    None => instructions.extend(call_site.instructions),
+                }
+                out_of_line_code.extend(out_of_line);
+            }
+            _ => {
+                let instruction = parse_token(token, &mut tokens)?;
+                let Some(open_block) = open_blocks.last_mut() else {
+                    for labelled_instruction in instruction.iter() {
+                        if let LabelledInstruction::Label(label) = labelled_instruction {
+                            if !is_local_label(label) {
+                                current_scope = Some(label.to_string());
+                            }
+                        }
+                    }
+                    instructions.extend(instruction);
+                    continue;
+                };
+                if let Some(LabelledInstruction::Label(label)) = instruction.first() {
+                    let keyword = open_block.kind.keyword();
+                    bail!("Label `{label}` is defined in the body of `{keyword}`.");
+                }
+                open_block.body.extend(instruction);
+            }
+        }
+    }
+    if let Some(open_block) = open_blocks.last() {
+        bail!("`{}` without matching `end`.", open_block.kind.keyword());
     }
+    instructions.extend(out_of_line_code);
 
-    let all_labels: Vec<String> = instructions
-        .iter()
-        .flat_map(|instr| match instr {
-            LabelledInstruction::Instruction(_) => vec![],
-            LabelledInstruction::Label(label) => vec![label.clone()],
-        })
-        .collect();
+    let mut scope = None;
     let mut seen_labels: HashSet<String> = HashSet::default();
-    let mut duplicate_labels: HashSet<String> = HashSet::default();
-    for label in all_labels {
-        if !seen_labels.insert(label.clone()) {
-            duplicate_labels.insert(label);
+    let mut duplicate_labels: Vec<String> = vec![];
+    for instruction in instructions.iter() {
+        if let LabelledInstruction::Label(label) = instruction {
+            if !is_local_label(label) {
+                scope = Some(label.as_str());
+            }
+            if !seen_labels.insert(mangle_label(scope, label)) {
+                duplicate_labels.push(describe_label_scope(scope, label));
+            }
         }
     }
 
     if !duplicate_labels.is_empty() {
-        bail!("Duplicate labels: {}", duplicate_labels.iter().join(", "));
+        bail!(
+            "Duplicate labels: {}",
+            duplicate_labels.iter().unique().join(", ")
+        );
+    }
+
+    Ok(mangle_local_labels(&instructions))
+}
+
+/// Structured control flow of Triton assembly, ended by `end`. The bodies of control structures
+/// are placed out of line, after the rest of the program, each starting with a fresh label. In
+/// place of the control structure, the bodies are `call`ed. Control structures can be nested,
+/// but their bodies cannot define labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControlStructure {
+    /// `repeat n … end` executes the body `n` times. While the body executes, the number of
+    /// remaining iterations, including the current one, is on top of the stack. The body must
+    /// leave it there.
+    Repeat(u32),
+
+    /// `while … end` pops the top of the stack and, if it is not zero, executes the body, which
+    /// must push the condition for the next iteration.
+    While,
+
+    /// `if … else … end` pops the top of the stack and executes the first branch if it is not
+    /// zero, and the optional second branch otherwise. Both branches must change the height of the
+    /// op stack by the same amount.
+    If,
+}
+
+impl ControlStructure {
+    fn keyword(&self) -> &'static str {
+        match self {
+            ControlStructure::Repeat(_) => "repeat",
+            ControlStructure::While => "while",
+            ControlStructure::If => "if",
+        }
+    }
+}
+
+/// Code in the body of a control structure, together with its effect on the height of the op
+/// stack, if that effect is known statically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StructuredCode {
+    instructions: Vec<LabelledInstruction>,
+    stack_effect: Option<i32>,
+}
+
+impl Default for StructuredCode {
+    fn default() -> Self {
+        Self {
+            instructions: vec![],
+            stack_effect: Some(0),
+        }
+    }
+}
+
+impl StructuredCode {
+    fn new(instructions: Vec<AnInstruction<String>>, stack_effect: Option<i32>) -> Self {
+        let instructions = instructions
+            .into_iter()
+            .map(LabelledInstruction::Instruction)
+            .collect();
+        Self {
+            instructions,
+            stack_effect,
+        }
+    }
+
+    /// Append the given instructions. The effect of `call`s and other jumps on the op stack is
+    /// not known statically.
+    fn extend(&mut self, instructions: Vec<LabelledInstruction>) {
+        for labelled_instruction in instructions.iter() {
+            if let LabelledInstruction::Instruction(instruction) = labelled_instruction {
+                self.stack_effect = match instruction {
+                    Call(_) | TailCall(_) | Return | Recurse | Halt => None,
+                    _ => self
+                        .stack_effect
+                        .map(|effect| effect + instruction.op_stack_size_influence()),
+                };
+            }
+        }
+        self.instructions.extend(instructions);
+    }
+
+    /// Append the call site of a nested control structure, whose effect is known.
+    fn extend_by_block(&mut self, call_site: StructuredCode) {
+        self.stack_effect = self
+            .stack_effect
+            .zip(call_site.stack_effect)
+            .map(|(effect, call_site_effect)| effect + call_site_effect);
+        self.instructions.extend(call_site.instructions);
+    }
+
+    /// The out-of-line body of a control structure, starting with the given label. Local labels
+    /// are resolved relative to the given scope.
+    fn out_of_line(
+        self,
+        label: String,
+        scope: Option<&str>,
+        prologue: Vec<AnInstruction<String>>,
+        epilogue: Vec<AnInstruction<String>>,
+    ) -> Vec<LabelledInstruction> {
+        let body =
+            self.instructions
+                .into_iter()
+                .map(|labelled_instruction| match labelled_instruction {
+                    LabelledInstruction::Instruction(instruction) => {
+                        LabelledInstruction::Instruction(
+                            instruction.map_call_address(|label| mangle_label(scope, label)),
+                        )
+                    }
+                    label => label,
+                });
+        let instruction = LabelledInstruction::Instruction;
+        [LabelledInstruction::Label(label)]
+            .into_iter()
+            .chain(prologue.into_iter().map(instruction))
+            .chain(body)
+            .chain(epilogue.into_iter().map(instruction))
+            .collect()
+    }
+}
+
+/// A control structure whose `end` has not been parsed yet.
+struct OpenBlock {
+    kind: ControlStructure,
+
+    /// The global label enclosing the control structure. Local labels used in the body refer to
+    /// this scope.
+    scope: Option<String>,
+    body: StructuredCode,
+
+    /// For `if`, the first branch once `else` has been parsed.
+    then_branch: Option<StructuredCode>,
+}
+
+impl OpenBlock {
+    fn new(kind: ControlStructure, scope: &Option<String>) -> Self {
+        Self {
+            kind,
+            scope: scope.clone(),
+            body: StructuredCode::default(),
+            then_branch: None,
+        }
+    }
+
+    /// The code replacing the control structure at the place of its definition, and the
+    /// out-of-line code it calls. The labels of the out-of-line code are made unique by the given
+    /// index of the control structure.
+    fn lower(self, index: usize) -> Result<(StructuredCode, Vec<LabelledInstruction>)> {
+        let scope = self.scope.as_deref();
+        let keyword = self.kind.keyword();
+        let label = |name: &str| format!("__{name}_{index}");
+        let zero = || Push(0_u64.into());
+        let body_effect = self.body.stack_effect;
+
+        let lowered = match (self.kind, self.then_branch) {
+            (ControlStructure::Repeat(num_iterations), _) => {
+                if let Some(effect) = body_effect.filter(|&effect| effect != 0) {
+                    bail!(
+                        "The body of `repeat` must not change the stack height, \
+                        but changes it by {effect:+}."
+                    );
+                }
+                let call_site = vec![Push(num_iterations.into()), Call(label(keyword)), Pop];
+                let prologue = vec![Dup(ST0), zero(), Eq, Skiz, Return];
+                let epilogue = vec![Push(-BFieldElement::new(1)), Add, Recurse];
+                let body = self
+                    .body
+                    .out_of_line(label(keyword), scope, prologue, epilogue);
+                (StructuredCode::new(call_site, body_effect), body)
+            }
+            (ControlStructure::While, _) => {
+                if let Some(effect) = body_effect.filter(|&effect| effect != 1) {
+                    bail!(
+                        "The body of `while` must grow the stack by 1, \
+                        but changes it by {effect:+}."
+                    );
+                }
+                let call_site = vec![Call(label(keyword))];
+                let call_site_effect = body_effect.map(|_| -1);
+                let prologue = vec![zero(), Eq, Skiz, Return];
+                let body = self
+                    .body
+                    .out_of_line(label(keyword), scope, prologue, vec![Recurse]);
+                (StructuredCode::new(call_site, call_site_effect), body)
+            }
+            (ControlStructure::If, None) => {
+                if let Some(effect) = body_effect.filter(|&effect| effect != 0) {
+                    bail!(
+                        "`if` without `else` must not change the stack height, \
+                        but changes it by {effect:+}."
+                    );
+                }
+                let call_site = vec![Skiz, Call(label("then"))];
+                let then_branch = self
+                    .body
+                    .out_of_line(label("then"), scope, vec![], vec![Return]);
+                let call_site_effect = body_effect.map(|_| -1);
+                (
+                    StructuredCode::new(call_site, call_site_effect),
+                    then_branch,
+                )
+            }
+            (ControlStructure::If, Some(then_branch)) => {
+                let else_branch = self.body;
+                let call_site_effect = match (then_branch.stack_effect, else_branch.stack_effect) {
+                    (Some(then_effect), Some(else_effect)) if then_effect != else_effect => bail!(
+                        "Branches of `if` change the stack height differently: \
+                        {then_effect:+} and {else_effect:+}."
+                    ),
+                    (Some(effect), Some(_)) => Some(effect - 1),
+                    _ => None,
+                };
+                let call_site = vec![
+                    Push(1_u64.into()),
+                    Swap(ST1),
+                    Skiz,
+                    Call(label("then")),
+                    Skiz,
+                    Call(label("else")),
+                ];
+                let then_branch =
+                    then_branch.out_of_line(label("then"), scope, vec![Pop], vec![zero(), Return]);
+                let else_branch =
+                    else_branch.out_of_line(label("else"), scope, vec![], vec![Return]);
+                let out_of_line = [then_branch, else_branch].concat();
+                (
+                    StructuredCode::new(call_site, call_site_effect),
+                    out_of_line,
+                )
+            }
+        };
+        Ok(lowered)
+    }
+}
+
+/// Local labels start with a `.`, like `.loop`, and are scoped to the closest preceding global
+/// label. This allows combining code snippets from different sources without label collisions.
+fn is_local_label(label: &str) -> bool {
+    label.starts_with('.')
+}
+
+fn mangle_label(scope: Option<&str>, label: &str) -> String {
+    match scope {
+        Some(scope) if is_local_label(label) => format!("{scope}{label}"),
+        _ => label.to_string(),
     }
+}
 
-    Ok(instructions)
+fn describe_label_scope(scope: Option<&str>, label: &str) -> String {
+    match scope {
+        Some(scope) if is_local_label(label) => format!("{label} (in scope {scope})"),
+        None if is_local_label(label) => format!("{label} (in global scope)"),
+        _ => label.to_string(),
+    }
 }
 
-fn parse_token(token: &str, tokens: &mut SplitWhitespace) -> Result<Vec<LabelledInstruction>> {
+/// Prefix every local label, as well as every call to a local label, with the name of the
+/// enclosing global label. For example, `.loop` following `foo:` becomes `foo.loop`. Local labels
+/// preceding all global labels are left as they are. Mangling already mangled code changes
+/// nothing.
+pub fn mangle_local_labels(program: &[LabelledInstruction]) -> Vec<LabelledInstruction> {
+    let mut scope = None;
+    program
+        .iter()
+        .map(|labelled_instruction| match labelled_instruction {
+            LabelledInstruction::Label(label) => {
+                if !is_local_label(label) {
+                    scope = Some(label.as_str());
+                }
+                LabelledInstruction::Label(mangle_label(scope, label))
+            }
+            LabelledInstruction::Instruction(instruction) => LabelledInstruction::Instruction(
+                instruction.map_call_address(|label| mangle_label(scope, label)),
+            ),
+        })
+        .collect()
+}
+
+pub(crate) fn parse_token(
+    token: &str,
+    tokens: &mut SplitWhitespace,
+) -> Result<Vec<LabelledInstruction>> {
     if let Some(label) = token.strip_suffix(':') {
         let label_name = label.to_string();
         return Ok(vec![LabelledInstruction::Label(label_name)]);
@@ -446,6 +1004,10 @@ fn parse_token(token: &str, tokens: &mut SplitWhitespace) -> Result<Vec<Labelled
         "push" => vec![Push(parse_elem(tokens)?)],
         "divine" => vec![Divine(None)],
         "divine_quotient" => vec![Divine(Some(Quotient))],
+        "divine_quotient_u64_hi" => vec![Divine(Some(QuotientU64Hi))],
+        "divine_quotient_u64_lo" => vec![Divine(Some(QuotientU64Lo))],
+        "divine_remainder_u64_hi" => vec![Divine(Some(RemainderU64Hi))],
+        "divine_remainder_u64_lo" => vec![Divine(Some(RemainderU64Lo))],
         "dup0" => vec![Dup(ST0)],
         "dup1" => vec![Dup(ST1)],
         "dup2" => vec![Dup(ST2)],
@@ -482,6 +1044,7 @@ fn parse_token(token: &str, tokens: &mut SplitWhitespace) -> Result<Vec<Labelled
         "nop" => vec![Nop],
         "skiz" => vec![Skiz],
         "call" => vec![Call(parse_label(tokens)?)],
+        "tail_call" => vec![TailCall(parse_label(tokens)?)],
         "return" => vec![Return],
         "recurse" => vec![Recurse],
         "assert" => vec![Assert],
@@ -490,6 +1053,8 @@ fn parse_token(token: &str, tokens: &mut SplitWhitespace) -> Result<Vec<Labelled
         // Memory access
         "read_mem" => vec![ReadMem],
         "write_mem" => vec![WriteMem],
+        "read_mem_inc" => vec![ReadMemInc],
+        "write_mem_inc" => vec![WriteMemInc],
 
         // Hashing-related instructions
         "hash" => vec![Hash],
@@ -521,14 +1086,32 @@ fn parse_token(token: &str, tokens: &mut SplitWhitespace) -> Result<Vec<Labelled
 
         "is_u32" => pseudo_instruction_is_u32(),
         "split_assert" => pseudo_instruction_split_assert(),
+        "add_u32_checked" => pseudo_instruction_add_u32_checked(),
+        "mul_u32_checked" => pseudo_instruction_mul_u32_checked(),
+
+        "lte_i32" => pseudo_instruction_lte_i32(),
+        "lt_i32" => pseudo_instruction_lt_i32(),
+        "div_i32" => pseudo_instruction_div_i32(),
+        "lte_i64" => pseudo_instruction_lte_i64(),
+        "lt_i64" => pseudo_instruction_lt_i64(),
+        "div_i64" => pseudo_instruction_div_i64(),
+
+        "unpack_bytes" => pseudo_instruction_unpack_bytes(),
 
         "eq_vector" => pseudo_instruction_eq_vector(),
 
+        "sponge_init" => pseudo_instruction_sponge_init(),
+        "sponge_absorb" => pseudo_instruction_sponge_absorb(),
+        "sponge_squeeze" => pseudo_instruction_sponge_squeeze(),
+
         // Read/write
         "read_io" => vec![ReadIo],
         "write_io" => vec![WriteIo],
 
-        _ => return Err(anyhow::Error::new(UnknownInstruction(token.to_string()))),
+        _ => match pseudo_instruction_spill_or_reload(token) {
+            Some(instructions) => instructions,
+            None => return Err(anyhow::Error::new(UnknownInstruction(token.to_string()))),
+        },
     };
 
     let labelled_instruction = instruction
@@ -559,7 +1142,7 @@ fn pseudo_instruction_is_u32() -> Vec<AnInstruction<String>> {
 }
 
 fn pseudo_instruction_split_assert() -> Vec<AnInstruction<String>> {
-    vec![
+    [
         vec![Split],
         pseudo_instruction_is_u32(),
         vec![Swap(ST1)],
@@ -569,8 +1152,20 @@ fn pseudo_instruction_split_assert() -> Vec<AnInstruction<String>> {
     .concat()
 }
 
+/// Add two u32s, asserting that the sum is a u32, too. The sum of two u32s is less than the field's
+/// modulus, so for u32 operands, the check catches any overflow.
+fn pseudo_instruction_add_u32_checked() -> Vec<AnInstruction<String>> {
+    [vec![Add], pseudo_instruction_is_u32()].concat()
+}
+
+/// Multiply two u32s, asserting that the product is a u32, too. The product of two u32s is less
+/// than the field's modulus, so for u32 operands, the check catches any overflow.
+fn pseudo_instruction_mul_u32_checked() -> Vec<AnInstruction<String>> {
+    [vec![Mul], pseudo_instruction_is_u32()].concat()
+}
+
 fn pseudo_instruction_lte() -> Vec<AnInstruction<String>> {
-    vec![
+    [
         vec![Push(-BFieldElement::new(1)), Mul, Add],
         pseudo_instruction_split_assert(),
         vec![Push(0_u64.into()), Eq, Swap(ST1), Pop],
@@ -579,11 +1174,11 @@ fn pseudo_instruction_lte() -> Vec<AnInstruction<String>> {
 }
 
 fn pseudo_instruction_lt() -> Vec<AnInstruction<String>> {
-    vec![vec![Push(1_u64.into()), Add], pseudo_instruction_lte()].concat()
+    [vec![Push(1_u64.into()), Add], pseudo_instruction_lte()].concat()
 }
 
 fn pseudo_instruction_div() -> Vec<AnInstruction<String>> {
-    vec![
+    [
         vec![
             // _ d n
             Divine(Some(Quotient)),
@@ -631,6 +1226,499 @@ fn pseudo_instruction_div() -> Vec<AnInstruction<String>> {
     .concat()
 }
 
+/// Maps the two's-complement encoding of an `i32` to a u32 that preserves the order of the
+/// signed integers by flipping the sign bit.
+fn flip_i32_sign_bit() -> Vec<AnInstruction<String>> {
+    [
+        pseudo_instruction_is_u32(),
+        vec![Push((1_u64 << 31).into()), Add],
+        pseudo_instruction_split_assert(),
+        vec![Pop],
+    ]
+    .concat()
+}
+
+fn pseudo_instruction_lte_i32() -> Vec<AnInstruction<String>> {
+    [
+        flip_i32_sign_bit(),
+        vec![Swap(ST1)],
+        flip_i32_sign_bit(),
+        vec![Swap(ST1)],
+        pseudo_instruction_lte(),
+    ]
+    .concat()
+}
+
+fn pseudo_instruction_lt_i32() -> Vec<AnInstruction<String>> {
+    [
+        flip_i32_sign_bit(),
+        vec![Swap(ST1)],
+        flip_i32_sign_bit(),
+        vec![Swap(ST1)],
+        pseudo_instruction_lt(),
+    ]
+    .concat()
+}
+
+/// Splits the two's-complement encoding of an `i32` into its absolute value and its sign bit.
+fn abs_i32() -> Vec<AnInstruction<String>> {
+    [
+        pseudo_instruction_is_u32(),
+        vec![
+            // _ a
+            Push((1_u64 << 31).into()),
+            // _ a 2^31
+            Dup(ST1),
+            // _ a 2^31 a
+        ],
+        pseudo_instruction_lt(),
+        vec![
+            // _ a (a<2^31)
+            Push(-BFieldElement::new(1)),
+            Mul,
+            Push(1_u64.into()),
+            Add,
+            // _ a s
+            Dup(ST1),
+            // _ a s a
+            Push(-BFieldElement::new(2)),
+            Mul,
+            Push((1_u64 << 32).into()),
+            Add,
+            // _ a s (2^32-2·a)
+            Dup(ST1),
+            Mul,
+            // _ a s s·(2^32-2·a)
+            Swap(ST1),
+            // _ a s·(2^32-2·a) s
+            Swap(ST2),
+            // _ s s·(2^32-2·a) a
+            Add,
+            // _ s |a|
+            Swap(ST1),
+            // _ |a| s
+        ],
+    ]
+    .concat()
+}
+
+/// The two's-complement encoding of `v` if sign bit `s` is 0, of `-v` otherwise.
+fn encode_i32() -> Vec<AnInstruction<String>> {
+    [
+        vec![
+            // _ v s
+            Dup(ST1),
+            // _ v s v
+            Push(-BFieldElement::new(2)),
+            Mul,
+            Push((1_u64 << 32).into()),
+            Add,
+            // _ v s (2^32-2·v)
+            Mul,
+            // _ v s·(2^32-2·v)
+            Add,
+            // _ (v or 2^32-v)
+        ],
+        pseudo_instruction_split_assert(),
+        vec![
+            // _ lo hi
+            Pop,
+            // _ lo
+        ],
+    ]
+    .concat()
+}
+
+fn pseudo_instruction_div_i32() -> Vec<AnInstruction<String>> {
+    [
+        // _ d n
+        abs_i32(),
+        // _ d |n| sn
+        vec![Swap(ST2)],
+        // _ sn |n| d
+        abs_i32(),
+        // _ sn |n| |d| sd
+        vec![Swap(ST2)],
+        // _ sn sd |d| |n|
+        pseudo_instruction_div(),
+        // _ sn sd |q| |r|
+        vec![Dup(ST3)],
+        // _ sn sd |q| |r| sn
+        encode_i32(),
+        // _ sn sd |q| r
+        vec![
+            Swap(ST3),
+            // _ r sd |q| sn
+            Dup(ST2),
+            // _ r sd |q| sn sd
+            Dup(ST1),
+            Dup(ST1),
+            Mul,
+            Push(-BFieldElement::new(2)),
+            Mul,
+            Add,
+            Add,
+            // _ r sd |q| (sn xor sd)
+        ],
+        encode_i32(),
+        vec![
+            // _ r sd q
+            Swap(ST1),
+            Pop,
+            // _ r q
+            Swap(ST1),
+            // _ q r
+        ],
+    ]
+    .concat()
+}
+
+/// Split an element packing 7 bytes into those bytes, the least significant byte on top. Crashes
+/// the VM if the element is 2^56 or larger. Since `split` does not range-check its results, the
+/// most significant byte of either half is asserted to be a byte, too.
+fn pseudo_instruction_unpack_bytes() -> Vec<AnInstruction<String>> {
+    let div_by = |divisor: u64| {
+        [
+            vec![Push(divisor.into()), Swap(ST1)],
+            pseudo_instruction_div(),
+        ]
+        .concat()
+    };
+    let assert_byte_below_top = [
+        vec![Swap(ST1)],
+        div_by(1 << 8),
+        vec![Swap(ST1), Push(0_u64.into()), Eq, Assert, Swap(ST1)],
+    ]
+    .concat();
+    [
+        // _ e
+        vec![Split],
+        // _ lo hi
+        div_by(1 << 16),
+        assert_byte_below_top.clone(),
+        // _ lo b6 r
+        div_by(1 << 8),
+        // _ lo b6 b5 b4
+        vec![Swap(ST1), Swap(ST2), Swap(ST3)],
+        // _ b6 b5 b4 lo
+        div_by(1 << 24),
+        assert_byte_below_top,
+        // _ b6 b5 b4 b3 r
+        div_by(1 << 16),
+        div_by(1 << 8),
+        // _ b6 b5 b4 b3 b2 b1 b0
+    ]
+    .concat()
+}
+
+/// Compares two u64s, each given as its high and low u32 limb. Turns `_ b_hi b_lo a_hi a_lo`
+/// into `_ (a ⋈ b)`, where `⋈` is the comparison of the given low limbs, `lt` or `lte`. Does not
+/// check that the limbs are u32s.
+fn compare_u64_limbs(compare_low_limbs: Vec<AnInstruction<String>>) -> Vec<AnInstruction<String>> {
+    [
+        vec![
+            // _ b_hi b_lo a_hi a_lo
+            Swap(ST1),
+            // _ b_hi b_lo a_lo a_hi
+            Dup(ST3),
+            Dup(ST1),
+            // _ b_hi b_lo a_lo a_hi b_hi a_hi
+        ],
+        pseudo_instruction_lt(),
+        vec![
+            // _ b_hi b_lo a_lo a_hi (a_hi<b_hi)
+            Swap(ST4),
+            // _ (a_hi<b_hi) b_lo a_lo a_hi b_hi
+            Eq,
+            // _ (a_hi<b_hi) b_lo a_lo (a_hi==b_hi)
+            Swap(ST2),
+            Swap(ST1),
+            // _ (a_hi<b_hi) (a_hi==b_hi) b_lo a_lo
+        ],
+        compare_low_limbs,
+        vec![
+            // _ (a_hi<b_hi) (a_hi==b_hi) (a_lo⋈b_lo)
+            Mul, Add,
+            // _ (a⋈b)
+        ],
+    ]
+    .concat()
+}
+
+/// Checks that all limbs of the two `i64`s `_ b_hi b_lo a_hi a_lo` are u32s and flips the sign
+/// bits of both high limbs, mapping the signed integers to u64s while preserving their order.
+fn flip_i64_sign_bits() -> Vec<AnInstruction<String>> {
+    [
+        pseudo_instruction_is_u32(),
+        vec![Swap(ST1)],
+        flip_i32_sign_bit(),
+        vec![Swap(ST1), Swap(ST2)],
+        pseudo_instruction_is_u32(),
+        vec![Swap(ST2), Swap(ST3)],
+        flip_i32_sign_bit(),
+        vec![Swap(ST3)],
+    ]
+    .concat()
+}
+
+fn pseudo_instruction_lte_i64() -> Vec<AnInstruction<String>> {
+    [
+        flip_i64_sign_bits(),
+        compare_u64_limbs(pseudo_instruction_lte()),
+    ]
+    .concat()
+}
+
+fn pseudo_instruction_lt_i64() -> Vec<AnInstruction<String>> {
+    [
+        flip_i64_sign_bits(),
+        compare_u64_limbs(pseudo_instruction_lt()),
+    ]
+    .concat()
+}
+
+/// The two's-complement encoding of `v` if sign bit `s` is 0, of `-v` otherwise, for a u64 `v`
+/// given as its high and low limb. Turns `_ v_hi v_lo s` into `_ w_hi w_lo`.
+fn negate_u64_if() -> Vec<AnInstruction<String>> {
+    [
+        vec![
+            // _ v_hi v_lo s
+            Dup(ST0),
+            Dup(ST2),
+            Push(-BFieldElement::new(2)),
+            Mul,
+            Push((1_u64 << 32).into()),
+            Add,
+            Mul,
+            // _ v_hi v_lo s s·(2^32-2·v_lo)
+            Swap(ST1),
+            Swap(ST2),
+            Add,
+            // _ v_hi s (v_lo or 2^32-v_lo)
+        ],
+        pseudo_instruction_split_assert(),
+        vec![
+            // _ v_hi s w_lo carry
+            Swap(ST1),
+            Swap(ST3),
+            // _ w_lo s carry v_hi
+            Dup(ST2),
+            Dup(ST1),
+            Push(-BFieldElement::new(2)),
+            Mul,
+            Push(((1_u64 << 32) - 1).into()),
+            Add,
+            Mul,
+            // _ w_lo s carry v_hi s·(2^32-1-2·v_hi)
+            Add,
+            Add,
+            // _ w_lo s ((v_hi or 2^32-1-v_hi)+carry)
+        ],
+        pseudo_instruction_split_assert(),
+        vec![
+            // _ w_lo s w_hi overflow
+            Pop,
+            Swap(ST2),
+            Swap(ST1),
+            Pop,
+            // _ w_hi w_lo
+        ],
+    ]
+    .concat()
+}
+
+/// Splits the two's-complement encoding of an `i64`, given as its high and low limb, into its
+/// absolute value and its sign bit. Turns `_ hi lo` into `_ |a|_hi |a|_lo s`.
+fn abs_i64() -> Vec<AnInstruction<String>> {
+    [
+        pseudo_instruction_is_u32(),
+        vec![Swap(ST1)],
+        pseudo_instruction_is_u32(),
+        vec![
+            // _ lo hi
+            Push((1_u64 << 31).into()),
+            Dup(ST1),
+        ],
+        pseudo_instruction_lt(),
+        vec![
+            // _ lo hi (hi<2^31)
+            Push(-BFieldElement::new(1)),
+            Mul,
+            Push(1_u64.into()),
+            Add,
+            // _ lo hi s
+            Dup(ST0),
+            Swap(ST3),
+            Swap(ST1),
+            // _ s hi lo s
+        ],
+        negate_u64_if(),
+        vec![
+            // _ s |a|_hi |a|_lo
+            Swap(ST1),
+            Swap(ST2),
+            // _ |a|_hi |a|_lo s
+        ],
+    ]
+    .concat()
+}
+
+/// Division with remainder of u64s, given as their high and low limbs. Turns
+/// `_ d_hi d_lo n_hi n_lo` into `_ q_hi q_lo r_hi r_lo`. Does not check that the limbs of `d` and
+/// `n` are u32s.
+fn div_u64() -> Vec<AnInstruction<String>> {
+    [
+        vec![Divine(Some(QuotientU64Hi))],
+        pseudo_instruction_is_u32(),
+        vec![Divine(Some(QuotientU64Lo))],
+        pseudo_instruction_is_u32(),
+        vec![Divine(Some(RemainderU64Hi))],
+        pseudo_instruction_is_u32(),
+        vec![Divine(Some(RemainderU64Lo))],
+        pseudo_instruction_is_u32(),
+        vec![
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo
+            Dup(ST7),
+            Dup(ST7),
+            Dup(ST3),
+            Dup(ST3),
+        ],
+        compare_u64_limbs(pseudo_instruction_lt()),
+        vec![
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo (r<d)
+            Assert,
+            // Either d_hi or q_hi is 0, else d·q overflows. Then, at most one of the products
+            // d_hi·q_lo and d_lo·q_hi is not 0, and none of the sums below wraps around.
+            Dup(ST7),
+            Dup(ST4),
+            Mul,
+            Push(0_u64.into()),
+            Eq,
+            Assert,
+            Dup(ST6),
+            Dup(ST3),
+            Mul,
+            Dup(ST1),
+            Add,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo (d_lo·q_lo+r_lo)
+        ],
+        pseudo_instruction_split_assert(),
+        vec![
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo lo carry
+            Swap(ST1),
+            Dup(ST6),
+            Eq,
+            Assert,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo carry
+            Dup(ST8),
+            Dup(ST4),
+            Mul,
+            Dup(ST8),
+            Dup(ST6),
+            Mul,
+            Add,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo carry (d_hi·q_lo+d_lo·q_hi)
+        ],
+        pseudo_instruction_split_assert(),
+        vec![
+            Push(0_u64.into()),
+            Eq,
+            Assert,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo carry (d_hi·q_lo+d_lo·q_hi)
+            Add,
+            Dup(ST2),
+            Add,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo (d_hi·q_lo+d_lo·q_hi+carry+r_hi)
+        ],
+        pseudo_instruction_split_assert(),
+        vec![
+            Push(0_u64.into()),
+            Eq,
+            Assert,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo hi
+            Dup(ST6),
+            Eq,
+            Assert,
+            // _ d_hi d_lo n_hi n_lo q_hi q_lo r_hi r_lo
+            Swap(ST4),
+            Pop,
+            Swap(ST4),
+            Pop,
+            Swap(ST4),
+            Pop,
+            Swap(ST4),
+            Pop,
+            // _ q_hi q_lo r_hi r_lo
+        ],
+    ]
+    .concat()
+}
+
+fn pseudo_instruction_div_i64() -> Vec<AnInstruction<String>> {
+    [
+        // _ d_hi d_lo n_hi n_lo
+        abs_i64(),
+        vec![
+            // _ d_hi d_lo |n|_hi |n|_lo sn
+            Swap(ST4),
+            Swap(ST2),
+            Swap(ST3),
+            Swap(ST1),
+            Swap(ST2),
+            Swap(ST1),
+            // _ sn |n|_hi |n|_lo d_hi d_lo
+        ],
+        abs_i64(),
+        vec![
+            // _ sn |n|_hi |n|_lo |d|_hi |d|_lo sd
+            Swap(ST4),
+            Swap(ST3),
+            Swap(ST1),
+            Swap(ST3),
+            Swap(ST2),
+            Swap(ST3),
+            Swap(ST2),
+            Swap(ST1),
+            // _ sn sd |d|_hi |d|_lo |n|_hi |n|_lo
+        ],
+        div_u64(),
+        vec![
+            // _ sn sd |q|_hi |q|_lo |r|_hi |r|_lo
+            Dup(ST5),
+        ],
+        negate_u64_if(),
+        vec![
+            // _ sn sd |q|_hi |q|_lo r_hi r_lo
+            Dup(ST5),
+            Dup(ST5),
+            Dup(ST1),
+            Dup(ST1),
+            Mul,
+            Push(-BFieldElement::new(2)),
+            Mul,
+            Add,
+            Add,
+            // _ sn sd |q|_hi |q|_lo r_hi r_lo (sn xor sd)
+            Swap(ST2),
+            Swap(ST6),
+            Pop,
+            Swap(ST4),
+            Pop,
+            // _ r_hi r_lo |q|_hi |q|_lo (sn xor sd)
+        ],
+        negate_u64_if(),
+        vec![
+            // _ r_hi r_lo q_hi q_lo
+            Swap(ST2),
+            Swap(ST1),
+            Swap(ST3),
+            Swap(ST1),
+            // _ q_hi q_lo r_hi r_lo
+        ],
+    ]
+    .concat()
+}
+
 fn pseudo_instruction_and() -> Vec<AnInstruction<String>> {
     let mut instructions = vec![];
 
@@ -680,7 +1768,7 @@ fn pseudo_instruction_and() -> Vec<AnInstruction<String>> {
 fn pseudo_instruction_xor() -> Vec<AnInstruction<String>> {
     // a+b = a^b + (a&b)<<1 => a^b = a+b - 2·(a&b)
     // Credit: Daniel Lubarov
-    vec![
+    [
         vec![Dup(ST1), Dup(ST1)],
         pseudo_instruction_and(),
         vec![Push(-BFieldElement::new(2)), Mul, Add, Add],
@@ -735,6 +1823,82 @@ fn pseudo_instruction_eq_vector() -> Vec<AnInstruction<String>> {
     ]
 }
 
+/// The initial state of the sponge used by `sponge_absorb` and `sponge_squeeze`: all zeros.
+fn pseudo_instruction_sponge_init() -> Vec<AnInstruction<String>> {
+    vec![Push(0_u64.into()); DIGEST_LENGTH]
+}
+
+/// Absorb five elements into the sponge state on top of the stack. The new state is the hash of
+/// the old state, followed by the absorbed elements.
+fn pseudo_instruction_sponge_absorb() -> Vec<AnInstruction<String>> {
+    // _ a4 a3 a2 a1 a0 s4 s3 s2 s1 s0
+    [vec![Hash], vec![Pop; DIGEST_LENGTH]].concat()
+    // _ s4' s3' s2' s1' s0'
+}
+
+/// Advance the sponge state on top of the stack, and put a copy of the new state below it as the
+/// pseudo-random output. The new state is the hash of five zeros, followed by the old state.
+fn pseudo_instruction_sponge_squeeze() -> Vec<AnInstruction<String>> {
+    [
+        // _ s4 s3 s2 s1 s0
+        vec![Push(0_u64.into()); DIGEST_LENGTH],
+        // _ s4 s3 s2 s1 s0 0 0 0 0 0
+        vec![Hash],
+        vec![Pop; DIGEST_LENGTH],
+        // _ s4' s3' s2' s1' s0'
+        vec![Dup(ST4); DIGEST_LENGTH],
+        // _ s4' s3' s2' s1' s0' s4' s3' s2' s1' s0'
+    ]
+    .concat()
+}
+
+/// The largest number of elements pseudo-instructions `spillK` and `reloadK` move at once.
+pub const MAX_SPILL_SIZE: usize = 15;
+
+/// Parse pseudo-instructions `spill1` through `spill15` and `reload1` through `reload15`.
+fn pseudo_instruction_spill_or_reload(token: &str) -> Option<Vec<AnInstruction<String>>> {
+    let num_elements = |suffix: &str| {
+        let num_elements = suffix.parse::<usize>().ok()?;
+        let is_canonical = num_elements.to_string() == suffix;
+        let is_in_range = (1..=MAX_SPILL_SIZE).contains(&num_elements);
+        (is_canonical && is_in_range).then_some(num_elements)
+    };
+    if let Some(num_elements) = token.strip_prefix("spill").and_then(num_elements) {
+        return Some(pseudo_instruction_spill(num_elements));
+    }
+    if let Some(num_elements) = token.strip_prefix("reload").and_then(num_elements) {
+        return Some(pseudo_instruction_reload(num_elements));
+    }
+    None
+}
+
+/// Moves the `num_elements` elements below the RAM pointer `p` on top of the stack to RAM,
+/// `st1` to address `p`, `st2` to address `p+1`, and so on. Leaves the pointer to the first
+/// address after the spilled elements on the stack.
+fn pseudo_instruction_spill(num_elements: usize) -> Vec<AnInstruction<String>> {
+    let mut instructions = vec![];
+    for _ in 0..num_elements {
+        instructions.extend([Swap(ST1), WriteMemInc, Pop]);
+    }
+    instructions
+}
+
+/// Inverse of `spill`: moves the `num_elements` elements right before the RAM pointer on top of
+/// the stack back to the stack, below the pointer to the first of them.
+fn pseudo_instruction_reload(num_elements: usize) -> Vec<AnInstruction<String>> {
+    let mut instructions = vec![];
+    for _ in 0..num_elements {
+        instructions.extend([
+            Push(-BFieldElement::new(1)),
+            Add,
+            Push(0_u64.into()),
+            ReadMem,
+            Swap(ST1),
+        ]);
+    }
+    instructions
+}
+
 fn parse_elem(tokens: &mut SplitWhitespace) -> Result<BFieldElement> {
     let constant_s = tokens.next().ok_or(UnexpectedEndOfStream)?;
 
@@ -757,48 +1921,36 @@ fn parse_label(tokens: &mut SplitWhitespace) -> Result<String> {
     Ok(label)
 }
 
-pub fn all_instructions_without_args() -> Vec<Instruction> {
-    let all_instructions = vec![
-        Pop,
-        Push(Default::default()),
-        Divine(None),
-        Dup(Default::default()),
-        Swap(Default::default()),
-        Nop,
-        Skiz,
-        Call(Default::default()),
-        Return,
-        Recurse,
-        Assert,
-        Halt,
-        ReadMem,
-        WriteMem,
-        Hash,
-        DivineSibling,
-        AssertVector,
-        Add,
-        Mul,
-        Invert,
-        Split,
-        Eq,
-        Lsb,
-        XxAdd,
-        XxMul,
-        XInvert,
-        XbMul,
-        ReadIo,
-        WriteIo,
-    ];
-    assert_eq!(Instruction::COUNT, all_instructions.len());
-    all_instructions
+macro_rules! all_instructions_without_args {
+    ($($name:ident: $pattern:pat => $representative:expr),* $(,)?) => {
+        /// One representative of every instruction, where arguments take their default value.
+        /// The order is the order of declaration in [`AnInstruction`], which is also the order of
+        /// [`Instruction::iter`].
+        pub fn all_instructions_without_args() -> [Instruction; Instruction::COUNT] {
+            // Fails to compile if `for_all_instructions!` misses a variant or lists one twice.
+            fn _list_is_exhaustive(instruction: &Instruction) {
+                match instruction {
+                    $($pattern => ()),*
+                }
+            }
+
+            [$($representative),*]
+        }
+    };
 }
 
+for_all_instructions!(all_instructions_without_args);
+
 pub fn all_labelled_instructions_with_args() -> Vec<LabelledInstruction> {
     vec![
         Pop,
         Push(BFieldElement::new(42)),
         Divine(None),
         Divine(Some(Quotient)),
+        Divine(Some(QuotientU64Hi)),
+        Divine(Some(QuotientU64Lo)),
+        Divine(Some(RemainderU64Hi)),
+        Divine(Some(RemainderU64Lo)),
         Dup(ST0),
         Dup(ST1),
         Dup(ST2),
@@ -833,12 +1985,15 @@ pub fn all_labelled_instructions_with_args() -> Vec<LabelledInstruction> {
         Nop,
         Skiz,
         Call("foo".to_string()),
+        TailCall("foo".to_string()),
         Return,
         Recurse,
         Assert,
         Halt,
         ReadMem,
         WriteMem,
+        ReadMemInc,
+        WriteMemInc,
         Hash,
         DivineSibling,
         AssertVector,
@@ -868,7 +2023,7 @@ pub mod sample_programs {
         pop
     ";
 
-    pub const EDGY_RAM_WRITES: &str = concat!();
+    pub const EDGY_RAM_WRITES: &str = "";
 
     pub const READ_WRITE_X3: &str = "
         read_io
@@ -902,6 +2057,7 @@ pub mod sample_programs {
         pop
         push 42
         divine divine_quotient
+        divine_quotient_u64_hi divine_quotient_u64_lo divine_remainder_u64_hi divine_remainder_u64_lo
 
         dup0 dup1 dup2 dup3 dup4 dup5 dup6 dup7 dup8 dup9 dup10 dup11 dup12 dup13 dup14 dup15
         swap1 swap2 swap3 swap4 swap5 swap6 swap7 swap8 swap9 swap10 swap11 swap12 swap13 swap14 swap15
@@ -909,8 +2065,9 @@ pub mod sample_programs {
         nop
         skiz
         call foo
+        tail_call foo
 
-        return recurse assert halt read_mem write_mem hash divine_sibling assert_vector
+        return recurse assert halt read_mem write_mem read_mem_inc write_mem_inc hash divine_sibling assert_vector
         add mul invert split eq lsb xxadd xxmul xinvert xbmul
 
         read_io write_io
@@ -922,6 +2079,10 @@ pub mod sample_programs {
             "push 42",
             "divine",
             "divine_quotient",
+            "divine_quotient_u64_hi",
+            "divine_quotient_u64_lo",
+            "divine_remainder_u64_hi",
+            "divine_remainder_u64_lo",
             "dup0",
             "dup1",
             "dup2",
@@ -956,12 +2117,15 @@ pub mod sample_programs {
             "nop",
             "skiz",
             "call foo",
+            "tail_call foo",
             "return",
             "recurse",
             "assert",
             "halt",
             "read_mem",
             "write_mem",
+            "read_mem_inc",
+            "write_mem_inc",
             "hash",
             "divine_sibling",
             "assert_vector",
@@ -986,6 +2150,8 @@ pub mod sample_programs {
 
 #[cfg(test)]
 mod instruction_tests {
+    use std::collections::HashSet;
+
     use itertools::Itertools;
     use num_traits::One;
     use num_traits::Zero;
@@ -1001,6 +2167,8 @@ mod instruction_tests {
     use super::parse;
     use super::sample_programs;
     use super::AnInstruction::{self, *};
+    use super::Instruction;
+    use super::LabelledInstruction;
 
     #[test]
     fn opcode_test() {
@@ -1112,6 +2280,144 @@ mod instruction_tests {
         );
     }
 
+    #[test]
+    fn local_labels_are_scoped_to_enclosing_global_label_test() {
+        let code = "
+            call foo
+            call bar
+            halt
+            foo: .loop: skiz call .loop return
+            bar: .loop: skiz call .loop return
+        ";
+        let program = Program::from_code(code).unwrap();
+        let call_targets = program
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Call(address) => Some(address.value()),
+                _ => None,
+            })
+            .dedup()
+            .collect_vec();
+        assert_eq!(vec![5, 9, 5, 9], call_targets);
+
+        let labels = parse(code)
+            .unwrap()
+            .into_iter()
+            .filter_map(|instruction| match instruction {
+                LabelledInstruction::Label(label) => Some(label),
+                LabelledInstruction::Instruction(_) => None,
+            })
+            .collect_vec();
+        assert_eq!(vec!["foo", "foo.loop", "bar", "bar.loop"], labels);
+    }
+
+    #[test]
+    fn duplicate_local_labels_mention_scope_test() {
+        let code = "
+            foo: .loop: push 1 .loop: halt
+        ";
+        let error = parse(code).unwrap_err();
+        assert!(
+            error.to_string().contains(".loop (in scope foo)"),
+            "Unexpected error: {error}"
+        );
+    }
+
+    #[test]
+    fn mangling_local_labels_is_idempotent_test() {
+        let code = ".start: push 1 foo: .loop: call .loop call .start halt";
+        let mangled = parse(code).unwrap();
+        assert_eq!(mangled, super::mangle_local_labels(&mangled));
+    }
+
+    #[test]
+    fn loops_are_lowered_to_calls_of_out_of_line_bodies_test() {
+        let code = "push 1 repeat 3 dup1 add end while push 0 end halt";
+        let expected = parse(
+            "push 1 push 3 call __repeat_0 pop call __while_1 halt \
+            __repeat_0: dup0 push 0 eq skiz return dup1 add push -1 add recurse \
+            __while_1: push 0 eq skiz return push 0 recurse",
+        )
+        .unwrap();
+        assert_eq!(expected, parse(code).unwrap());
+    }
+
+    #[test]
+    fn nested_loops_are_lowered_innermost_first_test() {
+        let code = "repeat 2 repeat 3 nop end end halt";
+        let expected = parse(
+            "push 2 call __repeat_1 pop halt \
+            __repeat_0: dup0 push 0 eq skiz return nop push -1 add recurse \
+            __repeat_1: dup0 push 0 eq skiz return push 3 call __repeat_0 pop push -1 add recurse",
+        )
+        .unwrap();
+        assert_eq!(expected, parse(code).unwrap());
+    }
+
+    #[test]
+    fn local_labels_in_loop_bodies_refer_to_enclosing_scope_test() {
+        let code = "foo: repeat 2 call .bar end return .bar: return";
+        let program = parse(code).unwrap();
+        let calls_bar = LabelledInstruction::Instruction(Call("foo.bar".to_string()));
+        assert!(program.contains(&calls_bar));
+        assert!(Program::from_code(code).is_ok());
+    }
+
+    #[test]
+    fn malformed_loops_are_rejected_test() {
+        assert!(parse("repeat 2 nop").is_err());
+        assert!(parse("while push 0 end end").is_err());
+        assert!(parse("repeat nop end").is_err());
+        assert!(parse("repeat -1 nop end").is_err());
+        assert!(parse("repeat 2 foo: nop end").is_err());
+    }
+
+    #[test]
+    fn loop_bodies_changing_stack_height_are_rejected_test() {
+        assert!(parse("repeat 2 push 1 end").is_err());
+        assert!(parse("while nop end").is_err());
+        assert!(parse("while push 1 push 1 end").is_err());
+        assert!(parse("repeat 2 call foo end halt foo: push 1 return").is_ok());
+    }
+
+    #[test]
+    fn if_else_is_lowered_to_skiz_and_call_test() {
+        let code = "push 1 if push 2 else push 3 end halt";
+        let expected = parse(
+            "push 1 push 1 swap1 skiz call __then_0 skiz call __else_0 halt \
+            __then_0: pop push 2 push 0 return \
+            __else_0: push 3 return",
+        )
+        .unwrap();
+        assert_eq!(expected, parse(code).unwrap());
+
+        let code = "push 1 if nop end halt";
+        let expected = parse("push 1 skiz call __then_0 halt __then_0: nop return").unwrap();
+        assert_eq!(expected, parse(code).unwrap());
+    }
+
+    #[test]
+    fn branches_of_if_must_agree_on_stack_effect_test() {
+        let error = parse("push 1 if push 2 else nop end halt").unwrap_err();
+        assert!(error.to_string().contains("+1 and +0"), "{error}");
+        assert!(parse("push 1 if push 2 end halt").is_err());
+        assert!(parse("push 1 if pop else push 0 mul pop end halt").is_ok());
+        assert!(parse("push 1 if push 2 else call foo end halt foo: return").is_ok());
+
+        let nested = "push 1 if push 0 if nop end else push 0 if push 2 else push 3 end end";
+        let error = parse(nested).unwrap_err();
+        assert!(error.to_string().contains("+0 and +1"), "{error}");
+    }
+
+    #[test]
+    fn malformed_conditionals_are_rejected_test() {
+        assert!(parse("push 1 else nop end").is_err());
+        assert!(parse("push 1 if nop else nop else nop end").is_err());
+        assert!(parse("push 1 repeat 2 else end").is_err());
+        assert!(parse("push 1 if nop").is_err());
+    }
+
     #[test]
     fn ib_registers_are_binary_test() {
         use Ord7::*;
@@ -1137,6 +2443,41 @@ mod instruction_tests {
         }
     }
 
+    #[test]
+    fn all_instructions_without_args_agrees_with_instruction_iter_test() {
+        let all_instructions = all_instructions_without_args();
+        assert_eq!(Instruction::iter().collect_vec(), all_instructions.to_vec());
+        for instruction in all_instructions {
+            assert_eq!(instruction, instruction.strip());
+        }
+    }
+
+    #[test]
+    fn all_labelled_instructions_with_args_cover_all_instructions_test() {
+        let covered_instructions = all_labelled_instructions_with_args()
+            .into_iter()
+            .filter_map(|labelled_instruction| match labelled_instruction {
+                LabelledInstruction::Instruction(instruction) => Some(instruction.strip().opcode()),
+                LabelledInstruction::Label(_) => None,
+            })
+            .collect::<HashSet<_>>();
+        for instruction in all_instructions_without_args() {
+            assert!(
+                covered_instructions.contains(&instruction.opcode()),
+                "Instruction {instruction} is not covered."
+            );
+        }
+    }
+
+    #[test]
+    fn instructions_shrinking_op_stack_are_2_mod_4_test() {
+        for instruction in all_instructions_without_args() {
+            let shrinks_op_stack = instruction.op_stack_size_influence() < 0;
+            let opcode_is_2_mod_4 = instruction.opcode() % 4 == 2;
+            assert_eq!(shrinks_op_stack, opcode_is_2_mod_4, "{instruction}");
+        }
+    }
+
     #[test]
     fn print_all_instructions_and_opcodes() {
         for instr in all_instructions_without_args() {
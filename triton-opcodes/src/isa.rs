@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+use crate::instruction::all_instructions_without_args;
+use crate::instruction::AnInstruction::*;
+use crate::instruction::Instruction;
+use crate::instruction::RamAccess;
+
+/// A machine-readable description of one instruction of Triton VM's instruction set
+/// architecture. Lets external assemblers, syntax highlighters, and documentation stay in sync
+/// with the instructions as they are defined in code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstructionDescription {
+    pub mnemonic: &'static str,
+    pub opcode: u32,
+
+    /// The kind of argument the instruction takes, if any.
+    pub argument: Option<ArgumentKind>,
+
+    /// The number of words the instruction occupies in program memory.
+    pub size: usize,
+
+    /// By how many elements the instruction changes the height of the operational stack.
+    pub op_stack_size_influence: i32,
+    pub ram_access: RamAccess,
+
+    /// All tables and input/output interfaces the instruction interacts with, apart from the
+    /// Processor Table, the Program Table, and the Instruction Table, which every instruction
+    /// interacts with.
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ArgumentKind {
+    /// Any element of the base field.
+    FieldElement,
+
+    /// The index of one of the operational stack's registers, `0` through `15`.
+    StackRegister,
+
+    /// An address in program memory, given as a label in assembly.
+    Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Interaction {
+    OpStackTable,
+    RamTable,
+    JumpStackTable,
+    HashTable,
+    StandardInput,
+    StandardOutput,
+    SecretInput,
+}
+
+impl InstructionDescription {
+    pub fn new(instruction: Instruction) -> Self {
+        let argument = match instruction {
+            Push(_) => Some(ArgumentKind::FieldElement),
+            Dup(_) | Swap(_) => Some(ArgumentKind::StackRegister),
+            Call(_) | TailCall(_) => Some(ArgumentKind::Address),
+            _ => None,
+        };
+
+        let mut interactions = vec![];
+        if instruction.op_stack_size_influence() != 0 {
+            interactions.push(Interaction::OpStackTable);
+        }
+        if instruction.ram_access() != RamAccess::None {
+            interactions.push(Interaction::RamTable);
+        }
+        match instruction {
+            Call(_) | TailCall(_) | Return | Recurse => {
+                interactions.push(Interaction::JumpStackTable)
+            }
+            Hash => interactions.push(Interaction::HashTable),
+            ReadIo => interactions.push(Interaction::StandardInput),
+            WriteIo => interactions.push(Interaction::StandardOutput),
+            Divine(_) | DivineSibling => interactions.push(Interaction::SecretInput),
+            _ => (),
+        }
+
+        Self {
+            mnemonic: instruction.name(),
+            opcode: instruction.opcode(),
+            argument,
+            size: instruction.size(),
+            op_stack_size_influence: instruction.op_stack_size_influence(),
+            ram_access: instruction.ram_access(),
+            interactions,
+        }
+    }
+}
+
+/// Describe every instruction of Triton VM's instruction set architecture.
+pub fn isa_description() -> Vec<InstructionDescription> {
+    all_instructions_without_args()
+        .map(InstructionDescription::new)
+        .to_vec()
+}
+
+/// Describe every instruction of Triton VM's instruction set architecture in JSON format.
+pub fn isa_description_json() -> String {
+    serde_json::to_string_pretty(&isa_description()).expect("ISA description must serialize")
+}
+
+#[cfg(test)]
+mod isa_tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn mnemonics_and_opcodes_are_unique_test() {
+        let description = isa_description();
+        let mnemonics: HashSet<_> = description.iter().map(|d| d.mnemonic).collect();
+        let opcodes: HashSet<_> = description.iter().map(|d| d.opcode).collect();
+        assert_eq!(description.len(), mnemonics.len());
+        assert_eq!(description.len(), opcodes.len());
+    }
+
+    #[test]
+    fn instructions_with_argument_are_double_word_instructions_test() {
+        for description in isa_description() {
+            let has_argument = description.argument.is_some();
+            assert_eq!(
+                has_argument,
+                description.size == 2,
+                "{}",
+                description.mnemonic
+            );
+        }
+    }
+
+    #[test]
+    fn isa_description_json_is_valid_test() {
+        let json = isa_description_json();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let push = &parsed[1];
+        assert_eq!("push", push["mnemonic"]);
+        assert_eq!(1, push["opcode"]);
+        assert_eq!("FieldElement", push["argument"]);
+        assert_eq!(2, push["size"]);
+        assert_eq!(1, push["op_stack_size_influence"]);
+        assert_eq!("None", push["ram_access"]);
+        assert_eq!("OpStackTable", push["interactions"][0]);
+    }
+}
@@ -0,0 +1,314 @@
+//! Decoding of programs encoded under any published version of the instruction set architecture.
+//!
+//! Opcodes are not stable across ISA versions. Every published version therefore keeps a frozen
+//! decode table, mapping its opcodes to mnemonics. Decoding goes through the mnemonic, so a program
+//! encoded under an old version can be decoded – and re-encoded – as long as all of its
+//! instructions still exist.
+//!
+//! Only programs are covered. A proof is bound to the AIR of the release that produced it, and old
+//! AIRs are not kept, so verifying an archived proof still requires that release. Decoding helps
+//! re-proving an archived program's claim under the current version.
+
+use anyhow::bail;
+use anyhow::Result;
+use strum_macros::EnumIter;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::instruction::all_instructions_without_args;
+use crate::instruction::AnInstruction::*;
+use crate::instruction::Instruction;
+use crate::ord_n::Ord16;
+use crate::program::Program;
+
+/// One entry of a decode table: an instruction's mnemonic, opcode, and size in words.
+pub type DecodeTableEntry = (&'static str, u32, usize);
+
+/// The decode table of version 1. Never change it; publish a new version instead.
+const DECODE_TABLE_V1: [DecodeTableEntry; 29] = [
+    ("pop", 2, 1),
+    ("push", 1, 2),
+    ("divine", 4, 1),
+    ("dup", 5, 2),
+    ("swap", 9, 2),
+    ("nop", 8, 1),
+    ("skiz", 6, 1),
+    ("call", 13, 2),
+    ("return", 12, 1),
+    ("recurse", 16, 1),
+    ("assert", 10, 1),
+    ("halt", 0, 1),
+    ("read_mem", 20, 1),
+    ("write_mem", 24, 1),
+    ("hash", 28, 1),
+    ("divine_sibling", 32, 1),
+    ("assert_vector", 36, 1),
+    ("add", 14, 1),
+    ("mul", 18, 1),
+    ("invert", 40, 1),
+    ("split", 44, 1),
+    ("eq", 22, 1),
+    ("lsb", 48, 1),
+    ("xxadd", 52, 1),
+    ("xxmul", 56, 1),
+    ("xinvert", 60, 1),
+    ("xbmul", 26, 1),
+    ("read_io", 64, 1),
+    ("write_io", 30, 1),
+];
+
+/// The decode table of version 2, which adds `read_mem_inc` and `write_mem_inc`. Never change it;
+/// publish a new version instead.
+const DECODE_TABLE_V2: [DecodeTableEntry; 31] = [
+    ("pop", 2, 1),
+    ("push", 1, 2),
+    ("divine", 4, 1),
+    ("dup", 5, 2),
+    ("swap", 9, 2),
+    ("nop", 8, 1),
+    ("skiz", 6, 1),
+    ("call", 13, 2),
+    ("return", 12, 1),
+    ("recurse", 16, 1),
+    ("assert", 10, 1),
+    ("halt", 0, 1),
+    ("read_mem", 20, 1),
+    ("write_mem", 24, 1),
+    ("read_mem_inc", 68, 1),
+    ("write_mem_inc", 72, 1),
+    ("hash", 28, 1),
+    ("divine_sibling", 32, 1),
+    ("assert_vector", 36, 1),
+    ("add", 14, 1),
+    ("mul", 18, 1),
+    ("invert", 40, 1),
+    ("split", 44, 1),
+    ("eq", 22, 1),
+    ("lsb", 48, 1),
+    ("xxadd", 52, 1),
+    ("xxmul", 56, 1),
+    ("xinvert", 60, 1),
+    ("xbmul", 26, 1),
+    ("read_io", 64, 1),
+    ("write_io", 30, 1),
+];
+
+/// The decode table of version 3, which adds `tail_call`. Never change it; publish a new version
+/// instead.
+const DECODE_TABLE_V3: [DecodeTableEntry; 32] = [
+    ("pop", 2, 1),
+    ("push", 1, 2),
+    ("divine", 4, 1),
+    ("dup", 5, 2),
+    ("swap", 9, 2),
+    ("nop", 8, 1),
+    ("skiz", 6, 1),
+    ("call", 13, 2),
+    ("tail_call", 17, 2),
+    ("return", 12, 1),
+    ("recurse", 16, 1),
+    ("assert", 10, 1),
+    ("halt", 0, 1),
+    ("read_mem", 20, 1),
+    ("write_mem", 24, 1),
+    ("read_mem_inc", 68, 1),
+    ("write_mem_inc", 72, 1),
+    ("hash", 28, 1),
+    ("divine_sibling", 32, 1),
+    ("assert_vector", 36, 1),
+    ("add", 14, 1),
+    ("mul", 18, 1),
+    ("invert", 40, 1),
+    ("split", 44, 1),
+    ("eq", 22, 1),
+    ("lsb", 48, 1),
+    ("xxadd", 52, 1),
+    ("xxmul", 56, 1),
+    ("xinvert", 60, 1),
+    ("xbmul", 26, 1),
+    ("read_io", 64, 1),
+    ("write_io", 30, 1),
+];
+
+/// A published version of the instruction set architecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum IsaVersion {
+    V1,
+    V2,
+    V3,
+}
+
+impl IsaVersion {
+    /// The version the [`Instruction`]s of this crate implement.
+    pub const CURRENT: Self = IsaVersion::V3;
+
+    pub fn decode_table(&self) -> &'static [DecodeTableEntry] {
+        match self {
+            IsaVersion::V1 => &DECODE_TABLE_V1,
+            IsaVersion::V2 => &DECODE_TABLE_V2,
+            IsaVersion::V3 => &DECODE_TABLE_V3,
+        }
+    }
+
+    /// The mnemonic and size of the instruction with the given opcode in this version.
+    fn lookup(&self, opcode: u32) -> Result<(&'static str, usize)> {
+        match self
+            .decode_table()
+            .iter()
+            .find(|(_, other_opcode, _)| *other_opcode == opcode)
+        {
+            Some(&(mnemonic, _, size)) => Ok((mnemonic, size)),
+            None => bail!("No instruction with opcode {opcode} exists in ISA {self:?}."),
+        }
+    }
+
+    /// Decode a program encoded under this version into the instructions of the current version.
+    /// The `entry_point` is the address execution starts at, for example as recorded in a claim.
+    /// Instruction sizes are the same in all versions, so addresses are preserved.
+    pub fn decode_program(&self, words: &[BFieldElement], entry_point: usize) -> Result<Program> {
+        let mut instructions = vec![];
+        let mut is_entry_point_valid = entry_point == 0;
+        let mut address = 0;
+        while address < words.len() {
+            is_entry_point_valid |= address == entry_point;
+            let opcode = words[address].value();
+            let Ok(opcode) = u32::try_from(opcode) else {
+                bail!("Word {opcode} at address {address} is not an opcode.");
+            };
+            let (mnemonic, size) = self.lookup(opcode)?;
+            let argument = match size {
+                1 => None,
+                _ => match words.get(address + 1) {
+                    Some(&argument) => Some(argument),
+                    None => bail!("Argument of `{mnemonic}` at address {address} is missing."),
+                },
+            };
+            let instruction = current_instruction(*self, mnemonic, argument)?;
+            instructions.extend(vec![instruction; instruction.size()]);
+            address += size;
+        }
+
+        if !is_entry_point_valid {
+            bail!("Entry point {entry_point} is not the address of an instruction.");
+        }
+
+        Ok(Program {
+            instructions,
+            entry_point,
+        })
+    }
+
+    /// Re-encode a program encoded under this version for the current version. The program's
+    /// entry point stays valid.
+    pub fn upgrade_program(&self, words: &[BFieldElement]) -> Result<Vec<BFieldElement>> {
+        Ok(self.decode_program(words, 0)?.to_bwords())
+    }
+}
+
+/// The instruction of the current version with the given mnemonic and argument.
+fn current_instruction(
+    version: IsaVersion,
+    mnemonic: &str,
+    argument: Option<BFieldElement>,
+) -> Result<Instruction> {
+    let Some(instruction) = all_instructions_without_args()
+        .into_iter()
+        .find(|instruction| instruction.name() == mnemonic)
+    else {
+        bail!("Instruction `{mnemonic}` of ISA {version:?} does not exist anymore.");
+    };
+    let stack_register = |argument: BFieldElement| match Ord16::try_from(argument.value() as u32) {
+        Ok(register) => Ok(register),
+        Err(err) => bail!("Invalid argument of `{mnemonic}`: {err}"),
+    };
+
+    let instruction = match (instruction, argument) {
+        (_, None) if instruction.size() == 1 => instruction,
+        (Push(_), Some(argument)) => Push(argument),
+        (Call(_), Some(argument)) => Call(argument),
+        (TailCall(_), Some(argument)) => TailCall(argument),
+        (Dup(_), Some(argument)) => Dup(stack_register(argument)?),
+        (Swap(_), Some(argument)) => Swap(stack_register(argument)?),
+        _ => bail!("Size of `{mnemonic}` differs between ISA {version:?} and the current ISA."),
+    };
+    Ok(instruction)
+}
+
+#[cfg(test)]
+mod isa_version_tests {
+    use itertools::Itertools;
+    use strum::IntoEnumIterator;
+
+    use crate::isa::isa_description;
+
+    use super::*;
+
+    #[test]
+    fn current_decode_table_matches_instructions_test() {
+        let current_table = isa_description()
+            .iter()
+            .map(|description| (description.mnemonic, description.opcode, description.size))
+            .collect_vec();
+        assert_eq!(
+            IsaVersion::CURRENT.decode_table(),
+            current_table,
+            "The instruction set changed. Publish a new ISA version with a new decode table."
+        );
+    }
+
+    #[test]
+    fn decode_tables_have_unique_opcodes_test() {
+        for version in IsaVersion::iter() {
+            let table = version.decode_table();
+            assert!(table.iter().map(|(_, opcode, _)| opcode).all_unique());
+            assert!(table.iter().map(|(mnemonic, _, _)| mnemonic).all_unique());
+        }
+    }
+
+    #[test]
+    fn decoding_current_encoding_is_identity_test() {
+        let code = "push 7 dup3 swap2 call foo halt foo: divine hash pop return";
+        let program = Program::from_code(code).unwrap();
+        let words = program.to_bwords();
+        let decoded = IsaVersion::CURRENT.decode_program(&words, 0).unwrap();
+        assert_eq!(program, decoded);
+        assert_eq!(words, IsaVersion::CURRENT.upgrade_program(&words).unwrap());
+    }
+
+    #[test]
+    fn programs_of_version_1_decode_unchanged_test() {
+        let program = Program::from_code("push 5 read_mem pop write_mem halt").unwrap();
+        let words = program.to_bwords();
+        assert_eq!(program, IsaVersion::V1.decode_program(&words, 0).unwrap());
+
+        let read_mem_inc = [Instruction::ReadMemInc.opcode_b()];
+        assert!(IsaVersion::V1.decode_program(&read_mem_inc, 0).is_err());
+        assert!(IsaVersion::V2.decode_program(&read_mem_inc, 0).is_ok());
+
+        let tail_call = [Instruction::TailCall(0_u64.into()).opcode_b(), 0_u64.into()];
+        assert!(IsaVersion::V2.decode_program(&tail_call, 0).is_err());
+        assert!(IsaVersion::V3.decode_program(&tail_call, 0).is_ok());
+    }
+
+    #[test]
+    fn malformed_encodings_are_rejected_test() {
+        let version = IsaVersion::CURRENT;
+        let words = |words: &[u64]| words.iter().map(|&w| BFieldElement::new(w)).collect_vec();
+        assert!(version.decode_program(&words(&[3]), 0).is_err());
+        assert!(version.decode_program(&words(&[1]), 0).is_err());
+        assert!(version.decode_program(&words(&[5, 16]), 0).is_err());
+        assert!(version.decode_program(&words(&[1 << 40]), 0).is_err());
+    }
+
+    #[test]
+    fn entry_point_is_carried_through_test() {
+        let code = "push 7 halt main: push 8 write_io halt";
+        let program = Program::from_code_with_entry_point(code, "main").unwrap();
+        let words = program.to_bwords();
+        let decoded = IsaVersion::V1.decode_program(&words, 3).unwrap();
+        assert_eq!(program, decoded);
+        assert_eq!(3, decoded.entry_point);
+
+        assert!(IsaVersion::V1.decode_program(&words, 1).is_err());
+        assert!(IsaVersion::V1.decode_program(&words, words.len()).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! A lexer for Triton assembly that keeps track of where each token is located and what role it
+//! plays, for example for syntax highlighting.
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+/// The role a token plays in Triton assembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum TokenKind {
+    /// An instruction or pseudo-instruction, like `push`, `dup3`, or `lt`.
+    Instruction,
+
+    /// The argument of instruction `push`.
+    Argument,
+
+    /// The definition of a label, like `foo:`.
+    LabelDefinition,
+
+    /// The use of a label as the argument of instruction `call`.
+    LabelUse,
+
+    /// A comment, starting with `//` and extending to the end of the line.
+    Comment,
+}
+
+/// A token of Triton assembly together with its location in the source code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Token<'src> {
+    pub kind: TokenKind,
+    pub text: &'src str,
+
+    /// The byte range of the token in the source code.
+    pub span: Range<usize>,
+}
+
+/// Split the source code into tokens, keeping track of where each token is located. Tokens are
+/// separated by whitespace. Comments are tokens, too.
+pub fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens: Vec<Token> = vec![];
+    let mut position = 0;
+
+    while let Some(offset) = source[position..].find(|c: char| !c.is_whitespace()) {
+        let start = position + offset;
+        let rest = &source[start..];
+        let (kind, len) = if rest.starts_with("//") {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            (TokenKind::Comment, len)
+        } else {
+            let end_of_word = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let len = rest.find("//").unwrap_or(end_of_word).min(end_of_word);
+            let text = &rest[..len];
+            let previous_instruction = tokens
+                .iter()
+                .rev()
+                .find(|token| token.kind != TokenKind::Comment)
+                .filter(|token| token.kind == TokenKind::Instruction)
+                .map(|token| token.text);
+            let kind = match previous_instruction {
+                _ if text.ends_with(':') => TokenKind::LabelDefinition,
+                Some("push" | "repeat") => TokenKind::Argument,
+                Some("call" | "tail_call") => TokenKind::LabelUse,
+                _ => TokenKind::Instruction,
+            };
+            (kind, len)
+        };
+
+        let end = start + len;
+        tokens.push(Token {
+            kind,
+            text: &source[start..end],
+            span: start..end,
+        });
+        position = end;
+    }
+
+    tokens
+}
+
+/// The zero-based line and column of the given byte offset in the source code. The column counts
+/// characters, not bytes.
+pub fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let preceding = &source[..offset];
+    let line = preceding.matches('\n').count();
+    let start_of_line = preceding.rfind('\n').map_or(0, |newline| newline + 1);
+    let column = preceding[start_of_line..].chars().count();
+    (line, column)
+}
+
+/// The byte offset of the given zero-based line and column in the source code. Positions past the
+/// end of a line or the source code are clamped.
+pub fn offset(source: &str, line: usize, column: usize) -> usize {
+    let start_of_line: usize = source
+        .split_inclusive('\n')
+        .take(line)
+        .map(|line| line.len())
+        .sum();
+    let line_content = source[start_of_line..].split('\n').next().unwrap_or("");
+    let column_offset = line_content
+        .char_indices()
+        .nth(column)
+        .map_or(line_content.len(), |(offset, _)| offset);
+    start_of_line + column_offset
+}
+
+#[cfg(test)]
+mod lexer_tests {
+    use super::TokenKind::*;
+    use super::*;
+
+    #[test]
+    fn tokenize_test() {
+        let source = "foo: push 1 // one\n  call bar//baz\nbar: halt";
+        let tokens = tokenize(source);
+        let kinds_and_texts = tokens
+            .iter()
+            .map(|token| (token.kind, token.text))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (LabelDefinition, "foo:"),
+            (Instruction, "push"),
+            (Argument, "1"),
+            (Comment, "// one"),
+            (Instruction, "call"),
+            (LabelUse, "bar"),
+            (Comment, "//baz"),
+            (LabelDefinition, "bar:"),
+            (Instruction, "halt"),
+        ];
+        assert_eq!(expected, kinds_and_texts);
+
+        for token in tokens {
+            assert_eq!(token.text, &source[token.span]);
+        }
+    }
+
+    #[test]
+    fn tokens_cover_all_non_whitespace_characters_test() {
+        let source = "foo:push 1//x\n\tcall  foo ünïcödé // €\n";
+        let mut highlighted = source.to_string().into_bytes();
+        for token in tokenize(source) {
+            highlighted[token.span].fill(b'#');
+        }
+        let highlighted = String::from_utf8(highlighted).unwrap();
+        assert!(highlighted.chars().all(|c| c == '#' || c.is_whitespace()));
+    }
+
+    #[test]
+    fn serialize_tokens_test() {
+        let tokens = tokenize("call foo");
+        let json = serde_json::to_string(&tokens).unwrap();
+        let expected = r#"[{"kind":"Instruction","text":"call","span":{"start":0,"end":4}},"#;
+        assert!(json.starts_with(expected), "{json}");
+    }
+
+    #[test]
+    fn line_and_column_round_trip_test() {
+        let source = "push 1\n// ünïcödé\n  halt";
+        for offset in source.char_indices().map(|(offset, _)| offset) {
+            let (line, column) = line_and_column(source, offset);
+            assert_eq!(offset, super::offset(source, line, column));
+        }
+        assert_eq!(
+            (2, 2),
+            line_and_column(source, source.find("halt").unwrap())
+        );
+    }
+}
@@ -1,3 +1,11 @@
+pub mod alias;
+pub mod fmt;
 pub mod instruction;
+pub mod isa;
+pub mod isa_version;
+pub mod lexer;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 pub mod ord_n;
 pub mod program;
+pub mod pseudo_instruction;
@@ -0,0 +1,385 @@
+//! A minimal language server for Triton assembly, providing diagnostics, go-to-definition for
+//! labels, and hover information for instructions.
+//!
+//! Only available with feature `lsp`. Run the server with binary `triton-lsp`, which communicates
+//! over standard input and output.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Range;
+
+use anyhow::Result;
+use itertools::Itertools;
+use lsp_server::Connection;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::Response;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidOpenTextDocument;
+use lsp_types::notification::Notification as _;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::GotoDefinition;
+use lsp_types::request::HoverRequest;
+use lsp_types::request::Request as _;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::GotoDefinitionParams;
+use lsp_types::GotoDefinitionResponse;
+use lsp_types::Hover;
+use lsp_types::HoverContents;
+use lsp_types::HoverParams;
+use lsp_types::HoverProviderCapability;
+use lsp_types::Location;
+use lsp_types::MarkedString;
+use lsp_types::OneOf;
+use lsp_types::Position;
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::ServerCapabilities;
+use lsp_types::TextDocumentPositionParams;
+use lsp_types::TextDocumentSyncCapability;
+use lsp_types::TextDocumentSyncKind;
+use lsp_types::Url;
+
+use crate::instruction::parse_token;
+use crate::instruction::LabelledInstruction;
+use crate::lexer::line_and_column;
+use crate::lexer::offset;
+use crate::lexer::tokenize;
+use crate::lexer::Token;
+use crate::lexer::TokenKind;
+
+/// A problem in the source code, located by its byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// All parse errors, duplicate labels, and unknown labels in the given source code.
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let tokens = code_tokens(source);
+    let mut diagnostics = vec![];
+
+    for (index, token) in tokens.iter().enumerate() {
+        let next_token = tokens.get(index + 1);
+        if token.kind != TokenKind::Instruction {
+            continue;
+        }
+        let argument = match token.text {
+            "push" | "call" | "tail_call" | "repeat" => match next_token {
+                Some(next) if matches!(next.kind, TokenKind::Argument | TokenKind::LabelUse) => {
+                    next.text
+                }
+                _ => {
+                    diagnostics.push(Diagnostic {
+                        span: token.span.clone(),
+                        message: format!("Instruction `{}` is missing its argument.", token.text),
+                    });
+                    continue;
+                }
+            },
+            _ => "",
+        };
+        if let Err(err) = parse_token_or_keyword(token.text, argument) {
+            let span = match argument {
+                "" => token.span.clone(),
+                _ => token.span.start..next_token.unwrap().span.end,
+            };
+            let message = format!("Invalid instruction: {err}");
+            diagnostics.push(Diagnostic { span, message });
+        }
+    }
+
+    let definitions = scoped_labels(&tokens, TokenKind::LabelDefinition);
+    let mut seen_labels = HashSet::new();
+    for (label, token) in definitions.iter() {
+        if !seen_labels.insert(label) {
+            let message = format!("Duplicate label: {label}");
+            let span = token.span.clone();
+            diagnostics.push(Diagnostic { span, message });
+        }
+    }
+    for (label, token) in scoped_labels(&tokens, TokenKind::LabelUse) {
+        if !seen_labels.contains(&label) {
+            let message = format!("Unknown label: {label}");
+            let span = token.span.clone();
+            diagnostics.push(Diagnostic { span, message });
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+/// Check a single instruction, or one of the keywords of structured control flow. Whether control
+/// structures are nested correctly is not checked.
+fn parse_token_or_keyword(token: &str, argument: &str) -> Result<()> {
+    match token {
+        "repeat" => {
+            argument.parse::<u32>()?;
+        }
+        "while" | "if" | "else" | "end" => (),
+        _ => {
+            parse_token(token, &mut argument.split_whitespace())?;
+        }
+    }
+    Ok(())
+}
+
+/// The location of the definition of the label at the given byte offset, if any.
+pub fn definition(source: &str, offset: usize) -> Option<Range<usize>> {
+    let tokens = code_tokens(source);
+    let uses = scoped_labels(&tokens, TokenKind::LabelUse);
+    let definitions = scoped_labels(&tokens, TokenKind::LabelDefinition);
+    let all_labels = uses.iter().chain(definitions.iter());
+    let (label, _) = all_labels
+        .into_iter()
+        .find(|(_, token)| token.span.contains(&offset))?;
+
+    definitions
+        .iter()
+        .find(|(defined_label, _)| defined_label == label)
+        .map(|(_, token)| token.span.clone())
+}
+
+/// A description of the instruction at the given byte offset, if any.
+pub fn hover(source: &str, offset: usize) -> Option<String> {
+    let tokens = code_tokens(source);
+    let token = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Instruction && token.span.contains(&offset))?;
+    let argument = match token.text {
+        "push" => "0",
+        "call" | "tail_call" => "label",
+        _ => "",
+    };
+    let instructions = parse_token(token.text, &mut argument.split_whitespace()).ok()?;
+    let instructions = instructions
+        .into_iter()
+        .filter_map(|instruction| match instruction {
+            LabelledInstruction::Instruction(instruction) => Some(instruction),
+            LabelledInstruction::Label(_) => None,
+        })
+        .collect_vec();
+
+    let description = match instructions.as_slice() {
+        [instruction] => format!(
+            "`{}` – opcode {}, size {}, op stack size influence {:+}",
+            instruction.name(),
+            instruction.opcode(),
+            instruction.size(),
+            instruction.op_stack_size_influence(),
+        ),
+        _ => format!(
+            "`{}` – pseudo-instruction for `{}`, op stack size influence {:+}",
+            token.text,
+            instructions.iter().join(" "),
+            instructions
+                .iter()
+                .map(|instruction| instruction.op_stack_size_influence())
+                .sum::<i32>(),
+        ),
+    };
+    Some(description)
+}
+
+/// All tokens apart from comments.
+fn code_tokens(source: &str) -> Vec<Token<'_>> {
+    tokenize(source)
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect()
+}
+
+/// All labels of the given kind, with local labels mangled, together with their tokens.
+fn scoped_labels<'src, 'tok>(
+    tokens: &'tok [Token<'src>],
+    kind: TokenKind,
+) -> Vec<(String, &'tok Token<'src>)> {
+    let mut scope = "";
+    let mut labels = vec![];
+    for token in tokens {
+        let label = token.text.trim_end_matches(':');
+        if token.kind == TokenKind::LabelDefinition && !label.starts_with('.') {
+            scope = label;
+        }
+        if token.kind == kind {
+            let label = match label.starts_with('.') {
+                true => format!("{scope}{label}"),
+                false => label.to_string(),
+            };
+            labels.push((label, token));
+        }
+    }
+    labels
+}
+
+/// Run the language server, communicating over standard input and output, until the client
+/// requests shutdown.
+pub fn run_stdio_server() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut documents = HashMap::new();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = handle_request(&documents, request)?;
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                let Some((uri, text)) = changed_document(notification)? else {
+                    continue;
+                };
+                let diagnostics = lsp_diagnostics(&text);
+                let params = PublishDiagnosticsParams::new(uri.clone(), diagnostics, None);
+                let notification = Notification::new(PublishDiagnostics::METHOD.into(), params);
+                connection
+                    .sender
+                    .send(Message::Notification(notification))?;
+                documents.insert(uri, text);
+            }
+            Message::Response(_) => (),
+        }
+    }
+
+    // the writer thread only stops once all senders are dropped
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn changed_document(notification: Notification) -> Result<Option<(Url, String)>> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: <DidOpenTextDocument as lsp_types::notification::Notification>::Params =
+                serde_json::from_value(notification.params)?;
+            Ok(Some((params.text_document.uri, params.text_document.text)))
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: <DidChangeTextDocument as lsp_types::notification::Notification>::Params =
+                serde_json::from_value(notification.params)?;
+            let text = params.content_changes.into_iter().last();
+            Ok(text.map(|change| (params.text_document.uri, change.text)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn handle_request(documents: &HashMap<Url, String>, request: Request) -> Result<Response> {
+    let id = request.id.clone();
+    let document_and_offset = |params: &TextDocumentPositionParams| {
+        let source = documents.get(&params.text_document.uri)?;
+        let position = params.position;
+        let offset = offset(source, position.line as usize, position.character as usize);
+        Some((source, offset))
+    };
+
+    let response = match request.method.as_str() {
+        GotoDefinition::METHOD => {
+            let params: GotoDefinitionParams = serde_json::from_value(request.params)?;
+            let params = params.text_document_position_params;
+            let location = document_and_offset(&params).and_then(|(source, offset)| {
+                let span = definition(source, offset)?;
+                let uri = params.text_document.uri.clone();
+                Some(Location::new(uri, lsp_range(source, span)))
+            });
+            Response::new_ok(id, location.map(GotoDefinitionResponse::Scalar))
+        }
+        HoverRequest::METHOD => {
+            let params: HoverParams = serde_json::from_value(request.params)?;
+            let params = params.text_document_position_params;
+            let hover = document_and_offset(&params).and_then(|(source, offset)| {
+                let description = hover(source, offset)?;
+                Some(Hover {
+                    contents: HoverContents::Scalar(MarkedString::String(description)),
+                    range: None,
+                })
+            });
+            Response::new_ok(id, hover)
+        }
+        _ => Response::new_ok(id, serde_json::Value::Null),
+    };
+    Ok(response)
+}
+
+fn lsp_diagnostics(source: &str) -> Vec<lsp_types::Diagnostic> {
+    diagnostics(source)
+        .into_iter()
+        .map(|diagnostic| lsp_types::Diagnostic {
+            range: lsp_range(source, diagnostic.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: diagnostic.message,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn lsp_range(source: &str, span: Range<usize>) -> lsp_types::Range {
+    let lsp_position = |offset| {
+        let (line, column) = line_and_column(source, offset);
+        Position::new(line as u32, column as u32)
+    };
+    lsp_types::Range::new(lsp_position(span.start), lsp_position(span.end))
+}
+
+#[cfg(test)]
+mod lsp_tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_test() {
+        let source = "foo: push 1 call bar foo: fizzle .loop: call .loop push";
+        let messages = diagnostics(source)
+            .into_iter()
+            .map(|diagnostic| (&source[diagnostic.span], diagnostic.message))
+            .collect_vec();
+        let expected = [
+            ("bar", "Unknown label: bar"),
+            ("foo:", "Duplicate label: foo"),
+            ("fizzle", "Invalid instruction: UnknownInstruction(fizzle)"),
+            ("push", "Instruction `push` is missing its argument."),
+        ];
+        let expected = expected.map(|(text, message)| (text, message.to_string()));
+        assert_eq!(expected.to_vec(), messages);
+    }
+
+    #[test]
+    fn valid_code_has_no_diagnostics_test() {
+        let source = "foo: push 1 call bar // call bar\n .loop: call .loop bar: lt return";
+        assert!(diagnostics(source).is_empty());
+        assert!(diagnostics("repeat 3 while push 0 end end halt").is_empty());
+        assert!(diagnostics("push 1 if push 2 else push 3 end halt").is_empty());
+    }
+
+    #[test]
+    fn go_to_definition_respects_local_label_scope_test() {
+        let source = "foo: .loop: call .loop bar: .loop: call .loop";
+        let second_use = source.rfind(".loop").unwrap();
+        let second_definition = source.rfind(".loop:").unwrap();
+        let definition_span = definition(source, second_use + 1).unwrap();
+        assert_eq!(second_definition, definition_span.start);
+        assert_eq!(".loop:", &source[definition_span]);
+        assert_eq!(None, definition(source, source.find("call").unwrap()));
+    }
+
+    #[test]
+    fn hover_shows_opcode_and_stack_effect_test() {
+        let source = "push 1 lt";
+        let push_hover = hover(source, 2).unwrap();
+        assert!(push_hover.contains("opcode 1"), "{push_hover}");
+        assert!(push_hover.contains("+1"), "{push_hover}");
+        let lt_hover = hover(source, source.find("lt").unwrap()).unwrap();
+        assert!(lt_hover.contains("pseudo-instruction"), "{lt_hover}");
+        assert_eq!(None, hover(source, source.find('1').unwrap()));
+    }
+}
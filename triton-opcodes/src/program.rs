@@ -1,14 +1,25 @@
+use anyhow::anyhow;
+use anyhow::bail;
 use anyhow::Result;
+use std::collections::BTreeSet;
 use std::fmt::Display;
+use std::fmt::Write;
 use std::io::Cursor;
 
 use twenty_first::shared_math::b_field_element::BFieldElement;
 
-use crate::instruction::{convert_labels, parse, Instruction, LabelledInstruction};
+use crate::instruction::AnInstruction::*;
+use crate::instruction::{
+    convert_labels, label_addresses, parse, Instruction, LabelledInstruction,
+};
+use crate::isa_version::IsaVersion;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Program {
     pub instructions: Vec<Instruction>,
+
+    /// The address of the first instruction to execute. Usually 0.
+    pub entry_point: usize,
 }
 
 impl Display for Program {
@@ -66,7 +77,43 @@ impl Program {
             .flat_map(|instr| vec![*instr; instr.size()])
             .collect::<Vec<_>>();
 
-        Program { instructions }
+        Program {
+            instructions,
+            entry_point: 0,
+        }
+    }
+
+    /// Create a `Program` from instructions with resolved addresses, one per instruction, like the
+    /// ones emitted by [`Program::to_rust_const`].
+    pub fn from_instructions(instructions: &[Instruction], entry_point: usize) -> Self {
+        let instructions = instructions
+            .iter()
+            .flat_map(|instr| vec![*instr; instr.size()])
+            .collect();
+
+        Program {
+            instructions,
+            entry_point,
+        }
+    }
+
+    /// Create a `Program` from a slice of `Instruction` that starts execution at the given label
+    /// instead of at address 0. This way, one program can expose multiple entry functions.
+    pub fn with_entry_point(input: &[LabelledInstruction], entry_label: &str) -> Result<Self> {
+        let entry_point = *label_addresses(input)
+            .get(entry_label)
+            .ok_or_else(|| anyhow!("Entry label not found: {entry_label}"))?;
+        let program = Program::new(input);
+        if entry_point >= program.len() {
+            return Err(anyhow!(
+                "Entry label {entry_label} does not point to an instruction."
+            ));
+        }
+
+        Ok(Program {
+            entry_point,
+            ..program
+        })
     }
 
     /// Create a `Program` by parsing source code.
@@ -75,6 +122,12 @@ impl Program {
         Ok(Program::new(&instructions))
     }
 
+    /// Create a `Program` by parsing source code. Execution starts at the given label.
+    pub fn from_code_with_entry_point(code: &str, entry_label: &str) -> Result<Self> {
+        let instructions = parse(code)?;
+        Program::with_entry_point(&instructions, entry_label)
+    }
+
     /// Convert a `Program` to a `Vec<BFieldElement>`.
     ///
     /// Every single-word instruction is converted to a single word.
@@ -94,6 +147,73 @@ impl Program {
             .collect()
     }
 
+    /// Rust source code defining constants `{name}` and `{name}_ENTRY_POINT`, from which
+    /// [`Program::from_instructions`] restores the program. This way, applications can embed a
+    /// program at compile time instead of parsing it at runtime. The name must be in
+    /// `SCREAMING_SNAKE_CASE`. The emitted code depends on crates `triton_opcodes` and
+    /// `twenty_first`.
+    pub fn to_rust_const(&self, name: &str) -> Result<String> {
+        let is_valid_char = |c: char| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_';
+        let starts_with_digit = name.starts_with(|c: char| c.is_ascii_digit());
+        if name.is_empty() || starts_with_digit || !name.chars().all(is_valid_char) {
+            bail!("`{name}` is not a valid name for a Rust constant.");
+        }
+
+        let instructions = self.clone().into_iter().collect::<Vec<_>>();
+        let uses_field_elements = instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Push(_) | Call(_) | TailCall(_)));
+        let uses_stack_registers = instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Dup(_) | Swap(_)));
+        let uses_divination_hints = instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Divine(Some(_))));
+
+        let mut code = String::new();
+        writeln!(
+            code,
+            "/// Restore with `Program::from_instructions({name}, {name}_ENTRY_POINT)`."
+        )?;
+        writeln!(
+            code,
+            "pub const {name}_ENTRY_POINT: usize = {};",
+            self.entry_point
+        )?;
+        writeln!(code)?;
+        writeln!(code, "/// {} instructions.", instructions.len())?;
+        writeln!(
+            code,
+            "pub const {name}: &[triton_opcodes::instruction::Instruction] = {{"
+        )?;
+        writeln!(
+            code,
+            "    use triton_opcodes::instruction::AnInstruction::*;"
+        )?;
+        if uses_divination_hints {
+            writeln!(
+                code,
+                "    use triton_opcodes::instruction::DivinationHint::*;"
+            )?;
+        }
+        if uses_stack_registers {
+            writeln!(code, "    use triton_opcodes::ord_n::Ord16::*;")?;
+        }
+        if uses_field_elements {
+            writeln!(
+                code,
+                "    use twenty_first::shared_math::b_field_element::BFieldElement;"
+            )?;
+        }
+        writeln!(code, "    &[")?;
+        for instruction in instructions {
+            writeln!(code, "        {},", rust_expression(instruction))?;
+        }
+        writeln!(code, "    ]")?;
+        writeln!(code, "}};")?;
+        Ok(code)
+    }
+
     pub fn len(&self) -> usize {
         self.instructions.len()
     }
@@ -102,3 +222,177 @@ impl Program {
         self.instructions.is_empty()
     }
 }
+
+/// Reconstruct Triton assembly from a program encoded as field elements, for example as returned
+/// by [`Program::to_bwords`]. Labels are not part of the encoding, so the target of every `call`
+/// and `tail_call` gets a synthetic label `address_{n}`, where `n` is the target's address.
+/// Displaying the returned instructions gives Triton assembly that parses to the same program.
+pub fn disassemble(words: &[BFieldElement]) -> Result<Vec<LabelledInstruction>> {
+    let instructions = IsaVersion::CURRENT
+        .decode_program(words, 0)?
+        .into_iter()
+        .collect::<Vec<_>>();
+    let addresses = instructions
+        .iter()
+        .scan(0, |address, instruction| {
+            let instruction_address = *address;
+            *address += instruction.size();
+            Some(instruction_address)
+        })
+        .collect::<Vec<_>>();
+
+    let mut targets = BTreeSet::new();
+    for instruction in instructions.iter() {
+        if let Call(target) | TailCall(target) = instruction {
+            let target = target.value() as usize;
+            if target != words.len() && addresses.binary_search(&target).is_err() {
+                bail!("Target of `{instruction}` is not the address of an instruction.");
+            }
+            targets.insert(target);
+        }
+    }
+
+    let label = |address: usize| format!("address_{address}");
+    let mut labelled_instructions = vec![];
+    for (address, instruction) in addresses.into_iter().zip(instructions) {
+        if targets.contains(&address) {
+            labelled_instructions.push(LabelledInstruction::Label(label(address)));
+        }
+        let instruction = instruction.map_call_address(|target| label(target.value() as usize));
+        labelled_instructions.push(LabelledInstruction::Instruction(instruction));
+    }
+    if targets.contains(&words.len()) {
+        labelled_instructions.push(LabelledInstruction::Label(label(words.len())));
+    }
+    Ok(labelled_instructions)
+}
+
+/// The Rust expression constructing the given instruction, assuming all variants of
+/// [`Instruction`], [`Ord16`](crate::ord_n::Ord16), and
+/// [`DivinationHint`](crate::instruction::DivinationHint) are in scope.
+fn rust_expression(instruction: Instruction) -> String {
+    match instruction {
+        Push(arg) => format!("Push(BFieldElement::new({}))", arg.value()),
+        Call(address) => format!("Call(BFieldElement::new({}))", address.value()),
+        TailCall(address) => format!("TailCall(BFieldElement::new({}))", address.value()),
+        Dup(register) => format!("Dup(ST{register})"),
+        Swap(register) => format!("Swap(ST{register})"),
+        Divine(None) => "Divine(None)".to_string(),
+        Divine(Some(hint)) => format!("Divine(Some({hint:?}))"),
+        _ => format!("{instruction:?}"),
+    }
+}
+
+#[cfg(test)]
+mod program_tests {
+    use itertools::Itertools;
+    use twenty_first::shared_math::b_field_element::BFieldElement;
+
+    use super::*;
+
+    /// Restore with `Program::from_instructions(DOUBLE, DOUBLE_ENTRY_POINT)`.
+    pub const DOUBLE_ENTRY_POINT: usize = 0;
+
+    /// 9 instructions.
+    pub const DOUBLE: &[crate::instruction::Instruction] = {
+        use crate::instruction::AnInstruction::*;
+        use crate::instruction::DivinationHint::*;
+        use crate::ord_n::Ord16::*;
+        use twenty_first::shared_math::b_field_element::BFieldElement;
+        &[
+            Push(BFieldElement::new(18446744069414584320)),
+            Divine(Some(Quotient)),
+            Call(BFieldElement::new(10)),
+            Swap(ST1),
+            Dup(ST1),
+            Halt,
+            Dup(ST0),
+            Add,
+            Return,
+        ]
+    };
+
+    #[test]
+    fn rust_const_test() {
+        let code = "push -1 divine_quotient call double swap1 dup1 halt double: dup0 add return";
+        let program = Program::from_code(code).unwrap();
+        let rust_const = program.to_rust_const("DOUBLE").unwrap();
+        println!("{rust_const}");
+
+        let expected = "\
+/// Restore with `Program::from_instructions(DOUBLE, DOUBLE_ENTRY_POINT)`.
+pub const DOUBLE_ENTRY_POINT: usize = 0;
+
+/// 9 instructions.
+pub const DOUBLE: &[triton_opcodes::instruction::Instruction] = {
+    use triton_opcodes::instruction::AnInstruction::*;
+    use triton_opcodes::instruction::DivinationHint::*;
+    use triton_opcodes::ord_n::Ord16::*;
+    use twenty_first::shared_math::b_field_element::BFieldElement;
+    &[
+        Push(BFieldElement::new(18446744069414584320)),
+        Divine(Some(Quotient)),
+        Call(BFieldElement::new(10)),
+        Swap(ST1),
+        Dup(ST1),
+        Halt,
+        Dup(ST0),
+        Add,
+        Return,
+    ]
+};
+";
+        assert_eq!(expected, rust_const);
+
+        // `DOUBLE` above is `expected`, pasted in with paths relative to this crate.
+        let restored = Program::from_instructions(DOUBLE, DOUBLE_ENTRY_POINT);
+        assert_eq!(program, restored);
+    }
+
+    #[test]
+    fn entry_point_is_preserved_test() {
+        let program =
+            Program::from_code_with_entry_point("push 1 halt main: halt", "main").unwrap();
+        let rust_const = program.to_rust_const("MAIN").unwrap();
+        assert!(rust_const.contains("pub const MAIN_ENTRY_POINT: usize = 3;"));
+        assert!(!rust_const.contains("Ord16"));
+
+        let instructions = [Push(BFieldElement::new(1)), Halt, Halt];
+        assert_eq!(program, Program::from_instructions(&instructions, 3));
+    }
+
+    #[test]
+    fn invalid_names_are_rejected_test() {
+        let program = Program::from_code("halt").unwrap();
+        for name in ["", "double", "1PROGRAM", "MY-PROGRAM"] {
+            assert!(program.to_rust_const(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn disassembled_program_assembles_to_same_words_test() {
+        let code = "push -1 call foo dup3 swap2 halt \
+            foo: divine hash pop tail_call bar \
+            bar: read_io skiz return recurse";
+        let words = Program::from_code(code).unwrap().to_bwords();
+        let disassembled = disassemble(&words).unwrap();
+        let disassembled_code = disassembled.iter().join(" ");
+        assert!(disassembled_code.starts_with("push -1 call address_9 dup3"));
+        assert!(disassembled_code.contains("address_9: divine hash pop tail_call address_14"));
+
+        let reassembled = Program::from_code(&disassembled_code).unwrap();
+        assert_eq!(words, reassembled.to_bwords());
+    }
+
+    #[test]
+    fn call_into_argument_cannot_be_disassembled_test() {
+        let words = Program::from_code("call foo halt foo: push 1 return")
+            .unwrap()
+            .to_bwords();
+        assert!(disassemble(&words).is_ok());
+
+        let mut words = words;
+        words[1] = BFieldElement::new(4);
+        assert!(disassemble(&words).is_err());
+    }
+}
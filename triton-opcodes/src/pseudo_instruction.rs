@@ -0,0 +1,221 @@
+//! Inspection of pseudo-instructions: what the assembler turns them into.
+
+use std::fmt::Display;
+
+use anyhow::bail;
+use anyhow::Result;
+
+use crate::instruction::parse_token;
+use crate::instruction::LabelledInstruction;
+use crate::instruction::MAX_SPILL_SIZE;
+
+/// All pseudo-instructions, _i.e._, mnemonics the assembler replaces by a sequence of
+/// instructions of the instruction set.
+pub const PSEUDO_INSTRUCTIONS: [&str; 23] = [
+    "neg",
+    "sub",
+    "lte",
+    "lt",
+    "and",
+    "xor",
+    "reverse",
+    "div",
+    "is_u32",
+    "split_assert",
+    "add_u32_checked",
+    "mul_u32_checked",
+    "lte_i32",
+    "lt_i32",
+    "div_i32",
+    "lte_i64",
+    "lt_i64",
+    "div_i64",
+    "unpack_bytes",
+    "eq_vector",
+    "sponge_init",
+    "sponge_absorb",
+    "sponge_squeeze",
+];
+
+/// All pseudo-instructions, including the families `spill1` through `spill15` and `reload1`
+/// through `reload15`.
+pub fn all_pseudo_instructions() -> Vec<String> {
+    let spill_and_reload = (1..=MAX_SPILL_SIZE).flat_map(|num_elements| {
+        [
+            format!("spill{num_elements}"),
+            format!("reload{num_elements}"),
+        ]
+    });
+    PSEUDO_INSTRUCTIONS
+        .iter()
+        .map(|pseudo_instruction| pseudo_instruction.to_string())
+        .chain(spill_and_reload)
+        .collect()
+}
+
+/// The instructions a pseudo-instruction assembles into. Displayed as Triton assembly, with
+/// every instruction annotated by the height of the op stack after its execution, relative to
+/// the height before the pseudo-instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PseudoInstructionExpansion {
+    pub pseudo_instruction: String,
+    pub instructions: Vec<LabelledInstruction>,
+}
+
+impl PseudoInstructionExpansion {
+    /// The relative height of the op stack after each of the expansion's instructions.
+    pub fn stack_heights(&self) -> Vec<i32> {
+        self.instructions
+            .iter()
+            .scan(0, |height, labelled_instruction| {
+                if let LabelledInstruction::Instruction(instruction) = labelled_instruction {
+                    *height += instruction.op_stack_size_influence();
+                }
+                Some(*height)
+            })
+            .collect()
+    }
+
+    /// By how many elements the pseudo-instruction changes the height of the op stack.
+    pub fn op_stack_size_influence(&self) -> i32 {
+        self.stack_heights().last().copied().unwrap_or_default()
+    }
+
+    /// The number of clock cycles the pseudo-instruction takes to execute. Pseudo-instructions
+    /// contain no control flow, so this number is exact.
+    pub fn num_cycles(&self) -> usize {
+        self.instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, LabelledInstruction::Instruction(_)))
+            .count()
+    }
+}
+
+impl Display for PseudoInstructionExpansion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instructions = self
+            .instructions
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect::<Vec<_>>();
+        let width = instructions
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or_default();
+
+        writeln!(f, "// {}", self.pseudo_instruction)?;
+        for (instruction, height) in instructions.iter().zip(self.stack_heights()) {
+            writeln!(f, "{instruction:<width$} // stack height {height:+}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The exact expansion of the given pseudo-instruction, as produced by the assembler.
+pub fn expand_pseudo_instruction(pseudo_instruction: &str) -> Result<PseudoInstructionExpansion> {
+    let is_pseudo_instruction = all_pseudo_instructions()
+        .iter()
+        .any(|other| other == pseudo_instruction);
+    if !is_pseudo_instruction {
+        bail!("`{pseudo_instruction}` is not a pseudo-instruction.");
+    }
+    let instructions = parse_token(pseudo_instruction, &mut "".split_whitespace())?;
+    Ok(PseudoInstructionExpansion {
+        pseudo_instruction: pseudo_instruction.to_string(),
+        instructions,
+    })
+}
+
+#[cfg(test)]
+mod pseudo_instruction_tests {
+    use crate::instruction::parse;
+    use crate::instruction::AnInstruction::*;
+    use crate::ord_n::Ord16::ST1;
+
+    use super::*;
+
+    #[test]
+    fn every_pseudo_instruction_expands_to_its_parse_test() {
+        for pseudo_instruction in all_pseudo_instructions() {
+            let expansion = expand_pseudo_instruction(&pseudo_instruction).unwrap();
+            assert_eq!(parse(&pseudo_instruction).unwrap(), expansion.instructions);
+            assert!(expansion.num_cycles() > 1, "{pseudo_instruction}");
+        }
+    }
+
+    #[test]
+    fn native_instructions_are_not_expanded_test() {
+        assert!(expand_pseudo_instruction("add").is_err());
+        assert!(expand_pseudo_instruction("divine_quotient").is_err());
+        assert!(expand_pseudo_instruction("no_such_instruction").is_err());
+        assert!(expand_pseudo_instruction("spill0").is_err());
+        assert!(expand_pseudo_instruction("spill01").is_err());
+        assert!(expand_pseudo_instruction("reload16").is_err());
+    }
+
+    #[test]
+    fn expansion_annotates_stack_heights_test() {
+        let sub = expand_pseudo_instruction("sub").unwrap();
+        assert_eq!(vec![0, 1, 0, -1], sub.stack_heights());
+        assert_eq!(-1, sub.op_stack_size_influence());
+        assert_eq!(
+            LabelledInstruction::Instruction(Swap(ST1)),
+            sub.instructions[0]
+        );
+
+        let expected = "// sub\n\
+            swap1 // stack height +0\n\
+            push -1 // stack height +1\n\
+            mul // stack height +0\n\
+            add // stack height -1\n";
+        let displayed = sub.to_string();
+        let normalized = displayed
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        assert_eq!(expected, normalized);
+        assert_eq!(sub.instructions, parse(&displayed).unwrap());
+    }
+
+    #[test]
+    fn stack_size_influence_of_binary_operations_test() {
+        let pseudo_instructions = [
+            "lt", "lte", "lt_i32", "lte_i32", "lt_i64", "lte_i64", "and", "xor", "div", "div_i64",
+        ];
+        for pseudo_instruction in pseudo_instructions {
+            let expansion = expand_pseudo_instruction(pseudo_instruction).unwrap();
+            let expected = match pseudo_instruction {
+                "div" | "div_i64" => 0,
+                "lt_i64" | "lte_i64" => -3,
+                _ => -1,
+            };
+            assert_eq!(
+                expected,
+                expansion.op_stack_size_influence(),
+                "{pseudo_instruction}"
+            );
+        }
+    }
+
+    #[test]
+    fn spilling_and_reloading_leaves_stack_height_unchanged_test() {
+        for num_elements in 1..=MAX_SPILL_SIZE {
+            let spill = expand_pseudo_instruction(&format!("spill{num_elements}")).unwrap();
+            let reload = expand_pseudo_instruction(&format!("reload{num_elements}")).unwrap();
+            let num_elements = num_elements as i32;
+            assert_eq!(-num_elements, spill.op_stack_size_influence());
+            assert_eq!(num_elements, reload.op_stack_size_influence());
+            assert_eq!(3 * num_elements as usize, spill.num_cycles());
+            assert_eq!(5 * num_elements as usize, reload.num_cycles());
+        }
+    }
+
+    #[test]
+    fn unpacking_bytes_pushes_six_elements_test() {
+        let unpack_bytes = expand_pseudo_instruction("unpack_bytes").unwrap();
+        assert_eq!(6, unpack_bytes.op_stack_size_influence());
+        assert_eq!(1654, unpack_bytes.num_cycles());
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Weights attributed to call stacks, in the folded format understood by standard flamegraph
+/// tooling like `flamegraph.pl` or `inferno-flamegraph`: one line per stack, the frames separated
+/// by `;` and outermost first, followed by a space and the stack's weight.
+///
+/// The weight of a stack excludes the weight of all stacks it is a prefix of.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FoldedStacks {
+    weights: BTreeMap<Vec<String>, u64>,
+}
+
+impl FoldedStacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the given weight to the given stack, outermost frame first.
+    pub fn add<S: AsRef<str>>(&mut self, stack: &[S], weight: u64) {
+        let stack = stack
+            .iter()
+            .map(|frame| frame.as_ref().to_string())
+            .collect();
+        *self.weights.entry(stack).or_insert(0) += weight;
+    }
+
+    /// The weight of exactly the given stack, excluding the weight of longer stacks.
+    pub fn weight<S: AsRef<str>>(&self, stack: &[S]) -> u64 {
+        self.weights
+            .iter()
+            .find(|(other, _)| {
+                other.len() == stack.len() && other.iter().zip(stack).all(|(a, b)| a == b.as_ref())
+            })
+            .map_or(0, |(_, &weight)| weight)
+    }
+
+    /// The weight of all stacks starting with the given frames.
+    pub fn inclusive_weight<S: AsRef<str>>(&self, prefix: &[S]) -> u64 {
+        self.weights
+            .iter()
+            .filter(|(stack, _)| {
+                stack.len() >= prefix.len()
+                    && stack.iter().zip(prefix).all(|(a, b)| a == b.as_ref())
+            })
+            .map(|(_, &weight)| weight)
+            .sum()
+    }
+
+    /// All stacks with their weights, in lexicographic order. A stack thus precedes all stacks it
+    /// is a prefix of.
+    pub fn stacks(&self) -> impl Iterator<Item = (&[String], u64)> {
+        self.weights
+            .iter()
+            .map(|(stack, &weight)| (stack.as_slice(), weight))
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.weights.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+}
+
+/// Separators of the folded format must not appear within frames.
+fn sanitize(frame: &str) -> String {
+    frame.replace([';', ' '], "_")
+}
+
+impl Display for FoldedStacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (stack, weight) in self.weights.iter() {
+            if *weight == 0 {
+                continue;
+            }
+            let stack: Vec<_> = stack.iter().map(|frame| sanitize(frame)).collect();
+            writeln!(f, "{} {weight}", stack.join(";"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod folded_stacks_tests {
+    use super::*;
+
+    #[test]
+    fn weights_of_identical_stacks_are_summed_test() {
+        let mut stacks = FoldedStacks::new();
+        stacks.add(&["main", "foo"], 3);
+        stacks.add(&["main"], 1);
+        stacks.add(&["main", "foo"], 2);
+        stacks.add(&["main", "foo", "bar"], 4);
+
+        assert_eq!(5, stacks.weight(&["main", "foo"]));
+        assert_eq!(9, stacks.inclusive_weight(&["main", "foo"]));
+        assert_eq!(10, stacks.inclusive_weight::<&str>(&[]));
+        assert_eq!(10, stacks.total_weight());
+        assert_eq!(0, stacks.weight(&["foo"]));
+    }
+
+    #[test]
+    fn folded_format_test() {
+        let mut stacks = FoldedStacks::new();
+        stacks.add(&["main", "foo bar"], 2);
+        stacks.add(&["main"], 1);
+        stacks.add(&["main", "baz"], 0);
+        assert_eq!("main 1\nmain;foo_bar 2\n", stacks.to_string());
+    }
+}
@@ -1 +1,2 @@
+pub mod folded_stacks;
 pub mod triton_profiler;
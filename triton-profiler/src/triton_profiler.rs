@@ -11,6 +11,8 @@ use criterion::profiler::Profiler;
 use twenty_first::shared_math::other::log_2_floor;
 use unicode_width::UnicodeWidthStr;
 
+use crate::folded_stacks::FoldedStacks;
+
 const GET_PROFILE_OUTPUT_AS_YOU_GO_ENV_VAR_NAME: &str = "PROFILE_AS_YOU_GO";
 
 #[derive(Clone, Debug)]
@@ -173,6 +175,62 @@ impl TritonProfiler {
         }
     }
 
+    /// The total time spent in the finished top-level tasks of the given name.
+    pub fn top_level_time(&self, name: &str) -> Duration {
+        self.profile
+            .iter()
+            .filter(|task| task.parent_index.is_none() && task.name == name)
+            .map(|task| task.time)
+            .sum()
+    }
+
+    /// The total time spent in each finished top-level task, in the order in which the tasks
+    /// were first started. Tasks of the same name are summed up.
+    pub fn top_level_times(&self) -> Vec<(String, Duration)> {
+        let mut times: Vec<(String, Duration)> = vec![];
+        for task in self
+            .profile
+            .iter()
+            .filter(|task| task.parent_index.is_none())
+        {
+            match times.iter_mut().find(|(name, _)| *name == task.name) {
+                Some((_, time)) => *time += task.time,
+                None => times.push((task.name.clone(), task.time)),
+            }
+        }
+        times
+    }
+
+    /// The time spent in every task, excluding the time spent in its subtasks, in microseconds.
+    /// Can be turned into a flamegraph by standard tooling.
+    pub fn folded_stacks(&self) -> FoldedStacks {
+        assert!(
+            self.stack.is_empty(),
+            "Cannot fold stacks before stack is empty."
+        );
+        let mut folded_stacks = FoldedStacks::new();
+        for (task_index, task) in self.profile.iter().enumerate() {
+            let subtasks_time: Duration = self
+                .profile
+                .iter()
+                .filter(|subtask| subtask.parent_index == Some(task_index))
+                .map(|subtask| subtask.time)
+                .sum();
+            let self_time = task.time.saturating_sub(subtasks_time);
+
+            let mut stack = vec![task.name.as_str()];
+            let mut parent_index = task.parent_index;
+            while let Some(index) = parent_index {
+                stack.push(&self.profile[index].name);
+                parent_index = self.profile[index].parent_index;
+            }
+            stack.push(&self.name);
+            stack.reverse();
+            folded_stacks.add(&stack, self_time.as_micros() as u64);
+        }
+        folded_stacks
+    }
+
     pub fn start(&mut self, name: &str) {
         if !self.ignoring() {
             self.plain_start(name, TaskType::Generic);
@@ -467,8 +525,7 @@ impl Display for Report {
         }
         if let Some(cycle_count) = self.cycle_count {
             let total_time = self.total_time.as_millis() as usize;
-            if total_time != 0 {
-                let freq = 1_000 * cycle_count / total_time;
+            if let Some(freq) = (1_000 * cycle_count).checked_div(total_time) {
                 writeln!(
                     f,
                     "Clock frequency is {freq} Hz ({cycle_count} clock cycles / {total_time} ms)",
@@ -478,8 +535,7 @@ impl Display for Report {
 
         if let Some(padded_height) = self.padded_height {
             let total_time = self.total_time.as_millis() as usize;
-            if total_time != 0 {
-                let optimal_freq = 1_000 * padded_height / total_time;
+            if let Some(optimal_freq) = (1_000 * padded_height).checked_div(total_time) {
                 writeln!(
                     f,
                     "Optimal clock frequency is {optimal_freq} Hz \
@@ -590,4 +646,48 @@ pub mod triton_profiler_tests {
         println!("{}", profiler.report(Some(0), Some(0), Some(0)));
         println!("{}", profiler.report(Some(5), Some(8), Some(13)));
     }
+
+    #[test]
+    fn top_level_time_sums_tasks_of_same_name_test() {
+        let mut profiler = TritonProfiler::new("Top Level Time Test");
+        for _ in 0..2 {
+            profiler.start("outer");
+            profiler.start("inner");
+            sleep(Duration::from_millis(2));
+            profiler.stop("inner");
+            profiler.stop("outer");
+        }
+        profiler.finish();
+
+        assert!(profiler.top_level_time("outer") >= Duration::from_millis(4));
+        assert_eq!(Duration::ZERO, profiler.top_level_time("inner"));
+        assert_eq!(Duration::ZERO, profiler.top_level_time("nonexistent"));
+
+        let top_level_times = profiler.top_level_times();
+        assert_eq!(1, top_level_times.len());
+        assert_eq!(
+            ("outer".to_string(), profiler.top_level_time("outer")),
+            top_level_times[0]
+        );
+    }
+
+    #[test]
+    fn folded_stacks_attribute_self_time_test() {
+        let mut profiler = TritonProfiler::new("folding");
+        profiler.start("outer");
+        profiler.start("inner");
+        sleep(Duration::from_millis(3));
+        profiler.stop("inner");
+        profiler.stop("outer");
+        profiler.finish();
+
+        let folded_stacks = profiler.folded_stacks();
+        let outer = ["folding", "outer"];
+        let inner = ["folding", "outer", "inner"];
+        assert!(folded_stacks.weight(&inner) >= 3_000);
+        assert!(folded_stacks.weight(&outer) < folded_stacks.weight(&inner));
+        // Self times are truncated to whole microseconds individually.
+        let outer_time = profiler.top_level_time("outer").as_micros() as u64;
+        assert!(outer_time.abs_diff(folded_stacks.inclusive_weight(&outer)) <= 1);
+    }
 }
@@ -30,18 +30,18 @@ fn prove_fib_100(criterion: &mut Criterion) {
         Ok(p) => p,
     };
     let input = vec![100_u64.into()];
-    let (aet, output, err) = simulate(&program, input.clone(), vec![]);
-    if let Some(error) = err {
-        panic!("The VM encountered the following problem: {}", error);
-    }
+    let (aet, output) = simulate(&program, input.clone(), vec![])
+        .unwrap_or_else(|error| panic!("The VM encountered the following problem: {error}"));
 
     let instructions = program.to_bwords();
     let padded_height = MasterBaseTable::padded_height(&aet, &instructions);
     let claim = Claim {
         input,
         program: instructions,
-        output,
+        output: output.into(),
+        output_tags: vec![],
         padded_height,
+        entry_point: program.entry_point,
     };
     let stark = Stark::new(claim, Default::default());
     //start the profiler
@@ -6,7 +6,7 @@ use triton_opcodes::program::Program;
 use triton_profiler::triton_profiler::Report;
 use triton_profiler::triton_profiler::TritonProfiler;
 use triton_vm::proof::Claim;
-use triton_vm::shared_tests::save_proof;
+use triton_vm::proof_file::ProofFile;
 use triton_vm::stark::Stark;
 use triton_vm::stark::StarkParameters;
 use triton_vm::table::master_table::MasterBaseTable;
@@ -24,10 +24,8 @@ fn prove_halt(_criterion: &mut Criterion) {
     };
 
     // witness
-    let (aet, output, err) = simulate_no_input(&program);
-    if let Some(error) = err {
-        panic!("The VM encountered the following problem: {}", error);
-    }
+    let (aet, output) = simulate_no_input(&program)
+        .unwrap_or_else(|error| panic!("The VM encountered the following problem: {error}"));
 
     let code = program.to_bwords();
     let cycle_count = aet.processor_matrix.nrows();
@@ -35,8 +33,10 @@ fn prove_halt(_criterion: &mut Criterion) {
     let claim = Claim {
         input: vec![],
         program: code,
-        output,
+        output: output.into(),
+        output_tags: vec![],
         padded_height,
+        entry_point: program.entry_point,
     };
     let parameters = StarkParameters::default();
     let stark = Stark::new(claim, parameters);
@@ -52,8 +52,9 @@ fn prove_halt(_criterion: &mut Criterion) {
     };
 
     // save proof
-    let filename = "halt.tsp";
-    if let Err(e) = save_proof(filename, proof) {
+    let filename = "halt.tpf";
+    let proof_file = ProofFile::new(stark.claim, stark.parameters, proof);
+    if let Err(e) = proof_file.save(filename) {
         println!("Error saving proof: {:?}", e);
     }
 
@@ -0,0 +1,32 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use triton_opcodes::program::Program;
+
+use triton_vm::benchmarks::corpus;
+use triton_vm::vm::simulate;
+
+/// cargo criterion --bench simulate_corpus
+fn simulate_corpus(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("simulate_corpus");
+    group.sample_size(10);
+
+    for benchmark in corpus() {
+        let program = Program::from_code(&benchmark.program.source_code).unwrap();
+        let input = benchmark.program.input;
+        let secret_input = benchmark.program.secret_input;
+        let benchmark_id = BenchmarkId::new("simulate", benchmark.name);
+        group.bench_function(benchmark_id, |bencher| {
+            bencher.iter(|| simulate(&program, input.clone(), secret_input.clone()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = simulate_corpus
+}
+criterion_main!(benches);
@@ -9,9 +9,7 @@ use triton_profiler::triton_profiler::TritonProfiler;
 
 use triton_opcodes::program::Program;
 use triton_vm::proof::Claim;
-use triton_vm::shared_tests::load_proof;
-use triton_vm::shared_tests::proof_file_exists;
-use triton_vm::shared_tests::save_proof;
+use triton_vm::proof_file::ProofFile;
 use triton_vm::stark::Stark;
 use triton_vm::stark::StarkParameters;
 use triton_vm::table::master_table::MasterBaseTable;
@@ -32,38 +30,32 @@ fn verify_halt(criterion: &mut Criterion) {
 
     let instructions = program.to_bwords();
     let stark_parameters = StarkParameters::default();
-    let filename = "halt.tsp";
+    let filename = "halt.tpf";
     let mut maybe_cycle_count = None;
-    let (proof, stark) = if proof_file_exists(filename) {
-        let proof = match load_proof(filename) {
-            Ok(p) => p,
+    let (proof, stark) = if std::path::Path::new(filename).exists() {
+        let proof_file = match ProofFile::load(filename) {
+            Ok(proof_file) => proof_file,
             Err(e) => panic!("Could not load proof from disk: {:?}", e),
         };
-        let padded_height = proof.padded_height();
-        let claim = Claim {
-            input: vec![],
-            program: instructions,
-            output: vec![],
-            padded_height,
-        };
-        let stark = Stark::new(claim, stark_parameters);
-        (proof, stark)
+        let stark = Stark::new(proof_file.claim, proof_file.parameters);
+        (proof_file.proof, stark)
     } else {
-        let (aet, output, err) = simulate_no_input(&program);
-        if let Some(error) = err {
-            panic!("The VM encountered the following problem: {}", error);
-        }
+        let (aet, output) = simulate_no_input(&program)
+            .unwrap_or_else(|error| panic!("The VM encountered the following problem: {error}"));
         maybe_cycle_count = Some(aet.processor_matrix.nrows());
         let padded_height = MasterBaseTable::padded_height(&aet, &instructions);
         let claim = Claim {
             input: vec![],
             program: instructions,
-            output,
+            output: output.into(),
+            output_tags: vec![],
             padded_height,
+            entry_point: program.entry_point,
         };
         let stark = Stark::new(claim, stark_parameters);
         let proof = stark.prove(aet, &mut None);
-        if let Err(e) = save_proof(filename, proof.clone()) {
+        let proof_file = ProofFile::new(stark.claim.clone(), stark.parameters, proof.clone());
+        if let Err(e) = proof_file.save(filename) {
             panic!("Problem! could not save proof to disk: {:?}", e);
         }
         (proof, stark)
@@ -0,0 +1,65 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use ndarray::Array1;
+use ndarray::Zip;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::other::random_elements;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+
+use triton_vm::x_field_ops::add_weighted_shifted_codeword;
+use triton_vm::x_field_ops::inner_product;
+
+/// cargo criterion --bench x_field_ops
+fn x_field_ops(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("x_field_ops");
+    let length = 1 << 14;
+
+    let lhs: Vec<XFieldElement> = random_elements(length);
+    let rhs: Vec<XFieldElement> = random_elements(length);
+
+    group.bench_function(BenchmarkId::new("inner_product/naive", length), |bencher| {
+        bencher.iter(|| {
+            lhs.iter()
+                .zip(rhs.iter())
+                .map(|(&l, &r)| l * r)
+                .sum::<XFieldElement>()
+        })
+    });
+    group.bench_function(
+        BenchmarkId::new("inner_product/batched", length),
+        |bencher| bencher.iter(|| inner_product(&lhs, &rhs)),
+    );
+
+    let weights = [lhs[0], rhs[0]];
+    let codeword = Array1::from(lhs.clone());
+    let shifts = Array1::from(random_elements::<BFieldElement>(length));
+    let mut accumulator = rhs.clone();
+
+    group.bench_function(BenchmarkId::new("combination/naive", length), |bencher| {
+        bencher.iter(|| {
+            Zip::from(&mut accumulator)
+                .and(codeword.view())
+                .and(shifts.view())
+                .par_for_each(|acc, &xfe, &shift| {
+                    *acc += weights[0] * xfe + weights[1] * xfe * shift
+                })
+        })
+    });
+    group.bench_function(BenchmarkId::new("combination/batched", length), |bencher| {
+        bencher.iter(|| {
+            add_weighted_shifted_codeword(&mut accumulator, codeword.view(), shifts.view(), weights)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = x_field_ops
+}
+
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+//! Proves the computation of the greatest common divisor of two numbers using Euclid's
+//! algorithm. The numbers are public input, their greatest common divisor is public output.
+//!
+//! Instruction `div` is a pseudo-instruction expanding to many instructions, which makes even a
+//! few iterations of the loop comparatively expensive to prove.
+
+use anyhow::Result;
+use triton_vm::stark::prove_and_verify;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+const GCD: &str = "
+    read_io read_io     // _ a b
+    call gcd            // _ gcd 0
+    pop write_io halt
+
+    // Invariant: the greatest common divisor of a and b is the result.
+    gcd:                // _ a b
+        dup0 push 0 eq
+        skiz return
+        dup0 swap2      // _ b b a
+        div             // _ b (a/b) (a%b)
+        swap1 pop       // _ b (a%b)
+        recurse
+";
+
+fn prove_gcd(a: u32, b: u32) -> Result<BFieldElement> {
+    let input = vec![BFieldElement::new(a as u64), BFieldElement::new(b as u64)];
+    let (stark, _) = prove_and_verify(GCD, input, vec![])?;
+    Ok(stark.claim.output[0])
+}
+
+fn main() -> Result<()> {
+    let (a, b) = (42, 56);
+    let gcd = prove_gcd(a, b)?;
+    println!("Proved that the greatest common divisor of {a} and {b} is {gcd}.");
+    Ok(())
+}
+
+#[test]
+fn gcd_is_proved_test() {
+    assert_eq!(BFieldElement::new(14), prove_gcd(42, 56).unwrap());
+}
@@ -0,0 +1,56 @@
+//! Proves the computation of a hash chain: the seed is hashed repeatedly, each time together with
+//! five zeros. The length of the chain and the seed are public input, the last digest of the chain
+//! is public output.
+
+use anyhow::Result;
+use triton_vm::hashing::hash10;
+use triton_vm::stark::prove_and_verify;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+
+const HASH_CHAIN: &str = "
+    read_io                                 // _ n
+    read_io read_io read_io read_io read_io // _ n [seed]
+    call chain
+    write_io write_io write_io write_io write_io
+    halt
+
+    chain:                                  // _ n [digest]
+        dup5 push 0 eq skiz return
+        push 0 push 0 push 0 push 0 push 0 hash pop pop pop pop pop
+        swap5 push -1 add swap5             // _ (n-1) [digest]
+        recurse
+";
+
+/// The hash chain computed outside of the VM.
+fn hash_chain(seed: Digest, length: usize) -> Digest {
+    let zeros = [BFieldElement::new(0); DIGEST_LENGTH];
+    (0..length).fold(seed, |digest, _| {
+        hash10([zeros, digest.values()].concat().try_into().unwrap())
+    })
+}
+
+fn prove_hash_chain(seed: Digest, length: usize) -> Result<Digest> {
+    let mut input = vec![BFieldElement::new(length as u64)];
+    // Element 0 of the seed ends up on top of the stack.
+    input.extend(seed.values().into_iter().rev());
+    let (stark, _) = prove_and_verify(HASH_CHAIN, input, vec![])?;
+    Ok(Digest::new(stark.claim.output.try_into().unwrap()))
+}
+
+fn main() -> Result<()> {
+    let seed = Digest::new([1, 2, 3, 4, 5].map(BFieldElement::new));
+    let length = 10;
+    let digest = prove_hash_chain(seed, length)?;
+    assert_eq!(hash_chain(seed, length), digest);
+    println!("Proved that the hash chain of length {length} starting at {seed} ends at {digest}.");
+    Ok(())
+}
+
+#[test]
+fn hash_chain_is_proved_test() {
+    let seed = Digest::new([1, 2, 3, 4, 5].map(BFieldElement::new));
+    assert_eq!(hash_chain(seed, 5), prove_hash_chain(seed, 5).unwrap());
+    assert_eq!(seed, prove_hash_chain(seed, 0).unwrap());
+}
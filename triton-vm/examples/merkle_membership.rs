@@ -0,0 +1,86 @@
+//! Proves knowledge of a leaf in a Merkle tree of height 3. The root and the leaf's index are
+//! public input. The leaf and its authentication path are secret input.
+
+use anyhow::Result;
+use itertools::Itertools;
+use triton_vm::hashing::hash10;
+use triton_vm::hashing::hash_varlen;
+use triton_vm::hashing::merkle_root;
+use triton_vm::stark::prove_and_verify;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+
+const TREE_HEIGHT: usize = 3;
+
+const MERKLE_MEMBERSHIP: &str = "
+    read_io push 8 add                          // _ node_index
+    divine divine divine divine divine          // _ node_index [leaf]
+    push 0 push 0 push 0 push 0 push 0
+    call authentication_path                    // _ 1 [root] 0 0 0 0 0
+    pop pop pop pop pop
+    read_io read_io read_io read_io read_io     // _ 1 [root] [claimed_root]
+    assert_vector
+    halt
+
+    authentication_path:
+        divine_sibling hash
+        dup10 push 1 eq skiz return
+        recurse
+";
+
+/// The leaf's digest followed by the digests of all its siblings on the path to the root, each
+/// digest in the order the VM divines it.
+fn secret_input(leafs: &[Digest], leaf_index: usize) -> Vec<BFieldElement> {
+    let mut secret_input = leafs[leaf_index].values().into_iter().rev().collect_vec();
+    let mut level = leafs.to_vec();
+    let mut index = leaf_index;
+    while level.len() > 1 {
+        let sibling = level[index ^ 1];
+        secret_input.extend(sibling.values().into_iter().rev());
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                hash10(
+                    [pair[0].values(), pair[1].values()]
+                        .concat()
+                        .try_into()
+                        .unwrap(),
+                )
+            })
+            .collect();
+        index /= 2;
+    }
+    secret_input
+}
+
+fn prove_membership(leafs: &[Digest], leaf_index: usize, root: Digest) -> Result<()> {
+    let mut input = vec![BFieldElement::new(leaf_index as u64)];
+    input.extend(root.values().into_iter().rev());
+    prove_and_verify(MERKLE_MEMBERSHIP, input, secret_input(leafs, leaf_index))?;
+    Ok(())
+}
+
+fn leafs() -> Vec<Digest> {
+    (0..1 << TREE_HEIGHT)
+        .map(|i| hash_varlen(&[BFieldElement::new(i)]))
+        .collect()
+}
+
+fn main() -> Result<()> {
+    let leafs = leafs();
+    let root = merkle_root(&leafs)?;
+    let leaf_index = 5;
+    prove_membership(&leafs, leaf_index, root)?;
+    println!("Proved knowledge of leaf {leaf_index} in the Merkle tree with root {root}.");
+    Ok(())
+}
+
+#[test]
+fn merkle_membership_is_proved_test() {
+    let leafs = leafs();
+    let root = merkle_root(&leafs).unwrap();
+    prove_membership(&leafs, 5, root).unwrap();
+
+    let other_root = hash_varlen(&[]);
+    assert!(prove_membership(&leafs, 5, other_root).is_err());
+}
@@ -0,0 +1,16 @@
+pub mod assertion_search;
+pub mod balancing;
+pub mod chrome_trace;
+pub mod conformance;
+pub mod control_flow_graph;
+pub mod cost_profile;
+pub mod cycle_budget;
+pub mod flamegraph;
+pub mod label_estimates;
+pub mod program_diff;
+pub mod secret_input;
+pub mod semantics_fuzzing;
+pub mod skiz_fusion;
+pub mod stack_depth;
+pub mod superinstructions;
+pub mod table_heights;
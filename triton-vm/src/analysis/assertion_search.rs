@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::state::VMState;
+use crate::vm;
+use crate::vm::error::InstructionError;
+
+/// The public and secret input of one execution, as produced by an input generator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchInput {
+    pub stdin: Vec<BFieldElement>,
+    pub secret_in: Vec<BFieldElement>,
+}
+
+/// An input for which one of the program's assertions fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionCounterExample {
+    pub input: SearchInput,
+
+    /// The clock cycle in which the assertion failed.
+    pub cycle: u32,
+
+    /// The address of the failing `assert` or `assert_vector`.
+    pub instruction_pointer: usize,
+
+    /// The value of `st0` when the assertion failed.
+    pub st0: BFieldElement,
+}
+
+impl Display for AssertionCounterExample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_words = |words: &[BFieldElement]| {
+            let words = words.iter().map(|word| word.to_string());
+            words.collect::<Vec<_>>().join(", ")
+        };
+        writeln!(
+            f,
+            "assertion failed in cycle {} at address {} with st0 = {}",
+            self.cycle, self.instruction_pointer, self.st0
+        )?;
+        writeln!(f, "stdin:     [{}]", format_words(&self.input.stdin))?;
+        write!(f, "secret_in: [{}]", format_words(&self.input.secret_in))
+    }
+}
+
+/// Searches for inputs that make a program fail one of its assertions. The search first draws
+/// inputs from a generator, then mutates those inputs that reached previously unexecuted
+/// instructions. Mutations change the values of individual words, but never the number of words,
+/// so that every tried input keeps the shape the generator gives it.
+///
+/// Executions that run out of input or exceed `max_cycles` are discarded, as are executions that
+/// fail for any reason other than a failed assertion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssertionSearch {
+    /// The number of inputs drawn from the generator.
+    pub num_generated_inputs: usize,
+
+    /// The number of mutated inputs tried after the generated ones.
+    pub num_mutated_inputs: usize,
+
+    /// The number of clock cycles after which an execution is abandoned.
+    pub max_cycles: u32,
+
+    /// The seed of the randomness, making searches reproducible.
+    pub seed: u64,
+}
+
+impl Default for AssertionSearch {
+    fn default() -> Self {
+        Self {
+            num_generated_inputs: 100,
+            num_mutated_inputs: 1_000,
+            max_cycles: 1 << 16,
+            seed: 0,
+        }
+    }
+}
+
+/// The result of executing the program on one input.
+enum Execution {
+    Failed(AssertionCounterExample),
+    Passed { covered: HashSet<usize> },
+}
+
+impl AssertionSearch {
+    /// Words that frequently lie on the border between passing and failing an assertion.
+    const INTERESTING_VALUES: [u64; 6] = [0, 1, 2, u32::MAX as u64, 1 << 32, BFieldElement::MAX];
+
+    /// The first input found for which an assertion fails, or `None` if the search found none.
+    pub fn run(
+        &self,
+        program: &Program,
+        mut generator: impl FnMut(&mut StdRng) -> SearchInput,
+    ) -> Option<AssertionCounterExample> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut covered = HashSet::new();
+        let mut corpus = vec![];
+
+        for _ in 0..self.num_generated_inputs {
+            let input = generator(&mut rng);
+            if let Some(counter_example) = self.try_input(program, input, &mut covered, &mut corpus)
+            {
+                return Some(counter_example);
+            }
+        }
+
+        for _ in 0..self.num_mutated_inputs {
+            let input = match corpus.is_empty() {
+                true => generator(&mut rng),
+                false => Self::mutate(&corpus[rng.gen_range(0..corpus.len())], &mut rng),
+            };
+            if let Some(counter_example) = self.try_input(program, input, &mut covered, &mut corpus)
+            {
+                return Some(counter_example);
+            }
+        }
+        None
+    }
+
+    /// Execute the program on the input. Returns the counter-example if an assertion fails.
+    /// Otherwise, adds the input to the corpus if it reached instructions no previous input
+    /// reached.
+    fn try_input(
+        &self,
+        program: &Program,
+        input: SearchInput,
+        covered: &mut HashSet<usize>,
+        corpus: &mut Vec<SearchInput>,
+    ) -> Option<AssertionCounterExample> {
+        match self.execute(program, &input)? {
+            Execution::Failed(counter_example) => Some(counter_example),
+            Execution::Passed {
+                covered: newly_covered,
+            } => {
+                if !newly_covered.is_subset(covered) {
+                    covered.extend(newly_covered);
+                    corpus.push(input);
+                }
+                None
+            }
+        }
+    }
+
+    /// Execute the program on the input, recording the addresses of all executed instructions.
+    /// Returns `None` for executions to be discarded.
+    fn execute(&self, program: &Program, input: &SearchInput) -> Option<Execution> {
+        let SearchInput { stdin, secret_in } = input.clone();
+        let mut covered = HashSet::new();
+        let observer = |state: &VMState, _| {
+            if !state.is_complete() {
+                covered.insert(state.instruction_pointer);
+            }
+        };
+        let err =
+            vm::execute_with_limit(program, stdin, secret_in, self.max_cycles, observer).err();
+
+        let Some(err) = err else {
+            return Some(Execution::Passed { covered });
+        };
+        match err.kind {
+            InstructionError::AssertionFailed(st0) => {
+                let counter_example = AssertionCounterExample {
+                    input: input.clone(),
+                    cycle: err.cycle_count,
+                    instruction_pointer: err.instruction_pointer,
+                    st0,
+                };
+                Some(Execution::Failed(counter_example))
+            }
+            _ => None,
+        }
+    }
+
+    /// A copy of the input in which a few words have been changed.
+    fn mutate(input: &SearchInput, rng: &mut StdRng) -> SearchInput {
+        let mut input = input.clone();
+        let num_words = input.stdin.len() + input.secret_in.len();
+        if num_words == 0 {
+            return input;
+        }
+
+        for _ in 0..rng.gen_range(1..=3) {
+            let index = rng.gen_range(0..num_words);
+            let word = match index < input.stdin.len() {
+                true => &mut input.stdin[index],
+                false => &mut input.secret_in[index - input.stdin.len()],
+            };
+            *word = match rng.gen_range(0..5) {
+                0 => BFieldElement::new(rng.gen()),
+                1 => BFieldElement::new(rng.gen_range(0..256)),
+                2 => *word + BFieldElement::new(1),
+                3 => *word - BFieldElement::new(1),
+                _ => {
+                    let index = rng.gen_range(0..Self::INTERESTING_VALUES.len());
+                    BFieldElement::new(Self::INTERESTING_VALUES[index])
+                }
+            };
+        }
+        input
+    }
+}
+
+#[cfg(test)]
+mod assertion_search_tests {
+    use triton_opcodes::instruction::AnInstruction::Assert;
+
+    use super::*;
+
+    fn random_stdin(num_words: usize) -> impl FnMut(&mut StdRng) -> SearchInput {
+        move |rng| SearchInput {
+            stdin: (0..num_words)
+                .map(|_| BFieldElement::new(rng.gen()))
+                .collect(),
+            secret_in: vec![],
+        }
+    }
+
+    #[test]
+    fn mutation_finds_input_failing_an_assertion_test() {
+        let code = "read_io read_io dup1 push 42 eq skiz call check pop pop halt \
+            check: dup0 push 4294967295 eq push 0 eq assert return";
+        let program = Program::from_code(code).unwrap();
+        let search = AssertionSearch {
+            num_mutated_inputs: 20_000,
+            ..AssertionSearch::default()
+        };
+        let counter_example = search
+            .run(&program, random_stdin(2))
+            .expect("a counter-example must be found");
+        println!("{counter_example}");
+
+        let [x, y] = [42, u32::MAX as u64].map(BFieldElement::new);
+        assert_eq!(vec![x, y], counter_example.input.stdin);
+        assert_eq!(BFieldElement::new(0), counter_example.st0);
+        assert_eq!(
+            Assert,
+            program.instructions[counter_example.instruction_pointer]
+        );
+    }
+
+    #[test]
+    fn search_without_failing_assertion_finds_nothing_test() {
+        let program = Program::from_code("read_io push 1 add pop halt").unwrap();
+        let counter_example = AssertionSearch::default().run(&program, random_stdin(1));
+        assert_eq!(None, counter_example);
+    }
+
+    #[test]
+    fn exhausted_input_and_runaway_executions_are_discarded_test() {
+        let program = Program::from_code("read_io divine assert halt").unwrap();
+        let counter_example = AssertionSearch::default().run(&program, random_stdin(1));
+        assert_eq!(None, counter_example);
+
+        let program = Program::from_code("push 0 call loop halt loop: push 1 add recurse").unwrap();
+        let search = AssertionSearch {
+            max_cycles: 100,
+            ..AssertionSearch::default()
+        };
+        assert_eq!(None, search.run(&program, random_stdin(1)));
+    }
+}
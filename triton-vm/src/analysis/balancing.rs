@@ -0,0 +1,382 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+use triton_opcodes::instruction::label_addresses;
+use triton_opcodes::instruction::AnInstruction;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::instruction::LabelledInstruction;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::other::roundup_npo2;
+
+use crate::analysis::cost_profile::CostModel;
+use crate::analysis::table_heights::TableHeights;
+use crate::table::master_table::TableId;
+use crate::table::table_column::BaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn::CI;
+use crate::table::table_column::ProcessorBaseTableColumn::IP;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// A transformation of a program that trades program size for clock cycles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transformation {
+    /// Replace every `call` of the subroutine by the subroutine's body without its `return`, and
+    /// remove the subroutine. Saves the `call` and the `return` on every execution of the
+    /// subroutine, but duplicates its body once per call site.
+    Inline {
+        subroutine: String,
+        call_sites: usize,
+    },
+
+    /// Duplicate the body of the loop, such that `recurse` is executed only every other
+    /// iteration. The loop's exit check remains in both copies of the body.
+    Unroll { loop_label: String },
+}
+
+impl Display for Transformation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transformation::Inline {
+                subroutine,
+                call_sites,
+            } => write!(f, "inline `{subroutine}` at {call_sites} call site(s)"),
+            Transformation::Unroll { loop_label } => write!(f, "unroll `{loop_label}` twice"),
+        }
+    }
+}
+
+/// The estimated effect of one [`Transformation`] on the execution it was derived from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advice {
+    pub transformation: Transformation,
+
+    /// The change in clock cycles. Negative if the transformation saves cycles.
+    pub cycle_delta: isize,
+
+    /// The change in program size, in words. Negative if the program shrinks.
+    pub program_size_delta: isize,
+
+    pub padded_height_before: usize,
+
+    /// The estimated table heights after the transformation.
+    pub heights_after: TableHeights,
+
+    /// The change in the weighted number of rows according to the [`CostModel`]. Negative if the
+    /// transformation saves.
+    pub cost_delta: isize,
+}
+
+impl Advice {
+    /// A transformation is recommended if it shrinks the padded height, or if it saves without
+    /// growing the padded height.
+    pub fn is_recommended(&self) -> bool {
+        let padded_height_after = self.heights_after.padded_height;
+        padded_height_after < self.padded_height_before
+            || (padded_height_after == self.padded_height_before && self.cost_delta < 0)
+    }
+
+    /// Whether the transformation changes the padded height, which dominates proving cost.
+    pub fn crosses_padding_boundary(&self) -> bool {
+        self.heights_after.padded_height != self.padded_height_before
+    }
+}
+
+/// Recommends transformations of a program – inlining subroutines, unrolling loops – by how they
+/// would shift table heights relative to the padding boundaries. Inlining and unrolling save
+/// clock cycles, growing the program in exchange. Which of the two matters more depends on
+/// which table is the tallest: a program whose Program Table is close to a power of two might
+/// double its proving cost by inlining a subroutine, while the same transformation can halve the
+/// proving cost of a program whose Processor Table is just over a power of two.
+///
+/// All numbers are estimates derived from one execution. Whether a transformation preserves the
+/// program's semantics, for example, whether a loop can be unrolled for every input, is for the
+/// programmer to decide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalancingReport {
+    pub heights: TableHeights,
+
+    /// The advice for every applicable transformation, most saving first.
+    pub advice: Vec<Advice>,
+}
+
+impl BalancingReport {
+    /// Analyze the given program and the trace of one of its executions.
+    ///
+    /// Subroutines are the code from a label up to the first `return`. Subroutines containing
+    /// other labels are not considered. Subroutines containing `recurse` are loops and can be
+    /// unrolled; subroutines without any control flow can be inlined.
+    pub fn new(
+        program: &[LabelledInstruction],
+        aet: &AlgebraicExecutionTrace,
+        cost_model: &CostModel,
+    ) -> Self {
+        let heights = TableHeights::new(aet, &Program::new(program).to_bwords());
+        let addresses = label_addresses(program);
+        let executed = aet
+            .processor_matrix
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let ip = row[IP.base_table_index()].value() as usize;
+                let instruction = Instruction::try_from(row[CI.base_table_index()].value())
+                    .expect("Trace must only contain valid opcodes.");
+                (ip, instruction)
+            })
+            .collect_vec();
+
+        let mut advice = vec![];
+        for subroutine in subroutines(program) {
+            let start = addresses[&subroutine.label];
+            let end = start + subroutine.num_words();
+            let transformation_and_deltas = if subroutine.is_loop() {
+                let num_recursions = executed
+                    .iter()
+                    .filter(|&&(ip, instruction)| {
+                        (start..end).contains(&ip) && instruction == Recurse
+                    })
+                    .count();
+                if num_recursions == 0 {
+                    continue;
+                }
+                let transformation = Transformation::Unroll {
+                    loop_label: subroutine.label.clone(),
+                };
+                let cycle_delta = -((num_recursions / 2) as isize);
+                let program_size_delta = (subroutine.num_words() - 2) as isize;
+                (transformation, cycle_delta, program_size_delta)
+            } else if subroutine.is_inlinable() {
+                let call_sites = call_sites(program, &subroutine.label);
+                if call_sites == 0 {
+                    continue;
+                }
+                let num_executions = executed.iter().filter(|&&(ip, _)| ip == start).count();
+                let transformation = Transformation::Inline {
+                    subroutine: subroutine.label.clone(),
+                    call_sites,
+                };
+                let num_words = subroutine.num_words() as isize;
+                let cycle_delta = -2 * num_executions as isize;
+                let program_size_delta = call_sites as isize * (num_words - 3) - num_words;
+                (transformation, cycle_delta, program_size_delta)
+            } else {
+                continue;
+            };
+
+            let (transformation, cycle_delta, program_size_delta) = transformation_and_deltas;
+            let heights_after = estimate_heights(&heights, cycle_delta, program_size_delta);
+            let cost_delta = TableId::iter()
+                .map(|table| {
+                    let height_delta = heights_after.unpadded_heights[table as usize] as isize
+                        - heights.unpadded_heights[table as usize] as isize;
+                    cost_model.weight(table) as isize * height_delta
+                })
+                .sum();
+            advice.push(Advice {
+                transformation,
+                cycle_delta,
+                program_size_delta,
+                padded_height_before: heights.padded_height,
+                heights_after,
+                cost_delta,
+            });
+        }
+        advice.sort_by_key(|advice| (advice.heights_after.padded_height, advice.cost_delta));
+
+        Self { heights, advice }
+    }
+
+    pub fn recommended(&self) -> impl Iterator<Item = &Advice> {
+        self.advice.iter().filter(|advice| advice.is_recommended())
+    }
+}
+
+impl Display for BalancingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Padded height: {}, determined by the {}",
+            self.heights.padded_height,
+            self.heights.tallest_table()
+        )?;
+        writeln!(
+            f,
+            "| Transformation                           |  Cycles | Words | Padded height |  Cost | Advice    |"
+        )?;
+        writeln!(
+            f,
+            "|:-----------------------------------------|--------:|------:|--------------:|------:|:----------|"
+        )?;
+        for advice in self.advice.iter() {
+            let verdict = match advice.is_recommended() {
+                true => "recommend",
+                false => "keep",
+            };
+            writeln!(
+                f,
+                "| {:<40} | {:>+7} | {:>+5} | {:>13} | {:>+5} | {verdict:<9} |",
+                advice.transformation.to_string(),
+                advice.cycle_delta,
+                advice.program_size_delta,
+                advice.heights_after.padded_height,
+                advice.cost_delta,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The code from a label up to and including the first `return`.
+struct Subroutine {
+    label: String,
+    body: Vec<AnInstruction<String>>,
+}
+
+impl Subroutine {
+    fn num_words(&self) -> usize {
+        self.body.iter().map(|instruction| instruction.size()).sum()
+    }
+
+    fn is_loop(&self) -> bool {
+        self.body.contains(&Recurse)
+    }
+
+    /// Straight-line code ending in an unconditional `return`.
+    fn is_inlinable(&self) -> bool {
+        let (_, instructions) = self.body.split_last().unwrap();
+        let has_control_flow = instructions.iter().any(|instruction| {
+            matches!(instruction, Call(_) | TailCall(_) | Recurse | Return | Halt)
+        });
+        let has_conditional_return = instructions.last() == Some(&Skiz);
+        !has_control_flow && !has_conditional_return
+    }
+}
+
+fn subroutines(program: &[LabelledInstruction]) -> Vec<Subroutine> {
+    let mut subroutines = vec![];
+    for (index, labelled_instruction) in program.iter().enumerate() {
+        let LabelledInstruction::Label(label) = labelled_instruction else {
+            continue;
+        };
+        let mut body = vec![];
+        for labelled_instruction in program[index + 1..].iter() {
+            match labelled_instruction {
+                LabelledInstruction::Label(_) => break,
+                LabelledInstruction::Instruction(instruction) => body.push(instruction.clone()),
+            }
+            if body.last() == Some(&Return) {
+                subroutines.push(Subroutine {
+                    label: label.clone(),
+                    body,
+                });
+                break;
+            }
+        }
+    }
+    subroutines
+}
+
+/// The number of `call`s of the given label, or 0 if the label is the target of a `tail_call`,
+/// which inlining does not account for.
+fn call_sites(program: &[LabelledInstruction], label: &str) -> usize {
+    let mut num_calls = 0;
+    for labelled_instruction in program {
+        match labelled_instruction {
+            LabelledInstruction::Instruction(Call(target)) if target == label => num_calls += 1,
+            LabelledInstruction::Instruction(TailCall(target)) if target == label => return 0,
+            _ => (),
+        }
+    }
+    num_calls
+}
+
+fn estimate_heights(
+    heights: &TableHeights,
+    cycle_delta: isize,
+    program_size_delta: isize,
+) -> TableHeights {
+    let mut unpadded_heights = heights.unpadded_heights;
+    for table in TableId::iter() {
+        let delta = match table {
+            TableId::ProgramTable | TableId::InstructionTable => program_size_delta,
+            TableId::ProcessorTable
+            | TableId::OpStackTable
+            | TableId::RamTable
+            | TableId::JumpStackTable => cycle_delta,
+            TableId::HashTable => 0,
+        };
+        let height = &mut unpadded_heights[table as usize];
+        *height = height.saturating_add_signed(delta);
+    }
+    let max_height = unpadded_heights.into_iter().max().unwrap_or_default();
+    TableHeights {
+        unpadded_heights,
+        padded_height: roundup_npo2(max_height as u64) as usize,
+    }
+}
+
+#[cfg(test)]
+mod balancing_tests {
+    use triton_opcodes::instruction::parse;
+
+    use crate::vm::simulate_no_input;
+
+    use super::*;
+
+    fn report(code: &str) -> BalancingReport {
+        let program = parse(code).unwrap();
+        let (aet, _) = simulate_no_input(&Program::new(&program)).unwrap();
+        let report = BalancingReport::new(&program, &aet, &CostModel::default());
+        println!("{report}");
+        report
+    }
+
+    #[test]
+    fn inlining_and_unrolling_save_cycles_test() {
+        let report = report(
+            "push 10 call loop pop halt \
+            loop: call inc push -1 add dup0 skiz recurse return \
+            inc: push 1 pop return",
+        );
+        assert_eq!(2, report.advice.len());
+
+        let inline = report
+            .advice
+            .iter()
+            .find(|advice| matches!(advice.transformation, Transformation::Inline { .. }))
+            .unwrap();
+        assert_eq!(-20, inline.cycle_delta);
+        assert_eq!(-3, inline.program_size_delta);
+        assert_eq!(-20, inline.cost_delta);
+        assert!(inline.is_recommended());
+
+        let unroll = report
+            .advice
+            .iter()
+            .find(|advice| matches!(advice.transformation, Transformation::Unroll { .. }))
+            .unwrap();
+        assert_eq!(-4, unroll.cycle_delta);
+        assert_eq!(8, unroll.program_size_delta);
+    }
+
+    #[test]
+    fn inlining_is_discouraged_if_program_crosses_padding_boundary_test() {
+        let code = format!(
+            "call big call big halt big: {} return unused: {} return",
+            "nop ".repeat(20),
+            "nop ".repeat(33)
+        );
+        let report = report(&code);
+        assert_eq!(TableId::ProgramTable, report.heights.tallest_table());
+        assert_eq!(64, report.heights.padded_height);
+
+        let [inline] = &report.advice[..] else {
+            panic!("Expected exactly one piece of advice.");
+        };
+        assert_eq!(15, inline.program_size_delta);
+        assert!(inline.cost_delta < 0);
+        assert!(inline.crosses_padding_boundary());
+        assert!(!inline.is_recommended());
+        assert_eq!(0, report.recommended().count());
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use itertools::Itertools;
+use serde_json::json;
+use triton_opcodes::instruction::label_addresses;
+use triton_opcodes::instruction::parse;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::vm::enclosing_label;
+use crate::vm::execute_to_last_state;
+
+/// The execution of the program, or of one call to a label, spanning consecutive clock cycles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSpan {
+    /// The label closest to, but not after, the first executed instruction.
+    pub label: String,
+
+    /// The clock cycle in which the `call` was executed, or 0 for the whole program.
+    pub start_cycle: u32,
+
+    /// The clock cycle right after the `return`, or right after the final `halt` if the call
+    /// never returned.
+    pub end_cycle: u32,
+
+    /// The number of enclosing spans.
+    pub depth: usize,
+}
+
+/// An execution of a program as nested spans, one per call, in Chrome's trace-event format.
+/// The JSON can be loaded into `about://tracing` or [Perfetto](https://ui.perfetto.dev), showing
+/// one microsecond per clock cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChromeTrace {
+    /// All spans, ordered by start cycle and depth. The first span is the whole program.
+    pub spans: Vec<CallSpan>,
+}
+
+impl ChromeTrace {
+    /// Execute the program, opening a span on every `call` and closing it on the matching
+    /// `return`.
+    pub fn new(
+        code: &str,
+        stdin: Vec<BFieldElement>,
+        secret_in: Vec<BFieldElement>,
+    ) -> Result<Self> {
+        let labelled_instructions = parse(code)?;
+        let program = Program::new(&labelled_instructions);
+        let labels = label_addresses(&labelled_instructions);
+        let label_at = |address: usize| -> String {
+            enclosing_label(&labels, address)
+                .map_or_else(|| format!("address {address}"), str::to_string)
+        };
+
+        let mut spans = vec![];
+        let mut open_spans = vec![(
+            CallSpan {
+                label: label_at(program.entry_point),
+                start_cycle: 0,
+                end_cycle: 0,
+                depth: 0,
+            },
+            0,
+        )];
+        let mut previous_jump_stack_height = 0;
+        let (final_state, err) = execute_to_last_state(&program, stdin, secret_in, |state, _| {
+            let jump_stack_height = state.jump_stack.len();
+            while open_spans
+                .last()
+                .is_some_and(|&(_, height)| jump_stack_height < height)
+            {
+                let (mut span, _) = open_spans.pop().unwrap();
+                span.end_cycle = state.cycle_count;
+                spans.push(span);
+            }
+
+            if jump_stack_height > previous_jump_stack_height {
+                let span = CallSpan {
+                    label: label_at(state.instruction_pointer),
+                    start_cycle: state.cycle_count - 1,
+                    end_cycle: 0,
+                    depth: open_spans.len(),
+                };
+                open_spans.push((span, jump_stack_height));
+            }
+            previous_jump_stack_height = jump_stack_height;
+        });
+        if let Some(err) = err {
+            return Err(err.into());
+        }
+
+        for (mut span, _) in open_spans.into_iter().rev() {
+            span.end_cycle = final_state.cycle_count + 1;
+            spans.push(span);
+        }
+        spans.sort_by_key(|span| (span.start_cycle, span.depth));
+
+        Ok(Self { spans })
+    }
+
+    /// Spans with identical labels, summed up: the label and the number of cycles spent in it,
+    /// including in nested calls, most expensive first.
+    pub fn inclusive_cycles(&self) -> Vec<(&str, u32)> {
+        let mut cycles: HashMap<&str, u32> = HashMap::new();
+        for span in self.spans.iter() {
+            *cycles.entry(&span.label).or_default() += span.end_cycle - span.start_cycle;
+        }
+        cycles
+            .into_iter()
+            .sorted_by_key(|&(label, cycles)| (std::cmp::Reverse(cycles), label))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        let events = self
+            .spans
+            .iter()
+            .map(|span| {
+                json!({
+                    "name": span.label,
+                    "cat": "call",
+                    "ph": "X",
+                    "ts": span.start_cycle,
+                    "dur": span.end_cycle - span.start_cycle,
+                    "pid": 0,
+                    "tid": 0,
+                    "args": { "depth": span.depth },
+                })
+            })
+            .collect_vec();
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ns",
+            "otherData": { "time unit": "1 µs corresponds to 1 clock cycle" },
+        })
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod chrome_trace_tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn span(label: &str, start_cycle: u32, end_cycle: u32, depth: usize) -> CallSpan {
+        CallSpan {
+            label: label.to_string(),
+            start_cycle,
+            end_cycle,
+            depth,
+        }
+    }
+
+    #[test]
+    fn calls_are_nested_by_jump_stack_test() {
+        let code = "call outer halt outer: call inner call inner return inner: nop return";
+        let trace = ChromeTrace::new(code, vec![], vec![]).unwrap();
+        let expected = vec![
+            span("address 0", 0, 9, 0),
+            span("outer", 0, 8, 1),
+            span("inner", 1, 4, 2),
+            span("inner", 4, 7, 2),
+        ];
+        assert_eq!(expected, trace.spans);
+    }
+
+    #[test]
+    fn recursion_stays_within_one_span_test() {
+        let code = "push 3 call countdown halt \
+            countdown: dup0 push 0 eq skiz return push -1 add recurse";
+        let trace = ChromeTrace::new(code, vec![], vec![]).unwrap();
+        assert_eq!(2, trace.spans.len());
+        let countdown = &trace.spans[1];
+        assert_eq!("countdown", countdown.label);
+        assert_eq!(1 + 3 * 7 + 5, countdown.end_cycle - countdown.start_cycle);
+    }
+
+    #[test]
+    fn unreturned_calls_end_with_execution_test() {
+        let code = "call foo foo: halt";
+        let trace = ChromeTrace::new(code, vec![], vec![]).unwrap();
+        let expected = vec![span("address 0", 0, 2, 0), span("foo", 0, 2, 1)];
+        assert_eq!(expected, trace.spans);
+    }
+
+    #[test]
+    fn json_contains_one_complete_event_per_span_test() {
+        let code = "push 1 call double write_io halt double: push 2 mul return";
+        let trace = ChromeTrace::new(code, vec![], vec![]).unwrap();
+        let json: Value = serde_json::from_str(&trace.to_json()).unwrap();
+        let events = json["traceEvents"].as_array().unwrap();
+        assert_eq!(trace.spans.len(), events.len());
+
+        let double = &events[1];
+        assert_eq!("double", double["name"]);
+        assert_eq!("X", double["ph"]);
+        assert_eq!(1, double["ts"]);
+        assert_eq!(4, double["dur"]);
+        assert_eq!(
+            vec![("address 0", 7), ("double", 4)],
+            trace.inclusive_cycles()
+        );
+    }
+}
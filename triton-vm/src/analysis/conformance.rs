@@ -0,0 +1,153 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+use ndarray::ArrayView1;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::instruction::RamAccess;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::table::table_column::BaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn::*;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// A state transition in an execution trace that deviates from the ISA specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceViolation {
+    /// The clock cycle in which the offending instruction was executed.
+    pub cycle: usize,
+
+    /// The offending instruction, arguments stripped.
+    pub instruction: Instruction,
+
+    pub description: String,
+}
+
+impl Display for ConformanceViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cycle = self.cycle;
+        let instruction = self.instruction;
+        let description = &self.description;
+        write!(f, "cycle {cycle}, instruction {instruction}: {description}")
+    }
+}
+
+/// Check every state transition recorded in the processor matrix of the given trace against the
+/// ISA's machine-readable description of instruction semantics, _i.e._,
+/// [`op_stack_size_influence`](Instruction::op_stack_size_influence) and
+/// [`ram_access`](Instruction::ram_access).
+///
+/// The check is independent of the AIR. It catches the case in which simulator and constraints
+/// agree with each other, but both deviate from the specification.
+pub fn check_conformance(aet: &AlgebraicExecutionTrace) -> Vec<ConformanceViolation> {
+    aet.processor_matrix
+        .rows()
+        .into_iter()
+        .enumerate()
+        .tuple_windows()
+        .flat_map(|((cycle, current_row), (_, next_row))| {
+            let instruction = current_instruction(current_row);
+            transition_violations(instruction, current_row, next_row)
+                .into_iter()
+                .map(move |description| ConformanceViolation {
+                    cycle,
+                    instruction,
+                    description,
+                })
+        })
+        .collect()
+}
+
+fn current_instruction(row: ArrayView1<BFieldElement>) -> Instruction {
+    row[CI.base_table_index()]
+        .value()
+        .try_into()
+        .expect("Trace must only contain valid opcodes.")
+}
+
+fn transition_violations(
+    instruction: Instruction,
+    current_row: ArrayView1<BFieldElement>,
+    next_row: ArrayView1<BFieldElement>,
+) -> Vec<String> {
+    let current = |column: ProcessorBaseTableColumn| current_row[column.base_table_index()];
+    let next = |column: ProcessorBaseTableColumn| next_row[column.base_table_index()];
+    let mut violations = vec![];
+
+    let expected_osp_change = instruction.op_stack_size_influence();
+    let actual_osp_change = next(OSP).value() as i64 - current(OSP).value() as i64;
+    if actual_osp_change != expected_osp_change as i64 {
+        violations.push(format!(
+            "op stack size changed by {actual_osp_change}, expected {expected_osp_change}"
+        ));
+    }
+
+    match instruction.ram_access() {
+        RamAccess::None => {
+            if next(RAMP) != current(RAMP) || next(RAMV) != current(RAMV) {
+                violations.push("RAM was accessed, expected no RAM access".to_string());
+            }
+        }
+        RamAccess::Read => {
+            if next(RAMP) != current(ST1) {
+                violations.push("RAM was not read at address st1".to_string());
+            }
+            if next(ST0) != next(RAMV) {
+                violations.push("value read from RAM was not put into st0".to_string());
+            }
+        }
+        RamAccess::Write => {
+            if next(RAMP) != current(ST1) {
+                violations.push("RAM was not written at address st1".to_string());
+            }
+            if next(RAMV) != current(ST0) {
+                violations.push("value st0 was not written to RAM".to_string());
+            }
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod conformance_tests {
+    use crate::test_corpus::bigger_tasm_test_programs;
+    use crate::test_corpus::property_based_test_programs;
+    use crate::test_corpus::small_tasm_test_programs;
+
+    use super::*;
+
+    #[test]
+    fn test_corpus_conforms_to_spec_test() {
+        let programs = small_tasm_test_programs()
+            .into_iter()
+            .chain(property_based_test_programs())
+            .chain(bigger_tasm_test_programs());
+        for program in programs {
+            let (aet, _) = program
+                .simulate()
+                .unwrap_or_else(|err| panic!("{}: {err}", program.source_code));
+            let violations = check_conformance(&aet);
+            assert!(
+                violations.is_empty(),
+                "{}\n{}",
+                program.source_code,
+                violations.iter().join("\n")
+            );
+        }
+    }
+
+    #[test]
+    fn tampered_trace_violates_spec_test() {
+        let program = small_tasm_test_programs().remove(1);
+        let (mut aet, _) = program.simulate().unwrap();
+        let tampered_cycle = 3;
+        aet.processor_matrix[[tampered_cycle + 1, OSP.base_table_index()]] += BFieldElement::new(1);
+
+        let violations = check_conformance(&aet);
+        assert!(!violations.is_empty());
+        assert!(violations
+            .iter()
+            .all(|v| v.cycle.abs_diff(tampered_cycle) <= 1));
+    }
+}
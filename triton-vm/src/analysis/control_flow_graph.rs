@@ -0,0 +1,377 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use anyhow::Result;
+use itertools::Itertools;
+use triton_opcodes::instruction::label_addresses;
+use triton_opcodes::instruction::parse;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::LabelledInstruction;
+use triton_opcodes::program::Program;
+
+/// A maximal sequence of instructions that is only ever entered at its first instruction and left
+/// after its last instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// The address of the block's first instruction.
+    pub start: usize,
+
+    /// The address right after the block's last instruction.
+    pub end: usize,
+
+    /// All labels pointing to the block's first instruction.
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EdgeKind {
+    /// Continue with the next instruction, including after `skiz` if the top of the stack is not
+    /// zero.
+    Fallthrough,
+
+    /// `skiz` skips the next instruction.
+    Skiz,
+
+    /// `call` jumps to the callee.
+    Call,
+
+    /// `return` jumps to the instruction following the `call` of the current function.
+    Return,
+
+    /// `recurse` jumps to the start of the current function.
+    Recurse,
+
+    /// `tail_call` jumps to the callee, which returns to the return sites of the current function.
+    TailCall,
+}
+
+impl EdgeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Fallthrough => "fallthrough",
+            EdgeKind::Skiz => "skiz",
+            EdgeKind::Call => "call",
+            EdgeKind::Return => "return",
+            EdgeKind::Recurse => "recurse",
+            EdgeKind::TailCall => "tail_call",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edge {
+    /// The index of the source block.
+    pub from: usize,
+
+    /// The index of the target block.
+    pub to: usize,
+
+    pub kind: EdgeKind,
+}
+
+/// The control-flow graph of a program, with basic blocks as nodes.
+///
+/// A function is everything reachable from the program's entry point or from the target of a
+/// `call` or `tail_call` without following `call`s or `tail_call`s. The `return`s and `recurse`s
+/// of a function lead to all return sites and the start of the function, respectively. A function
+/// reached through a `tail_call` returns to the return sites of the function it was tail-called
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    /// All basic blocks, ordered by address.
+    pub blocks: Vec<BasicBlock>,
+
+    /// All edges, ordered by source block, target block, and kind.
+    pub edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    pub fn from_code(code: &str) -> Result<Self> {
+        Ok(Self::new(&parse(code)?))
+    }
+
+    pub fn new(labelled_instructions: &[LabelledInstruction]) -> Self {
+        let program = Program::new(labelled_instructions);
+        let instructions = &program.instructions;
+
+        let mut labels: HashMap<usize, Vec<String>> = HashMap::new();
+        for (label, address) in label_addresses(labelled_instructions) {
+            labels.entry(address).or_default().push(label);
+        }
+
+        let mut leaders = BTreeSet::from([program.entry_point]);
+        leaders.extend(labels.keys().copied());
+        let mut address = 0;
+        while let Some(&instruction) = instructions.get(address) {
+            let next_address = address + instruction.size();
+            match instruction {
+                Call(_) | TailCall(_) | Return | Recurse | Halt => {
+                    leaders.insert(next_address);
+                }
+                Skiz => {
+                    leaders.insert(next_address);
+                    if let Some(skipped) = instructions.get(next_address) {
+                        leaders.insert(next_address + skipped.size());
+                    }
+                }
+                _ => (),
+            }
+            if let Call(target) | TailCall(target) = instruction {
+                leaders.insert(target.value() as usize);
+            }
+            address = next_address;
+        }
+        leaders.retain(|&leader| leader < instructions.len());
+
+        let starts = leaders.into_iter().collect_vec();
+        let blocks = starts
+            .iter()
+            .enumerate()
+            .map(|(index, &start)| {
+                let end = starts.get(index + 1).copied().unwrap_or(instructions.len());
+                let mut labels = labels.remove(&start).unwrap_or_default();
+                labels.sort();
+                BasicBlock { start, end, labels }
+            })
+            .collect_vec();
+        let block_index: HashMap<usize, usize> = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.start, index))
+            .collect();
+        let last_instruction = |block: &BasicBlock| {
+            let mut address = block.start;
+            while address + instructions[address].size() < block.end {
+                address += instructions[address].size();
+            }
+            (address, instructions[address])
+        };
+
+        // Edges that stay within a function.
+        let mut edges = BTreeSet::new();
+        let mut call_sites = vec![];
+        let mut tail_callees = vec![];
+        for (from, block) in blocks.iter().enumerate() {
+            let (address, instruction) = last_instruction(block);
+            let next_address = address + instruction.size();
+            let mut add_edge = |to_address: usize, kind| {
+                if let Some(&to) = block_index.get(&to_address) {
+                    edges.insert(Edge { from, to, kind });
+                }
+            };
+            match instruction {
+                Return | Recurse | Halt => (),
+                Call(target) => {
+                    let callee = target.value() as usize;
+                    add_edge(callee, EdgeKind::Call);
+                    call_sites.push((callee, next_address));
+                }
+                TailCall(target) => {
+                    let callee = target.value() as usize;
+                    add_edge(callee, EdgeKind::TailCall);
+                    tail_callees.push(callee);
+                }
+                Skiz => {
+                    add_edge(next_address, EdgeKind::Fallthrough);
+                    if let Some(skipped) = instructions.get(next_address) {
+                        add_edge(next_address + skipped.size(), EdgeKind::Skiz);
+                    }
+                }
+                _ => add_edge(next_address, EdgeKind::Fallthrough),
+            }
+        }
+
+        // Edges of `return` and `recurse` depend on the function a block belongs to.
+        let function_entries = call_sites
+            .iter()
+            .map(|&(callee, _)| callee)
+            .chain(tail_callees)
+            .chain([program.entry_point])
+            .unique()
+            .filter_map(|entry| block_index.get(&entry).copied())
+            .collect_vec();
+        let function_bodies = function_entries
+            .iter()
+            .map(|&entry_block| (entry_block, Self::function_body(entry_block, &edges)))
+            .collect_vec();
+
+        let mut return_sites: HashMap<usize, BTreeSet<usize>> = HashMap::new();
+        for (callee, return_site) in call_sites.iter() {
+            if let (Some(&callee), Some(&return_site)) =
+                (block_index.get(callee), block_index.get(return_site))
+            {
+                return_sites.entry(callee).or_default().insert(return_site);
+            }
+        }
+        let tail_calls = function_bodies
+            .iter()
+            .flat_map(|(entry_block, body)| body.iter().map(move |&from| (*entry_block, from)))
+            .filter_map(
+                |(entry_block, from)| match last_instruction(&blocks[from]).1 {
+                    TailCall(target) => block_index
+                        .get(&(target.value() as usize))
+                        .map(|&callee| (entry_block, callee)),
+                    _ => None,
+                },
+            )
+            .collect_vec();
+        let mut return_sites_changed = true;
+        while return_sites_changed {
+            return_sites_changed = false;
+            for &(caller, callee) in tail_calls.iter() {
+                let caller_return_sites = return_sites.get(&caller).cloned().unwrap_or_default();
+                let callee_return_sites = return_sites.entry(callee).or_default();
+                for return_site in caller_return_sites {
+                    return_sites_changed |= callee_return_sites.insert(return_site);
+                }
+            }
+        }
+
+        let mut function_edges = vec![];
+        for (entry_block, body) in function_bodies {
+            let function_return_sites = return_sites.remove(&entry_block).unwrap_or_default();
+            for from in body {
+                match last_instruction(&blocks[from]).1 {
+                    Return => function_edges.extend(function_return_sites.iter().map(|&to| Edge {
+                        from,
+                        to,
+                        kind: EdgeKind::Return,
+                    })),
+                    Recurse => function_edges.push(Edge {
+                        from,
+                        to: entry_block,
+                        kind: EdgeKind::Recurse,
+                    }),
+                    _ => (),
+                }
+            }
+        }
+        edges.extend(function_edges);
+
+        Self {
+            blocks,
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// All blocks reachable from the given block without following `call`s.
+    fn function_body(entry_block: usize, edges: &BTreeSet<Edge>) -> BTreeSet<usize> {
+        let mut body = BTreeSet::from([entry_block]);
+        let mut worklist = vec![entry_block];
+        while let Some(block) = worklist.pop() {
+            let successors = edges
+                .iter()
+                .filter(|edge| edge.from == block)
+                .filter(|edge| !matches!(edge.kind, EdgeKind::Call | EdgeKind::TailCall))
+                .map(|edge| edge.to);
+            for successor in successors {
+                if body.insert(successor) {
+                    worklist.push(successor);
+                }
+            }
+        }
+        body
+    }
+
+    /// The graph in Graphviz's DOT language. Every node shows the labels and the address range of
+    /// its basic block.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph cfg {{").unwrap();
+        writeln!(dot, "  node [shape=box, fontname=monospace];").unwrap();
+        for (index, block) in self.blocks.iter().enumerate() {
+            let labels = block.labels.iter().map(|label| format!("{label}:\\l"));
+            let range = format!("[{}, {})\\l", block.start, block.end);
+            let node_label = labels.chain([range]).join("");
+            writeln!(dot, "  b{index} [label=\"{node_label}\"];").unwrap();
+        }
+        for edge in self.edges.iter() {
+            let Edge { from, to, kind } = edge;
+            writeln!(dot, "  b{from} -> b{to} [label=\"{}\"];", kind.name()).unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+#[cfg(test)]
+mod control_flow_graph_tests {
+    use super::*;
+
+    fn edge(from: usize, to: usize, kind: EdgeKind) -> Edge {
+        Edge { from, to, kind }
+    }
+
+    #[test]
+    fn straight_line_program_is_one_block_test() {
+        let cfg = ControlFlowGraph::from_code("push 1 push 2 add pop halt").unwrap();
+        let expected_block = BasicBlock {
+            start: 0,
+            end: 7,
+            labels: vec![],
+        };
+        assert_eq!(vec![expected_block], cfg.blocks);
+        assert!(cfg.edges.is_empty());
+    }
+
+    #[test]
+    fn skiz_splits_into_three_blocks_test() {
+        let cfg = ControlFlowGraph::from_code("push 0 skiz push 1 push 2 halt").unwrap();
+        let starts = cfg.blocks.iter().map(|block| block.start).collect_vec();
+        assert_eq!(vec![0, 3, 5], starts);
+        let expected_edges = vec![
+            edge(0, 1, EdgeKind::Fallthrough),
+            edge(0, 2, EdgeKind::Skiz),
+            edge(1, 2, EdgeKind::Fallthrough),
+        ];
+        assert_eq!(expected_edges, cfg.edges);
+    }
+
+    #[test]
+    fn call_return_and_recurse_edges_test() {
+        let code = "push 3 call countdown halt \
+            countdown: dup0 skiz return push -1 add recurse";
+        let cfg = ControlFlowGraph::from_code(code).unwrap();
+        let starts = cfg.blocks.iter().map(|block| block.start).collect_vec();
+        // push 3 call countdown | halt | countdown: dup0 skiz | return | push -1 add recurse
+        assert_eq!(vec![0, 4, 5, 8, 9], starts);
+        assert_eq!(vec!["countdown".to_string()], cfg.blocks[2].labels);
+
+        let expected_edges = vec![
+            edge(0, 2, EdgeKind::Call),
+            edge(2, 3, EdgeKind::Fallthrough),
+            edge(2, 4, EdgeKind::Skiz),
+            edge(3, 1, EdgeKind::Return),
+            edge(4, 2, EdgeKind::Recurse),
+        ];
+        assert_eq!(expected_edges, cfg.edges);
+    }
+
+    #[test]
+    fn tail_called_function_returns_to_caller_of_tail_calling_function_test() {
+        let code = "call foo halt foo: tail_call bar bar: return";
+        let cfg = ControlFlowGraph::from_code(code).unwrap();
+        let starts = cfg.blocks.iter().map(|block| block.start).collect_vec();
+        // call foo | halt | foo: tail_call bar | bar: return
+        assert_eq!(vec![0, 2, 3, 5], starts);
+
+        let expected_edges = vec![
+            edge(0, 2, EdgeKind::Call),
+            edge(2, 3, EdgeKind::TailCall),
+            edge(3, 1, EdgeKind::Return),
+        ];
+        assert_eq!(expected_edges, cfg.edges);
+    }
+
+    #[test]
+    fn dot_output_contains_all_nodes_and_edges_test() {
+        let code = "call foo halt foo: push 1 pop return";
+        let dot = ControlFlowGraph::from_code(code).unwrap().to_dot();
+        println!("{dot}");
+        assert!(dot.starts_with("digraph cfg {"));
+        assert!(dot.contains("b2 [label=\"foo:\\l[3, 7)\\l\"];"));
+        assert!(dot.contains("b0 -> b2 [label=\"call\"];"));
+        assert!(dot.contains("b2 -> b1 [label=\"return\"];"));
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use strum::EnumCount;
+use strum::IntoEnumIterator;
+use triton_opcodes::instruction::AnInstruction::Hash;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::instruction::LabelledInstruction;
+use twenty_first::shared_math::rescue_prime_regular::NUM_ROUNDS;
+
+use crate::table::master_table::TableId;
+use crate::table::table_column::BaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn::CI;
+use crate::table::table_column::ProcessorBaseTableColumn::IP;
+use crate::vm::enclosing_label;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// How much a single row in each of the tables counts towards the cost of executing an
+/// instruction. Since all tables are padded to the same height, a program's proving cost is
+/// determined by its tallest table, not by its number of clock cycles alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostModel {
+    table_weights: [usize; TableId::COUNT],
+}
+
+impl Default for CostModel {
+    /// Counts rows in the Processor Table and the Hash Table. All other tables either grow in
+    /// lockstep with the Processor Table or depend only on the program.
+    fn default() -> Self {
+        Self::zero()
+            .with_weight(TableId::ProcessorTable, 1)
+            .with_weight(TableId::HashTable, 1)
+    }
+}
+
+impl CostModel {
+    fn zero() -> Self {
+        Self {
+            table_weights: [0; TableId::COUNT],
+        }
+    }
+
+    /// The cost model counting raw clock cycles.
+    pub fn cycles() -> Self {
+        Self::zero().with_weight(TableId::ProcessorTable, 1)
+    }
+
+    pub fn with_weight(mut self, table: TableId, weight: usize) -> Self {
+        self.table_weights[table as usize] = weight;
+        self
+    }
+
+    pub fn weight(&self, table: TableId) -> usize {
+        self.table_weights[table as usize]
+    }
+
+    /// The number of rows executing the given instruction adds to the given table.
+    pub fn rows_added(table: TableId, instruction: Instruction) -> usize {
+        match table {
+            TableId::ProgramTable => 0,
+            TableId::InstructionTable
+            | TableId::ProcessorTable
+            | TableId::OpStackTable
+            | TableId::RamTable
+            | TableId::JumpStackTable => 1,
+            TableId::HashTable => match instruction {
+                Hash => NUM_ROUNDS + 1,
+                _ => 0,
+            },
+        }
+    }
+
+    /// The weighted sum of the rows executing the given instruction adds to all tables.
+    pub fn cost(&self, instruction: Instruction) -> usize {
+        TableId::iter()
+            .map(|table| self.weight(table) * Self::rows_added(table, instruction))
+            .sum()
+    }
+
+    /// The weighted sum of the rows executing the given straight-line code adds to all tables,
+    /// _e.g._, of the expansion of a pseudo-instruction. Labels cost nothing.
+    pub fn cost_of_code(&self, code: &[LabelledInstruction]) -> usize {
+        code.iter()
+            .filter_map(|labelled_instruction| match labelled_instruction {
+                LabelledInstruction::Instruction(instruction) => Some(instruction.opcode()),
+                LabelledInstruction::Label(_) => None,
+            })
+            .map(|opcode| Instruction::try_from(opcode).unwrap())
+            .map(|instruction| self.cost(instruction))
+            .sum()
+    }
+}
+
+/// The clock cycles and the cost spent in the code following one label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostProfileEntry {
+    /// The label closest to, but not after, the executed instructions. `None` for instructions
+    /// preceding all labels.
+    pub label: Option<String>,
+
+    pub cycles: usize,
+
+    /// The cycles weighted according to the [`CostModel`].
+    pub cost: usize,
+}
+
+/// Attributes the cost of executing a program to the labels of the program, revealing the hot
+/// spots. Depending on the [`CostModel`], these are the hot spots in terms of proving cost or in
+/// terms of clock cycles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostProfile {
+    pub total_cycles: usize,
+    pub total_cost: usize,
+
+    /// All labels in which at least one instruction was executed, most costly label first.
+    pub entries: Vec<CostProfileEntry>,
+}
+
+impl CostProfile {
+    /// Profile the given trace, given all labels and their addresses, for example as returned
+    /// by `label_addresses`.
+    pub fn new(
+        trace: &AlgebraicExecutionTrace,
+        labels: &HashMap<String, usize>,
+        cost_model: &CostModel,
+    ) -> Self {
+        let mut cycles_and_cost: HashMap<Option<&str>, (usize, usize)> = HashMap::new();
+        for row in trace.processor_matrix.rows() {
+            let ip = row[IP.base_table_index()].value() as usize;
+            let opcode = row[CI.base_table_index()].value();
+            let instruction: Instruction = opcode
+                .try_into()
+                .expect("Trace must only contain valid opcodes.");
+            let (cycles, cost) = cycles_and_cost
+                .entry(enclosing_label(labels, ip))
+                .or_insert((0, 0));
+            *cycles += 1;
+            *cost += cost_model.cost(instruction);
+        }
+
+        let entries = cycles_and_cost
+            .into_iter()
+            .map(|(label, (cycles, cost))| CostProfileEntry {
+                label: label.map(str::to_string),
+                cycles,
+                cost,
+            })
+            .sorted_by_key(|entry| (std::cmp::Reverse(entry.cost), entry.label.clone()))
+            .collect_vec();
+
+        Self {
+            total_cycles: entries.iter().map(|entry| entry.cycles).sum(),
+            total_cost: entries.iter().map(|entry| entry.cost).sum(),
+            entries,
+        }
+    }
+}
+
+impl Display for CostProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "| Label                          |   Cycles |     Cost | Share |"
+        )?;
+        writeln!(
+            f,
+            "|:-------------------------------|---------:|---------:|------:|"
+        )?;
+        for entry in self.entries.iter() {
+            let label = entry.label.as_deref().unwrap_or("(before first label)");
+            let share = 100 * entry.cost / self.total_cost.max(1);
+            writeln!(
+                f,
+                "| {label:<30} | {:>8} | {:>8} | {share:>4}% |",
+                entry.cycles, entry.cost,
+            )?;
+        }
+        write!(
+            f,
+            "Total: {} clock cycles, cost {}",
+            self.total_cycles, self.total_cost
+        )
+    }
+}
+
+#[cfg(test)]
+mod cost_profile_tests {
+    use triton_opcodes::instruction::label_addresses;
+    use triton_opcodes::instruction::parse;
+    use triton_opcodes::program::Program;
+    use triton_opcodes::pseudo_instruction::expand_pseudo_instruction;
+
+    use crate::test_corpus::test_hash_nop_nop_lt;
+    use crate::vm::simulate_no_input;
+
+    use super::*;
+
+    fn profile(code: &str, cost_model: &CostModel) -> CostProfile {
+        let program = Program::from_code(code).unwrap();
+        let (aet, _) = simulate_no_input(&program).unwrap();
+        let labels = label_addresses(&parse(code).unwrap());
+        CostProfile::new(&aet, &labels, cost_model)
+    }
+
+    #[test]
+    fn hashing_is_hot_spot_only_when_weighing_hash_rows_test() {
+        let code = "call hashing call looping halt \
+            hashing: hash return \
+            looping: push 2 call loop pop return \
+            loop: push -1 add dup0 skiz recurse return";
+
+        let cycle_profile = profile(code, &CostModel::cycles());
+        assert_eq!(Some("loop"), cycle_profile.entries[0].label.as_deref());
+        assert_eq!(cycle_profile.total_cycles, cycle_profile.total_cost);
+
+        let cost_profile = profile(code, &CostModel::default());
+        assert_eq!(Some("hashing"), cost_profile.entries[0].label.as_deref());
+        assert_eq!(2 + NUM_ROUNDS + 1, cost_profile.entries[0].cost);
+        assert_eq!(cycle_profile.total_cycles, cost_profile.total_cycles);
+    }
+
+    #[test]
+    fn instructions_before_first_label_are_attributed_to_no_label_test() {
+        let cost_profile = profile("push 1 pop halt", &CostModel::default());
+        assert_eq!(1, cost_profile.entries.len());
+        assert_eq!(None, cost_profile.entries[0].label);
+        assert_eq!(3, cost_profile.entries[0].cycles);
+    }
+
+    #[test]
+    fn ignoring_all_tables_costs_nothing_test() {
+        let cost_model = CostModel::zero();
+        let cost_profile = profile(&test_hash_nop_nop_lt().source_code, &cost_model);
+        assert_eq!(0, cost_profile.total_cost);
+        println!("{cost_profile}");
+    }
+
+    #[test]
+    fn spilling_to_ram_is_cheaper_than_writing_element_by_element_test() {
+        let cost_model = CostModel::default();
+        let spill = expand_pseudo_instruction("spill5").unwrap();
+        let reload = expand_pseudo_instruction("reload5").unwrap();
+        assert_eq!(
+            spill.num_cycles(),
+            cost_model.cost_of_code(&spill.instructions)
+        );
+        assert_eq!(
+            8 * 5,
+            cost_model.cost_of_code(&spill.instructions)
+                + cost_model.cost_of_code(&reload.instructions)
+        );
+
+        let write_one_element = "swap1 write_mem pop push 1 add ";
+        let write_element_by_element = parse(&write_one_element.repeat(5)).unwrap();
+        assert!(
+            cost_model.cost_of_code(&spill.instructions)
+                < cost_model.cost_of_code(&write_element_by_element)
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use anyhow::bail;
+use anyhow::Result;
+use triton_opcodes::instruction::label_addresses;
+use triton_opcodes::instruction::parse;
+use triton_opcodes::lexer::tokenize;
+use triton_opcodes::lexer::TokenKind;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::vm::execute_to_last_state;
+
+const ANNOTATION_PREFIX: &str = "max_cycles:";
+
+/// The maximum number of clock cycles each annotated label may take per call, keyed by the label.
+///
+/// A label is annotated with a comment `// max_cycles: 512`, either on the same line after the
+/// label, or anywhere before the label but after the previous label. Local labels are mangled,
+/// _i.e._, a local label `.loop` following global label `foo:` is found as `foo.loop`.
+pub fn cycle_budgets(code: &str) -> Result<HashMap<String, u32>> {
+    let mut budgets = HashMap::new();
+    let mut pending_budget = None;
+    let mut scope = None;
+    let mut previous_label = None;
+    let mut previous_label_line_end = 0;
+
+    for token in tokenize(code) {
+        match token.kind {
+            TokenKind::LabelDefinition => {
+                let unmangled_label = token.text.trim_end_matches(':');
+                let is_local_label = unmangled_label.starts_with('.');
+                let label = match scope {
+                    Some(scope) if is_local_label => format!("{scope}{unmangled_label}"),
+                    _ => unmangled_label.to_string(),
+                };
+                if !is_local_label {
+                    scope = Some(unmangled_label);
+                }
+                if let Some(budget) = pending_budget.take() {
+                    budgets.insert(label.clone(), budget);
+                }
+                previous_label_line_end = code[token.span.end..]
+                    .find('\n')
+                    .map_or(code.len(), |offset| token.span.end + offset);
+                previous_label = Some(label);
+            }
+            TokenKind::Comment => {
+                let comment = token.text.trim_start_matches('/').trim();
+                let Some(budget) = comment.strip_prefix(ANNOTATION_PREFIX) else {
+                    continue;
+                };
+                let Ok(budget) = budget.trim().parse() else {
+                    bail!("Malformed cycle budget annotation: {}", token.text);
+                };
+                let annotates_previous_label = token.span.start < previous_label_line_end;
+                match &previous_label {
+                    Some(label) if annotates_previous_label => {
+                        budgets.insert(label.clone(), budget);
+                    }
+                    _ => pending_budget = Some(budget),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if pending_budget.is_some() {
+        bail!("Cycle budget annotation is not followed by a label.");
+    }
+    Ok(budgets)
+}
+
+/// A call to an annotated label that took more clock cycles than its budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleBudgetViolation {
+    pub label: String,
+    pub budget: u32,
+
+    /// The number of clock cycles the call took, including the `call` and the `return`.
+    pub cycles: u32,
+
+    /// The clock cycle in which the `call` was executed.
+    pub call_cycle: u32,
+}
+
+impl Display for CycleBudgetViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "call to `{}` in cycle {} took {} cycles, exceeding its budget of {}",
+            self.label, self.call_cycle, self.cycles, self.budget
+        )
+    }
+}
+
+/// Checks every call to an annotated label against the label's cycle budget. Catches performance
+/// regressions in Triton assembly libraries long before they show up in proving cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleBudgetReport {
+    /// The number of calls to annotated labels.
+    pub num_checked_calls: usize,
+
+    pub violations: Vec<CycleBudgetViolation>,
+}
+
+/// A call to an annotated label that has not returned yet.
+struct OpenCall {
+    label: String,
+    budget: u32,
+    call_cycle: u32,
+    jump_stack_height: usize,
+}
+
+impl OpenCall {
+    fn check(&self, current_cycle: u32) -> Option<CycleBudgetViolation> {
+        let cycles = current_cycle - self.call_cycle;
+        (cycles > self.budget).then(|| CycleBudgetViolation {
+            label: self.label.clone(),
+            budget: self.budget,
+            cycles,
+            call_cycle: self.call_cycle,
+        })
+    }
+}
+
+impl CycleBudgetReport {
+    /// Execute the program, measuring every call to a label annotated with a cycle budget. Calls
+    /// that have not returned when execution halts are measured up to that point.
+    pub fn new(
+        code: &str,
+        stdin: Vec<BFieldElement>,
+        secret_in: Vec<BFieldElement>,
+    ) -> Result<Self> {
+        let budgets = cycle_budgets(code)?;
+        let labelled_instructions = parse(code)?;
+        let program = Program::new(&labelled_instructions);
+        let budgets_by_address: HashMap<usize, (String, u32)> =
+            label_addresses(&labelled_instructions)
+                .into_iter()
+                .filter_map(|(label, address)| {
+                    let budget = *budgets.get(&label)?;
+                    Some((address, (label, budget)))
+                })
+                .collect();
+
+        let mut num_checked_calls = 0;
+        let mut violations = vec![];
+        let mut open_calls: Vec<OpenCall> = vec![];
+        let mut previous_jump_stack_height = 0;
+        let (final_state, err) = execute_to_last_state(&program, stdin, secret_in, |state, _| {
+            let jump_stack_height = state.jump_stack.len();
+            while open_calls
+                .last()
+                .is_some_and(|call| jump_stack_height < call.jump_stack_height)
+            {
+                let call = open_calls.pop().unwrap();
+                violations.extend(call.check(state.cycle_count));
+            }
+
+            let is_call = jump_stack_height > previous_jump_stack_height;
+            if let Some((label, budget)) = budgets_by_address.get(&state.instruction_pointer) {
+                if is_call {
+                    num_checked_calls += 1;
+                    open_calls.push(OpenCall {
+                        label: label.clone(),
+                        budget: *budget,
+                        call_cycle: state.cycle_count - 1,
+                        jump_stack_height,
+                    });
+                }
+            }
+            previous_jump_stack_height = jump_stack_height;
+        });
+        if let Some(err) = err {
+            return Err(err.into());
+        }
+
+        for call in open_calls.iter().rev() {
+            violations.extend(call.check(final_state.cycle_count));
+        }
+        violations.sort_by_key(|violation| violation.call_cycle);
+
+        Ok(Self {
+            num_checked_calls,
+            violations,
+        })
+    }
+}
+
+impl Display for CycleBudgetReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} checked calls, {} over budget",
+            self.num_checked_calls,
+            self.violations.len()
+        )?;
+        for violation in self.violations.iter() {
+            writeln!(f, "  {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod cycle_budget_tests {
+    use super::*;
+
+    #[test]
+    fn parse_cycle_budgets_test() {
+        let code = "
+            call foo call bar halt
+            // max_cycles: 10
+            foo: return
+            bar: // max_cycles: 20
+            call .baz return
+            .baz: return // max_cycles: 30
+        ";
+        let budgets = cycle_budgets(code).unwrap();
+        let expected = HashMap::from([
+            ("foo".to_string(), 10),
+            ("bar".to_string(), 20),
+            ("bar.baz".to_string(), 30),
+        ]);
+        assert_eq!(expected, budgets);
+    }
+
+    #[test]
+    fn malformed_or_dangling_annotations_are_rejected_test() {
+        assert!(cycle_budgets("foo: return // max_cycles: many").is_err());
+        assert!(cycle_budgets("halt // max_cycles: 5").is_err());
+    }
+
+    #[test]
+    fn calls_within_budget_are_not_reported_test() {
+        let code = "call foo call foo halt foo: // max_cycles: 3\n nop return";
+        let report = CycleBudgetReport::new(code, vec![], vec![]).unwrap();
+        assert_eq!(2, report.num_checked_calls);
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn calls_over_budget_are_reported_test() {
+        let code = "
+            push 2 call countdown push 5 call countdown halt
+            // max_cycles: 20
+            countdown: dup0 push 0 eq skiz return push -1 add recurse
+        ";
+        let report = CycleBudgetReport::new(code, vec![], vec![]).unwrap();
+        println!("{report}");
+        assert_eq!(2, report.num_checked_calls);
+        assert_eq!(1, report.violations.len());
+
+        let violation = &report.violations[0];
+        assert_eq!("countdown", violation.label);
+        assert_eq!(20, violation.budget);
+        // 1 call, 5 iterations of 7 cycles, and 5 cycles to return
+        assert_eq!(1 + 5 * 7 + 5, violation.cycles);
+    }
+
+    #[test]
+    fn nested_calls_are_measured_separately_test() {
+        let code = "
+            call outer halt
+            outer: // max_cycles: 4
+            call inner call inner return
+            inner: // max_cycles: 2
+            return
+        ";
+        let report = CycleBudgetReport::new(code, vec![], vec![]).unwrap();
+        assert_eq!(3, report.num_checked_calls);
+        assert_eq!(1, report.violations.len());
+        assert_eq!("outer", report.violations[0].label);
+        assert_eq!(6, report.violations[0].cycles);
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use triton_opcodes::instruction::label_addresses;
+use triton_opcodes::instruction::parse;
+use triton_opcodes::instruction::AnInstruction::Hash;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::program::Program;
+use triton_profiler::folded_stacks::FoldedStacks;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::vm::enclosing_label;
+use crate::vm::execute_to_last_state;
+
+/// The cost of an execution, attributed to call chains of labels, ready for standard flamegraph
+/// tooling. The outermost frame of every call chain is the label of the program's entry point.
+///
+/// A `call` and its matching `return` are attributed to the callee. The final `halt` is attributed
+/// like any other instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionFlamegraph {
+    /// Clock cycles per call chain.
+    pub cycles: FoldedStacks,
+
+    /// Executed instructions `hash` per call chain.
+    pub hashes: FoldedStacks,
+}
+
+impl ExecutionFlamegraph {
+    pub fn new(
+        code: &str,
+        stdin: Vec<BFieldElement>,
+        secret_in: Vec<BFieldElement>,
+    ) -> Result<Self> {
+        let labelled_instructions = parse(code)?;
+        let program = Program::new(&labelled_instructions);
+        let labels = label_addresses(&labelled_instructions);
+        let label_at = |address: usize| -> String {
+            enclosing_label(&labels, address)
+                .map_or_else(|| format!("address {address}"), str::to_string)
+        };
+
+        let mut cycles = FoldedStacks::new();
+        let mut hashes = FoldedStacks::new();
+        let mut attribute = |call_chain: &[String], instruction: Instruction| {
+            cycles.add(call_chain, 1);
+            if instruction == Hash {
+                hashes.add(call_chain, 1);
+            }
+        };
+
+        // The call chain and, for every frame, the height of the jump stack while in it.
+        let mut call_chain = vec![label_at(program.entry_point)];
+        let mut jump_stack_heights = vec![0];
+        let (final_state, err) = execute_to_last_state(&program, stdin, secret_in, |state, _| {
+            if state.cycle_count == 0 {
+                return;
+            }
+            let instruction: Instruction = state
+                .previous_instruction
+                .value()
+                .try_into()
+                .expect("Executed instructions must have valid opcodes.");
+            let jump_stack_height = state.jump_stack.len();
+            let current_height = *jump_stack_heights.last().unwrap();
+
+            if jump_stack_height > current_height {
+                call_chain.push(label_at(state.instruction_pointer));
+                jump_stack_heights.push(jump_stack_height);
+            }
+            attribute(&call_chain, instruction);
+            while jump_stack_height < *jump_stack_heights.last().unwrap() {
+                call_chain.pop();
+                jump_stack_heights.pop();
+            }
+        });
+        if let Some(err) = err {
+            return Err(err.into());
+        }
+        attribute(&call_chain, final_state.current_instruction()?);
+
+        Ok(Self { cycles, hashes })
+    }
+}
+
+#[cfg(test)]
+mod flamegraph_tests {
+    use super::*;
+
+    #[test]
+    fn cycles_are_attributed_to_call_chains_test() {
+        let code = "main: call outer halt \
+            outer: call inner call inner return \
+            inner: nop return";
+        let flamegraph = ExecutionFlamegraph::new(code, vec![], vec![]).unwrap();
+        let cycles = &flamegraph.cycles;
+        println!("{cycles}");
+
+        assert_eq!(1, cycles.weight(&["main"]));
+        assert_eq!(2, cycles.weight(&["main", "outer"]));
+        assert_eq!(6, cycles.weight(&["main", "outer", "inner"]));
+        assert_eq!(9, cycles.total_weight());
+        assert!(flamegraph.hashes.is_empty());
+    }
+
+    #[test]
+    fn hashes_are_attributed_to_call_chains_test() {
+        let code = "main: hash call hashing call hashing halt \
+            hashing: hash hash return";
+        let flamegraph = ExecutionFlamegraph::new(code, vec![], vec![]).unwrap();
+        let hashes = &flamegraph.hashes;
+        assert_eq!(1, hashes.weight(&["main"]));
+        assert_eq!(4, hashes.weight(&["main", "hashing"]));
+        assert_eq!("main 1\nmain;hashing 4\n", hashes.to_string());
+    }
+
+    #[test]
+    fn recursion_does_not_deepen_call_chain_test() {
+        let code = "push 3 call countdown halt \
+            countdown: dup0 push 0 eq skiz return push -1 add recurse";
+        let flamegraph = ExecutionFlamegraph::new(code, vec![], vec![]).unwrap();
+        let countdown = ["address 0", "countdown"];
+        assert_eq!(1 + 3 * 7 + 5, flamegraph.cycles.weight(&countdown));
+        assert_eq!(
+            flamegraph.cycles.total_weight(),
+            flamegraph.cycles.inclusive_weight(&["address 0"])
+        );
+    }
+}
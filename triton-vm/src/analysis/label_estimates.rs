@@ -0,0 +1,207 @@
+//! Static estimates of the rows the code following each label adds to the tables per invocation,
+//! derived from the [`CostModel`] without executing the program. Annotating the program's listing
+//! with these estimates gives authors feedback while writing code, long before a full run.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::ops::Add;
+
+use anyhow::Result;
+use itertools::Itertools;
+use triton_opcodes::instruction::label_addresses;
+use triton_opcodes::instruction::parse;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::instruction::LabelledInstruction;
+use triton_opcodes::program::Program;
+
+use crate::analysis::cost_profile::CostModel;
+use crate::table::master_table::TableId;
+
+const INDENTATION: &str = "    ";
+
+/// The rows one invocation adds to the tables whose height depends on the executed code. All other
+/// tables grow in lockstep with the Processor Table, or depend only on the program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowEstimate {
+    pub processor_rows: usize,
+    pub hash_rows: usize,
+}
+
+impl RowEstimate {
+    fn of(instruction: Instruction) -> Self {
+        Self {
+            processor_rows: CostModel::rows_added(TableId::ProcessorTable, instruction),
+            hash_rows: CostModel::rows_added(TableId::HashTable, instruction),
+        }
+    }
+}
+
+impl Add for RowEstimate {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            processor_rows: self.processor_rows + rhs.processor_rows,
+            hash_rows: self.hash_rows + rhs.hash_rows,
+        }
+    }
+}
+
+impl Display for RowEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} processor rows, {} hash rows",
+            self.processor_rows, self.hash_rows
+        )
+    }
+}
+
+/// Estimates the rows of one invocation of the code at every label.
+///
+/// Every instruction reachable from the label without following `call`s counts once: loops count
+/// as a single iteration, and both the instruction `skiz` might skip and the one after it count.
+/// A `call` or `tail_call` adds the estimate of the callee. Recursive calls add nothing.
+pub fn label_estimates(
+    labelled_instructions: &[LabelledInstruction],
+) -> HashMap<String, RowEstimate> {
+    let program = Program::new(labelled_instructions);
+    label_addresses(labelled_instructions)
+        .into_iter()
+        .map(|(label, address)| {
+            // fresh per label, keeping estimates of mutually recursive code deterministic
+            let mut estimator = Estimator {
+                program: &program,
+                estimates: HashMap::new(),
+            };
+            (label, estimator.estimate(address))
+        })
+        .collect()
+}
+
+/// The listing of the given Triton assembly: every label on its own line, annotated with the
+/// [estimate](label_estimates) of one invocation, and every instruction on its own indented line.
+/// Control structures appear in their lowered form.
+pub fn annotated_listing(code: &str) -> Result<String> {
+    let labelled_instructions = parse(code)?;
+    let estimates = label_estimates(&labelled_instructions);
+    let listing = labelled_instructions
+        .iter()
+        .map(|labelled_instruction| match labelled_instruction {
+            LabelledInstruction::Label(label) => {
+                format!("{label}: // per invocation: {}\n", estimates[label])
+            }
+            LabelledInstruction::Instruction(instruction) => {
+                format!("{INDENTATION}{instruction}\n")
+            }
+        })
+        .join("");
+    Ok(listing)
+}
+
+struct Estimator<'pgm> {
+    program: &'pgm Program,
+
+    /// The estimates keyed by address. `None` while the code at that address is being estimated.
+    estimates: HashMap<usize, Option<RowEstimate>>,
+}
+
+impl<'pgm> Estimator<'pgm> {
+    fn estimate(&mut self, entry: usize) -> RowEstimate {
+        if let Some(estimate) = self.estimates.get(&entry) {
+            return estimate.unwrap_or_default();
+        }
+        self.estimates.insert(entry, None);
+
+        let mut estimate = RowEstimate::default();
+        let mut visited = HashSet::new();
+        let mut worklist = vec![entry];
+        while let Some(address) = worklist.pop() {
+            if !visited.insert(address) {
+                continue;
+            }
+            let Some(&instruction) = self.program.instructions.get(address) else {
+                continue;
+            };
+            estimate = estimate + RowEstimate::of(instruction);
+
+            let next_address = address + instruction.size();
+            match instruction {
+                Halt | Return | Recurse => (),
+                Call(target) => {
+                    estimate = estimate + self.estimate(target.value() as usize);
+                    worklist.push(next_address);
+                }
+                TailCall(target) => estimate = estimate + self.estimate(target.value() as usize),
+                Skiz => {
+                    worklist.push(next_address);
+                    if let Some(skipped) = self.program.instructions.get(next_address) {
+                        worklist.push(next_address + skipped.size());
+                    }
+                }
+                _ => worklist.push(next_address),
+            }
+        }
+
+        self.estimates.insert(entry, Some(estimate));
+        estimate
+    }
+}
+
+#[cfg(test)]
+mod label_estimates_tests {
+    use twenty_first::shared_math::rescue_prime_regular::NUM_ROUNDS;
+
+    use super::*;
+
+    fn estimates(code: &str) -> HashMap<String, RowEstimate> {
+        label_estimates(&parse(code).unwrap())
+    }
+
+    #[test]
+    fn estimate_includes_callees_test() {
+        let code = "call main halt \
+            main: push 1 call foo pop return \
+            foo: hash pop pop pop pop pop return";
+        let estimates = estimates(code);
+        let foo = RowEstimate {
+            processor_rows: 7,
+            hash_rows: NUM_ROUNDS + 1,
+        };
+        assert_eq!(foo, estimates["foo"]);
+        let main = RowEstimate {
+            processor_rows: 4 + foo.processor_rows,
+            hash_rows: foo.hash_rows,
+        };
+        assert_eq!(main, estimates["main"]);
+    }
+
+    #[test]
+    fn loops_count_once_and_recursive_calls_terminate_test() {
+        let code = "call countdown halt \
+            countdown: dup0 skiz return push -1 add recurse \
+            ping: call pong return \
+            pong: call ping return";
+        let estimates = estimates(code);
+        assert_eq!(6, estimates["countdown"].processor_rows);
+        assert_eq!(0, estimates["countdown"].hash_rows);
+        assert_eq!(4, estimates["ping"].processor_rows);
+        assert_eq!(4, estimates["pong"].processor_rows);
+    }
+
+    #[test]
+    fn listing_annotates_every_label_test() {
+        let code = "call foo halt foo: push 1 // one\n pop return";
+        let listing = annotated_listing(code).unwrap();
+        let expected = "    call foo
+    halt
+foo: // per invocation: 3 processor rows, 0 hash rows
+    push 1
+    pop
+    return
+";
+        assert_eq!(expected, listing);
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use anyhow::Result;
+use itertools::Itertools;
+use triton_opcodes::instruction::parse;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::LabelledInstruction;
+use triton_opcodes::program::Program;
+
+use crate::analysis::control_flow_graph::ControlFlowGraph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockChange {
+    Added,
+    Removed,
+
+    /// The block exists in both versions, but its instructions differ.
+    Changed,
+}
+
+/// A basic block that differs between the two versions of a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDiff {
+    /// The block's first label. Blocks without a label are named after the closest labelled block
+    /// preceding them, followed by `#` and their position after that block. Blocks preceding all
+    /// labels are named `#` followed by their position.
+    pub name: String,
+    pub change: BlockChange,
+
+    /// The number of words the block occupies in the old version, 0 if it was added.
+    pub old_size: usize,
+
+    /// The number of words the block occupies in the new version, 0 if it was removed.
+    pub new_size: usize,
+}
+
+/// The differences between two versions of a program at the level of basic blocks, for reviewing
+/// changes to generated code. Blocks are matched by their labels, not by their addresses, such
+/// that code moving to a different address does not show up as a change. For the same reason,
+/// the targets of `call`s and `tail_call`s are compared by label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramDiff {
+    /// Blocks of the old version in the order of their addresses, followed by added blocks in the
+    /// order of their addresses in the new version.
+    pub blocks: Vec<BlockDiff>,
+
+    /// The number of words of the entire old program.
+    pub old_size: usize,
+
+    /// The number of words of the entire new program.
+    pub new_size: usize,
+}
+
+/// A basic block, named and with its instructions rendered independently of addresses.
+struct NamedBlock {
+    name: String,
+    instructions: Vec<String>,
+    size: usize,
+}
+
+impl ProgramDiff {
+    pub fn from_code(old_code: &str, new_code: &str) -> Result<Self> {
+        Ok(Self::new(&parse(old_code)?, &parse(new_code)?))
+    }
+
+    pub fn new(old: &[LabelledInstruction], new: &[LabelledInstruction]) -> Self {
+        let old_program = Program::new(old);
+        let new_program = Program::new(new);
+        let old_blocks = Self::named_blocks(old);
+        let new_blocks = Self::named_blocks(new);
+
+        let new_blocks_by_name: HashMap<_, _> = new_blocks
+            .iter()
+            .map(|block| (block.name.as_str(), block))
+            .collect();
+        let old_names = old_blocks
+            .iter()
+            .map(|block| block.name.as_str())
+            .collect_vec();
+
+        let mut blocks = vec![];
+        for old_block in old_blocks.iter() {
+            let Some(new_block) = new_blocks_by_name.get(old_block.name.as_str()) else {
+                blocks.push(BlockDiff {
+                    name: old_block.name.clone(),
+                    change: BlockChange::Removed,
+                    old_size: old_block.size,
+                    new_size: 0,
+                });
+                continue;
+            };
+            if old_block.instructions != new_block.instructions {
+                blocks.push(BlockDiff {
+                    name: old_block.name.clone(),
+                    change: BlockChange::Changed,
+                    old_size: old_block.size,
+                    new_size: new_block.size,
+                });
+            }
+        }
+        let added_blocks = new_blocks
+            .iter()
+            .filter(|block| !old_names.contains(&block.name.as_str()))
+            .map(|block| BlockDiff {
+                name: block.name.clone(),
+                change: BlockChange::Added,
+                old_size: 0,
+                new_size: block.size,
+            });
+        blocks.extend(added_blocks);
+
+        Self {
+            blocks,
+            old_size: old_program.instructions.len(),
+            new_size: new_program.instructions.len(),
+        }
+    }
+
+    fn named_blocks(labelled_instructions: &[LabelledInstruction]) -> Vec<NamedBlock> {
+        let program = Program::new(labelled_instructions);
+        let cfg = ControlFlowGraph::new(labelled_instructions);
+        let label_at: HashMap<usize, &str> = cfg
+            .blocks
+            .iter()
+            .filter_map(|block| Some((block.start, block.labels.first()?.as_str())))
+            .collect();
+
+        let mut named_blocks = vec![];
+        let mut closest_label = String::new();
+        let mut position_after_label = 0;
+        for block in cfg.blocks.iter() {
+            let name = match block.labels.first() {
+                Some(label) => {
+                    closest_label = label.clone();
+                    position_after_label = 0;
+                    label.clone()
+                }
+                None => {
+                    position_after_label += 1;
+                    format!("{closest_label}#{position_after_label}")
+                }
+            };
+
+            let mut instructions = vec![];
+            let mut address = block.start;
+            while address < block.end {
+                let instruction = program.instructions[address];
+                let rendered = match instruction {
+                    Call(target) | TailCall(target) => {
+                        let target = target.value() as usize;
+                        let target = match label_at.get(&target) {
+                            Some(label) => label.to_string(),
+                            None => target.to_string(),
+                        };
+                        let mnemonic = match instruction {
+                            Call(_) => "call",
+                            _ => "tail_call",
+                        };
+                        format!("{mnemonic} {target}")
+                    }
+                    _ => instruction.to_string(),
+                };
+                instructions.push(rendered);
+                address += instruction.size();
+            }
+
+            named_blocks.push(NamedBlock {
+                name,
+                instructions,
+                size: block.end - block.start,
+            });
+        }
+        named_blocks
+    }
+
+    pub fn added(&self) -> impl Iterator<Item = &BlockDiff> {
+        self.with_change(BlockChange::Added)
+    }
+
+    pub fn removed(&self) -> impl Iterator<Item = &BlockDiff> {
+        self.with_change(BlockChange::Removed)
+    }
+
+    pub fn changed(&self) -> impl Iterator<Item = &BlockDiff> {
+        self.with_change(BlockChange::Changed)
+    }
+
+    fn with_change(&self, change: BlockChange) -> impl Iterator<Item = &BlockDiff> {
+        self.blocks
+            .iter()
+            .filter(move |block| block.change == change)
+    }
+
+    /// `true` iff both versions consist of the same blocks with the same instructions.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// The growth of the program in words. Negative if the program shrinks.
+    pub fn size_difference(&self) -> isize {
+        self.new_size as isize - self.old_size as isize
+    }
+}
+
+impl Display for ProgramDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for block in self.blocks.iter() {
+            let marker = match block.change {
+                BlockChange::Added => '+',
+                BlockChange::Removed => '-',
+                BlockChange::Changed => '~',
+            };
+            writeln!(
+                f,
+                "{marker} {}: {} → {} words",
+                block.name, block.old_size, block.new_size
+            )?;
+        }
+        write!(
+            f,
+            "code size: {} → {} words ({:+})",
+            self.old_size,
+            self.new_size,
+            self.size_difference()
+        )
+    }
+}
+
+#[cfg(test)]
+mod program_diff_tests {
+    use super::*;
+
+    const OLD_CODE: &str = "push 3 call countdown halt \
+        countdown: dup0 skiz return push -1 add recurse \
+        unused: push 0 pop return";
+
+    #[test]
+    fn identical_programs_have_empty_diff_test() {
+        let diff = ProgramDiff::from_code(OLD_CODE, OLD_CODE).unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(0, diff.size_difference());
+    }
+
+    #[test]
+    fn moved_code_is_matched_by_label_test() {
+        let new_code = "push 3 call countdown halt \
+            unused: push 0 pop return \
+            countdown: dup0 skiz return push -1 add recurse";
+        let diff = ProgramDiff::from_code(OLD_CODE, new_code).unwrap();
+        assert!(diff.is_empty(), "{diff}");
+    }
+
+    #[test]
+    fn added_removed_and_changed_blocks_are_reported_test() {
+        let new_code = "push 3 call countdown halt \
+            countdown: dup0 skiz return push -1 add nop recurse \
+            helper: push 1 pop return";
+        let diff = ProgramDiff::from_code(OLD_CODE, new_code).unwrap();
+        println!("{diff}");
+
+        let names = |blocks: Vec<&BlockDiff>| {
+            blocks
+                .into_iter()
+                .map(|block| block.name.clone())
+                .collect_vec()
+        };
+        assert_eq!(vec!["helper"], names(diff.added().collect()));
+        assert_eq!(vec!["unused"], names(diff.removed().collect()));
+        let changed = diff.changed().collect_vec();
+        assert_eq!(vec!["countdown#2"], names(changed.clone()));
+        assert_eq!(4, changed[0].old_size);
+        assert_eq!(5, changed[0].new_size);
+        assert_eq!(1, diff.size_difference());
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use triton_opcodes::instruction::AnInstruction::Divine;
+use triton_opcodes::instruction::AnInstruction::DivineSibling;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+
+use crate::state::TerminalState;
+use crate::state::VMOutput;
+use crate::state::VMState;
+use crate::vm;
+use crate::vm::enclosing_label;
+use crate::vm::error::InstructionError::SecretInputExhausted;
+
+/// The words a single instruction took from secret input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretInputRead {
+    /// The clock cycle in which the instruction was executed.
+    pub cycle: u32,
+
+    /// The address of the instruction.
+    pub instruction_pointer: usize,
+
+    pub instruction: Instruction,
+
+    /// The index of the first word read, relative to the beginning of the provided secret input.
+    pub first_index: usize,
+
+    pub words: Vec<BFieldElement>,
+}
+
+/// A way in which the provided secret input and the program's use of it do not match up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretInputFinding {
+    /// Execution ended without consuming all provided secret input. The leftover words have no
+    /// influence on the execution, which commonly means that prover and executor disagree about
+    /// the shape of the secret input.
+    UnconsumedWords { num_words: usize },
+
+    /// An instruction required more words of secret input than were left.
+    Exhausted {
+        cycle: u32,
+        instruction_pointer: usize,
+        num_missing_words: usize,
+    },
+
+    /// Re-running the program with only the consumed prefix of the secret input did not
+    /// reproduce the original execution.
+    NonDeterministicExecution,
+}
+
+impl Display for SecretInputFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretInputFinding::UnconsumedWords { num_words } => {
+                write!(f, "{num_words} words of secret input were never consumed")
+            }
+            SecretInputFinding::Exhausted {
+                cycle,
+                instruction_pointer,
+                num_missing_words,
+            } => write!(
+                f,
+                "secret input exhausted in cycle {cycle} at address {instruction_pointer}: \
+                {num_missing_words} words missing"
+            ),
+            SecretInputFinding::NonDeterministicExecution => write!(
+                f,
+                "execution with only the consumed secret input differs from the original execution"
+            ),
+        }
+    }
+}
+
+/// How a program consumes its secret input, _i.e._, the words read by `divine` and
+/// `divine_sibling`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretInputAudit {
+    /// Every read from secret input, in the order of execution.
+    pub reads: Vec<SecretInputRead>,
+
+    /// The words of secret input that were never read.
+    pub unconsumed: Vec<BFieldElement>,
+
+    pub findings: Vec<SecretInputFinding>,
+}
+
+/// The outcome of one execution, as far as it is relevant for comparing executions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Outcome {
+    terminal_state: TerminalState,
+    stdout: Vec<BFieldElement>,
+    error: Option<String>,
+}
+
+impl SecretInputAudit {
+    /// Execute the program, recording every word taken from secret input, and verify that the
+    /// secret input is consumed word by word in the order it was provided. Then, re-run the
+    /// program with only the consumed words as secret input to make sure the execution is
+    /// reproduced exactly and does not depend on leftover input.
+    ///
+    /// If secret input is exhausted, the audit records a finding pointing at the instruction that
+    /// came up short.
+    pub fn new(
+        program: &Program,
+        stdin: Vec<BFieldElement>,
+        secret_in: Vec<BFieldElement>,
+    ) -> Self {
+        let mut reads = vec![];
+        let (outcome, unconsumed) =
+            Self::execute(program, stdin.clone(), secret_in.clone(), |read| {
+                reads.push(read)
+            });
+
+        let mut findings = vec![];
+        let exhausted = matches!(outcome, Err(SecretInputFinding::Exhausted { .. }));
+        let outcome = match outcome {
+            Ok(outcome) => Some(outcome),
+            Err(finding) => {
+                findings.push(finding);
+                None
+            }
+        };
+
+        if !unconsumed.is_empty() && !exhausted {
+            let num_words = unconsumed.len();
+            findings.push(SecretInputFinding::UnconsumedWords { num_words });
+        }
+
+        if let Some(outcome) = outcome {
+            let num_consumed = secret_in.len() - unconsumed.len();
+            let consumed = secret_in[..num_consumed].to_vec();
+            let (rerun_outcome, _) = Self::execute(program, stdin, consumed, |_| ());
+            if rerun_outcome.ok() != Some(outcome) {
+                findings.push(SecretInputFinding::NonDeterministicExecution);
+            }
+        }
+
+        Self {
+            reads,
+            unconsumed,
+            findings,
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    fn execute(
+        program: &Program,
+        stdin: Vec<BFieldElement>,
+        secret_in: Vec<BFieldElement>,
+        mut on_read: impl FnMut(SecretInputRead),
+    ) -> (
+        std::result::Result<Outcome, SecretInputFinding>,
+        Vec<BFieldElement>,
+    ) {
+        let mut num_read_words = 0;
+        let mut stdout = vec![];
+        let mut previous_instruction = None;
+        let observer = |state: &VMState, vm_output| {
+            if let Some(VMOutput::WriteOutputSymbol(word)) = vm_output {
+                stdout.push(word);
+            }
+            if let Some((cycle, instruction_pointer, instruction)) = previous_instruction {
+                let num_words = num_secret_words(instruction);
+                if num_words > 0 {
+                    let first_index = num_read_words;
+                    num_read_words += num_words;
+                    on_read(SecretInputRead {
+                        cycle,
+                        instruction_pointer,
+                        instruction,
+                        first_index,
+                        words: secret_in[first_index..num_read_words].to_vec(),
+                    });
+                }
+            }
+            previous_instruction = state
+                .current_instruction()
+                .ok()
+                .map(|instruction| (state.cycle_count, state.instruction_pointer, instruction));
+        };
+        let (state, err) = vm::execute_to_last_state(program, stdin, secret_in.clone(), observer);
+        let unconsumed = secret_in[num_read_words..].to_vec();
+
+        if let Some(err) = err.as_ref().filter(|err| err.kind == SecretInputExhausted) {
+            let num_words = err.instruction.map_or(0, num_secret_words);
+            let finding = SecretInputFinding::Exhausted {
+                cycle: err.cycle_count,
+                instruction_pointer: err.instruction_pointer,
+                num_missing_words: num_words - unconsumed.len(),
+            };
+            return (Err(finding), unconsumed);
+        }
+
+        let outcome = Outcome {
+            terminal_state: state.into(),
+            stdout,
+            error: err.map(|err| err.to_string()),
+        };
+        (Ok(outcome), unconsumed)
+    }
+}
+
+impl Display for SecretInputAudit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let num_consumed_words: usize = self.reads.iter().map(|read| read.words.len()).sum();
+        writeln!(f, "Reads from secret input:  {:>8}", self.reads.len())?;
+        writeln!(f, "Consumed words:           {:>8}", num_consumed_words)?;
+        write!(f, "Unconsumed words:         {:>8}", self.unconsumed.len())?;
+        for finding in self.findings.iter() {
+            write!(f, "\n{finding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The words of secret input consumed by one divination site, _i.e._, one instruction reading
+/// from secret input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessSite {
+    pub instruction_pointer: usize,
+    pub instruction: Instruction,
+
+    /// The label closest to, but not after, the site. `None` for sites preceding all labels.
+    pub label: Option<String>,
+
+    /// The number of times the site was executed.
+    pub num_reads: usize,
+
+    pub num_words: usize,
+}
+
+/// How many words of secret input every divination site and every label consumed during
+/// execution. Lets witness-generation pipelines check that they produce exactly the required
+/// nondeterminism, and locate the culprit if they do not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessSizeReport {
+    /// All executed divination sites, ordered by address.
+    pub sites: Vec<WitnessSite>,
+
+    pub num_consumed_words: usize,
+    pub num_unconsumed_words: usize,
+}
+
+impl WitnessSizeReport {
+    /// Account for the reads of the audit, given all labels and their addresses, for example as
+    /// returned by `label_addresses`.
+    pub fn new(audit: &SecretInputAudit, labels: &HashMap<String, usize>) -> Self {
+        let mut sites: BTreeMap<usize, WitnessSite> = BTreeMap::new();
+        for read in audit.reads.iter() {
+            let site = sites
+                .entry(read.instruction_pointer)
+                .or_insert_with(|| WitnessSite {
+                    instruction_pointer: read.instruction_pointer,
+                    instruction: read.instruction,
+                    label: enclosing_label(labels, read.instruction_pointer).map(str::to_string),
+                    num_reads: 0,
+                    num_words: 0,
+                });
+            site.num_reads += 1;
+            site.num_words += read.words.len();
+        }
+        let sites = sites.into_values().collect_vec();
+
+        Self {
+            num_consumed_words: sites.iter().map(|site| site.num_words).sum(),
+            num_unconsumed_words: audit.unconsumed.len(),
+            sites,
+        }
+    }
+
+    /// The number of consumed words per label, summed over all sites following the label.
+    pub fn words_per_label(&self) -> HashMap<Option<&str>, usize> {
+        let mut words_per_label = HashMap::new();
+        for site in self.sites.iter() {
+            *words_per_label.entry(site.label.as_deref()).or_insert(0) += site.num_words;
+        }
+        words_per_label
+    }
+
+    /// Every label for which the number of consumed words differs from the expected number, with
+    /// the expected and the actual number. Labels that consumed words but are missing from the
+    /// expectations are expected to consume none.
+    pub fn mismatches(&self, expected: &HashMap<String, usize>) -> Vec<(String, usize, usize)> {
+        let words_per_label = self.words_per_label();
+        let actual = |label: &str| words_per_label.get(&Some(label)).copied().unwrap_or(0);
+        let unexpected_labels = words_per_label
+            .keys()
+            .flatten()
+            .filter(|label| !expected.contains_key(**label))
+            .map(|label| (label.to_string(), 0));
+        expected
+            .iter()
+            .map(|(label, &num_words)| (label.clone(), num_words))
+            .chain(unexpected_labels)
+            .filter(|(label, num_words)| actual(label) != *num_words)
+            .map(|(label, num_words)| {
+                let actual_num_words = actual(&label);
+                (label, num_words, actual_num_words)
+            })
+            .sorted()
+            .collect()
+    }
+}
+
+impl Display for WitnessSizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "| Address | Instruction      | Label                          |  Reads |  Words |"
+        )?;
+        writeln!(
+            f,
+            "|--------:|:-----------------|:-------------------------------|-------:|-------:|"
+        )?;
+        for site in self.sites.iter() {
+            let label = site.label.as_deref().unwrap_or("(before first label)");
+            writeln!(
+                f,
+                "| {:>7} | {:<16} | {label:<30} | {:>6} | {:>6} |",
+                site.instruction_pointer,
+                site.instruction.to_string(),
+                site.num_reads,
+                site.num_words,
+            )?;
+        }
+        write!(
+            f,
+            "Consumed words: {}, unconsumed words: {}",
+            self.num_consumed_words, self.num_unconsumed_words
+        )
+    }
+}
+
+/// The number of words the given instruction takes from secret input.
+pub(crate) fn num_secret_words(instruction: Instruction) -> usize {
+    match instruction {
+        Divine(None) => 1,
+        DivineSibling => DIGEST_LENGTH,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod secret_input_tests {
+    use triton_opcodes::instruction::label_addresses;
+    use triton_opcodes::instruction::parse;
+
+    use crate::test_corpus::test_program_for_divine_sibling_noswitch;
+
+    use super::*;
+
+    #[test]
+    fn exactly_consumed_secret_input_is_clean_test() {
+        let program = Program::from_code("divine divine add write_io halt").unwrap();
+        let secret_in = [3, 4].map(BFieldElement::new).to_vec();
+        let audit = SecretInputAudit::new(&program, vec![], secret_in.clone());
+        assert!(audit.is_clean(), "{audit}");
+        assert_eq!(2, audit.reads.len());
+        assert_eq!(1, audit.reads[1].first_index);
+        assert_eq!(vec![secret_in[1]], audit.reads[1].words);
+        assert_eq!(1, audit.reads[1].cycle);
+    }
+
+    #[test]
+    fn leftover_secret_input_is_flagged_test() {
+        let program = Program::from_code("divine pop halt").unwrap();
+        let secret_in = [3, 4, 5].map(BFieldElement::new).to_vec();
+        let audit = SecretInputAudit::new(&program, vec![], secret_in);
+        assert_eq!(
+            vec![SecretInputFinding::UnconsumedWords { num_words: 2 }],
+            audit.findings
+        );
+        assert_eq!([4, 5].map(BFieldElement::new).to_vec(), audit.unconsumed);
+    }
+
+    #[test]
+    fn exhausted_secret_input_is_flagged_instead_of_panicking_test() {
+        let program = Program::from_code("divine divine halt").unwrap();
+        let secret_in = vec![BFieldElement::new(3)];
+        let audit = SecretInputAudit::new(&program, vec![], secret_in);
+        let expected = SecretInputFinding::Exhausted {
+            cycle: 1,
+            instruction_pointer: 1,
+            num_missing_words: 1,
+        };
+        assert_eq!(vec![expected], audit.findings);
+    }
+
+    #[test]
+    fn witness_size_is_accounted_per_site_and_label_test() {
+        let code = "divine call two_words call two_words halt \
+            two_words: divine divine pop pop return \
+            digest: divine_sibling return";
+        let labelled_instructions = parse(code).unwrap();
+        let program = Program::new(&labelled_instructions);
+        let labels = label_addresses(&labelled_instructions);
+        let secret_in = (1..=5).map(BFieldElement::new).collect_vec();
+        let audit = SecretInputAudit::new(&program, vec![], secret_in);
+        let report = WitnessSizeReport::new(&audit, &labels);
+        println!("{report}");
+
+        assert_eq!(3, report.sites.len());
+        assert_eq!(None, report.sites[0].label);
+        assert_eq!(Some("two_words"), report.sites[1].label.as_deref());
+        assert_eq!(2, report.sites[1].num_reads);
+        assert_eq!(5, report.num_consumed_words);
+        assert_eq!(0, report.num_unconsumed_words);
+
+        let expected = HashMap::from([("two_words".to_string(), 4), ("digest".to_string(), 0)]);
+        assert!(report.mismatches(&expected).is_empty());
+
+        let expected = HashMap::from([("digest".to_string(), 5)]);
+        let mismatches = report.mismatches(&expected);
+        let expected_mismatches = vec![
+            ("digest".to_string(), 5, 0),
+            ("two_words".to_string(), 0, 4),
+        ];
+        assert_eq!(expected_mismatches, mismatches);
+    }
+
+    #[test]
+    fn divine_sibling_consumes_one_digest_test() {
+        let code_and_input = test_program_for_divine_sibling_noswitch();
+        let program = Program::from_code(&code_and_input.source_code).unwrap();
+        let audit =
+            SecretInputAudit::new(&program, code_and_input.input, code_and_input.secret_input);
+        assert!(audit.is_clean(), "{audit}");
+        assert_eq!(DIGEST_LENGTH, audit.reads[0].words.len());
+    }
+}
@@ -0,0 +1,325 @@
+//! Differential fuzzing of the simulator against the AIR.
+//!
+//! Random short programs are executed, and every transition of the resulting processor matrix is
+//! checked against the transition constraints of the executed instruction. A violated constraint
+//! means that the simulator's semantics of that instruction and its arithmetization disagree.
+//! Since every transition is checked against the constraints of exactly one instruction, any
+//! disagreement is localized to that instruction.
+//!
+//! Forks adding instructions can run [`fuzz_semantics`] to get immediate feedback on whether
+//! the new instruction's simulator semantics and constraints agree.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use ndarray::s;
+use ndarray::Array2;
+use num_traits::One;
+use num_traits::Zero;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use triton_opcodes::instruction::all_instructions_without_args;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::ord_n::Ord16;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+
+use crate::table::challenges::AllChallenges;
+use crate::table::constraint_circuit::ConstraintCircuit;
+use crate::table::constraint_circuit::DualRowIndicator;
+use crate::table::master_table::NUM_BASE_COLUMNS;
+use crate::table::master_table::NUM_EXT_COLUMNS;
+use crate::table::master_table::PROCESSOR_TABLE_END;
+use crate::table::master_table::PROCESSOR_TABLE_START;
+use crate::table::processor_table::DualRowConstraints;
+use crate::table::processor_table::ProcessorTableChallenges;
+use crate::table::table_column::BaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn::CI;
+use crate::vm::execute;
+use crate::vm::AlgebraicExecutionTrace;
+use crate::vm::TraceRecorder;
+
+type TransitionConstraint = ConstraintCircuit<
+    ProcessorTableChallenges,
+    DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
+>;
+
+/// A transition of the processor matrix that violates a transition constraint of the executed
+/// instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticsMismatch {
+    /// The clock cycle in which the offending instruction was executed.
+    pub cycle: usize,
+
+    /// The offending instruction, arguments stripped.
+    pub instruction: Instruction,
+
+    /// The index of the violated constraint among the instruction's transition constraints.
+    pub constraint_index: usize,
+}
+
+impl Display for SemanticsMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cycle = self.cycle;
+        let instruction = self.instruction;
+        let constraint_index = self.constraint_index;
+        write!(
+            f,
+            "cycle {cycle}, instruction {instruction}: \
+            transition constraint {constraint_index} is violated"
+        )
+    }
+}
+
+/// Checks transitions of processor matrices against the instruction-specific transition
+/// constraints. The constraints of every instruction are built once, when first needed.
+pub struct AirOracle {
+    constraints: HashMap<Instruction, Vec<TransitionConstraint>>,
+    challenges: AllChallenges,
+}
+
+impl Default for AirOracle {
+    fn default() -> Self {
+        Self {
+            constraints: HashMap::new(),
+            challenges: AllChallenges::placeholder(&[], &[]),
+        }
+    }
+}
+
+impl AirOracle {
+    /// Every transition of the trace's processor matrix that violates a transition constraint of
+    /// the instruction executed in that transition.
+    pub fn check(&mut self, aet: &AlgebraicExecutionTrace) -> Vec<SemanticsMismatch> {
+        let num_rows = aet.processor_matrix.nrows();
+        let mut base_table = Array2::zeros([num_rows, NUM_BASE_COLUMNS]);
+        base_table
+            .slice_mut(s![.., PROCESSOR_TABLE_START..PROCESSOR_TABLE_END])
+            .assign(&aet.processor_matrix);
+        let ext_table = Array2::zeros([2, NUM_EXT_COLUMNS]);
+
+        let mut mismatches = vec![];
+        for cycle in 0..num_rows.saturating_sub(1) {
+            let opcode = aet.processor_matrix[[cycle, CI.base_table_index()]].value();
+            let instruction = Instruction::try_from(opcode)
+                .expect("Trace must only contain valid opcodes.")
+                .strip();
+            let rows = base_table.slice(s![cycle..=cycle + 1, ..]);
+            let constraints = self.constraints.entry(instruction).or_insert_with(|| {
+                DualRowConstraints::default()
+                    .instruction_transition_constraints(instruction)
+                    .into_iter()
+                    .map(|constraint| constraint.consume())
+                    .collect()
+            });
+            for (constraint_index, constraint) in constraints.iter().enumerate() {
+                let evaluation = constraint.evaluate(
+                    rows,
+                    ext_table.view(),
+                    &self.challenges.processor_table_challenges,
+                );
+                if evaluation != XFieldElement::zero() {
+                    mismatches.push(SemanticsMismatch {
+                        cycle,
+                        instruction,
+                        constraint_index,
+                    });
+                }
+            }
+        }
+        mismatches
+    }
+}
+
+/// The result of a fuzzing campaign.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuzzReport {
+    pub num_programs: usize,
+    pub num_transitions: usize,
+
+    /// Every mismatch found, together with the source code of the program exposing it.
+    pub mismatches: Vec<(String, SemanticsMismatch)>,
+}
+
+impl FuzzReport {
+    /// The instructions whose simulator semantics and constraints disagree, in order of first
+    /// detection.
+    pub fn mismatching_instructions(&self) -> Vec<Instruction> {
+        self.mismatches
+            .iter()
+            .map(|(_, mismatch)| mismatch.instruction)
+            .unique()
+            .collect()
+    }
+}
+
+impl Display for FuzzReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let num_programs = self.num_programs;
+        let num_transitions = self.num_transitions;
+        writeln!(
+            f,
+            "Checked {num_transitions} transitions of {num_programs} random programs."
+        )?;
+        let mismatching_instructions = self.mismatching_instructions();
+        if mismatching_instructions.is_empty() {
+            return writeln!(f, "Simulator and AIR agree.");
+        }
+        for instruction in mismatching_instructions {
+            let (source_code, mismatch) = self
+                .mismatches
+                .iter()
+                .find(|(_, mismatch)| mismatch.instruction == instruction)
+                .unwrap();
+            writeln!(f, "{mismatch}")?;
+            writeln!(f, "  first exposed by: {source_code}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Execute `num_programs` random programs of `program_length` instructions each and check every
+/// transition against the AIR. The programs, as well as their public and secret input, are
+/// derived from the `seed`, making every finding reproducible.
+///
+/// Programs are straight-line code: they contain no instructions changing the control flow
+/// except `skiz`. A program failing at runtime, for example on a failed assertion, is checked
+/// up to the point of failure.
+pub fn fuzz_semantics(num_programs: usize, program_length: usize, seed: u64) -> FuzzReport {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut oracle = AirOracle::default();
+    let mut report = FuzzReport::default();
+
+    for _ in 0..num_programs {
+        let source_code = random_program(&mut rng, program_length);
+        let program = Program::from_code(&source_code).expect("Random program must parse.");
+        let num_input_words = 5 * program_length;
+        let stdin = random_elements(&mut rng, num_input_words);
+        let secret_in = random_elements(&mut rng, num_input_words);
+        let mut recorder = TraceRecorder::default();
+        let mut stdout = vec![];
+        // A failing program is checked up to the point of failure, so the error is irrelevant.
+        let _ = execute(&program, stdin, secret_in, |state, vm_output| {
+            recorder.record(&mut stdout, state, vm_output)
+        });
+        let aet = recorder.finish();
+
+        report.num_programs += 1;
+        report.num_transitions += aet.processor_matrix.nrows().saturating_sub(1);
+        let mismatches = oracle.check(&aet);
+        report.mismatches.extend(
+            mismatches
+                .into_iter()
+                .map(|mismatch| (source_code.clone(), mismatch)),
+        );
+    }
+    report
+}
+
+/// The source code of a random straight-line program, terminated by `halt`. The op stack is first
+/// filled with random elements. Instructions that would crash the VM on most stacks are preceded
+/// by a few instructions establishing their precondition. This keeps the programs running long
+/// enough for most of their instructions to be checked.
+fn random_program(rng: &mut StdRng, length: usize) -> String {
+    let candidates = all_instructions_without_args()
+        .into_iter()
+        .filter(|instruction| {
+            !matches!(instruction, Call(_) | TailCall(_) | Return | Recurse | Halt)
+        })
+        .collect_vec();
+
+    let mut instructions = (0..16).map(|_| Push(random_element(rng))).collect_vec();
+    for _ in 0..length {
+        let instruction = candidates[rng.gen_range(0..candidates.len())];
+        let instruction = match instruction {
+            Push(_) => Push(random_element(rng)),
+            Dup(_) => Dup(random_stack_register(rng, 0)),
+            Swap(_) => Swap(random_stack_register(rng, 1)),
+            _ => instruction,
+        };
+        let precondition = precondition(rng, instruction);
+        // `skiz` must not skip only part of the precondition
+        if instructions.last() == Some(&Skiz) && !precondition.is_empty() {
+            instructions.push(Nop);
+        }
+        instructions.extend(precondition);
+        instructions.push(instruction);
+    }
+    instructions.push(Halt);
+    instructions.iter().join(" ")
+}
+
+/// Instructions establishing the precondition of the given instruction, if it has any.
+fn precondition(rng: &mut StdRng, instruction: Instruction) -> Vec<Instruction> {
+    let mut push_u32 = || Push(BFieldElement::new(rng.gen_range(1..=u32::MAX as u64)));
+    match instruction {
+        Assert => vec![Push(BFieldElement::one())],
+        AssertVector => vec![Dup(Ord16::ST4); 5],
+        Invert | Lsb => vec![push_u32()],
+        XInvert => vec![push_u32(), push_u32(), push_u32()],
+        DivineSibling => vec![push_u32(), Swap(Ord16::ST11), Pop],
+        _ => vec![],
+    }
+}
+
+/// A random op-stack register, `ST{min}` through `ST15`.
+fn random_stack_register(rng: &mut StdRng, min: u32) -> Ord16 {
+    rng.gen_range(min..16).try_into().unwrap()
+}
+
+/// Small elements are likelier than uniformly random elements to satisfy the preconditions of
+/// instructions like `lsb` or `split`, and to exercise edge cases like zero.
+fn random_element(rng: &mut StdRng) -> BFieldElement {
+    match rng.gen_range(0..3) {
+        0 => BFieldElement::new(rng.gen_range(0..4)),
+        1 => BFieldElement::new(rng.gen::<u32>() as u64),
+        _ => BFieldElement::new(rng.gen()),
+    }
+}
+
+fn random_elements(rng: &mut StdRng, num_elements: usize) -> Vec<BFieldElement> {
+    (0..num_elements).map(|_| random_element(rng)).collect()
+}
+
+#[cfg(test)]
+mod semantics_fuzzing_tests {
+    use crate::table::table_column::ProcessorBaseTableColumn::ST0;
+    use crate::vm::simulate;
+
+    use super::*;
+
+    #[test]
+    fn simulator_and_air_agree_on_random_programs_test() {
+        let report = fuzz_semantics(10, 25, 0);
+        println!("{report}");
+        assert_eq!(10, report.num_programs);
+        assert!(report.num_transitions > 0);
+        assert!(report.mismatches.is_empty());
+    }
+
+    #[test]
+    fn disagreement_is_localized_to_offending_instruction_test() {
+        let program = Program::from_code("push 2 push 3 add push 4 mul halt").unwrap();
+        let (mut aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        let mut oracle = AirOracle::default();
+        assert!(oracle.check(&aet).is_empty());
+
+        // pretend the simulator computes 2 + 3 = 6
+        let add_cycle = 2;
+        let st0 = ST0.base_table_index();
+        aet.processor_matrix[[add_cycle + 1, st0]] = BFieldElement::new(6);
+        let mismatches = oracle.check(&aet);
+        assert!(!mismatches.is_empty());
+        assert!(mismatches
+            .iter()
+            .all(|mismatch| mismatch.instruction == Add
+                || mismatch.instruction == Push(0_u64.into())));
+        assert!(mismatches
+            .iter()
+            .any(|mismatch| mismatch.instruction == Add && mismatch.cycle == add_cycle));
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use num_traits::Zero;
+use triton_opcodes::instruction::AnInstruction;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::instruction::LabelledInstruction;
+
+use crate::table::table_column::BaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn::CI;
+use crate::table::table_column::ProcessorBaseTableColumn::IP;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// A rewrite of a sequence of instructions ending in `skiz` into a cheaper, equivalent sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkizRewrite {
+    /// `push 0 eq push 0 eq skiz` becomes `skiz`: testing for zero twice does not change whether
+    /// `skiz` skips.
+    DoubleZeroTest,
+
+    /// `push c skiz` with non-zero `c` is removed: the next instruction is always executed.
+    NonZeroCondition,
+
+    /// `push 0 skiz` and the instruction following it are removed: that instruction is never
+    /// executed.
+    ZeroCondition,
+}
+
+/// Rewrite every compare-and-branch sequence for which a cheaper equivalent exists. Sequences
+/// interrupted by a label are left untouched, since they might be entered halfway. So are
+/// sequences directly following a `skiz`, since that `skiz` might skip their first instruction.
+/// Returns the rewritten program and all applied rewrites.
+///
+/// Sequences like `eq skiz` or `lt skiz` have no cheaper equivalent in the current instruction
+/// set. How often they are executed is reported by the [`SkizPatternReport`].
+pub fn fuse_compare_and_branch(
+    program: &[LabelledInstruction],
+) -> (Vec<LabelledInstruction>, Vec<SkizRewrite>) {
+    let mut program = program.to_vec();
+    let mut applied_rewrites = vec![];
+    loop {
+        let (rewritten_program, rewrites) = rewrite_once(&program);
+        if rewrites.is_empty() {
+            return (program, applied_rewrites);
+        }
+        program = rewritten_program;
+        applied_rewrites.extend(rewrites);
+    }
+}
+
+fn rewrite_once(program: &[LabelledInstruction]) -> (Vec<LabelledInstruction>, Vec<SkizRewrite>) {
+    let mut rewritten_program = vec![];
+    let mut rewrites = vec![];
+    let mut index = 0;
+    while index < program.len() {
+        let instructions = consecutive_instructions(&program[index..]);
+        let preceded_by_skiz = index
+            .checked_sub(1)
+            .is_some_and(|previous| program[previous] == LabelledInstruction::Instruction(Skiz));
+        let rewrite = match instructions[..] {
+            _ if preceded_by_skiz => None,
+            [Push(a), Eq, Push(b), Eq, Skiz, ..] if a.is_zero() && b.is_zero() => {
+                rewritten_program.push(LabelledInstruction::Instruction(Skiz));
+                Some((SkizRewrite::DoubleZeroTest, 5))
+            }
+            [Push(c), Skiz, _, ..] if c.is_zero() => Some((SkizRewrite::ZeroCondition, 3)),
+            [Push(c), Skiz, ..] if !c.is_zero() => Some((SkizRewrite::NonZeroCondition, 2)),
+            _ => None,
+        };
+        match rewrite {
+            Some((rewrite, num_replaced_instructions)) => {
+                rewrites.push(rewrite);
+                index += num_replaced_instructions;
+            }
+            None => {
+                rewritten_program.push(program[index].clone());
+                index += 1;
+            }
+        }
+    }
+    (rewritten_program, rewrites)
+}
+
+/// The instructions at the start of the program up to, but excluding, the first label.
+fn consecutive_instructions(program: &[LabelledInstruction]) -> Vec<AnInstruction<String>> {
+    program
+        .iter()
+        .map_while(|labelled_instruction| match labelled_instruction {
+            LabelledInstruction::Instruction(instruction) => Some(instruction.clone()),
+            LabelledInstruction::Label(_) => None,
+        })
+        .take(5)
+        .collect()
+}
+
+/// How often each instruction directly preceded an executed `skiz`, _i.e._, computed the condition
+/// `skiz` branched on. Motivates future fused compare-and-branch instructions with data.
+///
+/// Pseudo-instructions show up as the last instruction of their expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkizPatternReport {
+    /// The number of executed `skiz` instructions.
+    pub num_skiz: usize,
+
+    /// The number of executed `skiz` instructions that skipped the next instruction.
+    pub num_skipped: usize,
+
+    /// Every instruction that preceded `skiz`, stripped of its argument, with the number of times
+    /// it did so. Most frequent first.
+    pub patterns: Vec<(Instruction, usize)>,
+}
+
+impl SkizPatternReport {
+    pub fn new(traces: &[AlgebraicExecutionTrace]) -> Self {
+        let mut num_skiz = 0;
+        let mut num_skipped = 0;
+        let mut occurrences: HashMap<Instruction, usize> = HashMap::new();
+
+        for trace in traces {
+            let executed_instructions = trace
+                .processor_matrix
+                .rows()
+                .into_iter()
+                .map(|row| {
+                    let ip = row[IP.base_table_index()].value() as usize;
+                    let opcode = row[CI.base_table_index()].value();
+                    let instruction: Instruction = opcode
+                        .try_into()
+                        .expect("Trace must only contain valid opcodes.");
+                    (ip, instruction)
+                })
+                .collect_vec();
+
+            for (index, &(ip, instruction)) in executed_instructions.iter().enumerate() {
+                if instruction != Skiz {
+                    continue;
+                }
+                num_skiz += 1;
+                if let Some(&(next_ip, _)) = executed_instructions.get(index + 1) {
+                    if next_ip != ip + instruction.size() {
+                        num_skipped += 1;
+                    }
+                }
+                let Some(&(previous_ip, previous_instruction)) = index
+                    .checked_sub(1)
+                    .and_then(|previous_index| executed_instructions.get(previous_index))
+                else {
+                    continue;
+                };
+                if previous_ip + previous_instruction.size() == ip {
+                    *occurrences.entry(previous_instruction).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let patterns = occurrences
+            .into_iter()
+            .sorted_by_key(|(instruction, count)| (std::cmp::Reverse(*count), instruction.name()))
+            .collect();
+
+        Self {
+            num_skiz,
+            num_skipped,
+            patterns,
+        }
+    }
+}
+
+impl Display for SkizPatternReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "| Pattern                | Occurrences |")?;
+        writeln!(f, "|:-----------------------|------------:|")?;
+        for (instruction, count) in self.patterns.iter() {
+            let pattern = format!("{} skiz", instruction.name());
+            writeln!(f, "| {pattern:<22} | {count:>11} |")?;
+        }
+        write!(
+            f,
+            "Executed skiz: {}, of which skipped: {}",
+            self.num_skiz, self.num_skipped
+        )
+    }
+}
+
+#[cfg(test)]
+mod skiz_fusion_tests {
+    use triton_opcodes::instruction::parse;
+    use triton_opcodes::program::Program;
+
+    use crate::test_corpus::small_tasm_test_programs;
+    use crate::vm::run;
+    use crate::vm::simulate_no_input;
+
+    use super::*;
+
+    fn rewrite_and_compare_output(code: &str, input: Vec<u64>) -> Vec<SkizRewrite> {
+        let input = input.into_iter().map(Into::into).collect_vec();
+        let original = parse(code).unwrap();
+        let (rewritten, rewrites) = fuse_compare_and_branch(&original);
+        assert!(rewritten.len() < original.len());
+
+        let (_, original_output) = run(&Program::new(&original), input.clone(), vec![]).unwrap();
+        let (_, rewritten_output) = run(&Program::new(&rewritten), input, vec![]).unwrap();
+        assert_eq!(original_output, rewritten_output);
+        rewrites
+    }
+
+    #[test]
+    fn double_zero_test_is_fused_test() {
+        let code = "read_io push 0 eq push 0 eq skiz push 7 push 8 write_io halt";
+        for input in [0, 5] {
+            let rewrites = rewrite_and_compare_output(code, vec![input]);
+            assert_eq!(vec![SkizRewrite::DoubleZeroTest], rewrites);
+        }
+    }
+
+    #[test]
+    fn constant_conditions_are_removed_test() {
+        let code = "push 1 skiz push 7 push 0 skiz push 8 push 9 write_io write_io halt";
+        let rewrites = rewrite_and_compare_output(code, vec![]);
+        assert_eq!(
+            vec![SkizRewrite::NonZeroCondition, SkizRewrite::ZeroCondition],
+            rewrites
+        );
+    }
+
+    #[test]
+    fn sequences_interrupted_by_labels_are_untouched_test() {
+        let code = "push 0 foo: skiz push 0 eq push 0 bar: eq skiz halt";
+        let original = parse(code).unwrap();
+        let (rewritten, rewrites) = fuse_compare_and_branch(&original);
+        assert_eq!(original, rewritten);
+        assert!(rewrites.is_empty());
+    }
+
+    #[test]
+    fn sequences_following_skiz_are_untouched_test() {
+        let code = "push 5 read_io skiz push 0 skiz push 7 write_io halt";
+        let original = parse(code).unwrap();
+        let (rewritten, rewrites) = fuse_compare_and_branch(&original);
+        assert_eq!(original, rewritten);
+        assert!(rewrites.is_empty());
+
+        // the outer `skiz` is removed first, after which the inner sequence can be rewritten
+        let code = "push 1 skiz push 0 skiz push 7 push 8 write_io halt";
+        let rewrites = rewrite_and_compare_output(code, vec![]);
+        assert_eq!(
+            vec![SkizRewrite::NonZeroCondition, SkizRewrite::ZeroCondition],
+            rewrites
+        );
+    }
+
+    #[test]
+    fn skiz_patterns_are_counted_test() {
+        let code = "push 3 call countdown halt \
+            countdown: dup0 push 0 eq skiz return push -1 add recurse";
+        let (aet, _) = simulate_no_input(&Program::from_code(code).unwrap()).unwrap();
+
+        let report = SkizPatternReport::new(&[aet]);
+        assert_eq!(4, report.num_skiz);
+        assert_eq!(3, report.num_skipped);
+        assert_eq!(vec![(Eq, 4)], report.patterns);
+    }
+
+    #[test]
+    fn print_skiz_pattern_report_for_small_programs() {
+        let traces = small_tasm_test_programs()
+            .into_iter()
+            .map(|program| program.simulate().unwrap().0)
+            .collect_vec();
+        let report = SkizPatternReport::new(&traces);
+        println!("{report}");
+    }
+}
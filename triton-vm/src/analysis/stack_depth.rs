@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use itertools::Itertools;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::program::Program;
+
+/// Depths beyond this bound are not tracked any further. Keeps the analysis finite for loops that
+/// grow the op-stack in every iteration.
+pub const MAX_TRACKED_DEPTH: usize = 1024;
+
+/// A way in which some execution of a program might misuse the op-stack. The depth of the
+/// op-stack is the number of elements the program has put there, _i.e._, excluding the zeros the
+/// op-stack is initialized with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackDepthFinding {
+    /// Executing the instruction at the given depth shrinks the op-stack below its initial height,
+    /// which crashes the VM.
+    Underflow {
+        address: usize,
+        instruction: Instruction,
+        depth: usize,
+    },
+
+    /// At the given depth, `dup` or `swap` accesses an element the program has not put on the
+    /// op-stack.
+    AccessBelowStack {
+        address: usize,
+        instruction: Instruction,
+        depth: usize,
+    },
+}
+
+impl Display for StackDepthFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackDepthFinding::Underflow {
+                address,
+                instruction,
+                depth,
+            } => write!(
+                f,
+                "`{instruction}` at address {address} might underflow the op-stack at depth {depth}"
+            ),
+            StackDepthFinding::AccessBelowStack {
+                address,
+                instruction,
+                depth,
+            } => write!(
+                f,
+                "`{instruction}` at address {address} might access below the op-stack at depth \
+                {depth}"
+            ),
+        }
+    }
+}
+
+/// The possible op-stack depths at every reachable address of a program, derived without
+/// executing it.
+///
+/// Functions are analyzed once per depth they are called at, `recurse` is assumed to jump to the
+/// start of the current function, and `skiz` to possibly skip the next instruction. Recursive
+/// `call`s are not followed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackDepthAnalysis {
+    /// The range of possible op-stack depths before executing the instruction at the address.
+    pub depth_ranges: BTreeMap<usize, RangeInclusive<usize>>,
+
+    pub findings: Vec<StackDepthFinding>,
+}
+
+impl StackDepthAnalysis {
+    pub fn new(program: &Program) -> Self {
+        let mut analyzer = Analyzer {
+            program,
+            depth_ranges: BTreeMap::new(),
+            findings: vec![],
+            function_summaries: HashMap::new(),
+        };
+        analyzer.analyze_function(program.entry_point, 0);
+
+        Self {
+            depth_ranges: analyzer.depth_ranges,
+            findings: analyzer.findings,
+        }
+    }
+
+    /// Fails with a description of all findings, if any.
+    pub fn check(program: &Program) -> Result<()> {
+        let findings = Self::new(program).findings;
+        if findings.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "Static op-stack depth check failed:\n{}",
+            findings.iter().join("\n")
+        ))
+    }
+}
+
+/// Assemble the given Triton assembly like [`Program::from_code`], additionally rejecting it if the
+/// [`StackDepthAnalysis`] has any findings. Opt-in, since the analysis is conservative: it might
+/// reject programs whose every actual execution is fine.
+pub fn assemble_with_stack_depth_check(code: &str) -> Result<Program> {
+    let program = Program::from_code(code)?;
+    StackDepthAnalysis::check(&program)?;
+    Ok(program)
+}
+
+struct Analyzer<'pgm> {
+    program: &'pgm Program,
+    depth_ranges: BTreeMap<usize, RangeInclusive<usize>>,
+    findings: Vec<StackDepthFinding>,
+
+    /// The possible depths upon `return` of a function, keyed by the function's address and the
+    /// depth it is called at. `None` while the function is being analyzed.
+    function_summaries: HashMap<(usize, usize), Option<BTreeSet<usize>>>,
+}
+
+impl<'pgm> Analyzer<'pgm> {
+    fn analyze_function(&mut self, entry: usize, entry_depth: usize) -> BTreeSet<usize> {
+        let key = (entry, entry_depth);
+        if let Some(summary) = self.function_summaries.get(&key) {
+            return summary.clone().unwrap_or_default();
+        }
+        self.function_summaries.insert(key, None);
+
+        let mut exit_depths = BTreeSet::new();
+        let mut visited = HashSet::new();
+        let mut worklist = vec![(entry, entry_depth)];
+        while let Some((address, depth)) = worklist.pop() {
+            if !visited.insert((address, depth)) {
+                continue;
+            }
+            let Some(&instruction) = self.program.instructions.get(address) else {
+                continue;
+            };
+            self.record_depth(address, depth);
+
+            if let Dup(arg) | Swap(arg) = instruction {
+                let num_accessed_elements = usize::from(arg) + 1;
+                if num_accessed_elements > depth {
+                    self.record_finding(StackDepthFinding::AccessBelowStack {
+                        address,
+                        instruction,
+                        depth,
+                    });
+                }
+            }
+
+            let new_depth = depth as i64 + instruction.op_stack_size_influence() as i64;
+            if new_depth < 0 {
+                self.record_finding(StackDepthFinding::Underflow {
+                    address,
+                    instruction,
+                    depth,
+                });
+                continue;
+            }
+            let new_depth = (new_depth as usize).min(MAX_TRACKED_DEPTH);
+
+            let next_address = address + instruction.size();
+            match instruction {
+                Halt => (),
+                Return => {
+                    exit_depths.insert(new_depth);
+                }
+                Recurse => worklist.push((entry, new_depth)),
+                Call(target) => {
+                    let target = target.value() as usize;
+                    for exit_depth in self.analyze_function(target, new_depth) {
+                        worklist.push((next_address, exit_depth));
+                    }
+                }
+                TailCall(target) => {
+                    let target = target.value() as usize;
+                    exit_depths.extend(self.analyze_function(target, new_depth));
+                }
+                Skiz => {
+                    worklist.push((next_address, new_depth));
+                    if let Some(skipped) = self.program.instructions.get(next_address) {
+                        worklist.push((next_address + skipped.size(), new_depth));
+                    }
+                }
+                _ => worklist.push((next_address, new_depth)),
+            }
+        }
+
+        self.function_summaries
+            .insert(key, Some(exit_depths.clone()));
+        exit_depths
+    }
+
+    fn record_depth(&mut self, address: usize, depth: usize) {
+        let range = self.depth_ranges.entry(address).or_insert(depth..=depth);
+        *range = *range.start().min(&depth)..=*range.end().max(&depth);
+    }
+
+    fn record_finding(&mut self, finding: StackDepthFinding) {
+        if !self.findings.contains(&finding) {
+            self.findings.push(finding);
+        }
+    }
+}
+
+#[cfg(test)]
+mod stack_depth_tests {
+    use super::*;
+
+    fn analyze(code: &str) -> StackDepthAnalysis {
+        StackDepthAnalysis::new(&Program::from_code(code).unwrap())
+    }
+
+    #[test]
+    fn balanced_program_has_no_findings_test() {
+        let analysis = analyze("push 1 push 2 add dup0 swap1 pop pop halt");
+        assert!(analysis.findings.is_empty());
+        assert_eq!(Some(&(2..=2)), analysis.depth_ranges.get(&4));
+    }
+
+    #[test]
+    fn opt_in_assembly_rejects_programs_with_findings_test() {
+        let code = "push 1 pop pop halt";
+        assert!(Program::from_code(code).is_ok());
+        let err = assemble_with_stack_depth_check(code).unwrap_err();
+        assert!(err.to_string().contains("might underflow"), "{err}");
+
+        let program = assemble_with_stack_depth_check("push 1 pop halt").unwrap();
+        assert_eq!(Program::from_code("push 1 pop halt").unwrap(), program);
+    }
+
+    #[test]
+    fn pop_on_empty_stack_underflows_test() {
+        let analysis = analyze("push 1 pop pop halt");
+        let expected = StackDepthFinding::Underflow {
+            address: 3,
+            instruction: Pop,
+            depth: 0,
+        };
+        assert_eq!(vec![expected], analysis.findings);
+    }
+
+    #[test]
+    fn dup_below_pushed_elements_is_reported_test() {
+        let analysis = analyze("push 1 push 2 dup1 dup3 halt");
+        assert_eq!(1, analysis.findings.len());
+        let StackDepthFinding::AccessBelowStack { address, depth, .. } = analysis.findings[0]
+        else {
+            panic!("Expected access below stack.");
+        };
+        assert_eq!(6, address);
+        assert_eq!(3, depth);
+    }
+
+    #[test]
+    fn function_summaries_are_applied_at_call_site_test() {
+        let analysis = analyze("push 1 push 2 call foo pop halt foo: pop return");
+        assert!(analysis.findings.is_empty());
+
+        let analysis = analyze("push 1 call foo pop halt foo: pop return");
+        assert_eq!(1, analysis.findings.len());
+        assert!(matches!(
+            analysis.findings[0],
+            StackDepthFinding::Underflow { address: 4, .. }
+        ));
+    }
+
+    #[test]
+    fn summaries_of_tail_called_functions_apply_at_original_call_site_test() {
+        let analysis =
+            analyze("push 1 push 2 call foo pop halt foo: tail_call bar bar: pop return");
+        assert!(analysis.findings.is_empty());
+
+        let analysis = analyze("push 1 call foo pop halt foo: tail_call bar bar: pop return");
+        assert_eq!(1, analysis.findings.len());
+        assert!(matches!(
+            analysis.findings[0],
+            StackDepthFinding::Underflow { address: 4, .. }
+        ));
+    }
+
+    #[test]
+    fn depth_neutral_loop_has_no_findings_test() {
+        let code = "push 10 call countdown halt \
+            countdown: dup0 skiz return push -1 add recurse";
+        let analysis = analyze(code);
+        assert!(analysis.findings.is_empty());
+    }
+
+    #[test]
+    fn loop_shrinking_the_stack_might_underflow_test() {
+        let code = "push 1 push 2 push 3 call drain halt \
+            drain: read_io skiz return pop recurse";
+        let analysis = analyze(code);
+        assert!(matches!(
+            analysis.findings[..],
+            [StackDepthFinding::Underflow {
+                instruction: Pop,
+                depth: 0,
+                ..
+            }]
+        ));
+        assert!(StackDepthAnalysis::check(&Program::from_code(code).unwrap()).is_err());
+    }
+
+    #[test]
+    fn loop_growing_the_stack_terminates_test() {
+        let code = "call grow halt grow: read_io skiz return push 1 recurse";
+        let analysis = analyze(code);
+        assert!(analysis.findings.is_empty());
+        let range = analysis.depth_ranges.values().last().unwrap();
+        assert_eq!(MAX_TRACKED_DEPTH, *range.end());
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use triton_opcodes::instruction::Instruction;
+
+use crate::table::table_column::BaseTableColumn;
+use crate::table::table_column::ProcessorBaseTableColumn::CI;
+use crate::table::table_column::ProcessorBaseTableColumn::IP;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// A sequence of instructions that was executed back to back, and which is thus a candidate for
+/// being fused into a single superinstruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FusionCandidate {
+    /// The instructions making up the candidate. Arguments are stripped.
+    pub instructions: Vec<Instruction>,
+
+    /// How often the sequence was executed across all analyzed traces.
+    pub occurrences: usize,
+}
+
+impl FusionCandidate {
+    /// The number of clock cycles that would have been saved across all analyzed traces if the
+    /// sequence had been a single instruction.
+    pub fn cycles_saved(&self) -> usize {
+        self.occurrences * (self.instructions.len() - 1)
+    }
+}
+
+impl Display for FusionCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.instructions.iter().join(" "))
+    }
+}
+
+/// Ranks sequences of instructions by how many clock cycles fusing them into superinstructions
+/// would save. Helps guiding the evolution of the instruction set with data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuperinstructionReport {
+    /// The total number of clock cycles across all analyzed traces.
+    pub total_cycles: usize,
+
+    /// All candidates, best candidate first.
+    pub candidates: Vec<FusionCandidate>,
+}
+
+impl SuperinstructionReport {
+    /// Mine the given traces for frequently executed sequences of instructions of the given
+    /// lengths, typically pairs and triples.
+    ///
+    /// Only sequences that are contiguous in the program are considered: if control flow jumps,
+    /// for example because of `call`, `return`, `recurse`, or a `skiz` that skips, the
+    /// instructions before and after the jump are not candidates for fusion.
+    pub fn mine(traces: &[AlgebraicExecutionTrace], sequence_lengths: &[usize]) -> Self {
+        let mut occurrences: HashMap<Vec<Instruction>, usize> = HashMap::new();
+        let mut total_cycles = 0;
+
+        for trace in traces {
+            let executed_instructions = Self::executed_instructions(trace);
+            total_cycles += executed_instructions.len();
+
+            for &sequence_length in sequence_lengths {
+                if sequence_length < 2 {
+                    continue;
+                }
+                for window in executed_instructions.windows(sequence_length) {
+                    let is_contiguous =
+                        window
+                            .iter()
+                            .tuple_windows()
+                            .all(|((ip, instruction), (next_ip, _))| {
+                                *next_ip == ip + instruction.size()
+                            });
+                    if is_contiguous {
+                        let sequence = window.iter().map(|&(_, instruction)| instruction);
+                        *occurrences.entry(sequence.collect()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let candidates = occurrences
+            .into_iter()
+            .map(|(instructions, occurrences)| FusionCandidate {
+                instructions,
+                occurrences,
+            })
+            .sorted_by_key(|candidate| {
+                (
+                    std::cmp::Reverse(candidate.cycles_saved()),
+                    candidate.to_string(),
+                )
+            })
+            .collect();
+
+        Self {
+            total_cycles,
+            candidates,
+        }
+    }
+
+    /// The instruction pointer and the stripped instruction of every executed clock cycle.
+    fn executed_instructions(trace: &AlgebraicExecutionTrace) -> Vec<(usize, Instruction)> {
+        trace
+            .processor_matrix
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let ip = row[IP.base_table_index()].value() as usize;
+                let opcode = row[CI.base_table_index()].value();
+                let instruction: Instruction = opcode
+                    .try_into()
+                    .expect("Trace must only contain valid opcodes.");
+                (ip, instruction)
+            })
+            .collect()
+    }
+}
+
+impl Display for SuperinstructionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "| Candidate                      | Occurrences | Cycles saved |"
+        )?;
+        writeln!(
+            f,
+            "|:-------------------------------|------------:|-------------:|"
+        )?;
+        for candidate in self.candidates.iter() {
+            writeln!(
+                f,
+                "| {:<30} | {:>11} | {:>12} |",
+                candidate.to_string(),
+                candidate.occurrences,
+                candidate.cycles_saved(),
+            )?;
+        }
+        write!(f, "Total number of clock cycles: {}", self.total_cycles)
+    }
+}
+
+#[cfg(test)]
+mod superinstruction_tests {
+    use triton_opcodes::instruction::AnInstruction::*;
+    use triton_opcodes::program::Program;
+
+    use crate::test_corpus::small_tasm_test_programs;
+    use crate::vm::simulate_no_input;
+
+    use super::*;
+
+    #[test]
+    fn most_frequent_pair_is_ranked_first_test() {
+        let program = Program::from_code("push 1 pop push 1 pop push 1 pop halt").unwrap();
+        let (aet, _) = simulate_no_input(&program).unwrap();
+
+        let report = SuperinstructionReport::mine(&[aet], &[2]);
+        assert_eq!(7, report.total_cycles);
+
+        let best_candidate = &report.candidates[0];
+        assert_eq!(
+            vec![Push(Default::default()), Pop],
+            best_candidate.instructions
+        );
+        assert_eq!(3, best_candidate.occurrences);
+        assert_eq!(3, best_candidate.cycles_saved());
+    }
+
+    #[test]
+    fn non_contiguous_instructions_are_not_candidates_test() {
+        let program = Program::from_code("call foo halt foo: return").unwrap();
+        let (aet, _) = simulate_no_input(&program).unwrap();
+
+        let report = SuperinstructionReport::mine(&[aet], &[2]);
+        assert!(report.candidates.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn print_superinstruction_report_for_small_programs() {
+        let traces = small_tasm_test_programs()
+            .into_iter()
+            .map(|program| program.simulate().unwrap().0)
+            .collect_vec();
+        let report = SuperinstructionReport::mine(&traces, &[2, 3]);
+        assert!(report.total_cycles > 0);
+        println!("{report}");
+    }
+}
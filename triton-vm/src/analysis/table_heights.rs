@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use strum::EnumCount;
+use strum::IntoEnumIterator;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::other::roundup_npo2;
+
+use crate::table::master_table::MasterBaseTable;
+use crate::table::master_table::TableId;
+use crate::vm::simulate;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// The heights of all tables of one execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableHeights {
+    /// The height of each table before padding, indexed by [`TableId`].
+    pub unpadded_heights: [usize; TableId::COUNT],
+
+    /// The height all tables are padded to.
+    pub padded_height: usize,
+}
+
+impl TableHeights {
+    pub fn new(aet: &AlgebraicExecutionTrace, program: &[BFieldElement]) -> Self {
+        let mut unpadded_heights = [0; TableId::COUNT];
+        for table in TableId::iter() {
+            unpadded_heights[table as usize] =
+                MasterBaseTable::unpadded_height(table, aet, program);
+        }
+        let padded_height = MasterBaseTable::padded_height(aet, program);
+        Self {
+            unpadded_heights,
+            padded_height,
+        }
+    }
+
+    /// The table determining the padded height. If several tables are equally tall, the first one
+    /// in the order of [`TableId`].
+    pub fn tallest_table(&self) -> TableId {
+        TableId::iter()
+            .rev()
+            .max_by_key(|&table| self.unpadded_heights[table as usize])
+            .unwrap()
+    }
+}
+
+/// How the tables of one execution were padded, as returned alongside a proof by
+/// [`Stark::prove_with_padding_report`](crate::stark::Stark::prove_with_padding_report).
+///
+/// Proving cost is determined by the padded height, which is the tallest table's height rounded up
+/// to the next power of two. A program whose tallest table is just over a power of two pays for
+/// almost twice the rows it needs; shaving off the few excess rows halves the proving cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddingReport {
+    pub heights: TableHeights,
+
+    /// The number of padding rows added to each table, indexed by [`TableId`].
+    pub padding_rows: [usize; TableId::COUNT],
+}
+
+impl PaddingReport {
+    /// The fraction of `padded_height` within which the tallest table must be above the previous
+    /// power of two to count as [just over](Self::is_just_over_power_of_two) it.
+    pub const JUST_OVER_FRACTION: usize = 8;
+
+    pub fn new(aet: &AlgebraicExecutionTrace, program: &[BFieldElement]) -> Self {
+        TableHeights::new(aet, program).into()
+    }
+
+    /// For each table, indexed by [`TableId`], the number of rows that would have to be removed
+    /// for all tables to fit into half the padded height.
+    pub fn excess_rows(&self) -> [usize; TableId::COUNT] {
+        let half_padded_height = self.heights.padded_height / 2;
+        self.heights
+            .unpadded_heights
+            .map(|height| height.saturating_sub(half_padded_height))
+    }
+
+    /// Whether the tallest table exceeds the previous power of two by at most
+    /// `1/JUST_OVER_FRACTION` of the padded height, in which case removing the
+    /// [excess rows](Self::excess_rows) from the program is likely worthwhile.
+    pub fn is_just_over_power_of_two(&self) -> bool {
+        let max_excess_rows = self.excess_rows().into_iter().max().unwrap_or_default();
+        max_excess_rows > 0
+            && max_excess_rows * Self::JUST_OVER_FRACTION <= self.heights.padded_height
+    }
+}
+
+impl From<TableHeights> for PaddingReport {
+    fn from(heights: TableHeights) -> Self {
+        let padding_rows = heights
+            .unpadded_heights
+            .map(|height| heights.padded_height - height);
+        Self {
+            heights,
+            padding_rows,
+        }
+    }
+}
+
+impl Display for PaddingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let padded_height = self.heights.padded_height;
+        writeln!(f, "Padded height: {padded_height}")?;
+        writeln!(
+            f,
+            "| {:>16} | {:>10} | {:>12} | {:>11} |",
+            "Table", "Height", "Padding rows", "Excess rows"
+        )?;
+        writeln!(
+            f,
+            "|-----------------:|-----------:|-------------:|------------:|"
+        )?;
+        let excess_rows = self.excess_rows();
+        for table in TableId::iter() {
+            let index = table as usize;
+            writeln!(
+                f,
+                "| {:>16} | {:>10} | {:>12} | {:>11} |",
+                table.to_string(),
+                self.heights.unpadded_heights[index],
+                self.padding_rows[index],
+                excess_rows[index],
+            )?;
+        }
+        if self.is_just_over_power_of_two() {
+            write!(
+                f,
+                "The {} is just over a power of two. Removing the excess rows halves the padded \
+                height to {}.",
+                self.heights.tallest_table(),
+                padded_height / 2
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The distribution of table heights across a corpus of executions. Since a deployment must pick
+/// a single set of [`StarkParameters`](crate::stark::StarkParameters), the distribution of padded
+/// heights – and which tables cause them – guides that choice.
+///
+/// Heights are bucketed by the power of two they round up to, which is what determines proving
+/// cost. Empty tables fall into bucket 0.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TableHeightHistogram {
+    pub num_executions: usize,
+
+    /// For each table, indexed by [`TableId`], the number of executions per height bucket.
+    pub unpadded_heights: [BTreeMap<usize, usize>; TableId::COUNT],
+
+    /// The number of executions per padded height.
+    pub padded_heights: BTreeMap<usize, usize>,
+
+    /// For each table, indexed by [`TableId`], the number of executions it was the tallest in.
+    pub tallest_table_counts: [usize; TableId::COUNT],
+}
+
+impl TableHeightHistogram {
+    /// Run every program on its public and secret input, and record the resulting table heights.
+    /// Fails if any of the executions fails, naming the offending corpus entry.
+    pub fn measure<'a>(
+        corpus: impl IntoIterator<Item = (&'a Program, Vec<BFieldElement>, Vec<BFieldElement>)>,
+    ) -> Result<Self> {
+        let mut histogram = Self::default();
+        for (index, (program, input, secret_input)) in corpus.into_iter().enumerate() {
+            let (aet, _) = simulate(program, input, secret_input)
+                .map_err(|error| anyhow!("Execution of corpus entry {index} failed: {error}"))?;
+            histogram.add(TableHeights::new(&aet, &program.to_bwords()));
+        }
+        Ok(histogram)
+    }
+
+    pub fn add(&mut self, heights: TableHeights) {
+        self.num_executions += 1;
+        for (histogram, &height) in self
+            .unpadded_heights
+            .iter_mut()
+            .zip(heights.unpadded_heights.iter())
+        {
+            *histogram.entry(Self::bucket(height)).or_default() += 1;
+        }
+        *self
+            .padded_heights
+            .entry(heights.padded_height)
+            .or_default() += 1;
+        self.tallest_table_counts[heights.tallest_table() as usize] += 1;
+    }
+
+    fn bucket(height: usize) -> usize {
+        match height {
+            0 => 0,
+            _ => roundup_npo2(height as u64) as usize,
+        }
+    }
+
+    /// The padded height sufficient for all executions, or `None` if there are no executions.
+    pub fn max_padded_height(&self) -> Option<usize> {
+        self.padded_heights.keys().last().copied()
+    }
+
+    /// The number of executions with a padded height of at most the given height.
+    pub fn num_executions_within(&self, padded_height: usize) -> usize {
+        self.padded_heights
+            .range(..=padded_height)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+impl Display for TableHeightHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let buckets: BTreeSet<usize> = self
+            .unpadded_heights
+            .iter()
+            .flat_map(|histogram| histogram.keys())
+            .chain(self.padded_heights.keys())
+            .copied()
+            .collect();
+
+        write!(f, "| {:>10} |", "Height")?;
+        for table in TableId::iter() {
+            write!(f, " {:>16} |", table.to_string())?;
+        }
+        writeln!(f, " {:>8} |", "Padded")?;
+        write!(f, "|-----------:|")?;
+        for _ in TableId::iter() {
+            write!(f, "-----------------:|")?;
+        }
+        writeln!(f, "---------:|")?;
+
+        for bucket in buckets {
+            write!(f, "| {bucket:>10} |")?;
+            for histogram in self.unpadded_heights.iter() {
+                let count = histogram.get(&bucket).copied().unwrap_or_default();
+                write!(f, " {count:>16} |")?;
+            }
+            let count = self
+                .padded_heights
+                .get(&bucket)
+                .copied()
+                .unwrap_or_default();
+            writeln!(f, " {count:>8} |")?;
+        }
+
+        write!(f, "| {:>10} |", "tallest")?;
+        for count in self.tallest_table_counts.iter() {
+            write!(f, " {count:>16} |")?;
+        }
+        writeln!(f, " {:>8} |", "")?;
+        write!(f, "Executions: {}", self.num_executions)
+    }
+}
+
+#[cfg(test)]
+mod table_heights_tests {
+    use itertools::Itertools;
+
+    use crate::test_corpus::property_based_test_programs;
+
+    use super::*;
+
+    #[test]
+    fn hashing_makes_hash_table_tallest_test() {
+        let program = Program::from_code("hash hash hash hash halt").unwrap();
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        let heights = TableHeights::new(&aet, &program.to_bwords());
+        assert_eq!(TableId::HashTable, heights.tallest_table());
+        assert_eq!(
+            5,
+            heights.unpadded_heights[TableId::InstructionTable as usize]
+        );
+        assert_eq!(
+            MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            heights.padded_height
+        );
+    }
+
+    #[test]
+    fn padding_report_flags_tables_just_over_power_of_two_test() {
+        // Every `hash` adds 9 rows to the Hash Table. 4 hashes need 36 rows, 4 more than 32.
+        let program = Program::from_code("hash hash hash hash halt").unwrap();
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        let report = PaddingReport::new(&aet, &program.to_bwords());
+        println!("{report}");
+
+        let hash_table = TableId::HashTable as usize;
+        assert_eq!(64, report.heights.padded_height);
+        assert_eq!(28, report.padding_rows[hash_table]);
+        assert_eq!(4, report.excess_rows()[hash_table]);
+        assert!(report.is_just_over_power_of_two());
+        assert!(report
+            .to_string()
+            .contains("halves the padded height to 32"));
+
+        let program = Program::from_code("hash hash hash halt").unwrap();
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        let report = PaddingReport::new(&aet, &program.to_bwords());
+        assert_eq!(32, report.heights.padded_height);
+        assert_eq!(11, report.excess_rows()[hash_table]);
+        assert!(!report.is_just_over_power_of_two());
+    }
+
+    #[test]
+    fn histogram_of_corpus_test() {
+        let corpus = property_based_test_programs();
+        let programs = corpus
+            .iter()
+            .map(|code| Program::from_code(&code.source_code).unwrap())
+            .collect_vec();
+        let entries = programs
+            .iter()
+            .zip(corpus.iter())
+            .map(|(program, code)| (program, code.input.clone(), code.secret_input.clone()));
+        let histogram = TableHeightHistogram::measure(entries).unwrap();
+        println!("{histogram}");
+
+        assert_eq!(corpus.len(), histogram.num_executions);
+        let sum_of_tallest: usize = histogram.tallest_table_counts.iter().sum();
+        assert_eq!(corpus.len(), sum_of_tallest);
+        for table_histogram in histogram.unpadded_heights.iter() {
+            assert_eq!(corpus.len(), table_histogram.values().sum::<usize>());
+        }
+
+        let max_padded_height = histogram.max_padded_height().unwrap();
+        assert_eq!(
+            corpus.len(),
+            histogram.num_executions_within(max_padded_height)
+        );
+        assert!(histogram.num_executions_within(max_padded_height / 2) < corpus.len());
+    }
+
+    #[test]
+    fn failing_execution_is_reported_test() {
+        let halting = Program::from_code("halt").unwrap();
+        let failing = Program::from_code("push 0 assert halt").unwrap();
+        let corpus = [(&halting, vec![], vec![]), (&failing, vec![], vec![])];
+        let error = TableHeightHistogram::measure(corpus).unwrap_err();
+        assert!(error.to_string().contains("corpus entry 1"));
+    }
+}
@@ -0,0 +1,590 @@
+//! Proving many executions of the same program, each on its own input, with a single proof.
+//!
+//! Proving `N` executions with one [`BatchStark`] is cheaper than producing `N` independent
+//! proofs. The nonlinear combination codewords of all executions are summed into one codeword,
+//! so that committing to the combination codeword and running FRI happen only once. This saves
+//! prover time and, since FRI makes up the bulk of a proof, most of the proof size. All
+//! executions' tables are padded to the same height.
+//!
+//! Each execution's master tables are still committed to individually. In particular, every
+//! execution commits to its own copy of the Program Table.
+
+use anyhow::anyhow;
+use anyhow::Result;
+use itertools::Itertools;
+use num_traits::Zero;
+use rayon::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use triton_opcodes::program::Program;
+use triton_profiler::prof_start;
+use triton_profiler::prof_stop;
+use triton_profiler::triton_profiler::TritonProfiler;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
+use twenty_first::util_types::merkle_tree::MerkleTree;
+use twenty_first::util_types::merkle_tree_maker::MerkleTreeMaker;
+
+use crate::proof::Claim;
+use crate::proof::Proof;
+use crate::proof_item::ProofItem;
+use crate::stark::CombinationEvaluator;
+use crate::stark::Maker;
+use crate::stark::Stark;
+use crate::stark::StarkHasher;
+use crate::stark::StarkParameters;
+use crate::stark::StarkProofStream;
+use crate::stark::StarkValidationError;
+use crate::table::challenges::AllChallenges;
+use crate::table::master_table::MasterBaseTable;
+use crate::table::master_table::MasterExtTable;
+use crate::table::master_table::MasterTable;
+use crate::vm::simulate;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// The claim that one program, run on each of the listed inputs, produces the respective output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchClaim {
+    pub program: Vec<BFieldElement>,
+
+    /// The address at which every execution starts, _i.e._, the initial instruction pointer.
+    pub entry_point: usize,
+
+    /// The height the tables of all executions are padded to.
+    pub padded_height: usize,
+
+    /// The public input and the output of every execution.
+    pub inputs_and_outputs: Vec<(Vec<BFieldElement>, Vec<BFieldElement>)>,
+
+    /// The [output tags](Claim::output_tags) of every execution. An execution without an entry,
+    /// or with an empty entry, has untagged output.
+    #[serde(default)]
+    pub output_tags: Vec<Vec<BFieldElement>>,
+}
+
+impl BatchClaim {
+    pub fn num_executions(&self) -> usize {
+        self.inputs_and_outputs.len()
+    }
+
+    /// The output tags of the execution with the given index, empty if its output is untagged.
+    pub fn output_tags(&self, index: usize) -> &[BFieldElement] {
+        self.output_tags.get(index).map_or(&[], |tags| tags)
+    }
+
+    /// Tag the output of every execution with the tags recorded in its execution trace, see
+    /// [`AlgebraicExecutionTrace::output_tags`].
+    pub fn tag_outputs(&mut self, aets: &[AlgebraicExecutionTrace]) {
+        self.output_tags = aets.iter().map(|aet| aet.output_tags()).collect();
+    }
+
+    /// The claim about the execution with the given index on its own. Its padded height is the
+    /// batch's, which may exceed the padded height of the execution's own trace.
+    pub fn claim(&self, index: usize) -> Claim {
+        let (input, output) = self.inputs_and_outputs[index].clone();
+        Claim {
+            input,
+            program: self.program.clone(),
+            output,
+            output_tags: self.output_tags(index).to_vec(),
+            padded_height: self.padded_height,
+            entry_point: self.entry_point,
+        }
+    }
+}
+
+/// Run the program on every pair of public and secret input. Returns the resulting batch claim
+/// and the execution traces required to prove it, or the first error any execution encounters.
+pub fn simulate_batch(
+    program: &Program,
+    inputs_and_secret_inputs: Vec<(Vec<BFieldElement>, Vec<BFieldElement>)>,
+) -> Result<(BatchClaim, Vec<AlgebraicExecutionTrace>)> {
+    let program_words = program.to_bwords();
+    let mut inputs_and_outputs = vec![];
+    let mut aets = vec![];
+    for (index, (input, secret_input)) in inputs_and_secret_inputs.into_iter().enumerate() {
+        let (aet, output) = simulate(program, input.clone(), secret_input)
+            .map_err(|error| anyhow!("Execution {index} failed: {error}"))?;
+        inputs_and_outputs.push((input, output.into()));
+        aets.push(aet);
+    }
+    let padded_height = aets
+        .iter()
+        .map(|aet| MasterBaseTable::padded_height(aet, &program_words))
+        .max()
+        .unwrap_or_default();
+    let claim = BatchClaim {
+        program: program_words,
+        entry_point: program.entry_point,
+        padded_height,
+        inputs_and_outputs,
+        output_tags: vec![],
+    };
+    Ok((claim, aets))
+}
+
+pub struct BatchStark {
+    pub claim: BatchClaim,
+
+    /// The STARK for a single execution of the batch, sharing parameters and FRI domain with all
+    /// others.
+    pub stark: Stark,
+}
+
+impl BatchStark {
+    /// Panics if the claim lists no executions.
+    pub fn new(claim: BatchClaim, parameters: StarkParameters) -> Self {
+        assert!(
+            claim.num_executions() > 0,
+            "A batch must contain at least one execution."
+        );
+        let stark = Stark::new(claim.claim(0), parameters);
+        Self { claim, stark }
+    }
+
+    /// The challenges of every execution. All executions share the same randomness, but the
+    /// challenges also depend on each execution's input and output.
+    fn all_challenges(&self, extension_weights: Vec<XFieldElement>) -> Vec<AllChallenges> {
+        self.claim
+            .inputs_and_outputs
+            .iter()
+            .enumerate()
+            .map(|(index, (input, output))| {
+                AllChallenges::create_challenges(
+                    extension_weights.clone(),
+                    input,
+                    output,
+                    self.claim.output_tags(index),
+                    self.claim.entry_point,
+                )
+            })
+            .collect()
+    }
+
+    /// Independent weights for the nonlinear combination of every execution.
+    fn all_non_lin_combi_weights(&self, seed: Digest) -> Vec<Vec<XFieldElement>> {
+        let num_weights = Stark::num_non_lin_combi_weights();
+        let all_weights = Stark::sample_weights(seed, self.claim.num_executions() * num_weights);
+        all_weights
+            .chunks_exact(num_weights)
+            .map(|weights| weights.to_vec())
+            .collect()
+    }
+
+    /// Prove the batch claim, given the execution traces of all executions, in order.
+    pub fn prove(
+        &self,
+        aets: Vec<AlgebraicExecutionTrace>,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Proof {
+        assert_eq!(
+            self.claim.num_executions(),
+            aets.len(),
+            "There must be exactly one execution trace per execution."
+        );
+        let stark = &self.stark;
+        let parameters = stark.parameters;
+        let padded_height = self.claim.padded_height;
+        if let Err(err) = parameters.validate_fri_domain_offset() {
+            panic!("Cannot prove with invalid parameters: {err}");
+        }
+
+        prof_start!(maybe_profiler, "base tables");
+        let mut proof_stream = StarkProofStream::new();
+        let padded_height_item = BFieldElement::new(padded_height as u64);
+        proof_stream.enqueue(&ProofItem::PaddedHeight(padded_height_item));
+        proof_stream.enqueue(&ProofItem::ParametersDigest(parameters.digest()));
+        let mut base_tables = vec![];
+        for aet in aets {
+            let mut master_base_table = MasterBaseTable::new_with_padded_height(
+                aet,
+                &self.claim.program,
+                padded_height,
+                parameters.num_trace_randomizers,
+                stark.fri.domain,
+            );
+            master_base_table.pad();
+            master_base_table.randomize_trace();
+            let fri_domain_master_base_table = master_base_table.to_fri_domain_table();
+            let base_merkle_tree = fri_domain_master_base_table.merkle_tree();
+            proof_stream.enqueue(&Stark::empty_tables_item(&master_base_table.empty_tables));
+            proof_stream.enqueue(&ProofItem::MerkleRoot(base_merkle_tree.get_root()));
+            base_tables.push((
+                master_base_table,
+                fri_domain_master_base_table,
+                base_merkle_tree,
+            ));
+        }
+        prof_stop!(maybe_profiler, "base tables");
+
+        prof_start!(maybe_profiler, "Fiat-Shamir");
+        let extension_weights = Stark::sample_weights(
+            proof_stream.prover_fiat_shamir(),
+            AllChallenges::TOTAL_CHALLENGES,
+        );
+        let all_challenges = self.all_challenges(extension_weights);
+        prof_stop!(maybe_profiler, "Fiat-Shamir");
+
+        prof_start!(maybe_profiler, "ext tables");
+        let mut ext_tables = vec![];
+        for ((master_base_table, _, _), challenges) in base_tables.iter().zip_eq(&all_challenges) {
+            let mut master_ext_table =
+                master_base_table.extend(challenges, parameters.num_randomizer_polynomials);
+            master_ext_table.randomize_trace();
+            let fri_domain_ext_master_table = master_ext_table.to_fri_domain_table();
+            let ext_merkle_tree = fri_domain_ext_master_table.merkle_tree();
+            proof_stream.enqueue(&ProofItem::MerkleRoot(ext_merkle_tree.get_root()));
+            ext_tables.push((fri_domain_ext_master_table, ext_merkle_tree));
+        }
+        prof_stop!(maybe_profiler, "ext tables");
+
+        prof_start!(maybe_profiler, "Fiat-Shamir");
+        let all_weights = self.all_non_lin_combi_weights(proof_stream.prover_fiat_shamir());
+        prof_stop!(maybe_profiler, "Fiat-Shamir");
+
+        prof_start!(maybe_profiler, "nonlinear combinations");
+        let mut fri_combination_codeword = vec![XFieldElement::zero(); stark.fri.domain.length];
+        for (((_, fri_domain_base_table, _), (fri_domain_ext_table, _)), (challenges, weights)) in
+            base_tables
+                .iter()
+                .zip_eq(&ext_tables)
+                .zip_eq(all_challenges.iter().zip_eq(&all_weights))
+        {
+            let execution_combination_codeword = stark.fri_combination_codeword(
+                fri_domain_base_table,
+                fri_domain_ext_table,
+                challenges,
+                weights,
+                maybe_profiler,
+            );
+            fri_combination_codeword
+                .par_iter_mut()
+                .zip_eq(execution_combination_codeword)
+                .for_each(|(acc, summand)| *acc += summand);
+        }
+        prof_stop!(maybe_profiler, "nonlinear combinations");
+
+        prof_start!(maybe_profiler, "Merkle tree 3");
+        let combination_codeword_digests = fri_combination_codeword
+            .par_iter()
+            .map(StarkHasher::hash)
+            .collect::<Vec<_>>();
+        let combination_tree: MerkleTree<StarkHasher, _> =
+            Maker::from_digests(&combination_codeword_digests);
+        let combination_root = combination_tree.get_root();
+        proof_stream.enqueue(&ProofItem::MerkleRoot(combination_root));
+        prof_stop!(maybe_profiler, "Merkle tree 3");
+
+        prof_start!(maybe_profiler, "Fiat-Shamir 3");
+        let indices_seed = proof_stream.prover_fiat_shamir();
+        let revealed_current_row_indices = StarkHasher::sample_indices(
+            &indices_seed,
+            stark.fri.domain.length,
+            parameters.num_non_linear_codeword_checks,
+        );
+        prof_stop!(maybe_profiler, "Fiat-Shamir 3");
+
+        prof_start!(maybe_profiler, "FRI");
+        match stark
+            .fri
+            .prove(&fri_combination_codeword, &mut proof_stream)
+        {
+            Ok((_, fri_first_round_merkle_root)) => assert_eq!(
+                combination_root, fri_first_round_merkle_root,
+                "Combination root from STARK and from FRI must agree."
+            ),
+            Err(e) => panic!("The FRI prover failed because of: {}", e),
+        }
+        prof_stop!(maybe_profiler, "FRI");
+
+        prof_start!(maybe_profiler, "open trace leafs");
+        let unit_distance = stark.fri.domain.length / padded_height;
+        let revealed_current_and_next_row_indices = stark
+            .revealed_current_and_next_row_indices(unit_distance, &revealed_current_row_indices);
+        for (
+            (_, fri_domain_base_table, base_merkle_tree),
+            (fri_domain_ext_table, ext_merkle_tree),
+        ) in base_tables.iter().zip_eq(&ext_tables)
+        {
+            let revealed_base_elems =
+                fri_domain_base_table.revealed_rows(&revealed_current_and_next_row_indices);
+            let auth_paths_base = base_merkle_tree
+                .get_authentication_structure(&revealed_current_and_next_row_indices);
+            proof_stream.enqueue(&ProofItem::MasterBaseTableRows(revealed_base_elems));
+            proof_stream.enqueue(&ProofItem::CompressedAuthenticationPaths(auth_paths_base));
+
+            let revealed_ext_elems =
+                fri_domain_ext_table.revealed_rows(&revealed_current_and_next_row_indices);
+            let auth_paths_ext = ext_merkle_tree
+                .get_authentication_structure(&revealed_current_and_next_row_indices);
+            proof_stream.enqueue(&ProofItem::MasterExtTableRows(revealed_ext_elems));
+            proof_stream.enqueue(&ProofItem::CompressedAuthenticationPaths(auth_paths_ext));
+        }
+
+        let revealed_combination_elements = revealed_current_row_indices
+            .iter()
+            .map(|&i| fri_combination_codeword[i])
+            .collect_vec();
+        let revealed_combination_auth_paths =
+            combination_tree.get_authentication_structure(&revealed_current_row_indices);
+        proof_stream.enqueue(&ProofItem::RevealedCombinationElements(
+            revealed_combination_elements,
+        ));
+        proof_stream.enqueue(&ProofItem::CompressedAuthenticationPaths(
+            revealed_combination_auth_paths,
+        ));
+        prof_stop!(maybe_profiler, "open trace leafs");
+
+        proof_stream.to_proof()
+    }
+
+    pub fn verify(
+        &self,
+        proof: Proof,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<bool> {
+        let stark = &self.stark;
+        let num_executions = self.claim.num_executions();
+        for index in 0..num_executions {
+            let claim = self.claim.claim(index);
+            if claim.is_output_tagged() && claim.output_tags.len() != claim.output.len() {
+                return Err(anyhow!(StarkValidationError::OutputTagsLengthMismatch));
+            }
+        }
+        stark.parameters.validate_fri_domain_offset()?;
+
+        prof_start!(maybe_profiler, "deserialize");
+        let mut proof_stream = StarkProofStream::from_proof(&proof)?;
+        prof_stop!(maybe_profiler, "deserialize");
+
+        prof_start!(maybe_profiler, "Fiat-Shamir 1");
+        let padded_height = proof_stream.dequeue()?.as_padded_heights()?.value() as usize;
+        if self.claim.padded_height != padded_height {
+            return Err(anyhow!(StarkValidationError::PaddedHeightInequality));
+        }
+        let parameters_digest = proof_stream.dequeue()?.as_parameters_digest()?;
+        if stark.parameters.digest() != parameters_digest {
+            return Err(anyhow!(StarkValidationError::ParametersDigestInequality));
+        }
+        let mut all_empty_tables = vec![];
+        let mut base_merkle_tree_roots = vec![];
+        for _ in 0..num_executions {
+            all_empty_tables.push(Stark::dequeue_empty_tables(&mut proof_stream)?);
+            base_merkle_tree_roots.push(proof_stream.dequeue()?.as_merkle_root()?);
+        }
+        let extension_weights = Stark::sample_weights(
+            proof_stream.verifier_fiat_shamir(),
+            AllChallenges::TOTAL_CHALLENGES,
+        );
+        let all_challenges = self.all_challenges(extension_weights);
+        prof_stop!(maybe_profiler, "Fiat-Shamir 1");
+
+        prof_start!(maybe_profiler, "dequeue");
+        let extension_tree_merkle_roots: Vec<Digest> = (0..num_executions)
+            .map(|_| proof_stream.dequeue()?.as_merkle_root())
+            .try_collect()?;
+        prof_stop!(maybe_profiler, "dequeue");
+
+        prof_start!(maybe_profiler, "Fiat-Shamir 2");
+        let all_weights = self.all_non_lin_combi_weights(proof_stream.verifier_fiat_shamir());
+        prof_stop!(maybe_profiler, "Fiat-Shamir 2");
+
+        prof_start!(maybe_profiler, "Fiat-Shamir 3");
+        let combination_root = proof_stream.dequeue()?.as_merkle_root()?;
+        let indices_seed = proof_stream.verifier_fiat_shamir();
+        let revealed_current_row_indices = StarkHasher::sample_indices(
+            &indices_seed,
+            stark.fri.domain.length,
+            stark.parameters.num_non_linear_codeword_checks,
+        );
+        prof_stop!(maybe_profiler, "Fiat-Shamir 3");
+
+        prof_start!(maybe_profiler, "FRI");
+        stark
+            .fri
+            .verify(&mut proof_stream, &combination_root, maybe_profiler)?;
+        prof_stop!(maybe_profiler, "FRI");
+
+        prof_start!(maybe_profiler, "check leafs");
+        let unit_distance = stark.fri.domain.length / padded_height;
+        let revealed_current_and_next_row_indices = stark
+            .revealed_current_and_next_row_indices(unit_distance, &revealed_current_row_indices);
+        let mut all_indexed_rows = vec![];
+        for (((base_merkle_tree_root, extension_tree_merkle_root), empty_tables), challenges) in
+            base_merkle_tree_roots
+                .into_iter()
+                .zip_eq(extension_tree_merkle_roots)
+                .zip_eq(all_empty_tables)
+                .zip_eq(&all_challenges)
+        {
+            let base_table_rows = Stark::dequeue_and_authenticate_base_rows(
+                &mut proof_stream,
+                base_merkle_tree_root,
+                &revealed_current_and_next_row_indices,
+                maybe_profiler,
+            )?;
+            let ext_table_rows = Stark::dequeue_and_authenticate_ext_rows(
+                &mut proof_stream,
+                extension_tree_merkle_root,
+                &revealed_current_and_next_row_indices,
+                maybe_profiler,
+            )?;
+            let base_table_rows =
+                MasterBaseTable::reinsert_empty_table_columns(&empty_tables, base_table_rows)?;
+            let ext_table_rows = MasterExtTable::reinsert_empty_table_columns(
+                &empty_tables,
+                ext_table_rows,
+                challenges,
+            )?;
+            all_indexed_rows.push(Stark::index_revealed_rows(
+                revealed_current_and_next_row_indices.clone(),
+                base_table_rows,
+                ext_table_rows,
+            ));
+        }
+        let revealed_combination_leafs = Stark::dequeue_and_authenticate_combination_leafs(
+            &mut proof_stream,
+            combination_root,
+            &revealed_current_row_indices,
+            maybe_profiler,
+        )?;
+        prof_stop!(maybe_profiler, "check leafs");
+
+        prof_start!(maybe_profiler, "nonlinear combination");
+        let combination_evaluator = CombinationEvaluator::new(stark, padded_height);
+        for (current_row_idx, revealed_combination_leaf) in revealed_current_row_indices
+            .into_iter()
+            .zip_eq(revealed_combination_leafs)
+        {
+            let mut expected_leaf = XFieldElement::zero();
+            for ((base_rows, ext_rows, randomizer_rows), (challenges, weights)) in all_indexed_rows
+                .iter()
+                .zip_eq(all_challenges.iter().zip_eq(&all_weights))
+            {
+                expected_leaf += combination_evaluator.evaluate(
+                    current_row_idx,
+                    base_rows,
+                    ext_rows,
+                    challenges,
+                    weights,
+                    &mut None,
+                );
+                expected_leaf += randomizer_rows[&current_row_idx].sum();
+            }
+            if !stark.leafs_match(revealed_combination_leaf, expected_leaf) {
+                return Err(anyhow!(StarkValidationError::CombinationLeafInequality));
+            }
+        }
+        prof_stop!(maybe_profiler, "nonlinear combination");
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod batch_stark_tests {
+    use super::*;
+
+    /// Writes the input, then counts it down to 0, taking time proportional to the input.
+    fn count_down_program() -> Program {
+        let source_code = "read_io dup0 write_io call loop pop halt \
+            loop: dup0 push 0 eq skiz return push -1 add recurse";
+        Program::from_code(source_code).unwrap()
+    }
+
+    fn inputs(values: &[u64]) -> Vec<(Vec<BFieldElement>, Vec<BFieldElement>)> {
+        values
+            .iter()
+            .map(|&value| (vec![BFieldElement::new(value)], vec![]))
+            .collect()
+    }
+
+    fn parameters() -> StarkParameters {
+        StarkParameters::new(32, 4)
+    }
+
+    #[test]
+    fn prove_verify_batch_test() {
+        let (claim, aets) = simulate_batch(&count_down_program(), inputs(&[1, 10, 30])).unwrap();
+        assert_eq!(3, claim.num_executions());
+        assert_eq!(vec![BFieldElement::new(10)], claim.inputs_and_outputs[1].1);
+
+        let batch_stark = BatchStark::new(claim, parameters());
+        let proof = batch_stark.prove(aets, &mut None);
+        assert!(batch_stark.verify(proof.clone(), &mut None).unwrap());
+
+        let batch_stark = BatchStark {
+            stark: batch_stark.stark.with_constant_time_verification(),
+            ..batch_stark
+        };
+        assert!(batch_stark.verify(proof, &mut None).unwrap());
+    }
+
+    #[test]
+    fn batch_proof_is_smaller_than_individual_proofs_test() {
+        let program = count_down_program();
+        let (claim, aets) = simulate_batch(&program, inputs(&[5, 6, 7, 8])).unwrap();
+        let batch_stark = BatchStark::new(claim.clone(), parameters());
+        let batch_proof = batch_stark.prove(aets.clone(), &mut None);
+
+        let mut individual_proofs_length = 0;
+        for (index, aet) in aets.into_iter().enumerate() {
+            let individual_claim = Claim {
+                padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+                ..claim.claim(index)
+            };
+            let stark = Stark::new(individual_claim, parameters());
+            let proof = stark.prove(aet, &mut None);
+            assert!(stark.verify(proof.clone(), &mut None).unwrap());
+            individual_proofs_length += proof.0.len();
+        }
+        assert!(batch_proof.0.len() < individual_proofs_length);
+    }
+
+    #[test]
+    fn batch_proof_for_different_outputs_is_rejected_test() {
+        let (claim, aets) = simulate_batch(&count_down_program(), inputs(&[3, 4])).unwrap();
+        let batch_stark = BatchStark::new(claim.clone(), parameters());
+        let proof = batch_stark.prove(aets, &mut None);
+
+        let mut swapped_claim = claim;
+        swapped_claim.inputs_and_outputs.swap(0, 1);
+        swapped_claim.inputs_and_outputs[0].0 = vec![BFieldElement::new(3)];
+        let dishonest_batch_stark = BatchStark::new(swapped_claim, parameters());
+        assert!(dishonest_batch_stark.verify(proof, &mut None).is_err());
+    }
+
+    #[test]
+    fn batch_proof_binds_output_tags_test() {
+        let program = Program::from_code("read_io dup0 write_io push 1 add write_io halt").unwrap();
+        let (mut claim, aets) = simulate_batch(&program, inputs(&[3, 4])).unwrap();
+        claim.tag_outputs(&aets);
+        assert_eq!(claim.output_tags(1), [4, 0].map(BFieldElement::new));
+
+        let batch_stark = BatchStark::new(claim.clone(), parameters());
+        let proof = batch_stark.prove(aets, &mut None);
+        assert!(batch_stark.verify(proof.clone(), &mut None).unwrap());
+
+        let mut swapped_tags = claim.clone();
+        swapped_tags.output_tags[0].swap(0, 1);
+        let dishonest_batch_stark = BatchStark::new(swapped_tags, parameters());
+        assert!(!matches!(
+            dishonest_batch_stark.verify(proof.clone(), &mut None),
+            Ok(true)
+        ));
+
+        let mut too_few_tags = claim;
+        too_few_tags.output_tags[1].pop();
+        let dishonest_batch_stark = BatchStark::new(too_few_tags, parameters());
+        assert!(dishonest_batch_stark.verify(proof, &mut None).is_err());
+    }
+
+    #[test]
+    fn failing_execution_is_reported_test() {
+        let program = Program::from_code("read_io assert halt").unwrap();
+        let result = simulate_batch(&program, inputs(&[1, 0, 1]));
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("Execution 1 failed"));
+    }
+}
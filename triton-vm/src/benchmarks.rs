@@ -0,0 +1,280 @@
+//! A fixed corpus of realistic programs along with their input, for measuring the performance of
+//! Triton VM. Because the programs and their inputs are deterministic, measurements on different
+//! forks or releases of Triton VM are comparable as long as they are taken on the same benchmark.
+//!
+//! To keep measurements comparable, existing benchmarks must never change. New workloads are added
+//! as new benchmarks instead.
+
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+
+use crate::shared_tests::SourceCodeAndInput;
+use crate::sponge::Sponge;
+
+/// A program of the benchmark corpus, its input, and the output it must produce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkProgram {
+    /// Unique among all benchmarks of the corpus.
+    pub name: &'static str,
+    pub program: SourceCodeAndInput,
+    pub expected_output: Vec<BFieldElement>,
+}
+
+/// All benchmarks of the corpus.
+pub fn corpus() -> Vec<BenchmarkProgram> {
+    vec![
+        merkle_verification(),
+        sponge_hashing(),
+        u64_arithmetic(),
+        ram_sort(),
+    ]
+}
+
+/// Verify authentication paths of several leaves in a Merkle tree using `divine_sibling`.
+fn merkle_verification() -> BenchmarkProgram {
+    const TREE_HEIGHT: u32 = 20;
+    const NUM_PATHS: u64 = 32;
+
+    let source_code = "
+        read_io call verify_paths pop halt
+
+        // _ num_paths
+        verify_paths:
+            read_io
+            read_io read_io read_io read_io read_io
+            push 0 push 0 push 0 push 0 push 0
+            call climb
+            pop pop pop pop pop
+            read_io read_io read_io read_io read_io
+            assert_vector
+            pop pop pop pop pop pop pop pop pop pop pop
+            push -1 add dup0 skiz recurse return
+
+        // _ node_index [digest; 5] [garbage; 5]
+        climb:
+            divine_sibling hash
+            dup10 push 1 eq skiz return
+            recurse
+    ";
+
+    let mut input = vec![BFieldElement::new(NUM_PATHS)];
+    let mut secret_input = vec![];
+    let mut node_counter = 0;
+    let mut pseudo_random_digest = || {
+        node_counter += 1;
+        RescuePrimeRegular::hash_varlen(&[BFieldElement::new(node_counter)])
+    };
+    for path in 0..NUM_PATHS {
+        let leaf_index = (path * 0x2_1f35) % (1 << TREE_HEIGHT);
+        let mut node_index = (1 << TREE_HEIGHT) + leaf_index;
+        let leaf = pseudo_random_digest();
+
+        input.push(BFieldElement::new(node_index));
+        input.extend(leaf.iter().rev());
+
+        let mut node = leaf;
+        while node_index > 1 {
+            let sibling = pseudo_random_digest();
+            secret_input.extend(sibling.iter().rev());
+            let (left, right) = match node_index % 2 {
+                0 => (node, sibling),
+                _ => (sibling, node),
+            };
+            let hash_input = [left, right].concat();
+            node = RescuePrimeRegular::hash_10(&hash_input.try_into().unwrap());
+            node_index >>= 1;
+        }
+        input.extend(node.iter().rev());
+    }
+
+    BenchmarkProgram {
+        name: "merkle_verification",
+        program: SourceCodeAndInput {
+            source_code: source_code.to_string(),
+            input,
+            secret_input,
+        },
+        expected_output: vec![],
+    }
+}
+
+/// Alternately squeeze and absorb the sponge of the `sponge_*` pseudo-instructions, like a
+/// verifier does when sampling many pseudo-random elements for Fiat-Shamir.
+fn sponge_hashing() -> BenchmarkProgram {
+    const NUM_ITERATIONS: u64 = 256;
+
+    let source_code = "
+        read_io sponge_init call squeeze_absorb
+        write_io write_io write_io write_io write_io
+        pop halt
+
+        // _ num_iterations [sponge_state; 5]
+        squeeze_absorb:
+            sponge_squeeze sponge_absorb
+            swap5 push -1 add swap5
+            dup5 skiz recurse return
+    ";
+
+    let mut sponge = Sponge::new();
+    for _ in 0..NUM_ITERATIONS {
+        let squeezed = sponge.squeeze();
+        sponge.absorb(squeezed);
+    }
+
+    BenchmarkProgram {
+        name: "sponge_hashing",
+        program: SourceCodeAndInput {
+            source_code: source_code.to_string(),
+            input: vec![BFieldElement::new(NUM_ITERATIONS)],
+            secret_input: vec![],
+        },
+        expected_output: sponge.state.to_vec(),
+    }
+}
+
+/// Iterate a linear congruential generator modulo 2^64, representing u64s as two u32 limbs.
+fn u64_arithmetic() -> BenchmarkProgram {
+    const NUM_ITERATIONS: u64 = 1000;
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+    const INCREMENT: u64 = 1_442_695_040_888_963_407;
+    const SEED: u64 = 0x0123_4567_89ab_cdef;
+
+    let limbs = |value: u64| (value >> 32, value & 0xffff_ffff);
+    let (multiplier_hi, multiplier_lo) = limbs(MULTIPLIER);
+    let (increment_hi, increment_lo) = limbs(INCREMENT);
+    let source_code = format!(
+        "
+        read_io read_io read_io call lcg
+        write_io write_io pop halt
+
+        // _ num_iterations hi lo
+        lcg:
+            dup0 push {multiplier_lo} mul split     // _ n hi lo lo₀ carry₀
+            swap2 push {multiplier_hi} mul split pop
+            swap3 push {multiplier_lo} mul split pop
+            swap1 push {increment_lo} add split     // _ n q₁ carry₀ q₂ lo' carry₁
+            swap1 swap4 add add add
+            push {increment_hi} add split pop       // _ n lo' hi'
+            swap1
+            swap2 push -1 add swap2
+            dup2 skiz recurse return
+        "
+    );
+
+    let mut value = SEED;
+    for _ in 0..NUM_ITERATIONS {
+        value = value.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+    }
+    let (seed_hi, seed_lo) = limbs(SEED);
+    let (value_hi, value_lo) = limbs(value);
+
+    BenchmarkProgram {
+        name: "u64_arithmetic",
+        program: SourceCodeAndInput {
+            source_code,
+            input: [NUM_ITERATIONS, seed_hi, seed_lo]
+                .map(BFieldElement::new)
+                .to_vec(),
+            secret_input: vec![],
+        },
+        expected_output: [value_lo, value_hi].map(BFieldElement::new).to_vec(),
+    }
+}
+
+/// Bubble sort u32s in RAM.
+fn ram_sort() -> BenchmarkProgram {
+    const NUM_ELEMENTS: u64 = 16;
+
+    let source_code = "
+        read_io
+        push 0 call read_elements pop
+        dup0 push -1 add call sort pop
+        push 0 call write_elements pop
+        pop halt
+
+        // _ n i
+        read_elements:
+            dup0 read_io write_mem pop pop
+            push 1 add dup0 dup2 eq skiz return
+            recurse
+
+        // _ n num_remaining_passes
+        sort:
+            push 0 call pass pop
+            push -1 add dup0 skiz recurse return
+
+        // _ n num_remaining_passes i
+        pass:
+            dup0 push 0 read_mem
+            swap1 push 1 add push 0 read_mem
+            swap1 pop                               // _ n k i a b
+            dup1 dup1 lt skiz call swap_elements
+            pop pop
+            push 1 add dup0 push 1 add dup3 eq skiz return
+            recurse
+
+        // _ n num_remaining_passes i a b
+        swap_elements:
+            dup2 dup1 write_mem pop pop
+            dup2 push 1 add dup2 write_mem pop pop
+            return
+
+        // _ n i
+        write_elements:
+            dup0 push 0 read_mem write_io pop
+            push 1 add dup0 dup2 eq skiz return
+            recurse
+    ";
+
+    let mut state = 0x2545_f491_u64;
+    let elements: Vec<_> = (0..NUM_ELEMENTS)
+        .map(|_| {
+            state = (state * 1_103_515_245 + 12_345) % (1 << 31);
+            state
+        })
+        .collect();
+    let mut sorted_elements = elements.clone();
+    sorted_elements.sort_unstable();
+
+    let mut input = vec![NUM_ELEMENTS];
+    input.extend(elements);
+    BenchmarkProgram {
+        name: "ram_sort",
+        program: SourceCodeAndInput {
+            source_code: source_code.to_string(),
+            input: input.into_iter().map(BFieldElement::new).collect(),
+            secret_input: vec![],
+        },
+        expected_output: sorted_elements
+            .into_iter()
+            .map(BFieldElement::new)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod benchmarks_tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn all_benchmarks_produce_expected_output_test() {
+        for benchmark in corpus() {
+            let (aet, stdout) = benchmark
+                .program
+                .simulate()
+                .unwrap_or_else(|err| panic!("Benchmark {} failed: {err}", benchmark.name));
+            let num_cycles = aet.processor_matrix.nrows();
+            println!("{}: {num_cycles} cycles", benchmark.name);
+            assert_eq!(benchmark.expected_output, stdout, "{}", benchmark.name);
+        }
+    }
+
+    #[test]
+    fn benchmark_names_are_unique_test() {
+        let corpus = corpus();
+        let names = corpus.iter().map(|benchmark| benchmark.name);
+        assert_eq!(corpus.len(), names.unique().count());
+    }
+}
@@ -75,8 +75,8 @@ impl BFieldCodec for Digest {
 impl<T: BFieldCodec, S: BFieldCodec> BFieldCodec for (T, S) {
     fn decode(str: &[BFieldElement]) -> Result<Box<Self>> {
         // decode T
-        let maybe_element_zero = str.get(0);
-        if matches!(maybe_element_zero, None) {
+        let maybe_element_zero = str.first();
+        if maybe_element_zero.is_none() {
             bail!("trying to decode empty slice as tuple",);
         }
 
@@ -88,7 +88,7 @@ impl<T: BFieldCodec, S: BFieldCodec> BFieldCodec for (T, S) {
 
         // decode S
         let maybe_next_element = str.get(1 + len_t);
-        if matches!(maybe_next_element, None) {
+        if maybe_next_element.is_none() {
             bail!("trying to decode singleton as tuple");
         }
 
@@ -169,8 +169,8 @@ impl BFieldCodec for PartialAuthenticationPath<Digest> {
 
 impl<T: BFieldCodec> BFieldCodec for Option<T> {
     fn decode(str: &[BFieldElement]) -> Result<Box<Self>> {
-        let maybe_element_zero = str.get(0);
-        if matches!(maybe_element_zero, None) {
+        let maybe_element_zero = str.first();
+        if maybe_element_zero.is_none() {
             bail!("trying to decode empty slice into option of elements");
         }
 
@@ -212,7 +212,7 @@ impl BFieldCodec for Vec<BFieldElement> {
 
 impl BFieldCodec for Vec<XFieldElement> {
     fn decode(str: &[BFieldElement]) -> Result<Box<Self>> {
-        if str.len() % EXTENSION_DEGREE != 0 {
+        if !str.len().is_multiple_of(EXTENSION_DEGREE) {
             bail!(
                 "cannot decode string of BFieldElements into XFieldElements \
                 when string length is not a multiple of EXTENSION_DEGREE",
@@ -233,7 +233,7 @@ impl BFieldCodec for Vec<XFieldElement> {
 
 impl BFieldCodec for Vec<Digest> {
     fn decode(str: &[BFieldElement]) -> Result<Box<Self>> {
-        if str.len() % DIGEST_LENGTH != 0 {
+        if !str.len().is_multiple_of(DIGEST_LENGTH) {
             bail!(
                 "cannot decode string of BFieldElements into Digests \
                 when string length is not a multiple of DIGEST_LENGTH",
@@ -324,7 +324,7 @@ impl BFieldCodec for Vec<PartialAuthenticationPath<Digest>> {
                 );
             }
 
-            if (len_remaining - 2) % DIGEST_LENGTH != 0 {
+            if !(len_remaining - 2).is_multiple_of(DIGEST_LENGTH) {
                 bail!(
                     "cannot decode string of BFieldElements as Vec of PartialAuthenticationPaths \
                     due to length mismatch (3)",
@@ -370,7 +370,7 @@ impl BFieldCodec for Vec<PartialAuthenticationPath<Digest>> {
                 2u64 + std::convert::TryInto::<u64>::try_into(vector.len()).unwrap(),
             ));
             str.push(BFieldElement::new(len.try_into().unwrap()));
-            str.push(BFieldElement::new(mask.try_into().unwrap()));
+            str.push(BFieldElement::new(mask.into()));
             str.append(&mut vector);
         }
         str
@@ -388,7 +388,7 @@ mod bfield_codec_tests {
 
     fn random_bool() -> bool {
         let mut rng = thread_rng();
-        rng.next_u32() % 2 == 0
+        rng.next_u32().is_multiple_of(2)
     }
 
     fn random_length(max: usize) -> usize {
@@ -424,7 +424,6 @@ mod bfield_codec_tests {
     fn random_partial_authentication_path(len: usize) -> PartialAuthenticationPath<Digest> {
         PartialAuthenticationPath(
             (0..len)
-                .into_iter()
                 .map(|_| {
                     if random_bool() {
                         Some(random_digest())
@@ -470,10 +469,7 @@ mod bfield_codec_tests {
     fn test_encode_decode_random_vec_of_bfieldelement() {
         for _ in 1..=10 {
             let len = random_length(100);
-            let bfe_vec = (0..len)
-                .into_iter()
-                .map(|_| random_bfieldelement())
-                .collect_vec();
+            let bfe_vec = (0..len).map(|_| random_bfieldelement()).collect_vec();
             let str = bfe_vec.encode();
             let bfe_vec_ = *Vec::<BFieldElement>::decode(&str).unwrap();
             assert_eq!(bfe_vec, bfe_vec_);
@@ -484,10 +480,7 @@ mod bfield_codec_tests {
     fn test_encode_decode_random_vec_of_xfieldelement() {
         for _ in 1..=10 {
             let len = random_length(100);
-            let xfe_vec = (0..len)
-                .into_iter()
-                .map(|_| random_xfieldelement())
-                .collect_vec();
+            let xfe_vec = (0..len).map(|_| random_xfieldelement()).collect_vec();
             let str = xfe_vec.encode();
             let xfe_vec_ = *Vec::<XFieldElement>::decode(&str).unwrap();
             assert_eq!(xfe_vec, xfe_vec_);
@@ -498,7 +491,7 @@ mod bfield_codec_tests {
     fn test_encode_decode_random_vec_of_digest() {
         for _ in 1..=10 {
             let len = random_length(100);
-            let digest_vec = (0..len).into_iter().map(|_| random_digest()).collect_vec();
+            let digest_vec = (0..len).map(|_| random_digest()).collect_vec();
             let str = digest_vec.encode();
             let digest_vec_ = *Vec::<Digest>::decode(&str).unwrap();
             assert_eq!(digest_vec, digest_vec_);
@@ -510,13 +503,9 @@ mod bfield_codec_tests {
         for _ in 1..=10 {
             let len = random_length(10);
             let bfe_vec_vec = (0..len)
-                .into_iter()
                 .map(|_| {
                     let inner_len = random_length(20);
-                    (0..inner_len)
-                        .into_iter()
-                        .map(|_| random_bfieldelement())
-                        .collect_vec()
+                    (0..inner_len).map(|_| random_bfieldelement()).collect_vec()
                 })
                 .collect_vec();
             let str = bfe_vec_vec.encode();
@@ -530,13 +519,9 @@ mod bfield_codec_tests {
         for _ in 1..=10 {
             let len = random_length(10);
             let xfe_vec_vec = (0..len)
-                .into_iter()
                 .map(|_| {
                     let inner_len = random_length(20);
-                    (0..inner_len)
-                        .into_iter()
-                        .map(|_| random_xfieldelement())
-                        .collect_vec()
+                    (0..inner_len).map(|_| random_xfieldelement()).collect_vec()
                 })
                 .collect_vec();
             let str = xfe_vec_vec.encode();
@@ -560,10 +545,7 @@ mod bfield_codec_tests {
     fn test_decode_random_negative() {
         for _ in 1..=10000 {
             let len = random_length(100);
-            let str = (0..len)
-                .into_iter()
-                .map(|_| random_bfieldelement())
-                .collect_vec();
+            let str = (0..len).map(|_| random_bfieldelement()).collect_vec();
 
             // Some of the following cases can be triggered by false
             // positives. This should occur with probability roughly
@@ -0,0 +1,149 @@
+//! The encoding of byte strings, and thus of text, as sequences of field elements.
+//!
+//! A byte string is encoded as its length in bytes, followed by its bytes packed into elements,
+//! [`BYTES_PER_ELEMENT`] bytes per element. Within an element, the first byte is the least
+//! significant. The last element is padded with zero bytes if the string's length is not a
+//! multiple of [`BYTES_PER_ELEMENT`]. Since 2^56 is smaller than the field's modulus, every
+//! packed element is a canonical field element. The pseudo-instruction `unpack_bytes` splits a
+//! packed element into its bytes on the stack.
+//!
+//! Every byte string has exactly one encoding: [`decode_bytes`] rejects nonzero padding, elements
+//! that do not pack bytes, and sequences of the wrong length.
+
+use anyhow::bail;
+use anyhow::Result;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+/// The number of bytes packed into one field element.
+pub const BYTES_PER_ELEMENT: usize = 7;
+
+/// The number of field elements encoding a byte string of the given length, including the length.
+pub fn encoding_length(num_bytes: usize) -> usize {
+    1 + num_bytes.div_ceil(BYTES_PER_ELEMENT)
+}
+
+/// The field elements encoding the given bytes.
+pub fn encode_bytes(bytes: &[u8]) -> Vec<BFieldElement> {
+    let mut encoding = vec![BFieldElement::new(bytes.len() as u64)];
+    for chunk in bytes.chunks(BYTES_PER_ELEMENT) {
+        let packed = chunk
+            .iter()
+            .rev()
+            .fold(0_u64, |acc, &byte| (acc << 8) | byte as u64);
+        encoding.push(BFieldElement::new(packed));
+    }
+    encoding
+}
+
+/// The bytes encoded by the given field elements, or an error if they are not a valid encoding.
+pub fn decode_bytes(encoding: &[BFieldElement]) -> Result<Vec<u8>> {
+    let Some((num_bytes, packed_elements)) = encoding.split_first() else {
+        bail!("Encoding of byte string must contain the length.");
+    };
+    let Ok(num_bytes) = usize::try_from(num_bytes.value()) else {
+        bail!("Length {num_bytes} of byte string is too large.");
+    };
+    if encoding.len() != encoding_length(num_bytes) {
+        bail!(
+            "Encoding of {num_bytes} bytes must have length {}, but has length {}.",
+            encoding_length(num_bytes),
+            encoding.len()
+        );
+    }
+
+    let mut bytes = Vec::with_capacity(num_bytes);
+    for element in packed_elements {
+        let value = element.value();
+        if value >> (8 * BYTES_PER_ELEMENT) != 0 {
+            bail!("Element {element} does not pack {BYTES_PER_ELEMENT} bytes.");
+        }
+        bytes.extend_from_slice(&value.to_le_bytes()[..BYTES_PER_ELEMENT]);
+    }
+    if bytes[num_bytes..].iter().any(|&byte| byte != 0) {
+        bail!("Padding of byte string must be zero.");
+    }
+    bytes.truncate(num_bytes);
+    Ok(bytes)
+}
+
+/// The field elements encoding the given string's UTF-8 bytes.
+pub fn encode_str(string: &str) -> Vec<BFieldElement> {
+    encode_bytes(string.as_bytes())
+}
+
+/// The string encoded by the given field elements, or an error if they are not a valid encoding
+/// of UTF-8 bytes.
+pub fn decode_string(encoding: &[BFieldElement]) -> Result<String> {
+    Ok(String::from_utf8(decode_bytes(encoding)?)?)
+}
+
+#[cfg(test)]
+mod byte_string_tests {
+    use rand::random;
+
+    use crate::shared_tests::SourceCodeAndInput;
+
+    use super::*;
+
+    #[test]
+    fn encoding_roundtrip_test() {
+        for num_bytes in 0..3 * BYTES_PER_ELEMENT {
+            let bytes = (0..num_bytes).map(|_| random()).collect::<Vec<u8>>();
+            let encoding = encode_bytes(&bytes);
+            assert_eq!(encoding_length(num_bytes), encoding.len());
+            assert_eq!(bytes, decode_bytes(&encoding).unwrap());
+        }
+        let text = "Triton VM – ✓";
+        assert_eq!(text, decode_string(&encode_str(text)).unwrap());
+    }
+
+    #[test]
+    fn encoding_is_little_endian_test() {
+        let encoding = encode_str("abcdefgh");
+        let expected = vec![
+            BFieldElement::new(8),
+            BFieldElement::new(0x67_6665_6463_6261),
+            BFieldElement::new(0x68),
+        ];
+        assert_eq!(expected, encoding);
+    }
+
+    #[test]
+    fn non_canonical_encodings_are_rejected_test() {
+        let encoding = encode_str("abc");
+        assert!(decode_bytes(&[]).is_err());
+        assert!(decode_bytes(&encoding[..1]).is_err());
+        assert!(decode_bytes(&[encoding.clone(), vec![BFieldElement::new(0)]].concat()).is_err());
+
+        let mut nonzero_padding = encoding.clone();
+        nonzero_padding[1] += BFieldElement::new(1 << 24);
+        assert!(decode_bytes(&nonzero_padding).is_err());
+
+        let mut too_large = encoding;
+        too_large[0] = BFieldElement::new(7);
+        too_large[1] += BFieldElement::new(1 << 56);
+        assert!(decode_bytes(&too_large).is_err());
+
+        assert!(decode_string(&encode_bytes(&[0xff])).is_err());
+    }
+
+    #[test]
+    fn unpack_bytes_agrees_with_encoding_test() {
+        let bytes: [u8; BYTES_PER_ELEMENT] = random();
+        let packed = encode_bytes(&bytes)[1];
+        let source_code = format!(
+            "push {packed} unpack_bytes \
+             write_io write_io write_io write_io write_io write_io write_io halt"
+        );
+        let output = SourceCodeAndInput::without_input(&source_code).run();
+        let expected = bytes.map(|byte| BFieldElement::new(byte as u64));
+        assert_eq!(expected.to_vec(), output);
+    }
+
+    #[test]
+    #[should_panic(expected = "Assertion failed")]
+    fn unpack_bytes_rejects_element_not_packing_bytes_test() {
+        let source_code = format!("push {} unpack_bytes halt", 1_u64 << 56);
+        SourceCodeAndInput::without_input(&source_code).run();
+    }
+}
@@ -30,6 +30,8 @@ use crate::proof_item::FriResponse;
 use crate::proof_item::ProofItem;
 use crate::proof_stream::ProofStream;
 use crate::stark::Maker;
+use crate::x_field_ops::constant_time_eq;
+use crate::x_field_ops::constant_time_inverse;
 
 impl Error for FriValidationError {}
 
@@ -58,6 +60,10 @@ pub struct Fri<H> {
     pub expansion_factor: usize,
     pub colinearity_checks_count: usize,
     pub domain: ArithmeticDomain,
+
+    /// Whether the verifier uses constant-time field operations where available. See
+    /// [`Stark::with_constant_time_verification`](crate::stark::Stark::with_constant_time_verification).
+    pub constant_time: bool,
     _hasher: PhantomData<H>,
 }
 
@@ -74,6 +80,7 @@ impl<H: AlgebraicHasher> Fri<H> {
             domain,
             expansion_factor,
             colinearity_checks_count,
+            constant_time: false,
             _hasher,
         }
     }
@@ -182,10 +189,6 @@ impl<H: AlgebraicHasher> Fri<H> {
         let mut offset = self.domain.offset;
         let mut codeword_local = codeword.to_vec();
 
-        let one: XFieldElement = XFieldElement::one();
-        let two: XFieldElement = one + one;
-        let two_inv = one / two;
-
         // Compute and send Merkle root
         let mut digests: Vec<Digest> = Vec::with_capacity(codeword_local.len());
         codeword_local
@@ -207,21 +210,12 @@ impl<H: AlgebraicHasher> Fri<H> {
             let challenge_digest = proof_stream.prover_fiat_shamir();
             let alpha: XFieldElement = XFieldElement::sample(&challenge_digest);
 
-            let x_offset: Vec<XFieldElement> = subgroup_generator
-                .get_cyclic_group_elements(None)
-                .into_par_iter()
-                .map(|x| (x * offset).lift())
-                .collect();
-
-            let x_offset_inverses = XFieldElement::batch_inversion(x_offset);
-            codeword_local = (0..n / 2)
-                .into_par_iter()
-                .map(|i| {
-                    two_inv
-                        * ((one + alpha * x_offset_inverses[i]) * codeword_local[i]
-                            + (one - alpha * x_offset_inverses[i]) * codeword_local[n / 2 + i])
-                })
-                .collect();
+            let domain = ArithmeticDomain {
+                offset,
+                generator: subgroup_generator,
+                length: n,
+            };
+            codeword_local = fold_codeword(&codeword_local, domain, alpha);
 
             // Compute and send Merkle root. We have to do that within this loops, since
             // the next round's alpha must be calculated from the previous round's Merkle root.
@@ -434,11 +428,13 @@ impl<H: AlgebraicHasher> Fri<H> {
             let c_values = (0..self.colinearity_checks_count)
                 .into_par_iter()
                 .map(|i| {
-                    Polynomial::<XFieldElement>::get_colinear_y(
-                        (self.get_evaluation_argument(a_indices[i], r), a_values[i]),
-                        (self.get_evaluation_argument(b_indices[i], r), b_values[i]),
-                        alphas[r],
-                    )
+                    let a = (self.get_evaluation_argument(a_indices[i], r), a_values[i]);
+                    let b = (self.get_evaluation_argument(b_indices[i], r), b_values[i]);
+                    if self.constant_time {
+                        Self::constant_time_colinear_y(a, b, alphas[r])
+                    } else {
+                        Polynomial::<XFieldElement>::get_colinear_y(a, b, alphas[r])
+                    }
                 })
                 .collect();
 
@@ -453,7 +449,14 @@ impl<H: AlgebraicHasher> Fri<H> {
         // enclosing scope) with last codeword from the proofstream.
         prof_start!(maybe_profiler, "compare last codeword");
         a_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
-        if (0..self.colinearity_checks_count).any(|i| last_codeword[a_indices[i]] != a_values[i]) {
+        let last_codeword_matches = if self.constant_time {
+            (0..self.colinearity_checks_count).fold(true, |acc, i| {
+                acc & constant_time_eq(last_codeword[a_indices[i]], a_values[i])
+            })
+        } else {
+            (0..self.colinearity_checks_count).all(|i| last_codeword[a_indices[i]] == a_values[i])
+        };
+        if !last_codeword_matches {
             return Err(anyhow::Error::new(
                 FriValidationError::MismatchingLastCodeword,
             ));
@@ -462,6 +465,18 @@ impl<H: AlgebraicHasher> Fri<H> {
         Ok(())
     }
 
+    /// Like [`Polynomial::get_colinear_y`], but using [`constant_time_inverse`] for the division.
+    fn constant_time_colinear_y(
+        p0: (XFieldElement, XFieldElement),
+        p1: (XFieldElement, XFieldElement),
+        p2_x: XFieldElement,
+    ) -> XFieldElement {
+        let dy = p0.1 - p1.1;
+        let dx = p0.0 - p1.0;
+        let p2_y_times_dx = dy * (p2_x - p0.0) + dx * p0.1;
+        p2_y_times_dx * constant_time_inverse(dx)
+    }
+
     /// Given index `i` of the FRI codeword in round `round`, compute the corresponding value in the
     /// FRI (co-)domain. This corresponds to `ω^i` in `f(ω^i)` from
     /// [STARK-Anatomy](https://neptune.cash/learn/stark-anatomy/fri/#split-and-fold).
@@ -473,7 +488,7 @@ impl<H: AlgebraicHasher> Fri<H> {
         evaluation_argument.lift()
     }
 
-    fn num_rounds(&self) -> (u8, u32) {
+    pub(crate) fn num_rounds(&self) -> (u8, u32) {
         let max_degree = (self.domain.length / self.expansion_factor) - 1;
         let mut rounds_count = log_2_ceil(max_degree as u128 + 1) as u8;
         let mut max_degree_of_last_round = 0u32;
@@ -490,6 +505,36 @@ impl<H: AlgebraicHasher> Fri<H> {
     }
 }
 
+/// One round of FRI folding. The given codeword over the given domain is the evaluation of some
+/// polynomial `f(X) = f_E(X²) + X·f_O(X²)`. The returned codeword, over the domain of half the
+/// length with squared offset and generator, is the evaluation of `f_E(X) + alpha·f_O(X)`.
+pub fn fold_codeword(
+    codeword: &[XFieldElement],
+    domain: ArithmeticDomain,
+    alpha: XFieldElement,
+) -> Vec<XFieldElement> {
+    let n = codeword.len();
+    let one = XFieldElement::one();
+    let two_inv = one / (one + one);
+
+    let x_offset: Vec<XFieldElement> = domain
+        .generator
+        .get_cyclic_group_elements(None)
+        .into_par_iter()
+        .map(|x| (x * domain.offset).lift())
+        .collect();
+
+    let x_offset_inverses = XFieldElement::batch_inversion(x_offset);
+    (0..n / 2)
+        .into_par_iter()
+        .map(|i| {
+            two_inv
+                * ((one + alpha * x_offset_inverses[i]) * codeword[i]
+                    + (one - alpha * x_offset_inverses[i]) * codeword[n / 2 + i])
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod triton_xfri_tests {
     use itertools::Itertools;
@@ -629,6 +674,25 @@ mod triton_xfri_tests {
         }
     }
 
+    #[test]
+    fn constant_time_verification_test() {
+        type Hasher = RescuePrimeRegular;
+
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        fri.constant_time = true;
+        let mut proof_stream: ProofStream<ProofItem, Hasher> = ProofStream::new();
+        let subgroup = fri.domain.generator.lift().get_cyclic_group_elements(None);
+
+        let (_, merkle_root_of_round_0) = fri.prove(&subgroup, &mut proof_stream).unwrap();
+        let verdict = fri.verify(&mut proof_stream, &merkle_root_of_round_0, &mut None);
+        assert!(verdict.is_ok());
+
+        proof_stream.reset_for_verifier();
+        let bad_root_digest = corrupt_digest(&merkle_root_of_round_0);
+        let bad_verdict = fri.verify(&mut proof_stream, &bad_root_digest, &mut None);
+        assert!(bad_verdict.is_err());
+    }
+
     #[test]
     fn fri_x_field_limit_test() {
         type Hasher = RescuePrimeRegular;
@@ -0,0 +1,158 @@
+//! Hashing outside of the VM, computing exactly the digests that programs compute inside of it.
+//!
+//! Element `i` of a [`Digest`] is the one at stack position `i` counted from the top of the
+//! digest, _i.e._, element 0 is the one closest to the top of the stack. Instruction `hash` hashes
+//! stack elements `st0` through `st9`, in this order, and leaves the resulting digest in `st5`
+//! through `st9`.
+
+use anyhow::bail;
+use anyhow::Result;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::b_field_element::BFIELD_ONE;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
+
+use crate::sponge::Sponge;
+
+/// Like instruction `hash`. Element `i` of the input is stack element `st{i}` before executing
+/// the instruction.
+pub fn hash10(input: [BFieldElement; 2 * DIGEST_LENGTH]) -> Digest {
+    Digest::new(RescuePrimeRegular::hash_10(&input))
+}
+
+/// Hash any number of elements like a program using the `sponge_*` pseudo-instructions: after
+/// `sponge_init`, the elements, followed by a single 1 and as many 0s as needed to reach a multiple
+/// of the digest length, are absorbed five at a time using `sponge_absorb`. The digest is the
+/// resulting sponge state. The padding makes sure that inputs of different lengths have different
+/// digests.
+pub fn hash_varlen(elements: &[BFieldElement]) -> Digest {
+    let mut padded_elements = elements.to_vec();
+    padded_elements.push(BFIELD_ONE);
+
+    let mut sponge = Sponge::new();
+    sponge.absorb_all(&padded_elements);
+    Digest::new(sponge.state)
+}
+
+/// The root of the binary Merkle tree over the given leafs, as authenticated by `divine_sibling`
+/// followed by `hash`. The number of leafs must be a power of 2. Leaf `i` has node index
+/// `num_leafs + i`, and the children of node `n` are `2n` on the left and `2n + 1` on the right.
+pub fn merkle_root(leafs: &[Digest]) -> Result<Digest> {
+    if leafs.is_empty() || !leafs.len().is_power_of_two() {
+        bail!(
+            "The number of leafs must be a power of 2, but is {}.",
+            leafs.len()
+        );
+    }
+    let mut digests = leafs.to_vec();
+    while digests.len() > 1 {
+        digests = digests
+            .chunks(2)
+            .map(|pair| RescuePrimeRegular::hash_pair(&pair[0], &pair[1]))
+            .collect();
+    }
+    Ok(digests[0])
+}
+
+#[cfg(test)]
+mod hashing_tests {
+    use itertools::Itertools;
+    use twenty_first::shared_math::other::random_elements;
+
+    use crate::shared_tests::SourceCodeAndInput;
+
+    use super::*;
+
+    /// Pushes the elements such that element 0 ends up on top.
+    fn push_all(elements: &[BFieldElement]) -> String {
+        elements.iter().rev().map(|e| format!("push {e}")).join(" ")
+    }
+
+    fn write_digest() -> &'static str {
+        "write_io write_io write_io write_io write_io"
+    }
+
+    fn random_digest() -> Digest {
+        let elements: Vec<BFieldElement> = random_elements(DIGEST_LENGTH);
+        Digest::new(elements.try_into().unwrap())
+    }
+
+    #[test]
+    fn hash10_agrees_with_instruction_hash_test() {
+        let input: Vec<BFieldElement> = random_elements(2 * DIGEST_LENGTH);
+        let digest = hash10(input.clone().try_into().unwrap());
+
+        let source_code = format!(
+            "{push} hash pop pop pop pop pop {write} halt",
+            push = push_all(&input),
+            write = write_digest(),
+        );
+        let output = SourceCodeAndInput::without_input(&source_code).run();
+        assert_eq!(digest.values().to_vec(), output);
+    }
+
+    #[test]
+    fn hash_varlen_agrees_with_sponge_instructions_test() {
+        let swap_digests = "swap5 swap1 swap6 swap1 swap2 swap7 swap2 swap3 swap8 swap3 swap4 \
+            swap9 swap4";
+        for num_elements in [0, 4, 5, 6, 11] {
+            let elements: Vec<BFieldElement> = random_elements(num_elements);
+            let mut padded_elements = elements.clone();
+            padded_elements.push(BFIELD_ONE);
+            padded_elements.resize(
+                padded_elements.len().next_multiple_of(DIGEST_LENGTH),
+                BFieldElement::new(0),
+            );
+
+            let mut source_code = "sponge_init".to_string();
+            for chunk in padded_elements.chunks(DIGEST_LENGTH) {
+                let push = push_all(chunk);
+                source_code.push_str(&format!(" {push} {swap_digests} sponge_absorb"));
+            }
+            source_code.push_str(&format!(" {} halt", write_digest()));
+
+            let output = SourceCodeAndInput::without_input(&source_code).run();
+            assert_eq!(hash_varlen(&elements).values().to_vec(), output);
+        }
+        assert_ne!(hash_varlen(&[]), hash_varlen(&[BFieldElement::new(0)]));
+    }
+
+    #[test]
+    fn merkle_root_agrees_with_authentication_in_vm_test() {
+        let leafs = (0..4).map(|_| random_digest()).collect_vec();
+        let root = merkle_root(&leafs).unwrap();
+
+        let leaf_index = 2;
+        let siblings = [
+            leafs[3],
+            hash10(
+                [leafs[0].values(), leafs[1].values()]
+                    .concat()
+                    .try_into()
+                    .unwrap(),
+            ),
+        ];
+        let secret_input = siblings
+            .iter()
+            .flat_map(|sibling| sibling.values().into_iter().rev())
+            .collect();
+        let source_code = format!(
+            "push {node_index} {push} push 0 push 0 push 0 push 0 push 0 \
+            divine_sibling hash divine_sibling hash pop pop pop pop pop {write} halt",
+            node_index = leafs.len() + leaf_index,
+            push = push_all(&leafs[leaf_index].values()),
+            write = write_digest(),
+        );
+        let program = SourceCodeAndInput {
+            source_code,
+            input: vec![],
+            secret_input,
+        };
+        assert_eq!(root.values().to_vec(), program.run());
+
+        assert!(merkle_root(&leafs[..3]).is_err());
+        assert!(merkle_root(&[]).is_err());
+    }
+}
@@ -1,13 +1,35 @@
+pub mod analysis;
 pub mod arithmetic_domain;
+pub mod batch_stark;
+pub mod benchmarks;
 pub mod bfield_codec;
-pub mod error;
+pub mod byte_string;
 pub mod fri;
+pub mod hashing;
+pub mod malleability;
 pub mod op_stack;
+pub mod pipeline;
 pub mod proof;
+pub mod proof_cache;
+pub mod proof_file;
 pub mod proof_item;
+pub mod proof_layout;
 pub mod proof_stream;
+pub mod prover_telemetry;
+pub mod public_output;
+pub mod ram;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod reference;
+pub mod semantics_vectors;
 pub mod shared_tests;
+pub mod signed_integer;
+pub mod soundness;
+pub mod sponge;
 pub mod stark;
 pub mod state;
 pub mod table;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_corpus;
+pub mod verifier_timings;
 pub mod vm;
+pub mod x_field_ops;
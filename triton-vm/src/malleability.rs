@@ -0,0 +1,247 @@
+//! Audit of a proof's malleability, for consumers binding proofs to identifiers. Every variant of
+//! a valid proof must be rejected by the verifier – otherwise, one statement has several proofs,
+//! and a proof's identifier can be changed without invalidating the proof.
+
+use std::fmt::Display;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+
+use anyhow::bail;
+use anyhow::Result;
+use num_traits::One;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::proof::Proof;
+use crate::stark::Stark;
+
+/// A variant of a proof, derived from the original by changing it in one place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofVariant {
+    /// The element at the given position is incremented by one.
+    Incremented(usize),
+
+    /// The element at the given position is negated. Skipped for elements that are zero.
+    Negated(usize),
+
+    /// The last element is removed.
+    Truncated,
+
+    /// An element with value zero is appended.
+    Extended,
+}
+
+impl ProofVariant {
+    /// The elements of the variant of the given proof, or `None` if the variant is identical to
+    /// the proof.
+    fn apply(&self, proof: &Proof) -> Option<Proof> {
+        let mut elements = proof.0.clone();
+        match *self {
+            ProofVariant::Incremented(position) => elements[position] += BFieldElement::one(),
+            ProofVariant::Negated(position) => elements[position] = -elements[position],
+            ProofVariant::Truncated => _ = elements.pop(),
+            ProofVariant::Extended => elements.push(BFieldElement::new(0)),
+        }
+        (elements != proof.0).then_some(Proof(elements))
+    }
+
+    /// The position of the changed element, if any.
+    pub fn position(&self) -> Option<usize> {
+        match *self {
+            ProofVariant::Incremented(position) | ProofVariant::Negated(position) => Some(position),
+            ProofVariant::Truncated | ProofVariant::Extended => None,
+        }
+    }
+}
+
+/// The outcome of a malleability audit. The proof is non-malleable with respect to the checked
+/// variants if, and only if, no variant was accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MalleabilityReport {
+    pub num_variants_checked: usize,
+
+    /// Variants the verifier accepted. Each of them is a finding.
+    pub accepted: Vec<ProofVariant>,
+
+    /// Variants on which the verifier panicked instead of returning an error. They are not
+    /// accepted, but malformed proofs should not crash the verifier.
+    pub panicked: Vec<ProofVariant>,
+
+    /// The start of each item in the proof, for locating the variants' changes.
+    item_offsets: Vec<usize>,
+}
+
+impl MalleabilityReport {
+    /// Verify all variants of the given proof that change one of the elements at the given
+    /// positions, plus the truncated and the extended proof. Fails if the original proof does not
+    /// verify, or if a position lies outside the proof.
+    ///
+    /// Every variant costs one verification; consider auditing a subset of the positions.
+    pub fn audit(
+        stark: &Stark,
+        proof: &Proof,
+        positions: impl IntoIterator<Item = usize>,
+    ) -> Result<Self> {
+        if !stark.verify(proof.clone(), &mut None)? {
+            bail!("The proof to audit must be valid.");
+        }
+
+        let mut variants = vec![];
+        for position in positions {
+            if position >= proof.0.len() {
+                bail!(
+                    "Position {position} is outside the proof of length {}.",
+                    proof.0.len()
+                );
+            }
+            variants.push(ProofVariant::Incremented(position));
+            variants.push(ProofVariant::Negated(position));
+        }
+        variants.push(ProofVariant::Truncated);
+        variants.push(ProofVariant::Extended);
+
+        let mut report = Self {
+            item_offsets: Self::item_offsets(proof),
+            ..Self::default()
+        };
+        for variant in variants {
+            let Some(variant_proof) = variant.apply(proof) else {
+                continue;
+            };
+            report.num_variants_checked += 1;
+            let verdict = catch_unwind(AssertUnwindSafe(|| {
+                stark.verify(variant_proof, &mut None).unwrap_or(false)
+            }));
+            match verdict {
+                Ok(true) => report.accepted.push(variant),
+                Ok(false) => (),
+                Err(_) => report.panicked.push(variant),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Like [`audit`](Self::audit), for every position of the proof.
+    pub fn audit_all(stark: &Stark, proof: &Proof) -> Result<Self> {
+        Self::audit(stark, proof, 0..proof.0.len())
+    }
+
+    pub fn is_non_malleable(&self) -> bool {
+        self.accepted.is_empty()
+    }
+
+    /// The index of the proof item containing the given position, and the position's offset
+    /// within that item. The offset of an item's length prefix is 0.
+    pub fn locate(&self, position: usize) -> (usize, usize) {
+        let item_index = self
+            .item_offsets
+            .partition_point(|&offset| offset <= position)
+            .saturating_sub(1);
+        let item_offset = self.item_offsets.get(item_index).copied().unwrap_or(0);
+        (item_index, position - item_offset)
+    }
+
+    /// Items are length-prefixed. Stops at the first length prefix pointing past the proof.
+    fn item_offsets(proof: &Proof) -> Vec<usize> {
+        let mut item_offsets = vec![];
+        let mut offset = 0;
+        while offset < proof.0.len() {
+            item_offsets.push(offset);
+            offset += 1 + proof.0[offset].value() as usize;
+        }
+        item_offsets
+    }
+
+    fn describe(&self, variant: &ProofVariant) -> String {
+        match variant.position() {
+            Some(position) => {
+                let (item_index, offset) = self.locate(position);
+                format!("{variant:?} – item {item_index}, offset {offset}")
+            }
+            None => format!("{variant:?}"),
+        }
+    }
+}
+
+impl Display for MalleabilityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Variants checked: {}", self.num_variants_checked)?;
+        writeln!(f, "Variants accepted: {}", self.accepted.len())?;
+        for variant in self.accepted.iter() {
+            writeln!(f, "  - {}", self.describe(variant))?;
+        }
+        writeln!(f, "Variants crashing the verifier: {}", self.panicked.len())?;
+        for variant in self.panicked.iter() {
+            writeln!(f, "  - {}", self.describe(variant))?;
+        }
+        let verdict = match self.is_non_malleable() {
+            true => "non-malleable",
+            false => "MALLEABLE",
+        };
+        write!(f, "Verdict: {verdict}")
+    }
+}
+
+#[cfg(test)]
+mod malleability_tests {
+    use triton_opcodes::program::Program;
+
+    use crate::proof::Claim;
+    use crate::stark::StarkParameters;
+    use crate::table::master_table::MasterBaseTable;
+    use crate::vm::simulate;
+
+    use super::*;
+
+    fn stark_and_proof() -> (Stark, Proof) {
+        let program = Program::from_code("push 1 write_io halt").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let stark = Stark::new(claim, StarkParameters::named("fast-dev").unwrap());
+        let proof = stark.prove(aet, &mut None);
+        (stark, proof)
+    }
+
+    #[test]
+    fn proof_is_non_malleable_test() {
+        let (stark, proof) = stark_and_proof();
+
+        // The first items are the padded height, the parameters' digest, the empty tables, and the
+        // Merkle roots.
+        let stride = proof.0.len() / 20;
+        let positions = (0..20).chain((20..proof.0.len()).step_by(stride));
+        let report = MalleabilityReport::audit(&stark, &proof, positions).unwrap();
+        println!("{report}");
+
+        assert!(report.is_non_malleable());
+        assert!(report.num_variants_checked > 40);
+        assert!(report.to_string().ends_with("Verdict: non-malleable"));
+    }
+
+    #[test]
+    fn auditing_invalid_proof_fails_test() {
+        let (stark, proof) = stark_and_proof();
+        let invalid_proof = ProofVariant::Incremented(3).apply(&proof).unwrap();
+        assert!(MalleabilityReport::audit(&stark, &invalid_proof, [0]).is_err());
+        assert!(MalleabilityReport::audit(&stark, &proof, [proof.0.len()]).is_err());
+    }
+
+    #[test]
+    fn positions_are_located_in_items_test() {
+        let proof = Proof([2, 7, 7, 0, 1, 7].map(BFieldElement::new).to_vec());
+        let report = MalleabilityReport {
+            item_offsets: MalleabilityReport::item_offsets(&proof),
+            ..MalleabilityReport::default()
+        };
+        assert_eq!((0, 0), report.locate(0));
+        assert_eq!((0, 2), report.locate(2));
+        assert_eq!((1, 0), report.locate(3));
+        assert_eq!((2, 1), report.locate(5));
+    }
+}
@@ -1,14 +1,13 @@
-use anyhow::Result;
 use num_traits::Zero;
 use triton_opcodes::ord_n::Ord16;
 use triton_opcodes::ord_n::Ord16::*;
 use twenty_first::shared_math::b_field_element::BFieldElement;
 use twenty_first::shared_math::x_field_element::XFieldElement;
 
-use super::error::vm_fail;
-use super::error::InstructionError::*;
+use crate::vm::error::InstructionError;
+use crate::vm::error::InstructionError::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OpStack {
     pub stack: Vec<BFieldElement>,
 }
@@ -37,21 +36,20 @@ impl OpStack {
         self.push(elem.coefficients[0]);
     }
 
-    pub fn pop(&mut self) -> Result<BFieldElement> {
-        self.stack.pop().ok_or_else(|| vm_fail(OpStackTooShallow))
+    pub fn pop(&mut self) -> Result<BFieldElement, InstructionError> {
+        self.stack.pop().ok_or(OpStackTooShallow)
     }
 
-    pub fn pop_x(&mut self) -> Result<XFieldElement> {
+    pub fn pop_x(&mut self) -> Result<XFieldElement, InstructionError> {
         Ok(XFieldElement::new([self.pop()?, self.pop()?, self.pop()?]))
     }
 
-    pub fn pop_u32(&mut self) -> Result<u32> {
+    pub fn pop_u32(&mut self) -> Result<u32, InstructionError> {
         let elem = self.pop()?;
-        elem.try_into()
-            .map_err(|_| vm_fail(FailedU32Conversion(elem)))
+        elem.try_into().map_err(|_| FailedU32Conversion(elem))
     }
 
-    pub fn pop_n<const N: usize>(&mut self) -> Result<[BFieldElement; N]> {
+    pub fn pop_n<const N: usize>(&mut self) -> Result<[BFieldElement; N], InstructionError> {
         let mut buffer = [BFieldElement::zero(); N];
         for element in buffer.iter_mut() {
             *element = self.pop()?;
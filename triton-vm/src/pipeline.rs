@@ -0,0 +1,155 @@
+//! Proving many independent claims with high throughput.
+//!
+//! [`prove_many`] pipelines the work: one thread simulates the programs, while several provers
+//! fill, commit to, and prove the tables of previously simulated executions. Proving is itself
+//! parallel, but has sequential parts, like the Fiat-Shamir heuristic, which are overlapped with
+//! other proofs' parallel parts. The traces held in memory at any time are bounded: simulation
+//! pauses until an earlier proof is done.
+
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::Result;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::proof::Claim;
+use crate::proof::Proof;
+use crate::stark::Stark;
+use crate::stark::StarkParameters;
+use crate::table::master_table::MasterBaseTable;
+use crate::vm::simulate;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// A program to prove an execution of, together with the execution's input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvingJob {
+    pub program: Program,
+    pub stdin: Vec<BFieldElement>,
+    pub secret_in: Vec<BFieldElement>,
+}
+
+impl ProvingJob {
+    pub fn new(program: Program, stdin: Vec<BFieldElement>, secret_in: Vec<BFieldElement>) -> Self {
+        Self {
+            program,
+            stdin,
+            secret_in,
+        }
+    }
+
+    /// Execute the program, giving the claim to prove and the trace proving it.
+    fn simulate(self) -> Result<(Claim, AlgebraicExecutionTrace)> {
+        let (aet, output) = simulate(&self.program, self.stdin.clone(), self.secret_in)?;
+        let program = self.program.to_bwords();
+        let claim = Claim {
+            input: self.stdin,
+            padded_height: MasterBaseTable::padded_height(&aet, &program),
+            program,
+            output: output.into(),
+            output_tags: vec![],
+            entry_point: self.program.entry_point,
+        };
+        Ok((claim, aet))
+    }
+}
+
+/// Prove every job, returning the claims and their proofs in the order of the jobs. A job whose
+/// program fails at runtime results in the VM's error. At most `max_in_flight` executions are
+/// simulated but not yet proven at any time, bounding the memory used by their traces, and as
+/// many proofs are generated concurrently.
+///
+/// # Panics
+///
+/// Panics if `max_in_flight` is 0.
+pub fn prove_many<I>(
+    jobs: I,
+    parameters: StarkParameters,
+    max_in_flight: usize,
+) -> Vec<Result<(Claim, Proof)>>
+where
+    I: IntoIterator<Item = ProvingJob>,
+    I::IntoIter: Send,
+{
+    assert!(
+        max_in_flight > 0,
+        "At least one execution must be in flight."
+    );
+
+    // Simulating a job takes a token, finishing its proof returns it.
+    let (token_sender, token_receiver) = mpsc::sync_channel(max_in_flight);
+    for _ in 0..max_in_flight {
+        token_sender
+            .send(())
+            .expect("channel has room for all tokens");
+    }
+    let (trace_sender, trace_receiver) = mpsc::channel();
+    let trace_receiver = Mutex::new(trace_receiver);
+    let (result_sender, result_receiver) = mpsc::channel();
+    let jobs = jobs.into_iter();
+
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            for (index, job) in jobs.enumerate() {
+                token_receiver.recv().expect("provers return their tokens");
+                trace_sender
+                    .send((index, job.simulate()))
+                    .expect("provers outlive the simulator");
+            }
+        });
+        for _ in 0..max_in_flight {
+            let result_sender = result_sender.clone();
+            let token_sender = token_sender.clone();
+            let trace_receiver = &trace_receiver;
+            scope.spawn(move || loop {
+                let next_trace = trace_receiver.lock().unwrap().recv();
+                let Ok((index, simulation)) = next_trace else {
+                    return;
+                };
+                let result = simulation.map(|(claim, aet)| {
+                    let stark = Stark::new(claim.clone(), parameters);
+                    let proof = stark.prove(aet, &mut None);
+                    (claim, proof)
+                });
+                result_sender
+                    .send((index, result))
+                    .expect("results are collected after all proofs");
+                // the simulator may be done, in which case nobody awaits the token
+                let _ = token_sender.send(());
+            });
+        }
+    });
+
+    drop(result_sender);
+    let mut results = result_receiver.into_iter().collect::<Vec<_>>();
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use super::*;
+
+    #[test]
+    fn pipelined_proofs_verify_and_keep_job_order_test() {
+        let double = Program::from_code("read_io push 2 mul write_io halt").unwrap();
+        let failing = Program::from_code("push 0 assert halt").unwrap();
+        let jobs = vec![
+            ProvingJob::new(double.clone(), vec![BFieldElement::new(21)], vec![]),
+            ProvingJob::new(failing, vec![], vec![]),
+            ProvingJob::new(double, vec![BFieldElement::new(5)], vec![]),
+        ];
+        let parameters = StarkParameters::new(32, 4);
+        let results = prove_many(jobs, parameters, 2);
+        assert_eq!(3, results.len());
+        assert!(results[1].is_err());
+
+        for (result, expected_output) in [(&results[0], 42), (&results[2], 10)] {
+            let (claim, proof) = result.as_ref().unwrap();
+            assert_eq!(vec![BFieldElement::new(expected_output)], claim.output);
+            let stark = Stark::new(claim.clone(), parameters);
+            assert!(stark.verify(proof.clone(), &mut None).unwrap());
+        }
+    }
+}
@@ -12,10 +12,44 @@ impl Proof {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Claim {
     pub input: Vec<BFieldElement>,
     pub program: Vec<BFieldElement>,
     pub output: Vec<BFieldElement>,
+
+    /// The tag of every output, or empty if the output is untagged. An output's tag is the
+    /// second-topmost stack element at the time it is written, _i.e._, `st1` at `write_io`. Tags
+    /// let consumers pick the outputs they care about, regardless of their positions.
+    pub output_tags: Vec<BFieldElement>,
+
     pub padded_height: usize,
+
+    /// The address at which execution starts, _i.e._, the initial instruction pointer.
+    pub entry_point: usize,
+}
+
+impl Claim {
+    pub fn is_output_tagged(&self) -> bool {
+        !self.output_tags.is_empty()
+    }
+
+    /// Pairs of tag and output, in the order the outputs were written. Empty if the output is
+    /// untagged.
+    pub fn tagged_output(&self) -> Vec<(BFieldElement, BFieldElement)> {
+        self.output_tags
+            .iter()
+            .copied()
+            .zip(self.output.iter().copied())
+            .collect()
+    }
+
+    /// The outputs with the given tag, in the order they were written.
+    pub fn output_with_tag(&self, tag: BFieldElement) -> Vec<BFieldElement> {
+        self.tagged_output()
+            .into_iter()
+            .filter(|&(output_tag, _)| output_tag == tag)
+            .map(|(_, output)| output)
+            .collect()
+    }
 }
@@ -0,0 +1,270 @@
+//! Caching of proofs for services that are asked to prove the same thing repeatedly.
+//!
+//! A proof is cached under a key derived from the program, the public and the secret input, and
+//! the [`StarkParameters`]. Since Triton VM is deterministic, these uniquely determine the claim.
+//! Note that the key is a digest of the secret input, among others. Storage backends should be
+//! treated with the same care as the secret input itself.
+
+use std::fs::create_dir_all;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use itertools::Itertools;
+use triton_opcodes::program::Program;
+use triton_profiler::prof_start;
+use triton_profiler::prof_stop;
+use triton_profiler::triton_profiler::TritonProfiler;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
+
+use crate::proof::Claim;
+use crate::proof::Proof;
+use crate::stark::Stark;
+use crate::stark::StarkHasher;
+use crate::stark::StarkParameters;
+use crate::table::master_table::MasterBaseTable;
+use crate::vm::simulate;
+
+/// A place to keep claims and their proofs, indexed by cache key.
+pub trait ProofStorage {
+    /// The claim and proof stored under the given key, or `None` if there is no such entry.
+    fn load(&self, key: Digest) -> Result<Option<(Claim, Proof)>>;
+
+    /// Store the claim and its proof under the given key, replacing any existing entry.
+    fn store(&mut self, key: Digest, claim: &Claim, proof: &Proof) -> Result<()>;
+}
+
+/// Stores every claim and its proof in one file in the given directory. The file's name is
+/// derived from the cache key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystemStorage {
+    pub directory: PathBuf,
+}
+
+impl FileSystemStorage {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, key: Digest) -> PathBuf {
+        let key_bytes: [u8; Digest::BYTES] = key.into();
+        let file_name = key_bytes.iter().map(|byte| format!("{byte:02x}")).join("");
+        self.directory.join(format!("{file_name}.tsp"))
+    }
+}
+
+impl ProofStorage for FileSystemStorage {
+    fn load(&self, key: Digest) -> Result<Option<(Claim, Proof)>> {
+        let mut file_handle = match File::open(self.path(key)) {
+            Ok(file_handle) => file_handle,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut contents = vec![];
+        file_handle.read_to_end(&mut contents)?;
+        Ok(Some(bincode::deserialize(&contents)?))
+    }
+
+    fn store(&mut self, key: Digest, claim: &Claim, proof: &Proof) -> Result<()> {
+        create_dir_all(&self.directory)?;
+        let binary = bincode::serialize(&(claim, proof))?;
+        File::create(self.path(key))?.write_all(&binary)?;
+        Ok(())
+    }
+}
+
+/// Returns existing proofs for claims that have been proven before, and proves – and stores –
+/// everything else.
+pub struct ProofCache<S: ProofStorage> {
+    pub storage: S,
+}
+
+impl<S: ProofStorage> ProofCache<S> {
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// The key under which the proof of executing the given program on the given input, using
+    /// the given parameters, is cached.
+    pub fn key(
+        program: &Program,
+        input: &[BFieldElement],
+        secret_input: &[BFieldElement],
+        parameters: &StarkParameters,
+    ) -> Digest {
+        let program_digest = StarkHasher::hash_slice(&program.to_bwords());
+        let input_digest = StarkHasher::hash_slice(input);
+        let secret_input_digest = StarkHasher::hash_slice(secret_input);
+        let entry_point = BFieldElement::new(program.entry_point as u64);
+
+        let key_preimage = [
+            program_digest.values().to_vec(),
+            input_digest.values().to_vec(),
+            secret_input_digest.values().to_vec(),
+            vec![entry_point],
+            parameters.digest().values().to_vec(),
+        ]
+        .concat();
+        StarkHasher::hash_slice(&key_preimage)
+    }
+
+    /// The claim and proof of executing the given program on the given input. Only simulates and
+    /// proves if the cache holds no proof for the same program, input, and parameters.
+    pub fn prove(
+        &mut self,
+        program: &Program,
+        input: Vec<BFieldElement>,
+        secret_input: Vec<BFieldElement>,
+        parameters: StarkParameters,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<(Claim, Proof)> {
+        let key = Self::key(program, &input, &secret_input, &parameters);
+        prof_start!(maybe_profiler, "proof cache lookup");
+        let cached = self.storage.load(key)?;
+        prof_stop!(maybe_profiler, "proof cache lookup");
+        if let Some(claim_and_proof) = cached {
+            return Ok(claim_and_proof);
+        }
+
+        prof_start!(maybe_profiler, "simulate");
+        let (aet, output) = simulate(program, input.clone(), secret_input)?;
+        prof_stop!(maybe_profiler, "simulate");
+
+        let program_words = program.to_bwords();
+        let padded_height = MasterBaseTable::padded_height(&aet, &program_words);
+        let claim = Claim {
+            input,
+            program: program_words,
+            output: output.into(),
+            output_tags: vec![],
+            padded_height,
+            entry_point: program.entry_point,
+        };
+        let stark = Stark::new(claim, parameters);
+        prof_start!(maybe_profiler, "prove");
+        let proof = stark.prove(aet, maybe_profiler);
+        prof_stop!(maybe_profiler, "prove");
+
+        self.storage.store(key, &stark.claim, &proof)?;
+        Ok((stark.claim, proof))
+    }
+}
+
+#[cfg(test)]
+mod proof_cache_tests {
+    use std::collections::HashMap;
+
+    use rand::random;
+
+    use super::*;
+
+    /// Keeps everything in memory and counts how often it was written to.
+    #[derive(Default)]
+    struct InMemoryStorage {
+        entries: HashMap<Digest, (Claim, Proof)>,
+        num_stores: usize,
+    }
+
+    impl ProofStorage for InMemoryStorage {
+        fn load(&self, key: Digest) -> Result<Option<(Claim, Proof)>> {
+            Ok(self.entries.get(&key).cloned())
+        }
+
+        fn store(&mut self, key: Digest, claim: &Claim, proof: &Proof) -> Result<()> {
+            self.entries.insert(key, (claim.clone(), proof.clone()));
+            self.num_stores += 1;
+            Ok(())
+        }
+    }
+
+    fn parameters() -> StarkParameters {
+        StarkParameters::new(32, 4)
+    }
+
+    fn program() -> Program {
+        Program::from_code("read_io read_io add write_io halt").unwrap()
+    }
+
+    #[test]
+    fn identical_claims_are_proven_only_once_test() {
+        let mut cache = ProofCache::new(InMemoryStorage::default());
+        let input = vec![BFieldElement::new(3), BFieldElement::new(4)];
+
+        for _ in 0..2 {
+            let (claim, proof) = cache
+                .prove(&program(), input.clone(), vec![], parameters(), &mut None)
+                .unwrap();
+            assert_eq!(vec![BFieldElement::new(7)], claim.output);
+            let stark = Stark::new(claim, parameters());
+            assert!(stark.verify(proof, &mut None).unwrap());
+        }
+        assert_eq!(1, cache.storage.num_stores);
+
+        let other_input = vec![BFieldElement::new(5), BFieldElement::new(6)];
+        let (claim, _) = cache
+            .prove(&program(), other_input, vec![], parameters(), &mut None)
+            .unwrap();
+        assert_eq!(vec![BFieldElement::new(11)], claim.output);
+        assert_eq!(2, cache.storage.num_stores);
+    }
+
+    #[test]
+    fn key_depends_on_everything_determining_the_claim_test() {
+        let program = program();
+        let input = [BFieldElement::new(1)];
+        let key = ProofCache::<InMemoryStorage>::key(&program, &input, &[], &parameters());
+
+        let other_program = Program::from_code("read_io write_io halt").unwrap();
+        let other_parameters = StarkParameters::new(64, 4);
+        let generator = BFieldElement::generator();
+        let other_offset = generator * generator;
+        let other_offset_parameters = parameters().with_fri_domain_offset(other_offset).unwrap();
+        let other_keys = [
+            ProofCache::<InMemoryStorage>::key(&other_program, &input, &[], &parameters()),
+            ProofCache::<InMemoryStorage>::key(&program, &[], &[], &parameters()),
+            ProofCache::<InMemoryStorage>::key(&program, &[], &input, &parameters()),
+            ProofCache::<InMemoryStorage>::key(&program, &input, &[], &other_parameters),
+            ProofCache::<InMemoryStorage>::key(&program, &input, &[], &other_offset_parameters),
+        ];
+        for other_key in other_keys {
+            assert_ne!(key, other_key);
+        }
+    }
+
+    #[test]
+    fn failing_program_is_not_cached_test() {
+        let mut cache = ProofCache::new(InMemoryStorage::default());
+        let program = Program::from_code("push 0 assert halt").unwrap();
+        let result = cache.prove(&program, vec![], vec![], parameters(), &mut None);
+        assert!(result.is_err());
+        assert_eq!(0, cache.storage.num_stores);
+    }
+
+    #[test]
+    fn file_system_storage_roundtrip_test() {
+        let directory = std::env::temp_dir().join(format!("proof_cache_{}", random::<u64>()));
+        let storage = FileSystemStorage::new(&directory);
+        let key = random();
+        assert!(storage.load(key).unwrap().is_none());
+
+        let mut cache = ProofCache::new(storage.clone());
+        let input = vec![BFieldElement::new(3), BFieldElement::new(4)];
+        let (claim, proof) = cache
+            .prove(&program(), input.clone(), vec![], parameters(), &mut None)
+            .unwrap();
+
+        let key = ProofCache::<FileSystemStorage>::key(&program(), &input, &[], &parameters());
+        let (loaded_claim, loaded_proof) = storage.load(key).unwrap().unwrap();
+        assert_eq!(claim.output, loaded_claim.output);
+        assert_eq!(proof.0, loaded_proof.0);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+//! A file format bundling a claim, the parameters used to prove it, and the proof.
+//!
+//! Keeping the three together, guarded by a checksum, turns operational mix-ups – verifying a
+//! proof against the wrong claim, or a truncated or otherwise corrupted file – into clear errors
+//! instead of a mere “proof is invalid.”
+//!
+//! The format is: the magic bytes `TRITONPF`, the format version as a little-endian `u32`, the
+//! 32-byte BLAKE3 checksum of the payload, and the payload, which is the bincode serialization of
+//! the [`ProofFile`].
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use triton_profiler::triton_profiler::TritonProfiler;
+
+use crate::proof::Claim;
+use crate::proof::Proof;
+use crate::stark::Stark;
+use crate::stark::StarkParameters;
+
+pub(crate) const MAGIC_BYTES: &[u8; 8] = b"TRITONPF";
+pub(crate) const FORMAT_VERSION: u32 = 3;
+pub(crate) const CHECKSUM_LENGTH: usize = 32;
+const HEADER_LENGTH: usize = MAGIC_BYTES.len() + 4 + CHECKSUM_LENGTH;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofFileError {
+    NotAProofFile,
+    UnsupportedVersion(u32),
+    ChecksumMismatch,
+    ClaimMismatch(&'static str),
+}
+
+impl Error for ProofFileError {}
+
+impl fmt::Display for ProofFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProofFileError::NotAProofFile => write!(f, "Not a proof file."),
+            ProofFileError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported proof file version {version}; expected {FORMAT_VERSION}."
+            ),
+            ProofFileError::ChecksumMismatch => {
+                write!(f, "Proof file is corrupted: checksum mismatch.")
+            }
+            ProofFileError::ClaimMismatch(field) => write!(
+                f,
+                "Proof file is for a different claim: field `{field}` differs."
+            ),
+        }
+    }
+}
+
+/// A claim, the parameters used to prove it, and the proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofFile {
+    pub claim: Claim,
+    pub parameters: StarkParameters,
+    pub proof: Proof,
+}
+
+impl ProofFile {
+    pub fn new(claim: Claim, parameters: StarkParameters, proof: Proof) -> Self {
+        Self {
+            claim,
+            parameters,
+            proof,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let payload = bincode::serialize(self)?;
+        let checksum = blake3::hash(&payload);
+        Ok([
+            MAGIC_BYTES.as_slice(),
+            &FORMAT_VERSION.to_le_bytes(),
+            checksum.as_bytes(),
+            &payload,
+        ]
+        .concat())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LENGTH || !bytes.starts_with(MAGIC_BYTES) {
+            return Err(anyhow!(ProofFileError::NotAProofFile));
+        }
+        let (version, rest) = bytes[MAGIC_BYTES.len()..].split_at(4);
+        let version = u32::from_le_bytes(version.try_into()?);
+        if version != FORMAT_VERSION {
+            return Err(anyhow!(ProofFileError::UnsupportedVersion(version)));
+        }
+        let (checksum, payload) = rest.split_at(CHECKSUM_LENGTH);
+        if blake3::hash(payload).as_bytes() != checksum {
+            return Err(anyhow!(ProofFileError::ChecksumMismatch));
+        }
+        Ok(bincode::deserialize(payload)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        File::create(path)?.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Check that the bundled claim is the expected one, naming the first differing field if
+    /// it is not.
+    pub fn ensure_claim(&self, expected: &Claim) -> Result<()> {
+        let claim = &self.claim;
+        let mismatch = if claim.program != expected.program {
+            Some("program")
+        } else if claim.entry_point != expected.entry_point {
+            Some("entry_point")
+        } else if claim.input != expected.input {
+            Some("input")
+        } else if claim.output != expected.output {
+            Some("output")
+        } else if claim.output_tags != expected.output_tags {
+            Some("output_tags")
+        } else if claim.padded_height != expected.padded_height {
+            Some("padded_height")
+        } else {
+            None
+        };
+        match mismatch {
+            Some(field) => Err(anyhow!(ProofFileError::ClaimMismatch(field))),
+            None => Ok(()),
+        }
+    }
+
+    /// Verify the bundled proof against the bundled claim, using the bundled parameters.
+    pub fn verify(self, maybe_profiler: &mut Option<TritonProfiler>) -> Result<bool> {
+        Stark::new(self.claim, self.parameters).verify(self.proof, maybe_profiler)
+    }
+}
+
+#[cfg(test)]
+mod proof_file_tests {
+    use rand::random;
+    use triton_opcodes::program::Program;
+    use twenty_first::shared_math::b_field_element::BFieldElement;
+
+    use crate::table::master_table::MasterBaseTable;
+    use crate::vm::simulate;
+
+    use super::*;
+
+    fn proof_file() -> ProofFile {
+        let program = Program::from_code("read_io push 2 mul write_io halt").unwrap();
+        let input = vec![BFieldElement::new(21)];
+        let (aet, output) = simulate(&program, input.clone(), vec![]).unwrap();
+        let claim = Claim {
+            input,
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let parameters = StarkParameters::new(32, 4);
+        let stark = Stark::new(claim.clone(), parameters);
+        let proof = stark.prove(aet, &mut None);
+        ProofFile::new(claim, stark.parameters, proof)
+    }
+
+    fn error_of<T: fmt::Debug>(result: Result<T>) -> ProofFileError {
+        result.unwrap_err().downcast::<ProofFileError>().unwrap()
+    }
+
+    #[test]
+    fn save_load_verify_roundtrip_test() {
+        let proof_file = proof_file();
+        let path = std::env::temp_dir().join(format!("proof_file_{}.tpf", random::<u64>()));
+        proof_file.save(&path).unwrap();
+        let loaded = ProofFile::load(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        loaded.ensure_claim(&proof_file.claim).unwrap();
+        assert!(loaded.verify(&mut None).unwrap());
+    }
+
+    #[test]
+    fn corrupted_files_are_rejected_test() {
+        let bytes = proof_file().to_bytes().unwrap();
+
+        let mut flipped_payload = bytes.clone();
+        *flipped_payload.last_mut().unwrap() ^= 1;
+        let checksum_error = error_of(ProofFile::from_bytes(&flipped_payload));
+        assert_eq!(ProofFileError::ChecksumMismatch, checksum_error);
+
+        let truncated = &bytes[..bytes.len() - 1];
+        let truncation_error = error_of(ProofFile::from_bytes(truncated));
+        assert_eq!(ProofFileError::ChecksumMismatch, truncation_error);
+
+        let mut future_version = bytes.clone();
+        future_version[MAGIC_BYTES.len()] += 1;
+        let version_error = error_of(ProofFile::from_bytes(&future_version));
+        assert_eq!(
+            ProofFileError::UnsupportedVersion(FORMAT_VERSION + 1),
+            version_error
+        );
+
+        let not_a_proof_file = error_of(ProofFile::from_bytes(b"halt"));
+        assert_eq!(ProofFileError::NotAProofFile, not_a_proof_file);
+    }
+
+    #[test]
+    fn proof_for_other_claim_is_reported_test() {
+        let proof_file = proof_file();
+        let mut other_claim = proof_file.claim.clone();
+        other_claim.output = vec![BFieldElement::new(43)];
+        let mismatch = error_of(proof_file.ensure_claim(&other_claim));
+        assert_eq!(ProofFileError::ClaimMismatch("output"), mismatch);
+    }
+}
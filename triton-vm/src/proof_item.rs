@@ -112,6 +112,8 @@ pub enum ProofItem {
     FriCodeword(Vec<XFieldElement>),
     FriResponse(FriResponse),
     PaddedHeight(BFieldElement),
+    ParametersDigest(Digest),
+    EmptyTables(Vec<BFieldElement>),
     Uncast(Vec<BFieldElement>),
 }
 
@@ -275,6 +277,36 @@ where
             ))),
         }
     }
+
+    pub fn as_parameters_digest(&self) -> Result<Digest> {
+        match self {
+            Self::ParametersDigest(digest) => Ok(*digest),
+            Self::Uncast(str) => match Digest::decode(str) {
+                Ok(digest) => Ok(*digest),
+                Err(_) => Err(anyhow::Error::new(ProofStreamError::new(
+                    "cast to parameters digest failed",
+                ))),
+            },
+            _ => Err(anyhow::Error::new(ProofStreamError::new(
+                "expected parameters digest, but got something else",
+            ))),
+        }
+    }
+
+    pub fn as_empty_tables(&self) -> Result<Vec<BFieldElement>> {
+        match self {
+            Self::EmptyTables(table_indices) => Ok(table_indices.to_owned()),
+            Self::Uncast(str) => match Vec::<BFieldElement>::decode(str) {
+                Ok(table_indices) => Ok(*table_indices),
+                Err(_) => Err(anyhow::Error::new(ProofStreamError::new(
+                    "cast to empty tables failed",
+                ))),
+            },
+            _ => Err(anyhow::Error::new(ProofStreamError::new(
+                "expected empty tables, but got something else",
+            ))),
+        }
+    }
 }
 
 impl BFieldCodec for ProofItem {
@@ -282,7 +314,7 @@ impl BFieldCodec for ProofItem {
     /// The first element denotes the length of the encoding; make
     /// sure it is correct!
     fn decode(str: &[BFieldElement]) -> Result<Box<Self>> {
-        if let Some(len) = str.get(0) {
+        if let Some(len) = str.first() {
             if len.value() as usize + 1 != str.len() {
                 Err(anyhow::Error::new(ProofStreamError::new("length mismatch")))
             } else {
@@ -306,6 +338,8 @@ impl BFieldCodec for ProofItem {
             ProofItem::FriCodeword(something) => something.encode(),
             ProofItem::FriResponse(something) => something.encode(),
             ProofItem::PaddedHeight(something) => something.encode(),
+            ProofItem::ParametersDigest(something) => something.encode(),
+            ProofItem::EmptyTables(something) => something.encode(),
             ProofItem::Uncast(something) => something.encode(),
         };
         let head = BFieldElement::new(tail.len().try_into().unwrap());
@@ -346,12 +380,10 @@ mod proof_item_typed_tests {
     fn random_fri_response() -> FriResponse {
         FriResponse(
             (0..18)
-                .into_iter()
                 .map(|r| {
                     (
                         PartialAuthenticationPath(
                             (0..(20 - r))
-                                .into_iter()
                                 .map(|_| {
                                     if random_bool() {
                                         Some(random_digest())
@@ -380,13 +412,11 @@ mod proof_item_typed_tests {
     fn test_serialize_stark_proof_with_fiat_shamir() {
         type H = RescuePrimeRegular;
         let mut proof_stream = ProofStream::<ProofItem, H>::new();
-        let map = (0..7).into_iter().map(|_| random_digest()).collect_vec();
+        let map = (0..7).map(|_| random_digest()).collect_vec();
         let auth_struct = (0..8)
-            .into_iter()
             .map(|_| {
                 PartialAuthenticationPath(
                     (0..11)
-                        .into_iter()
                         .map(|_| {
                             if random_bool() {
                                 Some(random_digest())
@@ -0,0 +1,616 @@
+//! Byte-level layout descriptions of proofs and proof files.
+//!
+//! The layouts are derived by walking actual proofs and measuring every part with the very codecs
+//! used for serialization. Tests check that the described fields tile the serialized data exactly,
+//! so any change to the serialization code that is not reflected here breaks the build instead of
+//! silently outdating the documentation external verifier implementers work from.
+
+use std::fmt::Display;
+use std::fmt::Write;
+
+use anyhow::Result;
+use itertools::Itertools;
+use num_traits::One;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
+
+use crate::bfield_codec::BFieldCodec;
+use crate::proof::Claim;
+use crate::proof_file::ProofFile;
+use crate::proof_file::CHECKSUM_LENGTH;
+use crate::proof_file::FORMAT_VERSION;
+use crate::proof_file::MAGIC_BYTES;
+use crate::proof_item::ProofItem;
+use crate::proof_stream::ProofStream;
+use crate::stark::Stark;
+use crate::stark::StarkParameters;
+use crate::table::master_table::MasterBaseTable;
+use crate::vm::simulate;
+
+/// A contiguous part of serialized data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// Repeated parts carry their index, like `rows[3].length`.
+    pub name: String,
+
+    /// In BFieldElements for proofs, in bytes for proof files.
+    pub offset: usize,
+
+    /// In the same unit as the offset.
+    pub length: usize,
+
+    pub encoding: &'static str,
+}
+
+impl Field {
+    /// The name with all indices replaced by `i`, identifying the field across repetitions.
+    pub fn schema_name(&self) -> String {
+        let mut schema_name = String::new();
+        let mut in_index = false;
+        for c in self.name.chars() {
+            match c {
+                '[' => {
+                    in_index = true;
+                    schema_name.push_str("[i");
+                }
+                ']' => {
+                    in_index = false;
+                    schema_name.push(']');
+                }
+                _ if in_index => (),
+                _ => schema_name.push(c),
+            }
+        }
+        schema_name
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FieldsBuilder {
+    offset: usize,
+    fields: Vec<Field>,
+}
+
+impl FieldsBuilder {
+    fn starting_at(offset: usize) -> Self {
+        Self {
+            offset,
+            fields: vec![],
+        }
+    }
+
+    fn push(&mut self, name: impl Into<String>, length: usize, encoding: &'static str) {
+        self.fields.push(Field {
+            name: name.into(),
+            offset: self.offset,
+            length,
+            encoding,
+        });
+        self.offset += length;
+    }
+}
+
+const LENGTH_PREFIX: &str = "number of BFieldElements following";
+const BFIELD_ELEMENTS: &str = "BFieldElements";
+const XFIELD_ELEMENTS: &str = "XFieldElements, 3 coefficients each, constant term first";
+const DIGESTS: &str = "Digests, 5 BFieldElements each";
+const PATH_HEIGHT: &str = "number of nodes on the path, revealed or not";
+const PATH_MASK: &str =
+    "revealed nodes as bit mask, the first node in the highest of `height` bits";
+
+/// The layout of one item of a proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemLayout {
+    pub kind: &'static str,
+
+    /// The offset of the item's length prefix within the proof, in BFieldElements.
+    pub offset: usize,
+
+    /// The length of the item including its length prefix, in BFieldElements.
+    pub length: usize,
+
+    /// All fields of the item, starting with the length prefix. Offsets are relative to the start
+    /// of the proof.
+    pub fields: Vec<Field>,
+}
+
+impl ItemLayout {
+    fn new(item: &ProofItem, offset: usize) -> Self {
+        let mut builder = FieldsBuilder::starting_at(offset);
+        builder.push("length", 1, LENGTH_PREFIX);
+        let kind = match item {
+            ProofItem::CompressedAuthenticationPaths(paths) => {
+                for (i, path) in paths.iter().enumerate() {
+                    let digests_length = path.0.iter().flatten().map(|d| d.encode().len()).sum();
+                    builder.push(format!("paths[{i}].length"), 1, LENGTH_PREFIX);
+                    builder.push(format!("paths[{i}].height"), 1, PATH_HEIGHT);
+                    builder.push(format!("paths[{i}].mask"), 1, PATH_MASK);
+                    builder.push(format!("paths[{i}].digests"), digests_length, DIGESTS);
+                }
+                "CompressedAuthenticationPaths"
+            }
+            ProofItem::MasterBaseTableRows(rows) => {
+                for (i, row) in rows.iter().enumerate() {
+                    builder.push(format!("rows[{i}].length"), 1, LENGTH_PREFIX);
+                    builder.push(
+                        format!("rows[{i}].elements"),
+                        row.encode().len(),
+                        BFIELD_ELEMENTS,
+                    );
+                }
+                "MasterBaseTableRows"
+            }
+            ProofItem::MasterExtTableRows(rows) => {
+                for (i, row) in rows.iter().enumerate() {
+                    builder.push(format!("rows[{i}].length"), 1, LENGTH_PREFIX);
+                    builder.push(
+                        format!("rows[{i}].elements"),
+                        row.encode().len(),
+                        XFIELD_ELEMENTS,
+                    );
+                }
+                "MasterExtTableRows"
+            }
+            ProofItem::MerkleRoot(root) => {
+                builder.push("root", root.encode().len(), DIGESTS);
+                "MerkleRoot"
+            }
+            ProofItem::AuthenticationPath(digests) => {
+                builder.push("digests", digests.encode().len(), DIGESTS);
+                "AuthenticationPath"
+            }
+            ProofItem::RevealedCombinationElements(elements) => {
+                builder.push("elements", elements.encode().len(), XFIELD_ELEMENTS);
+                "RevealedCombinationElements"
+            }
+            ProofItem::FriCodeword(codeword) => {
+                builder.push("codeword", codeword.encode().len(), XFIELD_ELEMENTS);
+                "FriCodeword"
+            }
+            ProofItem::FriResponse(response) => {
+                for (i, (path, value)) in response.0.iter().enumerate() {
+                    let digests_length = path.0.iter().flatten().map(|d| d.encode().len()).sum();
+                    builder.push(format!("responses[{i}].height"), 1, PATH_HEIGHT);
+                    builder.push(format!("responses[{i}].mask"), 1, PATH_MASK);
+                    builder.push(format!("responses[{i}].digests"), digests_length, DIGESTS);
+                    builder.push(
+                        format!("responses[{i}].value"),
+                        value.encode().len(),
+                        XFIELD_ELEMENTS,
+                    );
+                }
+                "FriResponse"
+            }
+            ProofItem::PaddedHeight(padded_height) => {
+                builder.push(
+                    "padded_height",
+                    padded_height.encode().len(),
+                    BFIELD_ELEMENTS,
+                );
+                "PaddedHeight"
+            }
+            ProofItem::ParametersDigest(digest) => {
+                builder.push("digest", digest.encode().len(), DIGESTS);
+                "ParametersDigest"
+            }
+            ProofItem::EmptyTables(table_indices) => {
+                builder.push("table_indices", table_indices.len(), BFIELD_ELEMENTS);
+                "EmptyTables"
+            }
+            ProofItem::Uncast(elements) => {
+                builder.push("elements", elements.len(), BFIELD_ELEMENTS);
+                "Uncast"
+            }
+        };
+
+        Self {
+            kind,
+            offset,
+            length: builder.offset - offset,
+            fields: builder.fields,
+        }
+    }
+
+    /// The item's fields, each repeated field listed once, with its encoding.
+    pub fn schema(&self) -> Vec<(String, &'static str)> {
+        self.fields
+            .iter()
+            .map(|field| (field.schema_name(), field.encoding))
+            .unique()
+            .collect()
+    }
+}
+
+/// The layout of a proof, _i.e._, of a sequence of BFieldElements, item by item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofLayout {
+    pub items: Vec<ItemLayout>,
+}
+
+impl ProofLayout {
+    /// Walk the typed items of the given proof stream in the order they appear in the proof.
+    pub fn new<H: AlgebraicHasher>(proof_stream: &ProofStream<ProofItem, H>) -> Self {
+        let mut offset = 0;
+        let mut items = vec![];
+        for item in proof_stream.items.iter() {
+            let item_layout = ItemLayout::new(item, offset);
+            offset += item_layout.length;
+            items.push(item_layout);
+        }
+        Self { items }
+    }
+
+    /// The length of the proof, in BFieldElements.
+    pub fn length(&self) -> usize {
+        self.items.iter().map(|item| item.length).sum()
+    }
+}
+
+impl Display for ProofLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "| Offset | Length | Item                          |")?;
+        writeln!(f, "|-------:|-------:|:------------------------------|")?;
+        for item in self.items.iter() {
+            writeln!(
+                f,
+                "| {:>6} | {:>6} | {:<29} |",
+                item.offset, item.length, item.kind
+            )?;
+        }
+        writeln!(f)?;
+
+        let distinct_kinds = self.items.iter().unique_by(|item| item.kind);
+        for item in distinct_kinds {
+            writeln!(f, "{}:", item.kind)?;
+            for (name, encoding) in item.schema() {
+                writeln!(f, "  - `{name}`: {encoding}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The layout of a [`ProofFile`], in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofFileLayout {
+    pub fields: Vec<Field>,
+
+    /// The layout of the bundled proof. Its first BFieldElement starts at byte `proof_offset`.
+    pub proof: ProofLayout,
+    pub proof_offset: usize,
+}
+
+impl ProofFileLayout {
+    /// The given proof stream must be the typed version of the proof bundled in the proof file.
+    pub fn new<H: AlgebraicHasher>(
+        proof_file: &ProofFile,
+        proof_stream: &ProofStream<ProofItem, H>,
+    ) -> Result<Self> {
+        let mut builder = FieldsBuilder::default();
+        builder.push("magic", MAGIC_BYTES.len(), "ASCII");
+        builder.push("version", 4, "u32, little-endian");
+        builder.push("checksum", CHECKSUM_LENGTH, "BLAKE3 hash of the payload");
+
+        let Claim {
+            input,
+            program,
+            output,
+            output_tags,
+            padded_height,
+            entry_point,
+        } = &proof_file.claim;
+        push_elements(&mut builder, "claim.input", input)?;
+        push_elements(&mut builder, "claim.program", program)?;
+        push_elements(&mut builder, "claim.output", output)?;
+        push_elements(&mut builder, "claim.output_tags", output_tags)?;
+        push_integer(&mut builder, "claim.padded_height", padded_height)?;
+        push_integer(&mut builder, "claim.entry_point", entry_point)?;
+
+        let StarkParameters {
+            security_level,
+            fri_expansion_factor,
+            num_trace_randomizers,
+            num_randomizer_polynomials,
+            num_colinearity_checks,
+            num_non_linear_codeword_checks,
+            ..
+        } = &proof_file.parameters;
+        push_integer(&mut builder, "parameters.security_level", security_level)?;
+        push_integer(
+            &mut builder,
+            "parameters.fri_expansion_factor",
+            fri_expansion_factor,
+        )?;
+        push_integer(
+            &mut builder,
+            "parameters.num_trace_randomizers",
+            num_trace_randomizers,
+        )?;
+        push_integer(
+            &mut builder,
+            "parameters.num_randomizer_polynomials",
+            num_randomizer_polynomials,
+        )?;
+        push_integer(
+            &mut builder,
+            "parameters.num_colinearity_checks",
+            num_colinearity_checks,
+        )?;
+        push_integer(
+            &mut builder,
+            "parameters.num_non_linear_codeword_checks",
+            num_non_linear_codeword_checks,
+        )?;
+        builder.push(
+            "parameters.fri_domain_offset",
+            bincode::serialized_size(&proof_file.parameters.fri_domain_offset())? as usize,
+            "BFieldElement, see element encoding",
+        );
+
+        push_elements(&mut builder, "proof", &proof_file.proof.0)?;
+        let proof_offset = builder.fields.last().unwrap().offset;
+
+        Ok(Self {
+            fields: builder.fields,
+            proof: ProofLayout::new(proof_stream),
+            proof_offset,
+        })
+    }
+
+    /// The length of the proof file, in bytes.
+    pub fn length(&self) -> usize {
+        self.fields.iter().map(|field| field.length).sum()
+    }
+}
+
+/// A bincode-serialized sequence of BFieldElements: a length prefix, then the elements.
+fn push_elements(
+    builder: &mut FieldsBuilder,
+    name: &str,
+    elements: &[BFieldElement],
+) -> Result<()> {
+    let prefix_length = bincode::serialized_size(&Vec::<BFieldElement>::new())? as usize;
+    let total_length = bincode::serialized_size(elements)? as usize;
+    builder.push(
+        format!("{name}.length"),
+        prefix_length,
+        "u64, little-endian; number of BFieldElements",
+    );
+    builder.push(
+        format!("{name}.elements"),
+        total_length - prefix_length,
+        "BFieldElements, see element encoding",
+    );
+    Ok(())
+}
+
+fn push_integer(builder: &mut FieldsBuilder, name: &str, integer: &usize) -> Result<()> {
+    let length = bincode::serialized_size(integer)? as usize;
+    builder.push(name, length, "u64, little-endian");
+    Ok(())
+}
+
+impl Display for ProofFileLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let one = bincode::serialize(&BFieldElement::one()).map_err(|_| std::fmt::Error)?;
+        let one = one.iter().map(|byte| format!("{byte:02x}")).join(" ");
+        writeln!(
+            f,
+            "Every BFieldElement takes 8 bytes: its Montgomery representation x·2⁶⁴ mod p as a \
+            little-endian u64. For example, 1 is encoded as `{one}`."
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "| Offset | Length | Field                                     | Encoding"
+        )?;
+        writeln!(
+            f,
+            "|-------:|-------:|:------------------------------------------|:--------"
+        )?;
+        for field in self.fields.iter() {
+            writeln!(
+                f,
+                "| {:>6} | {:>6} | {:<41} | {}",
+                field.offset, field.length, field.name, field.encoding
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe the proof file format of the current version, byte by byte, by proving a small example
+/// program and walking the result.
+pub fn proof_format_documentation() -> Result<String> {
+    let program = Program::from_code("read_io push 2 mul write_io halt")?;
+    let input = vec![BFieldElement::new(21)];
+    let (aet, output) = simulate(&program, input.clone(), vec![])?;
+    let claim = Claim {
+        input,
+        program: program.to_bwords(),
+        output: output.into(),
+        output_tags: vec![],
+        padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+        entry_point: program.entry_point,
+    };
+    let stark = Stark::new(claim.clone(), StarkParameters::new(32, 4));
+    let proof_stream = stark.prove_to_proof_stream(aet, &mut None);
+    let proof_file = ProofFile::new(claim, stark.parameters, proof_stream.to_proof());
+    let layout = ProofFileLayout::new(&proof_file, &proof_stream)?;
+
+    let mut documentation = String::new();
+    writeln!(
+        documentation,
+        "# Proof File Format, Version {FORMAT_VERSION}"
+    )?;
+    writeln!(documentation)?;
+    writeln!(documentation, "{layout}")?;
+    writeln!(documentation, "## Proof")?;
+    writeln!(documentation)?;
+    writeln!(
+        documentation,
+        "The proof is a sequence of items, each prefixed by its length. Offsets and lengths are in \
+        BFieldElements, counted from byte {} of the example file.",
+        layout.proof_offset
+    )?;
+    writeln!(documentation)?;
+    write!(documentation, "{}", layout.proof)?;
+    Ok(documentation)
+}
+
+#[cfg(test)]
+mod proof_layout_tests {
+    use rand::random;
+    use twenty_first::shared_math::other::random_elements;
+    use twenty_first::shared_math::rescue_prime_digest::Digest;
+    use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+    use twenty_first::shared_math::x_field_element::XFieldElement;
+    use twenty_first::util_types::merkle_tree::PartialAuthenticationPath;
+
+    use crate::proof_item::FriResponse;
+
+    use super::*;
+
+    fn random_partial_path(height: usize) -> PartialAuthenticationPath<Digest> {
+        let path = (0..height)
+            .map(|_| random::<bool>().then(|| random_elements(1)[0]))
+            .collect();
+        PartialAuthenticationPath(path)
+    }
+
+    fn proof_stream_with_all_item_kinds() -> ProofStream<ProofItem, RescuePrimeRegular> {
+        let fri_response = (0..4)
+            .map(|i| (random_partial_path(10 - i), random_elements(1)[0]))
+            .collect();
+        let mut proof_stream = ProofStream::new();
+        for item in [
+            ProofItem::PaddedHeight(BFieldElement::new(256)),
+            ProofItem::ParametersDigest(random_elements(1)[0]),
+            ProofItem::EmptyTables(vec![BFieldElement::new(6)]),
+            ProofItem::MerkleRoot(random_elements(1)[0]),
+            ProofItem::AuthenticationPath(random_elements(7)),
+            ProofItem::MasterBaseTableRows(vec![random_elements(11); 3]),
+            ProofItem::MasterExtTableRows(vec![random_elements(5); 3]),
+            ProofItem::CompressedAuthenticationPaths(
+                (0..5).map(|_| random_partial_path(8)).collect(),
+            ),
+            ProofItem::RevealedCombinationElements(random_elements(6)),
+            ProofItem::FriCodeword(random_elements::<XFieldElement>(16)),
+            ProofItem::FriResponse(FriResponse(fri_response)),
+            ProofItem::Uncast(random_elements(9)),
+        ] {
+            proof_stream.enqueue(&item);
+        }
+        proof_stream
+    }
+
+    fn assert_fields_are_contiguous(fields: &[Field], start: usize) {
+        let mut offset = start;
+        for field in fields {
+            assert_eq!(offset, field.offset, "field `{}` is misplaced", field.name);
+            offset += field.length;
+        }
+    }
+
+    #[test]
+    fn item_layouts_tile_the_encoding_test() {
+        let proof_stream = proof_stream_with_all_item_kinds();
+        let layout = ProofLayout::new(&proof_stream);
+
+        let mut offset = 0;
+        for (item, item_layout) in proof_stream.items.iter().zip_eq(layout.items.iter()) {
+            assert_eq!(offset, item_layout.offset);
+            assert_eq!(
+                item.encode().len(),
+                item_layout.length,
+                "{}",
+                item_layout.kind
+            );
+            assert_fields_are_contiguous(&item_layout.fields, item_layout.offset);
+            let fields_length: usize = item_layout.fields.iter().map(|f| f.length).sum();
+            assert_eq!(item_layout.length, fields_length, "{}", item_layout.kind);
+            offset += item_layout.length;
+        }
+        assert_eq!(proof_stream.to_proof().0.len(), layout.length());
+    }
+
+    #[test]
+    fn length_prefixes_are_where_the_layout_says_test() {
+        let proof_stream = proof_stream_with_all_item_kinds();
+        let proof = proof_stream.to_proof();
+        let layout = ProofLayout::new(&proof_stream);
+        for item_layout in layout.items.iter() {
+            let length_prefix = proof.0[item_layout.offset].value() as usize;
+            assert_eq!(item_layout.length - 1, length_prefix);
+        }
+
+        let fri_response = layout.items.iter().find(|item| item.kind == "FriResponse");
+        for field in fri_response.unwrap().fields.iter() {
+            if field.name.ends_with(".height") {
+                assert!(proof.0[field.offset].value() <= 10);
+            }
+        }
+    }
+
+    #[test]
+    fn schema_lists_repeated_fields_once_test() {
+        let proof_stream = proof_stream_with_all_item_kinds();
+        let layout = ProofLayout::new(&proof_stream);
+        let base_rows = &layout.items[5];
+        assert_eq!("MasterBaseTableRows", base_rows.kind);
+        let schema_names = base_rows.schema().into_iter().map(|(name, _)| name);
+        let expected = ["length", "rows[i].length", "rows[i].elements"];
+        assert_eq!(expected.to_vec(), schema_names.collect_vec());
+    }
+
+    #[test]
+    fn bfield_elements_are_serialized_in_montgomery_representation_test() {
+        let modulus = BFieldElement::QUOTIENT as u128;
+        for value in [0, 1, 2, 42, BFieldElement::MAX] {
+            let montgomery = (((value as u128) << 64) % modulus) as u64;
+            let serialized = bincode::serialize(&BFieldElement::new(value)).unwrap();
+            assert_eq!(montgomery.to_le_bytes().to_vec(), serialized);
+        }
+    }
+
+    #[test]
+    fn proof_file_layout_tiles_the_file_test() {
+        let proof_stream = proof_stream_with_all_item_kinds();
+        let claim = Claim {
+            input: random_elements(3),
+            program: random_elements(10),
+            output: random_elements(2),
+            output_tags: vec![],
+            padded_height: 256,
+            entry_point: 4,
+        };
+        let proof = proof_stream.to_proof();
+        let proof_file = ProofFile::new(claim, StarkParameters::default(), proof.clone());
+        let bytes = proof_file.to_bytes().unwrap();
+        let layout = ProofFileLayout::new(&proof_file, &proof_stream).unwrap();
+
+        assert_fields_are_contiguous(&layout.fields, 0);
+        assert_eq!(bytes.len(), layout.length());
+        assert_eq!(MAGIC_BYTES.as_slice(), &bytes[..layout.fields[0].length]);
+
+        let proof_start = layout.proof_offset;
+        let proof_end = proof_start + proof.0.len() * 8;
+        let proof_bytes = proof.0.iter().map(|e| bincode::serialize(e).unwrap());
+        assert_eq!(proof_bytes.concat(), bytes[proof_start..proof_end]);
+    }
+
+    #[test]
+    fn print_proof_format_documentation() {
+        let documentation = proof_format_documentation().unwrap();
+        println!("{documentation}");
+        for kind in [
+            "PaddedHeight",
+            "MerkleRoot",
+            "FriResponse",
+            "MasterBaseTableRows",
+        ] {
+            assert!(documentation.contains(kind));
+        }
+    }
+}
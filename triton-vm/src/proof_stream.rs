@@ -1,8 +1,10 @@
 use std::error::Error;
+use std::fmt::Debug;
 use std::fmt::Display;
 use std::marker::PhantomData;
 
 use anyhow::Result;
+use twenty_first::shared_math::b_field_element::BFieldElement;
 use twenty_first::shared_math::rescue_prime_digest::Digest;
 use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
 
@@ -10,13 +12,61 @@ use crate::bfield_codec::BFieldCodec;
 use crate::proof::Proof;
 use crate::proof_item::MayBeUncast;
 
-#[derive(Debug, PartialEq, Eq)]
+/// A source of challenges replacing Fiat-Shamir, for interactive proving and verification. Every
+/// challenge is requested with the transcript so far, _i.e._, the encoding of all proof items
+/// sent up to that point. Prover and verifier each need a channel, and the verifier's channel
+/// must produce the same challenges as the prover's for the proof to verify.
+///
+/// Any `FnMut(&[BFieldElement]) -> Digest` is a challenge channel.
+pub trait ChallengeChannel {
+    fn challenge(&mut self, transcript: &[BFieldElement]) -> Digest;
+}
+
+impl<F: FnMut(&[BFieldElement]) -> Digest> ChallengeChannel for F {
+    fn challenge(&mut self, transcript: &[BFieldElement]) -> Digest {
+        self(transcript)
+    }
+}
+
 pub struct ProofStream<Item: Clone + BFieldCodec + MayBeUncast, H: AlgebraicHasher> {
     pub items: Vec<Item>,
     items_index: usize,
+    challenge_channel: Option<Box<dyn ChallengeChannel>>,
     _hasher: PhantomData<H>,
 }
 
+impl<Item, H> Debug for ProofStream<Item, H>
+where
+    Item: Clone + BFieldCodec + MayBeUncast + Debug,
+    H: AlgebraicHasher,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProofStream")
+            .field("items", &self.items)
+            .field("items_index", &self.items_index)
+            .field("is_interactive", &self.is_interactive())
+            .finish()
+    }
+}
+
+/// Proof streams are equal if their items are, regardless of where their challenges come from.
+impl<Item, H> PartialEq for ProofStream<Item, H>
+where
+    Item: Clone + BFieldCodec + MayBeUncast + PartialEq,
+    H: AlgebraicHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items && self.items_index == other.items_index
+    }
+}
+
+impl<Item, H> Eq for ProofStream<Item, H>
+where
+    Item: Clone + BFieldCodec + MayBeUncast + Eq,
+    H: AlgebraicHasher,
+{
+}
+
 #[derive(Debug, Clone)]
 pub struct ProofStreamError {
     pub message: String,
@@ -49,10 +99,21 @@ where
         ProofStream {
             items: vec![],
             items_index: 0,
+            challenge_channel: None,
             _hasher: PhantomData,
         }
     }
 
+    /// Take challenges from the given channel instead of deriving them through Fiat-Shamir.
+    pub fn with_challenge_channel(mut self, challenge_channel: Box<dyn ChallengeChannel>) -> Self {
+        self.challenge_channel = Some(challenge_channel);
+        self
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.challenge_channel.is_some()
+    }
+
     /// Reset the counter counting how many items were read. For testing purposes, so
     /// we don't have to re-run tests needlessly.
     pub fn reset_for_verifier(&mut self) {
@@ -67,6 +128,11 @@ where
         self.items.len()
     }
 
+    /// The number of items not yet dequeued.
+    pub fn num_unread_items(&self) -> usize {
+        self.items.len() - self.items_index
+    }
+
     pub fn transcript_length(&self) -> usize {
         self.to_proof().0.len()
     }
@@ -109,6 +175,7 @@ where
         Ok(ProofStream {
             items,
             items_index: 0,
+            challenge_channel: None,
             _hasher: PhantomData,
         })
     }
@@ -129,20 +196,31 @@ where
         Ok(item.clone())
     }
 
-    pub fn prover_fiat_shamir(&self) -> Digest {
+    /// The next challenge for the prover: the hash of the transcript, or, in interactive mode,
+    /// whatever the challenge channel provides.
+    pub fn prover_fiat_shamir(&mut self) -> Digest {
         let mut transcript = vec![];
         for item in self.items.iter() {
             transcript.append(&mut item.encode());
         }
-        H::hash_slice(&transcript)
+        self.challenge(&transcript)
     }
 
-    pub fn verifier_fiat_shamir(&self) -> Digest {
+    /// The next challenge for the verifier: the hash of the transcript, or, in interactive mode,
+    /// whatever the challenge channel provides.
+    pub fn verifier_fiat_shamir(&mut self) -> Digest {
         let mut transcript = vec![];
         for item in self.items[0..self.items_index].iter() {
             transcript.append(&mut item.uncast());
         }
-        H::hash_slice(&transcript)
+        self.challenge(&transcript)
+    }
+
+    fn challenge(&mut self, transcript: &[BFieldElement]) -> Digest {
+        match self.challenge_channel.as_mut() {
+            Some(challenge_channel) => challenge_channel.challenge(transcript),
+            None => H::hash_slice(transcript),
+        }
     }
 }
 
@@ -207,7 +285,7 @@ mod proof_stream_typed_tests {
 
     impl BFieldCodec for TestItem {
         fn decode(str: &[BFieldElement]) -> Result<Box<Self>> {
-            let maybe_element_zero = str.get(0);
+            let maybe_element_zero = str.first();
             match maybe_element_zero {
                 None => Err(ProofStreamError::new(
                     "trying to decode empty string into test item",
@@ -0,0 +1,163 @@
+//! A machine-readable record of one proof's resource usage, for proving farms aggregating
+//! capacity data across many proofs.
+
+use std::mem::size_of;
+use std::time::Instant;
+
+use serde::Serialize;
+use strum::IntoEnumIterator;
+use triton_profiler::triton_profiler::TritonProfiler;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+
+use crate::analysis::table_heights::PaddingReport;
+use crate::proof::Proof;
+use crate::stark::Stark;
+use crate::table::master_table::randomized_padded_trace_len;
+use crate::table::master_table::TableId;
+use crate::table::master_table::NUM_BASE_COLUMNS;
+use crate::table::master_table::NUM_EXT_COLUMNS;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// The height of one table of one proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableTelemetry {
+    pub table: String,
+    pub unpadded_height: usize,
+    pub padding_rows: usize,
+}
+
+/// The wall-clock time of one of the prover's top-level phases, as reported to the
+/// [`TritonProfiler`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PhaseTelemetry {
+    pub phase: String,
+    pub micros: u64,
+}
+
+/// Telemetry of one proof. Serializes to JSON using [`to_json`](Self::to_json).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProverTelemetry {
+    pub tables: Vec<TableTelemetry>,
+    pub padded_height: usize,
+    pub fri_domain_length: usize,
+
+    /// The prover's phases, in the order the prover runs them.
+    pub phases: Vec<PhaseTelemetry>,
+
+    /// The time of the entire proof, including time not attributed to any phase.
+    pub total_micros: u64,
+
+    /// The size of the master tables, both on the trace domain and on the FRI domain. These
+    /// tables are alive at the same time and dominate the prover's memory usage, making their size
+    /// an estimate of the prover's peak resident set size.
+    pub estimated_peak_memory_bytes: usize,
+
+    /// The number of threads available to the prover.
+    pub num_threads: usize,
+
+    /// The [digest](crate::stark::StarkParameters::digest) of the STARK parameters, identifying
+    /// proofs made with the same parameters.
+    pub parameter_digest: String,
+}
+
+impl ProverTelemetry {
+    /// Prove the given trace, recording the proof's telemetry.
+    pub fn measure(stark: &Stark, aet: AlgebraicExecutionTrace) -> (Proof, Self) {
+        let padding_report = PaddingReport::new(&aet, &stark.claim.program);
+
+        let mut maybe_profiler = Some(TritonProfiler::new("Prover Telemetry"));
+        let start = Instant::now();
+        let proof = stark.prove(aet, &mut maybe_profiler);
+        let total = start.elapsed();
+        let mut profiler = maybe_profiler.unwrap();
+        profiler.finish();
+
+        let tables = TableId::iter()
+            .map(|table| TableTelemetry {
+                table: table.to_string(),
+                unpadded_height: padding_report.heights.unpadded_heights[table as usize],
+                padding_rows: padding_report.padding_rows[table as usize],
+            })
+            .collect();
+        let phases = profiler
+            .top_level_times()
+            .into_iter()
+            .map(|(phase, time)| PhaseTelemetry {
+                phase,
+                micros: time.as_micros() as u64,
+            })
+            .collect();
+
+        let padded_height = padding_report.heights.padded_height;
+        let fri_domain_length = stark.fri.domain.length;
+        let trace_length =
+            randomized_padded_trace_len(stark.parameters.num_trace_randomizers, padded_height);
+        let row_size = NUM_BASE_COLUMNS * size_of::<BFieldElement>()
+            + NUM_EXT_COLUMNS * size_of::<XFieldElement>();
+        let estimated_peak_memory_bytes = (trace_length + fri_domain_length) * row_size;
+
+        let telemetry = Self {
+            tables,
+            padded_height,
+            fri_domain_length,
+            phases,
+            total_micros: total.as_micros() as u64,
+            estimated_peak_memory_bytes,
+            num_threads: rayon::current_num_threads(),
+            parameter_digest: stark.parameters.digest().to_string(),
+        };
+        (proof, telemetry)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Telemetry must serialize to JSON.")
+    }
+}
+
+#[cfg(test)]
+mod prover_telemetry_tests {
+    use crate::stark::triton_stark_tests::parse_setup_simulate;
+    use crate::stark::triton_stark_tests::parse_simulate_pad;
+
+    #[test]
+    fn telemetry_is_recorded_and_exported_test() {
+        let source_code = "push 2 push 3 mul hash write_io halt";
+        let (stark, _, _) = parse_simulate_pad(source_code, vec![], vec![]);
+        let (aet, _, _) = parse_setup_simulate(source_code, vec![], vec![]);
+        let (proof, telemetry) = stark.prove_with_telemetry(aet);
+        assert!(stark.verify(proof, &mut None).unwrap());
+
+        assert_eq!(stark.claim.padded_height, telemetry.padded_height);
+        for table in telemetry.tables.iter() {
+            assert_eq!(
+                telemetry.padded_height,
+                table.unpadded_height + table.padding_rows,
+                "{}",
+                table.table
+            );
+        }
+        let phase_names = telemetry
+            .phases
+            .iter()
+            .map(|phase| phase.phase.as_str())
+            .collect::<Vec<_>>();
+        assert!(phase_names.contains(&"base tables"));
+        assert!(phase_names.contains(&"FRI"));
+        let sum_of_phases: u64 = telemetry.phases.iter().map(|phase| phase.micros).sum();
+        assert!(sum_of_phases <= telemetry.total_micros);
+        assert!(telemetry.estimated_peak_memory_bytes > 0);
+        assert!(telemetry.num_threads > 0);
+        assert_eq!(
+            stark.parameters.digest().to_string(),
+            telemetry.parameter_digest
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&telemetry.to_json()).unwrap();
+        assert_eq!(
+            telemetry.padded_height,
+            json["padded_height"].as_u64().unwrap() as usize
+        );
+        assert_eq!("ProcessorTable", json["tables"][2]["table"]);
+    }
+}
@@ -0,0 +1,270 @@
+//! The public output of a program, _i.e._, the words written by instruction `write_io`, and its
+//! decoding into Rust types.
+//!
+//! Values are decoded in the order in which the program writes them:
+//! - A `u32` is one element smaller than 2^32.
+//! - A `u64` is two such elements, the least significant one first. This is the order produced by
+//!   `split write_io write_io`.
+//! - An [`XFieldElement`] is its three coefficients, the constant one first. This is the order
+//!   produced by `write_io write_io write_io`.
+//! - A [`Digest`] is its [`DIGEST_LENGTH`] elements in order.
+//! - Byte strings and strings use the encoding of [`byte_string`](crate::byte_string).
+
+use std::ops::Deref;
+
+use anyhow::bail;
+use anyhow::Result;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+use twenty_first::shared_math::x_field_element::EXTENSION_DEGREE;
+
+use crate::byte_string::decode_bytes;
+use crate::byte_string::decode_string;
+use crate::byte_string::encoding_length;
+
+/// The words a program wrote to its standard output.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PublicOutput {
+    words: Vec<BFieldElement>,
+}
+
+impl PublicOutput {
+    pub fn new(words: Vec<BFieldElement>) -> Self {
+        Self { words }
+    }
+
+    /// Decode the entire output as one value of type `T`. Fails if the output is not a valid
+    /// encoding of a `T` or if words remain after decoding.
+    pub fn decode<T: DecodeOutput>(&self) -> Result<T> {
+        let mut reader = self.reader();
+        let value = reader.read()?;
+        if !reader.is_exhausted() {
+            bail!(
+                "{} words of output remain after decoding.",
+                reader.remaining().len()
+            );
+        }
+        Ok(value)
+    }
+
+    /// A reader decoding the output value by value, for programs writing values of different
+    /// types.
+    pub fn reader(&self) -> OutputReader<'_> {
+        OutputReader {
+            words: &self.words,
+            position: 0,
+        }
+    }
+
+    pub fn into_words(self) -> Vec<BFieldElement> {
+        self.words
+    }
+}
+
+impl Deref for PublicOutput {
+    type Target = [BFieldElement];
+
+    fn deref(&self) -> &Self::Target {
+        &self.words
+    }
+}
+
+impl From<Vec<BFieldElement>> for PublicOutput {
+    fn from(words: Vec<BFieldElement>) -> Self {
+        Self::new(words)
+    }
+}
+
+impl From<PublicOutput> for Vec<BFieldElement> {
+    fn from(output: PublicOutput) -> Self {
+        output.into_words()
+    }
+}
+
+impl PartialEq<Vec<BFieldElement>> for PublicOutput {
+    fn eq(&self, other: &Vec<BFieldElement>) -> bool {
+        &self.words == other
+    }
+}
+
+impl PartialEq<PublicOutput> for Vec<BFieldElement> {
+    fn eq(&self, other: &PublicOutput) -> bool {
+        self == &other.words
+    }
+}
+
+/// Decodes the values of a [`PublicOutput`] one after the other.
+#[derive(Debug, Clone)]
+pub struct OutputReader<'output> {
+    words: &'output [BFieldElement],
+    position: usize,
+}
+
+impl<'output> OutputReader<'output> {
+    /// Decode the next value.
+    pub fn read<T: DecodeOutput>(&mut self) -> Result<T> {
+        let (value, num_words) = T::decode_prefix(self.remaining())?;
+        self.position += num_words;
+        Ok(value)
+    }
+
+    /// The words that have not been decoded yet.
+    pub fn remaining(&self) -> &'output [BFieldElement] {
+        &self.words[self.position..]
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_empty()
+    }
+}
+
+/// A type that can be decoded from the public output of a program.
+pub trait DecodeOutput: Sized {
+    /// Decode a value from the beginning of the given words. Returns the value and the number of
+    /// words it was decoded from.
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)>;
+}
+
+fn take<const N: usize>(words: &[BFieldElement]) -> Result<[BFieldElement; N]> {
+    if words.len() < N {
+        bail!(
+            "Expected {N} words of output, but only {} remain.",
+            words.len()
+        );
+    }
+    Ok(words[..N].try_into().unwrap())
+}
+
+fn u32_from_word(word: BFieldElement) -> Result<u32> {
+    match u32::try_from(word.value()) {
+        Ok(value) => Ok(value),
+        Err(_) => bail!("Output word {word} is not a u32."),
+    }
+}
+
+impl DecodeOutput for BFieldElement {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let [word] = take(words)?;
+        Ok((word, 1))
+    }
+}
+
+impl DecodeOutput for u32 {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let [word] = take(words)?;
+        Ok((u32_from_word(word)?, 1))
+    }
+}
+
+impl DecodeOutput for u64 {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let [lo, hi] = take(words)?;
+        let value = (u32_from_word(hi)? as u64) << 32 | u32_from_word(lo)? as u64;
+        Ok((value, 2))
+    }
+}
+
+impl DecodeOutput for XFieldElement {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let coefficients = take::<EXTENSION_DEGREE>(words)?;
+        Ok((XFieldElement::new(coefficients), EXTENSION_DEGREE))
+    }
+}
+
+impl DecodeOutput for Digest {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let values = take::<DIGEST_LENGTH>(words)?;
+        Ok((Digest::new(values), DIGEST_LENGTH))
+    }
+}
+
+/// The number of words encoding the byte string at the beginning of the given words.
+fn byte_string_length(words: &[BFieldElement]) -> Result<usize> {
+    let [num_bytes] = take(words)?;
+    let Ok(num_bytes) = usize::try_from(num_bytes.value()) else {
+        bail!("Length {num_bytes} of byte string is too large.");
+    };
+    let num_words = encoding_length(num_bytes);
+    if words.len() < num_words {
+        bail!(
+            "Encoding of {num_bytes} bytes needs {num_words} words of output, \
+            but only {} remain.",
+            words.len()
+        );
+    }
+    Ok(num_words)
+}
+
+impl DecodeOutput for Vec<u8> {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let num_words = byte_string_length(words)?;
+        Ok((decode_bytes(&words[..num_words])?, num_words))
+    }
+}
+
+impl DecodeOutput for String {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let num_words = byte_string_length(words)?;
+        Ok((decode_string(&words[..num_words])?, num_words))
+    }
+}
+
+impl<A: DecodeOutput, B: DecodeOutput> DecodeOutput for (A, B) {
+    fn decode_prefix(words: &[BFieldElement]) -> Result<(Self, usize)> {
+        let (a, num_words_a) = A::decode_prefix(words)?;
+        let (b, num_words_b) = B::decode_prefix(&words[num_words_a..])?;
+        Ok(((a, b), num_words_a + num_words_b))
+    }
+}
+
+#[cfg(test)]
+mod public_output_tests {
+    use triton_opcodes::program::Program;
+
+    use crate::byte_string::encode_str;
+    use crate::vm::run;
+    use crate::vm::simulate;
+
+    use super::*;
+
+    #[test]
+    fn decode_values_written_by_program_test() {
+        let source_code = "
+            push 3 push 2 push 1 write_io write_io write_io
+            push 0 push 1 push 2 push 3 push 4 hash
+            write_io write_io write_io write_io write_io
+            push 8589934594 split write_io write_io
+            read_io read_io write_io write_io
+            halt";
+        let program = Program::from_code(source_code).unwrap();
+        let mut stdin = encode_str("triton");
+        stdin.reverse();
+        let (_, output) = simulate(&program, stdin, vec![]).unwrap();
+
+        let mut reader = output.reader();
+        let xfe: XFieldElement = reader.read().unwrap();
+        let expected_xfe = XFieldElement::new([1, 2, 3].map(BFieldElement::new));
+        assert_eq!(expected_xfe, xfe);
+        let _: Digest = reader.read().unwrap();
+        assert_eq!(8_589_934_594, reader.read::<u64>().unwrap());
+        assert_eq!("triton", reader.read::<String>().unwrap());
+        assert!(reader.is_exhausted());
+        assert!(reader.read::<BFieldElement>().is_err());
+    }
+
+    #[test]
+    fn decoding_rejects_invalid_or_trailing_words_test() {
+        let program = Program::from_code("push 1 push -1 write_io write_io halt").unwrap();
+        let (_, output) = run(&program, vec![], vec![]).unwrap();
+        assert!(output.decode::<u64>().is_err());
+        assert!(output.decode::<BFieldElement>().is_err());
+        assert!(output.decode::<Digest>().is_err());
+        assert!(output.decode::<Vec<u8>>().is_err());
+        let (minus_one, one) = output.decode::<(BFieldElement, u32)>().unwrap();
+        assert_eq!(-BFieldElement::new(1), minus_one);
+        assert_eq!(1, one);
+        assert_eq!(vec![minus_one, BFieldElement::new(1)], output);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use num_traits::Zero;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+/// Triton VM's random-access memory, mapping addresses to values. Uninitialized memory holds 0.
+///
+/// The memory is copy-on-write, which makes [forking](Self::fork) cheap regardless of its size:
+/// forking freezes all writes so far into a layer shared by the original and the fork. Afterwards,
+/// each of them records only its own writes, and reads fall back to the shared layers. This allows
+/// executing speculatively – fork, execute the fork, and drop it to roll back – and branching
+/// executions, for example in fuzzers or symbolic executors.
+#[derive(Debug, Default, Clone)]
+pub struct Ram {
+    /// The writes since the last fork, not shared with any other memory.
+    writes: HashMap<BFieldElement, BFieldElement>,
+
+    /// The writes before the last fork, possibly shared with other memories.
+    frozen: Option<Arc<FrozenLayer>>,
+}
+
+/// Writes that can no longer change. Writes in a layer shadow the writes in its parent.
+#[derive(Debug)]
+struct FrozenLayer {
+    writes: HashMap<BFieldElement, BFieldElement>,
+    parent: Option<Arc<FrozenLayer>>,
+}
+
+impl Ram {
+    /// The value stored at the given address.
+    pub fn get(&self, address: BFieldElement) -> BFieldElement {
+        if let Some(&value) = self.writes.get(&address) {
+            return value;
+        }
+        let mut layer = self.frozen.as_deref();
+        while let Some(frozen_layer) = layer {
+            if let Some(&value) = frozen_layer.writes.get(&address) {
+                return value;
+            }
+            layer = frozen_layer.parent.as_deref();
+        }
+        BFieldElement::zero()
+    }
+
+    pub fn insert(&mut self, address: BFieldElement, value: BFieldElement) {
+        self.writes.insert(address, value);
+    }
+
+    /// A memory with the same content that shares all but future writes with this memory.
+    pub fn fork(&mut self) -> Self {
+        self.freeze();
+        self.clone()
+    }
+
+    /// Move the writes since the last fork into a frozen layer. If no other memory shares the
+    /// current frozen layer, for example because all forks have been dropped, the writes are
+    /// merged into it instead of stacking another layer on top.
+    fn freeze(&mut self) {
+        if self.writes.is_empty() {
+            return;
+        }
+        let writes = std::mem::take(&mut self.writes);
+        let layer = match self.frozen.take().map(Arc::try_unwrap) {
+            None => FrozenLayer {
+                writes,
+                parent: None,
+            },
+            Some(Ok(mut unshared_layer)) => {
+                unshared_layer.writes.extend(writes);
+                unshared_layer
+            }
+            Some(Err(shared_layer)) => FrozenLayer {
+                writes,
+                parent: Some(shared_layer),
+            },
+        };
+        self.frozen = Some(Arc::new(layer));
+    }
+
+    /// All written addresses and their values.
+    pub fn to_hash_map(&self) -> HashMap<BFieldElement, BFieldElement> {
+        let mut layers = vec![&self.writes];
+        let mut layer = self.frozen.as_deref();
+        while let Some(frozen_layer) = layer {
+            layers.push(&frozen_layer.writes);
+            layer = frozen_layer.parent.as_deref();
+        }
+
+        let mut ram = HashMap::new();
+        for writes in layers.into_iter().rev() {
+            ram.extend(writes);
+        }
+        ram
+    }
+}
+
+impl FromIterator<(BFieldElement, BFieldElement)> for Ram {
+    fn from_iter<I: IntoIterator<Item = (BFieldElement, BFieldElement)>>(iter: I) -> Self {
+        Self {
+            writes: iter.into_iter().collect(),
+            frozen: None,
+        }
+    }
+}
+
+/// Memories are equal if the same addresses have been written, holding the same values. An
+/// address written with 0 is thus different from an address never written, even though both read
+/// as 0.
+impl PartialEq for Ram {
+    fn eq(&self, other: &Self) -> bool {
+        let ram = self.to_hash_map();
+        let other_ram = other.to_hash_map();
+        let written_addresses: HashSet<_> = ram.keys().collect();
+        let other_written_addresses: HashSet<_> = other_ram.keys().collect();
+        written_addresses == other_written_addresses
+            && written_addresses
+                .into_iter()
+                .all(|address| ram[address] == other_ram[address])
+    }
+}
+
+impl Eq for Ram {}
+
+#[cfg(test)]
+mod ram_tests {
+    use super::*;
+
+    #[test]
+    fn forks_do_not_see_each_others_writes_test() {
+        let [zero, one, two, three] = [0, 1, 2, 3].map(BFieldElement::new);
+        let mut ram = Ram::default();
+        ram.insert(zero, one);
+        ram.insert(one, two);
+
+        let mut fork = ram.fork();
+        fork.insert(one, three);
+        fork.insert(two, three);
+        ram.insert(zero, two);
+
+        assert_eq!(two, ram.get(zero));
+        assert_eq!(two, ram.get(one));
+        assert_eq!(zero, ram.get(two));
+        assert_eq!(one, fork.get(zero));
+        assert_eq!(three, fork.get(one));
+        assert_eq!(three, fork.get(two));
+
+        let mut fork_of_fork = fork.fork();
+        fork_of_fork.insert(zero, zero);
+        assert_eq!(one, fork.get(zero));
+        assert_eq!(zero, fork_of_fork.get(zero));
+        assert_eq!(three, fork_of_fork.get(one));
+    }
+
+    #[test]
+    fn address_written_with_zero_differs_from_unwritten_address_test() {
+        let [zero, one] = [0, 1].map(BFieldElement::new);
+        let mut ram = Ram::default();
+        ram.insert(one, zero);
+        assert_eq!(ram.get(zero), ram.get(one));
+        assert_ne!(Ram::default(), ram);
+
+        let mut fork = ram.fork();
+        assert_eq!(ram, fork);
+        fork.insert(one, one);
+        assert_ne!(ram, fork);
+        fork.insert(one, zero);
+        assert_eq!(ram, fork);
+    }
+
+    #[test]
+    fn dropped_forks_release_their_layers_test() {
+        let mut ram: Ram = (0..10)
+            .map(|i| (BFieldElement::new(i), BFieldElement::new(i + 1)))
+            .collect();
+        let expected = ram.to_hash_map();
+        for _ in 0..100 {
+            let mut fork = ram.fork();
+            fork.insert(BFieldElement::new(3), BFieldElement::new(42));
+            ram.insert(BFieldElement::new(3), BFieldElement::new(4));
+        }
+        ram.freeze();
+
+        let frozen_layer = ram.frozen.as_ref().unwrap();
+        assert!(frozen_layer.parent.is_none());
+        assert_eq!(expected, ram.to_hash_map());
+    }
+}
@@ -0,0 +1,675 @@
+//! Slow but obviously correct implementations of the computations the prover and the verifier
+//! perform in optimized form, and a harness checking that two implementations agree.
+//!
+//! The optimized code paths – constraint evaluators generated from the constraint circuits,
+//! quotients computed with batch-inverted zerofiers, parallelized FRI folding and Merkle tree
+//! construction – are where optimization-induced soundness bugs hide. A [`Backend`] bundles these
+//! computations. The [`OptimizedBackend`] uses the code paths of the prover and verifier, the
+//! [`ReferenceBackend`] computes the same values straight from their definitions. Function
+//! [`check_backends_agree`] runs two backends on the same program and panics on the first
+//! disagreement.
+//!
+//! Only available with feature `test-utils`.
+
+use ndarray::s;
+use ndarray::stack;
+use ndarray::Array2;
+use ndarray::ArrayView1;
+use ndarray::ArrayView2;
+use ndarray::Axis;
+use num_traits::One;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::other::random_elements;
+use twenty_first::shared_math::polynomial::Polynomial;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::shared_math::traits::Inverse;
+use twenty_first::shared_math::traits::ModPowU32;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
+use twenty_first::util_types::merkle_tree::MerkleTree;
+use twenty_first::util_types::merkle_tree_maker::MerkleTreeMaker;
+
+use crate::arithmetic_domain::ArithmeticDomain;
+use crate::fri::fold_codeword;
+use crate::proof::Claim;
+use crate::shared_tests::SourceCodeAndInput;
+use crate::stark::Maker;
+use crate::stark::Stark;
+use crate::stark::StarkHasher;
+use crate::stark::StarkParameters;
+use crate::table::challenges::AllChallenges;
+use crate::table::challenges::TableChallenges;
+use crate::table::constraint_circuit::BinOp;
+use crate::table::constraint_circuit::CircuitExpression;
+use crate::table::constraint_circuit::ConstraintCircuit;
+use crate::table::constraint_circuit::InputIndicator;
+use crate::table::cross_table_argument::GrandCrossTableArg;
+use crate::table::extension_table::Evaluable;
+use crate::table::hash_table::ExtHashTable;
+use crate::table::instruction_table::ExtInstructionTable;
+use crate::table::jump_stack_table::ExtJumpStackTable;
+use crate::table::master_table::all_quotients;
+use crate::table::master_table::evaluate_all_constraints;
+use crate::table::master_table::num_all_consistency_quotients;
+use crate::table::master_table::num_all_initial_quotients;
+use crate::table::master_table::num_all_table_quotients;
+use crate::table::master_table::num_all_transition_quotients;
+use crate::table::master_table::MasterBaseTable;
+use crate::table::master_table::MasterTable;
+use crate::table::op_stack_table::ExtOpStackTable;
+use crate::table::processor_table::ExtProcessorTable;
+use crate::table::program_table::ExtProgramTable;
+use crate::table::ram_table::ExtRamTable;
+use crate::vm::simulate;
+
+/// The computations of the prover and the verifier that have an optimized implementation.
+pub trait Backend {
+    /// All constraints, evaluated on the given current and next rows of the master tables, in
+    /// the order of [`evaluate_all_constraints`]. Constraints on a single row are evaluated on the
+    /// current row.
+    fn evaluate_constraints(
+        &self,
+        current_base_row: ArrayView1<BFieldElement>,
+        current_ext_row: ArrayView1<XFieldElement>,
+        next_base_row: ArrayView1<BFieldElement>,
+        next_ext_row: ArrayView1<XFieldElement>,
+        challenges: &AllChallenges,
+    ) -> Vec<XFieldElement>;
+
+    /// The Master Quotient Table for the given master tables over the quotient domain, in the
+    /// layout of [`all_quotients`].
+    fn quotients(
+        &self,
+        quotient_domain_master_base_table: ArrayView2<BFieldElement>,
+        quotient_domain_master_ext_table: ArrayView2<XFieldElement>,
+        trace_domain: ArithmeticDomain,
+        quotient_domain: ArithmeticDomain,
+        challenges: &AllChallenges,
+    ) -> Array2<XFieldElement>;
+
+    /// One round of FRI folding, as specified by [`fold_codeword`].
+    fn fri_fold(
+        &self,
+        codeword: &[XFieldElement],
+        domain: ArithmeticDomain,
+        alpha: XFieldElement,
+    ) -> Vec<XFieldElement>;
+
+    /// The root of the Merkle tree with the given leafs. The number of leafs is a power of 2.
+    fn merkle_root(&self, leafs: &[Digest]) -> Digest;
+}
+
+/// The code paths used by the prover and the verifier.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptimizedBackend;
+
+impl Backend for OptimizedBackend {
+    fn evaluate_constraints(
+        &self,
+        current_base_row: ArrayView1<BFieldElement>,
+        current_ext_row: ArrayView1<XFieldElement>,
+        next_base_row: ArrayView1<BFieldElement>,
+        next_ext_row: ArrayView1<XFieldElement>,
+        challenges: &AllChallenges,
+    ) -> Vec<XFieldElement> {
+        evaluate_all_constraints(
+            current_base_row,
+            current_ext_row,
+            next_base_row,
+            next_ext_row,
+            challenges,
+        )
+    }
+
+    fn quotients(
+        &self,
+        quotient_domain_master_base_table: ArrayView2<BFieldElement>,
+        quotient_domain_master_ext_table: ArrayView2<XFieldElement>,
+        trace_domain: ArithmeticDomain,
+        quotient_domain: ArithmeticDomain,
+        challenges: &AllChallenges,
+    ) -> Array2<XFieldElement> {
+        all_quotients(
+            quotient_domain_master_base_table,
+            quotient_domain_master_ext_table,
+            trace_domain,
+            quotient_domain,
+            challenges,
+            &mut None,
+        )
+    }
+
+    fn fri_fold(
+        &self,
+        codeword: &[XFieldElement],
+        domain: ArithmeticDomain,
+        alpha: XFieldElement,
+    ) -> Vec<XFieldElement> {
+        fold_codeword(codeword, domain, alpha)
+    }
+
+    fn merkle_root(&self, leafs: &[Digest]) -> Digest {
+        let merkle_tree: MerkleTree<StarkHasher, Maker> = Maker::from_digests(leafs);
+        merkle_tree.get_root()
+    }
+}
+
+/// A constraint of any table, evaluated on the master tables' rows – only the current row for
+/// single-row constraints, the current and the next row for transition constraints.
+type Constraint = Box<
+    dyn Fn(ArrayView2<BFieldElement>, ArrayView2<XFieldElement>, &AllChallenges) -> XFieldElement,
+>;
+
+/// Straight-line implementations without parallelism, batching, or code generation. Constraints
+/// are evaluated by walking the constraint circuits the constraint-evaluation-generator compiles
+/// from. The constraints of the Grand Cross-Table Argument are not generated and have no circuits,
+/// so they are evaluated as defined in [`GrandCrossTableArg`].
+pub struct ReferenceBackend {
+    initial_constraints: Vec<Constraint>,
+    consistency_constraints: Vec<Constraint>,
+    transition_constraints: Vec<Constraint>,
+    terminal_constraints: Vec<Constraint>,
+}
+
+/// The given table's constraints of the given kind, each paired with the table's challenges.
+macro_rules! table_constraints {
+    ($kind:ident, $($table:ident: $challenges:ident),+ $(,)?) => {
+        [$(
+            boxed_constraints($table::$kind, |challenges| &challenges.$challenges),
+        )+]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+    };
+}
+
+impl ReferenceBackend {
+    pub fn new() -> Self {
+        macro_rules! all_table_constraints {
+            ($kind:ident) => {
+                table_constraints!(
+                    $kind,
+                    ExtProgramTable: program_table_challenges,
+                    ExtInstructionTable: instruction_table_challenges,
+                    ExtProcessorTable: processor_table_challenges,
+                    ExtOpStackTable: op_stack_table_challenges,
+                    ExtRamTable: ram_table_challenges,
+                    ExtJumpStackTable: jump_stack_table_challenges,
+                    ExtHashTable: hash_table_challenges,
+                )
+            };
+        }
+
+        let grand_cross_table_arg_terminal: Constraint =
+            Box::new(|base_rows, ext_rows, challenges| {
+                let terminal_constraints = GrandCrossTableArg::evaluate_terminal_constraints(
+                    base_rows.row(0),
+                    ext_rows.row(0),
+                    challenges,
+                );
+                assert_eq!(1, terminal_constraints.len());
+                terminal_constraints[0]
+            });
+
+        let mut terminal_constraints = all_table_constraints!(ext_terminal_constraints_as_circuits);
+        terminal_constraints.push(grand_cross_table_arg_terminal);
+
+        Self {
+            initial_constraints: all_table_constraints!(ext_initial_constraints_as_circuits),
+            consistency_constraints: all_table_constraints!(
+                ext_consistency_constraints_as_circuits
+            ),
+            transition_constraints: all_table_constraints!(ext_transition_constraints_as_circuits),
+            terminal_constraints,
+        }
+    }
+}
+
+impl Default for ReferenceBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The constraints built by the given circuit constructor, in the order of the generated
+/// evaluators: those that only involve base field elements first. Like the
+/// constraint-evaluation-generator, the order is decided on the constant-folded circuits, but the
+/// constraints evaluate the circuits as built.
+fn boxed_constraints<T, II>(
+    circuits: fn() -> Vec<ConstraintCircuit<T, II>>,
+    table_challenges: fn(&AllChallenges) -> &T,
+) -> Vec<Constraint>
+where
+    T: TableChallenges + 'static,
+    II: InputIndicator + 'static,
+{
+    let mut folded_circuits = circuits();
+    ConstraintCircuit::constant_folding(&mut folded_circuits.iter_mut().collect::<Vec<_>>());
+    let (base_field_circuits, ext_field_circuits): (Vec<_>, Vec<_>) = circuits()
+        .into_iter()
+        .zip(folded_circuits.iter())
+        .partition(|(_, folded_circuit)| is_base_field_only(folded_circuit));
+
+    base_field_circuits
+        .into_iter()
+        .chain(ext_field_circuits)
+        .map(|(circuit, _)| {
+            let constraint = move |base_rows: ArrayView2<BFieldElement>,
+                                   ext_rows: ArrayView2<XFieldElement>,
+                                   challenges: &AllChallenges| {
+                evaluate_circuit(&circuit, base_rows, ext_rows, table_challenges(challenges))
+            };
+            Box::new(constraint) as Constraint
+        })
+        .collect()
+}
+
+/// Whether the circuit only involves base table inputs and base field constants.
+fn is_base_field_only<T: TableChallenges, II: InputIndicator>(
+    circuit: &ConstraintCircuit<T, II>,
+) -> bool {
+    match &circuit.expression {
+        CircuitExpression::XConstant(_) | CircuitExpression::Challenge(_) => false,
+        CircuitExpression::BConstant(_) => true,
+        CircuitExpression::Input(input) => input.is_base_table_row(),
+        CircuitExpression::BinaryOperation(_, lhs, rhs) => {
+            is_base_field_only(&lhs.borrow()) && is_base_field_only(&rhs.borrow())
+        }
+    }
+}
+
+/// Evaluate the circuit node by node. Unlike [`ConstraintCircuit::evaluate`], leaves the circuit
+/// untouched.
+fn evaluate_circuit<T: TableChallenges, II: InputIndicator>(
+    circuit: &ConstraintCircuit<T, II>,
+    base_rows: ArrayView2<BFieldElement>,
+    ext_rows: ArrayView2<XFieldElement>,
+    challenges: &T,
+) -> XFieldElement {
+    match &circuit.expression {
+        CircuitExpression::XConstant(xfe) => *xfe,
+        CircuitExpression::BConstant(bfe) => bfe.lift(),
+        CircuitExpression::Input(input) => input.evaluate(base_rows, ext_rows),
+        CircuitExpression::Challenge(id) => challenges.get_challenge(*id),
+        CircuitExpression::BinaryOperation(binop, lhs, rhs) => {
+            let lhs = evaluate_circuit(&lhs.borrow(), base_rows, ext_rows, challenges);
+            let rhs = evaluate_circuit(&rhs.borrow(), base_rows, ext_rows, challenges);
+            match binop {
+                BinOp::Add => lhs + rhs,
+                BinOp::Sub => lhs - rhs,
+                BinOp::Mul => lhs * rhs,
+            }
+        }
+    }
+}
+
+impl Backend for ReferenceBackend {
+    fn evaluate_constraints(
+        &self,
+        current_base_row: ArrayView1<BFieldElement>,
+        current_ext_row: ArrayView1<XFieldElement>,
+        next_base_row: ArrayView1<BFieldElement>,
+        next_ext_row: ArrayView1<XFieldElement>,
+        challenges: &AllChallenges,
+    ) -> Vec<XFieldElement> {
+        let current_base_rows = current_base_row.insert_axis(Axis(0));
+        let current_ext_rows = current_ext_row.insert_axis(Axis(0));
+        let both_base_rows = stack![Axis(0), current_base_row, next_base_row];
+        let both_ext_rows = stack![Axis(0), current_ext_row, next_ext_row];
+
+        let single_row = |constraints: &[Constraint]| {
+            constraints
+                .iter()
+                .map(|constraint| constraint(current_base_rows, current_ext_rows, challenges))
+                .collect::<Vec<_>>()
+        };
+        let transition = self
+            .transition_constraints
+            .iter()
+            .map(|constraint| constraint(both_base_rows.view(), both_ext_rows.view(), challenges))
+            .collect();
+
+        [
+            single_row(&self.initial_constraints),
+            single_row(&self.consistency_constraints),
+            transition,
+            single_row(&self.terminal_constraints),
+        ]
+        .concat()
+    }
+
+    fn quotients(
+        &self,
+        quotient_domain_master_base_table: ArrayView2<BFieldElement>,
+        quotient_domain_master_ext_table: ArrayView2<XFieldElement>,
+        trace_domain: ArithmeticDomain,
+        quotient_domain: ArithmeticDomain,
+        challenges: &AllChallenges,
+    ) -> Array2<XFieldElement> {
+        let initial_end = num_all_initial_quotients();
+        let consistency_end = initial_end + num_all_consistency_quotients();
+        let transition_end = consistency_end + num_all_transition_quotients();
+
+        // The last row of the trace domain, the only row where transition constraints don't apply.
+        let last_trace_row = trace_domain.generator.inverse();
+        let unit_distance = quotient_domain.length / trace_domain.length;
+
+        let mut quotients = Array2::zeros([quotient_domain.length, num_all_table_quotients()]);
+        for row_idx in 0..quotient_domain.length {
+            let next_row_idx = (row_idx + unit_distance) % quotient_domain.length;
+            let constraints = self.evaluate_constraints(
+                quotient_domain_master_base_table.row(row_idx),
+                quotient_domain_master_ext_table.row(row_idx),
+                quotient_domain_master_base_table.row(next_row_idx),
+                quotient_domain_master_ext_table.row(next_row_idx),
+                challenges,
+            );
+
+            let x = quotient_domain.domain_value(row_idx as u32);
+            let on_all_rows = x.mod_pow_u32(trace_domain.length as u32) - BFieldElement::one();
+            for (quotient_idx, constraint) in constraints.into_iter().enumerate() {
+                let zerofier = match quotient_idx {
+                    i if i < initial_end => x - BFieldElement::one(),
+                    i if i < consistency_end => on_all_rows,
+                    i if i < transition_end => on_all_rows / (x - last_trace_row),
+                    _ => x - last_trace_row,
+                };
+                quotients[[row_idx, quotient_idx]] = constraint / zerofier.lift();
+            }
+        }
+        quotients
+    }
+
+    fn fri_fold(
+        &self,
+        codeword: &[XFieldElement],
+        domain: ArithmeticDomain,
+        alpha: XFieldElement,
+    ) -> Vec<XFieldElement> {
+        // The folded codeword's value in x² is the value in alpha of the line through points
+        // (x, f(x)) and (-x, f(-x)).
+        let half_length = domain.length / 2;
+        (0..half_length)
+            .map(|i| {
+                let x = domain.domain_value(i as u32).lift();
+                let minus_x = domain.domain_value((i + half_length) as u32).lift();
+                Polynomial::get_colinear_y(
+                    (x, codeword[i]),
+                    (minus_x, codeword[i + half_length]),
+                    alpha,
+                )
+            })
+            .collect()
+    }
+
+    fn merkle_root(&self, leafs: &[Digest]) -> Digest {
+        match leafs.len() {
+            1 => leafs[0],
+            num_leafs => {
+                let (left, right) = leafs.split_at(num_leafs / 2);
+                StarkHasher::hash_pair(&self.merkle_root(left), &self.merkle_root(right))
+            }
+        }
+    }
+}
+
+/// Run both backends on the given program and check that they agree on every computation of the
+/// [`Backend`]: the constraints on every row of the trace, the Master Quotient Table, every round of FRI folding of the nonlinear
+/// combination codeword, and the Merkle roots of the base and extension tables. Panics on the
+/// first disagreement.
+pub fn check_backends_agree(
+    expected: &dyn Backend,
+    actual: &dyn Backend,
+    source_code_and_input: SourceCodeAndInput,
+) {
+    let program = Program::from_code(&source_code_and_input.source_code).unwrap();
+    let (aet, output) = simulate(
+        &program,
+        source_code_and_input.input.clone(),
+        source_code_and_input.secret_input,
+    )
+    .unwrap_or_else(|err| panic!("The program must not crash: {err}"));
+
+    let instructions = program.to_bwords();
+    let claim = Claim {
+        input: source_code_and_input.input,
+        padded_height: MasterBaseTable::padded_height(&aet, &instructions),
+        program: instructions,
+        output: output.into(),
+        output_tags: vec![],
+        entry_point: program.entry_point,
+    };
+    let stark = Stark::new(claim, StarkParameters::new(32, 4));
+    let challenges = AllChallenges::create_challenges(
+        random_elements(AllChallenges::TOTAL_CHALLENGES),
+        &stark.claim.input,
+        &stark.claim.output,
+        &stark.claim.output_tags,
+        stark.claim.entry_point,
+    );
+
+    let mut base_table = MasterBaseTable::new(
+        aet,
+        &stark.claim.program,
+        stark.parameters.num_trace_randomizers,
+        stark.fri.domain,
+    );
+    base_table.pad();
+    base_table.randomize_trace();
+    let mut ext_table = base_table.extend(&challenges, stark.parameters.num_randomizer_polynomials);
+    ext_table.randomize_trace();
+
+    let base_trace = base_table.trace_table();
+    let ext_trace = ext_table.trace_table();
+    for row_idx in 0..base_trace.nrows() - 1 {
+        let evaluate_constraints = |backend: &dyn Backend| {
+            backend.evaluate_constraints(
+                base_trace.row(row_idx),
+                ext_trace.row(row_idx),
+                base_trace.row(row_idx + 1),
+                ext_trace.row(row_idx + 1),
+                &challenges,
+            )
+        };
+        assert_agree(
+            &evaluate_constraints(expected),
+            &evaluate_constraints(actual),
+            &format!("the constraints in row {row_idx} of the trace"),
+        );
+    }
+
+    let fri_domain_base_table = base_table.to_fri_domain_table();
+    let fri_domain_ext_table = ext_table.to_fri_domain_table();
+    let base_matrix = fri_domain_base_table.master_base_matrix.view();
+    let ext_matrix = fri_domain_ext_table.master_ext_matrix.view();
+
+    let quotient_domain = stark.quotient_domain();
+    let trace_domain = ArithmeticDomain::new_no_offset(stark.claim.padded_height);
+    let quotient_unit_distance = stark.fri.domain.length / quotient_domain.length;
+    let quotient_domain_base_table = base_matrix.slice(s![..; quotient_unit_distance, ..]);
+    let quotient_domain_ext_table = ext_matrix.slice(s![..; quotient_unit_distance, ..]);
+    let quotients = |backend: &dyn Backend| {
+        backend.quotients(
+            quotient_domain_base_table,
+            quotient_domain_ext_table,
+            trace_domain,
+            quotient_domain,
+            &challenges,
+        )
+    };
+    let expected_quotients = quotients(expected);
+    let actual_quotients = quotients(actual);
+    assert_eq!(expected_quotients.dim(), actual_quotients.dim());
+    for (row_idx, (expected_row, actual_row)) in expected_quotients
+        .rows()
+        .into_iter()
+        .zip(actual_quotients.rows())
+        .enumerate()
+    {
+        assert_agree(
+            &expected_row.to_vec(),
+            &actual_row.to_vec(),
+            &format!("the quotients in row {row_idx} of the quotient domain"),
+        );
+    }
+
+    let num_weights = Stark::num_non_lin_combi_weights();
+    let weights = random_elements(num_weights);
+    let mut codeword = stark.fri_combination_codeword(
+        &fri_domain_base_table,
+        &fri_domain_ext_table,
+        &challenges,
+        &weights,
+        &mut None,
+    );
+    let mut domain = stark.fri.domain;
+    while domain.length > 1 {
+        let alpha = random_elements(1)[0];
+        let expected_folding = expected.fri_fold(&codeword, domain, alpha);
+        let actual_folding = actual.fri_fold(&codeword, domain, alpha);
+        assert_agree(
+            &expected_folding,
+            &actual_folding,
+            &format!("folding the codeword of length {}", domain.length),
+        );
+        codeword = expected_folding;
+        domain = ArithmeticDomain {
+            offset: domain.offset * domain.offset,
+            generator: domain.generator * domain.generator,
+            length: domain.length / 2,
+        };
+    }
+
+    let base_leafs = base_matrix
+        .axis_iter(Axis(0))
+        .map(|row| StarkHasher::hash_slice(&row.to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        expected.merkle_root(&base_leafs),
+        actual.merkle_root(&base_leafs),
+        "Backends must agree on the Merkle root of the base table."
+    );
+    let ext_leafs = ext_matrix
+        .axis_iter(Axis(0))
+        .map(|row| {
+            let row = row
+                .iter()
+                .flat_map(|xfe| xfe.coefficients)
+                .collect::<Vec<_>>();
+            StarkHasher::hash_slice(&row)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        expected.merkle_root(&ext_leafs),
+        actual.merkle_root(&ext_leafs),
+        "Backends must agree on the Merkle root of the extension table."
+    );
+}
+
+/// Panics with a message naming the first index where the two computations disagree, if any.
+fn assert_agree(expected: &[XFieldElement], actual: &[XFieldElement], computation: &str) {
+    assert_eq!(
+        expected.len(),
+        actual.len(),
+        "Backends must agree on the number of values in {computation}."
+    );
+    let maybe_disagreement = expected.iter().zip(actual).position(|(e, a)| e != a);
+    if let Some(idx) = maybe_disagreement {
+        panic!(
+            "Backends must agree on {computation}, but disagree at index {idx}: \
+            expected {}, got {}.",
+            expected[idx], actual[idx]
+        );
+    }
+}
+
+#[cfg(test)]
+mod reference_tests {
+    use super::*;
+
+    /// The optimized backend, except that the quotient of the first transition constraint is
+    /// scaled in one row of the quotient domain – like an off-by-one in a zerofier would.
+    struct FaultyQuotientsBackend;
+
+    impl Backend for FaultyQuotientsBackend {
+        fn evaluate_constraints(
+            &self,
+            current_base_row: ArrayView1<BFieldElement>,
+            current_ext_row: ArrayView1<XFieldElement>,
+            next_base_row: ArrayView1<BFieldElement>,
+            next_ext_row: ArrayView1<XFieldElement>,
+            challenges: &AllChallenges,
+        ) -> Vec<XFieldElement> {
+            OptimizedBackend.evaluate_constraints(
+                current_base_row,
+                current_ext_row,
+                next_base_row,
+                next_ext_row,
+                challenges,
+            )
+        }
+
+        fn quotients(
+            &self,
+            quotient_domain_master_base_table: ArrayView2<BFieldElement>,
+            quotient_domain_master_ext_table: ArrayView2<XFieldElement>,
+            trace_domain: ArithmeticDomain,
+            quotient_domain: ArithmeticDomain,
+            challenges: &AllChallenges,
+        ) -> Array2<XFieldElement> {
+            let mut quotients = OptimizedBackend.quotients(
+                quotient_domain_master_base_table,
+                quotient_domain_master_ext_table,
+                trace_domain,
+                quotient_domain,
+                challenges,
+            );
+            let first_transition_quotient =
+                num_all_initial_quotients() + num_all_consistency_quotients();
+            quotients[[1, first_transition_quotient]] *= BFieldElement::new(2);
+            quotients
+        }
+
+        fn fri_fold(
+            &self,
+            codeword: &[XFieldElement],
+            domain: ArithmeticDomain,
+            alpha: XFieldElement,
+        ) -> Vec<XFieldElement> {
+            OptimizedBackend.fri_fold(codeword, domain, alpha)
+        }
+
+        fn merkle_root(&self, leafs: &[Digest]) -> Digest {
+            OptimizedBackend.merkle_root(leafs)
+        }
+    }
+
+    #[test]
+    fn optimized_backend_agrees_with_reference_backend_test() {
+        let source_code_and_input = SourceCodeAndInput {
+            source_code: "read_io push 3 mul dup0 write_io push 7 push 5 write_mem pop \
+                call square read_io assert halt \
+                square: dup0 mul return"
+                .to_string(),
+            input: vec![BFieldElement::new(14), BFieldElement::one()],
+            secret_input: vec![],
+        };
+        check_backends_agree(
+            &ReferenceBackend::new(),
+            &OptimizedBackend,
+            source_code_and_input,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Backends must agree on the quotients in row 1")]
+    fn disagreeing_backends_are_detected_test() {
+        let source_code_and_input = SourceCodeAndInput::without_input("halt");
+        check_backends_agree(
+            &ReferenceBackend::new(),
+            &FaultyQuotientsBackend,
+            source_code_and_input,
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Canonical test vectors for the semantics of every instruction: a state, an instruction, and
+//! the state after executing that instruction. The vectors are machine-readable, allowing
+//! alternative implementations of Triton VM to check conformance with this one.
+//!
+//! All field elements are given in their canonical representation, not in Montgomery form.
+
+use anyhow::bail;
+use anyhow::Result;
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::state::VMOutput;
+use crate::state::VMState;
+
+/// Everything an instruction can read or modify, in a stable, serializable format. Doubles as a
+/// checkpoint from which execution can be [resumed](crate::vm::resume).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// The entire op stack, including the op-stack registers. The top of the stack comes last.
+    pub op_stack: Vec<u64>,
+
+    /// All initialized RAM cells as `(address, value)`, sorted by address.
+    pub ram: Vec<(u64, u64)>,
+
+    /// The jump stack as `(origin, destination)`. The top of the stack comes last.
+    pub jump_stack: Vec<(u64, u64)>,
+
+    pub cycle_count: u32,
+    pub instruction_pointer: usize,
+
+    /// The opcode of the instruction that was executed last.
+    pub previous_instruction: u64,
+    pub ramp: u64,
+
+    /// The not yet consumed part of the public input.
+    pub stdin: Vec<u64>,
+
+    /// The not yet consumed part of the secret input.
+    pub secret_input: Vec<u64>,
+}
+
+impl StateSnapshot {
+    pub fn new(state: &VMState, stdin: &[BFieldElement], secret_in: &[BFieldElement]) -> Self {
+        let values = |elements: &[BFieldElement]| elements.iter().map(|e| e.value()).collect_vec();
+        let pairs = |pairs: &[(BFieldElement, BFieldElement)]| {
+            pairs
+                .iter()
+                .map(|(a, b)| (a.value(), b.value()))
+                .collect_vec()
+        };
+        let ram = state
+            .ram
+            .to_hash_map()
+            .into_iter()
+            .sorted_by_key(|(address, _)| address.value())
+            .collect_vec();
+
+        Self {
+            op_stack: values(&state.op_stack.stack),
+            ram: pairs(&ram),
+            jump_stack: pairs(&state.jump_stack),
+            cycle_count: state.cycle_count,
+            instruction_pointer: state.instruction_pointer,
+            previous_instruction: state.previous_instruction.value(),
+            ramp: state.ramp,
+            stdin: values(stdin),
+            secret_input: values(secret_in),
+        }
+    }
+
+    /// The state of the VM running the given program, as well as public and secret input.
+    pub fn to_vm_state<'pgm>(
+        &self,
+        program: &'pgm Program,
+    ) -> (VMState<'pgm>, Vec<BFieldElement>, Vec<BFieldElement>) {
+        let elements = |values: &[u64]| values.iter().map(|&v| BFieldElement::new(v)).collect_vec();
+        let pairs = |pairs: &[(u64, u64)]| {
+            pairs
+                .iter()
+                .map(|&(a, b)| (BFieldElement::new(a), BFieldElement::new(b)))
+                .collect_vec()
+        };
+
+        let mut state = VMState::new(program);
+        state.op_stack.stack = elements(&self.op_stack);
+        state.ram = pairs(&self.ram).into_iter().collect();
+        state.jump_stack = pairs(&self.jump_stack);
+        state.cycle_count = self.cycle_count;
+        state.instruction_pointer = self.instruction_pointer;
+        state.previous_instruction = BFieldElement::new(self.previous_instruction);
+        state.ramp = self.ramp;
+        (state, elements(&self.stdin), elements(&self.secret_input))
+    }
+}
+
+/// The execution of one instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SemanticsVector {
+    /// Unique among all vectors. Starts with the mnemonic of the executed instruction.
+    pub name: String,
+
+    /// The source code of the program. Its instruction at the pre-state's instruction pointer is
+    /// the one being executed.
+    pub program: String,
+
+    /// The executed instruction, including its argument.
+    pub instruction: String,
+
+    pub pre_state: StateSnapshot,
+    pub post_state: StateSnapshot,
+
+    /// The words written to standard output by the instruction.
+    pub output: Vec<u64>,
+}
+
+impl SemanticsVector {
+    /// Run the program until `num_preceding_steps` instructions are executed, then record the
+    /// execution of the next instruction.
+    fn generate(
+        name: &str,
+        code: &str,
+        stdin: &[u64],
+        secret_in: &[u64],
+        num_preceding_steps: usize,
+    ) -> Result<Self> {
+        let program = Program::from_code(code)?;
+        let mut stdin = stdin.iter().map(|&v| BFieldElement::new(v)).collect_vec();
+        let mut secret_in = secret_in
+            .iter()
+            .map(|&v| BFieldElement::new(v))
+            .collect_vec();
+        let mut state = VMState::new(&program);
+        for _ in 0..num_preceding_steps {
+            state.step_mut(&mut stdin, &mut secret_in)?;
+        }
+
+        let instruction = state.current_instruction()?.to_string();
+        let pre_state = StateSnapshot::new(&state, &stdin, &secret_in);
+        let vm_output = state.step_mut(&mut stdin, &mut secret_in)?;
+        let post_state = StateSnapshot::new(&state, &stdin, &secret_in);
+
+        Ok(Self {
+            name: name.to_string(),
+            program: code.to_string(),
+            instruction,
+            pre_state,
+            post_state,
+            output: output(vm_output),
+        })
+    }
+
+    /// Execute the instruction on the pre-state and compare the result to the post-state.
+    pub fn check(&self) -> Result<()> {
+        let name = &self.name;
+        let program = Program::from_code(&self.program)?;
+        let (mut state, mut stdin, mut secret_in) = self.pre_state.to_vm_state(&program);
+
+        let instruction = state.current_instruction()?.to_string();
+        if instruction != self.instruction {
+            bail!(
+                "Vector {name} executes `{instruction}`, not `{}`.",
+                self.instruction
+            );
+        }
+        let vm_output = state.step_mut(&mut stdin, &mut secret_in)?;
+        let post_state = StateSnapshot::new(&state, &stdin, &secret_in);
+        if post_state != self.post_state {
+            bail!(
+                "Vector {name} results in the wrong state.\n\
+                expected: {:?}\n  actual: {post_state:?}",
+                self.post_state
+            );
+        }
+        let output = output(vm_output);
+        if output != self.output {
+            bail!(
+                "Vector {name} results in the wrong output.\n\
+                expected: {:?}\n  actual: {output:?}",
+                self.output
+            );
+        }
+        Ok(())
+    }
+}
+
+fn output(vm_output: Option<VMOutput>) -> Vec<u64> {
+    match vm_output {
+        Some(VMOutput::WriteOutputSymbol(word)) => vec![word.value()],
+        _ => vec![],
+    }
+}
+
+/// The canonical test vectors, generated by executing the simulator. Every instruction is
+/// executed at least once; instructions with multiple outcomes once per outcome.
+pub fn semantics_vectors() -> Result<Vec<SemanticsVector>> {
+    let push_10 = "push 1 push 2 push 3 push 4 push 5 push 6 push 7 push 8 push 9 push 10";
+    let divine_sibling = format!("push 3 {push_10} divine_sibling halt");
+    let hash = format!("{push_10} hash halt");
+    let assert_vector = format!("{push_10} dup4 dup4 dup4 dup4 dup4 assert_vector halt");
+    let push_6 = "push 1 push 2 push 3 push 4 push 5 push 6";
+    let xxadd = format!("{push_6} xxadd halt");
+    let xxmul = format!("{push_6} xxmul halt");
+
+    let specifications = vec![
+        ("pop", "push 7 pop halt", vec![], vec![], 1),
+        ("push", "push 7 halt", vec![], vec![], 0),
+        ("divine", "divine halt", vec![], vec![42], 0),
+        ("dup", "push 3 push 5 dup1 halt", vec![], vec![], 2),
+        ("swap", "push 3 push 5 swap1 halt", vec![], vec![], 2),
+        ("nop", "nop halt", vec![], vec![], 0),
+        ("skiz_nonzero", "push 1 skiz push 7 halt", vec![], vec![], 1),
+        ("skiz_zero", "push 0 skiz push 7 halt", vec![], vec![], 1),
+        ("call", "call foo halt foo: return", vec![], vec![], 0),
+        (
+            "tail_call",
+            "call foo halt foo: tail_call bar bar: return",
+            vec![],
+            vec![],
+            1,
+        ),
+        ("return", "call foo halt foo: return", vec![], vec![], 1),
+        ("recurse", "call foo halt foo: recurse", vec![], vec![], 1),
+        ("assert", "push 1 assert halt", vec![], vec![], 1),
+        ("halt", "halt", vec![], vec![], 0),
+        (
+            "read_mem",
+            "push 5 push 9 write_mem pop push 0 read_mem halt",
+            vec![],
+            vec![],
+            5,
+        ),
+        (
+            "write_mem",
+            "push 5 push 9 write_mem halt",
+            vec![],
+            vec![],
+            2,
+        ),
+        (
+            "read_mem_inc",
+            "push 5 push 9 write_mem pop push 0 read_mem_inc halt",
+            vec![],
+            vec![],
+            5,
+        ),
+        (
+            "write_mem_inc",
+            "push 5 push 9 write_mem_inc halt",
+            vec![],
+            vec![],
+            2,
+        ),
+        ("hash", &hash, vec![], vec![], 10),
+        (
+            "divine_sibling_left",
+            &divine_sibling,
+            vec![],
+            vec![6, 7, 8, 9, 10],
+            11,
+        ),
+        ("assert_vector", &assert_vector, vec![], vec![], 15),
+        ("add", "push 3 push 5 add halt", vec![], vec![], 2),
+        ("mul", "push 3 push 5 mul halt", vec![], vec![], 2),
+        ("invert", "push 5 invert halt", vec![], vec![], 1),
+        ("split", "push -1 split halt", vec![], vec![], 1),
+        ("eq_equal", "push 3 push 3 eq halt", vec![], vec![], 2),
+        ("eq_unequal", "push 3 push 5 eq halt", vec![], vec![], 2),
+        ("lsb", "push 7 lsb halt", vec![], vec![], 1),
+        ("xxadd", &xxadd, vec![], vec![], 6),
+        ("xxmul", &xxmul, vec![], vec![], 6),
+        (
+            "xinvert",
+            "push 1 push 2 push 3 xinvert halt",
+            vec![],
+            vec![],
+            3,
+        ),
+        (
+            "xbmul",
+            "push 1 push 2 push 3 push 4 xbmul halt",
+            vec![],
+            vec![],
+            4,
+        ),
+        ("read_io", "read_io halt", vec![42], vec![], 0),
+        ("write_io", "push 42 write_io halt", vec![], vec![], 1),
+    ];
+
+    specifications
+        .into_iter()
+        .map(|(name, code, stdin, secret_in, num_preceding_steps)| {
+            SemanticsVector::generate(name, code, &stdin, &secret_in, num_preceding_steps)
+        })
+        .collect()
+}
+
+/// The canonical test vectors as pretty-printed JSON.
+pub fn semantics_vectors_json() -> Result<String> {
+    Ok(serde_json::to_string_pretty(&semantics_vectors()?)?)
+}
+
+pub fn parse_semantics_vectors(json: &str) -> Result<Vec<SemanticsVector>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+#[cfg(test)]
+mod semantics_vectors_tests {
+    use std::collections::HashSet;
+
+    use triton_opcodes::instruction::all_instructions_without_args;
+
+    use super::*;
+
+    fn vector(name: &str) -> SemanticsVector {
+        semantics_vectors()
+            .unwrap()
+            .into_iter()
+            .find(|vector| vector.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn all_vectors_agree_with_simulator_test() {
+        let vectors = semantics_vectors().unwrap();
+        for vector in vectors.iter() {
+            vector.check().unwrap();
+        }
+        assert!(vectors.iter().map(|vector| &vector.name).all_unique());
+    }
+
+    #[test]
+    fn every_instruction_is_covered_test() {
+        let covered = semantics_vectors()
+            .unwrap()
+            .into_iter()
+            .map(|vector| {
+                let program = Program::from_code(&vector.program).unwrap();
+                program.instructions[vector.pre_state.instruction_pointer].opcode()
+            })
+            .collect::<HashSet<_>>();
+        for instruction in all_instructions_without_args() {
+            assert!(covered.contains(&instruction.opcode()), "{instruction}");
+        }
+    }
+
+    #[test]
+    fn vectors_survive_json_round_trip_test() {
+        let json = semantics_vectors_json().unwrap();
+        let parsed = parse_semantics_vectors(&json).unwrap();
+        assert_eq!(semantics_vectors().unwrap(), parsed);
+    }
+
+    #[test]
+    fn vectors_record_expected_semantics_test() {
+        let add = vector("add");
+        assert_eq!(Some(&8), add.post_state.op_stack.last());
+        assert_eq!(
+            add.pre_state.op_stack.len() - 1,
+            add.post_state.op_stack.len()
+        );
+
+        let skiz = vector("skiz_zero");
+        assert_eq!(2, skiz.pre_state.instruction_pointer);
+        assert_eq!(5, skiz.post_state.instruction_pointer);
+
+        let read_mem = vector("read_mem");
+        assert_eq!(vec![(5, 9)], read_mem.pre_state.ram);
+        assert_eq!(Some(&9), read_mem.post_state.op_stack.last());
+
+        let read_mem_inc = vector("read_mem_inc");
+        let post_stack = &read_mem_inc.post_state.op_stack;
+        assert_eq!(&[6, 9], &post_stack[post_stack.len() - 2..]);
+
+        let write_mem_inc = vector("write_mem_inc");
+        assert_eq!(vec![(5, 9)], write_mem_inc.post_state.ram);
+        let post_stack = &write_mem_inc.post_state.op_stack;
+        assert_eq!(&[6, 9], &post_stack[post_stack.len() - 2..]);
+
+        let tail_call = vector("tail_call");
+        assert_eq!(vec![(2, 3)], tail_call.pre_state.jump_stack);
+        assert_eq!(vec![(2, 5)], tail_call.post_state.jump_stack);
+
+        let write_io = vector("write_io");
+        assert_eq!(vec![42], write_io.output);
+        assert_eq!(vec![42], vector("read_io").pre_state.stdin);
+        assert!(vector("read_io").post_state.stdin.is_empty());
+    }
+
+    #[test]
+    fn tampered_vector_is_rejected_test() {
+        let mut mul = vector("mul");
+        *mul.post_state.op_stack.last_mut().unwrap() = 16;
+        assert!(mul.check().is_err());
+
+        let mut call = vector("call");
+        call.instruction = "call 4".to_string();
+        assert!(call.check().is_err());
+    }
+}
@@ -14,9 +14,11 @@ use twenty_first::shared_math::b_field_element::BFieldElement;
 
 use crate::proof::Claim;
 use crate::proof::Proof;
+use crate::public_output::PublicOutput;
 use crate::stark::Stark;
 use crate::stark::StarkParameters;
 use crate::table::master_table::MasterBaseTable;
+use crate::vm::error::VMError;
 use crate::vm::run;
 use crate::vm::simulate;
 use crate::vm::AlgebraicExecutionTrace;
@@ -33,13 +35,11 @@ pub fn parse_setup_simulate(
     let program = program.unwrap();
 
     prof_start!(maybe_profiler, "simulate");
-    let (aet, stdout, err) = simulate(&program, input_symbols, secret_input_symbols);
-    if let Some(error) = err {
-        panic!("The VM encountered the following problem: {}", error);
-    }
+    let (aet, stdout) = simulate(&program, input_symbols, secret_input_symbols)
+        .unwrap_or_else(|error| panic!("The VM encountered the following problem: {error}"));
     prof_stop!(maybe_profiler, "simulate");
 
-    (aet, stdout, program)
+    (aet, stdout.into(), program)
 }
 
 pub fn parse_simulate_prove(
@@ -60,7 +60,9 @@ pub fn parse_simulate_prove(
         input: input_symbols,
         program: program.to_bwords(),
         output: output_symbols,
+        output_tags: vec![],
         padded_height,
+        entry_point: program.entry_point,
     };
     let log_expansion_factor = 2;
     let security_level = 32;
@@ -75,6 +77,7 @@ pub fn parse_simulate_prove(
 }
 
 /// Source code and associated input. Primarily for testing of the VM's instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SourceCodeAndInput {
     pub source_code: String,
     pub input: Vec<BFieldElement>,
@@ -92,14 +95,12 @@ impl SourceCodeAndInput {
 
     pub fn run(&self) -> Vec<BFieldElement> {
         let program = Program::from_code(&self.source_code).expect("Could not load source code");
-        let (_, output, err) = run(&program, self.input.clone(), self.secret_input.clone());
-        if let Some(e) = err {
-            panic!("Running the program failed: {}", e)
-        }
-        output
+        let (_, output) = run(&program, self.input.clone(), self.secret_input.clone())
+            .unwrap_or_else(|e| panic!("Running the program failed: {e}"));
+        output.into()
     }
 
-    pub fn simulate(&self) -> (AlgebraicExecutionTrace, Vec<BFieldElement>, Option<Error>) {
+    pub fn simulate(&self) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
         let program = Program::from_code(&self.source_code).expect("Could not load source code.");
         simulate(&program, self.input.clone(), self.secret_input.clone())
     }
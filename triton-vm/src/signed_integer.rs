@@ -0,0 +1,252 @@
+//! The encoding of signed integers used by the `_i32` and `_i64` pseudo-instructions.
+//!
+//! An `i32` is represented by the single field element holding its two's-complement bit pattern,
+//! i.e., the `u32` with the same bits. Non-negative values are thus encoded as themselves, and
+//! `-1` is encoded as `4294967295`. Division truncates towards zero and wraps like Rust's
+//! [`i32::wrapping_div`], meaning `i32::MIN / -1` results in `i32::MIN`.
+//!
+//! An `i64` does not fit into a single field element. It is represented by two field elements,
+//! the high and the low `u32` limb of its two's-complement bit pattern. On the stack, the low limb
+//! is on top, i.e., pushing an `i64` means pushing its high limb first. Division behaves like
+//! [`i64::wrapping_div`].
+
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+/// The field element encoding the given `i32`.
+pub fn i32_to_bfe(value: i32) -> BFieldElement {
+    BFieldElement::new(value as u32 as u64)
+}
+
+/// The `i32` encoded by the given field element, or `None` if it is not a valid encoding.
+pub fn bfe_to_i32(element: BFieldElement) -> Option<i32> {
+    u32::try_from(element.value())
+        .ok()
+        .map(|value| value as i32)
+}
+
+/// The field elements encoding the given `i64`, high limb first.
+pub fn i64_to_bfes(value: i64) -> [BFieldElement; 2] {
+    let bits = value as u64;
+    [
+        BFieldElement::new(bits >> 32),
+        BFieldElement::new(bits & 0xffff_ffff),
+    ]
+}
+
+/// The `i64` encoded by the given field elements, high limb first, or `None` if they are not a
+/// valid encoding.
+pub fn bfes_to_i64(limbs: [BFieldElement; 2]) -> Option<i64> {
+    let [hi, lo] = limbs.map(|limb| u32::try_from(limb.value()).ok());
+    Some(((hi? as u64) << 32 | lo? as u64) as i64)
+}
+
+#[cfg(test)]
+mod signed_integer_tests {
+    use itertools::Itertools;
+    use rand::thread_rng;
+    use rand::Rng;
+
+    use triton_opcodes::instruction::DivinationHint;
+    use triton_opcodes::program::Program;
+
+    use crate::op_stack::OpStack;
+    use crate::shared_tests::SourceCodeAndInput;
+    use crate::vm::error::InstructionError::AssertionFailed;
+    use crate::vm::simulate;
+    use crate::vm::stream::Diviner;
+    use crate::vm::stream::WithDiviner;
+
+    use super::*;
+
+    fn interesting_values() -> Vec<i32> {
+        let mut rng = thread_rng();
+        let mut values = vec![
+            i32::MIN,
+            i32::MIN + 1,
+            -2,
+            -1,
+            0,
+            1,
+            2,
+            i32::MAX - 1,
+            i32::MAX,
+        ];
+        values.extend((0..8).map(|_| rng.gen::<i32>()));
+        values.extend((0..4).map(|_| rng.gen_range(-100..100)));
+        values
+    }
+
+    /// Runs `push b push a` followed by the given instructions, decoding the output.
+    fn run_binary_op(instructions: &str, b: i32, a: i32) -> Vec<i32> {
+        let source_code = format!(
+            "push {} push {} {instructions} halt",
+            i32_to_bfe(b),
+            i32_to_bfe(a),
+        );
+        SourceCodeAndInput::without_input(&source_code)
+            .run()
+            .into_iter()
+            .map(|element| bfe_to_i32(element).unwrap())
+            .collect()
+    }
+
+    fn interesting_i64_values() -> Vec<i64> {
+        let mut rng = thread_rng();
+        let mut values = vec![
+            i64::MIN,
+            i64::MIN + 1,
+            i32::MIN as i64 - 1,
+            -(1 << 32),
+            -2,
+            -1,
+            0,
+            1,
+            2,
+            u32::MAX as i64,
+            1 << 32,
+            i64::MAX - 1,
+            i64::MAX,
+        ];
+        values.extend((0..6).map(|_| rng.gen::<i64>()));
+        values.extend((0..4).map(|_| rng.gen::<i32>() as i64));
+        values
+    }
+
+    /// Like [`run_binary_op`], but for `i64`s. Decodes the output as `i64`s, too, except for a
+    /// single output word, which is a boolean.
+    fn run_binary_op_i64(instructions: &str, b: i64, a: i64) -> Vec<i64> {
+        let [b_hi, b_lo] = i64_to_bfes(b);
+        let [a_hi, a_lo] = i64_to_bfes(a);
+        let source_code =
+            format!("push {b_hi} push {b_lo} push {a_hi} push {a_lo} {instructions} halt");
+        let output = SourceCodeAndInput::without_input(&source_code).run();
+        if output.len() == 1 {
+            return vec![output[0].value() as i64];
+        }
+        output
+            .chunks_exact(2)
+            .map(|limbs| bfes_to_i64([limbs[1], limbs[0]]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn encoding_roundtrip_test() {
+        for value in interesting_values() {
+            assert_eq!(Some(value), bfe_to_i32(i32_to_bfe(value)));
+        }
+        assert_eq!(BFieldElement::new(u32::MAX as u64), i32_to_bfe(-1));
+        assert_eq!(None, bfe_to_i32(BFieldElement::new(1 << 32)));
+    }
+
+    #[test]
+    fn i64_encoding_roundtrip_test() {
+        for value in interesting_i64_values() {
+            assert_eq!(Some(value), bfes_to_i64(i64_to_bfes(value)));
+        }
+        let max_limb = BFieldElement::new(u32::MAX as u64);
+        assert_eq!([max_limb, max_limb], i64_to_bfes(-1));
+        assert_eq!(
+            None,
+            bfes_to_i64([BFieldElement::new(0), BFieldElement::new(1 << 32)])
+        );
+    }
+
+    #[test]
+    fn lt_i32_and_lte_i32_agree_with_rust_test() {
+        let values = interesting_values();
+        for (&b, &a) in values.iter().cartesian_product(values.iter()) {
+            let lt = run_binary_op("lt_i32 write_io", b, a)[0];
+            assert_eq!(a < b, lt == 1, "{a} < {b}");
+            let lte = run_binary_op("lte_i32 write_io", b, a)[0];
+            assert_eq!(a <= b, lte == 1, "{a} <= {b}");
+        }
+    }
+
+    #[test]
+    fn div_i32_agrees_with_rust_test() {
+        let values = interesting_values();
+        for (&d, &n) in values.iter().cartesian_product(values.iter()) {
+            if d == 0 {
+                continue;
+            }
+            let output = run_binary_op("div_i32 write_io write_io", d, n);
+            assert_eq!(n.wrapping_rem(d), output[0], "{n} % {d}");
+            assert_eq!(n.wrapping_div(d), output[1], "{n} / {d}");
+        }
+    }
+
+    #[test]
+    fn lt_i64_and_lte_i64_agree_with_rust_test() {
+        let values = interesting_i64_values();
+        for (&b, &a) in values.iter().cartesian_product(values.iter()) {
+            let lt = run_binary_op_i64("lt_i64 write_io", b, a)[0];
+            assert_eq!(a < b, lt == 1, "{a} < {b}");
+            let lte = run_binary_op_i64("lte_i64 write_io", b, a)[0];
+            assert_eq!(a <= b, lte == 1, "{a} <= {b}");
+        }
+    }
+
+    #[test]
+    fn div_i64_agrees_with_rust_test() {
+        let values = interesting_i64_values();
+        for (&d, &n) in values.iter().cartesian_product(values.iter()) {
+            if d == 0 {
+                continue;
+            }
+            let output = run_binary_op_i64("div_i64 write_io write_io write_io write_io", d, n);
+            assert_eq!(n.wrapping_rem(d), output[0], "{n} % {d}");
+            assert_eq!(n.wrapping_div(d), output[1], "{n} / {d}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn div_i64_by_zero_crashes_test() {
+        run_binary_op_i64("div_i64", 0, 1);
+    }
+
+    /// Claims that 7 / 2 is 2 with remainder 5.
+    struct DishonestDiviner;
+
+    impl Diviner for DishonestDiviner {
+        fn divine_hinted(
+            &mut self,
+            hint: DivinationHint,
+            _op_stack: &OpStack,
+        ) -> Option<BFieldElement> {
+            match hint {
+                DivinationHint::QuotientU64Lo => Some(BFieldElement::new(2)),
+                DivinationHint::RemainderU64Lo => Some(BFieldElement::new(5)),
+                _ => Some(BFieldElement::new(0)),
+            }
+        }
+    }
+
+    #[test]
+    fn div_i64_rejects_dishonest_divination_test() {
+        let program = Program::from_code("push 0 push 2 push 0 push 7 div_i64 halt").unwrap();
+        let secret_in = WithDiviner::new(vec![], DishonestDiviner);
+        let err = simulate(&program, vec![], secret_in).expect_err("division must be checked");
+        assert!(matches!(err.kind, AssertionFailed(_)));
+    }
+
+    #[test]
+    #[should_panic(expected = "st0 must be 1.")]
+    fn lt_i64_rejects_non_u32_limb_test() {
+        let source_code = "push 0 push 0 push 0 push 4294967296 lt_i64 halt";
+        SourceCodeAndInput::without_input(source_code).run();
+    }
+
+    #[test]
+    #[should_panic]
+    fn div_i32_by_zero_crashes_test() {
+        run_binary_op("div_i32", 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "st0 must be 1.")]
+    fn lt_i32_rejects_non_u32_operand_test() {
+        let source_code = "push 0 push 4294967296 lt_i32 halt";
+        SourceCodeAndInput::without_input(source_code).run();
+    }
+}
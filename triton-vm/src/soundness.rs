@@ -0,0 +1,205 @@
+//! The concrete soundness of a [`Stark`], computed from its actual parameters and domain sizes
+//! instead of the targeted security level, for auditors confirming the margin of a deployment.
+//!
+//! All numbers are in bits, _i.e._, a soundness error of 2^(-λ) is reported as λ. They assume the
+//! conjectured soundness of FRI: every query of a codeword that is far from any low-degree codeword
+//! goes undetected with probability at most the rate, the inverse of the FRI expansion factor.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use anyhow::Result;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::x_field_element::EXTENSION_DEGREE;
+
+use crate::proof::Proof;
+use crate::stark::Stark;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundnessReport {
+    /// The security level the parameters were derived from.
+    pub target_security_level: usize,
+    pub fri_domain_length: usize,
+    pub fri_expansion_factor: usize,
+    pub num_fri_rounds: usize,
+
+    /// The number of colinearity checks per FRI round. The queried indices are always distinct.
+    pub num_fri_queries: usize,
+
+    /// The number of indices at which the combination codeword is checked against the revealed
+    /// rows.
+    pub num_combination_queries: usize,
+
+    /// The number of distinct indices among the combination queries. Indices are sampled
+    /// independently, so some might coincide. A repeated query adds nothing to soundness.
+    pub num_distinct_combination_queries: usize,
+
+    /// The security contributed by the FRI queries.
+    pub fri_query_security: f64,
+
+    /// The security contributed by the distinct combination queries.
+    pub combination_query_security: f64,
+
+    /// The security against unlucky challenges – the FRI folding challenges and the weights of
+    /// the nonlinear combination – drawn from the extension field.
+    pub challenge_security: f64,
+
+    /// The concrete security, combining all of the above.
+    pub security: f64,
+}
+
+impl SoundnessReport {
+    /// The soundness of the given `Stark`, assuming all combination queries are distinct.
+    pub fn new(stark: &Stark) -> Self {
+        let num_combination_queries = stark.parameters.num_non_linear_codeword_checks;
+        Self::with_distinct_combination_queries(stark, num_combination_queries)
+    }
+
+    /// Verify the given proof and report the soundness of the verification, accounting for the
+    /// combination queries the verifier actually sampled. For proofs the verifier rejects with an
+    /// error, the error is returned instead.
+    pub fn measure(stark: &Stark, proof: Proof) -> Result<(bool, Self)> {
+        let revealed_indices = stark.verify_revealing_indices(proof, &mut None)?;
+        let num_distinct_indices = revealed_indices.iter().collect::<HashSet<_>>().len();
+        let report = Self::with_distinct_combination_queries(stark, num_distinct_indices);
+        Ok((true, report))
+    }
+
+    fn with_distinct_combination_queries(
+        stark: &Stark,
+        num_distinct_combination_queries: usize,
+    ) -> Self {
+        let parameters = stark.parameters;
+        let fri_domain_length = stark.fri.domain.length;
+        let (num_fri_rounds, _) = stark.fri.num_rounds();
+        let num_fri_rounds = num_fri_rounds as usize;
+
+        let security_per_query = (parameters.fri_expansion_factor as f64).log2();
+        let fri_query_security = parameters.num_colinearity_checks as f64 * security_per_query;
+        let combination_query_security =
+            num_distinct_combination_queries as f64 * security_per_query;
+
+        // Every challenge is unlucky with probability at most (domain length) / (field size).
+        let extension_field_bits =
+            EXTENSION_DEGREE as f64 * (BFieldElement::QUOTIENT as f64).log2();
+        let num_challenges = (num_fri_rounds + 1) as f64;
+        let challenge_security =
+            extension_field_bits - (fri_domain_length as f64).log2() - num_challenges.log2();
+
+        let soundness_error = [
+            fri_query_security,
+            combination_query_security,
+            challenge_security,
+        ]
+        .map(|security| (-security).exp2())
+        .iter()
+        .sum::<f64>();
+
+        Self {
+            target_security_level: parameters.security_level,
+            fri_domain_length,
+            fri_expansion_factor: parameters.fri_expansion_factor,
+            num_fri_rounds,
+            num_fri_queries: parameters.num_colinearity_checks,
+            num_combination_queries: parameters.num_non_linear_codeword_checks,
+            num_distinct_combination_queries,
+            fri_query_security,
+            combination_query_security,
+            challenge_security,
+            security: -soundness_error.log2(),
+        }
+    }
+
+    /// The concrete security minus the targeted security level. Negative if the target is missed.
+    pub fn margin(&self) -> f64 {
+        self.security - self.target_security_level as f64
+    }
+
+    pub fn meets_target(&self) -> bool {
+        self.margin() >= 0.0
+    }
+}
+
+impl Display for SoundnessReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "FRI domain length:    {}", self.fri_domain_length)?;
+        writeln!(f, "FRI expansion factor: {}", self.fri_expansion_factor)?;
+        writeln!(f, "FRI rounds:           {}", self.num_fri_rounds)?;
+        writeln!(
+            f,
+            "FRI queries:          {} ({:.1} bits)",
+            self.num_fri_queries, self.fri_query_security
+        )?;
+        writeln!(
+            f,
+            "Combination queries:  {} of {} distinct ({:.1} bits)",
+            self.num_distinct_combination_queries,
+            self.num_combination_queries,
+            self.combination_query_security
+        )?;
+        writeln!(
+            f,
+            "Challenges:           {:.1} bits",
+            self.challenge_security
+        )?;
+        write!(
+            f,
+            "Security:             {:.1} bits (target {}, margin {:+.1})",
+            self.security,
+            self.target_security_level,
+            self.margin()
+        )
+    }
+}
+
+#[cfg(test)]
+mod soundness_tests {
+    use crate::proof::Claim;
+    use crate::shared_tests::parse_simulate_prove;
+    use crate::stark::StarkParameters;
+    use crate::test_corpus::test_hash_nop_nop_lt;
+
+    use super::*;
+
+    #[test]
+    fn measured_soundness_is_at_most_parameterized_soundness_test() {
+        let code_with_input = test_hash_nop_nop_lt();
+        let (stark, proof) = parse_simulate_prove(
+            &code_with_input.source_code,
+            code_with_input.input.clone(),
+            code_with_input.secret_input.clone(),
+            &mut None,
+        );
+
+        let report = SoundnessReport::new(&stark);
+        let (verdict, measured_report) = SoundnessReport::measure(&stark, proof).unwrap();
+        println!("{measured_report}");
+        assert!(verdict);
+        assert!(measured_report.num_distinct_combination_queries > 0);
+        assert!(measured_report.security <= report.security);
+        assert_eq!(
+            report.fri_query_security,
+            measured_report.fri_query_security
+        );
+        assert!(report.security <= report.fri_query_security);
+    }
+
+    #[test]
+    fn default_parameters_are_close_to_their_target_test() {
+        let claim = Claim {
+            input: vec![],
+            program: vec![],
+            output: vec![],
+            output_tags: vec![],
+            padded_height: 1 << 10,
+            entry_point: 0,
+        };
+        let stark = Stark::new(claim, StarkParameters::default());
+        let report = SoundnessReport::new(&stark);
+        println!("{report}");
+
+        assert!(report.fri_query_security >= report.target_security_level as f64);
+        assert!(report.challenge_security > report.security);
+        assert!(report.margin() > -1.0);
+    }
+}
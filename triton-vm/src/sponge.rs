@@ -0,0 +1,126 @@
+//! The running sponge of the `sponge_*` pseudo-instructions, which derive pseudo-random field
+//! elements inside the VM, _e.g._, for Fiat-Shamir in a verifier running in Triton VM.
+//!
+//! The sponge's state is a digest living on top of the op stack. `sponge_init` pushes the all-zero
+//! state. `sponge_absorb` replaces the state `s` and the five elements `a` below it by
+//! `hash(s ‖ a)`. `sponge_squeeze` replaces the state `s` by `s' = hash(0⁵ ‖ s)` and puts a copy of
+//! `s'` below it, to be used as pseudo-random elements. Here, `hash` is the hash function of
+//! instruction `hash`, and element 0 of a digest is the one closest to the top of the stack.
+//!
+//! The [`Sponge`] computes the same values outside of the VM.
+
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+
+/// Mirrors the sponge of the `sponge_*` pseudo-instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sponge {
+    pub state: [BFieldElement; DIGEST_LENGTH],
+}
+
+impl Sponge {
+    /// Like `sponge_init`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like `sponge_absorb`.
+    pub fn absorb(&mut self, elements: [BFieldElement; DIGEST_LENGTH]) {
+        let hash_input = [self.state, elements].concat();
+        self.state = RescuePrimeRegular::hash_10(&hash_input.try_into().unwrap());
+    }
+
+    /// Absorb any number of elements, five at a time. The last chunk is padded with zeros. Like a
+    /// sequence of `sponge_absorb`s.
+    pub fn absorb_all(&mut self, elements: &[BFieldElement]) {
+        for chunk in elements.chunks(DIGEST_LENGTH) {
+            let mut padded_chunk = [BFieldElement::new(0); DIGEST_LENGTH];
+            padded_chunk[..chunk.len()].copy_from_slice(chunk);
+            self.absorb(padded_chunk);
+        }
+    }
+
+    /// Like `sponge_squeeze`. Element 0 of the result ends up closest to the top of the stack.
+    pub fn squeeze(&mut self) -> [BFieldElement; DIGEST_LENGTH] {
+        let hash_input = [[BFieldElement::new(0); DIGEST_LENGTH], self.state].concat();
+        self.state = RescuePrimeRegular::hash_10(&hash_input.try_into().unwrap());
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod sponge_tests {
+    use itertools::Itertools;
+    use twenty_first::shared_math::other::random_elements;
+
+    use crate::shared_tests::SourceCodeAndInput;
+
+    use super::*;
+
+    /// Pushes the elements such that element 0 ends up on top.
+    fn push_all(elements: &[BFieldElement]) -> String {
+        elements.iter().rev().map(|e| format!("push {e}")).join(" ")
+    }
+
+    fn write_digest() -> &'static str {
+        "write_io write_io write_io write_io write_io"
+    }
+
+    /// Swaps the top two digests on the stack.
+    fn swap_digests() -> &'static str {
+        "swap5 swap1 swap6 swap1 swap2 swap7 swap2 swap3 swap8 swap3 swap4 swap9 swap4"
+    }
+
+    #[test]
+    fn vm_sponge_agrees_with_rust_sponge_test() {
+        let absorbed: Vec<BFieldElement> = random_elements(3 * DIGEST_LENGTH);
+        let mut sponge = Sponge::new();
+        let mut expected_output = vec![];
+        let mut source_code = "sponge_init".to_string();
+        for chunk in absorbed.chunks(DIGEST_LENGTH) {
+            sponge.absorb(chunk.try_into().unwrap());
+            expected_output.extend(sponge.squeeze());
+            source_code.push_str(&format!(
+                " {push} {swap} sponge_absorb sponge_squeeze {swap} {write}",
+                push = push_all(chunk),
+                swap = swap_digests(),
+                write = write_digest(),
+            ));
+        }
+        source_code.push_str(" halt");
+
+        let output = SourceCodeAndInput::without_input(&source_code).run();
+        assert_eq!(expected_output, output);
+    }
+
+    #[test]
+    fn squeezing_twice_gives_different_elements_test() {
+        let mut sponge = Sponge::new();
+        let first = sponge.squeeze();
+        let second = sponge.squeeze();
+        assert_ne!(first, second);
+
+        let source_code = format!(
+            "sponge_init sponge_squeeze {swap} {write} sponge_squeeze {swap} {write} halt",
+            swap = swap_digests(),
+            write = write_digest(),
+        );
+        let output = SourceCodeAndInput::without_input(&source_code).run();
+        assert_eq!([first, second].concat(), output);
+    }
+
+    #[test]
+    fn absorb_all_pads_with_zeros_test() {
+        let elements: Vec<BFieldElement> = random_elements(7);
+        let mut sponge = Sponge::new();
+        sponge.absorb_all(&elements);
+
+        let mut padded_elements = elements;
+        padded_elements.resize(2 * DIGEST_LENGTH, BFieldElement::new(0));
+        let mut manual_sponge = Sponge::new();
+        manual_sponge.absorb(padded_elements[..DIGEST_LENGTH].try_into().unwrap());
+        manual_sponge.absorb(padded_elements[DIGEST_LENGTH..].try_into().unwrap());
+        assert_eq!(manual_sponge, sponge);
+    }
+}
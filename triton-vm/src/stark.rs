@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::error::Error;
 use std::fmt;
 use std::ops::Add;
@@ -11,10 +12,13 @@ use ndarray::s;
 use ndarray::Array1;
 use ndarray::ArrayBase;
 use ndarray::ArrayView2;
-use ndarray::Zip;
 use num_traits::One;
 use num_traits::Zero;
 use rayon::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+use triton_opcodes::program::Program;
 use triton_profiler::prof_itr0;
 use triton_profiler::prof_start;
 use triton_profiler::prof_stop;
@@ -25,7 +29,6 @@ use twenty_first::shared_math::other::is_power_of_two;
 use twenty_first::shared_math::other::roundup_npo2;
 use twenty_first::shared_math::rescue_prime_digest::Digest;
 use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
-use twenty_first::shared_math::traits::FiniteField;
 use twenty_first::shared_math::traits::Inverse;
 use twenty_first::shared_math::traits::ModPowU32;
 use twenty_first::shared_math::x_field_element::XFieldElement;
@@ -34,21 +37,82 @@ use twenty_first::util_types::merkle_tree::CpuParallel;
 use twenty_first::util_types::merkle_tree::MerkleTree;
 use twenty_first::util_types::merkle_tree_maker::MerkleTreeMaker;
 
+use crate::analysis::table_heights::PaddingReport;
 use crate::arithmetic_domain::ArithmeticDomain;
 use crate::fri::Fri;
 use crate::fri::FriValidationError;
 use crate::proof::Claim;
 use crate::proof::Proof;
 use crate::proof_item::ProofItem;
+use crate::proof_stream::ChallengeChannel;
 use crate::proof_stream::ProofStream;
+use crate::prover_telemetry::ProverTelemetry;
 use crate::table::challenges::AllChallenges;
 use crate::table::master_table::*;
+use crate::vm::simulate;
 use crate::vm::AlgebraicExecutionTrace;
+use crate::x_field_ops::add_weighted_shifted_codeword;
+use crate::x_field_ops::constant_time_eq;
+use crate::x_field_ops::inner_product;
 
 pub type StarkHasher = RescuePrimeRegular;
 pub type Maker = CpuParallel;
 pub type StarkProofStream = ProofStream<ProofItem, StarkHasher>;
 
+/// A well-known set of [`StarkParameters`], accessible by name through
+/// [`StarkParameters::named`]. Published profiles never change; changing the parameters of a
+/// profile means publishing a new version of it. All parameters are pinned explicitly, such that
+/// changes to [`StarkParameters::new`] cannot change a published profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterProfile {
+    pub name: &'static str,
+    pub version: u32,
+    pub parameters: StarkParameters,
+}
+
+pub const PARAMETER_PROFILES: [ParameterProfile; 3] = [
+    ParameterProfile {
+        name: "triton-2022-testnet",
+        version: 1,
+        parameters: StarkParameters {
+            security_level: 160,
+            fri_expansion_factor: 4,
+            num_trace_randomizers: 160,
+            num_randomizer_polynomials: 1,
+            num_colinearity_checks: 80,
+            num_non_linear_codeword_checks: 160,
+            fri_domain_offset: BFieldElement::new(7),
+        },
+    },
+    ParameterProfile {
+        name: "high-security",
+        version: 1,
+        parameters: StarkParameters {
+            security_level: 256,
+            fri_expansion_factor: 8,
+            num_trace_randomizers: 170,
+            num_randomizer_polynomials: 1,
+            num_colinearity_checks: 85,
+            num_non_linear_codeword_checks: 256,
+            fri_domain_offset: BFieldElement::new(7),
+        },
+    },
+    ParameterProfile {
+        name: "fast-dev",
+        version: 1,
+        parameters: StarkParameters {
+            security_level: 32,
+            fri_expansion_factor: 4,
+            num_trace_randomizers: 32,
+            num_randomizer_polynomials: 1,
+            num_colinearity_checks: 16,
+            num_non_linear_codeword_checks: 32,
+            fri_domain_offset: BFieldElement::new(7),
+        },
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StarkParameters {
     pub security_level: usize,
     pub fri_expansion_factor: usize,
@@ -56,6 +120,11 @@ pub struct StarkParameters {
     pub num_randomizer_polynomials: usize,
     pub num_colinearity_checks: usize,
     pub num_non_linear_codeword_checks: usize,
+
+    /// The offset of the coset that is the FRI domain, and thus the domain of all low-degree
+    /// extensions. Must not lie in any subgroup of power-of-two order, see
+    /// [`with_fri_domain_offset`](Self::with_fri_domain_offset).
+    fri_domain_offset: BFieldElement,
 }
 
 impl StarkParameters {
@@ -83,23 +152,102 @@ impl StarkParameters {
         let num_trace_randomizers = num_colinearity_checks * 2;
         let num_non_linear_codeword_checks = security_level;
 
-        StarkParameters {
+        let parameters = StarkParameters {
             security_level,
             fri_expansion_factor,
             num_trace_randomizers,
             num_randomizer_polynomials,
             num_colinearity_checks,
             num_non_linear_codeword_checks,
+            fri_domain_offset: BFieldElement::generator(),
+        };
+        parameters
+            .validate_fri_domain_offset()
+            .expect("The default FRI domain offset must be valid.");
+        parameters
+    }
+
+    pub fn fri_domain_offset(&self) -> BFieldElement {
+        self.fri_domain_offset
+    }
+
+    /// Use the coset with the given offset as the FRI domain, for interoperability with tooling
+    /// that expects a specific coset. Fails if the coset is not disjoint from the trace domain.
+    pub fn with_fri_domain_offset(mut self, fri_domain_offset: BFieldElement) -> Result<Self> {
+        self.fri_domain_offset = fri_domain_offset;
+        self.validate_fri_domain_offset()?;
+        Ok(self)
+    }
+
+    /// Check that the FRI domain is disjoint from the trace domain, as well as from every other
+    /// subgroup of power-of-two order, irrespective of the padded height. Otherwise, the
+    /// zerofiers of the AIR constraints vanish on some point of the FRI domain.
+    ///
+    /// The multiplicative group of the base field has a unique subgroup of order 2^32, which
+    /// contains all subgroups of power-of-two order. The coset `offset·H` of such a subgroup `H`
+    /// is disjoint from all of them if and only if `offset` is not in the subgroup of order 2^32.
+    pub fn validate_fri_domain_offset(&self) -> Result<()> {
+        let offset = self.fri_domain_offset;
+        if offset.is_zero() {
+            bail!("The FRI domain offset must not be zero.");
+        }
+        if offset.mod_pow(1 << 32).is_one() {
+            bail!(
+                "The FRI domain offset {offset} lies in a subgroup of power-of-two order. \
+                The FRI domain would intersect the trace domain."
+            );
+        }
+        Ok(())
+    }
+
+    /// The parameters of a [well-known profile](PARAMETER_PROFILES). A profile is referenced
+    /// either by name, resolving to its latest version, or by name and version, like
+    /// `fast-dev@1`.
+    pub fn named(name: &str) -> Result<Self> {
+        let (name, version) = match name.split_once('@') {
+            Some((name, version)) => match version.parse::<u32>() {
+                Ok(version) => (name, Some(version)),
+                Err(_) => bail!("Malformed version of parameter profile `{name}`: `{version}`."),
+            },
+            None => (name, None),
+        };
+        let profile = PARAMETER_PROFILES
+            .iter()
+            .filter(|profile| profile.name == name)
+            .filter(|profile| version.is_none() || Some(profile.version) == version)
+            .max_by_key(|profile| profile.version);
+        match profile {
+            Some(profile) => Ok(profile.parameters),
+            None => bail!(
+                "Unknown parameter profile `{name}`. Known profiles are: {}.",
+                PARAMETER_PROFILES
+                    .iter()
+                    .map(|profile| format!("{}@{}", profile.name, profile.version))
+                    .join(", ")
+            ),
         }
     }
+
+    /// Commits to all parameters. Included in every proof, so that a proof can only be verified
+    /// with the exact parameters it was generated with.
+    pub fn digest(&self) -> Digest {
+        let parameters = [
+            self.security_level,
+            self.fri_expansion_factor,
+            self.num_trace_randomizers,
+            self.num_randomizer_polynomials,
+            self.num_colinearity_checks,
+            self.num_non_linear_codeword_checks,
+        ]
+        .map(|parameter| BFieldElement::new(parameter as u64));
+        let parameters = [parameters.as_slice(), &[self.fri_domain_offset]].concat();
+        StarkHasher::hash_slice(&parameters)
+    }
 }
 
 impl Default for StarkParameters {
     fn default() -> Self {
-        let fri_expansion_factor = 4;
-        let security_level = 160;
-
-        Self::new(security_level, fri_expansion_factor)
+        Self::named("triton-2022-testnet").unwrap()
     }
 }
 
@@ -107,6 +255,10 @@ impl Default for StarkParameters {
 pub enum StarkValidationError {
     CombinationLeafInequality,
     PaddedHeightInequality,
+    ParametersDigestInequality,
+    InvalidEmptyTables,
+    TrailingProofItems,
+    OutputTagsLengthMismatch,
     FriValidationError(FriValidationError),
 }
 
@@ -118,12 +270,96 @@ impl fmt::Display for StarkValidationError {
     }
 }
 
+/// The constraints that a developer-mode [`Stark`] proves and verifies, see
+/// [`Stark::with_constraint_selection`]. By default, all constraints are selected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintSelection {
+    tables: HashSet<TableId>,
+    grand_cross_table_argument: bool,
+}
+
+impl Default for ConstraintSelection {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl ConstraintSelection {
+    /// The constraints of all tables, and the Grand Cross-Table Argument.
+    pub fn all() -> Self {
+        Self {
+            tables: TableId::iter().collect(),
+            grand_cross_table_argument: true,
+        }
+    }
+
+    /// No constraints at all. Only the low degree of the base and extension columns is checked.
+    pub fn none() -> Self {
+        Self {
+            tables: HashSet::new(),
+            grand_cross_table_argument: false,
+        }
+    }
+
+    /// The constraints of the given tables, without the Grand Cross-Table Argument.
+    pub fn only(tables: &[TableId]) -> Self {
+        Self {
+            tables: tables.iter().copied().collect(),
+            grand_cross_table_argument: false,
+        }
+    }
+
+    pub fn with_table(mut self, table: TableId) -> Self {
+        self.tables.insert(table);
+        self
+    }
+
+    pub fn without_table(mut self, table: TableId) -> Self {
+        self.tables.remove(&table);
+        self
+    }
+
+    pub fn with_grand_cross_table_argument(mut self, selected: bool) -> Self {
+        self.grand_cross_table_argument = selected;
+        self
+    }
+
+    pub fn is_all(&self) -> bool {
+        *self == Self::all()
+    }
+
+    fn is_selected(&self, quotient_origin: Option<TableId>) -> bool {
+        match quotient_origin {
+            Some(table) => self.tables.contains(&table),
+            None => self.grand_cross_table_argument,
+        }
+    }
+
+    /// Zero the weights of the nonlinear combination that belong to deselected quotients.
+    fn mask_weights(&self, non_lin_combi_weights: &mut [XFieldElement]) {
+        if self.is_all() {
+            return;
+        }
+        let quotient_weights_start = 2 * (NUM_BASE_COLUMNS + NUM_EXT_COLUMNS);
+        let quotient_weights = &mut non_lin_combi_weights[quotient_weights_start..];
+        for (weights, origin) in quotient_weights
+            .chunks_exact_mut(2)
+            .zip_eq(all_quotient_origins())
+        {
+            if !self.is_selected(origin) {
+                weights.fill(XFieldElement::zero());
+            }
+        }
+    }
+}
+
 pub struct Stark {
     pub parameters: StarkParameters,
     pub claim: Claim,
     pub max_degree: Degree,
     pub interpolant_degree: Degree,
     pub fri: Fri<StarkHasher>,
+    pub constraint_selection: ConstraintSelection,
 }
 
 impl Stark {
@@ -134,9 +370,8 @@ impl Stark {
             max_degree_with_origin(interpolant_degree, claim.padded_height);
         let max_degree = (roundup_npo2(max_degree_with_origin.degree as u64) - 1) as Degree;
         let fri_domain_length = parameters.fri_expansion_factor * (max_degree as usize + 1);
-        let coset_offset = BFieldElement::generator();
         let fri = Fri::new(
-            coset_offset,
+            parameters.fri_domain_offset,
             fri_domain_length,
             parameters.fri_expansion_factor,
             parameters.num_colinearity_checks,
@@ -147,14 +382,124 @@ impl Stark {
             max_degree,
             interpolant_degree,
             fri,
+            constraint_selection: ConstraintSelection::default(),
         }
     }
 
+    /// Make the verifier use constant-time field operations where available, for embedding
+    /// verification in side-channel-conscious contexts. Verification becomes slightly slower.
+    ///
+    /// Concretely, extension field inversions and the comparisons of revealed values in FRI and
+    /// the nonlinear combination are constant-time. Base field arithmetic is constant-time
+    /// regardless. The authentication of Merkle paths is not, since it is implemented by
+    /// `twenty-first`. Everything the verifier processes is public; the mode is about not leaking
+    /// where, for example, a malformed proof is rejected.
+    pub fn with_constant_time_verification(mut self) -> Self {
+        self.fri.constant_time = true;
+        self
+    }
+
+    /// Developer mode: only prove and verify the selected constraints, to bisect which table or
+    /// argument rejects a proof while iterating on table changes. The weights of deselected
+    /// constraints in the nonlinear combination are zero, so prover and verifier must use the
+    /// same selection. A proof generated with anything but [`ConstraintSelection::all`] does
+    /// not verify with a regular `Stark`, and vice versa. Never use this in production: a proof
+    /// that leaves out constraints proves nothing about them.
+    pub fn with_constraint_selection(mut self, constraint_selection: ConstraintSelection) -> Self {
+        self.constraint_selection = constraint_selection;
+        self
+    }
+
     pub fn prove(
         &self,
         aet: AlgebraicExecutionTrace,
         maybe_profiler: &mut Option<TritonProfiler>,
     ) -> Proof {
+        self.prove_to_proof_stream(aet, maybe_profiler).to_proof()
+    }
+
+    /// Like [`prove`](Self::prove), but additionally report how the tables were padded, which
+    /// shows whether a slightly shorter execution would halve the proving cost.
+    pub fn prove_with_padding_report(
+        &self,
+        aet: AlgebraicExecutionTrace,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> (Proof, PaddingReport) {
+        let padding_report = PaddingReport::new(&aet, &self.claim.program);
+        (self.prove(aet, maybe_profiler), padding_report)
+    }
+
+    /// Like [`prove`](Self::prove), but additionally record the proof's [`ProverTelemetry`],
+    /// for aggregating capacity data across many proofs. The prover's phases are timed with an
+    /// internal profiler.
+    pub fn prove_with_telemetry(&self, aet: AlgebraicExecutionTrace) -> (Proof, ProverTelemetry) {
+        ProverTelemetry::measure(self, aet)
+    }
+
+    /// Like [`prove`](Self::prove), but first [check the execution trace](Self::check_trace),
+    /// failing early if it violates a constraint.
+    pub fn prove_checked(
+        &self,
+        aet: AlgebraicExecutionTrace,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<Proof> {
+        prof_start!(maybe_profiler, "check trace");
+        self.check_trace(&aet)?;
+        prof_stop!(maybe_profiler, "check trace");
+        Ok(self.prove(aet, maybe_profiler))
+    }
+
+    /// A cheap pre-flight for proving: check the transition constraints referring to the base
+    /// table only on the padded execution trace, without any low-degree extension. Fails with
+    /// the violated constraint and row, see
+    /// [`MasterBaseTable::check_base_transition_constraints`]. A trace passing the check might
+    /// still be rejected by the verifier.
+    pub fn check_trace(&self, aet: &AlgebraicExecutionTrace) -> Result<()> {
+        let mut master_base_table = MasterBaseTable::new(
+            aet.clone(),
+            &self.claim.program,
+            self.parameters.num_trace_randomizers,
+            self.fri.domain,
+        );
+        master_base_table.pad();
+        master_base_table.check_base_transition_constraints()
+    }
+
+    /// Interactive mode: like [`prove`](Self::prove), but take all challenges from the given
+    /// channel instead of deriving them through Fiat-Shamir. The resulting proof only verifies
+    /// with [`verify_interactively`](Self::verify_interactively), given a channel that produces
+    /// the same challenges.
+    pub fn prove_interactively(
+        &self,
+        aet: AlgebraicExecutionTrace,
+        challenge_channel: Box<dyn ChallengeChannel>,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Proof {
+        let proof_stream = StarkProofStream::new().with_challenge_channel(challenge_channel);
+        self.prove_into_proof_stream(aet, proof_stream, maybe_profiler)
+            .to_proof()
+    }
+
+    /// Like [`prove`](Self::prove), but keeps the proof items typed.
+    pub(crate) fn prove_to_proof_stream(
+        &self,
+        aet: AlgebraicExecutionTrace,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> StarkProofStream {
+        self.prove_into_proof_stream(aet, StarkProofStream::new(), maybe_profiler)
+    }
+
+    fn prove_into_proof_stream(
+        &self,
+        aet: AlgebraicExecutionTrace,
+        mut proof_stream: StarkProofStream,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> StarkProofStream {
+        // Parameters can be deserialized without going through any validating constructor.
+        if let Err(err) = self.parameters.validate_fri_domain_offset() {
+            panic!("Cannot prove with invalid parameters: {err}");
+        }
+
         prof_start!(maybe_profiler, "base tables");
         prof_start!(maybe_profiler, "create");
         let mut master_base_table = MasterBaseTable::new(
@@ -181,8 +526,9 @@ impl Stark {
 
         prof_start!(maybe_profiler, "Fiat-Shamir");
         let padded_height = BFieldElement::new(master_base_table.padded_height as u64);
-        let mut proof_stream = StarkProofStream::new();
         proof_stream.enqueue(&ProofItem::PaddedHeight(padded_height));
+        proof_stream.enqueue(&ProofItem::ParametersDigest(self.parameters.digest()));
+        proof_stream.enqueue(&Self::empty_tables_item(&master_base_table.empty_tables));
         proof_stream.enqueue(&ProofItem::MerkleRoot(base_merkle_tree_root));
         let extension_weights = Self::sample_weights(
             proof_stream.prover_fiat_shamir(),
@@ -192,6 +538,8 @@ impl Stark {
             extension_weights,
             &self.claim.input,
             &self.claim.output,
+            &self.claim.output_tags,
+            self.claim.entry_point,
         );
         prof_stop!(maybe_profiler, "Fiat-Shamir");
 
@@ -216,69 +564,24 @@ impl Stark {
         prof_stop!(maybe_profiler, "Merkle tree");
         prof_stop!(maybe_profiler, "ext tables");
 
-        prof_start!(maybe_profiler, "quotient degree bounds");
-        let quotient_degree_bounds =
-            all_quotient_degree_bounds(self.interpolant_degree, master_base_table.padded_height);
-        prof_stop!(maybe_profiler, "quotient degree bounds");
-
-        prof_start!(maybe_profiler, "quotient-domain codewords");
-        let trace_domain = ArithmeticDomain::new_no_offset(master_base_table.padded_height);
-        let quotient_domain = self.quotient_domain();
-        let unit_distance = self.fri.domain.length / quotient_domain.length;
-        let base_quotient_domain_codewords = fri_domain_master_base_table
-            .master_base_matrix
-            .slice(s![..; unit_distance, ..]);
-        let extension_quotient_domain_codewords = fri_domain_ext_master_table
-            .master_ext_matrix
-            .slice(s![..; unit_distance, ..]);
-        prof_stop!(maybe_profiler, "quotient-domain codewords");
-
-        prof_start!(maybe_profiler, "quotient codewords");
-        let master_quotient_table = all_quotients(
-            base_quotient_domain_codewords,
-            extension_quotient_domain_codewords,
-            trace_domain,
-            quotient_domain,
-            &extension_challenges,
-            maybe_profiler,
-        );
-        prof_stop!(maybe_profiler, "quotient codewords");
-
-        // Get weights for nonlinear combination. Concretely, sample 2 weights for each base
-        // polynomial, each extension polynomial, and each quotient. The factor is 2 because
-        // transition constraints check 2 rows.
+        // Get weights for nonlinear combination.
         prof_start!(maybe_profiler, "Fiat-Shamir");
         let non_lin_combi_weights_seed = proof_stream.prover_fiat_shamir();
-        let num_non_lin_combi_weights =
-            2 * (NUM_BASE_COLUMNS + NUM_EXT_COLUMNS + num_all_table_quotients());
-        let non_lin_combi_weights =
-            Self::sample_weights(non_lin_combi_weights_seed, num_non_lin_combi_weights);
+        let mut non_lin_combi_weights = Self::sample_weights(
+            non_lin_combi_weights_seed,
+            Self::num_non_lin_combi_weights(),
+        );
+        self.constraint_selection
+            .mask_weights(&mut non_lin_combi_weights);
         prof_stop!(maybe_profiler, "Fiat-Shamir");
 
-        prof_start!(maybe_profiler, "nonlinear combination");
-        prof_start!(maybe_profiler, "create combination codeword");
-        let combination_codeword = self.create_combination_codeword(
-            quotient_domain,
-            base_quotient_domain_codewords,
-            extension_quotient_domain_codewords.slice(s![.., ..NUM_EXT_COLUMNS]),
-            master_quotient_table.view(),
+        let fri_combination_codeword = self.fri_combination_codeword(
+            &fri_domain_master_base_table,
+            &fri_domain_ext_master_table,
+            &extension_challenges,
             &non_lin_combi_weights,
-            quotient_degree_bounds,
-        );
-        prof_stop!(maybe_profiler, "create combination codeword");
-
-        prof_start!(maybe_profiler, "LDE 3");
-        let fri_combination_codeword_without_randomizer = Array1::from(
-            quotient_domain.low_degree_extension(&combination_codeword, self.fri.domain),
+            maybe_profiler,
         );
-        prof_stop!(maybe_profiler, "LDE 3");
-
-        let fri_combination_codeword = fri_domain_ext_master_table
-            .randomizer_polynomials()
-            .into_iter()
-            .fold(fri_combination_codeword_without_randomizer, ArrayBase::add)
-            .to_vec();
-        prof_stop!(maybe_profiler, "nonlinear combination");
 
         prof_start!(maybe_profiler, "Merkle tree 3");
         let combination_codeword_digests = fri_combination_codeword
@@ -318,19 +621,15 @@ impl Stark {
         let revealed_current_and_next_row_indices = self
             .revealed_current_and_next_row_indices(unit_distance, &revealed_current_row_indices);
 
-        let revealed_base_elems = Self::get_revealed_elements(
-            fri_domain_master_base_table.master_base_matrix.view(),
-            &revealed_current_and_next_row_indices,
-        );
+        let revealed_base_elems =
+            fri_domain_master_base_table.revealed_rows(&revealed_current_and_next_row_indices);
         let auth_paths_base =
             base_merkle_tree.get_authentication_structure(&revealed_current_and_next_row_indices);
         proof_stream.enqueue(&ProofItem::MasterBaseTableRows(revealed_base_elems));
         proof_stream.enqueue(&ProofItem::CompressedAuthenticationPaths(auth_paths_base));
 
-        let revealed_ext_elems = Self::get_revealed_elements(
-            fri_domain_ext_master_table.master_ext_matrix.view(),
-            &revealed_current_and_next_row_indices,
-        );
+        let revealed_ext_elems =
+            fri_domain_ext_master_table.revealed_rows(&revealed_current_and_next_row_indices);
         let auth_paths_ext =
             ext_merkle_tree.get_authentication_structure(&revealed_current_and_next_row_indices);
         proof_stream.enqueue(&ProofItem::MasterExtTableRows(revealed_ext_elems));
@@ -360,10 +659,84 @@ impl Stark {
             );
         }
 
-        proof_stream.to_proof()
+        proof_stream
+    }
+
+    /// The number of weights for the nonlinear combination. Concretely, 2 weights for each base
+    /// polynomial, each extension polynomial, and each quotient. The factor is 2 because
+    /// transition constraints check 2 rows.
+    pub(crate) fn num_non_lin_combi_weights() -> usize {
+        2 * (NUM_BASE_COLUMNS + NUM_EXT_COLUMNS + num_all_table_quotients())
+    }
+
+    /// The nonlinear combination of all base, extension, and quotient codewords, low-degree
+    /// extended to the FRI domain, with the randomizer codewords added.
+    pub(crate) fn fri_combination_codeword(
+        &self,
+        fri_domain_master_base_table: &MasterBaseTable,
+        fri_domain_ext_master_table: &MasterExtTable,
+        challenges: &AllChallenges,
+        non_lin_combi_weights: &[XFieldElement],
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Vec<XFieldElement> {
+        let padded_height = fri_domain_master_base_table.padded_height;
+        prof_start!(maybe_profiler, "quotient degree bounds");
+        let quotient_degree_bounds =
+            all_quotient_degree_bounds(self.interpolant_degree, padded_height);
+        prof_stop!(maybe_profiler, "quotient degree bounds");
+
+        prof_start!(maybe_profiler, "quotient-domain codewords");
+        let trace_domain = ArithmeticDomain::new_no_offset(padded_height);
+        let quotient_domain = self.quotient_domain();
+        let unit_distance = self.fri.domain.length / quotient_domain.length;
+        let base_quotient_domain_codewords = fri_domain_master_base_table
+            .master_base_matrix
+            .slice(s![..; unit_distance, ..]);
+        let extension_quotient_domain_codewords = fri_domain_ext_master_table
+            .master_ext_matrix
+            .slice(s![..; unit_distance, ..]);
+        prof_stop!(maybe_profiler, "quotient-domain codewords");
+
+        prof_start!(maybe_profiler, "quotient codewords");
+        let master_quotient_table = all_quotients(
+            base_quotient_domain_codewords,
+            extension_quotient_domain_codewords,
+            trace_domain,
+            quotient_domain,
+            challenges,
+            maybe_profiler,
+        );
+        prof_stop!(maybe_profiler, "quotient codewords");
+
+        prof_start!(maybe_profiler, "nonlinear combination");
+        prof_start!(maybe_profiler, "create combination codeword");
+        let combination_codeword = self.create_combination_codeword(
+            quotient_domain,
+            base_quotient_domain_codewords,
+            extension_quotient_domain_codewords.slice(s![.., ..NUM_EXT_COLUMNS]),
+            master_quotient_table.view(),
+            non_lin_combi_weights,
+            quotient_degree_bounds,
+        );
+        prof_stop!(maybe_profiler, "create combination codeword");
+
+        prof_start!(maybe_profiler, "LDE 3");
+        let fri_combination_codeword_without_randomizer = Array1::from(
+            quotient_domain.low_degree_extension(&combination_codeword, self.fri.domain),
+        );
+        prof_stop!(maybe_profiler, "LDE 3");
+
+        let fri_combination_codeword = fri_domain_ext_master_table
+            .randomizer_polynomials()
+            .into_iter()
+            .fold(fri_combination_codeword_without_randomizer, ArrayBase::add)
+            .to_vec();
+        prof_stop!(maybe_profiler, "nonlinear combination");
+
+        fri_combination_codeword
     }
 
-    fn quotient_domain(&self) -> ArithmeticDomain {
+    pub(crate) fn quotient_domain(&self) -> ArithmeticDomain {
         // When debugging, it is useful to check the degree of some intermediate polynomials.
         // The quotient domain is chosen to be _just_ large enough to perform all the necessary
         // computations on polynomials. Concretely, the maximal degree of a polynomial over the
@@ -381,7 +754,7 @@ impl Stark {
         }
     }
 
-    fn revealed_current_and_next_row_indices(
+    pub(crate) fn revealed_current_and_next_row_indices(
         &self,
         unit_distance: usize,
         revealed_current_rows_indices: &[usize],
@@ -396,14 +769,12 @@ impl Stark {
         indices
     }
 
-    fn get_revealed_elements<FF: FiniteField>(
-        master_matrix: ArrayView2<FF>,
-        revealed_indices: &[usize],
-    ) -> Vec<Vec<FF>> {
-        revealed_indices
+    pub(crate) fn empty_tables_item(empty_tables: &[TableId]) -> ProofItem {
+        let table_indices = empty_tables
             .iter()
-            .map(|&idx| master_matrix.slice(s![idx, ..]).to_vec())
-            .collect_vec()
+            .map(|&id| BFieldElement::new(id as u64))
+            .collect();
+        ProofItem::EmptyTables(table_indices)
     }
 
     fn create_combination_codeword(
@@ -435,12 +806,12 @@ impl Stark {
             .zip_eq(base_weights.chunks_exact(2))
             .enumerate()
         {
-            Zip::from(&mut combination_codeword)
-                .and(codeword)
-                .and(shifted_domain_values.view())
-                .par_for_each(|acc, &bfe, &shift| {
-                    *acc += weights[0] * bfe + weights[1] * bfe * shift
-                });
+            add_weighted_shifted_codeword(
+                &mut combination_codeword,
+                codeword,
+                shifted_domain_values.view(),
+                [weights[0], weights[1]],
+            );
             self.debug_check_degree(idx, &combination_codeword, quotient_domain);
         }
         if std::env::var("DEBUG").is_ok() {
@@ -452,12 +823,12 @@ impl Stark {
             .zip_eq(ext_weights.chunks_exact(2))
             .enumerate()
         {
-            Zip::from(&mut combination_codeword)
-                .and(codeword)
-                .and(shifted_domain_values.view())
-                .par_for_each(|acc, &xfe, &shift| {
-                    *acc += weights[0] * xfe + weights[1] * xfe * shift
-                });
+            add_weighted_shifted_codeword(
+                &mut combination_codeword,
+                codeword,
+                shifted_domain_values.view(),
+                [weights[0], weights[1]],
+            );
             self.debug_check_degree(idx, &combination_codeword, quotient_domain);
         }
         if std::env::var("DEBUG").is_ok() {
@@ -472,12 +843,12 @@ impl Stark {
         {
             let shifted_domain_values =
                 Self::degree_shift_domain(&quotient_domain_values, self.max_degree - degree_bound);
-            Zip::from(&mut combination_codeword)
-                .and(codeword)
-                .and(shifted_domain_values.view())
-                .par_for_each(|acc, &xfe, &shift| {
-                    *acc += weights[0] * xfe + weights[1] * xfe * shift
-                });
+            add_weighted_shifted_codeword(
+                &mut combination_codeword,
+                codeword,
+                shifted_domain_values.view(),
+                [weights[0], weights[1]],
+            );
             self.debug_check_degree(idx, &combination_codeword, quotient_domain);
         }
 
@@ -519,7 +890,7 @@ impl Stark {
         );
     }
 
-    fn sample_weights(seed: Digest, num_weights: usize) -> Vec<XFieldElement> {
+    pub(crate) fn sample_weights(seed: Digest, num_weights: usize) -> Vec<XFieldElement> {
         StarkHasher::get_n_hash_rounds(&seed, num_weights)
             .iter()
             .map(XFieldElement::sample)
@@ -532,14 +903,69 @@ impl Stark {
         maybe_profiler: &mut Option<TritonProfiler>,
     ) -> Result<bool> {
         prof_start!(maybe_profiler, "deserialize");
-        let mut proof_stream = StarkProofStream::from_proof(&proof)?;
+        let proof_stream = StarkProofStream::from_proof(&proof)?;
         prof_stop!(maybe_profiler, "deserialize");
+        self.verify_proof_stream(proof_stream, maybe_profiler)
+    }
+
+    /// Interactive mode: like [`verify`](Self::verify), but take all challenges from the given
+    /// channel instead of deriving them through Fiat-Shamir. See
+    /// [`prove_interactively`](Self::prove_interactively).
+    pub fn verify_interactively(
+        &self,
+        proof: Proof,
+        challenge_channel: Box<dyn ChallengeChannel>,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<bool> {
+        prof_start!(maybe_profiler, "deserialize");
+        let proof_stream =
+            StarkProofStream::from_proof(&proof)?.with_challenge_channel(challenge_channel);
+        prof_stop!(maybe_profiler, "deserialize");
+        self.verify_proof_stream(proof_stream, maybe_profiler)
+    }
+
+    fn verify_proof_stream(
+        &self,
+        proof_stream: StarkProofStream,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<bool> {
+        self.verify_proof_stream_revealing_indices(proof_stream, maybe_profiler)
+            .map(|_| true)
+    }
+
+    /// Like [`verify`](Self::verify), but returns the indices of the combination codeword that
+    /// the verifier sampled and checked, including duplicates.
+    pub(crate) fn verify_revealing_indices(
+        &self,
+        proof: Proof,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<Vec<usize>> {
+        let proof_stream = StarkProofStream::from_proof(&proof)?;
+        self.verify_proof_stream_revealing_indices(proof_stream, maybe_profiler)
+    }
+
+    fn verify_proof_stream_revealing_indices(
+        &self,
+        mut proof_stream: StarkProofStream,
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<Vec<usize>> {
+        if self.claim.is_output_tagged() && self.claim.output_tags.len() != self.claim.output.len()
+        {
+            return Err(anyhow!(StarkValidationError::OutputTagsLengthMismatch));
+        }
+
+        self.parameters.validate_fri_domain_offset()?;
 
         prof_start!(maybe_profiler, "Fiat-Shamir 1");
         let padded_height = proof_stream.dequeue()?.as_padded_heights()?.value() as usize;
         if self.claim.padded_height != padded_height {
             return Err(anyhow!(StarkValidationError::PaddedHeightInequality));
         }
+        let parameters_digest = proof_stream.dequeue()?.as_parameters_digest()?;
+        if self.parameters.digest() != parameters_digest {
+            return Err(anyhow!(StarkValidationError::ParametersDigestInequality));
+        }
+        let empty_tables = Self::dequeue_empty_tables(&mut proof_stream)?;
         let base_merkle_tree_root = proof_stream.dequeue()?.as_merkle_root()?;
 
         let extension_challenge_seed = proof_stream.verifier_fiat_shamir();
@@ -549,6 +975,8 @@ impl Stark {
             extension_challenge_weights,
             &self.claim.input,
             &self.claim.output,
+            &self.claim.output_tags,
+            self.claim.entry_point,
         );
         prof_stop!(maybe_profiler, "Fiat-Shamir 1");
 
@@ -556,43 +984,155 @@ impl Stark {
         let extension_tree_merkle_root = proof_stream.dequeue()?.as_merkle_root()?;
         prof_stop!(maybe_profiler, "dequeue");
 
-        // Get weights for nonlinear combination. Concretely, sample 2 weights for each base
-        // polynomial, each extension polynomial, and each quotient. The factor is 2 because
-        // transition constraints check 2 rows.
+        // Get weights for nonlinear combination.
         prof_start!(maybe_profiler, "Fiat-Shamir 2");
         let non_lin_combi_weights_seed = proof_stream.verifier_fiat_shamir();
-        let num_non_lin_combi_weights =
-            2 * (NUM_BASE_COLUMNS + NUM_EXT_COLUMNS + num_all_table_quotients());
-        let non_lin_combi_weights = Array1::from(Self::sample_weights(
+        let mut non_lin_combi_weights = Self::sample_weights(
             non_lin_combi_weights_seed,
-            num_non_lin_combi_weights,
-        ));
+            Self::num_non_lin_combi_weights(),
+        );
+        self.constraint_selection
+            .mask_weights(&mut non_lin_combi_weights);
         prof_stop!(maybe_profiler, "Fiat-Shamir 2");
 
-        prof_start!(maybe_profiler, "Fiat-Shamir 3");
-        let combination_root = proof_stream.dequeue()?.as_merkle_root()?;
-        let indices_seed = proof_stream.verifier_fiat_shamir();
-        let revealed_current_row_indices = StarkHasher::sample_indices(
-            &indices_seed,
-            self.fri.domain.length,
-            self.parameters.num_non_linear_codeword_checks,
-        );
-        prof_stop!(maybe_profiler, "Fiat-Shamir 3");
+        prof_start!(maybe_profiler, "Fiat-Shamir 3");
+        let combination_root = proof_stream.dequeue()?.as_merkle_root()?;
+        let indices_seed = proof_stream.verifier_fiat_shamir();
+        let revealed_current_row_indices = StarkHasher::sample_indices(
+            &indices_seed,
+            self.fri.domain.length,
+            self.parameters.num_non_linear_codeword_checks,
+        );
+        prof_stop!(maybe_profiler, "Fiat-Shamir 3");
+
+        // verify low degree of combination polynomial with FRI
+        prof_start!(maybe_profiler, "FRI");
+        self.fri
+            .verify(&mut proof_stream, &combination_root, maybe_profiler)?;
+        prof_stop!(maybe_profiler, "FRI");
+
+        prof_start!(maybe_profiler, "check leafs");
+        prof_start!(maybe_profiler, "get indices");
+        // the relation between the FRI domain and the trace domain
+        let unit_distance = self.fri.domain.length / padded_height;
+        let revealed_current_and_next_row_indices = self
+            .revealed_current_and_next_row_indices(unit_distance, &revealed_current_row_indices);
+        prof_stop!(maybe_profiler, "get indices");
+
+        let base_table_rows = Self::dequeue_and_authenticate_base_rows(
+            &mut proof_stream,
+            base_merkle_tree_root,
+            &revealed_current_and_next_row_indices,
+            maybe_profiler,
+        )?;
+        let ext_table_rows = Self::dequeue_and_authenticate_ext_rows(
+            &mut proof_stream,
+            extension_tree_merkle_root,
+            &revealed_current_and_next_row_indices,
+            maybe_profiler,
+        )?;
+        let revealed_combination_leafs = Self::dequeue_and_authenticate_combination_leafs(
+            &mut proof_stream,
+            combination_root,
+            &revealed_current_row_indices,
+            maybe_profiler,
+        )?;
+        prof_stop!(maybe_profiler, "check leafs");
+
+        prof_start!(maybe_profiler, "nonlinear combination");
+        prof_start!(maybe_profiler, "index");
+        let base_table_rows =
+            MasterBaseTable::reinsert_empty_table_columns(&empty_tables, base_table_rows)?;
+        let ext_table_rows = MasterExtTable::reinsert_empty_table_columns(
+            &empty_tables,
+            ext_table_rows,
+            &challenges,
+        )?;
+        let (indexed_base_table_rows, indexed_ext_table_rows, indexed_randomizer_rows) =
+            Self::index_revealed_rows(
+                revealed_current_and_next_row_indices,
+                base_table_rows,
+                ext_table_rows,
+            );
+        prof_stop!(maybe_profiler, "index");
+
+        // verify non-linear combination
+        prof_start!(maybe_profiler, "degree bounds");
+        let combination_evaluator = CombinationEvaluator::new(self, padded_height);
+        prof_stop!(maybe_profiler, "degree bounds");
+
+        prof_start!(maybe_profiler, "main loop");
+        for (&current_row_idx, revealed_combination_leaf) in revealed_current_row_indices
+            .iter()
+            .zip_eq(revealed_combination_leafs)
+        {
+            prof_itr0!(maybe_profiler, "main loop");
+            let inner_product = combination_evaluator.evaluate(
+                current_row_idx,
+                &indexed_base_table_rows,
+                &indexed_ext_table_rows,
+                &challenges,
+                &non_lin_combi_weights,
+                maybe_profiler,
+            );
+            let randomizer_codewords_contribution = indexed_randomizer_rows[&current_row_idx].sum();
+            let expected_leaf = inner_product + randomizer_codewords_contribution;
+            if !self.leafs_match(revealed_combination_leaf, expected_leaf) {
+                return Err(anyhow!(StarkValidationError::CombinationLeafInequality));
+            }
+        }
+        prof_stop!(maybe_profiler, "main loop");
+        prof_stop!(maybe_profiler, "nonlinear combination");
+
+        // Otherwise, appending items to a valid proof gives another valid proof.
+        if proof_stream.num_unread_items() > 0 {
+            return Err(anyhow!(StarkValidationError::TrailingProofItems));
+        }
+        Ok(revealed_current_row_indices)
+    }
 
-        // verify low degree of combination polynomial with FRI
-        prof_start!(maybe_profiler, "FRI");
-        self.fri
-            .verify(&mut proof_stream, &combination_root, maybe_profiler)?;
-        prof_stop!(maybe_profiler, "FRI");
+    /// Compare a revealed leaf of the combination codeword to the value recomputed by the
+    /// verifier, in constant time if so configured.
+    pub(crate) fn leafs_match(
+        &self,
+        revealed_leaf: XFieldElement,
+        expected: XFieldElement,
+    ) -> bool {
+        if self.fri.constant_time {
+            constant_time_eq(revealed_leaf, expected)
+        } else {
+            revealed_leaf == expected
+        }
+    }
 
-        prof_start!(maybe_profiler, "check leafs");
-        prof_start!(maybe_profiler, "get indices");
-        // the relation between the FRI domain and the trace domain
-        let unit_distance = self.fri.domain.length / padded_height;
-        let revealed_current_and_next_row_indices = self
-            .revealed_current_and_next_row_indices(unit_distance, &revealed_current_row_indices);
-        prof_stop!(maybe_profiler, "get indices");
+    /// The tables the prover did not commit to, see [`MasterBaseTable::empty_tables`]. The
+    /// indices must be increasing, and each table must be one that can be empty.
+    pub(crate) fn dequeue_empty_tables(
+        proof_stream: &mut StarkProofStream,
+    ) -> Result<Vec<TableId>> {
+        let table_indices = proof_stream.dequeue()?.as_empty_tables()?;
+        let table_indices = table_indices.iter().map(|index| index.value() as usize);
+        if !table_indices.clone().tuple_windows().all(|(a, b)| a < b) {
+            return Err(anyhow!(StarkValidationError::InvalidEmptyTables));
+        }
+        let mut empty_tables = vec![];
+        for table_index in table_indices {
+            match TableId::iter().nth(table_index) {
+                Some(id) if MasterBaseTable::empty_table_base_row(id).is_some() => {
+                    empty_tables.push(id)
+                }
+                _ => return Err(anyhow!(StarkValidationError::InvalidEmptyTables)),
+            }
+        }
+        Ok(empty_tables)
+    }
 
+    pub(crate) fn dequeue_and_authenticate_base_rows(
+        proof_stream: &mut StarkProofStream,
+        base_merkle_tree_root: Digest,
+        revealed_indices: &[usize],
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<Vec<Vec<BFieldElement>>> {
         prof_start!(maybe_profiler, "dequeue base elements");
         let base_table_rows = proof_stream.dequeue()?.as_master_base_table_rows()?;
         let base_auth_paths = proof_stream
@@ -607,14 +1147,22 @@ impl Stark {
         prof_start!(maybe_profiler, "Merkle verify (base tree)");
         if !MerkleTree::<StarkHasher, Maker>::verify_authentication_structure_from_leaves(
             base_merkle_tree_root,
-            &revealed_current_and_next_row_indices,
+            revealed_indices,
             &leaf_digests_base,
             &base_auth_paths,
         ) {
             bail!("Failed to verify authentication path for base codeword");
         }
         prof_stop!(maybe_profiler, "Merkle verify (base tree)");
+        Ok(base_table_rows)
+    }
 
+    pub(crate) fn dequeue_and_authenticate_ext_rows(
+        proof_stream: &mut StarkProofStream,
+        extension_tree_merkle_root: Digest,
+        revealed_indices: &[usize],
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<Vec<Vec<XFieldElement>>> {
         prof_start!(maybe_profiler, "dequeue extension elements");
         let ext_table_rows = proof_stream.dequeue()?.as_master_ext_table_rows()?;
         let auth_paths_ext = proof_stream
@@ -635,15 +1183,22 @@ impl Stark {
         prof_start!(maybe_profiler, "Merkle verify (extension tree)");
         if !MerkleTree::<StarkHasher, Maker>::verify_authentication_structure_from_leaves(
             extension_tree_merkle_root,
-            &revealed_current_and_next_row_indices,
+            revealed_indices,
             &leaf_digests_ext,
             &auth_paths_ext,
         ) {
             bail!("Failed to verify authentication path for extension codeword");
         }
         prof_stop!(maybe_profiler, "Merkle verify (extension tree)");
+        Ok(ext_table_rows)
+    }
 
-        // Verify Merkle authentication path for combination elements
+    pub(crate) fn dequeue_and_authenticate_combination_leafs(
+        proof_stream: &mut StarkProofStream,
+        combination_root: Digest,
+        revealed_indices: &[usize],
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> Result<Vec<XFieldElement>> {
         prof_start!(maybe_profiler, "Merkle verify (combination tree)");
         let revealed_combination_leafs =
             proof_stream.dequeue()?.as_revealed_combination_elements()?;
@@ -656,188 +1211,21 @@ impl Stark {
             .as_compressed_authentication_paths()?;
         if !MerkleTree::<StarkHasher, Maker>::verify_authentication_structure_from_leaves(
             combination_root,
-            &revealed_current_row_indices,
+            revealed_indices,
             &revealed_combination_digests,
             &revealed_combination_auth_paths,
         ) {
             bail!("Failed to verify authentication path for combination codeword");
         }
         prof_stop!(maybe_profiler, "Merkle verify (combination tree)");
-        prof_stop!(maybe_profiler, "check leafs");
-
-        prof_start!(maybe_profiler, "nonlinear combination");
-        prof_start!(maybe_profiler, "index");
-        let (indexed_base_table_rows, indexed_ext_table_rows, indexed_randomizer_rows) =
-            Self::index_revealed_rows(
-                revealed_current_and_next_row_indices,
-                base_table_rows,
-                ext_table_rows,
-            );
-        prof_stop!(maybe_profiler, "index");
-
-        // verify non-linear combination
-        prof_start!(maybe_profiler, "degree bounds");
-        let base_and_ext_col_shift = self.max_degree - self.interpolant_degree;
-        let initial_quotient_degree_bounds =
-            all_initial_quotient_degree_bounds(self.interpolant_degree);
-        let consistency_quotient_degree_bounds =
-            all_consistency_quotient_degree_bounds(self.interpolant_degree, padded_height);
-        let transition_quotient_degree_bounds =
-            all_transition_quotient_degree_bounds(self.interpolant_degree, padded_height);
-        let terminal_quotient_degree_bounds =
-            all_terminal_quotient_degree_bounds(self.interpolant_degree);
-        prof_stop!(maybe_profiler, "degree bounds");
-
-        prof_start!(maybe_profiler, "pre-compute all shifts");
-        let mut all_shifts = vec![self.interpolant_degree]
-            .iter()
-            .chain(initial_quotient_degree_bounds.iter())
-            .chain(consistency_quotient_degree_bounds.iter())
-            .chain(transition_quotient_degree_bounds.iter())
-            .chain(terminal_quotient_degree_bounds.iter())
-            .map(|degree_bound| self.max_degree - degree_bound)
-            .collect_vec();
-        all_shifts.sort();
-        all_shifts.dedup();
-        let mut all_shifted_fri_domain_values = HashMap::new();
-        prof_stop!(maybe_profiler, "pre-compute all shifts");
-
-        prof_start!(maybe_profiler, "main loop");
-        let trace_domain_generator = derive_domain_generator(padded_height as u64);
-        let trace_domain_generator_inverse = trace_domain_generator.inverse();
-        for (current_row_idx, revealed_combination_leaf) in revealed_current_row_indices
-            .into_iter()
-            .zip_eq(revealed_combination_leafs)
-        {
-            prof_itr0!(maybe_profiler, "main loop");
-            let next_row_idx = (current_row_idx + unit_distance) % self.fri.domain.length;
-            let current_base_row = indexed_base_table_rows[&current_row_idx].view();
-            let current_ext_row = indexed_ext_table_rows[&current_row_idx].view();
-            let next_base_row = indexed_base_table_rows[&next_row_idx].view();
-            let next_ext_row = indexed_ext_table_rows[&next_row_idx].view();
-
-            prof_start!(maybe_profiler, "zerofiers");
-            let one = BFieldElement::one();
-            let current_fri_domain_value = self.fri.domain.domain_value(current_row_idx as u32);
-            let initial_zerofier_inverse = (current_fri_domain_value - one).inverse();
-            let consistency_zerofier_inverse =
-                (current_fri_domain_value.mod_pow_u32(padded_height as u32) - one).inverse();
-            let except_last_row = current_fri_domain_value - trace_domain_generator_inverse;
-            let transition_zerofier_inverse = except_last_row * consistency_zerofier_inverse;
-            let terminal_zerofier_inverse = except_last_row.inverse(); // i.e., only last row
-            prof_stop!(maybe_profiler, "zerofiers");
-
-            prof_start!(maybe_profiler, "shifted FRI domain values");
-            // Minimize the respective exponents and thus work spent exponentiating by using the
-            // fact that `all_shifts` is sorted. Concretely, use
-            // 1. `x^curr_shift = x^(prev_shift + shift_diff) = x^prev_shift * x^shift_diff`,
-            // 2. memoization of `x^prev_shift`, and
-            // 3. the fact that exponentiation by a smaller exponent is computationally cheaper.
-            let mut previous_shift = all_shifts[0];
-            let mut previously_shifted_fri_domain_value =
-                current_fri_domain_value.mod_pow_u32(previous_shift as u32);
-            all_shifted_fri_domain_values
-                .insert(previous_shift, previously_shifted_fri_domain_value);
-            for &shift in all_shifts.iter().skip(1) {
-                let current_shifted_fri_domain_value = previously_shifted_fri_domain_value
-                    * current_fri_domain_value.mod_pow_u32((shift - previous_shift) as u32);
-                all_shifted_fri_domain_values.insert(shift, current_shifted_fri_domain_value);
-                previous_shift = shift;
-                previously_shifted_fri_domain_value = current_shifted_fri_domain_value;
-            }
-            prof_stop!(maybe_profiler, "shifted FRI domain values");
-
-            prof_start!(maybe_profiler, "evaluate AIR");
-            let evaluated_initial_constraints =
-                evaluate_all_initial_constraints(current_base_row, current_ext_row, &challenges);
-            let evaluated_consistency_constraints = evaluate_all_consistency_constraints(
-                current_base_row,
-                current_ext_row,
-                &challenges,
-            );
-            let evaluated_transition_constraints = evaluate_all_transition_constraints(
-                current_base_row,
-                current_ext_row,
-                next_base_row,
-                next_ext_row,
-                &challenges,
-            );
-            let evaluated_terminal_constraints =
-                evaluate_all_terminal_constraints(current_base_row, current_ext_row, &challenges);
-            prof_stop!(maybe_profiler, "evaluate AIR");
-
-            prof_start!(maybe_profiler, "populate base & ext elements");
-            // populate summands with a the revealed FRI domain master table rows and their shifts
-            let base_ext_fri_domain_value_shifted =
-                all_shifted_fri_domain_values[&base_and_ext_col_shift];
-            let mut summands = Vec::with_capacity(non_lin_combi_weights.len());
-            for &base_row_element in current_base_row.iter() {
-                let base_row_element_shifted = base_row_element * base_ext_fri_domain_value_shifted;
-                summands.push(base_row_element.lift());
-                summands.push(base_row_element_shifted.lift());
-            }
-
-            for &ext_row_element in current_ext_row.iter() {
-                let ext_row_element_shifted = ext_row_element * base_ext_fri_domain_value_shifted;
-                summands.push(ext_row_element);
-                summands.push(ext_row_element_shifted);
-            }
-            prof_stop!(maybe_profiler, "populate base & ext elements");
-
-            prof_start!(maybe_profiler, "populate quotient elements");
-            for (degree_bound_category, evaluated_constraints_category, zerofier_inverse) in [
-                (
-                    &initial_quotient_degree_bounds,
-                    evaluated_initial_constraints,
-                    initial_zerofier_inverse,
-                ),
-                (
-                    &consistency_quotient_degree_bounds,
-                    evaluated_consistency_constraints,
-                    consistency_zerofier_inverse,
-                ),
-                (
-                    &transition_quotient_degree_bounds,
-                    evaluated_transition_constraints,
-                    transition_zerofier_inverse,
-                ),
-                (
-                    &terminal_quotient_degree_bounds,
-                    evaluated_terminal_constraints,
-                    terminal_zerofier_inverse,
-                ),
-            ] {
-                for (degree_bound, evaluated_constraint) in degree_bound_category
-                    .iter()
-                    .zip_eq(evaluated_constraints_category.into_iter())
-                {
-                    let shift = self.max_degree - degree_bound;
-                    let quotient = evaluated_constraint * zerofier_inverse;
-                    let quotient_shifted = quotient * all_shifted_fri_domain_values[&shift];
-                    summands.push(quotient);
-                    summands.push(quotient_shifted);
-                }
-            }
-            prof_stop!(maybe_profiler, "populate quotient elements");
-
-            prof_start!(maybe_profiler, "compute inner product");
-            let inner_product = (&non_lin_combi_weights * &Array1::from(summands)).sum();
-            let randomizer_codewords_contribution = indexed_randomizer_rows[&current_row_idx].sum();
-            if revealed_combination_leaf != inner_product + randomizer_codewords_contribution {
-                return Err(anyhow!(StarkValidationError::CombinationLeafInequality));
-            }
-            prof_stop!(maybe_profiler, "compute inner product");
-        }
-        prof_stop!(maybe_profiler, "main loop");
-        prof_stop!(maybe_profiler, "nonlinear combination");
-        Ok(true)
+        Ok(revealed_combination_leafs)
     }
 
     /// Hash-maps for base, extension, and randomizer rows that allow doing
     /// `indexed_revealed_base_rows[revealed_index]` instead of
     /// `revealed_base_rows[revealed_indices.iter().position(|&i| i == revealed_index).unwrap()]`.
     #[allow(clippy::type_complexity)]
-    fn index_revealed_rows(
+    pub(crate) fn index_revealed_rows(
         revealed_indices: Vec<usize>,
         revealed_base_rows: Vec<Vec<BFieldElement>>,
         revealed_ext_rows: Vec<Vec<XFieldElement>>,
@@ -871,14 +1259,225 @@ impl Stark {
     }
 }
 
+/// Recomputes values of the combination codeword from revealed rows of the master tables.
+pub(crate) struct CombinationEvaluator {
+    max_degree: Degree,
+    padded_height: usize,
+    fri_domain: ArithmeticDomain,
+    base_and_ext_col_shift: Degree,
+    initial_quotient_degree_bounds: Vec<Degree>,
+    consistency_quotient_degree_bounds: Vec<Degree>,
+    transition_quotient_degree_bounds: Vec<Degree>,
+    terminal_quotient_degree_bounds: Vec<Degree>,
+    all_shifts: Vec<Degree>,
+    trace_domain_generator_inverse: BFieldElement,
+}
+
+impl CombinationEvaluator {
+    pub(crate) fn new(stark: &Stark, padded_height: usize) -> Self {
+        let interpolant_degree = stark.interpolant_degree;
+        let max_degree = stark.max_degree;
+        let base_and_ext_col_shift = max_degree - interpolant_degree;
+        let initial_quotient_degree_bounds = all_initial_quotient_degree_bounds(interpolant_degree);
+        let consistency_quotient_degree_bounds =
+            all_consistency_quotient_degree_bounds(interpolant_degree, padded_height);
+        let transition_quotient_degree_bounds =
+            all_transition_quotient_degree_bounds(interpolant_degree, padded_height);
+        let terminal_quotient_degree_bounds =
+            all_terminal_quotient_degree_bounds(interpolant_degree);
+
+        let mut all_shifts = [interpolant_degree]
+            .iter()
+            .chain(initial_quotient_degree_bounds.iter())
+            .chain(consistency_quotient_degree_bounds.iter())
+            .chain(transition_quotient_degree_bounds.iter())
+            .chain(terminal_quotient_degree_bounds.iter())
+            .map(|degree_bound| max_degree - degree_bound)
+            .collect_vec();
+        all_shifts.sort();
+        all_shifts.dedup();
+
+        let trace_domain_generator = derive_domain_generator(padded_height as u64);
+        Self {
+            max_degree,
+            padded_height,
+            fri_domain: stark.fri.domain,
+            base_and_ext_col_shift,
+            initial_quotient_degree_bounds,
+            consistency_quotient_degree_bounds,
+            transition_quotient_degree_bounds,
+            terminal_quotient_degree_bounds,
+            all_shifts,
+            trace_domain_generator_inverse: trace_domain_generator.inverse(),
+        }
+    }
+
+    /// The value of the combination codeword, without the randomizer codewords, in the given
+    /// row. Both the row and its successor must have been revealed.
+    pub(crate) fn evaluate(
+        &self,
+        current_row_idx: usize,
+        indexed_base_table_rows: &HashMap<usize, Array1<BFieldElement>>,
+        indexed_ext_table_rows: &HashMap<usize, Array1<XFieldElement>>,
+        challenges: &AllChallenges,
+        non_lin_combi_weights: &[XFieldElement],
+        maybe_profiler: &mut Option<TritonProfiler>,
+    ) -> XFieldElement {
+        let unit_distance = self.fri_domain.length / self.padded_height;
+        let next_row_idx = (current_row_idx + unit_distance) % self.fri_domain.length;
+        let current_base_row = indexed_base_table_rows[&current_row_idx].view();
+        let current_ext_row = indexed_ext_table_rows[&current_row_idx].view();
+        let next_base_row = indexed_base_table_rows[&next_row_idx].view();
+        let next_ext_row = indexed_ext_table_rows[&next_row_idx].view();
+
+        prof_start!(maybe_profiler, "zerofiers");
+        let one = BFieldElement::one();
+        let current_fri_domain_value = self.fri_domain.domain_value(current_row_idx as u32);
+        let initial_zerofier_inverse = (current_fri_domain_value - one).inverse();
+        let consistency_zerofier_inverse =
+            (current_fri_domain_value.mod_pow_u32(self.padded_height as u32) - one).inverse();
+        let except_last_row = current_fri_domain_value - self.trace_domain_generator_inverse;
+        let transition_zerofier_inverse = except_last_row * consistency_zerofier_inverse;
+        let terminal_zerofier_inverse = except_last_row.inverse(); // i.e., only last row
+        prof_stop!(maybe_profiler, "zerofiers");
+
+        prof_start!(maybe_profiler, "shifted FRI domain values");
+        // Minimize the respective exponents and thus work spent exponentiating by using the
+        // fact that `all_shifts` is sorted. Concretely, use
+        // 1. `x^curr_shift = x^(prev_shift + shift_diff) = x^prev_shift * x^shift_diff`,
+        // 2. memoization of `x^prev_shift`, and
+        // 3. the fact that exponentiation by a smaller exponent is computationally cheaper.
+        let mut all_shifted_fri_domain_values = HashMap::new();
+        let mut previous_shift = self.all_shifts[0];
+        let mut previously_shifted_fri_domain_value =
+            current_fri_domain_value.mod_pow_u32(previous_shift as u32);
+        all_shifted_fri_domain_values.insert(previous_shift, previously_shifted_fri_domain_value);
+        for &shift in self.all_shifts.iter().skip(1) {
+            let current_shifted_fri_domain_value = previously_shifted_fri_domain_value
+                * current_fri_domain_value.mod_pow_u32((shift - previous_shift) as u32);
+            all_shifted_fri_domain_values.insert(shift, current_shifted_fri_domain_value);
+            previous_shift = shift;
+            previously_shifted_fri_domain_value = current_shifted_fri_domain_value;
+        }
+        prof_stop!(maybe_profiler, "shifted FRI domain values");
+
+        prof_start!(maybe_profiler, "evaluate AIR");
+        let evaluated_initial_constraints =
+            evaluate_all_initial_constraints(current_base_row, current_ext_row, challenges);
+        let evaluated_consistency_constraints =
+            evaluate_all_consistency_constraints(current_base_row, current_ext_row, challenges);
+        let evaluated_transition_constraints = evaluate_all_transition_constraints(
+            current_base_row,
+            current_ext_row,
+            next_base_row,
+            next_ext_row,
+            challenges,
+        );
+        let evaluated_terminal_constraints =
+            evaluate_all_terminal_constraints(current_base_row, current_ext_row, challenges);
+        prof_stop!(maybe_profiler, "evaluate AIR");
+
+        prof_start!(maybe_profiler, "populate base & ext elements");
+        // populate summands with a the revealed FRI domain master table rows and their shifts
+        let base_ext_fri_domain_value_shifted =
+            all_shifted_fri_domain_values[&self.base_and_ext_col_shift];
+        let mut summands = Vec::with_capacity(non_lin_combi_weights.len());
+        for &base_row_element in current_base_row.iter() {
+            let base_row_element_shifted = base_row_element * base_ext_fri_domain_value_shifted;
+            summands.push(base_row_element.lift());
+            summands.push(base_row_element_shifted.lift());
+        }
+
+        for &ext_row_element in current_ext_row.iter() {
+            let ext_row_element_shifted = ext_row_element * base_ext_fri_domain_value_shifted;
+            summands.push(ext_row_element);
+            summands.push(ext_row_element_shifted);
+        }
+        prof_stop!(maybe_profiler, "populate base & ext elements");
+
+        prof_start!(maybe_profiler, "populate quotient elements");
+        for (degree_bound_category, evaluated_constraints_category, zerofier_inverse) in [
+            (
+                &self.initial_quotient_degree_bounds,
+                evaluated_initial_constraints,
+                initial_zerofier_inverse,
+            ),
+            (
+                &self.consistency_quotient_degree_bounds,
+                evaluated_consistency_constraints,
+                consistency_zerofier_inverse,
+            ),
+            (
+                &self.transition_quotient_degree_bounds,
+                evaluated_transition_constraints,
+                transition_zerofier_inverse,
+            ),
+            (
+                &self.terminal_quotient_degree_bounds,
+                evaluated_terminal_constraints,
+                terminal_zerofier_inverse,
+            ),
+        ] {
+            for (degree_bound, evaluated_constraint) in degree_bound_category
+                .iter()
+                .zip_eq(evaluated_constraints_category)
+            {
+                let shift = self.max_degree - degree_bound;
+                let quotient = evaluated_constraint * zerofier_inverse;
+                let quotient_shifted = quotient * all_shifted_fri_domain_values[&shift];
+                summands.push(quotient);
+                summands.push(quotient_shifted);
+            }
+        }
+        prof_stop!(maybe_profiler, "populate quotient elements");
+
+        prof_start!(maybe_profiler, "compute inner product");
+        let inner_product = inner_product(non_lin_combi_weights, &summands);
+        prof_stop!(maybe_profiler, "compute inner product");
+        inner_product
+    }
+}
+
+/// Parse, simulate, prove, and verify the given program in one go, using the parameters of
+/// profile `fast-dev`. Fails if the program does not parse, the VM fails, or the proof does not
+/// verify. The returned `Stark` holds the [`Claim`], including the program's output.
+pub fn prove_and_verify(
+    source_code: &str,
+    input: Vec<BFieldElement>,
+    secret_input: Vec<BFieldElement>,
+) -> Result<(Stark, Proof)> {
+    let program = Program::from_code(source_code)?;
+    let (aet, output) = simulate(&program, input.clone(), secret_input)?;
+
+    let claim = Claim {
+        input,
+        program: program.to_bwords(),
+        output: output.into(),
+        output_tags: vec![],
+        padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+        entry_point: program.entry_point,
+    };
+    let stark = Stark::new(claim, StarkParameters::named("fast-dev")?);
+    let proof = stark.prove(aet, &mut None);
+    if !stark.verify(proof.clone(), &mut None)? {
+        bail!("The proof does not verify.");
+    }
+    Ok((stark, proof))
+}
+
 #[cfg(test)]
 pub(crate) mod triton_stark_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
     use itertools::izip;
     use ndarray::Array1;
     use num_traits::Zero;
 
     use triton_opcodes::instruction::AnInstruction;
     use triton_opcodes::program::Program;
+    use twenty_first::shared_math::other::random_elements_array;
+    use twenty_first::shared_math::traits::PrimitiveRootOfUnity;
 
     use crate::shared_tests::*;
     use crate::table::cross_table_argument::CrossTableArg;
@@ -903,11 +1502,11 @@ pub(crate) mod triton_stark_tests {
     use crate::table::table_column::ProcessorExtTableColumn::InputTableEvalArg;
     use crate::table::table_column::ProcessorExtTableColumn::OutputTableEvalArg;
     use crate::table::table_column::RamBaseTableColumn;
+    use crate::test_corpus::bigger_tasm_test_programs;
+    use crate::test_corpus::property_based_test_programs;
+    use crate::test_corpus::small_tasm_test_programs;
+    use crate::test_corpus::test_hash_nop_nop_lt;
     use crate::vm::simulate;
-    use crate::vm::triton_vm_tests::bigger_tasm_test_programs;
-    use crate::vm::triton_vm_tests::property_based_test_programs;
-    use crate::vm::triton_vm_tests::small_tasm_test_programs;
-    use crate::vm::triton_vm_tests::test_hash_nop_nop_lt;
     use crate::vm::AlgebraicExecutionTrace;
 
     use super::*;
@@ -922,11 +1521,8 @@ pub(crate) mod triton_stark_tests {
         assert!(program.is_ok(), "program parses correctly");
         let program = program.unwrap();
 
-        let (aet, stdout, err) = simulate(&program, input_symbols, secret_input_symbols);
-        if let Some(error) = err {
-            panic!("The VM encountered the following problem: {}", error);
-        }
-        (aet, stdout, program)
+        let (aet, stdout) = simulate(&program, input_symbols, secret_input_symbols).unwrap();
+        (aet, stdout.into(), program)
     }
 
     pub fn parse_simulate_pad(
@@ -942,7 +1538,9 @@ pub(crate) mod triton_stark_tests {
             input: stdin,
             program: instructions,
             output: stdout,
+            output_tags: vec![],
             padded_height,
+            entry_point: program.entry_point,
         };
         let log_expansion_factor = 2;
         let security_level = 32;
@@ -1557,6 +2155,20 @@ pub(crate) mod triton_stark_tests {
         }
     }
 
+    #[test]
+    fn triton_table_constraints_evaluate_to_zero_on_programs_skipping_coprocessors_test() {
+        let programs = [
+            "push 1 push 2 add pop halt",
+            "hash halt",
+            "call foo halt foo: return",
+            "push 2 push 1 write_mem pop pop halt",
+        ];
+        for program in programs {
+            println!("Testing program {program}.");
+            triton_table_constraints_evaluate_to_zero(SourceCodeAndInput::without_input(program));
+        }
+    }
+
     #[test]
     fn triton_table_constraints_evaluate_to_zero_on_property_based_programs_test() {
         for (program_idx, program) in property_based_test_programs().into_iter().enumerate() {
@@ -1702,6 +2314,406 @@ pub(crate) mod triton_stark_tests {
         assert!(result.unwrap());
     }
 
+    #[test]
+    fn named_parameter_profiles_test() {
+        let testnet = StarkParameters::named("triton-2022-testnet").unwrap();
+        assert_eq!(StarkParameters::default(), testnet);
+        assert_eq!(
+            testnet,
+            StarkParameters::named("triton-2022-testnet@1").unwrap()
+        );
+        assert_eq!(
+            StarkParameters::new(32, 4),
+            StarkParameters::named("fast-dev").unwrap()
+        );
+
+        assert!(StarkParameters::named("triton-2022-testnet@2").is_err());
+        assert!(StarkParameters::named("fast-dev@latest").is_err());
+        assert!(StarkParameters::named("no-such-profile").is_err());
+
+        let digests = PARAMETER_PROFILES
+            .iter()
+            .map(|profile| profile.parameters.digest())
+            .collect_vec();
+        assert!(digests.iter().all_unique());
+    }
+
+    #[test]
+    fn parameter_profiles_never_change_test() {
+        let expected_digests = [
+            (
+                "triton-2022-testnet@1",
+                [
+                    2644363707691400119,
+                    5657346432555337180,
+                    7711364569334289680,
+                    1990186234944986459,
+                    12041097896943645839,
+                ],
+            ),
+            (
+                "high-security@1",
+                [
+                    18026277891968910000,
+                    16680675543602485297,
+                    16908084915251789909,
+                    7091607930414228616,
+                    9131041173764343338,
+                ],
+            ),
+            (
+                "fast-dev@1",
+                [
+                    10920834170356635707,
+                    3509011847040613594,
+                    5755994335023948966,
+                    6698010510644031227,
+                    3127118656818123079,
+                ],
+            ),
+        ];
+        assert_eq!(PARAMETER_PROFILES.len(), expected_digests.len());
+        for (profile, expected_digest) in expected_digests {
+            let expected_digest = Digest::new(expected_digest.map(BFieldElement::new));
+            let parameters = StarkParameters::named(profile).unwrap();
+            assert_eq!(expected_digest, parameters.digest(), "{profile}");
+        }
+    }
+
+    #[test]
+    fn fri_domain_offset_is_validated_and_committed_to_test() {
+        let parameters = StarkParameters::named("fast-dev").unwrap();
+        assert!(parameters.validate_fri_domain_offset().is_ok());
+        for offset in [0, 1, BFieldElement::MAX] {
+            let offset = BFieldElement::new(offset);
+            assert!(parameters.with_fri_domain_offset(offset).is_err());
+        }
+        let root_of_unity = BFieldElement::primitive_root_of_unity(1 << 10).unwrap();
+        assert!(parameters.with_fri_domain_offset(root_of_unity).is_err());
+
+        let program = Program::from_code("push 1 write_io halt").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let other_parameters = parameters
+            .with_fri_domain_offset(BFieldElement::new(343))
+            .unwrap();
+        assert_ne!(parameters.digest(), other_parameters.digest());
+
+        let stark = Stark::new(claim.clone(), other_parameters);
+        assert_eq!(BFieldElement::new(343), stark.fri.domain.offset);
+        let proof = stark.prove(aet, &mut None);
+        assert!(stark.verify(proof.clone(), &mut None).unwrap());
+
+        let err = Stark::new(claim, parameters)
+            .verify(proof, &mut None)
+            .unwrap_err();
+        assert_eq!(
+            StarkValidationError::ParametersDigestInequality,
+            err.downcast::<StarkValidationError>().unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot prove with invalid parameters")]
+    fn proving_with_invalid_fri_domain_offset_panics_test() {
+        let parameters = StarkParameters::named("fast-dev").unwrap();
+        let invalid_parameters = StarkParameters {
+            fri_domain_offset: BFieldElement::one(),
+            ..parameters
+        };
+        let serialized_parameters = serde_json::to_string(&invalid_parameters).unwrap();
+        let invalid_parameters: StarkParameters =
+            serde_json::from_str(&serialized_parameters).unwrap();
+
+        let (stark, aet) = stark_and_aet(SourceCodeAndInput::without_input("halt"));
+        let stark = Stark::new(stark.claim, invalid_parameters);
+        stark.prove(aet, &mut None);
+    }
+
+    #[test]
+    fn proof_for_other_parameters_is_rejected_test() {
+        let program = Program::from_code("push 1 write_io halt").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let parameters = StarkParameters::named("fast-dev").unwrap();
+        let proof = Stark::new(claim.clone(), parameters).prove(aet, &mut None);
+
+        let other_parameters = StarkParameters {
+            num_non_linear_codeword_checks: parameters.num_non_linear_codeword_checks - 1,
+            ..parameters
+        };
+        let err = Stark::new(claim, other_parameters)
+            .verify(proof, &mut None)
+            .unwrap_err();
+        assert_eq!(
+            StarkValidationError::ParametersDigestInequality,
+            err.downcast::<StarkValidationError>().unwrap()
+        );
+    }
+
+    #[test]
+    fn proof_with_altered_empty_tables_is_rejected_test() {
+        let program = Program::from_code("push 1 write_io halt").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let stark = Stark::new(claim, StarkParameters::named("fast-dev").unwrap());
+        let proof = stark.prove(aet, &mut None);
+
+        let empty_tables_item_index = 2;
+        let proof_stream = StarkProofStream::from_proof(&proof).unwrap();
+        let empty_tables = proof_stream.items[empty_tables_item_index].as_empty_tables();
+        let hash_table_index = BFieldElement::new(TableId::HashTable as u64);
+        assert_eq!(vec![hash_table_index], empty_tables.unwrap());
+
+        let with_empty_tables = |table_indices: Vec<BFieldElement>| {
+            let mut proof_stream = StarkProofStream::from_proof(&proof).unwrap();
+            proof_stream.items[empty_tables_item_index] = ProofItem::EmptyTables(table_indices);
+            proof_stream.to_proof()
+        };
+
+        let committed_to_hash_table = with_empty_tables(vec![]);
+        assert!(!stark
+            .verify(committed_to_hash_table, &mut None)
+            .unwrap_or(false));
+
+        let processor_table_index = BFieldElement::new(ProcessorTable as u64);
+        for table_indices in [
+            vec![processor_table_index],
+            vec![hash_table_index, hash_table_index],
+        ] {
+            let err = stark
+                .verify(with_empty_tables(table_indices), &mut None)
+                .unwrap_err();
+            assert_eq!(
+                StarkValidationError::InvalidEmptyTables,
+                err.downcast::<StarkValidationError>().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn constraint_selection_bisects_rejecting_component_test() {
+        let program = Program::from_code("push 1 write_io halt").unwrap();
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        let wrong_output = vec![BFieldElement::new(2)];
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: wrong_output,
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let parameters = StarkParameters::named("fast-dev").unwrap();
+        let prove_and_verify = |constraint_selection: ConstraintSelection| {
+            let stark = Stark::new(claim.clone(), parameters)
+                .with_constraint_selection(constraint_selection);
+            let proof = stark.prove(aet.clone(), &mut None);
+            stark.verify(proof, &mut None).unwrap_or(false)
+        };
+
+        // Only the Grand Cross-Table Argument links the claimed output to the trace.
+        assert!(!prove_and_verify(ConstraintSelection::all()));
+        assert!(!prove_and_verify(
+            ConstraintSelection::none().with_grand_cross_table_argument(true)
+        ));
+        assert!(prove_and_verify(
+            ConstraintSelection::all().with_grand_cross_table_argument(false)
+        ));
+
+        let partial_selection = ConstraintSelection::only(&[ProcessorTable]);
+        let partial_stark =
+            Stark::new(claim.clone(), parameters).with_constraint_selection(partial_selection);
+        let partial_proof = partial_stark.prove(aet, &mut None);
+        let regular_stark = Stark::new(claim, parameters);
+        assert!(regular_stark.verify(partial_proof, &mut None).is_err());
+    }
+
+    #[test]
+    fn honest_traces_pass_pre_flight_check_test() {
+        let programs = small_tasm_test_programs()
+            .into_iter()
+            .chain(property_based_test_programs())
+            .chain(bigger_tasm_test_programs());
+        for source_code_and_input in programs {
+            let (stark, aet) = stark_and_aet(source_code_and_input);
+            if let Err(err) = stark.check_trace(&aet) {
+                panic!("{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn pre_flight_check_names_violated_constraint_and_row_test() {
+        let (stark, mut aet) =
+            stark_and_aet(SourceCodeAndInput::without_input("push 1 write_io halt"));
+        let st0 = ProcessorBaseTableColumn::ST0.base_table_index();
+        aet.processor_matrix[[1, st0]] = BFieldElement::new(2);
+
+        let err = stark.check_trace(&aet).unwrap_err().to_string();
+        println!("{err}");
+        assert!(err.contains("ProcessorTable"));
+        assert!(err.contains("between rows 0 and 1"));
+        assert!(stark.prove_checked(aet, &mut None).is_err());
+    }
+
+    fn stark_and_aet(
+        source_code_and_input: SourceCodeAndInput,
+    ) -> (Stark, AlgebraicExecutionTrace) {
+        let (aet, output, program) = parse_setup_simulate(
+            &source_code_and_input.source_code,
+            source_code_and_input.input.clone(),
+            source_code_and_input.secret_input,
+        );
+        let claim = Claim {
+            input: source_code_and_input.input,
+            program: program.to_bwords(),
+            output,
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        (Stark::new(claim, StarkParameters::new(32, 4)), aet)
+    }
+
+    #[test]
+    fn prove_verify_tagged_output_test() {
+        // Outputs tagged 0 are diagnostic noise, the others are results.
+        let source_code = "push 0 push 10 write_io pop push 1 push 11 write_io pop \
+            push 0 push 12 write_io pop push 2 push 13 write_io pop halt";
+        let program = Program::from_code(source_code).unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: aet.output_tags(),
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let tag = BFieldElement::new;
+        assert_eq!(vec![tag(10), tag(12)], claim.output_with_tag(tag(0)));
+        assert_eq!(vec![tag(11)], claim.output_with_tag(tag(1)));
+        assert_eq!(vec![tag(13)], claim.output_with_tag(tag(2)));
+
+        let parameters = StarkParameters::named("fast-dev").unwrap();
+        let stark = Stark::new(claim.clone(), parameters);
+        let proof = stark.prove(aet, &mut None);
+        assert!(stark.verify(proof.clone(), &mut None).unwrap());
+
+        let mut swapped_tags = claim.clone();
+        swapped_tags.output_tags.swap(1, 3);
+        let stark = Stark::new(swapped_tags, parameters);
+        assert!(!matches!(stark.verify(proof.clone(), &mut None), Ok(true)));
+
+        let untagged = Claim {
+            output_tags: vec![],
+            ..claim.clone()
+        };
+        let stark = Stark::new(untagged, parameters);
+        assert!(!matches!(stark.verify(proof.clone(), &mut None), Ok(true)));
+
+        let mut too_few_tags = claim;
+        too_few_tags.output_tags.pop();
+        let stark = Stark::new(too_few_tags, parameters);
+        assert!(stark.verify(proof, &mut None).is_err());
+    }
+
+    #[test]
+    fn prove_verify_interactively_test() {
+        let program = Program::from_code("push 1 write_io halt").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let stark = Stark::new(claim, StarkParameters::named("fast-dev").unwrap());
+
+        // The prover's channel hands out random challenges and records them together with the
+        // transcripts they were requested for.
+        let recorded = Rc::new(RefCell::new(vec![]));
+        let recorder = recorded.clone();
+        let prover_channel = move |transcript: &[BFieldElement]| {
+            let challenge = Digest::new(random_elements_array());
+            recorder.borrow_mut().push((transcript.to_vec(), challenge));
+            challenge
+        };
+        let proof = stark.prove_interactively(aet, Box::new(prover_channel), &mut None);
+
+        let replay = |recorded: Vec<(Vec<BFieldElement>, Digest)>| {
+            let mut recorded = recorded.into_iter();
+            move |transcript: &[BFieldElement]| {
+                let (prover_transcript, challenge) = recorded.next().unwrap();
+                assert_eq!(prover_transcript, transcript);
+                challenge
+            }
+        };
+        let verifier_channel = replay(recorded.borrow().clone());
+        let verdict =
+            stark.verify_interactively(proof.clone(), Box::new(verifier_channel), &mut None);
+        assert!(verdict.unwrap());
+
+        assert!(stark.verify(proof.clone(), &mut None).is_err());
+
+        let mut other_challenges = recorded.borrow().clone();
+        other_challenges[0].1 = Digest::new(random_elements_array());
+        let other_channel = replay(other_challenges);
+        let verdict = stark.verify_interactively(proof, Box::new(other_channel), &mut None);
+        assert!(verdict.is_err());
+    }
+
+    #[test]
+    fn prove_verify_program_with_entry_point_test() {
+        let code = "push 1 write_io halt second_entry: push 2 write_io halt";
+        let program = Program::from_code_with_entry_point(code, "second_entry").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        assert_eq!(vec![BFieldElement::new(2)], output);
+
+        let claim = Claim {
+            input: vec![],
+            program: program.to_bwords(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &program.to_bwords()),
+            entry_point: program.entry_point,
+        };
+        let stark = Stark::new(claim.clone(), StarkParameters::new(32, 4));
+        let proof = stark.prove(aet, &mut None);
+        assert!(stark.verify(proof.clone(), &mut None).unwrap());
+
+        let wrong_claim = Claim {
+            entry_point: 0,
+            ..claim
+        };
+        let stark = Stark::new(wrong_claim, StarkParameters::new(32, 4));
+        assert!(!matches!(stark.verify(proof, &mut None), Ok(true)));
+    }
+
     #[test]
     fn triton_prove_verify_halt_test() {
         let mut profiler = Some(TritonProfiler::new("Prove Halt"));
@@ -2,7 +2,6 @@ use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::Display;
 
-use anyhow::Result;
 use ndarray::Array1;
 use num_traits::One;
 use num_traits::Zero;
@@ -19,14 +18,17 @@ use twenty_first::shared_math::rescue_prime_regular::STATE_SIZE;
 use twenty_first::shared_math::traits::Inverse;
 use twenty_first::shared_math::x_field_element::XFieldElement;
 
-use crate::error::vm_err;
-use crate::error::vm_fail;
-use crate::error::InstructionError::*;
 use crate::op_stack::OpStack;
+use crate::ram::Ram;
 use crate::table::processor_table;
 use crate::table::processor_table::ProcessorMatrixRow;
 use crate::table::table_column::BaseTableColumn;
 use crate::table::table_column::ProcessorBaseTableColumn;
+use crate::vm::error::InstructionError;
+use crate::vm::error::InstructionError::*;
+use crate::vm::error::VMError;
+use crate::vm::stream::InputStream;
+use crate::vm::stream::SecretInputStream;
 
 /// The number of state registers for hashing-specific instructions.
 pub const STATE_REGISTER_COUNT: usize = 16;
@@ -43,7 +45,7 @@ pub struct VMState<'pgm> {
     pub program: &'pgm [Instruction],
 
     /// 2. **Random-access memory**, to which the VM can read and write field elements
-    pub ram: HashMap<BFieldElement, BFieldElement>,
+    pub ram: Ram,
 
     /// 3. **Op-stack memory**, which stores the part of the operational stack
     ///    that is not represented explicitly by the operational stack registers
@@ -68,6 +70,62 @@ pub struct VMState<'pgm> {
 
     /// RAM pointer
     pub ramp: u64,
+
+    /// Limits imposed by the embedder, not by the VM's semantics.
+    pub config: VMConfig,
+}
+
+/// Limits an embedder can impose on the VM, for example to bound the memory usage of runaway
+/// programs. By default, there are no limits. Exceeding a limit fails execution with a typed
+/// error, see [`InstructionError`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VMConfig {
+    /// The maximal number of elements on the op stack, including the op-stack registers.
+    pub max_op_stack_height: Option<usize>,
+}
+
+impl VMConfig {
+    /// Fail execution with [`OpStackOverflow`] as soon as the op stack holds more than the given
+    /// number of elements. The op stack always holds at least the 16 op-stack registers.
+    pub fn with_max_op_stack_height(mut self, max_op_stack_height: usize) -> Self {
+        self.max_op_stack_height = Some(max_op_stack_height);
+        self
+    }
+}
+
+/// The state of the VM after execution has halted or failed. Unlike a [`VMState`], it does not
+/// borrow the program, and only holds what is commonly read after execution: memory and registers.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TerminalState {
+    pub ram: HashMap<BFieldElement, BFieldElement>,
+    pub op_stack: OpStack,
+    pub jump_stack: Vec<(BFieldElement, BFieldElement)>,
+    pub cycle_count: u32,
+    pub instruction_pointer: usize,
+    pub ramp: u64,
+}
+
+impl TerminalState {
+    /// The value stored at the given RAM address. Uninitialized RAM holds 0.
+    pub fn read_ram(&self, address: BFieldElement) -> BFieldElement {
+        self.ram
+            .get(&address)
+            .copied()
+            .unwrap_or_else(BFieldElement::zero)
+    }
+}
+
+impl From<VMState<'_>> for TerminalState {
+    fn from(state: VMState<'_>) -> Self {
+        Self {
+            ram: state.ram.to_hash_map(),
+            op_stack: state.op_stack,
+            jump_stack: state.jump_stack,
+            cycle_count: state.cycle_count,
+            instruction_pointer: state.instruction_pointer,
+            ramp: state.ramp,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -89,13 +147,23 @@ impl<'pgm> VMState<'pgm> {
     /// inner helper functions refer to it, a read-only reference is kept in
     /// the struct.
     pub fn new(program: &'pgm Program) -> Self {
+        let instruction_pointer = program.entry_point;
         let program = &program.instructions;
         Self {
             program,
+            instruction_pointer,
             ..VMState::default()
         }
     }
 
+    /// Like [`new`](Self::new), but subject to the limits of the given [`VMConfig`].
+    pub fn new_with_config(program: &'pgm Program, config: VMConfig) -> Self {
+        Self {
+            config,
+            ..Self::new(program)
+        }
+    }
+
     /// Determine if this is a final state.
     pub fn is_complete(&self) -> bool {
         match self.current_instruction() {
@@ -107,15 +175,26 @@ impl<'pgm> VMState<'pgm> {
     /// Given a state, compute `(next_state, vm_output)`.
     pub fn step(
         &self,
-        stdin: &mut Vec<BFieldElement>,
-        secret_in: &mut Vec<BFieldElement>,
-    ) -> Result<(VMState<'pgm>, Option<VMOutput>)> {
+        stdin: &mut impl InputStream,
+        secret_in: &mut impl SecretInputStream,
+    ) -> Result<(VMState<'pgm>, Option<VMOutput>), VMError> {
         let mut next_state = self.clone();
         next_state
             .step_mut(stdin, secret_in)
             .map(|vm_output| (next_state, vm_output))
     }
 
+    /// A copy of this state whose RAM is shared copy-on-write with this state's RAM, which makes
+    /// forking cheap even for large RAM. To execute speculatively, step the fork and drop it to
+    /// roll back.
+    pub fn fork(&mut self) -> Self {
+        let ram = self.ram.fork();
+        Self {
+            ram,
+            ..self.clone()
+        }
+    }
+
     pub fn derive_helper_variables(&self) -> [BFieldElement; HV_REGISTER_COUNT] {
         let mut hvs = [BFieldElement::zero(); HV_REGISTER_COUNT];
 
@@ -158,9 +237,7 @@ impl<'pgm> VMState<'pgm> {
             }
             Split => {
                 let elem = self.op_stack.safe_peek(ST0);
-                let n: u64 = elem.value();
-                let lo = BFieldElement::new(n & 0xffff_ffff);
-                let hi = BFieldElement::new(n >> 32);
+                let (lo, hi) = Self::split_u64(elem);
                 if !lo.is_zero() {
                     let max_val_of_hi = BFieldElement::new(2_u64.pow(32) - 1);
                     hvs[0] = (hi - max_val_of_hi).inverse_or_zero();
@@ -178,11 +255,43 @@ impl<'pgm> VMState<'pgm> {
     }
 
     /// Perform the state transition as a mutable operation on `self`.
+    ///
+    /// This is the one authoritative state transition function of Triton VM. All means of
+    /// executing a program, like [`step`](Self::step) or [`execute`](crate::vm::execute), build
+    /// on it.
     pub fn step_mut(
         &mut self,
-        stdin: &mut Vec<BFieldElement>,
-        secret_in: &mut Vec<BFieldElement>,
-    ) -> Result<Option<VMOutput>> {
+        stdin: &mut impl InputStream,
+        secret_in: &mut impl SecretInputStream,
+    ) -> Result<Option<VMOutput>, VMError> {
+        let instruction_pointer = self.instruction_pointer;
+        let cycle_count = self.cycle_count;
+        let instruction = self.current_instruction().ok();
+        self.execute_current_instruction(stdin, secret_in)
+            .map_err(|kind| VMError {
+                kind,
+                instruction_pointer,
+                cycle_count,
+                instruction,
+            })
+    }
+
+    /// The failure of an execution in this state, for errors that do not stem from executing an
+    /// instruction, like exceeding a cycle limit.
+    pub(crate) fn vm_error(&self, kind: InstructionError) -> VMError {
+        VMError {
+            kind,
+            instruction_pointer: self.instruction_pointer,
+            cycle_count: self.cycle_count,
+            instruction: self.current_instruction().ok(),
+        }
+    }
+
+    fn execute_current_instruction(
+        &mut self,
+        stdin: &mut impl InputStream,
+        secret_in: &mut impl SecretInputStream,
+    ) -> Result<Option<VMOutput>, InstructionError> {
         // All instructions increase the cycle count
         self.cycle_count += 1;
         let mut vm_output = None;
@@ -206,26 +315,37 @@ impl<'pgm> VMState<'pgm> {
             Divine(hint) => {
                 use DivinationHint::*;
 
-                let elem = if let Some(context) = hint {
+                let divined = match (hint, secret_in.diviner()) {
+                    (Some(hint), Some(diviner)) => diviner.divine_hinted(hint, &self.op_stack),
+                    _ => None,
+                };
+                let elem = if let Some(divined) = divined {
+                    divined
+                } else if let Some(context) = hint {
                     match context {
                         Quotient => {
-                            let numerator: u32 = self
-                                .op_stack
-                                .safe_peek(ST0)
-                                .value()
+                            let numerator = self.op_stack.safe_peek(ST0);
+                            let numerator: u32 = numerator
                                 .try_into()
-                                .expect("Numerator uses more than 32 bits.");
-                            let denominator: u32 = self
-                                .op_stack
-                                .safe_peek(ST1)
-                                .value()
+                                .map_err(|_| FailedU32Conversion(numerator))?;
+                            let denominator = self.op_stack.safe_peek(ST1);
+                            let denominator: u32 = denominator
                                 .try_into()
-                                .expect("Denominator uses more than 32 bits.");
+                                .map_err(|_| FailedU32Conversion(denominator))?;
+                            if denominator == 0 {
+                                return Err(DivisionByZero);
+                            }
                             BFieldElement::new((numerator / denominator) as u64)
                         }
+                        QuotientU64Hi => BFieldElement::new(self.u64_division(0)?.0 >> 32),
+                        QuotientU64Lo => BFieldElement::new(self.u64_division(1)?.0 & 0xffff_ffff),
+                        RemainderU64Hi => BFieldElement::new(self.u64_division(2)?.1 >> 32),
+                        RemainderU64Lo => BFieldElement::new(self.u64_division(3)?.1 & 0xffff_ffff),
                     }
                 } else {
-                    secret_in.remove(0)
+                    secret_in
+                        .divine_at(self.instruction_pointer)
+                        .ok_or(SecretInputExhausted)?
                 };
                 self.op_stack.push(elem);
                 self.instruction_pointer += 1;
@@ -264,6 +384,12 @@ impl<'pgm> VMState<'pgm> {
                 self.instruction_pointer = addr.value() as usize;
             }
 
+            TailCall(addr) => {
+                let (orig_addr, _dest_addr) = self.jump_stack_pop()?;
+                self.jump_stack.push((orig_addr, addr));
+                self.instruction_pointer = addr.value() as usize;
+            }
+
             Return => {
                 let (orig_addr, _dest_addr) = self.jump_stack_pop()?;
                 self.instruction_pointer = orig_addr.value() as usize;
@@ -277,11 +403,7 @@ impl<'pgm> VMState<'pgm> {
             Assert => {
                 let elem = self.op_stack.pop()?;
                 if !elem.is_one() {
-                    return vm_err(AssertionFailed(
-                        self.instruction_pointer,
-                        self.cycle_count,
-                        elem,
-                    ));
+                    return Err(AssertionFailed(elem));
                 }
                 self.instruction_pointer += 1;
             }
@@ -307,6 +429,28 @@ impl<'pgm> VMState<'pgm> {
                 self.instruction_pointer += 1;
             }
 
+            ReadMemInc => {
+                let ramp = self.op_stack.safe_peek(ST1);
+                let ramv = self.memory_get(&ramp);
+                self.op_stack.pop()?;
+                self.op_stack.pop()?;
+                self.op_stack.push(ramp + BFieldElement::one());
+                self.op_stack.push(ramv);
+                self.ramp = ramp.value();
+                self.instruction_pointer += 1;
+            }
+
+            WriteMemInc => {
+                let ramp = self.op_stack.safe_peek(ST1);
+                let ramv = self.op_stack.pop()?;
+                self.ram.insert(ramp, ramv);
+                self.op_stack.pop()?;
+                self.op_stack.push(ramp + BFieldElement::one());
+                self.op_stack.push(ramv);
+                self.ramp = ramp.value();
+                self.instruction_pointer += 1;
+            }
+
             Hash => {
                 let hash_input: [BFieldElement; 2 * DIGEST_LENGTH] = self.op_stack.pop_n()?;
                 let hash_trace = RescuePrimeRegular::trace(&hash_input);
@@ -331,9 +475,7 @@ impl<'pgm> VMState<'pgm> {
 
             AssertVector => {
                 if !self.assert_vector() {
-                    return vm_err(AssertionFailed(
-                        self.instruction_pointer,
-                        self.cycle_count,
+                    return Err(AssertionFailed(
                         self.op_stack
                             .peek(0)
                             .expect("Could not unwrap top of stack."),
@@ -359,7 +501,7 @@ impl<'pgm> VMState<'pgm> {
             Invert => {
                 let elem = self.op_stack.pop()?;
                 if elem.is_zero() {
-                    return vm_err(InverseOfZero);
+                    return Err(InverseOfZero);
                 }
                 self.op_stack.push(elem.inverse());
                 self.instruction_pointer += 1;
@@ -367,9 +509,7 @@ impl<'pgm> VMState<'pgm> {
 
             Split => {
                 let elem = self.op_stack.pop()?;
-                let n: u64 = elem.value();
-                let lo = BFieldElement::new(n & 0xffff_ffff);
-                let hi = BFieldElement::new(n >> 32);
+                let (lo, hi) = Self::split_u64(elem);
                 self.op_stack.push(lo);
                 self.op_stack.push(hi);
                 self.instruction_pointer += 1;
@@ -384,39 +524,40 @@ impl<'pgm> VMState<'pgm> {
 
             Lsb => {
                 let top = self.op_stack.pop()?;
-                let lsb = BFieldElement::new(top.value() & 1);
-                self.op_stack.push(BFieldElement::new(top.value() >> 1));
+                let (shifted, lsb) = Self::lsb_semantics(top);
+                self.op_stack.push(shifted);
                 self.op_stack.push(lsb);
                 self.instruction_pointer += 1;
             }
 
             XxAdd => {
-                let lhs: XFieldElement = self.op_stack.pop_x()?;
-                let rhs: XFieldElement = self.op_stack.safe_peek_x();
-                self.op_stack.push_x(lhs + rhs);
+                let lhs = self.op_stack.pop_n()?;
+                let rhs = self.op_stack.safe_peek_x().coefficients;
+                let sum = Self::xfe_add_on_stack(lhs, rhs);
+                self.op_stack.push_x(XFieldElement::new(sum));
                 self.instruction_pointer += 1;
             }
 
             XxMul => {
-                let lhs: XFieldElement = self.op_stack.pop_x()?;
-                let rhs: XFieldElement = self.op_stack.safe_peek_x();
-                self.op_stack.push_x(lhs * rhs);
+                let lhs = self.op_stack.pop_n()?;
+                let rhs = self.op_stack.safe_peek_x().coefficients;
+                let product = Self::xfe_mul_on_stack(lhs, rhs);
+                self.op_stack.push_x(XFieldElement::new(product));
                 self.instruction_pointer += 1;
             }
 
             XInvert => {
-                let elem: XFieldElement = self.op_stack.pop_x()?;
-                if elem.is_zero() {
-                    return vm_err(InverseOfZero);
-                }
-                self.op_stack.push_x(elem.inverse());
+                let elem = self.op_stack.pop_n()?;
+                let inverse = Self::xfe_invert_on_stack(elem)?;
+                self.op_stack.push_x(XFieldElement::new(inverse));
                 self.instruction_pointer += 1;
             }
 
             XbMul => {
-                let lhs: BFieldElement = self.op_stack.pop()?;
-                let rhs: XFieldElement = self.op_stack.pop_x()?;
-                self.op_stack.push_x(lhs.lift() * rhs);
+                let lhs = self.op_stack.pop()?;
+                let rhs = self.op_stack.pop_n()?;
+                let product = Self::xb_mul_on_stack(lhs, rhs);
+                self.op_stack.push_x(XFieldElement::new(product));
                 self.instruction_pointer += 1;
             }
 
@@ -426,7 +567,7 @@ impl<'pgm> VMState<'pgm> {
             }
 
             ReadIo => {
-                let in_elem = stdin.remove(0);
+                let in_elem = stdin.read().ok_or(StandardInputExhausted)?;
                 self.op_stack.push(in_elem);
                 self.instruction_pointer += 1;
             }
@@ -434,7 +575,13 @@ impl<'pgm> VMState<'pgm> {
 
         // Check that no instruction left the OpStack with too few elements
         if self.op_stack.is_too_shallow() {
-            return vm_err(OpStackTooShallow);
+            return Err(OpStackTooShallow);
+        }
+
+        if let Some(max_op_stack_height) = self.config.max_op_stack_height {
+            if self.op_stack.height() > max_op_stack_height {
+                return Err(OpStackOverflow(max_op_stack_height));
+            }
         }
 
         Ok(vm_output)
@@ -491,7 +638,8 @@ impl<'pgm> VMState<'pgm> {
         row
     }
 
-    fn eq(lhs: BFieldElement, rhs: BFieldElement) -> BFieldElement {
+    /// The semantics of instruction `eq`: 1 if the operands are equal, 0 otherwise.
+    pub fn eq(lhs: BFieldElement, rhs: BFieldElement) -> BFieldElement {
         if lhs == rhs {
             BFieldElement::one()
         } else {
@@ -499,6 +647,59 @@ impl<'pgm> VMState<'pgm> {
         }
     }
 
+    /// The semantics of instruction `split`: the lower and the upper 32 bits of the canonical
+    /// representative of `element`, in the order they are pushed. That is, `hi` ends up on top.
+    pub fn split_u64(element: BFieldElement) -> (BFieldElement, BFieldElement) {
+        let n: u64 = element.value();
+        let lo = BFieldElement::new(n & 0xffff_ffff);
+        let hi = BFieldElement::new(n >> 32);
+        (lo, hi)
+    }
+
+    /// The semantics of instruction `lsb`: the canonical representative of `element` shifted right
+    /// by one bit, and its least significant bit, in the order they are pushed. That is, the least
+    /// significant bit ends up on top.
+    pub fn lsb_semantics(element: BFieldElement) -> (BFieldElement, BFieldElement) {
+        let n = element.value();
+        (BFieldElement::new(n >> 1), BFieldElement::new(n & 1))
+    }
+
+    /// The semantics of instruction `xxadd` on the top 6 stack elements. Element `i` of each operand
+    /// is stack element `st{i}` of the respective extension field element, which coincides with
+    /// its `i`th coefficient. The result replaces the operand `lhs`, which is on top of the stack.
+    pub fn xfe_add_on_stack(
+        lhs: [BFieldElement; 3],
+        rhs: [BFieldElement; 3],
+    ) -> [BFieldElement; 3] {
+        (XFieldElement::new(lhs) + XFieldElement::new(rhs)).coefficients
+    }
+
+    /// The semantics of instruction `xxmul`. See [`xfe_add_on_stack`](Self::xfe_add_on_stack) for
+    /// the layout of the operands.
+    pub fn xfe_mul_on_stack(
+        lhs: [BFieldElement; 3],
+        rhs: [BFieldElement; 3],
+    ) -> [BFieldElement; 3] {
+        (XFieldElement::new(lhs) * XFieldElement::new(rhs)).coefficients
+    }
+
+    /// The semantics of instruction `xbmul`, where `scalar` is stack element `st0` and `xfe` are
+    /// stack elements `st1` through `st3`.
+    pub fn xb_mul_on_stack(scalar: BFieldElement, xfe: [BFieldElement; 3]) -> [BFieldElement; 3] {
+        (scalar.lift() * XFieldElement::new(xfe)).coefficients
+    }
+
+    /// The semantics of instruction `xinvert`. Fails for the zero element, like the instruction.
+    pub fn xfe_invert_on_stack(
+        xfe: [BFieldElement; 3],
+    ) -> Result<[BFieldElement; 3], InstructionError> {
+        let xfe = XFieldElement::new(xfe);
+        if xfe.is_zero() {
+            return Err(InverseOfZero);
+        }
+        Ok(xfe.inverse().coefficients)
+    }
+
     fn nia(&self) -> BFieldElement {
         self.current_instruction()
             .map(|curr_instr| {
@@ -532,10 +733,10 @@ impl<'pgm> VMState<'pgm> {
             .unwrap_or_else(BFieldElement::zero)
     }
 
-    pub fn current_instruction(&self) -> Result<Instruction> {
+    pub fn current_instruction(&self) -> Result<Instruction, InstructionError> {
         self.program
             .get(self.instruction_pointer)
-            .ok_or_else(|| vm_fail(InstructionPointerOverflow(self.instruction_pointer)))
+            .ok_or(InstructionPointerOverflow(self.instruction_pointer))
             .copied()
     }
 
@@ -545,43 +746,35 @@ impl<'pgm> VMState<'pgm> {
     // since the current instruction could be a jump, but it is either
     // program[ip + 1] or program[ip + 2] depending on whether the current
     // instruction takes an argument or not.
-    pub fn next_instruction(&self) -> Result<Instruction> {
+    pub fn next_instruction(&self) -> Result<Instruction, InstructionError> {
         let ci = self.current_instruction()?;
         let ci_size = ci.size();
         let ni_pointer = self.instruction_pointer + ci_size;
         self.program
             .get(ni_pointer)
-            .ok_or_else(|| vm_fail(InstructionPointerOverflow(ni_pointer)))
+            .ok_or(InstructionPointerOverflow(ni_pointer))
             .copied()
     }
 
-    fn _next_next_instruction(&self) -> Result<Instruction> {
+    fn _next_next_instruction(&self) -> Result<Instruction, InstructionError> {
         let cur_size = self.current_instruction()?.size();
         let next_size = self.next_instruction()?.size();
         self.program
             .get(self.instruction_pointer + cur_size + next_size)
-            .ok_or_else(|| vm_fail(InstructionPointerOverflow(self.instruction_pointer)))
+            .ok_or(InstructionPointerOverflow(self.instruction_pointer))
             .copied()
     }
 
-    fn jump_stack_pop(&mut self) -> Result<(BFieldElement, BFieldElement)> {
-        self.jump_stack
-            .pop()
-            .ok_or_else(|| vm_fail(JumpStackTooShallow))
+    fn jump_stack_pop(&mut self) -> Result<(BFieldElement, BFieldElement), InstructionError> {
+        self.jump_stack.pop().ok_or(JumpStackTooShallow)
     }
 
-    fn jump_stack_peek(&mut self) -> Result<(BFieldElement, BFieldElement)> {
-        self.jump_stack
-            .last()
-            .copied()
-            .ok_or_else(|| vm_fail(JumpStackTooShallow))
+    fn jump_stack_peek(&mut self) -> Result<(BFieldElement, BFieldElement), InstructionError> {
+        self.jump_stack.last().copied().ok_or(JumpStackTooShallow)
     }
 
     fn memory_get(&self, mem_addr: &BFieldElement) -> BFieldElement {
-        self.ram
-            .get(mem_addr)
-            .copied()
-            .unwrap_or_else(BFieldElement::zero)
+        self.ram.get(*mem_addr)
     }
 
     fn assert_vector(&self) -> bool {
@@ -599,7 +792,7 @@ impl<'pgm> VMState<'pgm> {
         true
     }
 
-    pub fn read_word(&self) -> Result<Option<BFieldElement>> {
+    pub fn read_word(&self) -> Result<Option<BFieldElement>, InstructionError> {
         let current_instruction = self.current_instruction()?;
         if matches!(current_instruction, ReadIo) {
             Ok(Some(self.op_stack.safe_peek(ST0)))
@@ -608,7 +801,31 @@ impl<'pgm> VMState<'pgm> {
         }
     }
 
-    fn divine_sibling(&mut self, secret_in: &mut Vec<BFieldElement>) -> Result<()> {
+    /// The quotient and remainder of dividing the u64s `_ d_hi d_lo n_hi n_lo`, each given as its
+    /// high and low limb, which lie below the given number of already divined limbs.
+    fn u64_division(&self, num_divined_limbs: usize) -> Result<(u64, u64), InstructionError> {
+        let limb = |depth: usize| -> Result<u64, InstructionError> {
+            let element = self
+                .op_stack
+                .peek(num_divined_limbs + depth)
+                .ok_or(OpStackTooShallow)?;
+            let limb: u32 = element
+                .try_into()
+                .map_err(|_| FailedU32Conversion(element))?;
+            Ok(limb as u64)
+        };
+        let denominator = (limb(3)? << 32) | limb(2)?;
+        let numerator = (limb(1)? << 32) | limb(0)?;
+        if denominator == 0 {
+            return Err(DivisionByZero);
+        }
+        Ok((numerator / denominator, numerator % denominator))
+    }
+
+    fn divine_sibling(
+        &mut self,
+        secret_in: &mut impl SecretInputStream,
+    ) -> Result<(), InstructionError> {
         // st0-st4
         let _ = self.op_stack.pop_n::<DIGEST_LENGTH>()?;
 
@@ -619,19 +836,23 @@ impl<'pgm> VMState<'pgm> {
         let node_index_elem: BFieldElement = self.op_stack.pop()?;
         let node_index: u32 = node_index_elem
             .try_into()
-            .unwrap_or_else(|_| panic!("{:?} is not a u32", node_index_elem));
-
-        // nondeterministic guess, flipped
-        let sibling_digest: [BFieldElement; DIGEST_LENGTH] = {
-            let mut tmp = [
-                secret_in.remove(0),
-                secret_in.remove(0),
-                secret_in.remove(0),
-                secret_in.remove(0),
-                secret_in.remove(0),
-            ];
-            tmp.reverse();
-            tmp
+            .map_err(|_| FailedU32Conversion(node_index_elem))?;
+
+        // nondeterministic guess, flipped if read from secret input
+        let divined = secret_in
+            .diviner()
+            .and_then(|diviner| diviner.divine_sibling(node_index, known_digest));
+        let sibling_digest = match divined {
+            Some(sibling_digest) => sibling_digest,
+            None => {
+                let mut sibling_digest = [BFieldElement::zero(); DIGEST_LENGTH];
+                for element in sibling_digest.iter_mut().rev() {
+                    *element = secret_in
+                        .divine_at(self.instruction_pointer)
+                        .ok_or(SecretInputExhausted)?;
+                }
+                sibling_digest
+            }
         };
 
         // least significant bit
@@ -720,7 +941,7 @@ mod vm_state_tests {
     #[test]
     fn run_tvm_parse_pop_p_test() {
         let program = Program::from_code("push 1 push 1 add pop").unwrap();
-        let (trace, _out, _err) = run(&program, vec![], vec![]);
+        let (trace, _out) = run(&program, vec![], vec![]).unwrap();
 
         for state in trace.iter() {
             println!("{}", state);
@@ -749,7 +970,7 @@ mod vm_state_tests {
             write_io write_io write_io write_io write_io write_io write_io
         ";
         let program = Program::from_code(code).unwrap();
-        let (trace, _out, _err) = run(&program, vec![], vec![]);
+        let (trace, _out) = run(&program, vec![], vec![]).unwrap();
 
         let last_state = trace.last().unwrap();
         assert_eq!(BFieldElement::zero(), last_state.op_stack.safe_peek(ST0));
@@ -761,14 +982,11 @@ mod vm_state_tests {
     fn run_tvm_halt_then_do_stuff_test() {
         let halt_then_do_stuff = "halt push 1 push 2 add invert write_io";
         let program = Program::from_code(halt_then_do_stuff).unwrap();
-        let (trace, _out, err) = run(&program, vec![], vec![]);
+        let (trace, _out) = run(&program, vec![], vec![]).unwrap();
 
         for state in trace.iter() {
             println!("{}", state);
         }
-        if let Some(e) = err {
-            println!("Error: {}", e);
-        }
 
         // check for graceful termination
         let last_state = trace.last().unwrap();
@@ -788,10 +1006,7 @@ mod vm_state_tests {
             halt
             ";
         let program = Program::from_code(basic_ram_read_write_code).unwrap();
-        let (trace, _out, err) = run(&program, vec![], vec![]);
-        if let Some(e) = err {
-            println!("Error: {}", e);
-        }
+        let (trace, _out) = run(&program, vec![], vec![]).unwrap();
 
         let last_state = trace.last().expect("Execution seems to have failed.");
         let five = BFieldElement::new(5);
@@ -802,8 +1017,8 @@ mod vm_state_tests {
         assert_eq!(five, last_state.op_stack.st(ST1));
         assert_eq!(sixteen, last_state.op_stack.st(ST2));
         assert_eq!(fifteen, last_state.op_stack.st(ST3));
-        assert_eq!(last_state.ram[&five], seven);
-        assert_eq!(last_state.ram[&fifteen], sixteen);
+        assert_eq!(last_state.ram.get(five), seven);
+        assert_eq!(last_state.ram.get(fifteen), sixteen);
     }
 
     #[test]
@@ -816,10 +1031,7 @@ mod vm_state_tests {
             halt
         ";
         let program = Program::from_code(edgy_ram_writes_code).unwrap();
-        let (trace, _out, err) = run(&program, vec![], vec![]);
-        if let Some(e) = err {
-            println!("Error: {}", e);
-        }
+        let (trace, _out) = run(&program, vec![], vec![]).unwrap();
 
         let last_state = trace.last().expect("Execution seems to have failed.");
         let zero = BFieldElement::zero();
@@ -828,8 +1040,8 @@ mod vm_state_tests {
         assert_eq!(three, last_state.op_stack.st(ST0));
         assert_eq!(five, last_state.op_stack.st(ST1));
         assert_eq!(three, last_state.op_stack.st(ST2));
-        assert_eq!(last_state.ram[&zero], zero);
-        assert_eq!(last_state.ram[&five], three);
+        assert_eq!(last_state.ram.get(zero), zero);
+        assert_eq!(last_state.ram.get(five), three);
     }
 
     #[test]
@@ -864,14 +1076,11 @@ mod vm_state_tests {
         let program = Program::from_code(sample_weights_code).unwrap();
         println!("Successfully parsed the program.");
         let input_symbols = vec![BFieldElement::new(11)];
-        let (trace, _out, err) = run(&program, input_symbols, vec![]);
+        let (trace, _out) = run(&program, input_symbols, vec![]).unwrap();
 
         for state in trace.iter() {
             println!("{}", state);
         }
-        if let Some(e) = err {
-            panic!("The VM encountered an error: {}", e);
-        }
 
         // check for graceful termination
         let last_state = trace.last().unwrap();
@@ -1022,14 +1231,11 @@ mod vm_state_tests {
             leafs[55].values()[order[4]],
         ];
 
-        let (trace, _out, err) = run(&program, input, secret_input);
+        let (trace, _out) = run(&program, input, secret_input).unwrap();
 
         for state in trace.iter() {
             println!("{}", state);
         }
-        if let Some(e) = err {
-            panic!("The VM encountered an error: {}", e);
-        }
 
         // check for graceful termination
         let last_state = trace.last().unwrap();
@@ -1054,14 +1260,11 @@ mod vm_state_tests {
         let program = Program::from_code(get_colinear_y_code).unwrap();
         println!("Successfully parsed the program.");
         let input_symbols = [7, 2, 1, 3, 4].map(BFieldElement::new).to_vec();
-        let (trace, out, err) = run(&program, input_symbols, vec![]);
+        let (trace, out) = run(&program, input_symbols, vec![]).unwrap();
         assert_eq!(out[0], BFieldElement::new(4));
         for state in trace.iter() {
             println!("{}", state);
         }
-        if let Some(e) = err {
-            panic!("The VM encountered an error: {}", e);
-        }
 
         // check for graceful termination
         let last_state = trace.last().unwrap();
@@ -1087,17 +1290,13 @@ mod vm_state_tests {
             ";
 
         let program = Program::from_code(countdown_code).unwrap();
-        let (trace, out, err) = run(&program, vec![], vec![]);
+        let (trace, out) = run(&program, vec![], vec![]).unwrap();
 
         println!("{}", program);
         for state in trace.iter() {
             println!("{}", state);
         }
 
-        if let Some(e) = err {
-            panic!("The VM encountered an error: {e}");
-        }
-
         let expected = (0..=10).map(BFieldElement::new).rev().collect_vec();
         assert_eq!(expected, out);
     }
@@ -1106,19 +1305,16 @@ mod vm_state_tests {
     fn run_tvm_fibonacci_vit_tvm() {
         let code = FIBONACCI_VIT;
         let program = Program::from_code(code).unwrap();
-        let (_trace, out, err) = run(&program, vec![7_u64.into()], vec![]);
-        if let Some(e) = err {
-            panic!("The VM encountered an error: {e}");
-        }
+        let (_trace, out) = run(&program, vec![7_u64.into()], vec![]).unwrap();
 
-        assert_eq!(Some(&BFieldElement::new(21)), out.get(0));
+        assert_eq!(Some(&BFieldElement::new(21)), out.first());
     }
 
     #[test]
     fn run_tvm_fibonacci_lt_test() {
         let code = FIB_FIXED_7_LT;
         let program = Program::from_code(code).unwrap();
-        let (trace, _out, _err) = run(&program, vec![], vec![]);
+        let (trace, _out) = run(&program, vec![], vec![]).unwrap();
         let last_state = trace.last().unwrap();
         assert_eq!(BFieldElement::new(21), last_state.op_stack.st(ST0));
     }
@@ -1129,7 +1325,7 @@ mod vm_state_tests {
         let program = Program::from_code(code).unwrap();
 
         println!("{}", program);
-        let (trace, out, _err) = run(&program, vec![42_u64.into(), 56_u64.into()], vec![]);
+        let (trace, out) = run(&program, vec![42_u64.into(), 56_u64.into()], vec![]).unwrap();
 
         println!("{}", program);
         for state in trace.iter() {
@@ -1145,14 +1341,84 @@ mod vm_state_tests {
     fn run_tvm_swap_test() {
         let code = "push 1 push 2 swap1 halt";
         let program = Program::from_code(code).unwrap();
-        let (_trace, _out, _err) = run(&program, vec![], vec![]);
+        let (_trace, _out) = run(&program, vec![], vec![]).unwrap();
+    }
+
+    #[test]
+    fn speculative_execution_on_fork_can_be_rolled_back_test() {
+        let code = "push 0 call loop halt loop: push 1 add dup0 write_mem pop recurse";
+        let program = Program::from_code(code).unwrap();
+        let mut state = VMState::new(&program);
+        for _ in 0..100 {
+            state.step_mut(&mut vec![], &mut vec![]).unwrap();
+        }
+        let ram_before_speculation = state.ram.to_hash_map();
+
+        let mut speculative_state = state.fork();
+        for _ in 0..1000 {
+            speculative_state
+                .step_mut(&mut vec![], &mut vec![])
+                .unwrap();
+        }
+        assert_eq!(state.cycle_count + 1000, speculative_state.cycle_count);
+        assert_eq!(ram_before_speculation, state.ram.to_hash_map());
+        assert_ne!(state.ram, speculative_state.ram);
+
+        for _ in 0..1000 {
+            state.step_mut(&mut vec![], &mut vec![]).unwrap();
+        }
+        assert_eq!(speculative_state.ram, state.ram);
+        assert_eq!(speculative_state.op_stack, state.op_stack);
+        assert_eq!(speculative_state.jump_stack, state.jump_stack);
+    }
+
+    #[test]
+    fn semantics_helpers_agree_with_execution_test() {
+        let largest = BFieldElement::new(BFieldElement::MAX);
+        assert_eq!(
+            (BFieldElement::zero(), BFieldElement::new(0xffff_ffff)),
+            VMState::split_u64(largest)
+        );
+        assert_eq!(
+            (
+                BFieldElement::new(BFieldElement::MAX >> 1),
+                BFieldElement::zero()
+            ),
+            VMState::lsb_semantics(largest)
+        );
+        assert!(VMState::xfe_invert_on_stack([BFieldElement::zero(); 3]).is_err());
+
+        let lhs: [BFieldElement; 3] = random_elements_array();
+        let rhs: [BFieldElement; 3] = random_elements_array();
+        let push =
+            |xfe: [BFieldElement; 3]| xfe.iter().rev().map(|e| format!("push {e}")).join(" ");
+        let write = "write_io write_io write_io";
+        let code = format!(
+            "{rhs} {lhs} xxmul {write} {lhs} xxadd {write} {lhs} xinvert {write} \
+            push {scalar} xbmul {write} halt",
+            rhs = push(rhs),
+            lhs = push(lhs),
+            scalar = lhs[0],
+        );
+        let program = Program::from_code(&code).unwrap();
+        let (_trace, output) = run(&program, vec![], vec![]).unwrap();
+
+        let inverse = VMState::xfe_invert_on_stack(lhs).unwrap();
+        let expected_output = [
+            VMState::xfe_mul_on_stack(lhs, rhs),
+            VMState::xfe_add_on_stack(lhs, rhs),
+            inverse,
+            VMState::xb_mul_on_stack(lhs[0], rhs),
+        ]
+        .concat();
+        assert_eq!(expected_output, output);
     }
 
     #[test]
     fn read_mem_unitialized() {
         let program = Program::from_code("read_mem halt").unwrap();
-        let (trace, _out, err) = run(&program, vec![], vec![]);
-        assert!(err.is_none(), "Reading from uninitialized memory address");
+        let (trace, _out) =
+            run(&program, vec![], vec![]).expect("Reading from uninitialized memory address");
         assert_eq!(2, trace.len());
     }
 }
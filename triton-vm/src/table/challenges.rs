@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::fmt::Display;
 use std::hash::Hash;
 
+use num_traits::Zero;
 use strum::EnumCount;
 use strum::IntoEnumIterator;
 use twenty_first::shared_math::b_field_element::BFieldElement;
@@ -59,19 +60,26 @@ pub struct AllChallenges {
 }
 
 impl AllChallenges {
-    pub const TOTAL_CHALLENGES: usize = 46 + NUM_CROSS_TABLE_WEIGHTS;
+    pub const TOTAL_CHALLENGES: usize = 47 + NUM_CROSS_TABLE_WEIGHTS;
 
     pub fn create_challenges(
         mut weights: Vec<XFieldElement>,
         claimed_input: &[BFieldElement],
         claimed_output: &[BFieldElement],
+        claimed_output_tags: &[BFieldElement],
+        claimed_entry_point: usize,
     ) -> Self {
+        // For untagged output, the running evaluation must not depend on the stack's content.
+        let standard_output_tag_weight = match claimed_output_tags.is_empty() {
+            true => weights.pop().map(|_| XFieldElement::zero()).unwrap(),
+            false => weights.pop().unwrap(),
+        };
         let processor_table_challenges = ProcessorTableChallenges {
             standard_input_eval_indeterminate: weights.pop().unwrap(),
             standard_output_eval_indeterminate: weights.pop().unwrap(),
             to_hash_table_eval_indeterminate: weights.pop().unwrap(),
             from_hash_table_eval_indeterminate: weights.pop().unwrap(),
-            instruction_perm_indeterminate: weights.pop().unwrap(),
+            instruction_lookup_indeterminate: weights.pop().unwrap(),
             op_stack_perm_indeterminate: weights.pop().unwrap(),
             ram_perm_indeterminate: weights.pop().unwrap(),
             jump_stack_perm_indeterminate: weights.pop().unwrap(),
@@ -115,6 +123,10 @@ impl AllChallenges {
             hash_table_digest_output_weight2: weights.pop().unwrap(),
             hash_table_digest_output_weight3: weights.pop().unwrap(),
             hash_table_digest_output_weight4: weights.pop().unwrap(),
+
+            standard_output_tag_weight,
+
+            entry_point: BFieldElement::new(claimed_entry_point as u64).lift(),
         };
 
         let program_table_challenges = ProgramTableChallenges {
@@ -125,7 +137,8 @@ impl AllChallenges {
         };
 
         let instruction_table_challenges = InstructionTableChallenges {
-            processor_perm_indeterminate: processor_table_challenges.instruction_perm_indeterminate,
+            processor_lookup_indeterminate: processor_table_challenges
+                .instruction_lookup_indeterminate,
             ip_processor_weight: processor_table_challenges.instruction_table_ip_weight,
             ci_processor_weight: processor_table_challenges.instruction_table_ci_processor_weight,
             nia_processor_weight: processor_table_challenges.instruction_table_nia_weight,
@@ -208,11 +221,22 @@ impl AllChallenges {
             EvalArg::default_initial(),
             processor_table_challenges.standard_input_eval_indeterminate,
         );
-        let output_terminal = EvalArg::compute_terminal(
-            claimed_output,
-            EvalArg::default_initial(),
-            processor_table_challenges.standard_output_eval_indeterminate,
-        );
+        let output_terminal = match claimed_output_tags.is_empty() {
+            true => EvalArg::compute_terminal(
+                claimed_output,
+                EvalArg::default_initial(),
+                processor_table_challenges.standard_output_eval_indeterminate,
+            ),
+            false => claimed_output.iter().zip(claimed_output_tags).fold(
+                EvalArg::default_initial(),
+                |running_evaluation, (&output, &tag)| {
+                    processor_table_challenges.standard_output_eval_indeterminate
+                        * running_evaluation
+                        + output
+                        + tag * standard_output_tag_weight
+                },
+            ),
+        };
 
         let cross_table_challenges = CrossTableChallenges {
             input_terminal,
@@ -245,13 +269,15 @@ impl AllChallenges {
         }
     }
 
-    /// Stand-in challenges. Can be used in tests. For non-interactive STARKs, use Fiat-Shamir to
-    /// derive the actual challenges.
+    /// Stand-in challenges for programs with entry point 0. Can be used in tests. For
+    /// non-interactive STARKs, use Fiat-Shamir to derive the actual challenges.
     pub fn placeholder(claimed_input: &[BFieldElement], claimed_output: &[BFieldElement]) -> Self {
         Self::create_challenges(
             random_elements(Self::TOTAL_CHALLENGES),
             claimed_input,
             claimed_output,
+            &[],
+            0,
         )
     }
 }
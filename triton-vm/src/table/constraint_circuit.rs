@@ -579,7 +579,7 @@ impl<T: TableChallenges, II: InputIndicator> ConstraintCircuit<T, II> {
                 let lhs_counters = lhs.as_ref().borrow().get_all_visited_counters();
                 let rhs_counters = rhs.as_ref().borrow().get_all_visited_counters();
                 let own_counter = self.visited_counter;
-                let mut all = vec![vec![own_counter], lhs_counters, rhs_counters].concat();
+                let mut all = [vec![own_counter], lhs_counters, rhs_counters].concat();
                 all.sort_unstable();
                 all.dedup();
                 all.reverse();
@@ -626,6 +626,26 @@ impl<T: TableChallenges, II: InputIndicator> ConstraintCircuit<T, II> {
         }
     }
 
+    /// Return true iff the evaluation value of this node depends on the extension table
+    pub fn references_ext_table(&self) -> bool {
+        match &self.expression {
+            Input(input) => !input.is_base_table_row(),
+            BinaryOperation(_, lhs, rhs) => {
+                lhs.as_ref().borrow().references_ext_table()
+                    || rhs.as_ref().borrow().references_ext_table()
+            }
+            _ => false,
+        }
+    }
+
+    /// Evaluate a circuit that neither is [randomized](Self::is_randomized) nor
+    /// [references the extension table](Self::references_ext_table) on the base table alone.
+    pub fn evaluate_on_base_table(&self, base_table: ArrayView2<BFieldElement>) -> XFieldElement {
+        debug_assert!(!self.is_randomized() && !self.references_ext_table());
+        let no_ext_table = ndarray::Array2::zeros([0, 0]);
+        self.evaluate_inner(base_table, no_ext_table.view())
+    }
+
     /// Replace all challenges with constants in subtree
     fn apply_challenges_to_one_root(&mut self, challenges: &T) {
         match &self.expression {
@@ -998,7 +1018,7 @@ mod constraint_circuit_tests {
                 2 => {
                     // p(x, y, z) = rand_i
                     circuit_builder
-                        .challenge(InstructionTableChallengeId::ProcessorPermIndeterminate)
+                        .challenge(InstructionTableChallengeId::ProcessorLookupIndeterminate)
                 }
                 3 => {
                     // p(x, y, z) = 0
@@ -1012,7 +1032,7 @@ mod constraint_circuit_tests {
                     // p(x, y, z) = rand_i * x
                     let input_value =
                         DualRowIndicator::CurrentExtRow(rng.next_u64() as usize % num_ext_columns);
-                    let challenge = InstructionTableChallengeId::ProcessorPermIndeterminate;
+                    let challenge = InstructionTableChallengeId::ProcessorLookupIndeterminate;
                     circuit_builder.input(input_value) * circuit_builder.challenge(challenge)
                 }
                 _ => unreachable!(),
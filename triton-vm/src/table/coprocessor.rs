@@ -0,0 +1,321 @@
+//! Scaffolding for tables that assist the processor, like the Op Stack Table or the Hash Table.
+//!
+//! Adding a coprocessor table touches many places. In order:
+//! 1. Add a variant to [`TableId`], and the table's base and extension columns to
+//!    [`table_column`](crate::table::table_column), including their master table indices.
+//! 1. Account for the table's widths and its height in [`master_table`]: the column ranges,
+//!    [`MasterBaseTable::unpadded_height`], and the calls to fill, pad, and extend the table.
+//! 1. Add the table's challenges to [`AllChallenges`].
+//! 1. Write the table's constraints as circuits, register them with the
+//!    constraint-evaluation-generator, and run it.
+//! 1. Link the table to the processor with a cross-table argument, and add its terminals to the
+//!    [`GrandCrossTableArg`](crate::table::cross_table_argument::GrandCrossTableArg).
+//! 1. Implement [`CoprocessorTable`] for the table, and invoke [`coprocessor_table_tests`] in the
+//!    table's test module.
+//!
+//! The generated tests fill, pad, and extend the table using only the functions given in the
+//! [`CoprocessorTable`] implementation, then check that the table's constraints hold and that the
+//! terminals of its cross-table arguments match those of the processor. They do so for every
+//! program of the test corpus, both with minimal and with excessive padding. Another generated
+//! test proves a program that never invokes the coprocessor, for which an empty table must not be
+//! committed to.
+
+use ndarray::ArrayView2;
+use ndarray::ArrayViewMut2;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+
+use crate::table::challenges::AllChallenges;
+use crate::table::extension_table::Evaluable;
+use crate::table::extension_table::Quotientable;
+use crate::table::master_table::TableId;
+use crate::table::table_column::MasterExtTableColumn;
+use crate::table::table_column::ProcessorExtTableColumn;
+use crate::vm::AlgebraicExecutionTrace;
+
+/// A table whose content is derived from the [`AlgebraicExecutionTrace`], and which is linked to
+/// the Processor Table through cross-table arguments.
+pub trait CoprocessorTable {
+    const ID: TableId;
+
+    /// The table's extension columns, for naming the columns of the cross-table arguments.
+    type ExtColumn: MasterExtTableColumn;
+
+    /// The table's constraints, usually generated by the constraint-evaluation-generator.
+    type Constraints: Evaluable + Quotientable;
+
+    /// Fill the table's base columns from the execution trace. The table is all-zero and has the
+    /// padded height.
+    fn fill(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace);
+
+    /// Pad the filled table. Rows beyond the execution trace are all-zero.
+    fn pad(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace);
+
+    /// Fill the table's extension columns.
+    fn extend(
+        base_table: ArrayView2<BFieldElement>,
+        ext_table: ArrayViewMut2<XFieldElement>,
+        challenges: &AllChallenges,
+    );
+
+    /// Pairs of extension columns, one of the Processor Table and one of this table, whose
+    /// terminals must be identical.
+    fn cross_table_arguments() -> Vec<(ProcessorExtTableColumn, Self::ExtColumn)>;
+}
+
+/// Generates tests for a table implementing [`CoprocessorTable`]. Invoke in the table's test
+/// module, giving the table's type.
+#[macro_export]
+macro_rules! coprocessor_table_tests {
+    ($table:ty) => {
+        #[test]
+        fn fill_then_check_on_corpus_test() {
+            $crate::table::coprocessor::checks::check_constraints_on_corpus::<$table>(1);
+        }
+
+        #[test]
+        fn pad_then_check_on_corpus_test() {
+            $crate::table::coprocessor::checks::check_constraints_on_corpus::<$table>(4);
+        }
+
+        #[test]
+        fn cross_table_arguments_match_on_corpus_test() {
+            $crate::table::coprocessor::checks::check_cross_table_arguments_on_corpus::<$table>();
+        }
+
+        #[test]
+        fn prove_verify_program_skipping_coprocessor_test() {
+            $crate::table::coprocessor::checks::check_program_skipping_coprocessor::<$table>();
+        }
+    };
+}
+
+/// The checks run by the tests that [`coprocessor_table_tests`] generates.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod checks {
+    use itertools::Itertools;
+    use num_traits::Zero;
+    use triton_opcodes::program::Program;
+    use twenty_first::shared_math::b_field_element::BFieldElement;
+    use twenty_first::shared_math::x_field_element::XFieldElement;
+
+    use crate::proof::Claim;
+    use crate::stark::Stark;
+    use crate::stark::StarkParameters;
+    use crate::table::challenges::AllChallenges;
+    use crate::table::coprocessor::CoprocessorTable;
+    use crate::table::extension_table::Evaluable;
+    use crate::table::master_table::MasterBaseTable;
+    use crate::table::master_table::MasterExtTable;
+    use crate::table::master_table::MasterTable;
+    use crate::table::table_column::MasterExtTableColumn;
+    use crate::test_corpus::bigger_tasm_test_programs;
+    use crate::test_corpus::property_based_test_programs;
+    use crate::test_corpus::small_tasm_test_programs;
+    use crate::vm::simulate;
+
+    /// Check the table's constraints on the traces of all programs of the test corpus. The padded
+    /// height is the smallest possible one, multiplied by the given factor.
+    pub fn check_constraints_on_corpus<T: CoprocessorTable>(padding_factor: usize) {
+        for (program_idx, (base_table, ext_table, challenges)) in
+            corpus_master_tables::<T>(padding_factor)
+                .into_iter()
+                .enumerate()
+        {
+            let base_table = base_table.trace_table();
+            let ext_table = ext_table.trace_table();
+            let num_rows = base_table.nrows();
+            let fail = |constraint_type: &str, row_idx: usize, constraint_idx: usize| -> ! {
+                panic!(
+                    "{} violates {constraint_type} constraint {constraint_idx} in row {row_idx} \
+                    of {num_rows} for program #{program_idx} of the test corpus.",
+                    T::ID
+                )
+            };
+
+            let initial_constraints = T::Constraints::evaluate_initial_constraints(
+                base_table.row(0),
+                ext_table.row(0),
+                &challenges,
+            );
+            if let Some(idx) = initial_constraints.iter().position(|c| !c.is_zero()) {
+                fail("initial", 0, idx);
+            }
+
+            for row_idx in 0..num_rows {
+                let consistency_constraints = T::Constraints::evaluate_consistency_constraints(
+                    base_table.row(row_idx),
+                    ext_table.row(row_idx),
+                    &challenges,
+                );
+                if let Some(idx) = consistency_constraints.iter().position(|c| !c.is_zero()) {
+                    fail("consistency", row_idx, idx);
+                }
+            }
+
+            for row_idx in 0..num_rows - 1 {
+                let transition_constraints = T::Constraints::evaluate_transition_constraints(
+                    base_table.row(row_idx),
+                    ext_table.row(row_idx),
+                    base_table.row(row_idx + 1),
+                    ext_table.row(row_idx + 1),
+                    &challenges,
+                );
+                if let Some(idx) = transition_constraints.iter().position(|c| !c.is_zero()) {
+                    fail("transition", row_idx, idx);
+                }
+            }
+
+            let terminal_constraints = T::Constraints::evaluate_terminal_constraints(
+                base_table.row(num_rows - 1),
+                ext_table.row(num_rows - 1),
+                &challenges,
+            );
+            if let Some(idx) = terminal_constraints.iter().position(|c| !c.is_zero()) {
+                fail("terminal", num_rows - 1, idx);
+            }
+        }
+    }
+
+    /// Check that the terminals of the table's cross-table arguments match those of the Processor
+    /// Table for all programs of the test corpus.
+    pub fn check_cross_table_arguments_on_corpus<T: CoprocessorTable>() {
+        for (program_idx, (_, ext_table, _)) in corpus_master_tables::<T>(1).into_iter().enumerate()
+        {
+            let ext_table = ext_table.trace_table();
+            let last_row = ext_table.row(ext_table.nrows() - 1);
+            for (processor_column, table_column) in T::cross_table_arguments() {
+                assert_eq!(
+                    last_row[processor_column.master_ext_table_index()],
+                    last_row[table_column.master_ext_table_index()],
+                    "Terminals of {processor_column} and the {} must match \
+                    for program #{program_idx} of the test corpus.",
+                    T::ID
+                );
+            }
+        }
+    }
+
+    /// Prove and verify a program that invokes no coprocessor. If the table is empty and its
+    /// padding rows are known in advance, its columns must be constant and not committed to.
+    pub fn check_program_skipping_coprocessor<T: CoprocessorTable>() {
+        let program = Program::from_code("push 1 push 2 add pop halt").unwrap();
+        let (aet, output) = simulate(&program, vec![], vec![]).unwrap();
+        let instructions = program.to_bwords();
+        let claim = Claim {
+            input: vec![],
+            program: instructions.clone(),
+            output: output.into(),
+            output_tags: vec![],
+            padded_height: MasterBaseTable::padded_height(&aet, &instructions),
+            entry_point: program.entry_point,
+        };
+        let stark = Stark::new(claim, StarkParameters::new(32, 4));
+
+        let mut base_table = MasterBaseTable::new(
+            aet.clone(),
+            &instructions,
+            stark.parameters.num_trace_randomizers,
+            stark.fri.domain,
+        );
+        base_table.pad();
+        let is_empty = MasterBaseTable::unpadded_height(T::ID, &aet, &instructions) == 0;
+        let empty_base_row = MasterBaseTable::empty_table_base_row(T::ID);
+        let is_skipped = base_table.empty_tables.contains(&T::ID);
+        assert_eq!(is_empty && empty_base_row.is_some(), is_skipped);
+
+        if is_skipped {
+            let challenges = AllChallenges::placeholder(&stark.claim.input, &stark.claim.output);
+            let mut ext_table =
+                base_table.extend(&challenges, stark.parameters.num_randomizer_polynomials);
+            base_table.randomize_trace();
+            ext_table.randomize_trace();
+            let base_table = base_table.to_fri_domain_table();
+            let ext_table = ext_table.to_fri_domain_table();
+
+            let empty_ext_row = MasterExtTable::empty_table_ext_row(T::ID, &challenges).unwrap();
+            let base_columns = base_table.empty_table_columns();
+            let ext_columns = ext_table.empty_table_columns();
+            for (col_idx, value) in base_columns.into_iter().zip_eq(empty_base_row.unwrap()) {
+                assert!(!base_table.committed_columns().contains(&col_idx));
+                assert!(base_table
+                    .master_base_matrix
+                    .column(col_idx)
+                    .iter()
+                    .all(|&v| v == value));
+            }
+            for (col_idx, value) in ext_columns.into_iter().zip_eq(empty_ext_row) {
+                assert!(!ext_table.committed_columns().contains(&col_idx));
+                assert!(ext_table
+                    .master_ext_matrix
+                    .column(col_idx)
+                    .iter()
+                    .all(|&v| v == value));
+            }
+        }
+
+        let proof = stark.prove(aet, &mut None);
+        assert!(stark.verify(proof, &mut None).unwrap());
+    }
+
+    /// The master tables of all programs of the test corpus. The section of table `T` is filled,
+    /// padded, and extended exclusively by the functions of `T`'s [`CoprocessorTable`]
+    /// implementation.
+    fn corpus_master_tables<T: CoprocessorTable>(
+        padding_factor: usize,
+    ) -> Vec<(MasterBaseTable, MasterExtTable, AllChallenges)> {
+        let mut corpus = small_tasm_test_programs();
+        corpus.append(&mut bigger_tasm_test_programs());
+        corpus.append(&mut property_based_test_programs());
+
+        let mut master_tables = vec![];
+        for code_with_input in corpus {
+            let program = Program::from_code(&code_with_input.source_code).unwrap();
+            let (aet, output) = simulate(
+                &program,
+                code_with_input.input.clone(),
+                code_with_input.secret_input,
+            )
+            .unwrap_or_else(|err| panic!("The test corpus' programs must not crash: {err}"));
+
+            let instructions = program.to_bwords();
+            let padded_height =
+                MasterBaseTable::padded_height(&aet, &instructions) * padding_factor;
+            let claim = Claim {
+                input: code_with_input.input,
+                program: instructions,
+                output: output.into(),
+                output_tags: vec![],
+                padded_height,
+                entry_point: program.entry_point,
+            };
+            let stark = Stark::new(claim, StarkParameters::new(32, 4));
+
+            let mut base_table = MasterBaseTable::new_with_padded_height(
+                aet.clone(),
+                &stark.claim.program,
+                padded_height,
+                stark.parameters.num_trace_randomizers,
+                stark.fri.domain,
+            );
+            base_table.pad();
+            let mut table = base_table.table_mut(T::ID);
+            table.fill(BFieldElement::zero());
+            T::fill(&mut table, &aet);
+            T::pad(&mut table, &aet);
+
+            let challenges = AllChallenges::placeholder(&stark.claim.input, &stark.claim.output);
+            let mut ext_table =
+                base_table.extend(&challenges, stark.parameters.num_randomizer_polynomials);
+            ext_table.table_mut(T::ID).fill(XFieldElement::zero());
+            T::extend(
+                base_table.table(T::ID),
+                ext_table.table_mut(T::ID),
+                &challenges,
+            );
+
+            master_tables.push((base_table, ext_table, challenges));
+        }
+        master_tables
+    }
+}
@@ -1,12 +1,15 @@
+use std::ops::Add;
 use std::ops::Mul;
 
 use ndarray::ArrayView1;
 use num_traits::One;
+use num_traits::Zero;
 use strum_macros::Display;
 use strum_macros::EnumCount as EnumCountMacro;
 use strum_macros::EnumIter;
 use twenty_first::shared_math::b_field_element::BFieldElement;
 use twenty_first::shared_math::mpolynomial::Degree;
+use twenty_first::shared_math::traits::Inverse;
 use twenty_first::shared_math::x_field_element::XFieldElement;
 
 use CrossTableChallengeId::*;
@@ -15,6 +18,7 @@ use crate::table::challenges::AllChallenges;
 use crate::table::challenges::TableChallenges;
 use crate::table::extension_table::Evaluable;
 use crate::table::extension_table::Quotientable;
+use crate::table::processor_table::PROCESSOR_TABLE_NUM_LOOKUP_ARGUMENTS;
 use crate::table::processor_table::PROCESSOR_TABLE_NUM_PERMUTATION_ARGUMENTS;
 use crate::table::table_column::HashExtTableColumn;
 use crate::table::table_column::InstructionExtTableColumn;
@@ -27,7 +31,9 @@ use crate::table::table_column::RamExtTableColumn;
 
 pub const NUM_PRIVATE_PERM_ARGS: usize = PROCESSOR_TABLE_NUM_PERMUTATION_ARGUMENTS;
 pub const NUM_PRIVATE_EVAL_ARGS: usize = 3;
-pub const NUM_CROSS_TABLE_ARGS: usize = NUM_PRIVATE_PERM_ARGS + NUM_PRIVATE_EVAL_ARGS;
+pub const NUM_PRIVATE_LOOKUP_ARGS: usize = PROCESSOR_TABLE_NUM_LOOKUP_ARGUMENTS;
+pub const NUM_CROSS_TABLE_ARGS: usize =
+    NUM_PRIVATE_PERM_ARGS + NUM_PRIVATE_EVAL_ARGS + NUM_PRIVATE_LOOKUP_ARGS;
 pub const NUM_PUBLIC_EVAL_ARGS: usize = 2;
 pub const NUM_CROSS_TABLE_WEIGHTS: usize = NUM_CROSS_TABLE_ARGS + NUM_PUBLIC_EVAL_ARGS;
 
@@ -92,6 +98,35 @@ impl CrossTableArg for EvalArg {
     }
 }
 
+/// A lookup argument via logarithmic derivatives. The client accumulates `1 / (x - symbol)` for
+/// every symbol it looks up; the server accumulates `multiplicity / (x - symbol)` for every
+/// symbol it provides, with the multiplicity being the number of times the symbol is looked up.
+/// The terminals are equal if and only if (with high probability) every looked-up symbol is
+/// provided by the server. The server's table height is independent of the number of lookups.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LookupArg {}
+
+impl CrossTableArg for LookupArg {
+    fn default_initial() -> XFieldElement {
+        XFieldElement::zero()
+    }
+
+    /// Compute the logarithmic derivative for a lookup argument as specified by `initial`,
+    /// `challenge`, and `symbols`. This amounts to evaluating
+    /// `f(x) = initial + Σ_i 1 / (x - symbols[i])`
+    /// at point `challenge`, i.e., returns `f(challenge)`.
+    fn compute_terminal(
+        symbols: &[BFieldElement],
+        initial: XFieldElement,
+        challenge: XFieldElement,
+    ) -> XFieldElement {
+        symbols
+            .iter()
+            .map(|&symbol| (challenge - symbol).inverse())
+            .fold(initial, XFieldElement::add)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct GrandCrossTableArg {}
 
@@ -201,8 +236,10 @@ impl Evaluable for GrandCrossTableArg {
             [ProgramExtTableColumn::RunningEvaluation.master_ext_table_index()]
             - ext_row[InstructionExtTableColumn::RunningEvaluation.master_ext_table_index()];
         let processor_to_instruction = ext_row
-            [ProcessorExtTableColumn::InstructionTablePermArg.master_ext_table_index()]
-            - ext_row[InstructionExtTableColumn::RunningProductPermArg.master_ext_table_index()];
+            [ProcessorExtTableColumn::InstructionLookupClientLogDerivative
+                .master_ext_table_index()]
+            - ext_row[InstructionExtTableColumn::InstructionLookupServerLogDerivative
+                .master_ext_table_index()];
         let processor_to_op_stack = ext_row
             [ProcessorExtTableColumn::OpStackTablePermArg.master_ext_table_index()]
             - ext_row[OpStackExtTableColumn::RunningProductPermArg.master_ext_table_index()];
@@ -17,6 +17,7 @@ use twenty_first::shared_math::rescue_prime_regular::ROUND_CONSTANTS;
 use twenty_first::shared_math::rescue_prime_regular::STATE_SIZE;
 use twenty_first::shared_math::x_field_element::XFieldElement;
 
+use crate::table::challenges::AllChallenges;
 use crate::table::challenges::TableChallenges;
 use crate::table::constraint_circuit::ConstraintCircuit;
 use crate::table::constraint_circuit::ConstraintCircuitBuilder;
@@ -25,9 +26,11 @@ use crate::table::constraint_circuit::DualRowIndicator;
 use crate::table::constraint_circuit::DualRowIndicator::*;
 use crate::table::constraint_circuit::SingleRowIndicator;
 use crate::table::constraint_circuit::SingleRowIndicator::*;
+use crate::table::coprocessor::CoprocessorTable;
 use crate::table::cross_table_argument::CrossTableArg;
 use crate::table::cross_table_argument::EvalArg;
 use crate::table::hash_table::HashTableChallengeId::*;
+use crate::table::master_table::TableId;
 use crate::table::master_table::NUM_BASE_COLUMNS;
 use crate::table::master_table::NUM_EXT_COLUMNS;
 use crate::table::table_column::BaseTableColumn;
@@ -38,6 +41,7 @@ use crate::table::table_column::HashExtTableColumn;
 use crate::table::table_column::HashExtTableColumn::*;
 use crate::table::table_column::MasterBaseTableColumn;
 use crate::table::table_column::MasterExtTableColumn;
+use crate::table::table_column::ProcessorExtTableColumn;
 use crate::vm::AlgebraicExecutionTrace;
 
 pub const HASH_TABLE_NUM_PERMUTATION_ARGUMENTS: usize = 0;
@@ -102,7 +106,7 @@ impl ExtHashTable {
             challenge(StackInputWeight9),
         ]
         .into_iter()
-        .zip_eq(state.into_iter())
+        .zip_eq(state)
         .map(|(w, s)| s * w)
         .sum();
         let from_processor_indeterminate = challenge(FromProcessorEvalIndeterminate);
@@ -349,7 +353,7 @@ impl ExtHashTable {
 
         let hash_function_round_correctly_performs_update = after_constants
             .into_iter()
-            .zip_eq(before_sbox.into_iter())
+            .zip_eq(before_sbox)
             .map(|(lhs, rhs)| {
                 round_number.clone()
                     * (round_number.clone() - constant(NUM_ROUNDS as u64 + 1))
@@ -380,7 +384,7 @@ impl ExtHashTable {
         .map(|w| circuit_builder.challenge(w));
         let compressed_row_from_processor = xlix_input
             .into_iter()
-            .zip_eq(stack_input_weights.into_iter())
+            .zip_eq(stack_input_weights)
             .map(|(state, weight)| weight * state)
             .sum();
 
@@ -410,7 +414,7 @@ impl ExtHashTable {
         .map(|w| circuit_builder.challenge(w));
         let compressed_row_to_processor = xlix_digest
             .into_iter()
-            .zip_eq(digest_output_weights.into_iter())
+            .zip_eq(digest_output_weights)
             .map(|(state, weight)| weight * state)
             .sum();
         let running_evaluation_to_processor_updates = running_evaluation_to_processor_next
@@ -552,6 +556,41 @@ impl HashTable {
     }
 }
 
+impl CoprocessorTable for HashTable {
+    const ID: TableId = TableId::HashTable;
+    type ExtColumn = HashExtTableColumn;
+    type Constraints = ExtHashTable;
+
+    fn fill(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        HashTable::fill_trace(table, aet);
+    }
+
+    fn pad(table: &mut ArrayViewMut2<BFieldElement>, _aet: &AlgebraicExecutionTrace) {
+        HashTable::pad_trace(table);
+    }
+
+    fn extend(
+        base_table: ArrayView2<BFieldElement>,
+        ext_table: ArrayViewMut2<XFieldElement>,
+        challenges: &AllChallenges,
+    ) {
+        HashTable::extend(base_table, ext_table, &challenges.hash_table_challenges);
+    }
+
+    fn cross_table_arguments() -> Vec<(ProcessorExtTableColumn, Self::ExtColumn)> {
+        vec![
+            (
+                ProcessorExtTableColumn::ToHashTableEvalArg,
+                FromProcessorRunningEvaluation,
+            ),
+            (
+                ProcessorExtTableColumn::FromHashTableEvalArg,
+                ToProcessorRunningEvaluation,
+            ),
+        ]
+    }
+}
+
 #[derive(Debug, Copy, Clone, Display, EnumCountMacro, EnumIter, PartialEq, Eq, Hash)]
 pub enum HashTableChallengeId {
     FromProcessorEvalIndeterminate,
@@ -640,6 +679,7 @@ impl TableChallenges for HashTableChallenges {
 mod constraint_tests {
     use num_traits::Zero;
 
+    use crate::coprocessor_table_tests;
     use crate::stark::triton_stark_tests::parse_simulate_pad_extend;
     use crate::table::extension_table::Evaluable;
     use crate::table::master_table::MasterTable;
@@ -720,4 +760,17 @@ mod constraint_tests {
             assert!(v.is_zero(), "Terminal constraint {idx} failed.");
         }
     }
+
+    #[test]
+    fn hash_table_is_committed_to_only_if_not_empty_test() {
+        let (_, _, master_base_table, _, _) =
+            parse_simulate_pad_extend("push 1 pop halt", vec![], vec![]);
+        assert_eq!(vec![TableId::HashTable], master_base_table.empty_tables);
+
+        let (_, _, master_base_table, _, _) =
+            parse_simulate_pad_extend("hash halt", vec![], vec![]);
+        assert!(master_base_table.empty_tables.is_empty());
+    }
+
+    coprocessor_table_tests!(HashTable);
 }
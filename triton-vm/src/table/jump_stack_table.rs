@@ -1,8 +1,8 @@
-use std::cmp::Ordering;
-
+use itertools::Itertools;
 use ndarray::parallel::prelude::*;
 use ndarray::s;
 use ndarray::Array1;
+use ndarray::Array2;
 use ndarray::ArrayView1;
 use ndarray::ArrayView2;
 use ndarray::ArrayViewMut2;
@@ -22,6 +22,7 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 use JumpStackTableChallengeId::*;
 
+use crate::table::challenges::AllChallenges;
 use crate::table::challenges::TableChallenges;
 use crate::table::constraint_circuit::ConstraintCircuit;
 use crate::table::constraint_circuit::ConstraintCircuitBuilder;
@@ -29,8 +30,10 @@ use crate::table::constraint_circuit::DualRowIndicator;
 use crate::table::constraint_circuit::DualRowIndicator::*;
 use crate::table::constraint_circuit::SingleRowIndicator;
 use crate::table::constraint_circuit::SingleRowIndicator::*;
+use crate::table::coprocessor::CoprocessorTable;
 use crate::table::cross_table_argument::CrossTableArg;
 use crate::table::cross_table_argument::PermArg;
+use crate::table::master_table::TableId;
 use crate::table::master_table::NUM_BASE_COLUMNS;
 use crate::table::master_table::NUM_EXT_COLUMNS;
 use crate::table::table_column::BaseTableColumn;
@@ -42,6 +45,7 @@ use crate::table::table_column::JumpStackExtTableColumn::*;
 use crate::table::table_column::MasterBaseTableColumn;
 use crate::table::table_column::MasterExtTableColumn;
 use crate::table::table_column::ProcessorBaseTableColumn;
+use crate::table::table_column::ProcessorExtTableColumn;
 use crate::vm::AlgebraicExecutionTrace;
 
 pub const JUMP_STACK_TABLE_NUM_PERMUTATION_ARGUMENTS: usize = 1;
@@ -117,6 +121,8 @@ impl ExtJumpStackTable {
         let one = circuit_builder.b_constant(1u32.into());
         let call_opcode =
             circuit_builder.b_constant(Instruction::Call(Default::default()).opcode_b());
+        let tail_call_opcode =
+            circuit_builder.b_constant(Instruction::TailCall(Default::default()).opcode_b());
         let return_opcode = circuit_builder.b_constant(Instruction::Return.opcode_b());
 
         let clk = circuit_builder.input(CurrentBaseRow(CLK.master_base_table_index()));
@@ -163,9 +169,11 @@ impl ExtJumpStackTable {
 
         // 3. The jump stack pointer jsp increases by 1
         //      or current instruction ci is return
+        //      or current instruction ci is tail_call
         //      or the jump stack destination jsd does not change
-        let jsp_inc_or_jsd_stays_or_ci_ret =
-            jsp_inc_by_one_or_ci_is_return * (jsd_next.clone() - jsd);
+        let jsp_inc_or_jsd_stays_or_ci_ret_or_ci_tail_call = jsp_inc_by_one_or_ci_is_return
+            * (ci.clone() - tail_call_opcode)
+            * (jsd_next.clone() - jsd);
 
         // 4. The jump stack pointer jsp increases by 1
         //      or the cycle count clk increases by 1
@@ -227,7 +235,7 @@ impl ExtJumpStackTable {
         [
             jsp_inc_or_stays,
             jsp_inc_or_jso_stays_or_ci_is_ret,
-            jsp_inc_or_jsd_stays_or_ci_ret,
+            jsp_inc_or_jsd_stays_or_ci_ret_or_ci_tail_call,
             jsp_inc_or_clk_inc_or_ci_call_or_ci_ret,
             clkdi_is_zero_or_clkdi_is_inverse_of_clock_diff_minus_one_or_jsp_changes,
             clock_diff_minus_one_is_zero_or_clock_diff_minus_one_is_clkdi_inverse_or_jsp_changes,
@@ -249,60 +257,58 @@ impl ExtJumpStackTable {
 }
 
 impl JumpStackTable {
-    /// Fills the trace table in-place and returns all clock jump differences greater than 1.
+    /// The row of the Jump Stack Table recording the jump stack registers of the given row of the
+    /// Processor Table. The auxiliary column is set by [`sort_trace`](Self::sort_trace).
+    pub fn row_of(processor_row: ArrayView1<BFieldElement>) -> Array1<BFieldElement> {
+        let mut row = Array1::zeros(BASE_WIDTH);
+        for (column, processor_column) in [
+            (CLK, ProcessorBaseTableColumn::CLK),
+            (CI, ProcessorBaseTableColumn::CI),
+            (JSP, ProcessorBaseTableColumn::JSP),
+            (JSO, ProcessorBaseTableColumn::JSO),
+            (JSD, ProcessorBaseTableColumn::JSD),
+        ] {
+            row[column.base_table_index()] = processor_row[processor_column.base_table_index()];
+        }
+        row
+    }
+
+    /// Sort the rows of an unpadded trace by JSP first and CLK second, given rows sorted by CLK,
+    /// and set the auxiliary column.
+    pub fn sort_trace(trace: &mut Array2<BFieldElement>) {
+        let mut row_indices = (0..trace.nrows()).collect::<Vec<_>>();
+        row_indices.sort_by_key(|&row_idx| trace[(row_idx, JSP.base_table_index())].value());
+        *trace = trace.select(Axis(0), &row_indices);
+
+        for row_idx in 0..trace.nrows().saturating_sub(1) {
+            let clk_diff = trace[(row_idx + 1, CLK.base_table_index())]
+                - trace[(row_idx, CLK.base_table_index())];
+            let clk_diff_minus_1 = clk_diff - BFieldElement::one();
+            trace[(row_idx, InverseOfClkDiffMinusOne.base_table_index())] =
+                clk_diff_minus_1.inverse_or_zero();
+        }
+        if let Some(mut last_row) = trace.rows_mut().into_iter().last() {
+            last_row[InverseOfClkDiffMinusOne.base_table_index()] = BFieldElement::zero();
+        }
+    }
+
+    /// Fills the trace table in-place from the execution trace's
+    /// [jump stack matrix](AlgebraicExecutionTrace::jump_stack_matrix) and returns all clock jump
+    /// differences greater than 1.
     pub fn fill_trace(
         jump_stack_table: &mut ArrayViewMut2<BFieldElement>,
         aet: &AlgebraicExecutionTrace,
     ) -> Vec<BFieldElement> {
-        // Store the registers relevant for the Jump Stack Table, i.e., CLK, CI, JSP, JSO, JSD,
-        // with JSP as the key. Preserves, thus allows reusing, the order of the processor's
-        // rows, which are sorted by CLK.
-        let mut pre_processed_jump_stack_table: Vec<Vec<_>> = vec![];
-        for processor_row in aet.processor_matrix.rows() {
-            let clk = processor_row[ProcessorBaseTableColumn::CLK.base_table_index()];
-            let ci = processor_row[ProcessorBaseTableColumn::CI.base_table_index()];
-            let jsp = processor_row[ProcessorBaseTableColumn::JSP.base_table_index()];
-            let jso = processor_row[ProcessorBaseTableColumn::JSO.base_table_index()];
-            let jsd = processor_row[ProcessorBaseTableColumn::JSD.base_table_index()];
-            // The (honest) prover can only grow the Jump Stack's size by at most 1 per execution
-            // step. Hence, the following (a) works, and (b) sorts.
-            let jsp_val = jsp.value() as usize;
-            let jump_stack_row = (clk, ci, jso, jsd);
-            match jsp_val.cmp(&pre_processed_jump_stack_table.len()) {
-                Ordering::Less => pre_processed_jump_stack_table[jsp_val].push(jump_stack_row),
-                Ordering::Equal => pre_processed_jump_stack_table.push(vec![jump_stack_row]),
-                Ordering::Greater => panic!("JSP must increase by at most 1 per execution step."),
-            }
-        }
-
-        // Move the rows into the Jump Stack Table, sorted by JSP first, CLK second.
-        let mut jump_stack_table_row = 0;
-        for (jsp_val, rows_with_this_jsp) in pre_processed_jump_stack_table.into_iter().enumerate()
-        {
-            let jsp = BFieldElement::new(jsp_val as u64);
-            for (clk, ci, jso, jsd) in rows_with_this_jsp {
-                jump_stack_table[(jump_stack_table_row, CLK.base_table_index())] = clk;
-                jump_stack_table[(jump_stack_table_row, CI.base_table_index())] = ci;
-                jump_stack_table[(jump_stack_table_row, JSP.base_table_index())] = jsp;
-                jump_stack_table[(jump_stack_table_row, JSO.base_table_index())] = jso;
-                jump_stack_table[(jump_stack_table_row, JSD.base_table_index())] = jsd;
-                jump_stack_table_row += 1;
-            }
-        }
-        assert_eq!(aet.processor_matrix.nrows(), jump_stack_table_row);
+        let num_rows = aet.jump_stack_matrix.nrows();
+        assert_eq!(aet.processor_matrix.nrows(), num_rows);
+        jump_stack_table
+            .slice_mut(s![..num_rows, ..])
+            .assign(&aet.jump_stack_matrix);
 
-        // Set inverse of (clock difference - 1). Also, collect all clock jump differences
-        // greater than 1.
         // The Jump Stack Table and the Processor Table have the same length.
         let mut clock_jump_differences_greater_than_1 = vec![];
-        for row_idx in 0..aet.processor_matrix.nrows() - 1 {
-            let (mut curr_row, next_row) =
-                jump_stack_table.multi_slice_mut((s![row_idx, ..], s![row_idx + 1, ..]));
+        for (curr_row, next_row) in aet.jump_stack_matrix.rows().into_iter().tuple_windows() {
             let clk_diff = next_row[CLK.base_table_index()] - curr_row[CLK.base_table_index()];
-            let clk_diff_minus_1 = clk_diff - BFieldElement::one();
-            let clk_diff_minus_1_inverse = clk_diff_minus_1.inverse_or_zero();
-            curr_row[InverseOfClkDiffMinusOne.base_table_index()] = clk_diff_minus_1_inverse;
-
             if curr_row[JSP.base_table_index()] == next_row[JSP.base_table_index()]
                 && clk_diff.value() > 1
             {
@@ -442,6 +448,39 @@ impl JumpStackTable {
     }
 }
 
+impl CoprocessorTable for JumpStackTable {
+    const ID: TableId = TableId::JumpStackTable;
+    type ExtColumn = JumpStackExtTableColumn;
+    type Constraints = ExtJumpStackTable;
+
+    fn fill(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        JumpStackTable::fill_trace(table, aet);
+    }
+
+    fn pad(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        JumpStackTable::pad_trace(table, aet.processor_matrix.nrows());
+    }
+
+    fn extend(
+        base_table: ArrayView2<BFieldElement>,
+        ext_table: ArrayViewMut2<XFieldElement>,
+        challenges: &AllChallenges,
+    ) {
+        JumpStackTable::extend(
+            base_table,
+            ext_table,
+            &challenges.jump_stack_table_challenges,
+        );
+    }
+
+    fn cross_table_arguments() -> Vec<(ProcessorExtTableColumn, Self::ExtColumn)> {
+        vec![(
+            ProcessorExtTableColumn::JumpStackTablePermArg,
+            RunningProductPermArg,
+        )]
+    }
+}
+
 #[derive(Debug, Copy, Clone, Display, EnumCountMacro, EnumIter, PartialEq, Eq, Hash)]
 pub enum JumpStackTableChallengeId {
     ProcessorPermRowIndeterminate,
@@ -514,3 +553,11 @@ impl Display for JumpStackMatrixRow {
         )
     }
 }
+
+#[cfg(test)]
+mod jump_stack_table_tests {
+    use crate::coprocessor_table_tests;
+    use crate::table::jump_stack_table::JumpStackTable;
+
+    coprocessor_table_tests!(JumpStackTable);
+}
@@ -1,6 +1,7 @@
-use std::cmp::max;
 use std::ops::MulAssign;
 
+use anyhow::bail;
+use anyhow::Result;
 use itertools::Itertools;
 use ndarray::parallel::prelude::*;
 use ndarray::prelude::*;
@@ -10,10 +11,12 @@ use ndarray::ArrayView2;
 use ndarray::ArrayViewMut2;
 use ndarray::Zip;
 use num_traits::One;
+use num_traits::Zero;
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use rand::random;
 use strum::EnumCount;
+use strum::IntoEnumIterator;
 use strum_macros::Display;
 use strum_macros::EnumCount as EnumCountMacro;
 use strum_macros::EnumIter;
@@ -37,6 +40,9 @@ use twenty_first::util_types::merkle_tree_maker::MerkleTreeMaker;
 use crate::arithmetic_domain::ArithmeticDomain;
 use crate::stark::StarkHasher;
 use crate::table::challenges::AllChallenges;
+use crate::table::challenges::TableChallenges;
+use crate::table::constraint_circuit::ConstraintCircuit;
+use crate::table::constraint_circuit::DualRowIndicator;
 use crate::table::cross_table_argument::GrandCrossTableArg;
 use crate::table::extension_table::DegreeWithOrigin;
 use crate::table::extension_table::Evaluable;
@@ -132,36 +138,36 @@ pub enum TableId {
 /// Conceptually, there are three Master Tables: the Master Base Table, the Master Extension
 /// Table, and the Master Quotient Table. The lifecycle of the Master Tables is as follows:
 /// 1. The Master Base Table is instantiated and filled using the Algebraic Execution Trace.
-///     This is the first time a Master Base Table is instantiated. It is in column-major form.
+///    This is the first time a Master Base Table is instantiated. It is in column-major form.
 /// 2. The Master Base Table is padded using logic from the individual tables.
 /// 3. The still-empty entries in the Master Base Table are filled with random elements. This
-///     step is also known as “trace randomization.”
+///    step is also known as “trace randomization.”
 /// 4. Each column of the Master Base Table is low-degree extended. The result is the Master Base
-///     Table over the FRI domain. This is the second and last time a Master Base Table is
-///     instantiated. It is in row-major form.
+///    Table over the FRI domain. This is the second and last time a Master Base Table is
+///    instantiated. It is in row-major form.
 /// 5. The Master Base Table and the Master Base Table over the FRI domain are used to derive the
-///     Master Extension Table using logic from the individual tables. This is the first time a
-///     Master Extension Table is instantiated. It is in column-major form.
+///    Master Extension Table using logic from the individual tables. This is the first time a
+///    Master Extension Table is instantiated. It is in column-major form.
 /// 6. The Master Extension Table is trace-randomized.
 /// 7. Each column of the Master Extension Table is low-degree extended. The result is the Master
-///     Extension Table over the FRI domain. This is the second and last time a Master Extension
-///     Table is instantiated. It is in row-major form.
+///    Extension Table over the FRI domain. This is the second and last time a Master Extension
+///    Table is instantiated. It is in row-major form.
 /// 8. Using the Master Base Table over the FRI domain and the Master Extension Table over the
-///     FRI domain, the Quotient Master Table is derived using the AIR. Each individual table
-///     defines that part of the AIR that is relevant to it.
+///    FRI domain, the Quotient Master Table is derived using the AIR. Each individual table
+///    defines that part of the AIR that is relevant to it.
 ///
 /// The following points are of note:
 /// - The Master Extension Table's rightmost columns are the randomizer codewords. These are
-///     necessary for zero-knowledge.
+///   necessary for zero-knowledge.
 /// - The terminal quotient of the cross-table argument, which links the individual tables together,
-///     is also stored in the Master Quotient Table. Even though the cross-table argument is not
-///     a table, it does define part of the AIR. Hence, the cross-table argument does not contribute
-///     to padding or extending the Master Tables, but is incorporated when deriving the Master
-///     Qoutient Table.
+///   is also stored in the Master Quotient Table. Even though the cross-table argument is not
+///   a table, it does define part of the AIR. Hence, the cross-table argument does not contribute
+///   to padding or extending the Master Tables, but is incorporated when deriving the Master
+///   Qoutient Table.
 /// - For better performance, it is possible to derive the Master Quotient Table (step 8) from the
-///     Master Base Table and Master Extension Table over a smaller domain than the FRI domain –
-///     the “quotient domain.” The quotient domain is a subset of the FRI domain. This
-///     performance improvement changes nothing conceptually.
+///   Master Base Table and Master Extension Table over a smaller domain than the FRI domain –
+///   the “quotient domain.” The quotient domain is a subset of the FRI domain. This
+///   performance improvement changes nothing conceptually.
 pub trait MasterTable<FF>
 where
     FF: FiniteField + MulAssign<BFieldElement>,
@@ -172,15 +178,42 @@ where
 
     /// Presents underlying trace data, excluding trace randomizers. Makes little sense over the
     /// FRI domain.
-    fn trace_table(&self) -> ArrayView2<FF>;
+    fn trace_table(&self) -> ArrayView2<'_, FF>;
 
     /// Presents all underlying data.
-    fn master_matrix(&self) -> ArrayView2<FF>;
+    fn master_matrix(&self) -> ArrayView2<'_, FF>;
 
     /// Presents all underlying data in a mutable manner.
-    fn master_matrix_mut(&mut self) -> ArrayViewMut2<FF>;
+    fn master_matrix_mut(&mut self) -> ArrayViewMut2<'_, FF>;
     fn fri_domain(&self) -> ArithmeticDomain;
 
+    /// The indices of all columns belonging to [empty tables](MasterBaseTable::empty_tables).
+    fn empty_table_columns(&self) -> Vec<usize>;
+
+    /// The indices of all columns that are committed to, _i.e._, all columns not belonging to an
+    /// empty table.
+    fn committed_columns(&self) -> Vec<usize> {
+        let empty_table_columns = self.empty_table_columns();
+        (0..self.master_matrix().ncols())
+            .filter(|col_idx| !empty_table_columns.contains(col_idx))
+            .collect()
+    }
+
+    /// The committed part of the rows with the given indices, as revealed in a proof.
+    fn revealed_rows(&self, row_indices: &[usize]) -> Vec<Vec<FF>> {
+        let committed_columns = self.committed_columns();
+        let master_matrix = self.master_matrix();
+        row_indices
+            .iter()
+            .map(|&row_idx| {
+                committed_columns
+                    .iter()
+                    .map(|&col_idx| master_matrix[[row_idx, col_idx]])
+                    .collect()
+            })
+            .collect()
+    }
+
     /// set all rows _not_ needed for the (padded) trace to random values
     fn randomize_trace(&mut self) {
         let randomized_padded_trace_len = self.randomized_padded_trace_len();
@@ -190,6 +223,15 @@ where
                 .slice_mut(s![offset..randomized_padded_trace_len; unit_distance, ..])
                 .par_mapv_inplace(|_| random::<FF>())
         });
+
+        // The columns of empty tables stay constant, such that their low-degree extension is
+        // known to the verifier.
+        for col_idx in self.empty_table_columns() {
+            let mut master_matrix = self.master_matrix_mut();
+            let mut column = master_matrix.column_mut(col_idx);
+            let value = column[0];
+            column.fill(value);
+        }
     }
 
     /// Result is in row-major order.
@@ -202,10 +244,15 @@ where
 
         let num_rows = self.fri_domain().length;
         let num_columns = self.master_matrix().ncols();
+        let empty_table_columns = self.empty_table_columns();
         let mut extended_columns = Array2::zeros([num_rows, num_columns]);
-        Zip::from(extended_columns.axis_iter_mut(Axis(1)))
+        Zip::indexed(extended_columns.axis_iter_mut(Axis(1)))
             .and(self.master_matrix().axis_iter(Axis(1)))
-            .par_for_each(|lde_column, trace_column| {
+            .par_for_each(|col_idx, mut lde_column, trace_column| {
+                if empty_table_columns.contains(&col_idx) {
+                    lde_column.fill(trace_column[0]);
+                    return;
+                }
                 let fri_codeword = randomized_trace_domain
                     .low_degree_extension(&trace_column.to_vec(), self.fri_domain());
                 Array1::from(fri_codeword).move_into(lde_column);
@@ -231,6 +278,12 @@ pub struct MasterBaseTable {
 
     pub fri_domain: ArithmeticDomain,
     pub master_base_matrix: Array2<BFieldElement>,
+
+    /// The tables that are [empty](Self::unpadded_height) and whose rows are all known to the
+    /// verifier, see [`empty_table_base_row`](Self::empty_table_base_row). Their columns are
+    /// constant. They are neither low-degree extended nor committed to, and the verifier
+    /// reinserts them into the revealed rows.
+    pub empty_tables: Vec<TableId>,
 }
 
 pub struct MasterExtTable {
@@ -246,6 +299,9 @@ pub struct MasterExtTable {
 
     pub fri_domain: ArithmeticDomain,
     pub master_ext_matrix: Array2<XFieldElement>,
+
+    /// The tables consisting of padding rows only, see [`MasterBaseTable::empty_tables`].
+    pub empty_tables: Vec<TableId>,
 }
 
 impl MasterTable<BFieldElement> for MasterBaseTable {
@@ -257,22 +313,32 @@ impl MasterTable<BFieldElement> for MasterBaseTable {
         self.rand_trace_to_padded_trace_unit_distance
     }
 
-    fn trace_table(&self) -> ArrayView2<BFieldElement> {
+    fn trace_table(&self) -> ArrayView2<'_, BFieldElement> {
         self.master_base_matrix
             .slice(s![..; self.rand_trace_to_padded_trace_unit_distance, ..])
     }
 
-    fn master_matrix(&self) -> ArrayView2<BFieldElement> {
+    fn master_matrix(&self) -> ArrayView2<'_, BFieldElement> {
         self.master_base_matrix.view()
     }
 
-    fn master_matrix_mut(&mut self) -> ArrayViewMut2<BFieldElement> {
+    fn master_matrix_mut(&mut self) -> ArrayViewMut2<'_, BFieldElement> {
         self.master_base_matrix.view_mut()
     }
 
     fn fri_domain(&self) -> ArithmeticDomain {
         self.fri_domain
     }
+
+    fn empty_table_columns(&self) -> Vec<usize> {
+        self.empty_tables
+            .iter()
+            .flat_map(|&id| {
+                let (table_start, table_end) = Self::table_slice_info(id);
+                table_start..table_end
+            })
+            .collect()
+    }
 }
 
 impl MasterTable<XFieldElement> for MasterExtTable {
@@ -284,29 +350,67 @@ impl MasterTable<XFieldElement> for MasterExtTable {
         self.rand_trace_to_padded_trace_unit_distance
     }
 
-    fn trace_table(&self) -> ArrayView2<XFieldElement> {
+    fn trace_table(&self) -> ArrayView2<'_, XFieldElement> {
         self.master_ext_matrix
             .slice(s![..; self.rand_trace_to_padded_trace_unit_distance, ..])
     }
 
-    fn master_matrix(&self) -> ArrayView2<XFieldElement> {
+    fn master_matrix(&self) -> ArrayView2<'_, XFieldElement> {
         self.master_ext_matrix.view()
     }
 
-    fn master_matrix_mut(&mut self) -> ArrayViewMut2<XFieldElement> {
+    fn master_matrix_mut(&mut self) -> ArrayViewMut2<'_, XFieldElement> {
         self.master_ext_matrix.view_mut()
     }
 
     fn fri_domain(&self) -> ArithmeticDomain {
         self.fri_domain
     }
+
+    fn empty_table_columns(&self) -> Vec<usize> {
+        self.empty_tables
+            .iter()
+            .flat_map(|&id| {
+                let (table_start, table_end) = Self::table_slice_info(id);
+                table_start..table_end
+            })
+            .collect()
+    }
 }
 
 impl MasterBaseTable {
+    /// The number of rows of the given table before padding. Tables of height 0 are empty, for
+    /// example, the Hash Table of a program that never executes instruction `hash`. Empty tables
+    /// do not influence the padded height, and consist of padding rows only.
+    ///
+    /// If all padding rows of an empty table are known in advance, the table is not committed to,
+    /// see [`empty_tables`](Self::empty_tables). It still takes part in the cross-table arguments:
+    /// the verifier uses the known padding rows, which only satisfy the arguments if the other
+    /// tables agree that the table is empty.
+    pub fn unpadded_height(
+        id: TableId,
+        aet: &AlgebraicExecutionTrace,
+        program: &[BFieldElement],
+    ) -> usize {
+        match id {
+            // The Program Table's running evaluation is only complete in the first padding row.
+            TableId::ProgramTable => program.len() + 1,
+            TableId::InstructionTable => program.len(),
+            TableId::ProcessorTable
+            | TableId::OpStackTable
+            | TableId::RamTable
+            | TableId::JumpStackTable => aet.processor_matrix.nrows(),
+            TableId::HashTable => aet.hash_matrix.nrows(),
+        }
+    }
+
     pub fn padded_height(aet: &AlgebraicExecutionTrace, program: &[BFieldElement]) -> usize {
-        let instruction_table_len = program.len() + aet.processor_matrix.nrows();
-        let hash_table_len = aet.hash_matrix.nrows();
-        let max_height = max(instruction_table_len, hash_table_len);
+        let coprocessor_heights = aet.coprocessor_tables.values().map(|table| table.nrows());
+        let max_height = TableId::iter()
+            .map(|id| Self::unpadded_height(id, aet, program))
+            .chain(coprocessor_heights)
+            .max()
+            .unwrap_or_default();
         roundup_npo2(max_height as u64) as usize
     }
 
@@ -317,12 +421,43 @@ impl MasterBaseTable {
         fri_domain: ArithmeticDomain,
     ) -> Self {
         let padded_height = Self::padded_height(&aet, program);
+        Self::new_with_padded_height(
+            aet,
+            program,
+            padded_height,
+            num_trace_randomizers,
+            fri_domain,
+        )
+    }
+
+    /// Like [`new`](Self::new), but padding to the given height instead of the smallest possible
+    /// one. This allows tables of different executions to share one FRI domain. The given height
+    /// must be a power of two and at least the smallest possible padded height.
+    pub fn new_with_padded_height(
+        aet: AlgebraicExecutionTrace,
+        program: &[BFieldElement],
+        padded_height: usize,
+        num_trace_randomizers: usize,
+        fri_domain: ArithmeticDomain,
+    ) -> Self {
+        assert!(
+            is_power_of_two(padded_height),
+            "Padded height must be a power of two, but is {padded_height}."
+        );
+        assert!(
+            padded_height >= Self::padded_height(&aet, program),
+            "Padded height {padded_height} is too small for the execution trace."
+        );
         let randomized_padded_trace_len =
             randomized_padded_trace_len(num_trace_randomizers, padded_height);
         let unit_distance = randomized_padded_trace_len / padded_height;
         let program_len = program.len();
         let main_execution_len = aet.processor_matrix.nrows();
         let hash_coprocessor_execution_len = aet.hash_matrix.nrows();
+        let empty_tables = TableId::iter()
+            .filter(|&id| Self::unpadded_height(id, &aet, program) == 0)
+            .filter(|&id| Self::empty_table_base_row(id).is_some())
+            .collect();
 
         let num_rows = randomized_padded_trace_len;
         let num_columns = NUM_BASE_COLUMNS;
@@ -338,20 +473,44 @@ impl MasterBaseTable {
             rand_trace_to_padded_trace_unit_distance: unit_distance,
             fri_domain,
             master_base_matrix,
+            empty_tables,
         };
 
-        let program_table = &mut master_base_table.table_mut(TableId::ProgramTable);
-        ProgramTable::fill_trace(program_table, program);
-        let instruction_table = &mut master_base_table.table_mut(TableId::InstructionTable);
-        InstructionTable::fill_trace(instruction_table, &aet, program);
-        let op_stack_table = &mut master_base_table.table_mut(TableId::OpStackTable);
-        let op_stack_clk_jump_diffs = OpStackTable::fill_trace(op_stack_table, &aet);
-        let ram_table = &mut master_base_table.table_mut(TableId::RamTable);
-        let ram_clk_jump_diffs = RamTable::fill_trace(ram_table, &aet);
-        let jump_stack_table = &mut master_base_table.table_mut(TableId::JumpStackTable);
-        let jump_stack_clk_jump_diffs = JumpStackTable::fill_trace(jump_stack_table, &aet);
-        let hash_table = &mut master_base_table.table_mut(TableId::HashTable);
-        HashTable::fill_trace(hash_table, &aet);
+        // All tables but the Processor Table are independent of each other and filled in parallel.
+        let table_slice = |id| {
+            let (table_start, table_end) = Self::table_slice_info(id);
+            s![..; unit_distance, table_start..table_end]
+        };
+        let (
+            mut program_table,
+            mut instruction_table,
+            mut op_stack_table,
+            mut ram_table,
+            mut jump_stack_table,
+            mut hash_table,
+        ) = master_base_table.master_base_matrix.multi_slice_mut((
+            table_slice(TableId::ProgramTable),
+            table_slice(TableId::InstructionTable),
+            table_slice(TableId::OpStackTable),
+            table_slice(TableId::RamTable),
+            table_slice(TableId::JumpStackTable),
+            table_slice(TableId::HashTable),
+        ));
+        let mut op_stack_clk_jump_diffs = vec![];
+        let mut ram_clk_jump_diffs = vec![];
+        let mut jump_stack_clk_jump_diffs = vec![];
+        rayon::scope(|scope| {
+            scope.spawn(|_| ProgramTable::fill_trace(&mut program_table, program));
+            scope.spawn(|_| InstructionTable::fill_trace(&mut instruction_table, &aet, program));
+            scope.spawn(|_| {
+                op_stack_clk_jump_diffs = OpStackTable::fill_trace(&mut op_stack_table, &aet)
+            });
+            scope.spawn(|_| ram_clk_jump_diffs = RamTable::fill_trace(&mut ram_table, &aet));
+            scope.spawn(|_| {
+                jump_stack_clk_jump_diffs = JumpStackTable::fill_trace(&mut jump_stack_table, &aet)
+            });
+            scope.spawn(|_| HashTable::fill_trace(&mut hash_table, &aet));
+        });
 
         // memory-like tables must be filled in before clock jump differences are known, hence
         // the break from the usual order
@@ -367,6 +526,68 @@ impl MasterBaseTable {
         master_base_table
     }
 
+    /// Evaluate all transition constraints that refer to the base table only on every pair of
+    /// consecutive rows. Much cheaper than proving, this quickly detects disagreements between
+    /// the VM and the AIR. Fails with the first violated constraint and the index of its current
+    /// row.
+    ///
+    /// Must be called after [`pad`](Self::pad): padding fixes up helper columns of the last
+    /// unpadded rows, like the inverses of clock jump differences in memory-like tables. Must be
+    /// called before [`randomize_trace`](MasterTable::randomize_trace). Constraints involving
+    /// challenges or the extension table are not checked.
+    pub fn check_base_transition_constraints(&self) -> Result<()> {
+        let trace_table = self.trace_table();
+        for id in TableId::iter() {
+            let violation = match id {
+                TableId::ProgramTable => first_violated_base_transition_constraint(
+                    ExtProgramTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+                TableId::InstructionTable => first_violated_base_transition_constraint(
+                    ExtInstructionTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+                TableId::ProcessorTable => first_violated_base_transition_constraint(
+                    ExtProcessorTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+                TableId::OpStackTable => first_violated_base_transition_constraint(
+                    ExtOpStackTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+                TableId::RamTable => first_violated_base_transition_constraint(
+                    ExtRamTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+                TableId::JumpStackTable => first_violated_base_transition_constraint(
+                    ExtJumpStackTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+                TableId::HashTable => first_violated_base_transition_constraint(
+                    ExtHashTable::ext_transition_constraints_as_circuits(),
+                    trace_table,
+                ),
+            };
+            if let Some((constraint_index, row_index)) = violation {
+                bail!(
+                    "Transition constraint {constraint_index} of the {id} is violated \
+                    between rows {row_index} and {}.",
+                    row_index + 1
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill the rows after each table's unpadded height with padding rows, such that every table
+    /// is exactly `padded_height` rows tall. The padding rows of each table follow that table's
+    /// rules: for example, the Processor Table repeats its last row with `IsPadding` set and the
+    /// clock keeps counting, while the Hash Table's padding rows are all zero. A padded table
+    /// satisfies all [base transition constraints](Self::check_base_transition_constraints) if
+    /// the unpadded table does.
+    ///
+    /// The prover pads as part of proving. Padding is public so that tools can inspect padded
+    /// tables and test the padding rules. See also [`AlgebraicExecutionTrace::pad`].
     pub fn pad(&mut self) {
         let program_len = self.program_len;
         let main_execution_len = self.main_execution_len;
@@ -374,7 +595,7 @@ impl MasterBaseTable {
         let program_table = &mut self.table_mut(TableId::ProgramTable);
         ProgramTable::pad_trace(program_table, program_len);
         let instruction_table = &mut self.table_mut(TableId::InstructionTable);
-        InstructionTable::pad_trace(instruction_table, program_len + main_execution_len);
+        InstructionTable::pad_trace(instruction_table, program_len);
         let processor_table = &mut self.table_mut(TableId::ProcessorTable);
         ProcessorTable::pad_trace(processor_table, main_execution_len);
         let op_stack_table = &mut self.table_mut(TableId::OpStackTable);
@@ -390,20 +611,64 @@ impl MasterBaseTable {
     pub fn to_fri_domain_table(&self) -> Self {
         Self {
             master_base_matrix: self.low_degree_extend_all_columns(),
+            empty_tables: self.empty_tables.clone(),
             ..*self
         }
     }
 
     pub fn merkle_tree(&self) -> MerkleTree<StarkHasher, CpuParallel> {
+        let committed_columns = self.committed_columns();
         let hashed_rows = self
             .master_base_matrix
             .axis_iter(Axis(0))
             .into_par_iter()
-            .map(|row| StarkHasher::hash_slice(&row.to_vec()))
+            .map(|row| {
+                let committed_row = committed_columns.iter().map(|&i| row[i]).collect_vec();
+                StarkHasher::hash_slice(&committed_row)
+            })
             .collect::<Vec<_>>();
         CpuParallel::from_digests(&hashed_rows)
     }
 
+    /// The row that every row of the given table is equal to if the table is empty, or `None`
+    /// if the padding rows of the table depend on the execution. For example, the Hash Table
+    /// is padded with all-zero rows, whereas the Processor Table's padding rows repeat its last
+    /// row.
+    pub fn empty_table_base_row(id: TableId) -> Option<Array1<BFieldElement>> {
+        match id {
+            TableId::HashTable => {
+                let mut hash_table = Array2::zeros([1, hash_table::BASE_WIDTH]);
+                HashTable::pad_trace(&mut hash_table.view_mut());
+                Some(hash_table.row(0).to_owned())
+            }
+            _ => None,
+        }
+    }
+
+    /// Insert the columns of the given empty tables into rows revealed by the prover, which only
+    /// contain the [committed columns](MasterTable::committed_columns).
+    pub fn reinsert_empty_table_columns(
+        empty_tables: &[TableId],
+        revealed_rows: Vec<Vec<BFieldElement>>,
+    ) -> Result<Vec<Vec<BFieldElement>>> {
+        let mut constant_columns = vec![];
+        for &id in empty_tables {
+            let Some(empty_row) = Self::empty_table_base_row(id) else {
+                bail!("The {id} cannot be empty.");
+            };
+            let (table_start, _) = Self::table_slice_info(id);
+            constant_columns.extend(empty_row.into_iter().zip(table_start..));
+        }
+        let num_committed_columns = NUM_BASE_COLUMNS - constant_columns.len();
+        if revealed_rows
+            .iter()
+            .any(|row| row.len() != num_committed_columns)
+        {
+            bail!("Revealed base rows must have {num_committed_columns} elements.");
+        }
+        Ok(reinsert_constant_columns(revealed_rows, &constant_columns))
+    }
+
     /// Create a `MasterExtTable` from a `MasterBaseTable` by `.extend()`ing each individual base
     /// table. The `.extend()` for each table is specific to that table, but always involves
     /// adding some number of columns.
@@ -428,6 +693,7 @@ impl MasterBaseTable {
             rand_trace_to_padded_trace_unit_distance: self.rand_trace_to_padded_trace_unit_distance,
             fri_domain: self.fri_domain,
             master_ext_matrix,
+            empty_tables: self.empty_tables.clone(),
         };
 
         ProgramTable::extend(
@@ -482,14 +748,14 @@ impl MasterBaseTable {
         }
     }
 
-    pub fn table(&self, id: TableId) -> ArrayView2<BFieldElement> {
+    pub fn table(&self, id: TableId) -> ArrayView2<'_, BFieldElement> {
         let (table_start, table_end) = Self::table_slice_info(id);
         let unit_distance = self.rand_trace_to_padded_trace_unit_distance;
         self.master_base_matrix
             .slice(s![..; unit_distance, table_start..table_end])
     }
 
-    pub fn table_mut(&mut self, id: TableId) -> ArrayViewMut2<BFieldElement> {
+    pub fn table_mut(&mut self, id: TableId) -> ArrayViewMut2<'_, BFieldElement> {
         let (table_start, table_end) = Self::table_slice_info(id);
         let unit_distance = self.rand_trace_to_padded_trace_unit_distance;
         self.master_base_matrix
@@ -501,6 +767,7 @@ impl MasterExtTable {
     pub fn to_fri_domain_table(&self) -> Self {
         Self {
             master_ext_matrix: self.low_degree_extend_all_columns(),
+            empty_tables: self.empty_tables.clone(),
             ..*self
         }
     }
@@ -515,15 +782,15 @@ impl MasterExtTable {
     }
 
     pub fn merkle_tree(&self) -> MerkleTree<StarkHasher, CpuParallel> {
+        let committed_columns = self.committed_columns();
         let hashed_rows = self
             .master_ext_matrix
             .axis_iter(Axis(0))
             .into_par_iter()
             .map(|row| {
-                let contiguous_row_bfe = row
-                    .to_vec()
+                let contiguous_row_bfe = committed_columns
                     .iter()
-                    .map(|xfe| xfe.coefficients.to_vec())
+                    .map(|&i| row[i].coefficients.to_vec())
                     .concat();
                 StarkHasher::hash_slice(&contiguous_row_bfe)
             })
@@ -531,6 +798,54 @@ impl MasterExtTable {
         CpuParallel::from_digests(&hashed_rows)
     }
 
+    /// Like [`MasterBaseTable::empty_table_base_row`], for the extension columns. Extending the
+    /// base row gives the extension row, since the running evaluations of an empty table never
+    /// change.
+    pub fn empty_table_ext_row(
+        id: TableId,
+        challenges: &AllChallenges,
+    ) -> Option<Array1<XFieldElement>> {
+        let empty_base_row = MasterBaseTable::empty_table_base_row(id)?;
+        let base_table = empty_base_row.insert_axis(Axis(0));
+        match id {
+            TableId::HashTable => {
+                let mut ext_table = Array2::zeros([1, hash_table::EXT_WIDTH]);
+                HashTable::extend(
+                    base_table.view(),
+                    ext_table.view_mut(),
+                    &challenges.hash_table_challenges,
+                );
+                Some(ext_table.row(0).to_owned())
+            }
+            _ => None,
+        }
+    }
+
+    /// Like [`MasterBaseTable::reinsert_empty_table_columns`], for the extension columns. The
+    /// randomizer polynomials' columns are kept as they are.
+    pub fn reinsert_empty_table_columns(
+        empty_tables: &[TableId],
+        revealed_rows: Vec<Vec<XFieldElement>>,
+        challenges: &AllChallenges,
+    ) -> Result<Vec<Vec<XFieldElement>>> {
+        let mut constant_columns = vec![];
+        for &id in empty_tables {
+            let Some(empty_row) = Self::empty_table_ext_row(id, challenges) else {
+                bail!("The {id} cannot be empty.");
+            };
+            let (table_start, _) = Self::table_slice_info(id);
+            constant_columns.extend(empty_row.into_iter().zip(table_start..));
+        }
+        let num_committed_columns = NUM_EXT_COLUMNS - constant_columns.len();
+        if revealed_rows
+            .iter()
+            .any(|row| row.len() < num_committed_columns)
+        {
+            bail!("Revealed extension rows must have at least {num_committed_columns} elements.");
+        }
+        Ok(reinsert_constant_columns(revealed_rows, &constant_columns))
+    }
+
     fn table_slice_info(id: TableId) -> (usize, usize) {
         use TableId::*;
         match id {
@@ -544,14 +859,14 @@ impl MasterExtTable {
         }
     }
 
-    pub fn table(&self, id: TableId) -> ArrayView2<XFieldElement> {
+    pub fn table(&self, id: TableId) -> ArrayView2<'_, XFieldElement> {
         let unit_distance = self.rand_trace_to_padded_trace_unit_distance;
         let (table_start, table_end) = Self::table_slice_info(id);
         self.master_ext_matrix
             .slice(s![..; unit_distance, table_start..table_end])
     }
 
-    pub fn table_mut(&mut self, id: TableId) -> ArrayViewMut2<XFieldElement> {
+    pub fn table_mut(&mut self, id: TableId) -> ArrayViewMut2<'_, XFieldElement> {
         let unit_distance = self.rand_trace_to_padded_trace_unit_distance;
         let (table_start, table_end) = Self::table_slice_info(id);
         self.master_ext_matrix
@@ -559,22 +874,82 @@ impl MasterExtTable {
     }
 }
 
+/// Insert each given value at its column index into every row. The column indices must be
+/// increasing and refer to the rows after insertion.
+fn reinsert_constant_columns<FF: Copy>(
+    rows: Vec<Vec<FF>>,
+    constant_columns: &[(FF, usize)],
+) -> Vec<Vec<FF>> {
+    rows.into_iter()
+        .map(|mut row| {
+            for &(value, col_idx) in constant_columns {
+                row.insert(col_idx, value);
+            }
+            row
+        })
+        .collect()
+}
+
+/// The index of the first transition constraint referring to the base table only that some pair
+/// of consecutive rows violates, along with the index of the first row of that pair.
+fn first_violated_base_transition_constraint<T: TableChallenges>(
+    circuits: Vec<ConstraintCircuit<T, DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>>>,
+    base_table: ArrayView2<BFieldElement>,
+) -> Option<(usize, usize)> {
+    let base_circuits = circuits
+        .iter()
+        .enumerate()
+        .filter(|(_, circuit)| !circuit.is_randomized() && !circuit.references_ext_table())
+        .collect_vec();
+    for (row_index, rows) in base_table
+        .windows([2, base_table.ncols()])
+        .into_iter()
+        .enumerate()
+    {
+        for &(constraint_index, circuit) in base_circuits.iter() {
+            if !circuit.evaluate_on_base_table(rows).is_zero() {
+                return Some((constraint_index, row_index));
+            }
+        }
+    }
+    None
+}
+
 pub fn all_degrees_with_origin(
     interpolant_degree: Degree,
     padded_height: usize,
 ) -> Vec<DegreeWithOrigin> {
-    let id = interpolant_degree;
-    let ph = padded_height;
-    [
-        ExtProgramTable::all_degrees_with_origin("program table", id, ph),
-        ExtInstructionTable::all_degrees_with_origin("instruction table", id, ph),
-        ExtProcessorTable::all_degrees_with_origin("processor table", id, ph),
-        ExtOpStackTable::all_degrees_with_origin("op stack table", id, ph),
-        ExtRamTable::all_degrees_with_origin("ram table", id, ph),
-        ExtJumpStackTable::all_degrees_with_origin("jump stack table", id, ph),
-        ExtHashTable::all_degrees_with_origin("hash table", id, ph),
-    ]
-    .concat()
+    TableId::iter()
+        .flat_map(|id| table_degrees_with_origin(id, interpolant_degree, padded_height))
+        .collect()
+}
+
+/// The degrees of all quotients of the given table, together with their origin.
+pub fn table_degrees_with_origin(
+    id: TableId,
+    interpolant_degree: Degree,
+    padded_height: usize,
+) -> Vec<DegreeWithOrigin> {
+    let (degree, height) = (interpolant_degree, padded_height);
+    match id {
+        TableId::ProgramTable => {
+            ExtProgramTable::all_degrees_with_origin("program table", degree, height)
+        }
+        TableId::InstructionTable => {
+            ExtInstructionTable::all_degrees_with_origin("instruction table", degree, height)
+        }
+        TableId::ProcessorTable => {
+            ExtProcessorTable::all_degrees_with_origin("processor table", degree, height)
+        }
+        TableId::OpStackTable => {
+            ExtOpStackTable::all_degrees_with_origin("op stack table", degree, height)
+        }
+        TableId::RamTable => ExtRamTable::all_degrees_with_origin("ram table", degree, height),
+        TableId::JumpStackTable => {
+            ExtJumpStackTable::all_degrees_with_origin("jump stack table", degree, height)
+        }
+        TableId::HashTable => ExtHashTable::all_degrees_with_origin("hash table", degree, height),
+    }
 }
 
 pub fn max_degree_with_origin(
@@ -635,6 +1010,142 @@ pub fn num_all_terminal_quotients() -> usize {
         + GrandCrossTableArg::num_terminal_quotients()
 }
 
+/// The number of initial, consistency, transition, and terminal quotients of the given table.
+fn num_quotients_by_category(id: TableId) -> [usize; 4] {
+    fn num_quotients<T: Quotientable>() -> [usize; 4] {
+        [
+            T::num_initial_quotients(),
+            T::num_consistency_quotients(),
+            T::num_transition_quotients(),
+            T::num_terminal_quotients(),
+        ]
+    }
+    match id {
+        TableId::ProgramTable => num_quotients::<ExtProgramTable>(),
+        TableId::InstructionTable => num_quotients::<ExtInstructionTable>(),
+        TableId::ProcessorTable => num_quotients::<ExtProcessorTable>(),
+        TableId::OpStackTable => num_quotients::<ExtOpStackTable>(),
+        TableId::RamTable => num_quotients::<ExtRamTable>(),
+        TableId::JumpStackTable => num_quotients::<ExtJumpStackTable>(),
+        TableId::HashTable => num_quotients::<ExtHashTable>(),
+    }
+}
+
+/// The table every quotient stems from, in the order of [`evaluate_all_constraints`]. The
+/// quotients of the Grand Cross-Table Argument stem from no single table and are `None`.
+pub fn all_quotient_origins() -> Vec<Option<TableId>> {
+    let mut origins = vec![];
+    for category in 0..4 {
+        for id in TableId::iter() {
+            let num_quotients = num_quotients_by_category(id)[category];
+            origins.extend(vec![Some(id); num_quotients]);
+        }
+    }
+    origins.extend(vec![None; GrandCrossTableArg::num_terminal_quotients()]);
+    origins
+}
+
+/// The number of constraints of one table, together with their degrees, grouped by type.
+/// Both grow the prover's cost: the number of constraints linearly, the maximal degree by
+/// determining the length of the FRI domain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintStats {
+    /// `None` for the constraints of the Grand Cross-Table Argument.
+    pub origin: Option<TableId>,
+    pub initial_degrees: Vec<Degree>,
+    pub consistency_degrees: Vec<Degree>,
+    pub transition_degrees: Vec<Degree>,
+    pub terminal_degrees: Vec<Degree>,
+}
+
+impl ConstraintStats {
+    fn of<T: Quotientable>(origin: Option<TableId>) -> Self {
+        // With interpolants of degree 1, the degree of a constraint is the degree bound of its
+        // quotient plus the degree of the quotient's zerofier.
+        let padded_height = 2;
+        let constraint_degrees = |quotient_degree_bounds: Vec<Degree>, zerofier_degree| {
+            quotient_degree_bounds
+                .into_iter()
+                .map(|degree| degree + zerofier_degree)
+                .collect_vec()
+        };
+        Self {
+            origin,
+            initial_degrees: constraint_degrees(T::initial_quotient_degree_bounds(1), 1),
+            consistency_degrees: constraint_degrees(
+                T::consistency_quotient_degree_bounds(1, padded_height),
+                padded_height as Degree,
+            ),
+            transition_degrees: constraint_degrees(
+                T::transition_quotient_degree_bounds(1, padded_height),
+                padded_height as Degree - 1,
+            ),
+            terminal_degrees: constraint_degrees(T::terminal_quotient_degree_bounds(1), 1),
+        }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.initial_degrees.len()
+            + self.consistency_degrees.len()
+            + self.transition_degrees.len()
+            + self.terminal_degrees.len()
+    }
+
+    pub fn max_degree(&self) -> Degree {
+        [
+            &self.initial_degrees,
+            &self.consistency_degrees,
+            &self.transition_degrees,
+            &self.terminal_degrees,
+        ]
+        .into_iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0)
+    }
+
+    /// Fails if there are more constraints than `max_num_constraints`, or if any constraint's
+    /// degree exceeds `max_degree`. Intended for tests guarding against changes to the ISA or the
+    /// tables that increase the prover's cost unnoticed.
+    pub fn check_budget(&self, max_num_constraints: usize, max_degree: Degree) -> Result<()> {
+        let origin = match self.origin {
+            Some(id) => id.to_string(),
+            None => "GrandCrossTableArg".to_string(),
+        };
+        let num_constraints = self.num_constraints();
+        if num_constraints > max_num_constraints {
+            bail!(
+                "{origin} has {num_constraints} constraints, \
+                exceeding the budget of {max_num_constraints}."
+            );
+        }
+        let degree = self.max_degree();
+        if degree > max_degree {
+            bail!("{origin} has a constraint of degree {degree}, exceeding the budget of {max_degree}.");
+        }
+        Ok(())
+    }
+}
+
+/// The [`ConstraintStats`] of every table, in the order of [`TableId`], followed by those of the
+/// Grand Cross-Table Argument.
+pub fn constraint_stats() -> Vec<ConstraintStats> {
+    let mut stats = TableId::iter()
+        .map(|id| match id {
+            TableId::ProgramTable => ConstraintStats::of::<ExtProgramTable>(Some(id)),
+            TableId::InstructionTable => ConstraintStats::of::<ExtInstructionTable>(Some(id)),
+            TableId::ProcessorTable => ConstraintStats::of::<ExtProcessorTable>(Some(id)),
+            TableId::OpStackTable => ConstraintStats::of::<ExtOpStackTable>(Some(id)),
+            TableId::RamTable => ConstraintStats::of::<ExtRamTable>(Some(id)),
+            TableId::JumpStackTable => ConstraintStats::of::<ExtJumpStackTable>(Some(id)),
+            TableId::HashTable => ConstraintStats::of::<ExtHashTable>(Some(id)),
+        })
+        .collect_vec();
+    stats.push(ConstraintStats::of::<GrandCrossTableArg>(None));
+    stats
+}
+
 pub fn all_initial_quotient_degree_bounds(interpolant_degree: Degree) -> Vec<Degree> {
     [
         ExtProgramTable::initial_quotient_degree_bounds(interpolant_degree),
@@ -1383,6 +1894,7 @@ mod master_table_tests {
     use ndarray::s;
     use num_traits::Zero;
     use strum::IntoEnumIterator;
+    use triton_opcodes::program::Program;
     use twenty_first::shared_math::b_field_element::BFieldElement;
     use twenty_first::shared_math::traits::FiniteField;
 
@@ -1392,10 +1904,16 @@ mod master_table_tests {
     use crate::table::hash_table;
     use crate::table::instruction_table;
     use crate::table::jump_stack_table;
+    use crate::table::master_table::all_degrees_with_origin;
+    use crate::table::master_table::all_quotient_origins;
     use crate::table::master_table::consistency_quotient_zerofier_inverse;
+    use crate::table::master_table::constraint_stats;
     use crate::table::master_table::initial_quotient_zerofier_inverse;
+    use crate::table::master_table::num_all_table_quotients;
+    use crate::table::master_table::num_quotients_by_category;
     use crate::table::master_table::terminal_quotient_zerofier_inverse;
     use crate::table::master_table::transition_quotient_zerofier_inverse;
+    use crate::table::master_table::TableId;
     use crate::table::master_table::TableId::*;
     use crate::table::master_table::EXT_HASH_TABLE_END;
     use crate::table::master_table::NUM_BASE_COLUMNS;
@@ -1421,6 +1939,24 @@ mod master_table_tests {
     use crate::table::table_column::ProgramExtTableColumn;
     use crate::table::table_column::RamBaseTableColumn;
     use crate::table::table_column::RamExtTableColumn;
+    use crate::vm::simulate_no_input;
+
+    use super::MasterBaseTable;
+
+    #[test]
+    fn empty_hash_table_does_not_influence_padded_height_test() {
+        let program = Program::from_code("push 1 push 2 add pop halt").unwrap();
+        let (aet, _) = simulate_no_input(&program).unwrap();
+        let program = program.to_bwords();
+
+        let hash_table_height = MasterBaseTable::unpadded_height(HashTable, &aet, &program);
+        assert_eq!(0, hash_table_height);
+
+        let processor_table_height =
+            MasterBaseTable::unpadded_height(ProcessorTable, &aet, &program);
+        let padded_height = MasterBaseTable::padded_height(&aet, &program);
+        assert_eq!(processor_table_height.next_power_of_two(), padded_height);
+    }
 
     #[test]
     fn base_table_width_is_correct() {
@@ -1498,6 +2034,73 @@ mod master_table_tests {
         );
     }
 
+    #[test]
+    fn every_quotient_has_an_origin_test() {
+        let origins = all_quotient_origins();
+        assert_eq!(num_all_table_quotients(), origins.len());
+        for id in TableId::iter() {
+            let num_quotients: usize = num_quotients_by_category(id).iter().sum();
+            let num_origins = origins.iter().filter(|&&origin| origin == Some(id)).count();
+            assert_eq!(num_quotients, num_origins, "{id}");
+        }
+    }
+
+    #[test]
+    fn constraint_stats_agree_with_quotients_test() {
+        let stats = constraint_stats();
+        let num_constraints: usize = stats.iter().map(|s| s.num_constraints()).sum();
+        assert_eq!(num_all_table_quotients(), num_constraints);
+        for table_stats in stats.iter() {
+            println!(
+                "{:?}: {} constraints, max degree {}",
+                table_stats.origin,
+                table_stats.num_constraints(),
+                table_stats.max_degree()
+            );
+            let Some(id) = table_stats.origin else {
+                continue;
+            };
+            let num_quotients = num_quotients_by_category(id);
+            assert_eq!(num_quotients[0], table_stats.initial_degrees.len());
+            assert_eq!(num_quotients[1], table_stats.consistency_degrees.len());
+            assert_eq!(num_quotients[2], table_stats.transition_degrees.len());
+            assert_eq!(num_quotients[3], table_stats.terminal_degrees.len());
+        }
+
+        let interpolant_degree = 1;
+        let padded_height = 2;
+        let max_degree = all_degrees_with_origin(interpolant_degree, padded_height)
+            .into_iter()
+            .map(|d| d.degree + d.zerofier_degree)
+            .max()
+            .unwrap();
+        let max_constraint_degree = stats.iter().map(|s| s.max_degree()).max().unwrap();
+        assert_eq!(max_degree, max_constraint_degree);
+    }
+
+    /// Fails if a change to the instruction set or the tables adds constraints or increases their
+    /// degree. If the increase is intended, adjust the budget.
+    #[test]
+    fn constraint_budget_test() {
+        let budget = |origin| match origin {
+            Some(ProgramTable) => (6, 2),
+            Some(InstructionTable) => (8, 3),
+            Some(ProcessorTable) => (125, 13),
+            Some(OpStackTable) => (11, 4),
+            Some(RamTable) => (23, 5),
+            Some(JumpStackTable) => (14, 4),
+            Some(HashTable) => (62, 11),
+            None => (1, 3),
+        };
+        for table_stats in constraint_stats() {
+            let (max_num_constraints, max_degree) = budget(table_stats.origin);
+            if let Err(err) = table_stats.check_budget(max_num_constraints, max_degree) {
+                panic!("{err}");
+            }
+        }
+        assert!(constraint_stats()[0].check_budget(0, 0).is_err());
+    }
+
     #[test]
     fn zerofiers_are_correct_test() {
         let big_order = 16;
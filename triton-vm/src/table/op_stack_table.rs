@@ -20,6 +20,7 @@ use twenty_first::shared_math::x_field_element::XFieldElement;
 use OpStackTableChallengeId::*;
 
 use crate::op_stack::OP_STACK_REG_COUNT;
+use crate::table::challenges::AllChallenges;
 use crate::table::challenges::TableChallenges;
 use crate::table::constraint_circuit::ConstraintCircuit;
 use crate::table::constraint_circuit::ConstraintCircuitBuilder;
@@ -27,8 +28,10 @@ use crate::table::constraint_circuit::DualRowIndicator;
 use crate::table::constraint_circuit::DualRowIndicator::*;
 use crate::table::constraint_circuit::SingleRowIndicator;
 use crate::table::constraint_circuit::SingleRowIndicator::*;
+use crate::table::coprocessor::CoprocessorTable;
 use crate::table::cross_table_argument::CrossTableArg;
 use crate::table::cross_table_argument::PermArg;
+use crate::table::master_table::TableId;
 use crate::table::master_table::NUM_BASE_COLUMNS;
 use crate::table::master_table::NUM_EXT_COLUMNS;
 use crate::table::table_column::BaseTableColumn;
@@ -40,6 +43,7 @@ use crate::table::table_column::OpStackBaseTableColumn::*;
 use crate::table::table_column::OpStackExtTableColumn;
 use crate::table::table_column::OpStackExtTableColumn::*;
 use crate::table::table_column::ProcessorBaseTableColumn;
+use crate::table::table_column::ProcessorExtTableColumn;
 use crate::vm::AlgebraicExecutionTrace;
 
 pub const OP_STACK_TABLE_NUM_PERMUTATION_ARGUMENTS: usize = 1;
@@ -415,6 +419,35 @@ impl OpStackTable {
     }
 }
 
+impl CoprocessorTable for OpStackTable {
+    const ID: TableId = TableId::OpStackTable;
+    type ExtColumn = OpStackExtTableColumn;
+    type Constraints = ExtOpStackTable;
+
+    fn fill(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        OpStackTable::fill_trace(table, aet);
+    }
+
+    fn pad(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        OpStackTable::pad_trace(table, aet.processor_matrix.nrows());
+    }
+
+    fn extend(
+        base_table: ArrayView2<BFieldElement>,
+        ext_table: ArrayViewMut2<XFieldElement>,
+        challenges: &AllChallenges,
+    ) {
+        OpStackTable::extend(base_table, ext_table, &challenges.op_stack_table_challenges);
+    }
+
+    fn cross_table_arguments() -> Vec<(ProcessorExtTableColumn, Self::ExtColumn)> {
+        vec![(
+            ProcessorExtTableColumn::OpStackTablePermArg,
+            RunningProductPermArg,
+        )]
+    }
+}
+
 #[derive(Debug, Copy, Clone, Display, EnumCountMacro, EnumIter, PartialEq, Eq, Hash)]
 pub enum OpStackTableChallengeId {
     ProcessorPermIndeterminate,
@@ -464,3 +497,11 @@ pub struct OpStackTableChallenges {
     /// Weight for accumulating all clock jump differences
     pub all_clock_jump_differences_multi_perm_indeterminate: XFieldElement,
 }
+
+#[cfg(test)]
+mod op_stack_table_tests {
+    use crate::coprocessor_table_tests;
+    use crate::table::op_stack_table::OpStackTable;
+
+    coprocessor_table_tests!(OpStackTable);
+}
@@ -18,6 +18,7 @@ use strum::EnumCount;
 use strum_macros::Display;
 use strum_macros::EnumCount as EnumCountMacro;
 use strum_macros::EnumIter;
+use triton_opcodes::for_all_instructions;
 use triton_opcodes::instruction::all_instructions_without_args;
 use triton_opcodes::instruction::{AnInstruction::*, Instruction};
 use triton_opcodes::ord_n::Ord7;
@@ -36,6 +37,7 @@ use crate::table::constraint_circuit::InputIndicator;
 use crate::table::constraint_circuit::SingleRowIndicator;
 use crate::table::cross_table_argument::CrossTableArg;
 use crate::table::cross_table_argument::EvalArg;
+use crate::table::cross_table_argument::LookupArg;
 use crate::table::cross_table_argument::PermArg;
 use crate::table::master_table::NUM_BASE_COLUMNS;
 use crate::table::master_table::NUM_EXT_COLUMNS;
@@ -49,8 +51,9 @@ use crate::table::table_column::ProcessorExtTableColumn;
 use crate::table::table_column::ProcessorExtTableColumn::*;
 use crate::vm::AlgebraicExecutionTrace;
 
-pub const PROCESSOR_TABLE_NUM_PERMUTATION_ARGUMENTS: usize = 5;
+pub const PROCESSOR_TABLE_NUM_PERMUTATION_ARGUMENTS: usize = 4;
 pub const PROCESSOR_TABLE_NUM_EVALUATION_ARGUMENTS: usize = 5;
+pub const PROCESSOR_TABLE_NUM_LOOKUP_ARGUMENTS: usize = 1;
 pub const PROCESSOR_TABLE_NUM_EXTENSION_CHALLENGES: usize = ProcessorTableChallengeId::COUNT;
 
 pub const BASE_WIDTH: usize = ProcessorBaseTableColumn::COUNT;
@@ -141,7 +144,7 @@ impl ProcessorTable {
 
         let mut input_table_running_evaluation = EvalArg::default_initial();
         let mut output_table_running_evaluation = EvalArg::default_initial();
-        let mut instruction_table_running_product = PermArg::default_initial();
+        let mut instruction_lookup_log_derivative = LookupArg::default_initial();
         let mut op_stack_table_running_product = PermArg::default_initial();
         let mut ram_table_running_product = PermArg::default_initial();
         let mut jump_stack_running_product = PermArg::default_initial();
@@ -168,9 +171,11 @@ impl ProcessorTable {
             // Output table
             if current_row[CI.base_table_index()] == Instruction::WriteIo.opcode_b() {
                 let output_symbol = current_row[ST0.base_table_index()];
+                let output_tag = current_row[ST1.base_table_index()];
                 output_table_running_evaluation = output_table_running_evaluation
                     * challenges.standard_output_eval_indeterminate
-                    + output_symbol;
+                    + output_symbol
+                    + output_tag * challenges.standard_output_tag_weight;
             }
 
             // Instruction table
@@ -178,12 +183,13 @@ impl ProcessorTable {
                 let ip = current_row[IP.base_table_index()];
                 let ci = current_row[CI.base_table_index()];
                 let nia = current_row[NIA.base_table_index()];
-                let compressed_row_for_instruction_table_permutation_argument = ip
+                let compressed_row_for_instruction_lookup = ip
                     * challenges.instruction_table_ip_weight
                     + ci * challenges.instruction_table_ci_processor_weight
                     + nia * challenges.instruction_table_nia_weight;
-                instruction_table_running_product *= challenges.instruction_perm_indeterminate
-                    - compressed_row_for_instruction_table_permutation_argument;
+                instruction_lookup_log_derivative += (challenges.instruction_lookup_indeterminate
+                    - compressed_row_for_instruction_lookup)
+                    .inverse();
             }
 
             // OpStack table
@@ -251,7 +257,7 @@ impl ProcessorTable {
                 ];
                 let compressed_row_for_hash_input: XFieldElement = st_0_through_9
                     .into_iter()
-                    .zip_eq(hash_table_stack_input_challenges.into_iter())
+                    .zip_eq(hash_table_stack_input_challenges)
                     .map(|(st, weight)| weight * st)
                     .sum();
                 to_hash_table_running_evaluation = to_hash_table_running_evaluation
@@ -278,7 +284,7 @@ impl ProcessorTable {
                     ];
                     let compressed_row_for_hash_digest: XFieldElement = st_5_through_9
                         .into_iter()
-                        .zip_eq(hash_table_digest_output_challenges.into_iter())
+                        .zip_eq(hash_table_digest_output_challenges)
                         .map(|(st, weight)| weight * st)
                         .sum();
                     from_hash_table_running_evaluation = from_hash_table_running_evaluation
@@ -319,8 +325,8 @@ impl ProcessorTable {
             let mut extension_row = ext_table.row_mut(row_idx);
             extension_row[InputTableEvalArg.ext_table_index()] = input_table_running_evaluation;
             extension_row[OutputTableEvalArg.ext_table_index()] = output_table_running_evaluation;
-            extension_row[InstructionTablePermArg.ext_table_index()] =
-                instruction_table_running_product;
+            extension_row[InstructionLookupClientLogDerivative.ext_table_index()] =
+                instruction_lookup_log_derivative;
             extension_row[OpStackTablePermArg.ext_table_index()] = op_stack_table_running_product;
             extension_row[RamTablePermArg.ext_table_index()] = ram_table_running_product;
             extension_row[JumpStackTablePermArg.ext_table_index()] = jump_stack_running_product;
@@ -507,7 +513,7 @@ pub enum ProcessorTableChallengeId {
     ToHashTableEvalIndeterminate,
     FromHashTableEvalIndeterminate,
 
-    InstructionPermIndeterminate,
+    InstructionLookupIndeterminate,
     OpStackPermIndeterminate,
     RamPermIndeterminate,
     JumpStackPermIndeterminate,
@@ -554,6 +560,13 @@ pub enum ProcessorTableChallengeId {
     HashTableDigestOutputWeight2,
     HashTableDigestOutputWeight3,
     HashTableDigestOutputWeight4,
+
+    /// Weight of an output's tag in the running evaluation of the standard output. Zero if the
+    /// claim's output is untagged.
+    StandardOutputTagWeight,
+
+    /// Not a challenge but the claimed entry point, _i.e._, the initial instruction pointer.
+    EntryPoint,
 }
 
 impl From<ProcessorTableChallengeId> for usize {
@@ -571,7 +584,7 @@ pub struct ProcessorTableChallenges {
     pub to_hash_table_eval_indeterminate: XFieldElement,
     pub from_hash_table_eval_indeterminate: XFieldElement,
 
-    pub instruction_perm_indeterminate: XFieldElement,
+    pub instruction_lookup_indeterminate: XFieldElement,
     pub op_stack_perm_indeterminate: XFieldElement,
     pub ram_perm_indeterminate: XFieldElement,
     pub jump_stack_perm_indeterminate: XFieldElement,
@@ -618,6 +631,13 @@ pub struct ProcessorTableChallenges {
     pub hash_table_digest_output_weight2: XFieldElement,
     pub hash_table_digest_output_weight3: XFieldElement,
     pub hash_table_digest_output_weight4: XFieldElement,
+
+    /// Weight of an output's tag in the running evaluation of the standard output. Zero if the
+    /// claim's output is untagged.
+    pub standard_output_tag_weight: XFieldElement,
+
+    /// Not a challenge but the claimed entry point, _i.e._, the initial instruction pointer.
+    pub entry_point: XFieldElement,
 }
 
 impl TableChallenges for ProcessorTableChallenges {
@@ -630,7 +650,7 @@ impl TableChallenges for ProcessorTableChallenges {
             StandardOutputEvalIndeterminate => self.standard_output_eval_indeterminate,
             ToHashTableEvalIndeterminate => self.to_hash_table_eval_indeterminate,
             FromHashTableEvalIndeterminate => self.from_hash_table_eval_indeterminate,
-            InstructionPermIndeterminate => self.instruction_perm_indeterminate,
+            InstructionLookupIndeterminate => self.instruction_lookup_indeterminate,
             OpStackPermIndeterminate => self.op_stack_perm_indeterminate,
             RamPermIndeterminate => self.ram_perm_indeterminate,
             JumpStackPermIndeterminate => self.jump_stack_perm_indeterminate,
@@ -671,6 +691,8 @@ impl TableChallenges for ProcessorTableChallenges {
             HashTableDigestOutputWeight2 => self.hash_table_digest_output_weight2,
             HashTableDigestOutputWeight3 => self.hash_table_digest_output_weight3,
             HashTableDigestOutputWeight4 => self.hash_table_digest_output_weight4,
+            StandardOutputTagWeight => self.standard_output_tag_weight,
+            EntryPoint => self.entry_point,
         }
     }
 }
@@ -699,7 +721,7 @@ impl ExtProcessorTable {
         let challenge = |c| factory.circuit_builder.challenge(c);
 
         let clk_is_0 = factory.clk();
-        let ip_is_0 = factory.ip();
+        let ip_is_entry_point = factory.ip() - challenge(EntryPoint);
         let jsp_is_0 = factory.jsp();
         let jso_is_0 = factory.jso();
         let jsd_is_0 = factory.jsd();
@@ -757,15 +779,18 @@ impl ExtProcessorTable {
             factory.running_evaluation_standard_input() - constant_x(EvalArg::default_initial());
 
         // instruction table
-        let instruction_indeterminate = challenge(InstructionPermIndeterminate);
+        let instruction_indeterminate = challenge(InstructionLookupIndeterminate);
+        let instruction_ip_weight = challenge(InstructionTableIpWeight);
         let instruction_ci_weight = challenge(InstructionTableCiProcessorWeight);
         let instruction_nia_weight = challenge(InstructionTableNiaWeight);
-        let compressed_row_for_instruction_table =
-            instruction_ci_weight * factory.ci() + instruction_nia_weight * factory.nia();
-        let running_product_for_instruction_table_is_initialized_correctly = factory
-            .running_product_instruction_table()
-            - constant_x(PermArg::default_initial())
-                * (instruction_indeterminate - compressed_row_for_instruction_table);
+        let compressed_row_for_instruction_table = instruction_ip_weight * factory.ip()
+            + instruction_ci_weight * factory.ci()
+            + instruction_nia_weight * factory.nia();
+        let instruction_lookup_log_derivative_is_initialized_correctly = (factory
+            .instruction_lookup_log_derivative()
+            - constant_x(LookupArg::default_initial()))
+            * (instruction_indeterminate - compressed_row_for_instruction_table)
+            - factory.one();
 
         // standard output
         let running_evaluation_for_standard_output_is_initialized_correctly =
@@ -825,7 +850,7 @@ impl ExtProcessorTable {
 
         [
             clk_is_0,
-            ip_is_0,
+            ip_is_entry_point,
             jsp_is_0,
             jso_is_0,
             jsd_is_0,
@@ -854,7 +879,7 @@ impl ExtProcessorTable {
             reu_starts_correctly,
             rpm_starts_correctly,
             running_evaluation_for_standard_input_is_initialized_correctly,
-            running_product_for_instruction_table_is_initialized_correctly,
+            instruction_lookup_log_derivative_is_initialized_correctly,
             running_evaluation_for_standard_output_is_initialized_correctly,
             running_product_for_op_stack_table_is_initialized_correctly,
             running_product_for_ram_table_is_initialized_correctly,
@@ -927,37 +952,13 @@ impl ExtProcessorTable {
         let mut factory = DualRowConstraints::default();
 
         // instruction-specific constraints
-        let all_instruction_transition_constraints: [_; Instruction::COUNT] = [
-            (Pop, factory.instruction_pop()),
-            (Push(Default::default()), factory.instruction_push()),
-            (Divine(Default::default()), factory.instruction_divine()),
-            (Dup(Default::default()), factory.instruction_dup()),
-            (Swap(Default::default()), factory.instruction_swap()),
-            (Nop, factory.instruction_nop()),
-            (Skiz, factory.instruction_skiz()),
-            (Call(Default::default()), factory.instruction_call()),
-            (Return, factory.instruction_return()),
-            (Recurse, factory.instruction_recurse()),
-            (Assert, factory.instruction_assert()),
-            (Halt, factory.instruction_halt()),
-            (ReadMem, factory.instruction_read_mem()),
-            (WriteMem, factory.instruction_write_mem()),
-            (Hash, factory.instruction_hash()),
-            (DivineSibling, factory.instruction_divine_sibling()),
-            (AssertVector, factory.instruction_assert_vector()),
-            (Add, factory.instruction_add()),
-            (Mul, factory.instruction_mul()),
-            (Invert, factory.instruction_invert()),
-            (Split, factory.instruction_split()),
-            (Eq, factory.instruction_eq()),
-            (Lsb, factory.instruction_lsb()),
-            (XxAdd, factory.instruction_xxadd()),
-            (XxMul, factory.instruction_xxmul()),
-            (XInvert, factory.instruction_xinv()),
-            (XbMul, factory.instruction_xbmul()),
-            (ReadIo, factory.instruction_read_io()),
-            (WriteIo, factory.instruction_write_io()),
-        ];
+        let all_instruction_transition_constraints =
+            all_instructions_without_args().map(|instruction| {
+                (
+                    instruction,
+                    factory.instruction_transition_constraints(instruction),
+                )
+            });
 
         let mut transition_constraints = Self::combine_instruction_constraints_with_deselectors(
             &mut factory,
@@ -1036,8 +1037,7 @@ impl ExtProcessorTable {
 
         transition_constraints
             .push(factory.running_evaluation_for_standard_input_updates_correctly());
-        transition_constraints
-            .push(factory.running_product_for_instruction_table_updates_correctly());
+        transition_constraints.push(factory.instruction_lookup_log_derivative_updates_correctly());
         transition_constraints
             .push(factory.running_evaluation_for_standard_output_updates_correctly());
         transition_constraints.push(factory.running_product_for_op_stack_table_updates_correctly());
@@ -1560,13 +1560,14 @@ impl SingleRowConstraints {
     > {
         self.ext_row_variables[OutputTableEvalArg.master_ext_table_index()].clone()
     }
-    pub fn running_product_instruction_table(
+    pub fn instruction_lookup_log_derivative(
         &self,
     ) -> ConstraintCircuitMonad<
         ProcessorTableChallenges,
         SingleRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
     > {
-        self.ext_row_variables[InstructionTablePermArg.master_ext_table_index()].clone()
+        self.ext_row_variables[InstructionLookupClientLogDerivative.master_ext_table_index()]
+            .clone()
     }
     pub fn running_product_op_stack_table(
         &self,
@@ -1994,6 +1995,35 @@ impl DualRowConstraints {
         [specific_constraints, self.keep_stack(), self.keep_ram()].concat()
     }
 
+    pub fn instruction_tail_call(
+        &self,
+    ) -> Vec<
+        ConstraintCircuitMonad<
+            ProcessorTableChallenges,
+            DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
+        >,
+    > {
+        // The jump stack pointer jsp does not change.
+        let jsp_does_not_change = self.jsp_next() - self.jsp();
+
+        // The jump's origin jso does not change.
+        let jso_does_not_change = self.jso_next() - self.jso();
+
+        // The jump's destination jsd is set to the instruction's argument.
+        let jsd_becomes_nia = self.jsd_next() - self.nia();
+
+        // The instruction pointer ip is set to the instruction's argument.
+        let ip_becomes_nia = self.ip_next() - self.nia();
+
+        let specific_constraints = vec![
+            jsp_does_not_change,
+            jso_does_not_change,
+            jsd_becomes_nia,
+            ip_becomes_nia,
+        ];
+        [specific_constraints, self.keep_stack(), self.keep_ram()].concat()
+    }
+
     pub fn instruction_return(
         &self,
     ) -> Vec<
@@ -2110,6 +2140,61 @@ impl DualRowConstraints {
         [specific_constraints, self.step_1(), self.keep_stack()].concat()
     }
 
+    pub fn instruction_read_mem_inc(
+        &self,
+    ) -> Vec<
+        ConstraintCircuitMonad<
+            ProcessorTableChallenges,
+            DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
+        >,
+    > {
+        // the RAM pointer is overwritten with st1
+        let update_ramp = self.ramp_next() - self.st1();
+
+        // The top of the stack is overwritten with the RAM value.
+        let st0_becomes_ramv = self.st0_next() - self.ramv_next();
+
+        // The address in st1 is incremented.
+        let st1_increments = self.st1_next() - self.st1() - self.one();
+
+        let specific_constraints = vec![update_ramp, st0_becomes_ramv, st1_increments];
+        [
+            specific_constraints,
+            self.step_1(),
+            self.stack_remains_and_top_two_elements_unconstrained(),
+        ]
+        .concat()
+    }
+
+    pub fn instruction_write_mem_inc(
+        &self,
+    ) -> Vec<
+        ConstraintCircuitMonad<
+            ProcessorTableChallenges,
+            DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
+        >,
+    > {
+        // the RAM pointer is overwritten with st1
+        let update_ramp = self.ramp_next() - self.st1();
+
+        // The RAM value is overwritten with the top of the stack.
+        let ramv_becomes_st0 = self.ramv_next() - self.st0();
+
+        // The top of the stack does not change.
+        let st0_remains = self.st0_next() - self.st0();
+
+        // The address in st1 is incremented.
+        let st1_increments = self.st1_next() - self.st1() - self.one();
+
+        let specific_constraints = vec![update_ramp, ramv_becomes_st0, st0_remains, st1_increments];
+        [
+            specific_constraints,
+            self.step_1(),
+            self.stack_remains_and_top_two_elements_unconstrained(),
+        ]
+        .concat()
+    }
+
     /// Two Evaluation Arguments with the Hash Table guarantee correct transition.
     pub fn instruction_hash(
         &self,
@@ -2452,7 +2537,7 @@ impl DualRowConstraints {
         .concat()
     }
 
-    pub fn instruction_xinv(
+    pub fn instruction_xinvert(
         &self,
     ) -> Vec<
         ConstraintCircuitMonad<
@@ -2564,6 +2649,27 @@ impl DualRowConstraints {
         [self.step_1(), self.shrink_stack(), self.keep_ram()].concat()
     }
 
+    /// The transition constraints specific to the given instruction. Any argument the instruction
+    /// might have is ignored.
+    pub fn instruction_transition_constraints(
+        &self,
+        instruction: Instruction,
+    ) -> Vec<
+        ConstraintCircuitMonad<
+            ProcessorTableChallenges,
+            DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
+        >,
+    > {
+        macro_rules! instruction_transition_constraints {
+            ($($name:ident: $pattern:pat => $representative:expr),* $(,)?) => {
+                match instruction {
+                    $($pattern => self.$name()),*
+                }
+            };
+        }
+        for_all_instructions!(instruction_transition_constraints)
+    }
+
     pub fn zero(
         &self,
     ) -> ConstraintCircuitMonad<
@@ -3045,13 +3151,15 @@ impl DualRowConstraints {
     > {
         self.current_ext_row_variables[OutputTableEvalArg.master_ext_table_index()].clone()
     }
-    pub fn running_product_instruction_table(
+    pub fn instruction_lookup_log_derivative(
         &self,
     ) -> ConstraintCircuitMonad<
         ProcessorTableChallenges,
         DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
     > {
-        self.current_ext_row_variables[InstructionTablePermArg.master_ext_table_index()].clone()
+        self.current_ext_row_variables
+            [InstructionLookupClientLogDerivative.master_ext_table_index()]
+        .clone()
     }
     pub fn running_product_op_stack_table(
         &self,
@@ -3486,13 +3594,14 @@ impl DualRowConstraints {
     > {
         self.next_ext_row_variables[OutputTableEvalArg.master_ext_table_index()].clone()
     }
-    pub fn running_product_instruction_table_next(
+    pub fn instruction_lookup_log_derivative_next(
         &self,
     ) -> ConstraintCircuitMonad<
         ProcessorTableChallenges,
         DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
     > {
-        self.next_ext_row_variables[InstructionTablePermArg.master_ext_table_index()].clone()
+        self.next_ext_row_variables[InstructionLookupClientLogDerivative.master_ext_table_index()]
+            .clone()
     }
     pub fn running_product_op_stack_table_next(
         &self,
@@ -3783,7 +3892,7 @@ impl DualRowConstraints {
         .concat()
     }
 
-    pub fn unop(
+    pub fn stack_remains_and_top_two_elements_unconstrained(
         &self,
     ) -> Vec<
         ConstraintCircuitMonad<
@@ -3792,8 +3901,6 @@ impl DualRowConstraints {
         >,
     > {
         let specific_constraints = vec![
-            // The stack element in st1 does not change.
-            self.st1_next() - self.st1(),
             // The stack element in st2 does not change.
             self.st2_next() - self.st2(),
         ];
@@ -3804,6 +3911,25 @@ impl DualRowConstraints {
         .concat()
     }
 
+    pub fn unop(
+        &self,
+    ) -> Vec<
+        ConstraintCircuitMonad<
+            ProcessorTableChallenges,
+            DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
+        >,
+    > {
+        let specific_constraints = vec![
+            // The stack element in st1 does not change.
+            self.st1_next() - self.st1(),
+        ];
+        [
+            specific_constraints,
+            self.stack_remains_and_top_two_elements_unconstrained(),
+        ]
+        .concat()
+    }
+
     pub fn keep_stack(
         &self,
     ) -> Vec<
@@ -3853,13 +3979,15 @@ impl DualRowConstraints {
             + read_io_deselector * running_evaluation_updates
     }
 
-    pub fn running_product_for_instruction_table_updates_correctly(
+    pub fn instruction_lookup_log_derivative_updates_correctly(
         &self,
     ) -> ConstraintCircuitMonad<
         ProcessorTableChallenges,
         DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
     > {
-        let indeterminate = self.circuit_builder.challenge(InstructionPermIndeterminate);
+        let indeterminate = self
+            .circuit_builder
+            .challenge(InstructionLookupIndeterminate);
         let ip_weight = self.circuit_builder.challenge(InstructionTableIpWeight);
         let ci_weight = self
             .circuit_builder
@@ -3867,13 +3995,14 @@ impl DualRowConstraints {
         let nia_weight = self.circuit_builder.challenge(InstructionTableNiaWeight);
         let compressed_row =
             ip_weight * self.ip_next() + ci_weight * self.ci_next() + nia_weight * self.nia_next();
-        let running_product_updates = self.running_product_instruction_table_next()
-            - self.running_product_instruction_table() * (indeterminate - compressed_row);
-        let running_product_remains = self.running_product_instruction_table_next()
-            - self.running_product_instruction_table();
+        let log_derivative_difference = self.instruction_lookup_log_derivative_next()
+            - self.instruction_lookup_log_derivative();
+        let log_derivative_updates =
+            log_derivative_difference.clone() * (indeterminate - compressed_row) - self.one();
+        let log_derivative_remains = log_derivative_difference;
 
-        (self.one() - self.is_padding_next()) * running_product_updates
-            + self.is_padding_next() * running_product_remains
+        (self.one() - self.is_padding_next()) * log_derivative_updates
+            + self.is_padding_next() * log_derivative_remains
     }
 
     pub fn running_evaluation_for_standard_output_updates_correctly(
@@ -3889,9 +4018,12 @@ impl DualRowConstraints {
             InstructionDeselectors::instruction_deselector_next(self, Instruction::WriteIo);
         let write_io_selector = self.ci_next() - self.constant_b(Instruction::WriteIo.opcode_b());
         let output_symbol = self.st0_next();
+        let output_tag = self.st1_next();
+        let tag_weight = self.circuit_builder.challenge(StandardOutputTagWeight);
         let running_evaluation_updates = self.running_evaluation_standard_output_next()
             - indeterminate * self.running_evaluation_standard_output()
-            - output_symbol;
+            - output_symbol
+            - tag_weight * output_tag;
         let running_evaluation_remains = self.running_evaluation_standard_output_next()
             - self.running_evaluation_standard_output();
 
@@ -4001,7 +4133,7 @@ impl DualRowConstraints {
         ];
         let compressed_row = weights
             .into_iter()
-            .zip_eq(state.into_iter())
+            .zip_eq(state)
             .map(|(weight, state)| weight * state)
             .sum();
         let running_evaluation_updates = self.running_evaluation_to_hash_table_next()
@@ -4043,7 +4175,7 @@ impl DualRowConstraints {
         ];
         let compressed_row = weights
             .into_iter()
-            .zip_eq(state.into_iter())
+            .zip_eq(state)
             .map(|(weight, state)| weight * state)
             .sum();
         let running_evaluation_updates = self.running_evaluation_from_hash_table_next()
@@ -4116,7 +4248,7 @@ impl InstructionDeselectors {
 
         instruction_bucket_polynomials
             .into_iter()
-            .zip_eq(deselector_polynomials.into_iter())
+            .zip_eq(deselector_polynomials)
             .map(|(bucket_poly, deselector_poly)| bucket_poly - deselector_poly)
             .fold(one, ConstraintCircuitMonad::mul)
     }
@@ -4230,6 +4362,7 @@ impl<'a> Display for ProcessorMatrixRow<'a> {
         let instruction_with_arg = match instruction {
             Push(_) => Push(self.row[NIA.base_table_index()]),
             Call(_) => Call(self.row[NIA.base_table_index()]),
+            TailCall(_) => TailCall(self.row[NIA.base_table_index()]),
             Dup(_) => Dup((self.row[NIA.base_table_index()].value() as u32)
                 .try_into()
                 .unwrap()),
@@ -4384,7 +4517,7 @@ impl<'a> Display for ExtProcessorMatrixRow<'a> {
         )?;
         row(f, "input_table_ea", InputTableEvalArg)?;
         row(f, "output_table_ea", OutputTableEvalArg)?;
-        row(f, "instr_table_pa", InstructionTablePermArg)?;
+        row(f, "instr_lookup_ld", InstructionLookupClientLogDerivative)?;
         row(f, "opstack_table_pa", OpStackTablePermArg)?;
         row(f, "ram_table_pa", RamTablePermArg)?;
         row(f, "jumpstack_table_pa", JumpStackTablePermArg)?;
@@ -4417,7 +4550,7 @@ mod constraint_polynomial_tests {
     fn print_simple_processor_table_row_test() {
         let code = "push 2 push -1 add assert halt";
         let program = Program::from_code(code).unwrap();
-        let (aet, _, _) = simulate_no_input(&program);
+        let (aet, _) = simulate_no_input(&program).unwrap();
         for row in aet.processor_matrix.rows() {
             println!("{}", ProcessorMatrixRow { row });
         }
@@ -4431,48 +4564,6 @@ mod constraint_polynomial_tests {
             .to_owned()
     }
 
-    fn get_transition_constraints_for_instruction(
-        instruction: Instruction,
-    ) -> Vec<
-        ConstraintCircuitMonad<
-            ProcessorTableChallenges,
-            DualRowIndicator<NUM_BASE_COLUMNS, NUM_EXT_COLUMNS>,
-        >,
-    > {
-        let tc = DualRowConstraints::default();
-        match instruction {
-            Pop => tc.instruction_pop(),
-            Push(_) => tc.instruction_push(),
-            Divine(_) => tc.instruction_divine(),
-            Dup(_) => tc.instruction_dup(),
-            Swap(_) => tc.instruction_swap(),
-            Nop => tc.instruction_nop(),
-            Skiz => tc.instruction_skiz(),
-            Call(_) => tc.instruction_call(),
-            Return => tc.instruction_return(),
-            Recurse => tc.instruction_recurse(),
-            Assert => tc.instruction_assert(),
-            Halt => tc.instruction_halt(),
-            ReadMem => tc.instruction_read_mem(),
-            WriteMem => tc.instruction_write_mem(),
-            Hash => tc.instruction_hash(),
-            DivineSibling => tc.instruction_divine_sibling(),
-            AssertVector => tc.instruction_assert_vector(),
-            Add => tc.instruction_add(),
-            Mul => tc.instruction_mul(),
-            Invert => tc.instruction_invert(),
-            Split => tc.instruction_split(),
-            Eq => tc.instruction_eq(),
-            Lsb => tc.instruction_lsb(),
-            XxAdd => tc.instruction_xxadd(),
-            XxMul => tc.instruction_xxmul(),
-            XInvert => tc.instruction_xinv(),
-            XbMul => tc.instruction_xbmul(),
-            ReadIo => tc.instruction_read_io(),
-            WriteIo => tc.instruction_write_io(),
-        }
-    }
-
     fn test_constraints_for_rows_with_debug_info(
         instruction: Instruction,
         master_base_tables: &[Array2<BFieldElement>],
@@ -4502,10 +4593,10 @@ mod constraint_polynomial_tests {
                 curr_row[CI.master_base_table_index()],
                 "The test is trying to check the wrong transition constraint polynomials."
             );
-            for (constraint_idx, constraint_circuit) in
-                get_transition_constraints_for_instruction(instruction)
-                    .into_iter()
-                    .enumerate()
+            for (constraint_idx, constraint_circuit) in DualRowConstraints::default()
+                .instruction_transition_constraints(instruction)
+                .into_iter()
+                .enumerate()
             {
                 let evaluation_result = constraint_circuit.consume().evaluate(
                     test_rows.view(),
@@ -4590,6 +4681,20 @@ mod constraint_polynomial_tests {
         );
     }
 
+    #[test]
+    fn transition_constraints_for_instruction_tail_call_test() {
+        let test_rows = [get_test_row_from_source_code(
+            "call foo halt foo: tail_call bar bar: return",
+            1,
+        )];
+        test_constraints_for_rows_with_debug_info(
+            TailCall(Default::default()),
+            &test_rows,
+            &[IP, CI, NIA, JSP, JSO, JSD],
+            &[IP, CI, NIA, JSP, JSO, JSD],
+        );
+    }
+
     #[test]
     fn transition_constraints_for_instruction_return_test() {
         let test_rows = [get_test_row_from_source_code(
@@ -4793,38 +4898,13 @@ mod constraint_polynomial_tests {
     #[test]
     fn print_number_and_degrees_of_transition_constraints_for_all_instructions() {
         let factory = DualRowConstraints::default();
-        let all_instructions_and_their_transition_constraints: [(Instruction, _);
-            Instruction::COUNT] = [
-            (Pop, factory.instruction_pop()),
-            (Push(Default::default()), factory.instruction_push()),
-            (Divine(Default::default()), factory.instruction_divine()),
-            (Dup(Default::default()), factory.instruction_dup()),
-            (Swap(Default::default()), factory.instruction_swap()),
-            (Nop, factory.instruction_nop()),
-            (Skiz, factory.instruction_skiz()),
-            (Call(Default::default()), factory.instruction_call()),
-            (Return, factory.instruction_return()),
-            (Recurse, factory.instruction_recurse()),
-            (Assert, factory.instruction_assert()),
-            (Halt, factory.instruction_halt()),
-            (ReadMem, factory.instruction_read_mem()),
-            (WriteMem, factory.instruction_write_mem()),
-            (Hash, factory.instruction_hash()),
-            (DivineSibling, factory.instruction_divine_sibling()),
-            (AssertVector, factory.instruction_assert_vector()),
-            (Add, factory.instruction_add()),
-            (Mul, factory.instruction_mul()),
-            (Invert, factory.instruction_invert()),
-            (Split, factory.instruction_split()),
-            (Eq, factory.instruction_eq()),
-            (Lsb, factory.instruction_lsb()),
-            (XxAdd, factory.instruction_xxadd()),
-            (XxMul, factory.instruction_xxmul()),
-            (XInvert, factory.instruction_xinv()),
-            (XbMul, factory.instruction_xbmul()),
-            (ReadIo, factory.instruction_read_io()),
-            (WriteIo, factory.instruction_write_io()),
-        ];
+        let all_instructions_and_their_transition_constraints = all_instructions_without_args()
+            .map(|instruction| {
+                (
+                    instruction,
+                    factory.instruction_transition_constraints(instruction),
+                )
+            });
 
         println!("| Instruction     | #polys | max deg | Degrees");
         println!("|:----------------|-------:|--------:|:------------");
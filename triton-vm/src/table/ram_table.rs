@@ -21,6 +21,7 @@ use twenty_first::shared_math::x_field_element::XFieldElement;
 
 use RamTableChallengeId::*;
 
+use crate::table::challenges::AllChallenges;
 use crate::table::challenges::TableChallenges;
 use crate::table::constraint_circuit::ConstraintCircuit;
 use crate::table::constraint_circuit::ConstraintCircuitBuilder;
@@ -28,8 +29,10 @@ use crate::table::constraint_circuit::DualRowIndicator;
 use crate::table::constraint_circuit::DualRowIndicator::*;
 use crate::table::constraint_circuit::SingleRowIndicator;
 use crate::table::constraint_circuit::SingleRowIndicator::*;
+use crate::table::coprocessor::CoprocessorTable;
 use crate::table::cross_table_argument::CrossTableArg;
 use crate::table::cross_table_argument::PermArg;
+use crate::table::master_table::TableId;
 use crate::table::master_table::NUM_BASE_COLUMNS;
 use crate::table::master_table::NUM_EXT_COLUMNS;
 use crate::table::table_column::BaseTableColumn;
@@ -37,6 +40,7 @@ use crate::table::table_column::ExtTableColumn;
 use crate::table::table_column::MasterBaseTableColumn;
 use crate::table::table_column::MasterExtTableColumn;
 use crate::table::table_column::ProcessorBaseTableColumn;
+use crate::table::table_column::ProcessorExtTableColumn;
 use crate::table::table_column::RamBaseTableColumn;
 use crate::table::table_column::RamBaseTableColumn::*;
 use crate::table::table_column::RamExtTableColumn;
@@ -327,6 +331,35 @@ impl RamTable {
     }
 }
 
+impl CoprocessorTable for RamTable {
+    const ID: TableId = TableId::RamTable;
+    type ExtColumn = RamExtTableColumn;
+    type Constraints = ExtRamTable;
+
+    fn fill(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        RamTable::fill_trace(table, aet);
+    }
+
+    fn pad(table: &mut ArrayViewMut2<BFieldElement>, aet: &AlgebraicExecutionTrace) {
+        RamTable::pad_trace(table, aet.processor_matrix.nrows());
+    }
+
+    fn extend(
+        base_table: ArrayView2<BFieldElement>,
+        ext_table: ArrayViewMut2<XFieldElement>,
+        challenges: &AllChallenges,
+    ) {
+        RamTable::extend(base_table, ext_table, &challenges.ram_table_challenges);
+    }
+
+    fn cross_table_arguments() -> Vec<(ProcessorExtTableColumn, Self::ExtColumn)> {
+        vec![(
+            ProcessorExtTableColumn::RamTablePermArg,
+            RunningProductPermArg,
+        )]
+    }
+}
+
 impl ExtRamTable {
     pub fn ext_initial_constraints_as_circuits() -> Vec<
         ConstraintCircuit<
@@ -363,8 +396,10 @@ impl ExtRamTable {
         let rppa = circuit_builder.input(ExtRow(RunningProductPermArg.master_ext_table_index()));
 
         let write_mem_opcode = circuit_builder.b_constant(Instruction::WriteMem.opcode_b());
-        let ramv_is_0_or_was_written_to =
-            ramv.clone() * (write_mem_opcode - previous_instruction.clone());
+        let write_mem_inc_opcode = circuit_builder.b_constant(Instruction::WriteMemInc.opcode_b());
+        let ramv_is_0_or_was_written_to = ramv.clone()
+            * (write_mem_opcode - previous_instruction.clone())
+            * (write_mem_inc_opcode - previous_instruction.clone());
         let bezout_coefficient_polynomial_coefficient_0_is_0 = bcpc0;
         let bezout_coefficient_0_is_0 = bc0;
         let bezout_coefficient_1_is_bezout_coefficient_polynomial_coefficient_1 = bc1 - bcpc1;
@@ -485,18 +520,23 @@ impl ExtRamTable {
         let ramp_diff_is_0_or_iord_is_inverse_of_ramp_diff =
             ramp_diff.clone() * (ramp_changes.clone() - one.clone());
 
-        // (ramp doesn't change) and (previous instruction is not write_mem)
-        //      implies the ramv doesn't change
+        // The previous instruction is neither write_mem nor write_mem_inc
         let op_code_write_mem = circuit_builder.b_constant(Instruction::WriteMem.opcode_b());
+        let op_code_write_mem_inc = circuit_builder.b_constant(Instruction::WriteMemInc.opcode_b());
+        let previous_instruction_next_is_no_write = (op_code_write_mem
+            - previous_instruction_next.clone())
+            * (op_code_write_mem_inc - previous_instruction_next.clone());
+
+        // (ramp doesn't change) and (previous instruction is no write)
+        //      implies the ramv doesn't change
         let ramp_changes_or_write_mem_or_ramv_stays = (one.clone() - ramp_changes.clone())
-            * (op_code_write_mem.clone() - previous_instruction_next.clone())
+            * previous_instruction_next_is_no_write.clone()
             * (ramv_next.clone() - ramv);
 
-        // (ramp changes) and (previous instruction is not write_mem)
+        // (ramp changes) and (previous instruction is no write)
         //      implies the next ramv is 0
-        let ramp_stays_or_write_mem_or_ramv_next_is_0 = ramp_diff.clone()
-            * (op_code_write_mem - previous_instruction_next.clone())
-            * ramv_next.clone();
+        let ramp_stays_or_write_mem_or_ramv_next_is_0 =
+            ramp_diff.clone() * previous_instruction_next_is_no_write * ramv_next.clone();
 
         let bcbp0_only_changes_if_ramp_changes =
             (one.clone() - ramp_changes.clone()) * (bcpc0_next.clone() - bcpc0);
@@ -641,3 +681,11 @@ impl TableChallenges for RamTableChallenges {
         }
     }
 }
+
+#[cfg(test)]
+mod ram_table_tests {
+    use crate::coprocessor_table_tests;
+    use crate::table::ram_table::RamTable;
+
+    coprocessor_table_tests!(RamTable);
+}
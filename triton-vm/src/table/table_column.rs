@@ -47,13 +47,14 @@ pub enum InstructionBaseTableColumn {
     Address,
     CI,
     NIA,
+    LookupMultiplicity,
     IsPadding,
 }
 
 #[repr(usize)]
 #[derive(Display, Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumCountMacro, Hash)]
 pub enum InstructionExtTableColumn {
-    RunningProductPermArg,
+    InstructionLookupServerLogDerivative,
     RunningEvaluation,
 }
 
@@ -112,7 +113,7 @@ pub enum ProcessorBaseTableColumn {
 pub enum ProcessorExtTableColumn {
     InputTableEvalArg,
     OutputTableEvalArg,
-    InstructionTablePermArg,
+    InstructionLookupClientLogDerivative,
     OpStackTablePermArg,
     RamTablePermArg,
     JumpStackTablePermArg,
@@ -494,7 +495,7 @@ mod table_column_tests {
         assert_eq!(
             program_table::BASE_WIDTH,
             ProgramBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -503,7 +504,7 @@ mod table_column_tests {
         assert_eq!(
             instruction_table::BASE_WIDTH,
             InstructionBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -512,7 +513,7 @@ mod table_column_tests {
         assert_eq!(
             processor_table::BASE_WIDTH,
             ProcessorBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -521,7 +522,7 @@ mod table_column_tests {
         assert_eq!(
             op_stack_table::BASE_WIDTH,
             OpStackBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -530,7 +531,7 @@ mod table_column_tests {
         assert_eq!(
             ram_table::BASE_WIDTH,
             RamBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -539,7 +540,7 @@ mod table_column_tests {
         assert_eq!(
             jump_stack_table::BASE_WIDTH,
             JumpStackBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -548,7 +549,7 @@ mod table_column_tests {
         assert_eq!(
             hash_table::BASE_WIDTH,
             HashBaseTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .base_table_index()
                 + 1,
@@ -558,7 +559,7 @@ mod table_column_tests {
         assert_eq!(
             program_table::EXT_WIDTH,
             ProgramExtTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .ext_table_index()
                 + 1,
@@ -567,7 +568,7 @@ mod table_column_tests {
         assert_eq!(
             instruction_table::EXT_WIDTH,
             InstructionExtTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .ext_table_index()
                 + 1,
@@ -576,7 +577,7 @@ mod table_column_tests {
         assert_eq!(
             processor_table::EXT_WIDTH,
             ProcessorExtTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .ext_table_index()
                 + 1,
@@ -585,7 +586,7 @@ mod table_column_tests {
         assert_eq!(
             op_stack_table::EXT_WIDTH,
             OpStackExtTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .ext_table_index()
                 + 1,
@@ -593,13 +594,17 @@ mod table_column_tests {
         );
         assert_eq!(
             ram_table::EXT_WIDTH,
-            RamExtTableColumn::iter().last().unwrap().ext_table_index() + 1,
+            RamExtTableColumn::iter()
+                .next_back()
+                .unwrap()
+                .ext_table_index()
+                + 1,
             "RamTable's EXT_WIDTH is 1 + its max column index",
         );
         assert_eq!(
             jump_stack_table::EXT_WIDTH,
             JumpStackExtTableColumn::iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .ext_table_index()
                 + 1,
@@ -607,7 +612,11 @@ mod table_column_tests {
         );
         assert_eq!(
             hash_table::EXT_WIDTH,
-            HashExtTableColumn::iter().last().unwrap().ext_table_index() + 1,
+            HashExtTableColumn::iter()
+                .next_back()
+                .unwrap()
+                .ext_table_index()
+                + 1,
             "HashTable's EXT_WIDTH is 1 + its max column index",
         );
     }
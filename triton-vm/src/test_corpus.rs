@@ -0,0 +1,742 @@
+//! Triton assembly programs along with their input, covering every instruction of Triton VM.
+//! Running these programs through a modified pipeline – simulation, proving, verification – is a
+//! quick way to gain confidence in the modification.
+//!
+//! Only available with feature `test-utils`.
+
+use std::ops::BitAnd;
+use std::ops::BitXor;
+
+use itertools::Itertools;
+use num_traits::One;
+use num_traits::Zero;
+use rand::rngs::ThreadRng;
+use rand::Rng;
+use rand::RngCore;
+use triton_opcodes::instruction::MAX_SPILL_SIZE;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::other::random_elements;
+use twenty_first::shared_math::other::random_elements_array;
+use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+
+use crate::shared_tests::SourceCodeAndInput;
+use crate::signed_integer::i32_to_bfe;
+use crate::signed_integer::i64_to_bfes;
+use crate::sponge::Sponge;
+
+pub fn test_hash_nop_nop_lt() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("hash nop hash nop nop hash push 3 push 2 lt assert halt")
+}
+
+pub fn test_program_for_halt() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("halt")
+}
+
+pub fn test_program_for_push_pop_dup_swap_nop() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 1 push 2 pop assert \
+        push 1 dup0 assert assert \
+        push 1 push 2 swap1 assert pop \
+        nop nop nop halt",
+    )
+}
+
+pub fn test_program_for_divine() -> SourceCodeAndInput {
+    SourceCodeAndInput {
+        source_code: "divine assert halt".to_string(),
+        input: vec![],
+        secret_input: vec![BFieldElement::one()],
+    }
+}
+
+pub fn test_program_for_skiz() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 1 skiz push 0 skiz assert push 1 skiz halt")
+}
+
+pub fn test_program_for_call_recurse_return() -> SourceCodeAndInput {
+    let source_code = "push 2 call label halt label: push -1 add dup0 skiz recurse return";
+    SourceCodeAndInput::without_input(source_code)
+}
+
+pub fn test_program_for_tail_call() -> SourceCodeAndInput {
+    let source_code = "push 5 call ping push 0 eq assert halt \
+        ping: dup0 skiz tail_call pong return \
+        pong: push -1 add dup0 push 2 eq skiz recurse tail_call ping";
+    SourceCodeAndInput::without_input(source_code)
+}
+
+pub fn test_program_for_write_mem_read_mem() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 2 push 1 write_mem pop push 0 read_mem assert halt")
+}
+
+pub fn test_program_for_write_mem_inc_read_mem_inc() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 5 push 7 write_mem_inc pop push 8 write_mem_inc pop push 9 write_mem_inc pop pop \
+        push 5 push 0 read_mem_inc push 7 eq assert \
+        push 0 read_mem_inc push 8 eq assert \
+        push 0 read_mem_inc push 9 eq assert \
+        push 8 eq assert halt",
+    )
+}
+
+pub fn test_program_for_hash() -> SourceCodeAndInput {
+    let source_code =
+        "push 0 push 0 push 0 push 1 push 2 push 3 hash pop pop pop pop pop read_io eq assert halt";
+    let mut hash_input = [BFieldElement::zero(); 10];
+    hash_input[0] = BFieldElement::new(3);
+    hash_input[1] = BFieldElement::new(2);
+    hash_input[2] = BFieldElement::new(1);
+    let digest = RescuePrimeRegular::hash_10(&hash_input);
+    SourceCodeAndInput {
+        source_code: source_code.to_string(),
+        input: vec![digest.to_vec()[0]],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_divine_sibling_noswitch() -> SourceCodeAndInput {
+    let source_code = "
+        push 3 \
+        push 4 push 2 push 2 push 2 push 1 \
+        push 5679457 push 1337 push 345887 push -234578456 push 23657565 \
+        divine_sibling \
+        push 1 add assert assert assert assert assert \
+        assert \
+        push -1 add assert \
+        push -1 add assert \
+        push -1 add assert \
+        push -3 add assert \
+        assert halt ";
+    let one = BFieldElement::one();
+    let zero = BFieldElement::zero();
+    SourceCodeAndInput {
+        source_code: source_code.to_string(),
+        input: vec![],
+        secret_input: vec![one, one, one, one, zero],
+    }
+}
+
+pub fn test_program_for_divine_sibling_switch() -> SourceCodeAndInput {
+    let source_code = "
+        push 2 \
+        push 4 push 2 push 2 push 2 push 1 \
+        push 5679457 push 1337 push 345887 push -234578456 push 23657565 \
+        divine_sibling \
+        assert \
+        push -1 add assert \
+        push -1 add assert \
+        push -1 add assert \
+        push -3 add assert \
+        push 1 add assert assert assert assert assert \
+        assert halt ";
+    let one = BFieldElement::one();
+    let zero = BFieldElement::zero();
+    SourceCodeAndInput {
+        source_code: source_code.to_string(),
+        input: vec![],
+        secret_input: vec![one, one, one, one, zero],
+    }
+}
+
+pub fn test_program_for_assert_vector() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 1 push 2 push 3 push 4 push 5 \
+         push 1 push 2 push 3 push 4 push 5 \
+         assert_vector halt",
+    )
+}
+
+pub fn test_program_for_eq_vector() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 1 push 2 push 3 push 4 push 5 \
+         push 1 push 2 push 3 push 4 push 5 \
+         eq_vector halt",
+    )
+}
+
+pub fn property_based_test_program_for_assert_vector() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st0 = rng.gen_range(0..BFieldElement::QUOTIENT);
+    let st1 = rng.gen_range(0..BFieldElement::QUOTIENT);
+    let st2 = rng.gen_range(0..BFieldElement::QUOTIENT);
+    let st3 = rng.gen_range(0..BFieldElement::QUOTIENT);
+    let st4 = rng.gen_range(0..BFieldElement::QUOTIENT);
+
+    let source_code = format!(
+        "push {} push {} push {} push {} push {} \
+        read_io read_io read_io read_io read_io \
+        assert_vector halt",
+        st4, st3, st2, st1, st0,
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![st4.into(), st3.into(), st2.into(), st1.into(), st0.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_add_mul_invert() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 2 push -1 add assert \
+        push -1 push -1 mul assert \
+        push 3 dup0 invert mul assert \
+        halt",
+    )
+}
+
+pub fn test_program_for_instruction_split() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push -1 split swap1 lt assert halt ")
+}
+
+pub fn property_based_test_program_for_split() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st0 = rng.next_u64() % BFieldElement::QUOTIENT;
+    let hi = st0 >> 32;
+    let lo = st0 & 0xffff_ffff;
+
+    let source_code = format!(
+        "push {} split read_io eq assert read_io eq assert halt",
+        st0
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![hi.into(), lo.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_eq() -> SourceCodeAndInput {
+    SourceCodeAndInput {
+        source_code: "read_io divine eq assert halt".to_string(),
+        input: vec![BFieldElement::new(42)],
+        secret_input: vec![BFieldElement::new(42)],
+    }
+}
+
+pub fn property_based_test_program_for_eq() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st0 = rng.next_u64() % BFieldElement::QUOTIENT;
+
+    let source_code = format!(
+        "push {} dup0 read_io eq assert dup0 divine eq assert halt",
+        st0
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![st0.into()],
+        secret_input: vec![st0.into()],
+    }
+}
+
+pub fn test_program_for_lsb() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 3 lsb assert assert halt")
+}
+
+pub fn property_based_test_program_for_lsb() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st0 = rng.next_u32();
+    let lsb = st0 % 2;
+    let st0_shift_right = st0 >> 1;
+
+    let source_code = format!("push {} lsb read_io eq assert read_io eq assert halt", st0);
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![lsb.into(), st0_shift_right.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_lt() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 2 lt assert halt")
+}
+
+pub fn property_based_test_program_for_lt() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st1 = rng.next_u32();
+    let st0 = rng.next_u32();
+    let result = if st0 < st1 {
+        1_u64.into()
+    } else {
+        0_u64.into()
+    };
+
+    let source_code = format!("push {} push {} lt read_io eq assert halt", st1, st0);
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![result],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_and() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 3 and assert halt")
+}
+
+pub fn property_based_test_program_for_and() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st1 = rng.next_u32();
+    let st0 = rng.next_u32();
+    let result = st0.bitand(st1);
+
+    let source_code = format!("push {} push {} and read_io eq assert halt", st1, st0);
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![result.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_xor() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 7 push 6 xor assert halt")
+}
+
+pub fn property_based_test_program_for_xor() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st1 = rng.next_u32();
+    let st0 = rng.next_u32();
+    let result = st0.bitxor(st1);
+
+    let source_code = format!("push {} push {} xor read_io eq assert halt", st1, st0);
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![result.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_reverse() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 2147483648 reverse assert halt")
+}
+
+pub fn property_based_test_program_for_reverse() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st0 = rng.next_u32();
+    let st0_rev = st0.reverse_bits().into();
+
+    let source_code = format!("push {} reverse read_io eq assert halt", st0);
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![st0_rev],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_lte() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 2 lte assert halt")
+}
+
+pub fn property_based_test_program_for_lte() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st1 = rng.next_u32();
+    let st0 = rng.next_u32();
+    let result = if st0 <= st1 {
+        1_u64.into()
+    } else {
+        0_u64.into()
+    };
+
+    let source_code = format!("push {} push {} lte read_io eq assert halt", st1, st0);
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![result],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_div() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 2 push 3 div assert assert halt")
+}
+
+pub fn property_based_test_program_for_div() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let denominator = rng.next_u32();
+    let numerator = rng.next_u32();
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+
+    let source_code = format!(
+        "push {} push {} div read_io eq assert read_io eq assert halt",
+        denominator, numerator
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![remainder.into(), quotient.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_i32_arithmetic() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 4294967295 push 4294967295 lt_i32 push 0 eq assert \
+         push 0 push 4294967295 lt_i32 assert \
+         push 4294967295 push 4294967295 lte_i32 assert \
+         push 2147483647 push 2147483648 lt_i32 assert \
+         push 2 push 4294967289 div_i32 push 4294967295 eq assert push 4294967293 eq assert \
+         push 4294967294 push 7 div_i32 push 1 eq assert push 4294967293 eq assert \
+         push 4294967295 push 2147483648 div_i32 push 0 eq assert push 2147483648 eq assert \
+         halt",
+    )
+}
+
+pub fn property_based_test_program_for_lt_i32() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st1 = rng.gen::<i32>();
+    let st0 = rng.gen::<i32>();
+    let result = BFieldElement::new((st0 < st1) as u64);
+
+    let source_code = format!(
+        "push {} push {} lt_i32 read_io eq assert halt",
+        i32_to_bfe(st1),
+        i32_to_bfe(st0)
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![result],
+        secret_input: vec![],
+    }
+}
+
+pub fn property_based_test_program_for_sponge() -> SourceCodeAndInput {
+    let absorbed: [BFieldElement; DIGEST_LENGTH] = random_elements_array();
+    let mut sponge = Sponge::new();
+    sponge.absorb(absorbed);
+    let squeezed = sponge.squeeze();
+
+    let source_code = format!(
+        "push {} push {} push {} push {} push {} sponge_init sponge_absorb sponge_squeeze \
+         pop pop pop pop pop read_io eq assert halt",
+        absorbed[4], absorbed[3], absorbed[2], absorbed[1], absorbed[0],
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![squeezed[0]],
+        secret_input: vec![],
+    }
+}
+
+pub fn property_based_test_program_for_div_i32() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let denominator = rng.gen::<i32>();
+    let numerator = rng.gen::<i32>();
+    let quotient = numerator.wrapping_div(denominator);
+    let remainder = numerator.wrapping_rem(denominator);
+
+    let source_code = format!(
+        "push {} push {} div_i32 read_io eq assert read_io eq assert halt",
+        i32_to_bfe(denominator),
+        i32_to_bfe(numerator)
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![i32_to_bfe(remainder), i32_to_bfe(quotient)],
+        secret_input: vec![],
+    }
+}
+
+pub fn test_program_for_i64_arithmetic() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 4294967295 push 4294967295 push 0 push 0 lt_i64 push 0 eq assert \
+         push 0 push 0 push 4294967295 push 4294967295 lt_i64 assert \
+         push 1 push 0 push 0 push 4294967295 lt_i64 assert \
+         push 4294967295 push 4294967295 push 4294967295 push 4294967295 lte_i64 assert \
+         push 0 push 2 push 4294967295 push 4294967289 div_i64 \
+         push 4294967295 eq assert push 4294967295 eq assert \
+         push 4294967293 eq assert push 4294967295 eq assert \
+         push 1 push 0 push 3 push 5 div_i64 \
+         push 5 eq assert push 0 eq assert push 3 eq assert push 0 eq assert \
+         halt",
+    )
+}
+
+pub fn property_based_test_program_for_lt_i64() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st1 = rng.gen::<i64>();
+    let st0 = rng.gen::<i64>();
+    let result = BFieldElement::new((st0 < st1) as u64);
+
+    let [st1_hi, st1_lo] = i64_to_bfes(st1);
+    let [st0_hi, st0_lo] = i64_to_bfes(st0);
+    let source_code = format!(
+        "push {st1_hi} push {st1_lo} push {st0_hi} push {st0_lo} lt_i64 read_io eq assert halt"
+    );
+
+    SourceCodeAndInput {
+        source_code,
+        input: vec![result],
+        secret_input: vec![],
+    }
+}
+
+pub fn property_based_test_program_for_div_i64() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let denominator = match rng.gen::<i64>() >> rng.gen_range(0..64) {
+        0 => 1,
+        denominator => denominator,
+    };
+    let numerator = rng.gen::<i64>();
+    let quotient = numerator.wrapping_div(denominator);
+    let remainder = numerator.wrapping_rem(denominator);
+
+    let [d_hi, d_lo] = i64_to_bfes(denominator);
+    let [n_hi, n_lo] = i64_to_bfes(numerator);
+    let source_code = format!(
+        "push {d_hi} push {d_lo} push {n_hi} push {n_lo} div_i64 \
+         read_io eq assert read_io eq assert read_io eq assert read_io eq assert halt"
+    );
+
+    let [q_hi, q_lo] = i64_to_bfes(quotient);
+    let [r_hi, r_lo] = i64_to_bfes(remainder);
+    SourceCodeAndInput {
+        source_code,
+        input: vec![r_lo, r_hi, q_lo, q_hi],
+        secret_input: vec![],
+    }
+}
+
+pub fn property_based_test_program_for_is_u32() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let st0 = rng.next_u32();
+
+    let source_code = format!("push {} is_u32 halt", st0);
+
+    SourceCodeAndInput::without_input(&source_code)
+}
+
+pub fn property_based_test_program_for_spill_reload() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let num_elements = rng.gen_range(1..=MAX_SPILL_SIZE);
+    let ram_pointer: BFieldElement = random_elements(1)[0];
+    let values: Vec<BFieldElement> = random_elements(num_elements);
+    let end_pointer = ram_pointer + BFieldElement::new(num_elements as u64);
+
+    let mut source_code = String::new();
+    for value in values.iter().rev() {
+        source_code.push_str(&format!("push {value} "));
+    }
+    source_code.push_str(&format!("push {ram_pointer} spill{num_elements} "));
+    source_code.push_str(&format!("dup0 push {end_pointer} eq assert "));
+    source_code.push_str(&format!(
+        "reload{num_elements} push {ram_pointer} eq assert "
+    ));
+    for value in values.iter() {
+        source_code.push_str(&format!("push {value} eq assert "));
+    }
+    source_code.push_str("halt");
+
+    SourceCodeAndInput::without_input(&source_code)
+}
+
+pub fn property_based_test_program_for_random_ram_access() -> SourceCodeAndInput {
+    let mut rng = ThreadRng::default();
+    let num_memory_accesses = rng.gen_range(10..50);
+    let memory_addresses: Vec<BFieldElement> = random_elements(num_memory_accesses);
+    let mut memory_values: Vec<BFieldElement> = random_elements(num_memory_accesses);
+    let mut source_code = String::new();
+
+    // Read some memory before first write to ensure that the memory is initialized with 0s.
+    // Not all addresses are read to have different access patterns:
+    // - Some addresses are read before written to.
+    // - Other addresses are written to before read.
+    for memory_address in memory_addresses.iter().take(num_memory_accesses / 4) {
+        source_code.push_str(&format!(
+            "push {memory_address} push 0 read_mem push 0 eq assert pop "
+        ));
+    }
+
+    // Write everything to RAM.
+    for (memory_address, memory_value) in memory_addresses.iter().zip_eq(memory_values.iter()) {
+        source_code.push_str(&format!(
+            "push {memory_address} push {memory_value} write_mem pop pop "
+        ));
+    }
+
+    // Read back in random order and check that the values did not change.
+    // For repeated sampling from the same range, better performance can be achieved by using
+    // `Uniform`. However, this is a test, and not very many samples – it's fine.
+    let mut reading_permutation = (0..num_memory_accesses).collect_vec();
+    for i in 0..num_memory_accesses {
+        let j = rng.gen_range(0..num_memory_accesses);
+        reading_permutation.swap(i, j);
+    }
+    for idx in reading_permutation {
+        let memory_address = memory_addresses[idx];
+        let memory_value = memory_values[idx];
+        source_code.push_str(&format!(
+            "push {memory_address} push 0 read_mem push {memory_value} eq assert pop "
+        ));
+    }
+
+    // Overwrite half the values with new ones.
+    let mut writing_permutation = (0..num_memory_accesses).collect_vec();
+    for i in 0..num_memory_accesses {
+        let j = rng.gen_range(0..num_memory_accesses);
+        writing_permutation.swap(i, j);
+    }
+    for idx in 0..num_memory_accesses / 2 {
+        let memory_address = memory_addresses[writing_permutation[idx]];
+        let new_memory_value = rng.gen();
+        memory_values[writing_permutation[idx]] = new_memory_value;
+        source_code.push_str(&format!(
+            "push {memory_address} push {new_memory_value} write_mem pop pop "
+        ));
+    }
+
+    // Read back all, i.e., unchanged and overwritten values in (different from before) random
+    // order and check that the values did not change.
+    let mut reading_permutation = (0..num_memory_accesses).collect_vec();
+    for i in 0..num_memory_accesses {
+        let j = rng.gen_range(0..num_memory_accesses);
+        reading_permutation.swap(i, j);
+    }
+    for idx in reading_permutation {
+        let memory_address = memory_addresses[idx];
+        let memory_value = memory_values[idx];
+        source_code.push_str(&format!(
+            "push {memory_address} push 0 read_mem push {memory_value} eq assert pop "
+        ));
+    }
+
+    source_code.push_str("halt");
+    SourceCodeAndInput::without_input(&source_code)
+}
+
+pub fn test_program_for_split() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push -2 split push 4294967294 eq assert push 4294967295 eq assert \
+         push -1 split push 4294967295 eq assert push 0 eq assert \
+         push  0 split push 0 eq assert push 0 eq assert \
+         push  1 split push 0 eq assert push 1 eq assert \
+         push  2 split push 0 eq assert push 2 eq assert \
+         push 4294967297 split assert assert \
+         halt",
+    )
+}
+
+pub fn test_program_for_split_assert() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push -2 split_assert push 4294967294 eq assert push 4294967295 eq assert \
+         push -1 split_assert push 4294967295 eq assert push 0 eq assert \
+         push  0 split_assert push 0 eq assert push 0 eq assert \
+         push  1 split_assert push 0 eq assert push 1 eq assert \
+         push  2 split_assert push 0 eq assert push 2 eq assert \
+         push 4294967297 split_assert assert assert \
+         halt",
+    )
+}
+
+pub fn test_program_for_u32_checked_arithmetic() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input(
+        "push 3 push 4 add_u32_checked push 7 eq assert \
+         push 2147483648 push 2147483647 add_u32_checked push 4294967295 eq assert \
+         push 65535 push 65537 mul_u32_checked push 4294967295 eq assert \
+         push 0 push 4294967295 mul_u32_checked push 0 eq assert \
+         halt",
+    )
+}
+
+pub fn test_program_for_xxadd() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 6 push 7 push 8 push 9 push 10 xxadd halt")
+}
+
+pub fn test_program_for_xxmul() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 6 push 7 push 8 push 9 push 10 xxmul halt")
+}
+
+pub fn test_program_for_xinvert() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 6 push 7 xinvert halt")
+}
+
+pub fn test_program_for_xbmul() -> SourceCodeAndInput {
+    SourceCodeAndInput::without_input("push 5 push 6 push 7 push 8 xbmul halt")
+}
+
+pub fn test_program_for_read_io_write_io() -> SourceCodeAndInput {
+    SourceCodeAndInput {
+        source_code: "read_io assert read_io read_io dup1 dup1 add write_io mul write_io halt"
+            .to_string(),
+        input: vec![1_u64.into(), 3_u64.into(), 14_u64.into()],
+        secret_input: vec![],
+    }
+}
+
+pub fn small_tasm_test_programs() -> Vec<SourceCodeAndInput> {
+    vec![
+        test_program_for_halt(),
+        test_program_for_push_pop_dup_swap_nop(),
+        test_program_for_divine(),
+        test_program_for_skiz(),
+        test_program_for_call_recurse_return(),
+        test_program_for_tail_call(),
+        test_program_for_write_mem_read_mem(),
+        test_program_for_write_mem_inc_read_mem_inc(),
+        test_program_for_hash(),
+        test_program_for_divine_sibling_noswitch(),
+        test_program_for_divine_sibling_switch(),
+        test_program_for_assert_vector(),
+        test_program_for_eq_vector(),
+        test_program_for_add_mul_invert(),
+        test_program_for_eq(),
+        test_program_for_lsb(),
+        test_program_for_split(),
+        test_program_for_xxadd(),
+        test_program_for_xxmul(),
+        test_program_for_xinvert(),
+        test_program_for_xbmul(),
+        test_program_for_read_io_write_io(),
+    ]
+}
+
+pub fn property_based_test_programs() -> Vec<SourceCodeAndInput> {
+    vec![
+        property_based_test_program_for_assert_vector(),
+        property_based_test_program_for_split(),
+        property_based_test_program_for_eq(),
+        property_based_test_program_for_lsb(),
+        property_based_test_program_for_lt(),
+        property_based_test_program_for_and(),
+        property_based_test_program_for_xor(),
+        property_based_test_program_for_reverse(),
+        property_based_test_program_for_lte(),
+        property_based_test_program_for_div(),
+        property_based_test_program_for_is_u32(),
+        property_based_test_program_for_lt_i32(),
+        property_based_test_program_for_div_i32(),
+        property_based_test_program_for_lt_i64(),
+        property_based_test_program_for_div_i64(),
+        property_based_test_program_for_sponge(),
+        property_based_test_program_for_spill_reload(),
+        property_based_test_program_for_random_ram_access(),
+    ]
+}
+
+/// programs with a cycle count of 150 and upwards
+pub fn bigger_tasm_test_programs() -> Vec<SourceCodeAndInput> {
+    vec![
+        test_hash_nop_nop_lt(),
+        test_program_for_instruction_split(),
+        test_program_for_lt(),
+        test_program_for_and(),
+        test_program_for_xor(),
+        test_program_for_reverse(),
+        test_program_for_lte(),
+        test_program_for_div(),
+        test_program_for_split_assert(),
+        test_program_for_u32_checked_arithmetic(),
+        test_program_for_i32_arithmetic(),
+        test_program_for_i64_arithmetic(),
+    ]
+}
@@ -0,0 +1,136 @@
+//! The wall-clock time the verifier spends in each of its phases, for users embedding
+//! verification in latency-sensitive contexts.
+
+use std::fmt::Display;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use triton_profiler::triton_profiler::TritonProfiler;
+
+use crate::proof::Proof;
+use crate::stark::Stark;
+
+/// The time spent in each phase of one verification. The phases correspond to the top-level tasks
+/// the verifier reports to the [`TritonProfiler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VerifierTimings {
+    pub deserialization: Duration,
+
+    /// Dequeuing Merkle roots and sampling challenges, weights, and indices.
+    pub fiat_shamir: Duration,
+
+    /// Dequeuing and authenticating the revealed rows of the base, extension, and combination
+    /// tables.
+    pub merkle_checks: Duration,
+
+    /// Evaluating the AIR constraints in the revealed rows and checking the nonlinear combination.
+    pub constraint_evaluation: Duration,
+
+    pub fri: Duration,
+
+    /// The time of the entire verification, including time not attributed to any phase.
+    pub total: Duration,
+
+    /// Whether the verifier used constant-time field operations where available.
+    pub constant_time: bool,
+}
+
+impl VerifierTimings {
+    /// Verify the given proof, measuring the time spent in each phase. Only verifications that
+    /// run to completion are measured – for proofs the verifier rejects with an error, the error
+    /// is returned instead.
+    pub fn measure(stark: &Stark, proof: Proof) -> Result<(bool, Self)> {
+        let mut maybe_profiler = Some(TritonProfiler::new("Verifier Timings"));
+        let start = Instant::now();
+        let verdict = stark.verify(proof, &mut maybe_profiler)?;
+        let total = start.elapsed();
+
+        let profiler = maybe_profiler.unwrap();
+        let time_of = |tasks: &[&str]| {
+            tasks
+                .iter()
+                .map(|&task| profiler.top_level_time(task))
+                .sum()
+        };
+        let timings = Self {
+            deserialization: time_of(&["deserialize"]),
+            fiat_shamir: time_of(&["Fiat-Shamir 1", "dequeue", "Fiat-Shamir 2", "Fiat-Shamir 3"]),
+            merkle_checks: time_of(&["check leafs"]),
+            constraint_evaluation: time_of(&["nonlinear combination"]),
+            fri: time_of(&["FRI"]),
+            total,
+            constant_time: stark.fri.constant_time,
+        };
+        Ok((verdict, timings))
+    }
+
+    /// All phases with their name, in the order the verifier runs them.
+    pub fn phases(&self) -> [(&'static str, Duration); 5] {
+        [
+            ("deserialization", self.deserialization),
+            ("Fiat-Shamir", self.fiat_shamir),
+            ("FRI", self.fri),
+            ("Merkle checks", self.merkle_checks),
+            ("constraint evaluation", self.constraint_evaluation),
+        ]
+    }
+}
+
+impl Display for VerifierTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "| Phase                 |         Time | Share |")?;
+        writeln!(f, "|:----------------------|-------------:|------:|")?;
+        let total_nanos = self.total.as_nanos().max(1);
+        for (phase, time) in self.phases() {
+            let share = 100 * time.as_nanos() / total_nanos;
+            writeln!(
+                f,
+                "| {phase:<21} | {:>12} | {share:>4}% |",
+                format!("{time:.2?}")
+            )?;
+        }
+        writeln!(f, "Total: {:.2?}", self.total)?;
+        let mode = if self.constant_time {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        write!(f, "Constant-time field operations: {mode}")
+    }
+}
+
+#[cfg(test)]
+mod verifier_timings_tests {
+    use crate::shared_tests::parse_simulate_prove;
+    use crate::test_corpus::test_hash_nop_nop_lt;
+
+    use super::*;
+
+    #[test]
+    fn phases_add_up_to_at_most_total_test() {
+        let code_with_input = test_hash_nop_nop_lt();
+        let (stark, proof) = parse_simulate_prove(
+            &code_with_input.source_code,
+            code_with_input.input.clone(),
+            code_with_input.secret_input.clone(),
+            &mut None,
+        );
+
+        let (verdict, timings) = VerifierTimings::measure(&stark, proof.clone()).unwrap();
+        assert!(verdict);
+        assert!(!timings.constant_time);
+        for (phase, time) in timings.phases() {
+            assert_ne!(Duration::ZERO, time, "phase {phase} must be measured");
+        }
+        let sum_of_phases: Duration = timings.phases().iter().map(|(_, time)| *time).sum();
+        assert!(sum_of_phases <= timings.total);
+        println!("{timings}");
+
+        let stark = stark.with_constant_time_verification();
+        let (verdict, timings) = VerifierTimings::measure(&stark, proof).unwrap();
+        assert!(verdict);
+        assert!(timings.constant_time);
+        println!("{timings}");
+    }
+}
@@ -1,64 +1,464 @@
+pub mod coprocessor;
+pub mod debugger;
+pub mod error;
+pub mod metering;
+pub mod profiler;
+pub mod stream;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+use ndarray::s;
+use ndarray::Array1;
 use ndarray::Array2;
+use ndarray::ArrayView1;
+use ndarray::ArrayView2;
 use ndarray::Axis;
-
+use rayon::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+
+use triton_opcodes::instruction::AnInstruction::ReadMem;
+use triton_opcodes::instruction::AnInstruction::ReadMemInc;
+use triton_opcodes::instruction::AnInstruction::WriteMem;
+use triton_opcodes::instruction::AnInstruction::WriteMemInc;
+use triton_opcodes::instruction::Instruction;
 use triton_opcodes::program::Program;
 use twenty_first::shared_math::b_field_element::BFieldElement;
 use twenty_first::shared_math::b_field_element::BFIELD_ZERO;
+use twenty_first::shared_math::rescue_prime_digest::Digest;
+use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
 use twenty_first::shared_math::rescue_prime_regular::NUM_ROUNDS;
 use twenty_first::shared_math::rescue_prime_regular::ROUND_CONSTANTS;
 use twenty_first::shared_math::rescue_prime_regular::STATE_SIZE;
+use twenty_first::util_types::algebraic_hasher::AlgebraicHasher;
 
+use crate::public_output::PublicOutput;
+use crate::semantics_vectors::StateSnapshot;
+use crate::state::TerminalState;
+use crate::state::VMConfig;
 use crate::state::VMOutput;
 use crate::state::VMState;
 use crate::table::hash_table;
+use crate::table::hash_table::HashTable;
 use crate::table::hash_table::NUM_ROUND_CONSTANTS;
+use crate::table::jump_stack_table;
+use crate::table::jump_stack_table::JumpStackTable;
+use crate::table::master_table::MasterBaseTable;
 use crate::table::processor_table;
+use crate::table::processor_table::ProcessorTable;
 use crate::table::table_column::BaseTableColumn;
 use crate::table::table_column::HashBaseTableColumn::CONSTANT0A;
 use crate::table::table_column::HashBaseTableColumn::ROUNDNUMBER;
 use crate::table::table_column::HashBaseTableColumn::STATE0;
+use crate::table::table_column::JumpStackBaseTableColumn::JSP;
+use crate::table::table_column::ProcessorBaseTableColumn::CI;
+use crate::table::table_column::ProcessorBaseTableColumn::ST1;
+use crate::vm::coprocessor::Coprocessor;
+use crate::vm::coprocessor::HashCoprocessor;
+use crate::vm::error::InstructionError::CycleLimitExceeded;
+use crate::vm::error::VMError;
+use crate::vm::stream::Diviner;
+use crate::vm::stream::InputStream;
+use crate::vm::stream::OutputSink;
+use crate::vm::stream::SecretInputStream;
+use crate::vm::stream::VecStream;
 
 /// Simulate (execute) a `Program` and record every state transition. Returns an
 /// `AlgebraicExecutionTrace` recording every intermediate state of the processor and all co-
 /// processors.
 ///
-/// On premature termination of the VM, returns the `AlgebraicExecutionTrace` for the execution
-/// up to the point of failure.
+/// On premature termination of the VM, returns the [`VMError`].
 pub fn simulate(
     program: &Program,
-    mut stdin: Vec<BFieldElement>,
-    mut secret_in: Vec<BFieldElement>,
-) -> (
-    AlgebraicExecutionTrace,
-    Vec<BFieldElement>,
-    Option<anyhow::Error>,
-) {
-    let mut aet = AlgebraicExecutionTrace::default();
-    let mut state = VMState::new(program);
-    // record initial state
-    aet.processor_matrix
-        .push_row(state.to_processor_row().view())
-        .expect("shapes must be identical");
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    simulate_with_hints(program, stdin, secret_in, ExecutionHints::default())
+}
+
+/// Hints about the size of an execution's trace. With accurate hints, the matrices of the
+/// [`AlgebraicExecutionTrace`] are allocated once, instead of growing – and thus being copied –
+/// repeatedly as the trace is recorded. Hints only affect performance, never the trace: a hint
+/// that is too small only means that the matrices grow. A hint of 0 means no hint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutionHints {
+    /// The expected number of clock cycles.
+    pub num_cycles: usize,
+
+    /// The expected number of executed `hash` instructions.
+    pub num_hashes: usize,
+}
 
+impl ExecutionHints {
+    /// The exact hints for the given execution, obtained by executing the program once without
+    /// recording anything. Worthwhile if the program is executed repeatedly on the same input, or
+    /// if the memory for growing the trace matrices is more precious than the time to execute.
+    pub fn dry_run(
+        program: &Program,
+        stdin: impl InputStream,
+        secret_in: impl SecretInputStream,
+    ) -> Self {
+        let mut num_hashes = 0;
+        let (final_state, _) = execute_to_last_state(program, stdin, secret_in, |_, vm_output| {
+            if let Some(VMOutput::XlixTrace(_)) = vm_output {
+                num_hashes += 1;
+            }
+        });
+        Self {
+            num_cycles: final_state.cycle_count as usize,
+            num_hashes,
+        }
+    }
+}
+
+/// Like [`simulate`], but allocates the matrices of the `AlgebraicExecutionTrace` according to
+/// the given [`ExecutionHints`].
+pub fn simulate_with_hints(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    hints: ExecutionHints,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    let aet = AlgebraicExecutionTrace::with_capacity(hints.num_cycles, hints.num_hashes);
+    let mut recorder = TraceRecorder::new(aet);
     let mut stdout = vec![];
-    while !state.is_complete() {
-        let vm_output = match state.step_mut(&mut stdin, &mut secret_in) {
-            Err(err) => return (aet, stdout, Some(err)),
-            Ok(vm_output) => vm_output,
-        };
+    execute(program, stdin, secret_in, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output)
+    })?;
+    Ok((recorder.finish(), stdout.into()))
+}
 
-        match vm_output {
-            Some(VMOutput::XlixTrace(hash_trace)) => aet.append_hash_trace(*hash_trace),
-            Some(VMOutput::WriteOutputSymbol(written_word)) => stdout.push(written_word),
-            None => (),
+/// Like [`simulate`], but additionally records every `VMState` the VM passes through, including
+/// the initial state. The `AlgebraicExecutionTrace` and the states stem from one and the same
+/// execution, which helps debugging, for example, failing proof generation: the AET can be
+/// inspected side by side with the human-friendly states.
+///
+/// The `i`th recorded state corresponds to the `i`th row of the processor matrix.
+pub fn simulate_and_record_states(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(AlgebraicExecutionTrace, Vec<VMState<'_>>, PublicOutput), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut states = vec![];
+    let mut stdout = vec![];
+    execute(program, stdin, secret_in, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output);
+        states.push(state.clone());
+    })?;
+    Ok((recorder.finish(), states, stdout.into()))
+}
+
+/// Records an execution in an [`AlgebraicExecutionTrace`]. The processor matrix is recorded
+/// directly, all other tables by [`Coprocessor`]s.
+pub(crate) struct TraceRecorder {
+    aet: AlgebraicExecutionTrace,
+    coprocessors: Vec<Box<dyn Coprocessor>>,
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        Self::new(AlgebraicExecutionTrace::default())
+    }
+}
+
+impl TraceRecorder {
+    fn new(aet: AlgebraicExecutionTrace) -> Self {
+        Self {
+            aet,
+            coprocessors: vec![Box::<HashCoprocessor>::default()],
         }
-        // Record next, to be executed state.
-        aet.processor_matrix
-            .push_row(state.to_processor_row().view())
+    }
+
+    /// Additionally run the given coprocessors, after the built-in ones.
+    fn with_coprocessors(mut self, coprocessors: Vec<Box<dyn Coprocessor>>) -> Self {
+        self.coprocessors.extend(coprocessors);
+        self
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        stdout: &mut Vec<BFieldElement>,
+        state: &VMState,
+        vm_output: Option<VMOutput>,
+    ) {
+        if state.cycle_count > 0 {
+            let executed_instruction = Instruction::try_from(state.previous_instruction.value())
+                .expect("executed instruction must be valid");
+            for coprocessor in self.coprocessors.iter_mut() {
+                coprocessor.observe(executed_instruction, state, vm_output.as_ref());
+            }
+        }
+        if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
+            stdout.push(written_word);
+        }
+        let processor_row = state.to_processor_row();
+        self.aet
+            .jump_stack_matrix
+            .push_row(JumpStackTable::row_of(processor_row.view()).view())
+            .expect("shapes must be identical");
+        self.aet
+            .processor_matrix
+            .push_row(processor_row.view())
             .expect("shapes must be identical");
+        if let Some(memory_accesses) = self.aet.memory_accesses.as_mut() {
+            memory_accesses.extend(MemoryAccess::of_transition(state));
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> AlgebraicExecutionTrace {
+        self.aet.sort_jump_stack_matrix();
+        for coprocessor in self.coprocessors {
+            coprocessor.finish(&mut self.aet);
+        }
+        self.aet
+    }
+}
+
+/// A word written to standard output, stamped with where and when it was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StampedWord {
+    pub word: BFieldElement,
+
+    /// The clock cycle in which the word was written.
+    pub cycle: u32,
+
+    /// The address of the `write_io` instruction that wrote the word.
+    pub instruction_pointer: usize,
+}
+
+impl StampedWord {
+    /// The label closest to, but not after, the `write_io` instruction that wrote the word,
+    /// given all labels and their addresses, for example as returned by `label_addresses`.
+    pub fn enclosing_label<'a>(&self, labels: &'a HashMap<String, usize>) -> Option<&'a str> {
+        enclosing_label(labels, self.instruction_pointer)
+    }
+}
+
+/// The label closest to, but not after, the given instruction pointer.
+pub(crate) fn enclosing_label(
+    labels: &HashMap<String, usize>,
+    instruction_pointer: usize,
+) -> Option<&str> {
+    labels
+        .iter()
+        .filter(|(_, &address)| address <= instruction_pointer)
+        .max_by_key(|(label, &address)| (address, *label))
+        .map(|(label, _)| label.as_str())
+}
+
+/// Like [`simulate`], but additionally hands every word written to standard output to the
+/// `sink` as soon as it is written, [stamped](StampedWord) with the clock cycle and the address of
+/// the `write_io` writing it. To collect the stamped words, use a `Vec<StampedWord>` as the sink.
+pub fn simulate_with_sink(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    sink: &mut impl OutputSink,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let mut previous_instruction_pointer = program.entry_point;
+    execute(program, stdin, secret_in, |state, vm_output| {
+        let num_written_words = stdout.len();
+        recorder.record(&mut stdout, state, vm_output);
+        for &word in &stdout[num_written_words..] {
+            sink.write(StampedWord {
+                word,
+                cycle: state.cycle_count - 1,
+                instruction_pointer: previous_instruction_pointer,
+            });
+        }
+        previous_instruction_pointer = state.instruction_pointer;
+    })?;
+    Ok((recorder.finish(), stdout.into()))
+}
+
+/// Like [`simulate`], but the returned `AlgebraicExecutionTrace` additionally holds every access
+/// of RAM, see [`AlgebraicExecutionTrace::ram_access_summary`].
+pub fn simulate_with_memory_access_log(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    let mut recorder =
+        TraceRecorder::new(AlgebraicExecutionTrace::default().with_memory_access_log());
+    let mut stdout = vec![];
+    execute(program, stdin, secret_in, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output)
+    })?;
+    Ok((recorder.finish(), stdout.into()))
+}
+
+/// Like [`simulate`], but subject to the limits of the given [`VMConfig`], for example a
+/// maximal op-stack height.
+pub fn simulate_with_config(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    config: VMConfig,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    execute_with_config(program, stdin, secret_in, config, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output)
+    })?;
+    Ok((recorder.finish(), stdout.into()))
+}
+
+/// Like [`simulate`], but additionally returns the final [`VMState`], which can be used to
+/// continue computing without executing the program a second time. To inspect the state in which
+/// a failing VM failed, use [`execute_to_last_state`].
+pub fn simulate_and_return_state(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput, VMState<'_>), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let final_state = execute(program, stdin, secret_in, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output)
+    })?;
+    Ok((recorder.finish(), stdout.into(), final_state))
+}
+
+/// Like [`simulate`], but additionally returns the [`TerminalState`], _i.e._, the contents of
+/// RAM, the stacks, and the registers after execution has halted. Unlike
+/// [`simulate_and_return_state`], the returned state does not borrow the program.
+pub fn simulate_with_terminal_state(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput, TerminalState), VMError> {
+    let (aet, stdout, final_state) = simulate_and_return_state(program, stdin, secret_in)?;
+    Ok((aet, stdout, final_state.into()))
+}
+
+/// Like [`simulate`], but additionally returns a [`TraceDigest`] of the processor trace, computed
+/// while executing. Two executors can compare their trace digests to confirm that they produced
+/// identical traces before spending time on proving.
+pub fn simulate_with_trace_digest(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput, TraceDigest), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let mut trace_digest = TraceDigest::default();
+    execute(program, stdin, secret_in, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output);
+        let latest_row = recorder.aet.processor_matrix.rows().into_iter().last();
+        trace_digest.absorb_row(latest_row.expect("a row was just recorded"));
+    })?;
+    Ok((recorder.finish(), stdout.into(), trace_digest))
+}
+
+/// A running digest over the rows of the processor trace. It is cheap to compute – a few field
+/// operations per trace element – and independent of the STARK's commitments. Because it is a
+/// polynomial evaluation at fixed points, it detects accidental divergence of executions, but
+/// is not collision resistant against a malicious executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceDigest {
+    accumulators: [BFieldElement; DIGEST_LENGTH],
+    num_rows: u64,
+}
+
+impl Default for TraceDigest {
+    fn default() -> Self {
+        Self {
+            accumulators: [BFIELD_ZERO; DIGEST_LENGTH],
+            num_rows: 0,
+        }
+    }
+}
+
+impl TraceDigest {
+    /// The points at which the trace, as one long polynomial, is evaluated.
+    const EVALUATION_POINTS: [BFieldElement; DIGEST_LENGTH] = [
+        BFieldElement::new(0x9e37_79b9_7f4a_7c15),
+        BFieldElement::new(0x6a09_e667_f3bc_c908),
+        BFieldElement::new(0xbb67_ae85_84ca_a73b),
+        BFieldElement::new(0x3c6e_f372_fe94_f82b),
+        BFieldElement::new(0xa54f_f53a_5f1d_36f1),
+    ];
+
+    /// The trace digest of an already recorded processor trace, for example the
+    /// `processor_matrix` of an [`AlgebraicExecutionTrace`].
+    pub fn of_processor_matrix(processor_matrix: ArrayView2<BFieldElement>) -> Self {
+        let mut trace_digest = Self::default();
+        for row in processor_matrix.rows() {
+            trace_digest.absorb_row(row);
+        }
+        trace_digest
+    }
+
+    pub fn absorb_row(&mut self, row: ArrayView1<BFieldElement>) {
+        for (accumulator, &point) in self.accumulators.iter_mut().zip(&Self::EVALUATION_POINTS) {
+            for &element in row.iter() {
+                *accumulator = *accumulator * point + element;
+            }
+        }
+        self.num_rows += 1;
     }
 
-    (aet, stdout, None)
+    /// The number of rows absorbed so far.
+    pub fn num_rows(&self) -> u64 {
+        self.num_rows
+    }
+
+    /// The digest of all rows absorbed so far, including their number.
+    pub fn digest(&self) -> Digest {
+        let num_rows = BFieldElement::new(self.num_rows);
+        let mut elements = self.accumulators;
+        for (element, &point) in elements.iter_mut().zip(&Self::EVALUATION_POINTS) {
+            *element = *element * point + num_rows;
+        }
+        Digest::new(elements)
+    }
+}
+
+/// One row of the [`AlgebraicExecutionTrace`], tagged with the matrix it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceRow {
+    Processor(Array1<BFieldElement>),
+    Hash(Array1<BFieldElement>),
+}
+
+/// Like [`simulate`], but instead of materializing the [`AlgebraicExecutionTrace`], every row is
+/// handed to the `row_consumer` as soon as it is known. This keeps memory consumption independent
+/// of the length of the execution, allowing callers to spill the trace to disk or to compute
+/// statistics on the fly.
+///
+/// Within each matrix, the rows arrive in the order in which [`simulate`] records them. The hash
+/// rows resulting from a state transition arrive before that transition's processor row.
+pub fn simulate_streaming(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    mut row_consumer: impl FnMut(TraceRow),
+) -> Result<PublicOutput, VMError> {
+    let mut stdout = vec![];
+    execute(program, stdin, secret_in, |state, vm_output| {
+        match vm_output {
+            Some(VMOutput::XlixTrace(hash_trace)) => {
+                let hash_rows =
+                    AlgebraicExecutionTrace::hash_trace_to_hash_matrix_rows(*hash_trace);
+                for hash_row in hash_rows.rows() {
+                    row_consumer(TraceRow::Hash(hash_row.to_owned()));
+                }
+            }
+            Some(VMOutput::WriteOutputSymbol(written_word)) => stdout.push(written_word),
+            None => (),
+        }
+        row_consumer(TraceRow::Processor(state.to_processor_row()));
+    })?;
+    Ok(stdout.into())
 }
 
 /// Wrapper around `.simulate_with_input()` and thus also around
@@ -67,61 +467,810 @@ pub fn simulate(
 /// of `.simulate_with_input()`
 pub fn simulate_no_input(
     program: &Program,
-) -> (
-    AlgebraicExecutionTrace,
-    Vec<BFieldElement>,
-    Option<anyhow::Error>,
-) {
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
     simulate(program, vec![], vec![])
 }
 
 pub fn run(
     program: &Program,
-    mut stdin: Vec<BFieldElement>,
-    mut secret_in: Vec<BFieldElement>,
-) -> (Vec<VMState>, Vec<BFieldElement>, Option<anyhow::Error>) {
-    let mut states = vec![VMState::new(program)];
-    let mut current_state = states.last().unwrap();
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(Vec<VMState<'_>>, PublicOutput), VMError> {
+    let mut states = vec![];
+    let mut stdout = vec![];
+    execute(program, stdin, secret_in, |state, vm_output| {
+        if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
+            stdout.push(written_word);
+        }
+        states.push(state.clone());
+    })?;
+    Ok((states, stdout.into()))
+}
 
+/// Like [`run`], but additionally hands every word written to standard output to the `sink` as
+/// soon as it is written, stamped like in [`simulate_with_sink`].
+pub fn run_with_sink<'pgm>(
+    program: &'pgm Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    sink: &mut impl OutputSink,
+) -> Result<(Vec<VMState<'pgm>>, PublicOutput), VMError> {
+    let mut states = vec![];
     let mut stdout = vec![];
-    while !current_state.is_complete() {
-        let step = current_state.step(&mut stdin, &mut secret_in);
-        let (next_state, vm_output) = match step {
-            Err(err) => {
-                println!("Encountered an error when running VM.");
-                return (states, stdout, Some(err));
+    let mut previous_instruction_pointer = program.entry_point;
+    execute(program, stdin, secret_in, |state, vm_output| {
+        if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
+            sink.write(StampedWord {
+                word: written_word,
+                cycle: state.cycle_count - 1,
+                instruction_pointer: previous_instruction_pointer,
+            });
+            stdout.push(written_word);
+        }
+        previous_instruction_pointer = state.instruction_pointer;
+        states.push(state.clone());
+    })?;
+    Ok((states, stdout.into()))
+}
+
+/// A predicate on the words written to standard output so far.
+pub type OutputPredicate<'a> = dyn Fn(&[BFieldElement]) -> bool + 'a;
+
+/// When [`run_with_policy`] stops executing before the program halts. By default, execution is
+/// never stopped early. Fuzzers searching for programs producing specific output can stop as soon
+/// as the output is known to match or not to match.
+#[derive(Default)]
+pub struct RunPolicy<'a> {
+    /// Stop as soon as standard output holds at least this many words.
+    pub max_output_len: Option<usize>,
+
+    /// Stop as soon as standard output satisfies this predicate. Checked whenever a word is
+    /// written.
+    pub output_predicate: Option<Box<OutputPredicate<'a>>>,
+}
+
+impl<'a> RunPolicy<'a> {
+    pub fn with_max_output_len(mut self, max_output_len: usize) -> Self {
+        self.max_output_len = Some(max_output_len);
+        self
+    }
+
+    pub fn with_output_predicate(
+        mut self,
+        output_predicate: impl Fn(&[BFieldElement]) -> bool + 'a,
+    ) -> Self {
+        self.output_predicate = Some(Box::new(output_predicate));
+        self
+    }
+
+    fn stops_at(&self, stdout: &[BFieldElement]) -> bool {
+        let reached_max_len = self
+            .max_output_len
+            .is_some_and(|max_output_len| stdout.len() >= max_output_len);
+        let satisfies_predicate = self
+            .output_predicate
+            .as_ref()
+            .is_some_and(|output_predicate| output_predicate(stdout));
+        reached_max_len || satisfies_predicate
+    }
+}
+
+/// Like [`run`], but stops early, without error, once standard output meets the [`RunPolicy`].
+/// Whether execution was stopped early can be told by the last state, which is then not
+/// [complete](VMState::is_complete).
+pub fn run_with_policy<'pgm>(
+    program: &'pgm Program,
+    mut stdin: impl InputStream,
+    mut secret_in: impl SecretInputStream,
+    policy: &RunPolicy,
+) -> Result<(Vec<VMState<'pgm>>, PublicOutput), VMError> {
+    let initial_state = VMState::new(program);
+    let mut states = vec![initial_state.clone()];
+    let mut stdout = vec![];
+    let (_, err) = execute_from_until(
+        initial_state,
+        &mut stdin,
+        &mut secret_in,
+        u32::MAX,
+        |state, vm_output| {
+            states.push(state.clone());
+            let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output else {
+                return ControlFlow::Continue(());
+            };
+            stdout.push(written_word);
+            if policy.stops_at(&stdout) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
             }
-            Ok((next_state, vm_output)) => (next_state, vm_output),
-        };
+        },
+    );
+    match err {
+        Some(err) => Err(err),
+        None => Ok((states, stdout.into())),
+    }
+}
 
+/// Like [`run`], but only retains the [`TerminalState`] instead of every intermediate state.
+pub fn run_to_terminal_state(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> Result<(TerminalState, PublicOutput), VMError> {
+    let mut stdout = vec![];
+    let final_state = execute(program, stdin, secret_in, |_, vm_output| {
         if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
             stdout.push(written_word);
         }
+    })?;
+    Ok((final_state.into(), stdout.into()))
+}
 
-        states.push(next_state);
-        current_state = states.last().unwrap();
+/// Run the same program on each of the given pairs of public and secret input, in parallel.
+/// Returns the output or the error of every run, in the order of the inputs. Since no
+/// execution trace or intermediate state is retained, this is suitable for simulating many
+/// inputs before deciding which of them to prove.
+pub fn run_batch(
+    program: &Program,
+    inputs: &[(Vec<BFieldElement>, Vec<BFieldElement>)],
+) -> Vec<Result<PublicOutput, VMError>> {
+    inputs
+        .par_iter()
+        .map(|(stdin, secret_in)| {
+            let mut stdout = vec![];
+            execute(program, stdin.clone(), secret_in.clone(), |_, vm_output| {
+                if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
+                    stdout.push(written_word);
+                }
+            })?;
+            Ok(stdout.into())
+        })
+        .collect()
+}
+
+/// Like [`run`], but only retains every `interval`th state, the final state, and the states
+/// immediately before and after every change of control flow, _i.e._, whenever the instruction
+/// pointer does not simply advance to the next instruction. This bounds the memory needed for
+/// long executions while keeping enough context for debugging. Any intermediate state can be
+/// reconstructed using [`SparseStates::state_at`].
+pub fn run_sparse(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    interval: u32,
+) -> Result<(SparseStates<'_>, PublicOutput), VMError> {
+    assert!(interval > 0, "The interval must be positive.");
+    let read_words = RefCell::new(vec![]);
+    let divined_words = RefCell::new(vec![]);
+    let stdin = RecordingStream::new(stdin, &read_words);
+    let secret_in = RecordingStream::new(secret_in, &divined_words);
+
+    let mut retained = vec![];
+    let mut stdout = vec![];
+    let mut previous = None;
+    execute(program, stdin, secret_in, |state, vm_output| {
+        if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
+            stdout.push(written_word);
+        }
+        let current = RetainedState {
+            state: state.clone(),
+            num_read_words: read_words.borrow().len(),
+            num_divined_words: divined_words.borrow().len(),
+        };
+        let control_flow_changed = previous.as_ref().is_some_and(|previous: &RetainedState| {
+            let size = previous.state.current_instruction().map_or(1, |i| i.size());
+            state.instruction_pointer != previous.state.instruction_pointer + size
+        });
+        if control_flow_changed {
+            SparseStates::retain(&mut retained, previous.take().unwrap());
+        }
+        if control_flow_changed || state.cycle_count % interval == 0 {
+            SparseStates::retain(&mut retained, current.clone());
+        }
+        previous = Some(current);
+    })?;
+    let last_state = previous.expect("the initial state is always observed");
+    SparseStates::retain(&mut retained, last_state);
+
+    let sparse_states = SparseStates {
+        retained,
+        read_words: read_words.into_inner(),
+        divined_words: divined_words.into_inner(),
+    };
+    Ok((sparse_states, stdout.into()))
+}
+
+/// The states retained by [`run_sparse`], together with all words read from standard input and
+/// secret input, which suffice to reconstruct the states in between.
+///
+/// Words supplied by a [`Diviner`] are not recorded. Reconstructing a state
+/// that follows such a divination is only faithful if the diviner agrees with the VM's defaults.
+#[derive(Debug, Clone)]
+pub struct SparseStates<'pgm> {
+    retained: Vec<RetainedState<'pgm>>,
+    read_words: Vec<BFieldElement>,
+    divined_words: Vec<BFieldElement>,
+}
+
+/// A retained state and the number of words read from either input to reach it.
+#[derive(Debug, Clone)]
+struct RetainedState<'pgm> {
+    state: VMState<'pgm>,
+    num_read_words: usize,
+    num_divined_words: usize,
+}
+
+impl<'pgm> SparseStates<'pgm> {
+    fn retain(retained: &mut Vec<RetainedState<'pgm>>, state: RetainedState<'pgm>) {
+        let last_cycle = retained.last().map(|last| last.state.cycle_count);
+        if last_cycle != Some(state.state.cycle_count) {
+            retained.push(state);
+        }
+    }
+
+    /// The retained states, ordered by their clock cycle.
+    pub fn retained_states(&self) -> impl Iterator<Item = &VMState<'pgm>> {
+        self.retained.iter().map(|retained| &retained.state)
+    }
+
+    /// The state in the given clock cycle, re-executing from the closest preceding retained state
+    /// if it was not retained. `None` if execution did not reach the given cycle.
+    pub fn state_at(&self, cycle: u32) -> Option<VMState<'pgm>> {
+        let closest = self
+            .retained
+            .iter()
+            .take_while(|retained| retained.state.cycle_count <= cycle)
+            .last()?;
+        let last_cycle = self.retained.last()?.state.cycle_count;
+        if cycle > last_cycle {
+            return None;
+        }
+
+        let mut state = closest.state.clone();
+        let mut stdin = VecStream::new(self.read_words[closest.num_read_words..].to_vec());
+        let mut secret_in =
+            VecStream::new(self.divined_words[closest.num_divined_words..].to_vec());
+        while state.cycle_count < cycle {
+            state
+                .step_mut(&mut stdin, &mut secret_in)
+                .expect("re-executing a recorded execution must succeed");
+        }
+        Some(state)
+    }
+}
+
+/// Forwards every word of the wrapped stream, keeping a copy.
+struct RecordingStream<'a, S> {
+    stream: S,
+    words: &'a RefCell<Vec<BFieldElement>>,
+}
+
+impl<'a, S> RecordingStream<'a, S> {
+    fn new(stream: S, words: &'a RefCell<Vec<BFieldElement>>) -> Self {
+        Self { stream, words }
     }
 
-    (states, stdout, None)
+    fn record(&self, word: Option<BFieldElement>) -> Option<BFieldElement> {
+        if let Some(word) = word {
+            self.words.borrow_mut().push(word);
+        }
+        word
+    }
+}
+
+impl<S: InputStream> InputStream for RecordingStream<'_, S> {
+    fn read(&mut self) -> Option<BFieldElement> {
+        let word = self.stream.read();
+        self.record(word)
+    }
+}
+
+impl<S: SecretInputStream> SecretInputStream for RecordingStream<'_, S> {
+    fn divine(&mut self) -> Option<BFieldElement> {
+        let word = self.stream.divine();
+        self.record(word)
+    }
+
+    fn divine_at(&mut self, instruction_pointer: usize) -> Option<BFieldElement> {
+        let word = self.stream.divine_at(instruction_pointer);
+        self.record(word)
+    }
+
+    fn diviner(&mut self) -> Option<&mut dyn Diviner> {
+        self.stream.diviner()
+    }
+}
+
+/// Like [`simulate`], but aborts execution with error [`CycleLimitExceeded`] if the program has
+/// not terminated after `max_cycles` clock cycles. The processor matrix of the returned
+/// `AlgebraicExecutionTrace` thus never has more than `max_cycles + 1` rows. Use this to execute
+/// untrusted programs.
+pub fn simulate_with_limit(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    max_cycles: u32,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    execute_with_limit(program, stdin, secret_in, max_cycles, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output)
+    })?;
+    Ok((recorder.finish(), stdout.into()))
+}
+
+/// Execute the `program` until it halts or fails. This is the one execution loop of Triton VM,
+/// shared by [`run`], [`simulate`], and all their variants. Custom executors can build on it, too.
+///
+/// The `observer` is called on the initial state, and on every state reached through a state
+/// transition – see [`VMState::step_mut`] – together with the output of that transition. It is
+/// not called if the state transition fails. In that case, the error is returned.
+///
+/// Returns the final state of the VM.
+pub fn execute<'pgm>(
+    program: &'pgm Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    observer: impl FnMut(&VMState<'pgm>, Option<VMOutput>),
+) -> Result<VMState<'pgm>, VMError> {
+    execute_with_limit(program, stdin, secret_in, u32::MAX, observer)
+}
+
+/// Like [`execute`], but fails with error [`CycleLimitExceeded`] instead of performing the state
+/// transition that would take the VM past clock cycle `max_cycles`.
+pub fn execute_with_limit<'pgm>(
+    program: &'pgm Program,
+    mut stdin: impl InputStream,
+    mut secret_in: impl SecretInputStream,
+    max_cycles: u32,
+    mut observer: impl FnMut(&VMState<'pgm>, Option<VMOutput>),
+) -> Result<VMState<'pgm>, VMError> {
+    let state = VMState::new(program);
+    observer(&state, None);
+    let (state, err) = execute_from(state, &mut stdin, &mut secret_in, max_cycles, observer);
+    match err {
+        Some(err) => Err(err),
+        None => Ok(state),
+    }
+}
+
+/// Like [`execute`], but subject to the limits of the given [`VMConfig`].
+pub fn execute_with_config<'pgm>(
+    program: &'pgm Program,
+    mut stdin: impl InputStream,
+    mut secret_in: impl SecretInputStream,
+    config: VMConfig,
+    mut observer: impl FnMut(&VMState<'pgm>, Option<VMOutput>),
+) -> Result<VMState<'pgm>, VMError> {
+    let state = VMState::new_with_config(program, config);
+    observer(&state, None);
+    let (state, err) = execute_from(state, &mut stdin, &mut secret_in, u32::MAX, observer);
+    match err {
+        Some(err) => Err(err),
+        None => Ok(state),
+    }
+}
+
+/// Like [`execute`], but returns the last state the VM reached even if execution fails – the
+/// state in which it failed – together with the error. Useful for inspecting failed executions.
+pub fn execute_to_last_state<'pgm>(
+    program: &'pgm Program,
+    mut stdin: impl InputStream,
+    mut secret_in: impl SecretInputStream,
+    mut observer: impl FnMut(&VMState<'pgm>, Option<VMOutput>),
+) -> (VMState<'pgm>, Option<VMError>) {
+    let state = VMState::new(program);
+    observer(&state, None);
+    execute_from(state, &mut stdin, &mut secret_in, u32::MAX, observer)
+}
+
+/// Continue execution from the given `state`, which the `observer` is not called on. Consumes
+/// `stdin` and `secret_in`, leaving only the unread words.
+fn execute_from<'pgm>(
+    state: VMState<'pgm>,
+    stdin: &mut impl InputStream,
+    secret_in: &mut impl SecretInputStream,
+    max_cycles: u32,
+    mut observer: impl FnMut(&VMState<'pgm>, Option<VMOutput>),
+) -> (VMState<'pgm>, Option<VMError>) {
+    execute_from_until(state, stdin, secret_in, max_cycles, |state, vm_output| {
+        observer(state, vm_output);
+        ControlFlow::Continue(())
+    })
+}
+
+/// Like [`execute_from`], but additionally stops without error as soon as the `observer` breaks.
+fn execute_from_until<'pgm>(
+    mut state: VMState<'pgm>,
+    stdin: &mut impl InputStream,
+    secret_in: &mut impl SecretInputStream,
+    max_cycles: u32,
+    mut observer: impl FnMut(&VMState<'pgm>, Option<VMOutput>) -> ControlFlow<()>,
+) -> (VMState<'pgm>, Option<VMError>) {
+    while !state.is_complete() {
+        if state.cycle_count >= max_cycles {
+            let err = state.vm_error(CycleLimitExceeded(max_cycles));
+            return (state, Some(err));
+        }
+        match state.step_mut(stdin, secret_in) {
+            Err(err) => return (state, Some(err)),
+            Ok(vm_output) => {
+                if observer(&state, vm_output).is_break() {
+                    break;
+                }
+            }
+        }
+    }
+    (state, None)
+}
+
+/// Like [`simulate`], but stops after `num_cycles` clock cycles and returns a checkpoint of the
+/// state reached, including the not yet consumed input. The checkpoint can be serialized, and
+/// execution can be continued later using [`resume`]. If the program terminates earlier, the
+/// checkpoint holds the terminal state.
+///
+/// If the VM fails before reaching the checkpoint, the error is returned.
+pub fn simulate_to_checkpoint(
+    program: &Program,
+    mut stdin: Vec<BFieldElement>,
+    mut secret_in: Vec<BFieldElement>,
+    num_cycles: u32,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput, StateSnapshot), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let mut observer = |state: &VMState, vm_output| recorder.record(&mut stdout, state, vm_output);
+    let state = VMState::new(program);
+    observer(&state, None);
+    let (state, err) = execute_from(state, &mut stdin, &mut secret_in, num_cycles, observer);
+    if let Some(err) = err.filter(|err| !matches!(err.kind, CycleLimitExceeded(_))) {
+        return Err(err);
+    }
+    let checkpoint = StateSnapshot::new(&state, &stdin, &secret_in);
+    Ok((recorder.finish(), stdout.into(), checkpoint))
+}
+
+/// Continue the execution of `program` from the given `checkpoint`, for example as returned by
+/// [`simulate_to_checkpoint`]. The returned `AlgebraicExecutionTrace` does not contain the
+/// checkpoint's state, but only the states following it. It can thus be
+/// [appended](AlgebraicExecutionTrace::append) to the `AlgebraicExecutionTrace` leading up to the
+/// checkpoint, which gives the `AlgebraicExecutionTrace` of the entire execution.
+pub fn resume(
+    program: &Program,
+    checkpoint: &StateSnapshot,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let (state, mut stdin, mut secret_in) = checkpoint.to_vm_state(program);
+    let (_, err) = execute_from(
+        state,
+        &mut stdin,
+        &mut secret_in,
+        u32::MAX,
+        |state, vm_output| recorder.record(&mut stdout, state, vm_output),
+    );
+    match err {
+        Some(err) => Err(err),
+        None => Ok((recorder.finish(), stdout.into())),
+    }
 }
 
+/// The state of the hash function's sponge after every round of one invocation of instruction
+/// `hash`, including the initial state.
+pub type HashTrace = [[BFieldElement; STATE_SIZE]; NUM_ROUNDS + 1];
+
 #[derive(Debug, Clone)]
 pub struct AlgebraicExecutionTrace {
     pub processor_matrix: Array2<BFieldElement>,
     pub hash_matrix: Array2<BFieldElement>,
+
+    /// The rows of the Jump Stack Table, unpadded: one row per row of the processor matrix,
+    /// sorted by jump stack pointer first and clock cycle second.
+    pub jump_stack_matrix: Array2<BFieldElement>,
+
+    /// Every access of RAM, in the order of execution. `None` unless enabled using
+    /// [`with_memory_access_log`](Self::with_memory_access_log), for example by
+    /// [`simulate_with_memory_access_log`].
+    pub memory_accesses: Option<Vec<MemoryAccess>>,
+
+    /// The tables of [`Coprocessor`]s other than the built-in ones, keyed by name. See
+    /// [`simulate_with_coprocessors`](coprocessor::simulate_with_coprocessors).
+    pub coprocessor_tables: BTreeMap<String, Array2<BFieldElement>>,
 }
 
-impl Default for AlgebraicExecutionTrace {
-    fn default() -> Self {
-        Self {
-            processor_matrix: Array2::default([0, processor_table::BASE_WIDTH]),
-            hash_matrix: Array2::default([0, hash_table::BASE_WIDTH]),
+impl Default for AlgebraicExecutionTrace {
+    fn default() -> Self {
+        Self {
+            processor_matrix: Array2::default([0, processor_table::BASE_WIDTH]),
+            hash_matrix: Array2::default([0, hash_table::BASE_WIDTH]),
+            jump_stack_matrix: Array2::default([0, jump_stack_table::BASE_WIDTH]),
+            memory_accesses: None,
+            coprocessor_tables: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MemoryAccessKind {
+    Read,
+    Write,
+}
+
+/// One access of RAM by one of the instructions `read_mem`, `write_mem`, `read_mem_inc`, or
+/// `write_mem_inc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryAccess {
+    pub address: BFieldElement,
+
+    /// The value read, or the value written.
+    pub value: BFieldElement,
+
+    /// The clock cycle in which the accessing instruction was executed.
+    pub clk: u32,
+
+    pub kind: MemoryAccessKind,
+}
+
+impl MemoryAccess {
+    /// The access performed by the state transition leading to the given state, if any.
+    fn of_transition(state: &VMState) -> Option<Self> {
+        if state.cycle_count == 0 {
+            return None;
+        }
+        let executed_instruction: Instruction =
+            state.previous_instruction.value().try_into().ok()?;
+        let kind = match executed_instruction {
+            ReadMem | ReadMemInc => MemoryAccessKind::Read,
+            WriteMem | WriteMemInc => MemoryAccessKind::Write,
+            _ => return None,
+        };
+        let address = BFieldElement::new(state.ramp);
+        Some(Self {
+            address,
+            value: state.ram.get(address),
+            clk: state.cycle_count - 1,
+            kind,
+        })
+    }
+}
+
+/// How a program used RAM, derived from the memory-access log. See
+/// [`AlgebraicExecutionTrace::ram_access_summary`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RamAccessSummary {
+    /// The maximal ranges of consecutive addresses that were accessed at least once, in
+    /// increasing order.
+    pub touched_ranges: Vec<RangeInclusive<u64>>,
+
+    /// The peak number of distinct addresses held in RAM. Since RAM never shrinks, this is the
+    /// number of distinct addresses accessed.
+    pub num_distinct_addresses: usize,
+
+    pub num_reads: usize,
+    pub num_writes: usize,
+}
+
+impl AlgebraicExecutionTrace {
+    /// An empty trace with room for the processor and jump stack rows of `num_cycles_hint` clock
+    /// cycles – one row more than cycles, accounting for the initial state – and for the hash rows of
+    /// `num_hashes_hint` invocations of instruction `hash`, such that recording a trace of this
+    /// size does not reallocate the matrices.
+    pub fn with_capacity(num_cycles_hint: usize, num_hashes_hint: usize) -> Self {
+        let processor_capacity = (num_cycles_hint + 1) * processor_table::BASE_WIDTH;
+        let hash_capacity = num_hashes_hint * (NUM_ROUNDS + 1) * hash_table::BASE_WIDTH;
+        let jump_stack_capacity = (num_cycles_hint + 1) * jump_stack_table::BASE_WIDTH;
+        let processor_matrix = Array2::from_shape_vec(
+            [0, processor_table::BASE_WIDTH],
+            Vec::with_capacity(processor_capacity),
+        )
+        .expect("shape must match the empty vector");
+        let hash_matrix = Array2::from_shape_vec(
+            [0, hash_table::BASE_WIDTH],
+            Vec::with_capacity(hash_capacity),
+        )
+        .expect("shape must match the empty vector");
+        let jump_stack_matrix = Array2::from_shape_vec(
+            [0, jump_stack_table::BASE_WIDTH],
+            Vec::with_capacity(jump_stack_capacity),
+        )
+        .expect("shape must match the empty vector");
+        Self {
+            processor_matrix,
+            hash_matrix,
+            jump_stack_matrix,
+            memory_accesses: None,
+            coprocessor_tables: BTreeMap::new(),
+        }
+    }
+
+    /// The tag of every output, in the order the outputs were written. See
+    /// [`Claim::output_tags`](crate::proof::Claim::output_tags).
+    pub fn output_tags(&self) -> Vec<BFieldElement> {
+        self.processor_matrix
+            .rows()
+            .into_iter()
+            .filter(|row| row[CI.base_table_index()] == Instruction::WriteIo.opcode_b())
+            .map(|row| row[ST1.base_table_index()])
+            .collect()
+    }
+
+    /// Pad the processor matrix and the hash matrix to the padded height of the execution of the
+    /// given program, the same height the prover pads to. See [`pad_to`](Self::pad_to).
+    pub fn pad(&mut self, program: &[BFieldElement]) {
+        let padded_height = MasterBaseTable::padded_height(self, program);
+        self.pad_to(padded_height);
+    }
+
+    /// Pad the processor matrix, the hash matrix, and the jump stack matrix to the given height,
+    /// following the padding rules of the respective tables. Produces the padded trace
+    /// for external tools, like other provers. The prover pads the
+    /// [`MasterBaseTable`](MasterBaseTable::pad) instead, and must be given an unpadded trace:
+    /// it would consider padding rows of the processor matrix part of the execution.
+    pub fn pad_to(&mut self, padded_height: usize) {
+        assert!(
+            padded_height >= self.processor_matrix.nrows()
+                && padded_height >= self.hash_matrix.nrows(),
+            "Padded height {padded_height} is too small for the execution trace."
+        );
+
+        let processor_len = self.processor_matrix.nrows();
+        let mut processor_matrix = Array2::zeros([padded_height, processor_table::BASE_WIDTH]);
+        processor_matrix
+            .slice_mut(s![..processor_len, ..])
+            .assign(&self.processor_matrix);
+        ProcessorTable::pad_trace(&mut processor_matrix.view_mut(), processor_len);
+        self.processor_matrix = processor_matrix;
+
+        let hash_len = self.hash_matrix.nrows();
+        let mut hash_matrix = Array2::zeros([padded_height, hash_table::BASE_WIDTH]);
+        hash_matrix
+            .slice_mut(s![..hash_len, ..])
+            .assign(&self.hash_matrix);
+        HashTable::pad_trace(&mut hash_matrix.view_mut());
+        self.hash_matrix = hash_matrix;
+
+        let mut jump_stack_matrix = Array2::zeros([padded_height, jump_stack_table::BASE_WIDTH]);
+        jump_stack_matrix
+            .slice_mut(s![..processor_len, ..])
+            .assign(&self.jump_stack_matrix);
+        JumpStackTable::pad_trace(&mut jump_stack_matrix.view_mut(), processor_len);
+        self.jump_stack_matrix = jump_stack_matrix;
+    }
+
+    /// The rows of the jump stack matrix in which the jump stack pointer has the given value, in
+    /// order of their clock cycle. These rows record the history of the jump stack's element at
+    /// that depth.
+    pub fn jump_stack_rows(&self, jump_stack_pointer: u64) -> ArrayView2<'_, BFieldElement> {
+        let jsps = self
+            .jump_stack_matrix
+            .column(JSP.base_table_index())
+            .iter()
+            .map(|jsp| jsp.value())
+            .collect_vec();
+        let start = jsps.partition_point(|&jsp| jsp < jump_stack_pointer);
+        let end = jsps.partition_point(|&jsp| jsp <= jump_stack_pointer);
+        self.jump_stack_matrix.slice(s![start..end, ..])
+    }
+
+    /// The largest value the jump stack pointer takes, _i.e._, the maximal depth of the jump
+    /// stack during the execution.
+    pub fn max_jump_stack_pointer(&self) -> u64 {
+        self.jump_stack_matrix
+            .column(JSP.base_table_index())
+            .iter()
+            .map(|jsp| jsp.value())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn sort_jump_stack_matrix(&mut self) {
+        JumpStackTable::sort_trace(&mut self.jump_stack_matrix);
+    }
+
+    /// Enable recording every access of RAM in [`memory_accesses`](Self::memory_accesses).
+    pub fn with_memory_access_log(mut self) -> Self {
+        self.memory_accesses.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Summarize the memory-access log, or `None` if it was not recorded.
+    pub fn ram_access_summary(&self) -> Option<RamAccessSummary> {
+        let memory_accesses = self.memory_accesses.as_ref()?;
+        let addresses = memory_accesses
+            .iter()
+            .map(|access| access.address.value())
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        let mut touched_ranges: Vec<RangeInclusive<u64>> = vec![];
+        for &address in addresses.iter() {
+            match touched_ranges.last_mut() {
+                Some(range) if *range.end() + 1 == address => {
+                    *range = *range.start()..=address;
+                }
+                _ => touched_ranges.push(address..=address),
+            }
+        }
+
+        let num_accesses_of = |kind| {
+            memory_accesses
+                .iter()
+                .filter(|access| access.kind == kind)
+                .count()
+        };
+        Some(RamAccessSummary {
+            touched_ranges,
+            num_distinct_addresses: addresses.len(),
+            num_reads: num_accesses_of(MemoryAccessKind::Read),
+            num_writes: num_accesses_of(MemoryAccessKind::Write),
+        })
+    }
+
+    /// A collision-resistant digest of the processor and hash matrices. Unlike [`TraceDigest`],
+    /// it binds a malicious executor, but hashing the entire trace with RescuePrime is
+    /// considerably more expensive.
+    pub fn digest(&self) -> Digest {
+        let processor_digest = Self::matrix_digest(self.processor_matrix.view());
+        let hash_digest = Self::matrix_digest(self.hash_matrix.view());
+        RescuePrimeRegular::hash_pair(&processor_digest, &hash_digest)
+    }
+
+    /// The number of rows is hashed along with the elements, disambiguating matrices of
+    /// different shapes.
+    fn matrix_digest(matrix: ArrayView2<BFieldElement>) -> Digest {
+        let num_rows = BFieldElement::new(matrix.nrows() as u64);
+        let elements = [num_rows].into_iter().chain(matrix.iter().copied());
+        Digest::new(RescuePrimeRegular::hash_varlen(
+            &elements.collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Append the trace of a continued execution, for example as returned by [`resume`].
+    pub fn append(&mut self, continuation: &Self) {
+        self.processor_matrix
+            .append(Axis(0), continuation.processor_matrix.view())
+            .expect("shapes must be identical");
+        self.hash_matrix
+            .append(Axis(0), continuation.hash_matrix.view())
+            .expect("shapes must be identical");
+        self.jump_stack_matrix
+            .append(Axis(0), continuation.jump_stack_matrix.view())
+            .expect("shapes must be identical");
+        self.sort_jump_stack_matrix();
+        if let (Some(memory_accesses), Some(continued_accesses)) =
+            (&mut self.memory_accesses, &continuation.memory_accesses)
+        {
+            memory_accesses.extend(continued_accesses);
+        }
+        for (name, table) in continuation.coprocessor_tables.iter() {
+            self.append_coprocessor_rows(name, table.view());
+        }
+    }
+
+    pub fn append_hash_trace(&mut self, hash_trace: HashTrace) {
+        let hash_matrix_addendum = Self::hash_trace_to_hash_matrix_rows(hash_trace);
+        self.hash_matrix
+            .append(Axis(0), hash_matrix_addendum.view())
+            .expect("shapes must be identical");
+    }
+
+    /// Like [`append_hash_trace`](Self::append_hash_trace) for every given hash trace, in order.
+    /// The rows of the hash matrix are generated in parallel. Appending in place makes use of
+    /// capacity reserved by [`with_capacity`](Self::with_capacity).
+    pub fn append_hash_traces(&mut self, hash_traces: &[HashTrace]) {
+        let hash_matrix_addenda: Vec<_> = hash_traces
+            .par_iter()
+            .map(|&hash_trace| Self::hash_trace_to_hash_matrix_rows(hash_trace))
+            .collect();
+        for hash_matrix_addendum in hash_matrix_addenda {
+            self.hash_matrix
+                .append(Axis(0), hash_matrix_addendum.view())
+                .expect("shapes must be identical");
         }
     }
-}
 
-impl AlgebraicExecutionTrace {
-    pub fn append_hash_trace(&mut self, hash_trace: [[BFieldElement; STATE_SIZE]; NUM_ROUNDS + 1]) {
+    /// The rows of the hash matrix recording the given hash trace.
+    pub fn hash_trace_to_hash_matrix_rows(hash_trace: HashTrace) -> Array2<BFieldElement> {
         let mut hash_matrix_addendum = Array2::default([NUM_ROUNDS + 1, hash_table::BASE_WIDTH]);
         for (row_idx, mut row) in hash_matrix_addendum.rows_mut().into_iter().enumerate() {
             let round_number = row_idx + 1;
@@ -135,9 +1284,7 @@ impl AlgebraicExecutionTrace {
                 row[CONSTANT0A.base_table_index() + rc_idx] = round_constants[rc_idx];
             }
         }
-        self.hash_matrix
-            .append(Axis(0), hash_matrix_addendum.view())
-            .expect("shapes must be identical");
+        hash_matrix_addendum
     }
 
     /// The 2·STATE_SIZE (= NUM_ROUND_CONSTANTS) round constants for round `round_number`.
@@ -160,23 +1307,29 @@ impl AlgebraicExecutionTrace {
 
 #[cfg(test)]
 pub mod triton_vm_tests {
-    use std::ops::BitAnd;
-    use std::ops::BitXor;
-
     use itertools::Itertools;
     use ndarray::Array1;
     use ndarray::ArrayView1;
     use num_traits::One;
     use num_traits::Zero;
     use rand::rngs::ThreadRng;
-    use rand::Rng;
     use rand::RngCore;
-    use twenty_first::shared_math::other::random_elements;
-    use twenty_first::shared_math::rescue_prime_regular::RescuePrimeRegular;
+    use triton_opcodes::instruction::label_addresses;
+    use triton_opcodes::instruction::parse;
+    use triton_opcodes::ord_n::Ord16::ST0;
+    use triton_opcodes::ord_n::Ord16::ST1;
     use twenty_first::shared_math::traits::FiniteField;
 
     use crate::shared_tests::SourceCodeAndInput;
+    use crate::stark::triton_stark_tests::parse_simulate_pad;
+    use crate::table::master_table::TableId;
     use crate::table::processor_table::ProcessorMatrixRow;
+    use crate::table::table_column::JumpStackBaseTableColumn;
+    use crate::table::table_column::ProcessorBaseTableColumn;
+    use crate::table::table_column::ProcessorBaseTableColumn::IsPadding;
+    use crate::table::table_column::ProcessorBaseTableColumn::CLK;
+    use crate::test_corpus::property_based_test_program_for_random_ram_access;
+    use crate::vm::error::InstructionError::*;
 
     use super::*;
 
@@ -227,16 +1380,12 @@ pub mod triton_vm_tests {
 
         let stdin = vec![BFieldElement::new(42), BFieldElement::new(56)];
 
-        let (aet, stdout, err) = simulate(&program, stdin, vec![]);
+        let (aet, stdout) = simulate(&program, stdin, vec![]).unwrap();
 
         println!(
             "VM output: [{}]",
-            pretty_print_array_view(Array1::from(stdout).view())
+            pretty_print_array_view(Array1::from(stdout.into_words()).view())
         );
-
-        if let Some(e) = err {
-            panic!("Execution failed: {e}");
-        }
         for row in aet.processor_matrix.rows() {
             println!("{}", ProcessorMatrixRow { row });
         }
@@ -255,9 +1404,8 @@ pub mod triton_vm_tests {
 
         println!("{}", program);
 
-        let (aet, _, err) = simulate_no_input(&program);
+        let (aet, _) = simulate_no_input(&program).unwrap();
 
-        println!("{:?}", err);
         for row in aet.processor_matrix.rows() {
             println!("{}", ProcessorMatrixRow { row });
         }
@@ -269,436 +1417,197 @@ pub mod triton_vm_tests {
         let program = Program::from_code(code).unwrap();
 
         let stdin = vec![42_u64.into(), 56_u64.into()];
-        let (_, stdout, err) = simulate(&program, stdin, vec![]);
+        let (_, stdout) = simulate(&program, stdin, vec![]).unwrap();
 
-        let stdout = Array1::from(stdout);
+        let stdout = Array1::from(stdout.into_words());
         println!("VM output: [{}]", pretty_print_array_view(stdout.view()));
 
-        if let Some(e) = err {
-            panic!("Execution failed: {e}");
-        }
-
         let expected_symbol = BFieldElement::new(14);
         let computed_symbol = stdout[0];
 
         assert_eq!(expected_symbol, computed_symbol);
     }
 
-    pub fn test_hash_nop_nop_lt() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("hash nop hash nop nop hash push 3 push 2 lt assert halt")
-    }
-
-    pub fn test_program_for_halt() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("halt")
-    }
+    #[test]
+    fn simulate_and_record_states_agrees_with_run_test() {
+        let program = Program::from_code(GCD_X_Y).unwrap();
+        let stdin = vec![42_u64.into(), 56_u64.into()];
 
-    pub fn test_program_for_push_pop_dup_swap_nop() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input(
-            "push 1 push 2 pop assert \
-            push 1 dup0 assert assert \
-            push 1 push 2 swap1 assert pop \
-            nop nop nop halt",
-        )
-    }
+        let (aet, states, stdout) =
+            simulate_and_record_states(&program, stdin.clone(), vec![]).unwrap();
+        let (run_states, run_stdout) = run(&program, stdin, vec![]).unwrap();
 
-    pub fn test_program_for_divine() -> SourceCodeAndInput {
-        SourceCodeAndInput {
-            source_code: "divine assert halt".to_string(),
-            input: vec![],
-            secret_input: vec![BFieldElement::one()],
+        assert_eq!(run_stdout, stdout);
+        assert_eq!(run_states.len(), states.len());
+        assert_eq!(aet.processor_matrix.nrows(), states.len());
+        for (state, row) in states.iter().zip_eq(aet.processor_matrix.rows()) {
+            assert_eq!(state.to_processor_row(), row);
         }
     }
 
-    pub fn test_program_for_skiz() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 1 skiz push 0 skiz assert push 1 skiz halt")
-    }
-
-    pub fn test_program_for_call_recurse_return() -> SourceCodeAndInput {
-        let source_code = "push 2 call label halt label: push -1 add dup0 skiz recurse return";
-        SourceCodeAndInput::without_input(source_code)
-    }
-
-    pub fn test_program_for_write_mem_read_mem() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 2 push 1 write_mem pop push 0 read_mem assert halt")
-    }
-
-    pub fn test_program_for_hash() -> SourceCodeAndInput {
-        let source_code =
-            "push 0 push 0 push 0 push 1 push 2 push 3 hash pop pop pop pop pop read_io eq assert halt";
-        let mut hash_input = [BFieldElement::zero(); 10];
-        hash_input[0] = BFieldElement::new(3);
-        hash_input[1] = BFieldElement::new(2);
-        hash_input[2] = BFieldElement::new(1);
-        let digest = RescuePrimeRegular::hash_10(&hash_input);
-        SourceCodeAndInput {
-            source_code: source_code.to_string(),
-            input: vec![digest.to_vec()[0]],
-            secret_input: vec![],
-        }
+    #[test]
+    fn custom_executor_sees_same_states_as_simulate_test() {
+        let program = Program::from_code("push 1 push 2 add push 3 eq assert halt").unwrap();
+        let mut cycle_counts = vec![];
+        execute(&program, vec![], vec![], |state, _| {
+            cycle_counts.push(state.cycle_count)
+        })
+        .unwrap();
+
+        let (aet, _) =
+            simulate_with_hints(&program, vec![], vec![], ExecutionHints::default()).unwrap();
+        assert_eq!(aet.processor_matrix.nrows(), cycle_counts.len());
+        assert_eq!((0..cycle_counts.len() as u32).collect_vec(), cycle_counts);
+
+        let program = Program::from_code("push 1 push 2 add assert push 0 assert halt").unwrap();
+        let err = execute(&program, vec![], vec![], |_, _| ()).unwrap_err();
+        let simulation_err =
+            simulate_with_hints(&program, vec![], vec![], ExecutionHints::default()).unwrap_err();
+        assert_eq!(err, simulation_err);
     }
 
-    pub fn test_program_for_divine_sibling_noswitch() -> SourceCodeAndInput {
-        let source_code = "
-            push 3 \
-            push 4 push 2 push 2 push 2 push 1 \
-            push 5679457 push 1337 push 345887 push -234578456 push 23657565 \
-            divine_sibling \
-            push 1 add assert assert assert assert assert \
-            assert \
-            push -1 add assert \
-            push -1 add assert \
-            push -1 add assert \
-            push -3 add assert \
-            assert halt ";
-        let one = BFieldElement::one();
-        let zero = BFieldElement::zero();
-        SourceCodeAndInput {
-            source_code: source_code.to_string(),
-            input: vec![],
-            secret_input: vec![one, one, one, one, zero],
-        }
-    }
-
-    pub fn test_program_for_divine_sibling_switch() -> SourceCodeAndInput {
-        let source_code = "
-            push 2 \
-            push 4 push 2 push 2 push 2 push 1 \
-            push 5679457 push 1337 push 345887 push -234578456 push 23657565 \
-            divine_sibling \
-            assert \
-            push -1 add assert \
-            push -1 add assert \
-            push -1 add assert \
-            push -3 add assert \
-            push 1 add assert assert assert assert assert \
-            assert halt ";
-        let one = BFieldElement::one();
-        let zero = BFieldElement::zero();
-        SourceCodeAndInput {
-            source_code: source_code.to_string(),
-            input: vec![],
-            secret_input: vec![one, one, one, one, zero],
+    #[test]
+    fn output_is_stamped_with_cycle_and_instruction_pointer_test() {
+        let code = "main: push 1 write_io call foo halt foo: push 2 write_io return";
+        let program = Program::from_code(code).unwrap();
+        let mut stamped_stdout: Vec<StampedWord> = vec![];
+        let (aet, _) = simulate_with_sink(&program, vec![], vec![], &mut stamped_stdout).unwrap();
+
+        let (unstamped_aet, stdout) = simulate_no_input(&program).unwrap();
+        assert_eq!(unstamped_aet.processor_matrix, aet.processor_matrix);
+        let stamped_words = stamped_stdout.iter().map(|stamped| stamped.word);
+        assert_eq!(stdout, stamped_words.collect_vec());
+
+        let expected_stamps = [(1, 2, "main"), (4, 8, "foo")];
+        let labels = label_addresses(&parse(code).unwrap());
+        for (stamped, (cycle, ip, label)) in stamped_stdout.iter().zip_eq(expected_stamps) {
+            assert_eq!(cycle, stamped.cycle);
+            assert_eq!(ip, stamped.instruction_pointer);
+            assert_eq!(Some(label), stamped.enclosing_label(&labels));
         }
     }
 
-    pub fn test_program_for_assert_vector() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input(
-            "push 1 push 2 push 3 push 4 push 5 \
-             push 1 push 2 push 3 push 4 push 5 \
-             assert_vector halt",
-        )
-    }
-
-    pub fn test_program_for_eq_vector() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input(
-            "push 1 push 2 push 3 push 4 push 5 \
-             push 1 push 2 push 3 push 4 push 5 \
-             eq_vector halt",
-        )
-    }
-
-    pub fn property_based_test_program_for_assert_vector() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st0 = rng.gen_range(0..BFieldElement::QUOTIENT);
-        let st1 = rng.gen_range(0..BFieldElement::QUOTIENT);
-        let st2 = rng.gen_range(0..BFieldElement::QUOTIENT);
-        let st3 = rng.gen_range(0..BFieldElement::QUOTIENT);
-        let st4 = rng.gen_range(0..BFieldElement::QUOTIENT);
-
-        let source_code = format!(
-            "push {} push {} push {} push {} push {} \
-            read_io read_io read_io read_io read_io \
-            assert_vector halt",
-            st4, st3, st2, st1, st0,
+    #[test]
+    fn terminal_state_holds_final_memory_and_registers_test() {
+        let program = Program::from_code("push 2 push 1 write_mem pop push 7 halt").unwrap();
+        let (_, _, terminal_state) =
+            simulate_with_terminal_state(&program, vec![], vec![]).unwrap();
+
+        assert_eq!(
+            BFieldElement::new(1),
+            terminal_state.read_ram(BFieldElement::new(2))
         );
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![st4.into(), st3.into(), st2.into(), st1.into(), st0.into()],
-            secret_input: vec![],
-        }
-    }
-
-    pub fn test_program_for_add_mul_invert() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input(
-            "push 2 push -1 add assert \
-            push -1 push -1 mul assert \
-            push 3 dup0 invert mul assert \
-            halt",
-        )
-    }
-
-    pub fn test_program_for_instruction_split() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push -1 split swap1 lt assert halt ")
-    }
-
-    pub fn property_based_test_program_for_split() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st0 = rng.next_u64() % BFieldElement::QUOTIENT;
-        let hi = st0 >> 32;
-        let lo = st0 & 0xffff_ffff;
-
-        let source_code = format!(
-            "push {} split read_io eq assert read_io eq assert halt",
-            st0
+        assert_eq!(
+            BFieldElement::new(0),
+            terminal_state.read_ram(BFieldElement::new(3))
         );
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![hi.into(), lo.into()],
-            secret_input: vec![],
-        }
-    }
-
-    pub fn test_program_for_eq() -> SourceCodeAndInput {
-        SourceCodeAndInput {
-            source_code: "read_io divine eq assert halt".to_string(),
-            input: vec![BFieldElement::new(42)],
-            secret_input: vec![BFieldElement::new(42)],
-        }
-    }
-
-    pub fn property_based_test_program_for_eq() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st0 = rng.next_u64() % BFieldElement::QUOTIENT;
-
-        let source_code = format!(
-            "push {} dup0 read_io eq assert dup0 divine eq assert halt",
-            st0
+        assert_eq!(
+            BFieldElement::new(7),
+            terminal_state.op_stack.safe_peek(ST0)
+        );
+        assert_eq!(
+            BFieldElement::new(2),
+            terminal_state.op_stack.safe_peek(ST1)
         );
 
-        SourceCodeAndInput {
-            source_code,
-            input: vec![st0.into()],
-            secret_input: vec![st0.into()],
-        }
-    }
-
-    pub fn test_program_for_lsb() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 3 lsb assert assert halt")
-    }
-
-    pub fn property_based_test_program_for_lsb() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st0 = rng.next_u32();
-        let lsb = st0 % 2;
-        let st0_shift_right = st0 >> 1;
-
-        let source_code = format!("push {} lsb read_io eq assert read_io eq assert halt", st0);
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![lsb.into(), st0_shift_right.into()],
-            secret_input: vec![],
-        }
-    }
-
-    pub fn test_program_for_lt() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 2 lt assert halt")
-    }
-
-    pub fn property_based_test_program_for_lt() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st1 = rng.next_u32();
-        let st0 = rng.next_u32();
-        let result = if st0 < st1 {
-            1_u64.into()
-        } else {
-            0_u64.into()
-        };
-
-        let source_code = format!("push {} push {} lt read_io eq assert halt", st1, st0);
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![result],
-            secret_input: vec![],
-        }
-    }
-
-    pub fn test_program_for_and() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 3 and assert halt")
-    }
-
-    pub fn property_based_test_program_for_and() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st1 = rng.next_u32();
-        let st0 = rng.next_u32();
-        let result = st0.bitand(st1);
-
-        let source_code = format!("push {} push {} and read_io eq assert halt", st1, st0);
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![result.into()],
-            secret_input: vec![],
-        }
-    }
-
-    pub fn test_program_for_xor() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 7 push 6 xor assert halt")
-    }
-
-    pub fn property_based_test_program_for_xor() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st1 = rng.next_u32();
-        let st0 = rng.next_u32();
-        let result = st0.bitxor(st1);
-
-        let source_code = format!("push {} push {} xor read_io eq assert halt", st1, st0);
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![result.into()],
-            secret_input: vec![],
-        }
-    }
-
-    pub fn test_program_for_reverse() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 2147483648 reverse assert halt")
-    }
-
-    pub fn property_based_test_program_for_reverse() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st0 = rng.next_u32();
-        let st0_rev = st0.reverse_bits().into();
-
-        let source_code = format!("push {} reverse read_io eq assert halt", st0);
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![st0_rev],
-            secret_input: vec![],
-        }
-    }
+        let (states, _) = run(&program, vec![], vec![]).unwrap();
+        let last_state = states.last().unwrap().clone();
+        assert_eq!(TerminalState::from(last_state), terminal_state);
 
-    pub fn test_program_for_lte() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 2 lte assert halt")
+        let (run_terminal_state, _) = run_to_terminal_state(&program, vec![], vec![]).unwrap();
+        assert_eq!(run_terminal_state, terminal_state);
     }
 
-    pub fn property_based_test_program_for_lte() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st1 = rng.next_u32();
-        let st0 = rng.next_u32();
-        let result = if st0 <= st1 {
-            1_u64.into()
-        } else {
-            0_u64.into()
-        };
-
-        let source_code = format!("push {} push {} lte read_io eq assert halt", st1, st0);
-
-        SourceCodeAndInput {
-            source_code,
-            input: vec![result],
-            secret_input: vec![],
-        }
-    }
+    #[test]
+    fn final_state_of_simulation_allows_continuing_computation_test() {
+        let program = Program::from_code("push 2 push 1 write_mem pop push 7 halt").unwrap();
+        let (aet, stdout, final_state) =
+            simulate_and_return_state(&program, vec![], vec![]).unwrap();
+        assert!(stdout.is_empty());
+        assert!(final_state.is_complete());
+        assert_eq!(
+            aet.processor_matrix.nrows() as u32 - 1,
+            final_state.cycle_count
+        );
+        assert_eq!(
+            BFieldElement::new(1),
+            final_state.ram.get(BFieldElement::new(2))
+        );
 
-    pub fn test_program_for_div() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 2 push 3 div assert assert halt")
-    }
+        let (_, _, terminal_state) =
+            simulate_with_terminal_state(&program, vec![], vec![]).unwrap();
+        assert_eq!(terminal_state, TerminalState::from(final_state.clone()));
 
-    pub fn property_based_test_program_for_div() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let denominator = rng.next_u32();
-        let numerator = rng.next_u32();
-        let quotient = numerator / denominator;
-        let remainder = numerator % denominator;
-
-        let source_code = format!(
-            "push {} push {} div read_io eq assert read_io eq assert halt",
-            denominator, numerator
+        let mut continued_state = final_state;
+        continued_state.instruction_pointer = 0;
+        continued_state.step_mut(&mut vec![], &mut vec![]).unwrap();
+        assert_eq!(
+            BFieldElement::new(2),
+            continued_state.op_stack.safe_peek(ST0)
+        );
+        assert_eq!(
+            BFieldElement::new(7),
+            continued_state.op_stack.safe_peek(ST1)
         );
+    }
 
-        SourceCodeAndInput {
-            source_code,
-            input: vec![remainder.into(), quotient.into()],
-            secret_input: vec![],
+    #[test]
+    fn batch_run_agrees_with_individual_runs_test() {
+        let program =
+            Program::from_code("read_io divine add dup0 write_io push 7 eq assert halt").unwrap();
+        let inputs = (0..20_u64)
+            .map(|i| {
+                let stdin = vec![BFieldElement::new(i)];
+                let secret_in = vec![BFieldElement::new(7 - i.min(7))];
+                (stdin, secret_in)
+            })
+            .collect::<Vec<_>>();
+
+        let results = run_batch(&program, &inputs);
+        assert_eq!(inputs.len(), results.len());
+        for ((stdin, secret_in), result) in inputs.iter().zip(results) {
+            let expected_result = run_to_terminal_state(&program, stdin.clone(), secret_in.clone())
+                .map(|(_, stdout)| stdout);
+            assert_eq!(stdin[0].value() > 7, result.is_err());
+            assert_eq!(expected_result, result);
         }
     }
 
-    pub fn property_based_test_program_for_is_u32() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let st0 = rng.next_u32();
-
-        let source_code = format!("push {} is_u32 halt", st0);
-
-        SourceCodeAndInput::without_input(&source_code)
+    #[test]
+    fn run_policy_stops_endless_output_early_test() {
+        let program =
+            Program::from_code("push 0 call count halt count: dup0 write_io push 1 add recurse")
+                .unwrap();
+        let policy = RunPolicy::default().with_max_output_len(3);
+        let (states, stdout) = run_with_policy(&program, vec![], vec![], &policy).unwrap();
+        assert_eq!([0, 1, 2].map(BFieldElement::new).to_vec(), stdout);
+        assert!(!states.last().unwrap().is_complete());
+
+        let policy = RunPolicy::default()
+            .with_output_predicate(|stdout| stdout.last().unwrap().value() == 5);
+        let (_, stdout) = run_with_policy(&program, vec![], vec![], &policy).unwrap();
+        assert_eq!(6, stdout.len());
+
+        let program = Program::from_code("push 1 write_io push 2 write_io halt").unwrap();
+        let (states, stdout) =
+            run_with_policy(&program, vec![], vec![], &RunPolicy::default()).unwrap();
+        let (expected_states, expected_stdout) = run(&program, vec![], vec![]).unwrap();
+        assert_eq!(expected_stdout, stdout);
+        assert_eq!(expected_states.len(), states.len());
+        assert!(states.last().unwrap().is_complete());
     }
 
-    pub fn property_based_test_program_for_random_ram_access() -> SourceCodeAndInput {
-        let mut rng = ThreadRng::default();
-        let num_memory_accesses = rng.gen_range(10..50);
-        let memory_addresses: Vec<BFieldElement> = random_elements(num_memory_accesses);
-        let mut memory_values: Vec<BFieldElement> = random_elements(num_memory_accesses);
-        let mut source_code = String::new();
-
-        // Read some memory before first write to ensure that the memory is initialized with 0s.
-        // Not all addresses are read to have different access patterns:
-        // - Some addresses are read before written to.
-        // - Other addresses are written to before read.
-        for memory_address in memory_addresses.iter().take(num_memory_accesses / 4) {
-            source_code.push_str(&format!(
-                "push {memory_address} push 0 read_mem push 0 eq assert pop "
-            ));
-        }
-
-        // Write everything to RAM.
-        for (memory_address, memory_value) in memory_addresses.iter().zip_eq(memory_values.iter()) {
-            source_code.push_str(&format!(
-                "push {memory_address} push {memory_value} write_mem pop pop "
-            ));
-        }
-
-        // Read back in random order and check that the values did not change.
-        // For repeated sampling from the same range, better performance can be achieved by using
-        // `Uniform`. However, this is a test, and not very many samples – it's fine.
-        let mut reading_permutation = (0..num_memory_accesses).collect_vec();
-        for i in 0..num_memory_accesses {
-            let j = rng.gen_range(0..num_memory_accesses);
-            reading_permutation.swap(i, j);
-        }
-        for idx in reading_permutation {
-            let memory_address = memory_addresses[idx];
-            let memory_value = memory_values[idx];
-            source_code.push_str(&format!(
-                "push {memory_address} push 0 read_mem push {memory_value} eq assert pop "
-            ));
-        }
-
-        // Overwrite half the values with new ones.
-        let mut writing_permutation = (0..num_memory_accesses).collect_vec();
-        for i in 0..num_memory_accesses {
-            let j = rng.gen_range(0..num_memory_accesses);
-            writing_permutation.swap(i, j);
-        }
-        for idx in 0..num_memory_accesses / 2 {
-            let memory_address = memory_addresses[writing_permutation[idx]];
-            let new_memory_value = rng.gen();
-            memory_values[writing_permutation[idx]] = new_memory_value;
-            source_code.push_str(&format!(
-                "push {memory_address} push {new_memory_value} write_mem pop pop "
-            ));
-        }
-
-        // Read back all, i.e., unchanged and overwritten values in (different from before) random
-        // order and check that the values did not change.
-        let mut reading_permutation = (0..num_memory_accesses).collect_vec();
-        for i in 0..num_memory_accesses {
-            let j = rng.gen_range(0..num_memory_accesses);
-            reading_permutation.swap(i, j);
-        }
-        for idx in reading_permutation {
-            let memory_address = memory_addresses[idx];
-            let memory_value = memory_values[idx];
-            source_code.push_str(&format!(
-                "push {memory_address} push 0 read_mem push {memory_value} eq assert pop "
-            ));
-        }
-
-        source_code.push_str("halt");
-        SourceCodeAndInput::without_input(&source_code)
+    #[test]
+    fn op_stack_height_limit_stops_runaway_program_test() {
+        let program = Program::from_code("loop: push 1 call loop").unwrap();
+        let config = VMConfig::default().with_max_op_stack_height(20);
+        let err = simulate_with_config(&program, vec![], vec![], config).unwrap_err();
+        assert_eq!(OpStackOverflow(20), err.kind);
+        assert!(err.cycle_count < 10);
+
+        let program = Program::from_code("push 1 push 2 push 3 pop pop pop halt").unwrap();
+        let config = VMConfig::default().with_max_op_stack_height(19);
+        assert!(simulate_with_config(&program, vec![], vec![], config).is_ok());
     }
 
     #[test]
@@ -719,110 +1628,18 @@ pub mod triton_vm_tests {
         let _ = program.run();
     }
 
-    pub fn test_program_for_split() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input(
-            "push -2 split push 4294967294 eq assert push 4294967295 eq assert \
-             push -1 split push 4294967295 eq assert push 0 eq assert \
-             push  0 split push 0 eq assert push 0 eq assert \
-             push  1 split push 0 eq assert push 1 eq assert \
-             push  2 split push 0 eq assert push 2 eq assert \
-             push 4294967297 split assert assert \
-             halt",
-        )
-    }
-
-    pub fn test_program_for_split_assert() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input(
-            "push -2 split_assert push 4294967294 eq assert push 4294967295 eq assert \
-             push -1 split_assert push 4294967295 eq assert push 0 eq assert \
-             push  0 split_assert push 0 eq assert push 0 eq assert \
-             push  1 split_assert push 0 eq assert push 1 eq assert \
-             push  2 split_assert push 0 eq assert push 2 eq assert \
-             push 4294967297 split_assert assert assert \
-             halt",
-        )
-    }
-
-    pub fn test_program_for_xxadd() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 6 push 7 push 8 push 9 push 10 xxadd halt")
-    }
-
-    pub fn test_program_for_xxmul() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 6 push 7 push 8 push 9 push 10 xxmul halt")
-    }
-
-    pub fn test_program_for_xinvert() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 6 push 7 xinvert halt")
-    }
-
-    pub fn test_program_for_xbmul() -> SourceCodeAndInput {
-        SourceCodeAndInput::without_input("push 5 push 6 push 7 push 8 xbmul halt")
-    }
-
-    pub fn test_program_for_read_io_write_io() -> SourceCodeAndInput {
-        SourceCodeAndInput {
-            source_code: "read_io assert read_io read_io dup1 dup1 add write_io mul write_io halt"
-                .to_string(),
-            input: vec![1_u64.into(), 3_u64.into(), 14_u64.into()],
-            secret_input: vec![],
-        }
+    #[test]
+    #[should_panic(expected = "st0 must be 1.")]
+    fn add_u32_checked_fails_on_overflow_test() {
+        let source_code = "push 4294967295 push 1 add_u32_checked halt";
+        let _ = SourceCodeAndInput::without_input(source_code).run();
     }
 
-    pub fn small_tasm_test_programs() -> Vec<SourceCodeAndInput> {
-        vec![
-            test_program_for_halt(),
-            test_program_for_push_pop_dup_swap_nop(),
-            test_program_for_divine(),
-            test_program_for_skiz(),
-            test_program_for_call_recurse_return(),
-            test_program_for_write_mem_read_mem(),
-            test_program_for_hash(),
-            test_program_for_divine_sibling_noswitch(),
-            test_program_for_divine_sibling_switch(),
-            test_program_for_assert_vector(),
-            test_program_for_eq_vector(),
-            test_program_for_add_mul_invert(),
-            test_program_for_eq(),
-            test_program_for_lsb(),
-            test_program_for_split(),
-            test_program_for_xxadd(),
-            test_program_for_xxmul(),
-            test_program_for_xinvert(),
-            test_program_for_xbmul(),
-            test_program_for_read_io_write_io(),
-        ]
-    }
-
-    pub fn property_based_test_programs() -> Vec<SourceCodeAndInput> {
-        vec![
-            property_based_test_program_for_assert_vector(),
-            property_based_test_program_for_split(),
-            property_based_test_program_for_eq(),
-            property_based_test_program_for_lsb(),
-            property_based_test_program_for_lt(),
-            property_based_test_program_for_and(),
-            property_based_test_program_for_xor(),
-            property_based_test_program_for_reverse(),
-            property_based_test_program_for_lte(),
-            property_based_test_program_for_div(),
-            property_based_test_program_for_is_u32(),
-            property_based_test_program_for_random_ram_access(),
-        ]
-    }
-
-    /// programs with a cycle count of 150 and upwards
-    pub fn bigger_tasm_test_programs() -> Vec<SourceCodeAndInput> {
-        vec![
-            test_hash_nop_nop_lt(),
-            test_program_for_instruction_split(),
-            test_program_for_lt(),
-            test_program_for_and(),
-            test_program_for_xor(),
-            test_program_for_reverse(),
-            test_program_for_lte(),
-            test_program_for_div(),
-            test_program_for_split_assert(),
-        ]
+    #[test]
+    #[should_panic(expected = "st0 must be 1.")]
+    fn mul_u32_checked_fails_on_overflow_test() {
+        let source_code = "push 65536 push 65536 mul_u32_checked halt";
+        let _ = SourceCodeAndInput::without_input(source_code).run();
     }
 
     #[test]
@@ -965,4 +1782,391 @@ pub mod triton_vm_tests {
 
         assert_eq!(expected_stdout, actual_stdout);
     }
+
+    #[test]
+    fn tail_call_does_not_grow_jump_stack_test() {
+        let code = "push 1000 call countdown halt \
+            countdown: dup0 skiz tail_call decrement return \
+            decrement: push -1 add tail_call countdown";
+        let program = Program::from_code(code).unwrap();
+        let mut max_jump_stack_height = 0;
+        let err = execute(&program, vec![], vec![], |state, _| {
+            max_jump_stack_height = max_jump_stack_height.max(state.jump_stack.len());
+        })
+        .err();
+        assert!(err.is_none());
+        assert_eq!(1, max_jump_stack_height);
+    }
+
+    #[test]
+    fn tail_call_outside_of_function_fails_test() {
+        let program = Program::from_code("tail_call foo foo: halt").unwrap();
+        let err = execute(&program, vec![], vec![], |_, _| ()).err();
+        assert!(err.is_some());
+    }
+
+    #[test]
+    fn repeat_loop_test() {
+        let source_code = "push 0 repeat 5 swap1 push 2 add swap1 end write_io halt";
+        let actual_stdout = SourceCodeAndInput::without_input(source_code).run();
+        assert_eq!(vec![BFieldElement::new(10)], actual_stdout);
+    }
+
+    #[test]
+    fn while_loop_test() {
+        let source_code = "push 0 push 3 dup0 \
+            while swap1 push 1 add swap1 push -1 add dup0 end \
+            pop write_io halt";
+        let actual_stdout = SourceCodeAndInput::without_input(source_code).run();
+        assert_eq!(vec![BFieldElement::new(3)], actual_stdout);
+    }
+
+    #[test]
+    fn if_else_test() {
+        for (condition, expected) in [(0, 3), (1, 2), (5, 2)] {
+            let source_code = format!("push {condition} if push 2 else push 3 end write_io halt");
+            let actual_stdout = SourceCodeAndInput::without_input(&source_code).run();
+            assert_eq!(vec![BFieldElement::new(expected)], actual_stdout);
+        }
+    }
+
+    #[test]
+    fn pseudo_spill_reload_test() {
+        let source_code = "push 3 push 2 push 1 push 100 spill3 \
+            push 102 push 0 read_mem write_io pop \
+            dup0 write_io reload2 write_io write_io write_io halt";
+        let actual_stdout = SourceCodeAndInput::without_input(source_code).run();
+        let expected_stdout = [3, 103, 101, 2, 3].map(BFieldElement::new).to_vec();
+
+        assert_eq!(expected_stdout, actual_stdout);
+    }
+
+    #[test]
+    fn streamed_rows_make_up_algebraic_execution_trace_test() {
+        let program = Program::from_code(GCD_X_Y).unwrap();
+        let stdin = vec![42_u64.into(), 56_u64.into()];
+        let (aet, stdout) = simulate(&program, stdin.clone(), vec![]).unwrap();
+
+        let mut streamed_aet = AlgebraicExecutionTrace::default();
+        let streamed_stdout = simulate_streaming(&program, stdin, vec![], |row| {
+            match row {
+                TraceRow::Processor(row) => streamed_aet.processor_matrix.push_row(row.view()),
+                TraceRow::Hash(row) => streamed_aet.hash_matrix.push_row(row.view()),
+            }
+            .unwrap()
+        })
+        .unwrap();
+        assert_eq!(stdout, streamed_stdout);
+        assert_eq!(aet.processor_matrix, streamed_aet.processor_matrix);
+        assert_eq!(aet.hash_matrix, streamed_aet.hash_matrix);
+
+        let program = Program::from_code("push 1 hash push 0 assert halt").unwrap();
+        let mut num_processor_rows = 0;
+        let mut num_hash_rows = 0;
+        let streamed_result = simulate_streaming(&program, vec![], vec![], |row| match row {
+            TraceRow::Processor(_) => num_processor_rows += 1,
+            TraceRow::Hash(_) => num_hash_rows += 1,
+        });
+        assert!(streamed_result.is_err());
+        assert_eq!(4, num_processor_rows);
+        assert_eq!(NUM_ROUNDS + 1, num_hash_rows);
+    }
+
+    #[test]
+    fn failure_modes_are_distinguishable_test() {
+        let zero = BFieldElement::zero();
+        let failing_programs = [
+            ("pop halt", OpStackTooShallow),
+            ("push 0 assert halt", AssertionFailed(zero)),
+            (
+                "push -1 push 0 push 0 push 0 push 0 push 0 \
+                push 0 push 0 push 0 push 0 push 0 divine_sibling halt",
+                FailedU32Conversion(-BFieldElement::one()),
+            ),
+            ("push 0 push 1 divine_quotient halt", DivisionByZero),
+            ("read_io halt", StandardInputExhausted),
+            ("divine halt", SecretInputExhausted),
+            ("return", JumpStackTooShallow),
+        ];
+        for (code, expected_kind) in failing_programs {
+            let program = Program::from_code(code).unwrap();
+            let err = simulate(&program, vec![], vec![]).expect_err(&format!("{code} must fail"));
+            println!("{err}");
+            assert_eq!(expected_kind, err.kind, "{code}");
+        }
+
+        let program = Program::from_code("push 0 assert halt").unwrap();
+        let err = simulate(&program, vec![], vec![]).unwrap_err();
+        assert_eq!(2, err.instruction_pointer);
+        assert_eq!(1, err.cycle_count);
+        assert_eq!(Some(Instruction::Assert), err.instruction);
+    }
+
+    #[test]
+    fn cycle_limit_aborts_runaway_execution_test() {
+        let program = Program::from_code("push 0 call loop halt loop: push 1 add recurse").unwrap();
+        let err = simulate_with_limit(&program, vec![], vec![], 100).unwrap_err();
+        assert_eq!(CycleLimitExceeded(100), err.kind);
+        assert_eq!(100, err.cycle_count);
+
+        let program = Program::from_code(GCD_X_Y).unwrap();
+        let stdin = vec![42_u64.into(), 56_u64.into()];
+        let (aet, stdout) = simulate(&program, stdin.clone(), vec![]).unwrap();
+        let num_cycles = aet.processor_matrix.nrows() as u32 - 1;
+
+        let (limited_aet, limited_stdout) =
+            simulate_with_limit(&program, stdin.clone(), vec![], num_cycles).unwrap();
+        assert_eq!(stdout, limited_stdout);
+        assert_eq!(aet.processor_matrix, limited_aet.processor_matrix);
+
+        assert!(simulate_with_limit(&program, stdin, vec![], num_cycles - 1).is_err());
+    }
+
+    #[test]
+    fn resuming_from_checkpoint_continues_execution_test() {
+        let program = Program::from_code(GCD_X_Y).unwrap();
+        let stdin = vec![42_u64.into(), 56_u64.into()];
+        let (aet, stdout) = simulate(&program, stdin.clone(), vec![]).unwrap();
+
+        let (mut stitched_aet, stitched_stdout, checkpoint) =
+            simulate_to_checkpoint(&program, stdin, vec![], 10).unwrap();
+        assert_eq!(10, checkpoint.cycle_count);
+        assert!(checkpoint.stdin.is_empty());
+
+        let serialized_checkpoint = serde_json::to_string(&checkpoint).unwrap();
+        let checkpoint: StateSnapshot = serde_json::from_str(&serialized_checkpoint).unwrap();
+        let (resumed_aet, resumed_stdout) = resume(&program, &checkpoint).unwrap();
+
+        stitched_aet.append(&resumed_aet);
+        let mut stitched_stdout = stitched_stdout.into_words();
+        stitched_stdout.extend(resumed_stdout.into_words());
+        assert_eq!(stdout, stitched_stdout);
+        assert_eq!(aet.processor_matrix, stitched_aet.processor_matrix);
+        assert_eq!(aet.hash_matrix, stitched_aet.hash_matrix);
+        assert_eq!(aet.jump_stack_matrix, stitched_aet.jump_stack_matrix);
+    }
+
+    #[test]
+    fn jump_stack_matrix_is_recorded_sorted_by_jump_stack_pointer_test() {
+        let source_code = "call foo call foo halt foo: call bar return bar: return";
+        let program = Program::from_code(source_code).unwrap();
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        assert_eq!(aet.processor_matrix.nrows(), aet.jump_stack_matrix.nrows());
+        assert_eq!(2, aet.max_jump_stack_pointer());
+
+        let mut num_rows = 0;
+        for jsp in 0..=2 {
+            let rows = aet.jump_stack_rows(jsp);
+            let num_processor_rows = aet
+                .processor_matrix
+                .column(ProcessorBaseTableColumn::JSP.base_table_index())
+                .iter()
+                .filter(|&&processor_jsp| processor_jsp.value() == jsp)
+                .count();
+            assert_eq!(num_processor_rows, rows.nrows());
+            let clks = rows.column(JumpStackBaseTableColumn::CLK.base_table_index());
+            assert!(clks
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| a.value() < b.value()));
+            num_rows += rows.nrows();
+        }
+        assert_eq!(aet.jump_stack_matrix.nrows(), num_rows);
+    }
+
+    #[test]
+    fn checkpoint_after_termination_holds_terminal_state_test() {
+        let program = Program::from_code("push 1 hash pop halt").unwrap();
+        let (aet, _, checkpoint) = simulate_to_checkpoint(&program, vec![], vec![], 100).unwrap();
+        assert_eq!(
+            aet.processor_matrix.nrows() as u32 - 1,
+            checkpoint.cycle_count
+        );
+
+        let (resumed_aet, resumed_stdout) = resume(&program, &checkpoint).unwrap();
+        assert!(resumed_stdout.is_empty());
+        assert_eq!(0, resumed_aet.processor_matrix.nrows());
+        assert_eq!(0, resumed_aet.hash_matrix.nrows());
+    }
+
+    #[test]
+    fn trace_digest_test() {
+        let program = Program::from_code("push 1 call foo halt foo: push 2 add return").unwrap();
+        let (aet, _, trace_digest) = simulate_with_trace_digest(&program, vec![], vec![]).unwrap();
+        assert_eq!(aet.processor_matrix.nrows() as u64, trace_digest.num_rows());
+
+        let recomputed_digest = TraceDigest::of_processor_matrix(aet.processor_matrix.view());
+        assert_eq!(trace_digest, recomputed_digest);
+
+        let program = Program::from_code("read_io read_io add write_io halt").unwrap();
+        let stdin = [2, 3].map(BFieldElement::new).to_vec();
+        let (_, _, trace_digest) =
+            simulate_with_trace_digest(&program, stdin.clone(), vec![]).unwrap();
+        let (_, _, same_digest) = simulate_with_trace_digest(&program, stdin, vec![]).unwrap();
+        assert_eq!(trace_digest.digest(), same_digest.digest());
+
+        let other_stdin = [3, 2].map(BFieldElement::new).to_vec();
+        let (_, _, other_digest) =
+            simulate_with_trace_digest(&program, other_stdin, vec![]).unwrap();
+        assert_eq!(trace_digest.num_rows(), other_digest.num_rows());
+        assert_ne!(trace_digest.digest(), other_digest.digest());
+    }
+
+    #[test]
+    fn sparse_states_reconstruct_every_state_test() {
+        let program = Program::from_code(
+            "read_io call countdown divine add write_io halt \
+            countdown: dup0 skiz return push -1 add nop nop nop recurse",
+        )
+        .unwrap();
+        let stdin = vec![BFieldElement::new(4)];
+        let secret_in = vec![BFieldElement::new(7)];
+        let (states, stdout) = run(&program, stdin.clone(), secret_in.clone()).unwrap();
+
+        let interval = 5;
+        let (sparse_states, sparse_stdout) =
+            run_sparse(&program, stdin, secret_in, interval).unwrap();
+        assert_eq!(stdout, sparse_stdout);
+
+        let retained_states = sparse_states.retained_states().collect_vec();
+        assert!(retained_states.len() < states.len());
+        let retained_cycles = retained_states.iter().map(|s| s.cycle_count).collect_vec();
+        for window in states.windows(2) {
+            let (previous, state) = (&window[0], &window[1]);
+            let size = previous.current_instruction().unwrap().size();
+            if state.instruction_pointer != previous.instruction_pointer + size {
+                assert!(retained_cycles.contains(&previous.cycle_count));
+                assert!(retained_cycles.contains(&state.cycle_count));
+            }
+        }
+        assert!(retained_cycles.iter().all_unique());
+        assert!(retained_cycles.contains(&states.last().unwrap().cycle_count));
+
+        for state in states.iter() {
+            let reconstructed = sparse_states.state_at(state.cycle_count).unwrap();
+            assert_eq!(state.to_processor_row(), reconstructed.to_processor_row());
+        }
+        assert!(sparse_states.state_at(states.len() as u32).is_none());
+    }
+
+    #[test]
+    fn memory_access_log_and_summary_test() {
+        let program = Program::from_code(
+            "push 5 push 42 write_mem pop pop push 6 push 43 write_mem pop pop \
+            push 9 push 1 write_mem pop pop push 5 push 0 read_mem pop pop halt",
+        )
+        .unwrap();
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        assert!(aet.memory_accesses.is_none());
+        assert!(aet.ram_access_summary().is_none());
+
+        let (aet, _) = simulate_with_memory_access_log(&program, vec![], vec![]).unwrap();
+        let memory_accesses = aet.memory_accesses.clone().unwrap();
+        assert_eq!(4, memory_accesses.len());
+        let first_write = MemoryAccess {
+            address: BFieldElement::new(5),
+            value: BFieldElement::new(42),
+            clk: 2,
+            kind: MemoryAccessKind::Write,
+        };
+        assert_eq!(first_write, memory_accesses[0]);
+        let read = memory_accesses[3];
+        assert_eq!(MemoryAccessKind::Read, read.kind);
+        assert_eq!(BFieldElement::new(42), read.value);
+
+        let summary = aet.ram_access_summary().unwrap();
+        assert_eq!(vec![5..=6, 9..=9], summary.touched_ranges);
+        assert_eq!(3, summary.num_distinct_addresses);
+        assert_eq!(1, summary.num_reads);
+        assert_eq!(3, summary.num_writes);
+
+        let exported = serde_json::to_string(&memory_accesses).unwrap();
+        let imported: Vec<MemoryAccess> = serde_json::from_str(&exported).unwrap();
+        assert_eq!(memory_accesses, imported);
+    }
+
+    #[test]
+    fn batched_hash_traces_agree_with_appending_one_by_one_test() {
+        let hash_traces = (0..5)
+            .map(|i| RescuePrimeRegular::trace(&[BFieldElement::new(i); 2 * DIGEST_LENGTH]))
+            .collect_vec();
+
+        let mut one_by_one = AlgebraicExecutionTrace::default();
+        for &hash_trace in hash_traces.iter() {
+            one_by_one.append_hash_trace(hash_trace);
+        }
+        let mut batched = AlgebraicExecutionTrace::default();
+        batched.append_hash_trace(hash_traces[0]);
+        batched.append_hash_traces(&hash_traces[1..]);
+        assert_eq!(one_by_one.hash_matrix, batched.hash_matrix);
+        assert_eq!(5 * (NUM_ROUNDS + 1), batched.hash_matrix.nrows());
+    }
+
+    #[test]
+    fn padded_trace_agrees_with_padded_master_base_table_test() {
+        let code = "push 2 push 3 mul dup0 write_io hash halt";
+        let (_, _, master_base_table) = parse_simulate_pad(code, vec![], vec![]);
+        let program = Program::from_code(code).unwrap();
+        let (mut aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        aet.pad(&program.to_bwords());
+
+        assert_eq!(
+            master_base_table.padded_height,
+            aet.processor_matrix.nrows()
+        );
+        let processor_table = master_base_table.table(TableId::ProcessorTable);
+        for column in [CLK, IsPadding] {
+            let column = column.base_table_index();
+            assert_eq!(
+                processor_table.column(column),
+                aet.processor_matrix.column(column)
+            );
+        }
+        assert_eq!(master_base_table.table(TableId::HashTable), aet.hash_matrix);
+        assert_eq!(
+            master_base_table.table(TableId::JumpStackTable),
+            aet.jump_stack_matrix
+        );
+    }
+
+    #[test]
+    fn hinted_simulation_records_identical_trace_without_reallocating_test() {
+        let program = Program::from_code("hash push 3 push 2 lt assert hash halt").unwrap();
+        let hints = ExecutionHints::dry_run(&program, vec![], vec![]);
+        let (aet, _) = simulate(&program, vec![], vec![]).unwrap();
+        assert_eq!(aet.processor_matrix.nrows(), hints.num_cycles + 1);
+        assert_eq!(2, hints.num_hashes);
+        assert_eq!(aet.hash_matrix.nrows(), hints.num_hashes * (NUM_ROUNDS + 1));
+
+        let (hinted_aet, _) = simulate_with_hints(&program, vec![], vec![], hints).unwrap();
+        assert_eq!(aet.processor_matrix, hinted_aet.processor_matrix);
+        assert_eq!(aet.hash_matrix, hinted_aet.hash_matrix);
+
+        let mut aet = AlgebraicExecutionTrace::with_capacity(10, 1);
+        let processor_matrix_location = aet.processor_matrix.as_ptr();
+        let hash_matrix_location = aet.hash_matrix.as_ptr();
+        let row = Array1::zeros(processor_table::BASE_WIDTH);
+        for _ in 0..11 {
+            aet.processor_matrix.push_row(row.view()).unwrap();
+        }
+        aet.append_hash_traces(&[RescuePrimeRegular::trace(&[BFieldElement::zero(); 10])]);
+        assert_eq!(processor_matrix_location, aet.processor_matrix.as_ptr());
+        assert_eq!(hash_matrix_location, aet.hash_matrix.as_ptr());
+    }
+
+    #[test]
+    fn algebraic_execution_trace_digest_test() {
+        let program = Program::from_code("read_io push 2 mul hash write_io halt").unwrap();
+        let stdin = vec![BFieldElement::new(3)];
+        let (aet, _) = simulate(&program, stdin.clone(), vec![]).unwrap();
+        let (same_aet, _) = simulate(&program, stdin, vec![]).unwrap();
+        assert_eq!(aet.digest(), same_aet.digest());
+
+        let (other_aet, _) = simulate(&program, vec![BFieldElement::new(4)], vec![]).unwrap();
+        assert_ne!(aet.digest(), other_aet.digest());
+
+        let mut truncated_aet = aet.clone();
+        truncated_aet.hash_matrix = Array2::default([0, hash_table::BASE_WIDTH]);
+        assert_ne!(aet.digest(), truncated_aet.digest());
+    }
 }
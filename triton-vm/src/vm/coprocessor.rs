@@ -0,0 +1,157 @@
+//! Coprocessors observe the execution of a program and record their own tables in the
+//! [`AlgebraicExecutionTrace`].
+//!
+//! The hash coprocessor, recording the [hash matrix](AlgebraicExecutionTrace::hash_matrix), is a
+//! [`Coprocessor`] like any other. Experimental coprocessors, for example a Keccak table, can be
+//! developed out of tree: implement [`Coprocessor`], and execute programs using
+//! [`simulate_with_coprocessors`]. Their tables end up in
+//! [`coprocessor_tables`](AlgebraicExecutionTrace::coprocessor_tables), and their heights are
+//! accounted for in the padded height. Proving them additionally requires adding them to the
+//! master table, as described in [`table::coprocessor`](crate::table::coprocessor).
+
+use ndarray::Array2;
+use ndarray::ArrayView2;
+use ndarray::Axis;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::state::VMOutput;
+use crate::state::VMState;
+use crate::vm::error::VMError;
+use crate::vm::execute;
+use crate::vm::stream::InputStream;
+use crate::vm::stream::SecretInputStream;
+use crate::vm::AlgebraicExecutionTrace;
+use crate::vm::HashTrace;
+use crate::vm::TraceRecorder;
+
+/// A unit assisting the processor, recording its own table of the execution trace.
+pub trait Coprocessor {
+    /// Observe one state transition: the executed instruction, without its argument, the state
+    /// the transition leads to, and the output of the transition.
+    fn observe(&mut self, instruction: Instruction, state: &VMState, vm_output: Option<&VMOutput>);
+
+    /// Record the coprocessor's table in the trace once execution has finished, for example using
+    /// [`append_coprocessor_rows`](AlgebraicExecutionTrace::append_coprocessor_rows).
+    fn finish(self: Box<Self>, aet: &mut AlgebraicExecutionTrace);
+}
+
+/// Records the [hash matrix](AlgebraicExecutionTrace::hash_matrix). The rows are only generated
+/// once execution has finished, in parallel. This keeps the execution loop lean, which matters
+/// most for hash-heavy programs.
+#[derive(Debug, Clone, Default)]
+pub struct HashCoprocessor {
+    hash_traces: Vec<HashTrace>,
+}
+
+impl Coprocessor for HashCoprocessor {
+    fn observe(&mut self, _: Instruction, _: &VMState, vm_output: Option<&VMOutput>) {
+        if let Some(VMOutput::XlixTrace(hash_trace)) = vm_output {
+            self.hash_traces.push(**hash_trace);
+        }
+    }
+
+    fn finish(self: Box<Self>, aet: &mut AlgebraicExecutionTrace) {
+        aet.append_hash_traces(&self.hash_traces);
+    }
+}
+
+/// Like [`simulate`](crate::vm::simulate), but additionally runs the given coprocessors alongside
+/// the built-in ones.
+pub fn simulate_with_coprocessors(
+    program: &Program,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+    coprocessors: Vec<Box<dyn Coprocessor>>,
+) -> (AlgebraicExecutionTrace, Vec<BFieldElement>, Option<VMError>) {
+    let mut recorder = TraceRecorder::default().with_coprocessors(coprocessors);
+    let mut stdout = vec![];
+    let err = execute(program, stdin, secret_in, |state, vm_output| {
+        recorder.record(&mut stdout, state, vm_output)
+    })
+    .err();
+    (recorder.finish(), stdout, err)
+}
+
+impl AlgebraicExecutionTrace {
+    /// Append rows to the table of the coprocessor with the given name, creating the table if
+    /// necessary.
+    pub fn append_coprocessor_rows(&mut self, name: &str, rows: ArrayView2<BFieldElement>) {
+        self.coprocessor_tables
+            .entry(name.to_string())
+            .or_insert_with(|| Array2::zeros([0, rows.ncols()]))
+            .append(Axis(0), rows)
+            .expect("shapes must be identical");
+    }
+}
+
+#[cfg(test)]
+mod coprocessor_tests {
+    use ndarray::Array1;
+    use triton_opcodes::instruction::AnInstruction::Mul;
+
+    use crate::table::master_table::MasterBaseTable;
+    use crate::table::table_column::BaseTableColumn;
+    use crate::table::table_column::ProcessorBaseTableColumn::ST0;
+    use crate::vm::simulate;
+
+    use super::*;
+
+    /// Records every product, `rows_per_product` times.
+    struct MulCoprocessor {
+        rows_per_product: usize,
+        rows: Vec<BFieldElement>,
+    }
+
+    impl Coprocessor for MulCoprocessor {
+        fn observe(&mut self, instruction: Instruction, state: &VMState, _: Option<&VMOutput>) {
+            if instruction == Mul {
+                let product = state.to_processor_row()[ST0.base_table_index()];
+                self.rows.extend(vec![product; self.rows_per_product]);
+            }
+        }
+
+        fn finish(self: Box<Self>, aet: &mut AlgebraicExecutionTrace) {
+            let num_rows = self.rows.len();
+            let rows = Array1::from(self.rows).into_shape([num_rows, 1]).unwrap();
+            aet.append_coprocessor_rows("mul", rows.view());
+        }
+    }
+
+    #[test]
+    fn out_of_tree_coprocessor_records_own_table_test() {
+        let program = Program::from_code("push 2 push 3 mul push 4 mul hash halt").unwrap();
+        let mul_coprocessor = MulCoprocessor {
+            rows_per_product: 1,
+            rows: vec![],
+        };
+        let (aet, stdout, err) =
+            simulate_with_coprocessors(&program, vec![], vec![], vec![Box::new(mul_coprocessor)]);
+        assert!(err.is_none());
+
+        let expected_mul_table = Array2::from_shape_vec([2, 1], vec![6, 24])
+            .unwrap()
+            .mapv(BFieldElement::new);
+        assert_eq!(expected_mul_table, aet.coprocessor_tables["mul"]);
+
+        let (builtin_aet, builtin_stdout) = simulate(&program, vec![], vec![]).unwrap();
+        assert!(builtin_aet.coprocessor_tables.is_empty());
+        assert_eq!(builtin_aet.processor_matrix, aet.processor_matrix);
+        assert_eq!(builtin_aet.hash_matrix, aet.hash_matrix);
+        assert_eq!(builtin_stdout, stdout);
+    }
+
+    #[test]
+    fn coprocessor_tables_determine_padded_height_test() {
+        let program = Program::from_code("push 2 push 3 mul halt").unwrap();
+        let mul_coprocessor = MulCoprocessor {
+            rows_per_product: 100,
+            rows: vec![],
+        };
+        let (aet, _, _) =
+            simulate_with_coprocessors(&program, vec![], vec![], vec![Box::new(mul_coprocessor)]);
+        let padded_height = MasterBaseTable::padded_height(&aet, &program.to_bwords());
+        assert_eq!(128, padded_height);
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use anyhow::Result;
+use triton_opcodes::ord_n::Ord16;
+use triton_opcodes::program::Program;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::state::VMOutput;
+use crate::state::VMState;
+
+/// A condition on the state of the VM that stops [continued](Debugger::continue_execution)
+/// execution once it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Breakpoint {
+    /// The instruction at the given address is about to be executed.
+    Address(usize),
+
+    /// The instruction at the given label is about to be executed. Requires the debugger to know
+    /// the program's labels, see [`Debugger::with_labels`].
+    Label(String),
+
+    /// The given clock cycle has been reached.
+    Cycle(u32),
+}
+
+/// A location whose value is observed. Execution stops as soon as the value changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Watchpoint {
+    Ram(BFieldElement),
+    OpStack(Ord16),
+}
+
+/// Why the debugger stopped execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    Breakpoint(Breakpoint),
+    Watchpoint {
+        watchpoint: Watchpoint,
+        old_value: BFieldElement,
+        new_value: BFieldElement,
+    },
+
+    /// The program has terminated. No further steps are possible.
+    Terminated,
+}
+
+/// Execute a program instruction by instruction, stopping at breakpoints and watchpoints, and
+/// inspect the state of the VM in between.
+#[derive(Debug, Clone)]
+pub struct Debugger<'pgm> {
+    state: VMState<'pgm>,
+    stdin: Vec<BFieldElement>,
+    secret_in: Vec<BFieldElement>,
+    stdout: Vec<BFieldElement>,
+    labels: HashMap<String, usize>,
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+}
+
+impl<'pgm> Debugger<'pgm> {
+    pub fn new(
+        program: &'pgm Program,
+        stdin: Vec<BFieldElement>,
+        secret_in: Vec<BFieldElement>,
+    ) -> Self {
+        Self {
+            state: VMState::new(program),
+            stdin,
+            secret_in,
+            stdout: vec![],
+            labels: HashMap::new(),
+            breakpoints: vec![],
+            watchpoints: vec![],
+        }
+    }
+
+    /// Make the program's labels, for example as returned by `label_addresses`, known to the
+    /// debugger, allowing breakpoints on labels.
+    #[must_use]
+    pub fn with_labels(mut self, labels: HashMap<String, usize>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Fails if the breakpoint is on a label unknown to the debugger.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> Result<()> {
+        if let Breakpoint::Label(label) = &breakpoint {
+            if !self.labels.contains_key(label) {
+                bail!("Cannot break on unknown label {label}.");
+            }
+        }
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+        Ok(())
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) {
+        self.breakpoints.retain(|existing| existing != breakpoint);
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        if !self.watchpoints.contains(&watchpoint) {
+            self.watchpoints.push(watchpoint);
+        }
+    }
+
+    pub fn remove_watchpoint(&mut self, watchpoint: &Watchpoint) {
+        self.watchpoints.retain(|existing| existing != watchpoint);
+    }
+
+    /// The current state of the VM.
+    pub fn inspect(&self) -> &VMState<'pgm> {
+        &self.state
+    }
+
+    /// Everything the program has written to standard output so far.
+    pub fn stdout(&self) -> &[BFieldElement] {
+        &self.stdout
+    }
+
+    /// Execute exactly one instruction, ignoring breakpoints. Stops if a watched value changes or
+    /// the program terminates, in which case the reason is returned.
+    pub fn step(&mut self) -> Result<Option<Stop>> {
+        if self.state.is_complete() {
+            return Ok(Some(Stop::Terminated));
+        }
+
+        let watched_values = self.watched_values();
+        let vm_output = self.state.step_mut(&mut self.stdin, &mut self.secret_in)?;
+        if let Some(VMOutput::WriteOutputSymbol(written_word)) = vm_output {
+            self.stdout.push(written_word);
+        }
+
+        for (watchpoint, old_value) in self.watchpoints.iter().zip(watched_values) {
+            let new_value = self.watched_value(watchpoint);
+            if new_value != old_value {
+                return Ok(Some(Stop::Watchpoint {
+                    watchpoint: *watchpoint,
+                    old_value,
+                    new_value,
+                }));
+            }
+        }
+
+        match self.state.is_complete() {
+            true => Ok(Some(Stop::Terminated)),
+            false => Ok(None),
+        }
+    }
+
+    /// Execute instructions until a breakpoint is hit, a watched value changes, or the program
+    /// terminates. Always executes at least one instruction, such that continuing from a
+    /// breakpoint makes progress.
+    pub fn continue_execution(&mut self) -> Result<Stop> {
+        loop {
+            if let Some(stop) = self.step()? {
+                return Ok(stop);
+            }
+            if let Some(breakpoint) = self.breakpoints.iter().find(|bp| self.is_hit(bp)) {
+                return Ok(Stop::Breakpoint(breakpoint.clone()));
+            }
+        }
+    }
+
+    fn is_hit(&self, breakpoint: &Breakpoint) -> bool {
+        match breakpoint {
+            Breakpoint::Address(address) => self.state.instruction_pointer == *address,
+            Breakpoint::Label(label) => {
+                self.labels.get(label) == Some(&self.state.instruction_pointer)
+            }
+            Breakpoint::Cycle(cycle) => self.state.cycle_count == *cycle,
+        }
+    }
+
+    fn watched_values(&self) -> Vec<BFieldElement> {
+        self.watchpoints
+            .iter()
+            .map(|watchpoint| self.watched_value(watchpoint))
+            .collect()
+    }
+
+    fn watched_value(&self, watchpoint: &Watchpoint) -> BFieldElement {
+        match *watchpoint {
+            Watchpoint::Ram(address) => self.state.ram.get(address),
+            Watchpoint::OpStack(position) => self.state.op_stack.safe_peek(position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod debugger_tests {
+    use triton_opcodes::instruction::label_addresses;
+    use triton_opcodes::instruction::parse;
+    use triton_opcodes::ord_n::Ord16::ST0;
+
+    use super::*;
+
+    const CODE: &str = "push 3 call countdown push 5 push 6 write_mem pop pop halt \
+        countdown: push -1 add dup0 skiz recurse return";
+
+    fn debugger_for(program: &Program) -> Debugger<'_> {
+        let labels = label_addresses(&parse(CODE).unwrap());
+        Debugger::new(program, vec![], vec![]).with_labels(labels)
+    }
+
+    #[test]
+    fn breakpoints_stop_continued_execution_test() {
+        let program = Program::from_code(CODE).unwrap();
+        let mut debugger = debugger_for(&program);
+        let countdown = Breakpoint::Label("countdown".to_string());
+        debugger.add_breakpoint(countdown.clone()).unwrap();
+        debugger.add_breakpoint(Breakpoint::Cycle(4)).unwrap();
+
+        for expected_counter in [3, 2, 1] {
+            assert_eq!(
+                Stop::Breakpoint(countdown.clone()),
+                debugger.continue_execution().unwrap()
+            );
+            assert_eq!(
+                expected_counter,
+                debugger.inspect().op_stack.safe_peek(ST0).value()
+            );
+            if expected_counter == 3 {
+                let stop = debugger.continue_execution().unwrap();
+                assert_eq!(Stop::Breakpoint(Breakpoint::Cycle(4)), stop);
+            }
+        }
+
+        debugger.remove_breakpoint(&countdown);
+        assert_eq!(Stop::Terminated, debugger.continue_execution().unwrap());
+        assert_eq!(Stop::Terminated, debugger.continue_execution().unwrap());
+    }
+
+    #[test]
+    fn unknown_label_cannot_be_a_breakpoint_test() {
+        let program = Program::from_code(CODE).unwrap();
+        let mut debugger = debugger_for(&program);
+        let unknown_label = Breakpoint::Label("no_such_label".to_string());
+        assert!(debugger.add_breakpoint(unknown_label).is_err());
+    }
+
+    #[test]
+    fn watchpoints_stop_on_changed_values_test() {
+        let program = Program::from_code(CODE).unwrap();
+        let mut debugger = debugger_for(&program);
+        let address = BFieldElement::new(5);
+        debugger.add_watchpoint(Watchpoint::Ram(address));
+
+        let expected_stop = Stop::Watchpoint {
+            watchpoint: Watchpoint::Ram(address),
+            old_value: BFieldElement::new(0),
+            new_value: BFieldElement::new(6),
+        };
+        assert_eq!(expected_stop, debugger.continue_execution().unwrap());
+        assert_eq!(BFieldElement::new(6), debugger.inspect().ram.get(address));
+
+        debugger.remove_watchpoint(&Watchpoint::Ram(address));
+        debugger.add_watchpoint(Watchpoint::OpStack(ST0));
+        let stop = debugger.step().unwrap();
+        assert!(matches!(stop, Some(Stop::Watchpoint { .. })));
+    }
+
+    #[test]
+    fn single_steps_execute_one_instruction_each_test() {
+        let program = Program::from_code("push 1 write_io push 2 write_io halt").unwrap();
+        let mut debugger = Debugger::new(&program, vec![], vec![]);
+        debugger.add_breakpoint(Breakpoint::Cycle(1)).unwrap();
+        for expected_cycle in 1..=3 {
+            assert_eq!(None, debugger.step().unwrap());
+            assert_eq!(expected_cycle, debugger.inspect().cycle_count);
+        }
+        assert_eq!(Some(Stop::Terminated), debugger.step().unwrap());
+        assert_eq!(4, debugger.inspect().cycle_count);
+        assert_eq!([1, 2].map(BFieldElement::new), debugger.stdout());
+    }
+}
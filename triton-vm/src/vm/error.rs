@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use triton_opcodes::instruction::Instruction;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use InstructionError::*;
+
+/// A failed execution of a program: what went wrong, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VMError {
+    pub kind: InstructionError,
+
+    /// The address of the offending instruction.
+    pub instruction_pointer: usize,
+
+    /// The clock cycle in which the offending instruction was executed.
+    pub cycle_count: u32,
+
+    /// `None` if the instruction pointer does not point into the program.
+    pub instruction: Option<Instruction>,
+}
+
+impl Display for VMError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        match self.instruction {
+            Some(instruction) => write!(f, " – executing `{instruction}`")?,
+            None => write!(f, " – no instruction")?,
+        }
+        write!(
+            f,
+            " at address {} in cycle {}",
+            self.instruction_pointer, self.cycle_count
+        )
+    }
+}
+
+impl Error for VMError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionError {
+    InstructionPointerUnderflow,
+    InstructionPointerOverflow(usize),
+    OpStackTooShallow,
+    JumpStackTooShallow,
+    /// The offending value of `st0`. Where the assertion failed is part of the [`VMError`].
+    AssertionFailed(BFieldElement),
+    InverseOfZero,
+    RunawayInstructionArg,
+    UngracefulTermination,
+    FailedU32Conversion(BFieldElement),
+    DivisionByZero,
+    StandardInputExhausted,
+    SecretInputExhausted,
+    CycleLimitExceeded(u32),
+    OpStackOverflow(usize),
+    BudgetExhausted(u64),
+}
+
+impl Display for InstructionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstructionPointerUnderflow => {
+                write!(f, "Instruction pointer points to before start of program",)
+            }
+
+            InstructionPointerOverflow(ip) => {
+                write!(f, "Instruction pointer {} points outside of program", ip)
+            }
+
+            OpStackTooShallow => {
+                write!(f, "Operational stack is too shallow")
+            }
+
+            JumpStackTooShallow => {
+                write!(f, "Jump stack does not contain return address")
+            }
+
+            AssertionFailed(st0) => {
+                write!(f, "Assertion failed: st0 must be 1. st0: {st0}")
+            }
+
+            InverseOfZero => {
+                write!(f, "0 does not have a multiplicative inverse")
+            }
+
+            RunawayInstructionArg => {
+                write!(
+                    f,
+                    "A numeric argument to an instruction occurred out of place"
+                )
+            }
+
+            UngracefulTermination => {
+                write!(
+                    f,
+                    "The Virtual Machine must terminate using instruction Halt"
+                )
+            }
+
+            FailedU32Conversion(word) => {
+                write!(
+                    f,
+                    "Failed to convert BFieldElement {} into u32",
+                    word.value()
+                )
+            }
+
+            DivisionByZero => {
+                write!(f, "Division by zero")
+            }
+
+            StandardInputExhausted => {
+                write!(f, "Standard input is exhausted")
+            }
+
+            SecretInputExhausted => {
+                write!(f, "Secret input is exhausted")
+            }
+
+            CycleLimitExceeded(max_cycles) => {
+                write!(
+                    f,
+                    "Execution did not terminate within the limit of {} cycles",
+                    max_cycles
+                )
+            }
+
+            OpStackOverflow(max_height) => {
+                write!(
+                    f,
+                    "Operational stack exceeds the limit of {} elements",
+                    max_height
+                )
+            }
+
+            BudgetExhausted(budget) => {
+                write!(
+                    f,
+                    "Execution exceeds the budget of {} units of cost",
+                    budget
+                )
+            }
+        }
+    }
+}
+
+impl Error for InstructionError {}
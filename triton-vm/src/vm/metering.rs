@@ -0,0 +1,211 @@
+//! Deterministic fee accounting for program executions.
+//!
+//! A [`CostModel`] assigns a cost to every executed instruction. The cost of an instruction is
+//! the cost of its mnemonic, plus a surcharge if the instruction invokes the hash function or
+//! accesses RAM. Since the cost only depends on the stream of executed instructions, every
+//! execution of a program on the same input has the same cost. [`simulate_with_metering`]
+//! charges every instruction before executing it, and aborts as soon as the budget would be
+//! exceeded.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use triton_opcodes::instruction::AnInstruction::Hash;
+use triton_opcodes::instruction::Instruction;
+use triton_opcodes::instruction::RamAccess;
+use triton_opcodes::program::Program;
+
+use crate::public_output::PublicOutput;
+use crate::state::VMState;
+use crate::vm::error::InstructionError::BudgetExhausted;
+use crate::vm::error::VMError;
+use crate::vm::execute_from_until;
+use crate::vm::stream::InputStream;
+use crate::vm::stream::SecretInputStream;
+use crate::vm::AlgebraicExecutionTrace;
+use crate::vm::TraceRecorder;
+
+/// The cost of executing instructions. By default, every instruction costs 1, making the cost of
+/// an execution its number of clock cycles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostModel {
+    instruction_cost: u64,
+
+    /// The costs differing from `instruction_cost`, keyed by the instruction's mnemonic.
+    instruction_costs: HashMap<&'static str, u64>,
+    hash_cost: u64,
+    ram_access_cost: u64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            instruction_cost: 1,
+            instruction_costs: HashMap::new(),
+            hash_cost: 0,
+            ram_access_cost: 0,
+        }
+    }
+}
+
+impl CostModel {
+    /// The cost of every instruction whose cost is not set explicitly.
+    pub fn with_default_instruction_cost(mut self, cost: u64) -> Self {
+        self.instruction_cost = cost;
+        self
+    }
+
+    /// The cost of the given instruction. The instruction's argument is ignored, _e.g._, all
+    /// `dup`s cost the same.
+    pub fn with_instruction_cost(mut self, instruction: Instruction, cost: u64) -> Self {
+        self.instruction_costs.insert(instruction.name(), cost);
+        self
+    }
+
+    /// The surcharge for every invocation of the hash function, _i.e._, every `hash`.
+    pub fn with_hash_cost(mut self, cost: u64) -> Self {
+        self.hash_cost = cost;
+        self
+    }
+
+    /// The surcharge for every instruction reading from or writing to RAM.
+    pub fn with_ram_access_cost(mut self, cost: u64) -> Self {
+        self.ram_access_cost = cost;
+        self
+    }
+
+    /// The total cost of executing the given instruction once, including all surcharges.
+    pub fn cost(&self, instruction: Instruction) -> u64 {
+        let mut cost = self
+            .instruction_costs
+            .get(instruction.name())
+            .copied()
+            .unwrap_or(self.instruction_cost);
+        if instruction == Hash {
+            cost = cost.saturating_add(self.hash_cost);
+        }
+        if instruction.ram_access() != RamAccess::None {
+            cost = cost.saturating_add(self.ram_access_cost);
+        }
+        cost
+    }
+}
+
+/// Like [`simulate`](crate::vm::simulate), but charges every instruction according to the
+/// [`CostModel`]. Additionally returns the total cost of the execution. If executing the next
+/// instruction would exceed the `budget`, execution is aborted before that instruction with
+/// error [`BudgetExhausted`].
+pub fn simulate_with_metering(
+    program: &Program,
+    mut stdin: impl InputStream,
+    mut secret_in: impl SecretInputStream,
+    cost_model: &CostModel,
+    budget: u64,
+) -> Result<(AlgebraicExecutionTrace, PublicOutput, u64), VMError> {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let mut total_cost = 0_u64;
+    let state = VMState::new(program);
+    recorder.record(&mut stdout, &state, None);
+
+    let mut within_budget = charge_next_instruction(&state, cost_model, budget, &mut total_cost);
+    let (state, err) = match within_budget {
+        false => (state, None),
+        true => execute_from_until(
+            state,
+            &mut stdin,
+            &mut secret_in,
+            u32::MAX,
+            |state, vm_output| {
+                recorder.record(&mut stdout, state, vm_output);
+                within_budget = charge_next_instruction(state, cost_model, budget, &mut total_cost);
+                if within_budget {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            },
+        ),
+    };
+    if let Some(err) = err {
+        return Err(err);
+    }
+    if !within_budget {
+        return Err(state.vm_error(BudgetExhausted(budget)));
+    }
+    Ok((recorder.finish(), stdout.into(), total_cost))
+}
+
+/// Add the cost of the instruction the VM executes next to the `total_cost`, unless that exceeds
+/// the `budget`. Returns whether the budget suffices.
+fn charge_next_instruction(
+    state: &VMState,
+    cost_model: &CostModel,
+    budget: u64,
+    total_cost: &mut u64,
+) -> bool {
+    if state.is_complete() {
+        return true;
+    }
+    let Ok(instruction) = state.current_instruction() else {
+        return true;
+    };
+    let cost = cost_model.cost(instruction);
+    match total_cost.checked_add(cost).filter(|&cost| cost <= budget) {
+        Some(new_total_cost) => {
+            *total_cost = new_total_cost;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod metering_tests {
+    use triton_opcodes::instruction::AnInstruction::*;
+    use triton_opcodes::ord_n::Ord16::ST0;
+
+    use crate::vm::simulate;
+
+    use super::*;
+
+    const SOURCE_CODE: &str = "push 1 push 2 write_mem dup0 read_mem hash pop halt";
+
+    #[test]
+    fn default_cost_is_number_of_cycles_test() {
+        let program = Program::from_code(SOURCE_CODE).unwrap();
+        let (aet, stdout, cost) =
+            simulate_with_metering(&program, vec![], vec![], &CostModel::default(), u64::MAX)
+                .unwrap();
+        assert_eq!(aet.processor_matrix.nrows() as u64 - 1, cost);
+
+        let (unmetered_aet, unmetered_stdout) = simulate(&program, vec![], vec![]).unwrap();
+        assert_eq!(unmetered_aet.processor_matrix, aet.processor_matrix);
+        assert_eq!(unmetered_stdout, stdout);
+    }
+
+    #[test]
+    fn surcharges_and_budget_are_applied_test() {
+        let program = Program::from_code(SOURCE_CODE).unwrap();
+        let cost_model = CostModel::default()
+            .with_instruction_cost(Dup(ST0), 5)
+            .with_hash_cost(100)
+            .with_ram_access_cost(10);
+        assert_eq!(5, cost_model.cost(Dup(ST0)));
+        assert_eq!(11, cost_model.cost(ReadMem));
+        assert_eq!(101, cost_model.cost(Hash));
+
+        // push, push, write_mem, dup0, read_mem, hash, pop
+        let expected_cost = 1 + 1 + 11 + 5 + 11 + 101 + 1;
+        let (_, _, cost) =
+            simulate_with_metering(&program, vec![], vec![], &cost_model, expected_cost).unwrap();
+        assert_eq!(expected_cost, cost);
+
+        let budget = 100;
+        let err =
+            simulate_with_metering(&program, vec![], vec![], &cost_model, budget).unwrap_err();
+        assert_eq!(BudgetExhausted(budget), err.kind);
+        assert_eq!(Some(Hash), err.instruction);
+        assert_eq!(5, err.cycle_count);
+    }
+}
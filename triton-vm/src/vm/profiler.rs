@@ -0,0 +1,428 @@
+//! Profiling of program executions, to find the hot loops of a program.
+//!
+//! A [`Profiler`] observes the states of an execution, for example through [`execute`], and
+//! attributes every clock cycle to the label of the executed instruction as well as to the call
+//! chain leading to it. [`simulate_with_profile`] is the convenient way of profiling while
+//! simulating.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use triton_opcodes::instruction::AnInstruction::Hash;
+use triton_opcodes::program::Program;
+use triton_profiler::folded_stacks::FoldedStacks;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+
+use crate::state::VMState;
+use crate::vm::enclosing_label;
+use crate::vm::error::VMError;
+use crate::vm::execute;
+use crate::vm::stream::InputStream;
+use crate::vm::stream::SecretInputStream;
+use crate::vm::AlgebraicExecutionTrace;
+use crate::vm::TraceRecorder;
+
+/// The name of code preceding all labels.
+const BEFORE_FIRST_LABEL: &str = "(before first label)";
+
+/// Like [`simulate`](crate::vm::simulate), but additionally profiles the execution. The `labels`
+/// and their addresses are, for example, as returned by `label_addresses`.
+pub fn simulate_with_profile<'pgm>(
+    program: &'pgm Program,
+    labels: &HashMap<String, usize>,
+    stdin: impl InputStream,
+    secret_in: impl SecretInputStream,
+) -> (
+    AlgebraicExecutionTrace,
+    Vec<BFieldElement>,
+    ProfileReport,
+    Option<VMError>,
+) {
+    let mut recorder = TraceRecorder::default();
+    let mut stdout = vec![];
+    let mut profiler = Profiler::new(labels);
+    let err = execute(
+        program,
+        stdin,
+        secret_in,
+        |state: &VMState<'pgm>, vm_output| {
+            recorder.record(&mut stdout, state, vm_output);
+            profiler.observe(state);
+        },
+    )
+    .err();
+    (recorder.finish(), stdout, profiler.finish(), err)
+}
+
+/// The number of calls to one label, grouped by the clock cycles spent per call. The cycles of a
+/// call include the cycles of all calls it makes in turn. Group `k` counts calls taking at least
+/// 2^k and fewer than 2^(k+1) cycles.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleHistogram {
+    counts: BTreeMap<u32, usize>,
+}
+
+impl CycleHistogram {
+    pub fn add(&mut self, cycles: usize) {
+        *self.counts.entry(cycles.max(1).ilog2()).or_insert(0) += 1;
+    }
+
+    /// The number of calls in group `k`.
+    pub fn count(&self, k: u32) -> usize {
+        self.counts.get(&k).copied().unwrap_or(0)
+    }
+
+    pub fn num_calls(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// The non-empty groups in increasing order, as the range of cycles `[lower, upper)` they
+    /// cover and their count.
+    pub fn groups(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.counts
+            .iter()
+            .map(|(&k, &count)| (1 << k, 1 << (k + 1), count))
+    }
+}
+
+impl Display for CycleHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let groups = self
+            .groups()
+            .map(|(lower, upper, count)| format!("[{lower}, {upper}): {count}"))
+            .join("  ");
+        write!(f, "{groups}")
+    }
+}
+
+/// The execution profile of the code following one label.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelProfile {
+    pub label: String,
+
+    /// The clock cycles spent executing instructions following the label, excluding the cycles
+    /// spent in the labels it calls.
+    pub cycles: usize,
+
+    /// The number of executed instructions `hash`, _i.e._, of invocations of the hash
+    /// coprocessor, excluding those in the labels it calls.
+    pub hashes: usize,
+
+    /// The calls to the label, grouped by their cycles. Empty if the label was never called.
+    pub calls: CycleHistogram,
+}
+
+/// The number of clock cycles one instruction was executed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionProfile {
+    pub name: &'static str,
+    pub cycles: usize,
+}
+
+/// The result of profiling an execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    /// The clock cycles of the execution, including the final `halt`. Equals the number of rows of
+    /// the processor matrix.
+    pub total_cycles: usize,
+
+    /// The number of invocations of the hash coprocessor.
+    pub total_hashes: usize,
+
+    /// All labels in which at least one instruction was executed or which were called, most
+    /// cycles first.
+    pub labels: Vec<LabelProfile>,
+
+    /// All executed instructions, most cycles first.
+    pub instructions: Vec<InstructionProfile>,
+
+    /// The clock cycles per call chain. A `call` is attributed to the caller, its matching
+    /// `return` to the callee. The outermost frame of every call chain is the label of the
+    /// program's entry point.
+    pub call_tree: FoldedStacks,
+}
+
+/// A call that has not returned yet.
+#[derive(Debug, Clone)]
+struct Frame {
+    label: String,
+    start_cycle: u32,
+    jump_stack_height: usize,
+}
+
+/// Profiles an execution state by state. Every observed state accounts for the instruction it is
+/// about to execute, which corresponds to one row of the processor matrix.
+#[derive(Debug, Clone)]
+pub struct Profiler<'a> {
+    labels: &'a HashMap<String, usize>,
+    label_profiles: HashMap<String, LabelProfile>,
+    instruction_cycles: HashMap<&'static str, usize>,
+    call_chain: Vec<Frame>,
+    call_tree: FoldedStacks,
+    last_cycle: u32,
+}
+
+impl<'a> Profiler<'a> {
+    pub fn new(labels: &'a HashMap<String, usize>) -> Self {
+        Self {
+            labels,
+            label_profiles: HashMap::new(),
+            instruction_cycles: HashMap::new(),
+            call_chain: vec![],
+            call_tree: FoldedStacks::new(),
+            last_cycle: 0,
+        }
+    }
+
+    fn label_at(&self, address: usize) -> String {
+        enclosing_label(self.labels, address)
+            .unwrap_or(BEFORE_FIRST_LABEL)
+            .to_string()
+    }
+
+    fn label_profile(&mut self, label: String) -> &mut LabelProfile {
+        self.label_profiles
+            .entry(label.clone())
+            .or_insert_with(|| LabelProfile {
+                label,
+                ..Default::default()
+            })
+    }
+
+    /// Account for the instruction the given state is about to execute. States must be observed
+    /// in the order of their clock cycles, starting with the initial state.
+    pub fn observe(&mut self, state: &VMState) {
+        let Ok(instruction) = state.current_instruction() else {
+            return;
+        };
+        self.last_cycle = state.cycle_count;
+
+        let jump_stack_height = state.jump_stack.len();
+        self.return_from_calls(jump_stack_height, state.cycle_count);
+        let is_new_frame = self
+            .call_chain
+            .last()
+            .is_none_or(|frame| jump_stack_height > frame.jump_stack_height);
+        if is_new_frame {
+            self.call_chain.push(Frame {
+                label: self.label_at(state.instruction_pointer),
+                start_cycle: state.cycle_count,
+                jump_stack_height,
+            });
+        }
+        let call_chain = self
+            .call_chain
+            .iter()
+            .map(|frame| &frame.label)
+            .collect_vec();
+        self.call_tree.add(&call_chain, 1);
+
+        let label = self.label_at(state.instruction_pointer);
+        let label_profile = self.label_profile(label);
+        label_profile.cycles += 1;
+        if instruction == Hash {
+            label_profile.hashes += 1;
+        }
+        *self
+            .instruction_cycles
+            .entry(instruction.name())
+            .or_insert(0) += 1;
+    }
+
+    /// Close all frames deeper than the given jump stack height. The entry frame is only closed
+    /// by [`finish`](Self::finish).
+    fn return_from_calls(&mut self, jump_stack_height: usize, cycle: u32) {
+        while self.call_chain.len() > 1
+            && self.call_chain.last().unwrap().jump_stack_height > jump_stack_height
+        {
+            let frame = self.call_chain.pop().unwrap();
+            let cycles = (cycle - frame.start_cycle) as usize;
+            self.label_profile(frame.label).calls.add(cycles);
+        }
+    }
+
+    pub fn finish(mut self) -> ProfileReport {
+        let end_cycle = self.last_cycle + 1;
+        self.return_from_calls(0, end_cycle);
+
+        let labels = self
+            .label_profiles
+            .into_values()
+            .sorted_by_key(|profile| (std::cmp::Reverse(profile.cycles), profile.label.clone()))
+            .collect_vec();
+        let instructions = self
+            .instruction_cycles
+            .into_iter()
+            .map(|(name, cycles)| InstructionProfile { name, cycles })
+            .sorted_by_key(|profile| (std::cmp::Reverse(profile.cycles), profile.name))
+            .collect_vec();
+
+        ProfileReport {
+            total_cycles: labels.iter().map(|profile| profile.cycles).sum(),
+            total_hashes: labels.iter().map(|profile| profile.hashes).sum(),
+            labels,
+            instructions,
+            call_tree: self.call_tree,
+        }
+    }
+}
+
+impl ProfileReport {
+    /// The profile of the given label, if any of its instructions were executed.
+    pub fn label(&self, label: &str) -> Option<&LabelProfile> {
+        self.labels.iter().find(|profile| profile.label == label)
+    }
+
+    /// The number of clock cycles the given instruction, identified by its name, was executed in.
+    pub fn instruction_cycles(&self, name: &str) -> usize {
+        self.instructions
+            .iter()
+            .find(|profile| profile.name == name)
+            .map_or(0, |profile| profile.cycles)
+    }
+
+    /// Renders the call tree like a flamegraph turned on its side: one line per call chain,
+    /// indented by its depth, with a bar proportional to the cycles spent in it, including the
+    /// cycles of all chains it is a prefix of.
+    pub fn flamegraph(&self) -> String {
+        const BAR_WIDTH: u64 = 40;
+
+        let call_chains: BTreeSet<&[String]> = self
+            .call_tree
+            .stacks()
+            .flat_map(|(stack, _)| (1..=stack.len()).map(move |depth| &stack[..depth]))
+            .collect();
+        let total_weight = self.call_tree.total_weight().max(1);
+
+        let mut rendering = String::new();
+        for call_chain in call_chains {
+            let weight = self.call_tree.inclusive_weight(call_chain);
+            let indentation = "  ".repeat(call_chain.len() - 1);
+            let name = format!("{indentation}{}", call_chain.last().unwrap());
+            let bar = "█".repeat(((BAR_WIDTH * weight).div_ceil(total_weight)) as usize);
+            let share = 100.0 * weight as f64 / total_weight as f64;
+            rendering.push_str(&format!("{name:<40} {weight:>8} {share:>5.1}% {bar}\n"));
+        }
+        rendering
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.flamegraph())?;
+
+        writeln!(
+            f,
+            "| Label                          |   Cycles | Hashes |  Calls |"
+        )?;
+        writeln!(
+            f,
+            "|:-------------------------------|---------:|-------:|-------:|"
+        )?;
+        for profile in self.labels.iter() {
+            writeln!(
+                f,
+                "| {:<30} | {:>8} | {:>6} | {:>6} |",
+                profile.label,
+                profile.cycles,
+                profile.hashes,
+                profile.calls.num_calls(),
+            )?;
+        }
+        writeln!(f)?;
+
+        for profile in self.labels.iter().filter(|p| p.calls.num_calls() > 0) {
+            writeln!(f, "cycles per call of {}: {}", profile.label, profile.calls)?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "| Instruction      |   Cycles |")?;
+        writeln!(f, "|:-----------------|---------:|")?;
+        for profile in self.instructions.iter() {
+            writeln!(f, "| {:<16} | {:>8} |", profile.name, profile.cycles)?;
+        }
+        write!(
+            f,
+            "Total: {} clock cycles, {} hashes",
+            self.total_cycles, self.total_hashes
+        )
+    }
+}
+
+#[cfg(test)]
+mod profiler_tests {
+    use triton_opcodes::instruction::label_addresses;
+    use triton_opcodes::instruction::parse;
+
+    use super::*;
+
+    fn profile(code: &str) -> (AlgebraicExecutionTrace, ProfileReport) {
+        let labelled_instructions = parse(code).unwrap();
+        let program = Program::new(&labelled_instructions);
+        let labels = label_addresses(&labelled_instructions);
+        let (aet, _, report, err) = simulate_with_profile(&program, &labels, vec![], vec![]);
+        assert!(err.is_none(), "{err:?}");
+        (aet, report)
+    }
+
+    #[test]
+    fn cycles_are_attributed_to_labels_and_instructions_test() {
+        let code = "main: push 3 call countdown hash halt \
+            countdown: dup0 push 0 eq skiz return push -1 add recurse";
+        let (aet, report) = profile(code);
+        println!("{report}");
+
+        assert_eq!(aet.processor_matrix.nrows(), report.total_cycles);
+        assert_eq!(1, report.total_hashes);
+        assert_eq!(4, report.label("main").unwrap().cycles);
+        assert_eq!(3 * 7 + 5, report.label("countdown").unwrap().cycles);
+        assert_eq!("countdown", report.labels[0].label);
+        assert_eq!(4, report.instruction_cycles("dup"));
+        assert_eq!(3, report.instruction_cycles("recurse"));
+        assert_eq!(0, report.instruction_cycles("pop"));
+    }
+
+    #[test]
+    fn calls_are_grouped_by_cycles_test() {
+        let code = "main: call short call short call long halt \
+            short: nop return \
+            long: nop nop nop nop nop nop return";
+        let (_, report) = profile(code);
+
+        let short = &report.label("short").unwrap().calls;
+        assert_eq!(2, short.num_calls());
+        assert_eq!(2, short.count(1));
+        let long = &report.label("long").unwrap().calls;
+        assert_eq!(1, long.num_calls());
+        assert_eq!(1, long.count(2));
+        assert_eq!(0, report.label("main").unwrap().calls.num_calls());
+
+        assert_eq!(4, report.call_tree.weight(&["main"]));
+        assert_eq!(4, report.call_tree.weight(&["main", "short"]));
+        assert_eq!(
+            report.total_cycles as u64,
+            report.call_tree.inclusive_weight(&["main"])
+        );
+    }
+
+    #[test]
+    fn flamegraph_renders_nested_calls_test() {
+        let code = "main: call outer halt \
+            outer: call inner return \
+            inner: hash return";
+        let (_, report) = profile(code);
+        let flamegraph = report.flamegraph();
+        println!("{flamegraph}");
+
+        let lines = flamegraph.lines().collect_vec();
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("main "));
+        assert!(lines[1].starts_with("  outer "));
+        assert!(lines[2].starts_with("    inner "));
+        assert!(lines[0].contains("100.0%"));
+        assert_eq!(1, report.label("inner").unwrap().hashes);
+    }
+}
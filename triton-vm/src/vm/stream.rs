@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use anyhow::bail;
+use anyhow::Result;
+use triton_opcodes::instruction::AnInstruction::*;
+use triton_opcodes::instruction::DivinationHint;
+use triton_opcodes::instruction::LabelledInstruction;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+
+use crate::op_stack::OpStack;
+use crate::vm::StampedWord;
+
+/// The source of the words read by instruction `read_io`. Words are only requested when they are
+/// read, allowing input to be fed lazily, for example from a file or a channel.
+pub trait InputStream {
+    /// The next word, or `None` if the stream is exhausted.
+    fn read(&mut self) -> Option<BFieldElement>;
+}
+
+/// The source of the non-deterministic words read by instructions `divine` and `divine_sibling`.
+/// Like [`InputStream`], words are only requested when they are read, allowing, for example, an
+/// oracle to compute them on demand.
+pub trait SecretInputStream {
+    /// The next word, or `None` if the stream is exhausted.
+    fn divine(&mut self) -> Option<BFieldElement>;
+
+    /// The next word for the instruction at the given address. By default, the address is ignored.
+    fn divine_at(&mut self, _instruction_pointer: usize) -> Option<BFieldElement> {
+        self.divine()
+    }
+
+    /// The [`Diviner`] to consult before falling back to the VM's defaults, if any.
+    fn diviner(&mut self) -> Option<&mut dyn Diviner> {
+        None
+    }
+}
+
+/// The destination of the words written by instruction `write_io`. Every word is handed to the
+/// sink as soon as it is written, allowing, for example, interactive tools to display output
+/// progressively, or tests to observe output of programs that never terminate.
+pub trait OutputSink {
+    /// Receive a written word, stamped with where and when it was written.
+    fn write(&mut self, stamped_word: StampedWord);
+}
+
+/// Computes non-deterministic witnesses on the fly, for example by looking up a Merkle sibling
+/// in an authentication structure, instead of requiring them to be pre-populated in the secret
+/// input in exactly the order the program divines them. Returning `None` falls back to the VM's
+/// default behavior.
+///
+/// To execute a program with a diviner, wrap the secret input using [`WithDiviner`].
+pub trait Diviner {
+    /// The word instruction `divine` with the given hint pushes. The op stack is in the state
+    /// before executing the instruction. By default, the VM computes the hinted value itself.
+    fn divine_hinted(
+        &mut self,
+        _hint: DivinationHint,
+        _op_stack: &OpStack,
+    ) -> Option<BFieldElement> {
+        None
+    }
+
+    /// The sibling of the node with the given index and digest, in the same order as the given
+    /// digest. By default, the VM reads the sibling from the secret input.
+    fn divine_sibling(
+        &mut self,
+        _node_index: u32,
+        _known_digest: [BFieldElement; DIGEST_LENGTH],
+    ) -> Option<[BFieldElement; DIGEST_LENGTH]> {
+        None
+    }
+}
+
+/// A secret input stream together with a [`Diviner`] that the VM consults first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WithDiviner<S, D> {
+    pub secret_in: S,
+    pub diviner: D,
+}
+
+impl<S: SecretInputStream, D: Diviner> WithDiviner<S, D> {
+    pub fn new(secret_in: S, diviner: D) -> Self {
+        Self { secret_in, diviner }
+    }
+}
+
+impl<S: SecretInputStream, D: Diviner> SecretInputStream for WithDiviner<S, D> {
+    fn divine(&mut self) -> Option<BFieldElement> {
+        self.secret_in.divine()
+    }
+
+    fn diviner(&mut self) -> Option<&mut dyn Diviner> {
+        Some(&mut self.diviner)
+    }
+}
+
+/// An instruction reading from secret input, identified by the closest label preceding it and
+/// the number of such instructions between that label and the instruction. Instructions preceding
+/// all labels have the empty label.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DivinationSite {
+    pub label: String,
+    pub occurrence: usize,
+}
+
+impl DivinationSite {
+    pub fn new(label: &str, occurrence: usize) -> Self {
+        Self {
+            label: label.to_string(),
+            occurrence,
+        }
+    }
+}
+
+impl Display for DivinationSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.label, self.occurrence)
+    }
+}
+
+/// Secret input split into one bucket of words per [`DivinationSite`], such that the words a
+/// subroutine divines can be provided independently of the order in which the program's
+/// subroutines are called. Both `divine` and `divine_sibling` are divination sites; the latter
+/// reads all words of the sibling digest from its bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartitionedSecretInput {
+    sites: HashMap<usize, DivinationSite>,
+    buckets: HashMap<DivinationSite, VecDeque<BFieldElement>>,
+}
+
+impl PartitionedSecretInput {
+    /// Fails if a bucket belongs to a site that does not exist in the program.
+    pub fn new(
+        program: &[LabelledInstruction],
+        buckets: HashMap<DivinationSite, Vec<BFieldElement>>,
+    ) -> Result<Self> {
+        let sites = Self::divination_sites(program);
+        for site in buckets.keys() {
+            if !sites.values().any(|known_site| known_site == site) {
+                bail!("The program has no divination site {site}.");
+            }
+        }
+        let buckets = buckets
+            .into_iter()
+            .map(|(site, words)| (site, words.into()))
+            .collect();
+        Ok(Self { sites, buckets })
+    }
+
+    fn divination_sites(program: &[LabelledInstruction]) -> HashMap<usize, DivinationSite> {
+        let mut sites = HashMap::new();
+        let mut label = "";
+        let mut occurrence = 0;
+        let mut instruction_pointer = 0;
+        for labelled_instruction in program.iter() {
+            match labelled_instruction {
+                LabelledInstruction::Label(label_name) => {
+                    label = label_name;
+                    occurrence = 0;
+                }
+                LabelledInstruction::Instruction(instruction) => {
+                    if matches!(instruction, Divine(None) | DivineSibling) {
+                        sites.insert(instruction_pointer, DivinationSite::new(label, occurrence));
+                        occurrence += 1;
+                    }
+                    instruction_pointer += instruction.size();
+                }
+            }
+        }
+        sites
+    }
+
+    /// The words not read yet from the given site's bucket.
+    pub fn remaining(&self, site: &DivinationSite) -> Vec<BFieldElement> {
+        self.buckets
+            .get(site)
+            .map(|words| words.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl SecretInputStream for PartitionedSecretInput {
+    /// Without an address, no bucket can be chosen.
+    fn divine(&mut self) -> Option<BFieldElement> {
+        None
+    }
+
+    fn divine_at(&mut self, instruction_pointer: usize) -> Option<BFieldElement> {
+        let site = self.sites.get(&instruction_pointer)?;
+        self.buckets.get_mut(site)?.pop_front()
+    }
+}
+
+/// A stream of words known in advance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VecStream {
+    words: VecDeque<BFieldElement>,
+}
+
+impl VecStream {
+    pub fn new(words: Vec<BFieldElement>) -> Self {
+        Self {
+            words: words.into(),
+        }
+    }
+
+    /// The words not read yet.
+    pub fn remaining(&self) -> Vec<BFieldElement> {
+        self.words.iter().copied().collect()
+    }
+}
+
+impl From<Vec<BFieldElement>> for VecStream {
+    fn from(words: Vec<BFieldElement>) -> Self {
+        Self::new(words)
+    }
+}
+
+impl InputStream for VecStream {
+    fn read(&mut self) -> Option<BFieldElement> {
+        self.words.pop_front()
+    }
+}
+
+impl SecretInputStream for VecStream {
+    fn divine(&mut self) -> Option<BFieldElement> {
+        self.words.pop_front()
+    }
+}
+
+/// Reading removes the first word. For long input, [`VecStream`] is faster.
+impl InputStream for Vec<BFieldElement> {
+    fn read(&mut self) -> Option<BFieldElement> {
+        (!self.is_empty()).then(|| self.remove(0))
+    }
+}
+
+/// Reading removes the first word. For long input, [`VecStream`] is faster.
+impl SecretInputStream for Vec<BFieldElement> {
+    fn divine(&mut self) -> Option<BFieldElement> {
+        (!self.is_empty()).then(|| self.remove(0))
+    }
+}
+
+impl<F: FnMut() -> Option<BFieldElement>> InputStream for F {
+    fn read(&mut self) -> Option<BFieldElement> {
+        self()
+    }
+}
+
+impl<F: FnMut() -> Option<BFieldElement>> SecretInputStream for F {
+    fn divine(&mut self) -> Option<BFieldElement> {
+        self()
+    }
+}
+
+impl OutputSink for Vec<BFieldElement> {
+    fn write(&mut self, stamped_word: StampedWord) {
+        self.push(stamped_word.word);
+    }
+}
+
+impl OutputSink for Vec<StampedWord> {
+    fn write(&mut self, stamped_word: StampedWord) {
+        self.push(stamped_word);
+    }
+}
+
+/// The closure receives every word together with the clock cycle in which it was written.
+impl<F: FnMut(BFieldElement, u32)> OutputSink for F {
+    fn write(&mut self, stamped_word: StampedWord) {
+        self(stamped_word.word, stamped_word.cycle)
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use triton_opcodes::instruction::parse;
+    use triton_opcodes::program::Program;
+
+    use triton_opcodes::ord_n::Ord16::*;
+
+    use crate::vm::run;
+    use crate::vm::run_with_sink;
+    use crate::vm::simulate;
+    use crate::vm::simulate_with_sink;
+
+    use super::*;
+
+    /// Answers every divination with a fixed value.
+    struct FixedDiviner;
+
+    impl Diviner for FixedDiviner {
+        fn divine_hinted(
+            &mut self,
+            _hint: DivinationHint,
+            op_stack: &OpStack,
+        ) -> Option<BFieldElement> {
+            Some(op_stack.safe_peek(ST0) + BFieldElement::new(100))
+        }
+
+        fn divine_sibling(
+            &mut self,
+            node_index: u32,
+            known_digest: [BFieldElement; DIGEST_LENGTH],
+        ) -> Option<[BFieldElement; DIGEST_LENGTH]> {
+            assert_eq!(3, node_index);
+            assert_eq!([5, 4, 3, 2, 1].map(BFieldElement::new), known_digest);
+            Some([10, 11, 12, 13, 14].map(BFieldElement::new))
+        }
+    }
+
+    #[test]
+    fn lazy_streams_are_read_on_demand_test() {
+        let program =
+            Program::from_code("read_io divine add read_io divine add add write_io halt").unwrap();
+        let mut num_reads = 0;
+        let stdin = || {
+            num_reads += 1;
+            Some(BFieldElement::new(10 * num_reads))
+        };
+        let secret_in = VecStream::new(vec![BFieldElement::new(1), BFieldElement::new(2)]);
+        let (_, stdout) = simulate(&program, stdin, secret_in).unwrap();
+        assert_eq!(vec![BFieldElement::new(33)], stdout);
+        assert_eq!(2, num_reads);
+    }
+
+    #[test]
+    fn exhausted_stream_fails_execution_test() {
+        let program = Program::from_code("divine divine halt").unwrap();
+        let mut secret_in = VecStream::from(vec![BFieldElement::new(7)]);
+        assert_eq!(Some(BFieldElement::new(7)), secret_in.divine());
+        assert!(secret_in.remaining().is_empty());
+
+        let err = simulate(&program, vec![], vec![BFieldElement::new(7)])
+            .expect_err("execution must fail");
+        assert_eq!(1, err.instruction_pointer);
+    }
+
+    #[test]
+    fn diviner_supplies_hinted_values_and_siblings_test() {
+        let code = "push 3 push 1 push 2 push 3 push 4 push 5 \
+            push 0 push 0 push 0 push 0 push 0 divine_sibling \
+            push 7 divine_quotient halt";
+        let program = Program::from_code(code).unwrap();
+        let secret_in = WithDiviner::new(vec![], FixedDiviner);
+        let (states, _) = run(&program, vec![], secret_in).unwrap();
+
+        let last_state = states.last().unwrap();
+        let stack = |position| last_state.op_stack.safe_peek(position).value();
+        assert_eq!(107, stack(ST0));
+        assert_eq!(7, stack(ST1));
+        assert_eq!([10, 11, 12, 13, 14], [ST2, ST3, ST4, ST5, ST6].map(stack));
+        assert_eq!([5, 4, 3, 2, 1], [ST7, ST8, ST9, ST10, ST11].map(stack));
+        assert_eq!(1, stack(ST12));
+    }
+
+    #[test]
+    fn partitioned_secret_input_is_read_per_site_test() {
+        let code = "divine call second call first add add write_io halt \
+            first: divine return \
+            second: push 1 divine divine add add return";
+        let labelled_instructions = parse(code).unwrap();
+        let program = Program::new(&labelled_instructions);
+
+        let words = |words: &[u64]| words.iter().copied().map(BFieldElement::new).collect();
+        let buckets = HashMap::from([
+            (DivinationSite::new("", 0), words(&[1000])),
+            (DivinationSite::new("first", 0), words(&[100, 7])),
+            (DivinationSite::new("second", 0), words(&[20])),
+            (DivinationSite::new("second", 1), words(&[3])),
+        ]);
+        let secret_in = PartitionedSecretInput::new(&labelled_instructions, buckets).unwrap();
+        let mut secret_in_copy = secret_in.clone();
+        assert_eq!(None, secret_in_copy.divine());
+
+        let (_, stdout) = simulate(&program, vec![], secret_in).unwrap();
+        assert_eq!(vec![BFieldElement::new(1124)], stdout);
+
+        let buckets = HashMap::from([(DivinationSite::new("first", 1), words(&[1]))]);
+        assert!(PartitionedSecretInput::new(&labelled_instructions, buckets).is_err());
+    }
+
+    #[test]
+    fn output_sink_observes_words_as_they_are_written_test() {
+        let program = Program::from_code("push 1 write_io push 2 write_io push 0 assert").unwrap();
+        let mut written = vec![];
+        let mut sink = |word: BFieldElement, cycle| written.push((word.value(), cycle));
+        let result = simulate_with_sink(&program, vec![], vec![], &mut sink);
+        assert!(result.is_err());
+        assert_eq!(vec![(1, 1), (2, 3)], written);
+
+        let mut sunk_words: Vec<BFieldElement> = vec![];
+        let result = run_with_sink(&program, vec![], vec![], &mut sunk_words);
+        assert!(result.is_err());
+        assert_eq!(
+            vec![BFieldElement::new(1), BFieldElement::new(2)],
+            sunk_words
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Batched arithmetic over [`XFieldElement`]s for the hot loops of prover and verifier.
+//!
+//! When summing many products in the extension field `𝔽_p[x]/(x³ - x + 1)`, the reduction modulo
+//! `x³ - x + 1` is deferred until the very end. The products themselves use Karatsuba's trick to
+//! get by with 6 instead of 9 base field multiplications. For a single product, this does not pay
+//! off – use the `*` operator. Benchmark `x_field_ops` compares the fast paths to the naïve ones.
+//!
+//! Additionally, this module provides constant-time replacements for those operations on
+//! [`XFieldElement`]s whose implementation in `twenty-first` branches on the operands' values.
+
+use std::ops::Mul;
+
+use ndarray::ArrayView1;
+use ndarray::Zip;
+use num_traits::Zero;
+use twenty_first::shared_math::b_field_element::BFieldElement;
+use twenty_first::shared_math::traits::Inverse;
+use twenty_first::shared_math::traits::ModPowU64;
+use twenty_first::shared_math::x_field_element::XFieldElement;
+
+/// The 6 base field products of Karatsuba multiplication, from which the coefficients of the
+/// (unreduced) product polynomial can be recovered. Sums of products can be accumulated in this
+/// form, requiring only one recovery and reduction in total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KaratsubaProducts([BFieldElement; 6]);
+
+impl KaratsubaProducts {
+    fn zero() -> Self {
+        Self([BFieldElement::zero(); 6])
+    }
+
+    fn of(lhs: XFieldElement, rhs: XFieldElement) -> Self {
+        let [c0, c1, c2] = lhs.coefficients;
+        let [d0, d1, d2] = rhs.coefficients;
+        Self([
+            c0 * d0,
+            c1 * d1,
+            c2 * d2,
+            (c0 + c1) * (d0 + d1),
+            (c0 + c2) * (d0 + d2),
+            (c1 + c2) * (d1 + d2),
+        ])
+    }
+
+    fn accumulate(&mut self, other: Self) {
+        for (acc, summand) in self.0.iter_mut().zip(other.0) {
+            *acc += summand;
+        }
+    }
+
+    /// Recover the product polynomial's coefficients and reduce modulo `x³ - x + 1`, using
+    /// `x³ = x - 1` and `x⁴ = x² - x`.
+    fn reduce(self) -> XFieldElement {
+        let [m0, m1, m2, m01, m02, m12] = self.0;
+        let r0 = m0;
+        let r1 = m01 - m0 - m1;
+        let r2 = m02 - m0 - m2 + m1;
+        let r3 = m12 - m1 - m2;
+        let r4 = m2;
+        XFieldElement::new([r0 - r3, r1 + r3 - r4, r2 + r4])
+    }
+}
+
+/// The inner product `Σ lhs[i]·rhs[i]`. Panics if the slices are of different lengths.
+pub fn inner_product(lhs: &[XFieldElement], rhs: &[XFieldElement]) -> XFieldElement {
+    assert_eq!(lhs.len(), rhs.len(), "Operands must be of equal length.");
+    let mut accumulator = KaratsubaProducts::zero();
+    for (&l, &r) in lhs.iter().zip(rhs) {
+        accumulator.accumulate(KaratsubaProducts::of(l, r));
+    }
+    accumulator.reduce()
+}
+
+/// Adds `(weights[0] + weights[1]·shifts[i])·codeword[i]` to `accumulator[i]` for every `i`, in
+/// parallel. This is the same as adding the weighted codeword and the weighted, shifted codeword
+/// individually, but needs only one multiplication by a codeword element instead of two.
+pub fn add_weighted_shifted_codeword<FF>(
+    accumulator: &mut [XFieldElement],
+    codeword: ArrayView1<FF>,
+    shifts: ArrayView1<BFieldElement>,
+    weights: [XFieldElement; 2],
+) where
+    FF: Copy + Send + Sync,
+    XFieldElement: Mul<FF, Output = XFieldElement>,
+{
+    Zip::from(accumulator)
+        .and(codeword)
+        .and(shifts)
+        .par_for_each(|acc, &element, &shift| *acc += (weights[0] + shift * weights[1]) * element);
+}
+
+/// The multiplicative inverse, computed in time independent of the value of the operand. Panics
+/// if the operand is zero.
+///
+/// The Frobenius automorphism `a ↦ a^p` permutes the roots of `x³ - x + 1`, so the norm
+/// `N(a) = a · a^p · a^(p²)` lies in the base field. Hence, `a⁻¹ = a^p · a^(p²) · N(a)⁻¹`. The
+/// exponentiations are by the fixed exponent `p`, and base field inversion uses a fixed addition
+/// chain. In contrast, the `Inverse` implementation of `XFieldElement` uses the extended Euclidean
+/// algorithm, the running time of which depends on the operand.
+pub fn constant_time_inverse(element: XFieldElement) -> XFieldElement {
+    let conjugate = element.mod_pow_u64(BFieldElement::QUOTIENT);
+    let other_conjugate = conjugate.mod_pow_u64(BFieldElement::QUOTIENT);
+    let conjugates_product = conjugate * other_conjugate;
+    let norm = (element * conjugates_product).coefficients[0];
+    conjugates_product * norm.inverse()
+}
+
+/// Equality, decided in time independent of the position of the first differing coefficient.
+pub fn constant_time_eq(lhs: XFieldElement, rhs: XFieldElement) -> bool {
+    let difference = lhs
+        .coefficients
+        .iter()
+        .zip(rhs.coefficients.iter())
+        .fold(0, |acc, (l, r)| acc | (l.value() ^ r.value()));
+    difference == 0
+}
+
+#[cfg(test)]
+mod x_field_ops_tests {
+    use ndarray::Array1;
+    use num_traits::One;
+    use twenty_first::shared_math::other::random_elements;
+
+    use super::*;
+
+    #[test]
+    fn karatsuba_products_agree_with_schoolbook_mul_test() {
+        let lhs: Vec<XFieldElement> = random_elements(100);
+        let rhs: Vec<XFieldElement> = random_elements(100);
+        for (&l, &r) in lhs.iter().zip(rhs.iter()) {
+            assert_eq!(l * r, KaratsubaProducts::of(l, r).reduce());
+        }
+
+        let (zero, one) = (BFieldElement::zero(), BFieldElement::one());
+        let x = XFieldElement::new([zero, one, zero]);
+        let x_squared = XFieldElement::new([zero, zero, one]);
+        let x_to_the_4 = XFieldElement::new([zero, -one, one]);
+        assert_eq!(x_to_the_4, inner_product(&[x_squared], &[x_squared]));
+        assert_eq!(x_squared, inner_product(&[x], &[x]));
+    }
+
+    #[test]
+    fn inner_product_agrees_with_naive_inner_product_test() {
+        for length in [0, 1, 2, 17, 1000] {
+            let lhs: Vec<XFieldElement> = random_elements(length);
+            let rhs: Vec<XFieldElement> = random_elements(length);
+            let naive: XFieldElement = lhs.iter().zip(rhs.iter()).map(|(&l, &r)| l * r).sum();
+            assert_eq!(naive, inner_product(&lhs, &rhs));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "equal length")]
+    fn inner_product_of_different_lengths_panics_test() {
+        inner_product(&random_elements(3), &random_elements(4));
+    }
+
+    #[test]
+    fn constant_time_inverse_agrees_with_inverse_test() {
+        let mut elements: Vec<XFieldElement> = random_elements(100);
+        elements.push(XFieldElement::one());
+        elements.push(XFieldElement::new_const(BFieldElement::new(2)));
+        for element in elements.into_iter().filter(|element| !element.is_zero()) {
+            let inverse = constant_time_inverse(element);
+            assert_eq!(element.inverse(), inverse);
+            assert!((element * inverse).is_one());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn constant_time_inverse_of_zero_panics_test() {
+        constant_time_inverse(XFieldElement::zero());
+    }
+
+    #[test]
+    fn constant_time_eq_agrees_with_eq_test() {
+        let elements: Vec<XFieldElement> = random_elements(10);
+        for (&lhs, &rhs) in elements.iter().zip(elements.iter().rev()) {
+            assert_eq!(lhs == rhs, constant_time_eq(lhs, rhs));
+            assert!(constant_time_eq(lhs, lhs));
+        }
+        let one = XFieldElement::one();
+        assert!(!constant_time_eq(
+            one,
+            one + XFieldElement::new([0, 0, 1].map(BFieldElement::new))
+        ));
+    }
+
+    #[test]
+    fn add_weighted_shifted_codeword_agrees_with_naive_combination_test() {
+        let length = 64;
+        let weights = [random_elements(1)[0], random_elements(1)[0]];
+        let shifts = Array1::from(random_elements::<BFieldElement>(length));
+        let base_codeword = Array1::from(random_elements::<BFieldElement>(length));
+        let ext_codeword = Array1::from(random_elements::<XFieldElement>(length));
+        let initial = Array1::from(random_elements::<XFieldElement>(length));
+
+        let mut accumulator = initial.clone();
+        add_weighted_shifted_codeword(
+            accumulator.as_slice_mut().unwrap(),
+            base_codeword.view(),
+            shifts.view(),
+            weights,
+        );
+        add_weighted_shifted_codeword(
+            accumulator.as_slice_mut().unwrap(),
+            ext_codeword.view(),
+            shifts.view(),
+            weights,
+        );
+
+        for i in 0..length {
+            let (bfe, xfe, shift) = (base_codeword[i], ext_codeword[i], shifts[i]);
+            let expected = initial[i]
+                + weights[0] * bfe
+                + weights[1] * bfe * shift
+                + weights[0] * xfe
+                + weights[1] * xfe * shift;
+            assert_eq!(expected, accumulator[i]);
+        }
+    }
+}